@@ -0,0 +1,41 @@
+-- États successifs d'un déploiement mis en file d'attente.
+-- 'queued' : en attente d'être pris en charge par un worker.
+-- 'building', 'creating_container', 'persisting' : étapes en cours d'exécution.
+-- 'succeeded', 'failed' : états terminaux.
+CREATE TYPE deployment_status AS ENUM ('queued', 'building', 'creating_container', 'persisting', 'succeeded', 'failed');
+
+-- File d'attente persistante des déploiements. Chaque ligne conserve sa position dans la machine à états
+-- ainsi que les artefacts Docker déjà créés, afin de pouvoir reprendre ou annuler après un redémarrage.
+CREATE TABLE deployments
+(
+    id SERIAL PRIMARY KEY,
+
+    -- Le login de l'utilisateur qui a demandé le déploiement.
+    owner VARCHAR(255) NOT NULL,
+
+    -- Nom du projet demandé.
+    project_name VARCHAR(63) NOT NULL,
+
+    status deployment_status NOT NULL DEFAULT 'queued',
+
+    -- Requête de déploiement complète (JSON), chiffrée car elle contient les variables d'environnement.
+    encrypted_payload TEXT NOT NULL,
+
+    -- Artefacts Docker créés au fil des étapes. Utilisés pour le nettoyage si le déploiement est interrompu.
+    image_tag VARCHAR(2048) NULL,
+    container_name VARCHAR(255) NULL,
+    volume_name VARCHAR(255) NULL,
+
+    -- Projet créé une fois le déploiement terminé avec succès.
+    project_id INTEGER NULL REFERENCES projects(id) ON DELETE SET NULL,
+
+    -- Code et message d'erreur lorsque le déploiement échoue.
+    error_code VARCHAR(64) NULL,
+    error_message TEXT NULL,
+
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_deployments_status ON deployments(status);
+CREATE INDEX idx_deployments_owner ON deployments(owner);
@@ -0,0 +1,8 @@
+-- Nom de l'hôte Docker (tel que déclaré dans DOCKER_HOSTS) sur lequel tourne le conteneur du projet.
+-- Les projets existants ont été déployés sur l'hôte unique historique, nommé 'default'.
+ALTER TABLE projects ADD COLUMN docker_host VARCHAR(63) NOT NULL DEFAULT 'default';
+
+CREATE INDEX idx_projects_docker_host ON projects(docker_host);
+
+-- Hôte Docker choisi pour un déploiement en file d'attente, renseigné dès que le worker le prend en charge.
+ALTER TABLE deployments ADD COLUMN docker_host VARCHAR(63) NULL;
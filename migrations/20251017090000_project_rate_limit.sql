@@ -0,0 +1,3 @@
+-- Limite de requêtes par projet, appliquée par un middleware Traefik (NULL = aucune limite)
+ALTER TABLE projects ADD COLUMN rate_limit_average INTEGER NULL;
+ALTER TABLE projects ADD COLUMN rate_limit_burst INTEGER NULL;
@@ -0,0 +1,2 @@
+-- Plages CIDR autorisées à joindre la route publique du projet (NULL = accès libre)
+ALTER TABLE projects ADD COLUMN ip_allowlist TEXT[] NULL;
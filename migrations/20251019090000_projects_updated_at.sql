@@ -0,0 +1,2 @@
+-- Date de dernière modification, mise à jour par le code à chaque changement du projet
+ALTER TABLE projects ADD COLUMN updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
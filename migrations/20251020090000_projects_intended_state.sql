@@ -0,0 +1,3 @@
+-- État voulu par l'utilisateur (démarré / arrêté) et conteneur introuvable détecté au démarrage
+ALTER TABLE projects ADD COLUMN intended_running BOOLEAN NOT NULL DEFAULT TRUE;
+ALTER TABLE projects ADD COLUMN lost_container BOOLEAN NOT NULL DEFAULT FALSE;
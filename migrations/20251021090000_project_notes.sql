@@ -0,0 +1,19 @@
+-- Notes opérationnelles partagées entre le propriétaire et les participants (texte brut)
+ALTER TABLE projects ADD COLUMN notes TEXT NULL;
+
+-- Historique minimal : la valeur remplacée et son auteur, pour récupérer un écrasement accidentel.
+CREATE TABLE project_note_revisions
+(
+    id SERIAL PRIMARY KEY,
+    project_id INTEGER NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+
+    -- Contenu des notes avant la modification (NULL si elles étaient vides).
+    previous_notes TEXT NULL,
+
+    -- Login de l'utilisateur ayant effectué la modification.
+    edited_by VARCHAR(255) NOT NULL,
+
+    edited_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_project_note_revisions_project ON project_note_revisions(project_id, edited_at DESC);
@@ -0,0 +1,12 @@
+-- Surcharges de quotas par utilisateur, gérées par les administrateurs.
+-- Une colonne NULL signifie que la valeur par défaut de la configuration s'applique.
+CREATE TABLE user_quotas
+(
+    login VARCHAR(255) PRIMARY KEY,
+    max_projects INTEGER NULL,
+    max_databases INTEGER NULL,
+
+    -- Login de l'administrateur ayant effectué la dernière modification.
+    updated_by VARCHAR(255) NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,16 @@
+-- États d'une participation à un projet.
+-- 'pending' : invitation envoyée par le propriétaire, en attente de réponse de l'invité.
+-- 'accepted' : l'invité a accepté, la participation donne accès au projet.
+CREATE TYPE participant_status AS ENUM ('pending', 'accepted');
+
+-- Les participations existantes ont été ajoutées directement et sont donc considérées comme acceptées.
+ALTER TABLE project_participants
+    ADD COLUMN id SERIAL UNIQUE,
+    ADD COLUMN status participant_status NOT NULL DEFAULT 'accepted',
+
+    -- Login de l'utilisateur ayant envoyé l'invitation. NULL pour les participations ajoutées directement.
+    ADD COLUMN invited_by VARCHAR(255) NULL,
+    ADD COLUMN invited_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
+
+-- Accélère la liste des invitations d'un utilisateur et le nettoyage des invitations expirées.
+CREATE INDEX idx_project_participants_pending ON project_participants(invited_at) WHERE status = 'pending';
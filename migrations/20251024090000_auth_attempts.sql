@@ -0,0 +1,37 @@
+-- Raisons d'échec d'une validation de ticket CAS.
+-- 'invalid_ticket' : le CAS a répondu mais a refusé le ticket (expiré, déjà utilisé ou forgé).
+-- 'cas_unreachable' : le CAS n'a pas pu être contacté ou n'a pas répondu à temps.
+-- 'cas_error' : le CAS a répondu avec un statut HTTP d'erreur.
+-- 'malformed_response' : la réponse du CAS n'a pas pu être interprétée.
+-- 'missing_attributes' : le ticket est valide mais des attributs utilisateur sont absents.
+CREATE TYPE auth_failure_reason AS ENUM ('invalid_ticket', 'cas_unreachable', 'cas_error', 'malformed_response', 'missing_attributes');
+
+-- Historique de toutes les tentatives de validation sur le callback d'authentification.
+-- Sert à limiter les tentatives par IP et aux investigations des administrateurs.
+CREATE TABLE auth_attempts
+(
+    id BIGSERIAL PRIMARY KEY,
+
+    -- Login renvoyé par le CAS. NULL lorsque la validation a échoué avant de l'obtenir.
+    login VARCHAR(255) NULL,
+
+    success BOOLEAN NOT NULL,
+    failure_reason auth_failure_reason NULL,
+
+    -- Adresse IP distante et User-Agent du client.
+    ip_address VARCHAR(45) NOT NULL,
+    user_agent TEXT NULL,
+
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_auth_attempts_ip_created_at ON auth_attempts(ip_address, created_at) WHERE NOT success;
+CREATE INDEX idx_auth_attempts_login_created_at ON auth_attempts(login, created_at);
+CREATE INDEX idx_auth_attempts_created_at ON auth_attempts(created_at);
+
+-- Date de dernière connexion réussie de chaque utilisateur.
+CREATE TABLE user_logins
+(
+    login VARCHAR(255) PRIMARY KEY,
+    last_login_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
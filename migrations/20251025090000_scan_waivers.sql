@@ -0,0 +1,28 @@
+-- Dérogations aux vulnérabilités remontées par Grype, créées par les administrateurs.
+-- Une dérogation active retire les vulnérabilités correspondantes avant l'application du seuil de sévérité.
+CREATE TABLE scan_waivers
+(
+    id SERIAL PRIMARY KEY,
+
+    -- Identifiant de la vulnérabilité (CVE-... ou GHSA-...).
+    cve_id VARCHAR(64) NOT NULL,
+
+    -- Paquet concerné. NULL si la dérogation s'applique quel que soit le paquet.
+    package VARCHAR(255) NULL,
+
+    -- Projet concerné. NULL si la dérogation s'applique à tous les projets.
+    project_name VARCHAR(63) NULL,
+
+    justification TEXT NOT NULL,
+
+    -- Au-delà de cette date, la dérogation cesse automatiquement de s'appliquer.
+    expires_at TIMESTAMPTZ NOT NULL,
+
+    -- Login de l'administrateur ayant créé ou modifié la dérogation en dernier.
+    created_by VARCHAR(255) NOT NULL,
+
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_scan_waivers_expires_at ON scan_waivers(expires_at);
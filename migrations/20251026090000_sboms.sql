@@ -0,0 +1,20 @@
+-- Nomenclatures logicielles (SBOM CycloneDX) générées par Syft pour chaque image déployée.
+-- Une même image partagée par plusieurs projets n'est stockée qu'une fois, indexée par son digest.
+CREATE TABLE sboms
+(
+    image_digest VARCHAR(255) PRIMARY KEY,
+
+    -- Référence de l'image analysée (tag ou URL).
+    image_ref VARCHAR(2048) NOT NULL,
+
+    -- Document CycloneDX JSON compressé en gzip. NULL si la génération a échoué.
+    document BYTEA NULL,
+
+    -- Paquets recensés sous la forme 'nom@version', utilisés pour la recherche.
+    packages TEXT[] NOT NULL DEFAULT '{}',
+
+    -- Message d'erreur lorsque la génération a échoué.
+    error_message TEXT NULL,
+
+    generated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,8 @@
+-- Cache des vérifications de signature cosign réussies, indexé par digest d'image (dépôt@sha256:...).
+-- Un digest étant immuable, une image déjà vérifiée n'a pas besoin de l'être à nouveau.
+-- Les échecs ne sont pas mis en cache afin qu'une image signée après coup puisse être déployée.
+CREATE TABLE image_signature_verifications
+(
+    image_digest VARCHAR(2048) PRIMARY KEY,
+    verified_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
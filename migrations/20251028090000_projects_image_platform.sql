@@ -0,0 +1,3 @@
+-- Plateforme (os/architecture) de l'image déployée, conservée pour faciliter le diagnostic.
+-- NULL pour les projets déployés avant l'ajout de cette colonne.
+ALTER TABLE projects ADD COLUMN image_platform VARCHAR(64) NULL;
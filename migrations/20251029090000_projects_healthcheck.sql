@@ -0,0 +1,5 @@
+-- Healthcheck actif du load balancer Traefik : chemin interrogé, intervalle en secondes et port optionnel.
+-- NULL si aucun healthcheck n'est configuré.
+ALTER TABLE projects ADD COLUMN healthcheck_path VARCHAR(255) NULL;
+ALTER TABLE projects ADD COLUMN healthcheck_interval INTEGER NULL;
+ALTER TABLE projects ADD COLUMN healthcheck_port INTEGER NULL;
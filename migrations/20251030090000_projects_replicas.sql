@@ -0,0 +1,6 @@
+-- Nombre de conteneurs identiques derrière le service Traefik du projet.
+-- container_name devient le nom de base : la réplique i (i >= 1) s'appelle container_name-i.
+ALTER TABLE projects ADD COLUMN replicas INTEGER NOT NULL DEFAULT 1 CHECK (replicas >= 1);
+
+-- Indique que le volume persistant peut être monté par plusieurs répliques en même temps.
+ALTER TABLE projects ADD COLUMN volume_share_safe BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,2 @@
+-- Projet arrêté volontairement avec la page de maintenance affichée à la place du conteneur.
+ALTER TABLE projects ADD COLUMN maintenance_page BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,4 @@
+-- Date de création de l'image déployée (d'après docker inspect) et date du dernier scan Grype.
+-- NULL si inconnues, par exemple pour les projets déployés avant l'ajout de ces colonnes.
+ALTER TABLE projects ADD COLUMN image_created_at TIMESTAMPTZ NULL;
+ALTER TABLE projects ADD COLUMN last_scanned_at TIMESTAMPTZ NULL;
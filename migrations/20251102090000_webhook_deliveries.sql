@@ -0,0 +1,31 @@
+-- Issue de la réception d'une livraison de webhook GitHub.
+-- 'accepted' : signature valide, livraison vue pour la première fois.
+-- 'duplicate' : signature valide mais GUID déjà accepté dans la fenêtre glissante (rejeu).
+-- 'invalid_signature' : aucune des clés configurées ne valide X-Hub-Signature-256.
+-- 'payload_too_large' : corps plus gros que WEBHOOK_MAX_PAYLOAD_BYTES.
+-- 'stale' : l'horodatage du contenu est plus ancien que la fenêtre glissante.
+-- 'malformed' : en-têtes GitHub manquants ou contenu illisible.
+CREATE TYPE webhook_delivery_outcome AS ENUM ('accepted', 'duplicate', 'invalid_signature', 'payload_too_large', 'stale', 'malformed');
+
+-- Historique des livraisons reçues sur la fenêtre de rejeu. Sert à rejeter les GUID
+-- déjà traités et à diagnostiquer les secrets mal configurés.
+CREATE TABLE webhook_deliveries
+(
+    id BIGSERIAL PRIMARY KEY,
+
+    -- En-têtes X-GitHub-Delivery et X-GitHub-Event. NULL lorsqu'ils sont absents.
+    delivery_id VARCHAR(64) NULL,
+    event VARCHAR(64) NULL,
+
+    outcome webhook_delivery_outcome NOT NULL,
+    payload_size BIGINT NOT NULL,
+
+    -- Adresse IP distante de l'émetteur.
+    ip_address VARCHAR(45) NOT NULL,
+
+    received_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Un GUID ne peut être accepté qu'une fois : l'insertion concurrente d'un rejeu échoue sur cet index.
+CREATE UNIQUE INDEX idx_webhook_deliveries_accepted_delivery_id ON webhook_deliveries(delivery_id) WHERE outcome = 'accepted';
+CREATE INDEX idx_webhook_deliveries_received_at ON webhook_deliveries(received_at);
@@ -0,0 +1,25 @@
+-- Projets arrêtés par un arrêt global (stop-all) : start-all ne redémarre que ceux-ci.
+-- Tant qu'au moins un projet porte ce marqueur, la plateforme est en fenêtre de maintenance
+-- et les projets concernés ne sont pas signalés comme tombés.
+ALTER TABLE projects ADD COLUMN stopped_by_broadcast BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE INDEX idx_projects_stopped_by_broadcast ON projects(id) WHERE stopped_by_broadcast;
+
+-- Journal des arrêts et redémarrages globaux lancés par les administrateurs, simulations comprises.
+CREATE TABLE container_broadcasts
+(
+    id BIGSERIAL PRIMARY KEY,
+    action VARCHAR(8) NOT NULL CHECK (action IN ('stop', 'start')),
+    admin_login VARCHAR(255) NOT NULL,
+
+    -- Filtre propriétaire de la requête. NULL lorsque tous les projets sont visés.
+    owner_filter VARCHAR(255) NULL,
+    dry_run BOOLEAN NOT NULL,
+
+    -- Résultat par projet : [{ "project_id", "project", "outcome": "ok" | "failed" | "skipped", "detail" }].
+    results JSONB NOT NULL,
+
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_container_broadcasts_created_at ON container_broadcasts(created_at);
@@ -0,0 +1,23 @@
+-- Comment l'utilisateur a prouvé une authentification récente avant de révéler un mot de passe.
+-- 'fresh_session' : le jeton de session a été émis il y a moins de DB_REVEAL_MAX_AUTH_AGE_SECONDS.
+-- 'cas_ticket' : un nouveau ticket CAS a été présenté avec la demande.
+CREATE TYPE reveal_method AS ENUM ('fresh_session', 'cas_ticket');
+
+-- Journal des révélations de mots de passe de bases de données.
+CREATE TABLE database_password_reveals
+(
+    id BIGSERIAL PRIMARY KEY,
+
+    -- Pas de clé étrangère : l'historique survit à la suppression de la base.
+    database_id INTEGER NOT NULL,
+    login VARCHAR(255) NOT NULL,
+    method reveal_method NOT NULL,
+
+    -- Adresse IP distante et User-Agent du client.
+    ip_address VARCHAR(45) NOT NULL,
+    user_agent TEXT NULL,
+
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_database_password_reveals_database_id ON database_password_reveals(database_id, created_at);
@@ -0,0 +1,4 @@
+-- Recherche de projets par préfixe du nom ou du login du propriétaire.
+-- text_pattern_ops permet d'utiliser l'index pour LIKE 'prefixe%' quelle que soit la collation.
+CREATE INDEX idx_projects_lower_name_pattern ON projects (LOWER(name) text_pattern_ops);
+CREATE INDEX idx_projects_lower_owner_pattern ON projects (LOWER(owner) text_pattern_ops);
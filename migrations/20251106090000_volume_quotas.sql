@@ -0,0 +1,47 @@
+-- Dernière mesure de l'espace occupé par le volume persistant de chaque projet.
+-- over_quota_since est renseigné au premier dépassement constaté et remis à NULL dès que l'usage repasse sous le quota.
+-- stopped_by_quota indique que le conteneur a été arrêté par l'application du quota.
+CREATE TABLE project_volume_usage
+(
+    project_id INTEGER PRIMARY KEY REFERENCES projects(id) ON DELETE CASCADE,
+    used_bytes BIGINT NOT NULL,
+    measured_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    over_quota_since TIMESTAMPTZ NULL,
+    stopped_by_quota BOOLEAN NOT NULL DEFAULT FALSE
+);
+
+-- Quotas de volume accordés projet par projet par les administrateurs, à la place de VOLUME_QUOTA_DEFAULT_MB.
+CREATE TABLE project_volume_quotas
+(
+    project_id INTEGER PRIMARY KEY REFERENCES projects(id) ON DELETE CASCADE,
+    max_bytes BIGINT NOT NULL CHECK (max_bytes > 0),
+
+    -- Login de l'administrateur ayant effectué la dernière modification.
+    updated_by VARCHAR(255) NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- 'exceeded' : premier dépassement constaté, le délai de grâce commence.
+-- 'stopped' : le délai de grâce est écoulé et la politique 'enforce' a arrêté le conteneur.
+-- 'cleared' : l'usage est repassé sous le quota.
+-- 'override_set' / 'override_removed' : un administrateur a modifié le quota du projet.
+CREATE TYPE volume_quota_action AS ENUM ('exceeded', 'stopped', 'cleared', 'override_set', 'override_removed');
+
+-- Journal des décisions liées aux quotas de volume.
+CREATE TABLE volume_quota_events
+(
+    id BIGSERIAL PRIMARY KEY,
+
+    -- Pas de clé étrangère : l'historique survit à la suppression du projet.
+    project_id INTEGER NOT NULL,
+    action volume_quota_action NOT NULL,
+    used_bytes BIGINT NULL,
+    quota_bytes BIGINT NOT NULL,
+
+    -- Administrateur à l'origine de l'événement. NULL pour les décisions prises par la plateforme.
+    actor VARCHAR(255) NULL,
+
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_volume_quota_events_project_id ON volume_quota_events(project_id, created_at);
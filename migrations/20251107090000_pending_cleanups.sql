@@ -0,0 +1,20 @@
+-- Actions de nettoyage d'opérations échouées (déploiement, mise à jour, provisionnement) qui ont encore échoué
+-- après les nouvelles tentatives immédiates. Une tâche de fond les rejoue et supprime chaque ligne dès que l'action réussit.
+CREATE TABLE pending_cleanups
+(
+    id BIGSERIAL PRIMARY KEY,
+
+    -- Opération d'origine, par exemple « deployment 42 of 'blog' ».
+    operation TEXT NOT NULL,
+
+    -- Action typée : { "kind": "remove_container" | "remove_image" | "remove_volume" | "deprovision_database", ... }.
+    action JSONB NOT NULL,
+
+    attempts INTEGER NOT NULL,
+    last_error TEXT NOT NULL,
+
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_attempt_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_pending_cleanups_created_at ON pending_cleanups(created_at);
@@ -0,0 +1,5 @@
+-- Identifiant Docker du premier réplica, remplacé à chaque (re)création du conteneur.
+-- Le nom reste la référence stable ; l'identifiant permet de ne pas confondre le conteneur du projet
+-- avec un autre conteneur créé entre-temps sous le même nom. NULL pour les projets antérieurs,
+-- jusqu'à ce que la réconciliation le renseigne.
+ALTER TABLE projects ADD COLUMN container_id VARCHAR(64);
@@ -0,0 +1,15 @@
+-- Journal des actions des utilisateurs sur l'ensemble de leur compte : consultation de
+-- l'inventaire de leurs ressources et nettoyage complet avant départ.
+CREATE TABLE account_actions
+(
+    id BIGSERIAL PRIMARY KEY,
+    login VARCHAR(255) NOT NULL,
+    action VARCHAR(16) NOT NULL CHECK (action IN ('inventory', 'cleanup')),
+
+    -- Résultat par ressource d'un nettoyage : [{ "kind", "name", "ok", "error" }]. NULL pour une consultation.
+    results JSONB NULL,
+
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_account_actions_login ON account_actions(login, created_at);
@@ -0,0 +1,3 @@
+-- Variables d'environnement dont la valeur est visible des participants du projet.
+-- Toute clé absente de cette liste reste secrète : seuls le propriétaire et les administrateurs en voient la valeur.
+ALTER TABLE projects ADD COLUMN shared_env_keys TEXT[] NOT NULL DEFAULT '{}';
@@ -0,0 +1,5 @@
+-- Politique de sortie réseau des conteneurs d'un projet.
+-- 'internal_only' place les conteneurs sur un réseau Docker interne, sans route vers Internet.
+CREATE TYPE egress_policy AS ENUM ('open', 'internal_only');
+
+ALTER TABLE projects ADD COLUMN egress_policy egress_policy NOT NULL DEFAULT 'open';
@@ -0,0 +1,5 @@
+-- Avertissements non bloquants relevés au déploiement : configuration de l'image, avertissements de Docker.
+ALTER TABLE deployments ADD COLUMN warnings TEXT[] NOT NULL DEFAULT '{}';
+
+-- Avertissements du dernier déploiement ou de la dernière mise à jour d'image du projet.
+ALTER TABLE projects ADD COLUMN deploy_warnings TEXT[] NOT NULL DEFAULT '{}';
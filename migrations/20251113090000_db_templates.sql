@@ -0,0 +1,12 @@
+-- Scripts SQL de schéma fournis par les enseignants, appliqués à la création d'une base.
+CREATE TABLE db_templates (
+    id SERIAL PRIMARY KEY,
+    name VARCHAR(64) NOT NULL UNIQUE,
+    description TEXT,
+    script TEXT NOT NULL,
+    created_by VARCHAR(255) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Modèle appliqué à la création ; le nom est conservé même si le modèle est supprimé ensuite.
+ALTER TABLE databases ADD COLUMN template_name VARCHAR(64);
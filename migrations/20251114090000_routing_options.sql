@@ -0,0 +1,2 @@
+-- Options de routage Traefik propres au projet : sessions persistantes et en-têtes de réponse.
+ALTER TABLE projects ADD COLUMN routing_options JSONB NOT NULL DEFAULT '{}';
@@ -0,0 +1,23 @@
+-- Historique des modifications d'un projet, visible de son propriétaire et de ses participants.
+-- Pas de clé étrangère : les événements d'un projet supprimé restent consultables pour l'audit.
+CREATE TYPE project_event_action AS ENUM (
+    'start', 'stop', 'restart', 'purge',
+    'image_update', 'rebuild', 'env_update', 'notes_update',
+    'participant_invite', 'participant_remove',
+    'replicas_update', 'rate_limit_update', 'ip_allowlist_update', 'healthcheck_update', 'routing_update',
+    'egress_policy_update'
+);
+
+CREATE TABLE project_events (
+    id BIGSERIAL PRIMARY KEY,
+    project_id INTEGER NOT NULL,
+    project_name VARCHAR(255) NOT NULL,
+    action project_event_action NOT NULL,
+    actor VARCHAR(255) NOT NULL,
+    -- Vrai quand un administrateur agit sur le projet d'un autre utilisateur.
+    performed_by_admin BOOLEAN NOT NULL DEFAULT FALSE,
+    details JSONB,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_project_events_project ON project_events (project_id, created_at DESC);
@@ -0,0 +1,5 @@
+-- Seuil de blocage Grype propre à un propriétaire, prioritaire sur GRYPE_FAIL_ON_SEVERITY.
+-- NULL : le seuil global de la configuration s'applique.
+CREATE TYPE scan_severity AS ENUM ('unknown', 'negligible', 'low', 'medium', 'high', 'critical');
+
+ALTER TABLE user_quotas ADD COLUMN scan_fail_on_severity scan_severity NULL;
@@ -0,0 +1,3 @@
+-- Une action qui échoue encore après CLEANUP_GIVE_UP_DAYS jours n'est plus rejouée.
+-- La ligne reste visible des administrateurs, qui doivent alors intervenir à la main.
+ALTER TABLE pending_cleanups ADD COLUMN abandoned_at TIMESTAMPTZ NULL;
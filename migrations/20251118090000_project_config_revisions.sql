@@ -0,0 +1,16 @@
+-- Instantanés de la configuration non secrète d'un projet, un par modification effective.
+-- Pas de clé étrangère, comme pour project_events : l'historique survit à la purge du projet.
+CREATE TABLE project_config_revisions (
+    id BIGSERIAL PRIMARY KEY,
+    project_id INTEGER NOT NULL,
+    revision INTEGER NOT NULL,
+    -- Jamais de valeur de variable d'environnement : seulement leurs clés.
+    snapshot JSONB NOT NULL,
+    -- Champs modifiés depuis la révision précédente ; NULL pour la première.
+    diff JSONB,
+    actor VARCHAR(255) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (project_id, revision)
+);
+
+ALTER TYPE project_event_action ADD VALUE 'config_restore';
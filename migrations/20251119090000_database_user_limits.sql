@@ -0,0 +1,10 @@
+-- Limites de ressources appliquées au compte MariaDB de chaque base. 0 signifie sans limite, comme dans MariaDB.
+-- Les bases existantes ont été créées sans limite.
+ALTER TABLE databases
+    ADD COLUMN max_user_connections INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN max_queries_per_hour INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN max_updates_per_hour INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN max_connections_per_hour INTEGER NOT NULL DEFAULT 0;
+
+-- Connexions simultanées propres à un propriétaire, prioritaires sur MARIADB_MAX_USER_CONNECTIONS.
+ALTER TABLE user_quotas ADD COLUMN db_max_user_connections INTEGER NULL;
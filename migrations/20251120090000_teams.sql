@@ -0,0 +1,28 @@
+-- Équipes (clubs, groupes de projet) possédant des projets en commun.
+CREATE TYPE team_role AS ENUM ('owner', 'member');
+
+CREATE TABLE teams (
+    id SERIAL PRIMARY KEY,
+    name VARCHAR(63) NOT NULL UNIQUE,
+    -- Surcharge du quota de projets de l'équipe, gérée par les administrateurs.
+    -- NULL : QUOTA_DEFAULT_TEAM_PROJECTS s'applique.
+    max_projects INTEGER NULL,
+    created_by VARCHAR(255) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE team_members (
+    team_id INTEGER NOT NULL REFERENCES teams(id) ON DELETE CASCADE,
+    login VARCHAR(255) NOT NULL,
+    role team_role NOT NULL,
+    added_by VARCHAR(255) NOT NULL,
+    added_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (team_id, login)
+);
+
+CREATE INDEX idx_team_members_login ON team_members (login);
+
+-- Un projet d'équipe garde dans `owner` le login de celui qui l'a déployé.
+-- Une équipe ne peut pas être supprimée tant qu'elle possède des projets.
+ALTER TABLE projects ADD COLUMN team_id INTEGER NULL REFERENCES teams(id) ON DELETE RESTRICT;
+CREATE INDEX idx_projects_team_id ON projects (team_id);
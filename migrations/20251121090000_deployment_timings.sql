@@ -0,0 +1,14 @@
+-- Phases du pipeline de déploiement dont la durée est mesurée.
+CREATE TYPE deploy_phase AS ENUM ('build_slot_wait', 'clone', 'build', 'pull', 'verify', 'scan', 'create_container', 'persist');
+
+-- Durée de chaque phase d'un déploiement, pour savoir où passe le temps avant de dimensionner les hôtes.
+CREATE TABLE deployment_timings (
+    deployment_id INTEGER NOT NULL REFERENCES deployments(id) ON DELETE CASCADE,
+    phase deploy_phase NOT NULL,
+    source_type project_source_type NOT NULL,
+    duration_ms BIGINT NOT NULL,
+    recorded_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (deployment_id, phase)
+);
+
+CREATE INDEX idx_deployment_timings_recorded_at ON deployment_timings (recorded_at);
@@ -0,0 +1,8 @@
+-- Qui peut lire les logs d'un projet : tous les participants, ou seulement ses propriétaires.
+CREATE TYPE logs_visibility AS ENUM ('all', 'owner_only');
+
+ALTER TABLE projects ADD COLUMN logs_visibility logs_visibility NOT NULL DEFAULT 'all';
+-- Masque les valeurs des variables d'environnement du projet dans les logs servis aux participants.
+ALTER TABLE projects ADD COLUMN logs_redaction BOOLEAN NOT NULL DEFAULT FALSE;
+
+ALTER TYPE project_event_action ADD VALUE 'logs_settings_update';
@@ -0,0 +1 @@
+ALTER TYPE project_event_action ADD VALUE 'rename';
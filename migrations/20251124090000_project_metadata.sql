@@ -0,0 +1,5 @@
+-- Description et étiquettes affichées par le portail ; sans effet sur les conteneurs.
+ALTER TABLE projects ADD COLUMN description VARCHAR(500) NULL;
+ALTER TABLE projects ADD COLUMN tags TEXT[] NOT NULL DEFAULT '{}';
+
+ALTER TYPE project_event_action ADD VALUE 'metadata_update';
@@ -0,0 +1,12 @@
+-- Domaines supplémentaires routés vers un projet, en plus de son sous-domaine.
+ALTER TABLE projects ADD COLUMN custom_domains TEXT[] NOT NULL DEFAULT '{}';
+
+-- Un domaine ne peut appartenir qu'à un seul projet : la clé primaire tranche entre deux requêtes concurrentes.
+CREATE TABLE project_domains (
+    domain VARCHAR(253) PRIMARY KEY,
+    project_id INTEGER NOT NULL REFERENCES projects(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_project_domains_project_id ON project_domains(project_id);
+
+ALTER TYPE project_event_action ADD VALUE 'domains_update';
@@ -0,0 +1,5 @@
+-- Projets archivés en fin de semestre : arrêtés et masqués des listes, mais conservés.
+ALTER TABLE projects ADD COLUMN archived_at TIMESTAMPTZ NULL;
+
+ALTER TYPE project_event_action ADD VALUE 'archive';
+ALTER TYPE project_event_action ADD VALUE 'unarchive';
@@ -0,0 +1,8 @@
+-- Suppression différée : le projet reste restaurable pendant le délai de grâce, puis le
+-- nettoyeur le purge. Son nom reste réservé jusqu'à la purge.
+ALTER TABLE projects ADD COLUMN deleted_at TIMESTAMPTZ NULL;
+
+CREATE INDEX idx_projects_deleted_at ON projects(deleted_at) WHERE deleted_at IS NOT NULL;
+
+ALTER TYPE project_event_action ADD VALUE 'delete';
+ALTER TYPE project_event_action ADD VALUE 'restore';
@@ -0,0 +1,19 @@
+-- Journal d'audit commun aux projets et aux bases : qui a fait quoi, sur quelle cible.
+-- Sans clé étrangère, les entrées survivent à la purge de leur cible.
+CREATE TYPE audit_target_type AS ENUM ('project', 'database');
+
+CREATE TABLE audit_log (
+    id BIGSERIAL PRIMARY KEY,
+    actor VARCHAR(255) NOT NULL,
+    action VARCHAR(64) NOT NULL,
+    target_type audit_target_type NOT NULL,
+    target_id INTEGER NOT NULL,
+    target_name VARCHAR(255) NULL,
+    performed_by_admin BOOLEAN NOT NULL DEFAULT FALSE,
+    details JSONB NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_audit_log_target ON audit_log(target_type, target_id, created_at DESC);
+CREATE INDEX idx_audit_log_actor ON audit_log(actor, created_at DESC);
+CREATE INDEX idx_audit_log_created_at ON audit_log(created_at DESC);
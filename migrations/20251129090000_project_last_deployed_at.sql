@@ -0,0 +1,8 @@
+-- Date du dernier déploiement d'une image : création, mise à jour d'image ou reconstruction.
+-- Les projets existants reprennent leur date de création.
+ALTER TABLE projects ADD COLUMN last_deployed_at TIMESTAMPTZ NULL;
+UPDATE projects SET last_deployed_at = created_at;
+ALTER TABLE projects ALTER COLUMN last_deployed_at SET NOT NULL;
+ALTER TABLE projects ALTER COLUMN last_deployed_at SET DEFAULT NOW();
+
+CREATE INDEX idx_projects_last_deployed_at ON projects(last_deployed_at);
@@ -0,0 +1,6 @@
+-- Dernier état observé des conteneurs, tenu à jour par la synchronisation périodique,
+-- pour que les listes et les requêtes SQL n'aient pas à interroger Docker.
+CREATE TYPE project_status AS ENUM ('running', 'stopped', 'crashed', 'missing');
+
+ALTER TABLE projects ADD COLUMN status project_status NULL;
+ALTER TABLE projects ADD COLUMN status_changed_at TIMESTAMPTZ NULL;
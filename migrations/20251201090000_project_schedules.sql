@@ -0,0 +1,8 @@
+-- Démarrages et arrêts planifiés : expressions cron à cinq champs, évaluées dans le fuseau
+-- SCHEDULE_UTC_OFFSET_HOURS. NULL désactive l'action correspondante.
+ALTER TABLE projects ADD COLUMN auto_start_cron TEXT NULL;
+ALTER TABLE projects ADD COLUMN auto_stop_cron TEXT NULL;
+
+ALTER TYPE project_event_action ADD VALUE 'schedule_update';
+ALTER TYPE project_event_action ADD VALUE 'scheduled_start';
+ALTER TYPE project_event_action ADD VALUE 'scheduled_stop';
@@ -0,0 +1,20 @@
+-- Changements d'état observés par la synchronisation des statuts. Au-delà de
+-- AVAILABILITY_RETENTION_DAYS, ils sont résumés par jour dans availability_daily.
+CREATE TABLE availability_events (
+    id BIGSERIAL PRIMARY KEY,
+    project_id INTEGER NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    status project_status NOT NULL,
+    observed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_availability_events_project_observed ON availability_events (project_id, observed_at);
+
+CREATE TABLE availability_daily (
+    project_id INTEGER NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+    day DATE NOT NULL,
+    up_seconds BIGINT NOT NULL DEFAULT 0,
+    down_seconds BIGINT NOT NULL DEFAULT 0,
+    incidents INTEGER NOT NULL DEFAULT 0,
+    longest_outage_seconds BIGINT NOT NULL DEFAULT 0,
+    PRIMARY KEY (project_id, day)
+);
@@ -0,0 +1,16 @@
+-- Les déploiements interrompus sont signalés à leur propriétaire dans le journal d'audit.
+ALTER TYPE audit_target_type ADD VALUE 'deployment';
+
+-- Un seul déploiement en cours par utilisateur, garanti par la base plutôt que par une vérification préalable.
+-- Les doublons hérités d'anciennes courses sont d'abord clos, en gardant le plus ancien.
+UPDATE deployments d
+SET status = 'failed', error_code = 'DEPLOYMENT_ALREADY_IN_PROGRESS', error_message = 'Un déploiement est déjà en cours pour cet utilisateur.', updated_at = NOW()
+WHERE status NOT IN ('succeeded', 'failed')
+  AND EXISTS (
+      SELECT 1 FROM deployments older
+      WHERE older.owner = d.owner
+        AND older.status NOT IN ('succeeded', 'failed')
+        AND older.id < d.id
+  );
+
+CREATE UNIQUE INDEX idx_deployments_one_pending_per_owner ON deployments(owner) WHERE status NOT IN ('succeeded', 'failed');
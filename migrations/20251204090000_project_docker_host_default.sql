@@ -0,0 +1,4 @@
+-- L'hôte par défaut dépend de DOCKER_HOSTS : il est résolu au démarrage, pas figé dans le schéma.
+-- Une valeur NULL désigne le premier hôte configuré.
+ALTER TABLE projects ALTER COLUMN docker_host DROP DEFAULT;
+ALTER TABLE projects ALTER COLUMN docker_host DROP NOT NULL;
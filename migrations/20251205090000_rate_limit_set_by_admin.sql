@@ -0,0 +1,2 @@
+-- Vrai quand la limite de débit a été fixée par un administrateur : le propriétaire ne peut plus la modifier.
+ALTER TABLE projects ADD COLUMN rate_limit_set_by_admin BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,2 @@
+-- Actions portant sur l'inventaire entier, comme son export. Leur target_id vaut 0.
+ALTER TYPE audit_target_type ADD VALUE 'inventory';
@@ -0,0 +1,11 @@
+-- Résultat du dernier scan Grype de chaque projet, qu'il ait bloqué le déploiement ou non.
+CREATE TABLE project_scan_results (
+    project_name VARCHAR(63) PRIMARY KEY,
+    image_ref TEXT NOT NULL,
+    passed BOOLEAN NOT NULL,
+    fail_on_severity scan_severity NOT NULL,
+    blocking_count INTEGER NOT NULL,
+    waived_count INTEGER NOT NULL,
+    summary TEXT NOT NULL,
+    scanned_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,3 @@
+-- Actions de la réconciliation au démarrage, visibles dans le fil d'événements du propriétaire.
+ALTER TYPE project_event_action ADD VALUE 'reconciled_start';
+ALTER TYPE project_event_action ADD VALUE 'container_lost';
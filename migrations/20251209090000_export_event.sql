@@ -0,0 +1,2 @@
+-- Chaque export d'archive apparaît dans le fil d'événements du projet.
+ALTER TYPE project_event_action ADD VALUE 'export';
@@ -0,0 +1,11 @@
+-- Profils de scan par cohorte (groupes de recherche, première année...), plus larges qu'une dérogation par propriétaire.
+-- Un projet peut recevoir son propre profil, prioritaire sur ceux de son propriétaire.
+CREATE TABLE scan_profiles (
+    name VARCHAR(63) PRIMARY KEY,
+    fail_on_severity scan_severity NOT NULL,
+    updated_by VARCHAR(255) NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+ALTER TABLE user_quotas ADD COLUMN scan_profile VARCHAR(63) NULL REFERENCES scan_profiles(name) ON DELETE SET NULL;
+ALTER TABLE projects ADD COLUMN scan_profile VARCHAR(63) NULL REFERENCES scan_profiles(name) ON DELETE SET NULL;
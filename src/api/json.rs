@@ -0,0 +1,164 @@
+use axum::
+{
+    body::Bytes,
+    extract::{FromRequest, OptionalFromRequest, Request},
+    http::{header, HeaderMap},
+};
+use serde::de::DeserializeOwned;
+
+use crate::error::AppError;
+
+// Contrairement à `axum::Json`, une erreur de désérialisation indique le chemin du champ fautif dans
+// `details.field`, et un `Content-Type` absent ou non JSON est refusé en 415.
+pub struct ApiJson<T>(pub T);
+
+impl<T, S> FromRequest<S> for ApiJson<T> where T: DeserializeOwned, S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection>
+    {
+        if !has_json_content_type(req.headers())
+        {
+            return Err(AppError::UnsupportedMediaType("Expected a request body with 'Content-Type: application/json'.".to_string()));
+        }
+
+        let body = Bytes::from_request(req, state).await
+            .map_err(|e| AppError::BadRequest(format!("Could not read the request body: {}", e.body_text())))?;
+
+        let deserializer = &mut serde_json::Deserializer::from_slice(&body);
+        serde_path_to_error::deserialize(deserializer)
+            .map(ApiJson)
+            .map_err(|e|
+            {
+                let path = e.path().to_string();
+                let message = e.inner().to_string();
+                let field = offending_field(&path, &message);
+                let message = match &field
+                {
+                    Some(field) => format!("Invalid JSON payload at '{}': {}", field, message),
+                    None => format!("Invalid JSON payload: {}", message),
+                };
+                AppError::InvalidPayload { field, message }
+            })
+    }
+}
+
+// Sans corps, `None` ; un type de contenu déclaré impose toujours un JSON valide.
+impl<T, S> OptionalFromRequest<S> for ApiJson<T> where T: DeserializeOwned, S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Option<Self>, Self::Rejection>
+    {
+        if !req.headers().contains_key(header::CONTENT_TYPE)
+        {
+            return Ok(None);
+        }
+
+        <ApiJson<T> as FromRequest<S>>::from_request(req, state).await.map(Some)
+    }
+}
+
+fn has_json_content_type(headers: &HeaderMap) -> bool
+{
+    let Some(content_type) = headers.get(header::CONTENT_TYPE).and_then(|value| value.to_str().ok())
+    else
+    {
+        return false;
+    };
+
+    let mime = content_type.split(';').next().unwrap_or_default().trim().to_ascii_lowercase();
+    mime == "application/json" || (mime.starts_with("application/") && mime.ends_with("+json"))
+}
+
+// Un champ manquant est rattaché à son parent : le nom est repris du message. Un champ inconnu est déjà dans le chemin.
+fn offending_field(path: &str, message: &str) -> Option<String>
+{
+    let named = ["unknown field `", "missing field `"]
+        .iter()
+        .find_map(|prefix| message.strip_prefix(prefix))
+        .and_then(|rest| rest.split('`').next());
+
+    match (path, named)
+    {
+        (".", Some(name)) => Some(name.to_string()),
+        (".", None) => None,
+        (path, Some(name)) if path != name && !path.ends_with(&format!(".{}", name)) => Some(format!("{}.{}", path, name)),
+        (path, _) => Some(path.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use axum::body::Body;
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct Payload
+    {
+        name: String,
+        replicas: u32,
+        routing: Option<Routing>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct Routing
+    {
+        sticky_sessions: bool,
+    }
+
+    async fn extract(content_type: Option<&str>, body: &str) -> Result<ApiJson<Payload>, AppError>
+    {
+        let mut request = Request::builder().method("POST").uri("/");
+        if let Some(content_type) = content_type
+        {
+            request = request.header(header::CONTENT_TYPE, content_type);
+        }
+        <ApiJson<Payload> as FromRequest<()>>::from_request(request.body(Body::from(body.to_string())).unwrap(), &()).await
+    }
+
+    async fn rejected_field(body: &str) -> Option<String>
+    {
+        match extract(Some("application/json"), body).await
+        {
+            Err(AppError::InvalidPayload { field, .. }) => field,
+            other => panic!("expected an invalid payload, got {:?}", other.map(|ApiJson(payload)| payload)),
+        }
+    }
+
+    #[tokio::test]
+    async fn valid_payload_is_accepted()
+    {
+        let body = r#"{"name":"app","replicas":2,"routing":{"sticky_sessions":true}}"#;
+        let ApiJson(payload) = extract(Some("application/json; charset=utf-8"), body).await.unwrap();
+
+        assert_eq!((payload.name.as_str(), payload.replicas), ("app", 2));
+        assert!(payload.routing.is_some_and(|routing| routing.sticky_sessions));
+    }
+
+    #[tokio::test]
+    async fn error_names_the_offending_field()
+    {
+        assert_eq!(rejected_field(r#"{"name":"app","replicas":2,"enviroment_vars":{}}"#).await.as_deref(), Some("enviroment_vars"));
+        assert_eq!(rejected_field(r#"{"name":"app","replicas":"two"}"#).await.as_deref(), Some("replicas"));
+        assert_eq!(rejected_field(r#"{"name":"app"}"#).await.as_deref(), Some("replicas"));
+        assert_eq!(rejected_field(r#"{"name":"app","replicas":2,"routing":{"sticky":true}}"#).await.as_deref(), Some("routing.sticky"));
+        assert_eq!(rejected_field(r#"{"name":"app","replicas":2,"routing":{}}"#).await.as_deref(), Some("routing.sticky_sessions"));
+    }
+
+    #[tokio::test]
+    async fn non_json_content_type_is_refused_with_415()
+    {
+        for content_type in [None, Some("text/plain"), Some("application/x-www-form-urlencoded")]
+        {
+            let result = extract(content_type, r#"{"name":"app","replicas":2}"#).await;
+            assert!(matches!(result, Err(AppError::UnsupportedMediaType(_))), "{:?} should be refused", content_type);
+        }
+        assert!(extract(Some("application/merge-patch+json"), r#"{"name":"app","replicas":2}"#).await.is_ok());
+    }
+}
@@ -0,0 +1,288 @@
+use std::{cmp::Ordering, marker::PhantomData};
+
+use axum::
+{
+    extract::{FromRequestParts, Query},
+    http::request::Parts,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::error;
+
+use crate::error::AppError;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SortOrder
+{
+    Asc,
+    Desc,
+}
+
+impl SortOrder
+{
+    pub fn as_sql(self) -> &'static str
+    {
+        match self
+        {
+            Self::Asc => "ASC",
+            Self::Desc => "DESC",
+        }
+    }
+}
+
+pub trait ListSpec
+{
+    const DEFAULT_PER_PAGE: u32 = 20;
+    const MAX_PER_PAGE: u32;
+    const SORTABLE: &'static [&'static str];
+    const DEFAULT_SORT: &'static str;
+    const DEFAULT_ORDER: SortOrder = SortOrder::Asc;
+}
+
+#[derive(Deserialize)]
+struct RawListParams
+{
+    page: Option<String>,
+    per_page: Option<String>,
+    sort_by: Option<String>,
+    order: Option<String>,
+    fields: Option<String>,
+}
+
+pub struct ListParams<S: ListSpec>
+{
+    pub page: u32,
+    pub per_page: u32,
+    pub sort_by: &'static str,
+    pub order: SortOrder,
+    pub fields: Option<Vec<String>>,
+    spec: PhantomData<fn() -> S>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Paginated<T>
+{
+    pub items: Vec<T>,
+    pub page: u32,
+    pub per_page: u32,
+    pub total: usize,
+}
+
+impl<S: ListSpec, St> FromRequestParts<St> for ListParams<S> where St: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &St) -> Result<Self, Self::Rejection>
+    {
+        let Query(raw) = Query::<RawListParams>::try_from_uri(&parts.uri)
+            .map_err(|e| AppError::BadRequest(format!("Invalid list parameters: {}", e)))?;
+
+        Self::from_raw(raw)
+    }
+}
+
+impl<S: ListSpec> ListParams<S>
+{
+    fn from_raw(raw: RawListParams) -> Result<Self, AppError>
+    {
+        let page = match raw.page
+        {
+            Some(page) => page.parse::<u32>().ok().filter(|page| *page >= 1).ok_or_else(||
+            {
+                invalid("page", "expected a positive integer.".to_string())
+            })?,
+            None => 1,
+        };
+
+        let per_page = match raw.per_page
+        {
+            Some(per_page) => per_page.parse::<u32>().ok().filter(|per_page| (1..=S::MAX_PER_PAGE).contains(per_page)).ok_or_else(||
+            {
+                invalid("per_page", format!("must be between 1 and {}.", S::MAX_PER_PAGE))
+            })?,
+            None => S::DEFAULT_PER_PAGE.min(S::MAX_PER_PAGE),
+        };
+
+        let sort_by = match raw.sort_by
+        {
+            Some(sort_by) => S::SORTABLE.iter().find(|key| **key == sort_by).copied().ok_or_else(||
+            {
+                invalid("sort_by", format!("'{}' is not sortable. Allowed: {}.", sort_by, S::SORTABLE.join(", ")))
+            })?,
+            None => S::DEFAULT_SORT,
+        };
+
+        let order = match raw.order.as_deref()
+        {
+            Some("asc") => SortOrder::Asc,
+            Some("desc") => SortOrder::Desc,
+            Some(_) => return Err(invalid("order", "expected 'asc' or 'desc'.".to_string())),
+            None => S::DEFAULT_ORDER,
+        };
+
+        let fields = match raw.fields
+        {
+            Some(fields) =>
+            {
+                let fields: Vec<String> = fields.split(',').map(|field| field.trim().to_string()).collect();
+                if fields.iter().any(String::is_empty)
+                {
+                    return Err(invalid("fields", "expected a comma-separated list of keys.".to_string()));
+                }
+                Some(fields)
+            }
+            None => None,
+        };
+
+        Ok(Self { page, per_page, sort_by, order, fields, spec: PhantomData })
+    }
+
+    // Le tri porte sur les valeurs sérialisées : même sémantique pour toutes les listes.
+    pub fn paginate<T: Serialize>(&self, items: &[T]) -> Result<Paginated<Value>, AppError>
+    {
+        let mut values = items
+            .iter()
+            .map(serde_json::to_value)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e|
+            {
+                error!("Failed to serialize list items: {}", e);
+                AppError::InternalServerError
+            })?;
+
+        values.sort_by(|a, b|
+        {
+            let ordering = compare_values(a.get(self.sort_by), b.get(self.sort_by));
+            match self.order
+            {
+                SortOrder::Asc => ordering,
+                SortOrder::Desc => ordering.reverse(),
+            }
+        });
+
+        let total = values.len();
+        let start = (self.page as usize - 1).saturating_mul(self.per_page as usize);
+
+        let items = values
+            .into_iter()
+            .skip(start)
+            .take(self.per_page as usize)
+            .map(|value| self.project(value))
+            .collect();
+
+        Ok(Paginated { items, page: self.page, per_page: self.per_page, total })
+    }
+
+    pub fn offset(&self) -> i64
+    {
+        (i64::from(self.page) - 1).saturating_mul(i64::from(self.per_page))
+    }
+
+    pub fn page_of<T: Serialize>(&self, items: &[T], total: usize) -> Result<Paginated<Value>, AppError>
+    {
+        let items = items
+            .iter()
+            .map(|item| serde_json::to_value(item).map(|value| self.project(value)))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e|
+            {
+                error!("Failed to serialize list items: {}", e);
+                AppError::InternalServerError
+            })?;
+
+        Ok(Paginated { items, page: self.page, per_page: self.per_page, total })
+    }
+
+    fn project(&self, mut value: Value) -> Value
+    {
+        if let (Some(fields), Some(object)) = (&self.fields, value.as_object_mut())
+        {
+            object.retain(|key, _| fields.iter().any(|field| field == key));
+        }
+        value
+    }
+}
+
+fn invalid(parameter: &str, reason: String) -> AppError
+{
+    AppError::BadRequest(format!("Invalid '{}' parameter: {}", parameter, reason))
+}
+
+// Absents et null d'abord. Les horodatages RFC 3339 se comparent correctement comme chaînes.
+fn compare_values(a: Option<&Value>, b: Option<&Value>) -> Ordering
+{
+    match (a, b)
+    {
+        (Some(Value::Number(a)), Some(Value::Number(b))) => a.as_f64().partial_cmp(&b.as_f64()).unwrap_or(Ordering::Equal),
+        (Some(Value::String(a)), Some(Value::String(b))) => a.cmp(b),
+        (Some(Value::Bool(a)), Some(Value::Bool(b))) => a.cmp(b),
+        (None | Some(Value::Null), None | Some(Value::Null)) => Ordering::Equal,
+        (None | Some(Value::Null), _) => Ordering::Less,
+        (_, None | Some(Value::Null)) => Ordering::Greater,
+        _ => Ordering::Equal,
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use serde_json::json;
+
+    struct TestList;
+
+    impl ListSpec for TestList
+    {
+        const MAX_PER_PAGE: u32 = 50;
+        const SORTABLE: &'static [&'static str] = &["name", "created_at"];
+        const DEFAULT_SORT: &'static str = "created_at";
+        const DEFAULT_ORDER: SortOrder = SortOrder::Desc;
+    }
+
+    fn parse(query: &str) -> Result<ListParams<TestList>, AppError>
+    {
+        let Query(raw) = Query::<RawListParams>::try_from_uri(&format!("/?{}", query).parse().unwrap()).unwrap();
+        ListParams::from_raw(raw)
+    }
+
+    #[test]
+    fn defaults_apply_without_parameters()
+    {
+        let params = parse("").unwrap();
+
+        assert_eq!((params.page, params.per_page, params.sort_by, params.order), (1, 20, "created_at", SortOrder::Desc));
+        assert!(params.fields.is_none());
+    }
+
+    #[test]
+    fn per_page_is_capped_by_the_list()
+    {
+        assert_eq!(parse("per_page=50").unwrap().per_page, 50);
+
+        for query in ["per_page=51", "per_page=0", "per_page=-1", "per_page=ten", "page=0"]
+        {
+            assert!(matches!(parse(query), Err(AppError::BadRequest(_))), "{} should be refused", query);
+        }
+    }
+
+    #[test]
+    fn only_whitelisted_keys_are_sortable()
+    {
+        assert_eq!(parse("sort_by=name&order=asc").unwrap().sort_by, "name");
+
+        for query in ["sort_by=owner", "sort_by=name;DROP%20TABLE%20projects", "order=up"]
+        {
+            assert!(matches!(parse(query), Err(AppError::BadRequest(_))), "{} should be refused", query);
+        }
+    }
+
+    #[test]
+    fn paginate_sorts_pages_and_selects_fields()
+    {
+        let items = [json!({ "name": "b", "id": 1 }), json!({ "name": "c", "id": 2 }), json!({ "name": "a", "id": 3 })];
+        let page = parse("sort_by=name&order=asc&per_page=2&page=2&fields=name").unwrap().paginate(&items).unwrap();
+
+        assert_eq!(page.total, 3);
+        assert_eq!(page.items, [json!({ "name": "c" })]);
+    }
+}
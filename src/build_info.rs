@@ -0,0 +1,37 @@
+use serde::Serialize;
+use time::OffsetDateTime;
+
+// HANGAR_GIT_SHA et HANGAR_BUILD_TIMESTAMP sont fournis par le build Docker ; en local ils restent inconnus.
+#[derive(Debug, Serialize, Clone, Copy)]
+pub struct BuildInfo
+{
+    pub version: &'static str,
+    pub git_sha: &'static str,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub built_at: Option<OffsetDateTime>,
+}
+
+pub fn build_info() -> BuildInfo
+{
+    BuildInfo
+    {
+        version: env!("CARGO_PKG_VERSION"),
+        git_sha: option_env!("HANGAR_GIT_SHA").filter(|sha| !sha.is_empty()).unwrap_or("unknown"),
+        built_at: option_env!("HANGAR_BUILD_TIMESTAMP")
+            .and_then(|timestamp| timestamp.parse::<i64>().ok())
+            .and_then(|timestamp| OffsetDateTime::from_unix_timestamp(timestamp).ok()),
+    }
+}
+
+impl std::fmt::Display for BuildInfo
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    {
+        let short_sha = self.git_sha.get(..12).unwrap_or(self.git_sha);
+        match self.built_at
+        {
+            Some(built_at) => write!(f, "hangar_back {} ({}, built {})", self.version, short_sha, built_at.date()),
+            None => write!(f, "hangar_back {} ({})", self.version, short_sha),
+        }
+    }
+}
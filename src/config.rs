@@ -1,8 +1,129 @@
 use crate::error::ConfigError;
-use serde::Deserialize;
+use crate::i18n::Language;
+use crate::model::database::UserResourceLimits;
+use crate::model::scan::Severity;
+use crate::model::volume_quota::VolumeQuotaPolicy;
+use crate::services::crypto_service::{EncryptionAlgorithm, EncryptionKeys};
+use serde::{Deserialize, Serialize};
 use base64::prelude::*;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::cell::RefCell;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
+const ENCRYPTION_KEY_LENGTH: usize = 32;
+// Un secret HS256 plus court que sa sortie de 256 bits affaiblit la signature.
+const JWT_SECRET_MIN_LENGTH: usize = 32;
+const DOCKER_TLS_FILES: [&str; 3] = ["ca.pem", "cert.pem", "key.pem"];
+
+#[derive(Deserialize, Clone)]
+pub struct DockerHostConfig
+{
+    pub name: String,
+    pub url: Option<String>,
+    pub tls_cert_dir: Option<PathBuf>,
+}
+
+impl DockerHostConfig
+{
+    pub fn client_env(&self) -> Vec<(&'static str, String)>
+    {
+        let Some(url) = &self.url
+        else
+        {
+            return Vec::new();
+        };
+
+        // Les clients Docker n'acceptent que tcp:// ; le TLS passe par DOCKER_TLS_VERIFY.
+        let docker_host = match url.split_once("://")
+        {
+            Some(("http" | "https", address)) => format!("tcp://{}", address),
+            _ => url.clone(),
+        };
+
+        let mut env = vec![("DOCKER_HOST", docker_host)];
+        if let Some(cert_dir) = &self.tls_cert_dir
+        {
+            env.push(("DOCKER_CERT_PATH", cert_dir.display().to_string()));
+            env.push(("DOCKER_TLS_VERIFY", "1".to_string()));
+        }
+        env
+    }
+}
+
+// Les routes `Streaming` n'ont pas de délai global : le leur ne borne que l'attente entre deux fragments.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RouteClass
+{
+    Short,
+    Long,
+    Streaming,
+}
+
+// Champs recopiés un à un : un nouveau secret de `Config` ne peut pas fuiter par ici.
+#[derive(Serialize)]
+pub struct ConfigSummary
+{
+    pub public_address: String,
+    pub app_prefix: String,
+    pub app_domain_suffix: String,
+    pub docker_network: String,
+    pub docker_hosts: Vec<String>,
+    pub docker_api_timeout_seconds: u64,
+    pub route_timeout_short_seconds: u64,
+    pub route_timeout_long_seconds: u64,
+    pub route_timeout_streaming_idle_seconds: u64,
+    pub egress_internal_network: Option<String>,
+    pub edge_hostname: Option<String>,
+    pub traefik_entrypoint: String,
+    pub traefik_tls_enabled: bool,
+    pub mariadb_public_host: String,
+    pub mariadb_public_port: u16,
+    pub build_base_image: String,
+    pub build_cache_enabled: bool,
+    pub build_max_concurrent: usize,
+    pub scanner: &'static str,
+    pub scan_fail_on_severity: Severity,
+    pub sbom_enabled: bool,
+    pub cosign_enabled: bool,
+    pub cosign_required_registries: Vec<String>,
+    pub replicas_max: i32,
+    pub container_memory_mb: i64,
+    pub container_cpu_quota: i64,
+    pub quota_default_projects: i32,
+    pub quota_default_databases: i32,
+    pub quota_default_team_projects: i32,
+    pub max_participants_per_project: i64,
+    pub env_vars_max_count: usize,
+    pub env_var_max_value_bytes: usize,
+    pub env_vars_max_total_bytes: usize,
+    pub volume_quota_default_mb: i64,
+    pub volume_quota_policy: VolumeQuotaPolicy,
+    pub project_deletion_grace_hours: i64,
+    pub status_sync_interval_seconds: u64,
+    pub status_drift_threshold_seconds: u64,
+    pub schedule_utc_offset_hours: i8,
+    pub availability_retention_days: i64,
+    pub strict_startup: bool,
+    pub cors_allowed_origins: Vec<String>,
+    pub cors_dev_allow_any_origin: bool,
+    pub mariadb_user_limits: UserResourceLimits,
+    pub db_max_connections: u32,
+    pub encryption_algorithm: &'static str,
+    pub previous_encryption_keys: usize,
+    pub default_language: &'static str,
+}
+
+#[derive(Serialize)]
+pub struct ConfigChange
+{
+    pub setting: &'static str,
+    pub previous: serde_json::Value,
+    pub current: serde_json::Value,
+}
+
+// Lue au démarrage. Seuls les réglages de `with_reloaded` changent au rechargement ; les autres (pools, clés,
+// hôtes Docker et leur délai d'API, intervalles) demandent un redémarrage.
 #[derive(Deserialize, Clone)]
 pub struct Config
 {
@@ -19,47 +140,237 @@ pub struct Config
     pub app_prefix: String,
     pub app_domain_suffix: String,
     pub build_base_image: String,
+    pub build_cache_enabled: bool,
+    pub build_cache_max_age_days: i64,
+    pub build_cache_max_size_mb: i64,
+    pub build_tmp_dir: PathBuf,
+    pub build_min_free_mb: u64,
+    pub build_max_concurrent: usize,
+    pub clone_timeout_seconds: u64,
+    pub clone_stall_timeout_seconds: u64,
     pub github_app_id: String,
     pub github_private_key: Vec<u8>,
     pub docker_network: String,
+    pub docker_hosts: Vec<DockerHostConfig>,
+    pub docker_api_timeout_seconds: u64,
+    // Traefik doit aussi y être rattaché.
+    pub egress_internal_network: Option<String>,
+    pub edge_hostname: Option<String>,
     pub traefik_entrypoint: String,
     pub traefik_cert_resolver: String,
+    pub traefik_tls_enabled: bool,
+    pub rate_limit_min_rps: i32,
+    pub rate_limit_max_rps: i32,
+    pub rate_limit_owner_max_rps: i32,
+    pub replicas_max: i32,
+    pub replicas_owner_max: i32,
     pub container_memory_mb: i64,
     pub container_cpu_quota: i64,
     pub grype_enabled: bool,
-    pub grype_fail_on_severity: String,
+    pub grype_fail_on_severity: Severity,
+    pub scan_timeout_seconds: u64,
+    pub syft_enabled: bool,
+    pub syft_path: String,
+    pub maintenance_page_image: String,
+    pub image_validation_max_concurrent: usize,
+    pub image_update_check_interval_seconds: u64,
+    pub default_language: Language,
+    pub github_webhook_secrets: Vec<String>,
+    pub webhook_max_payload_bytes: usize,
+    pub webhook_replay_window_seconds: i64,
+    pub cosign_enabled: bool,
+    pub cosign_path: String,
+    pub cosign_public_keys: Vec<String>,
+    pub cosign_required_registries: HashSet<String>,
+    // `*` remplace toute suite de caractères ; ces noms s'ajoutent à la liste intégrée sans la remplacer.
+    pub forbidden_env_vars: Vec<String>,
+    pub forbidden_env_prefixes: Vec<String>,
+    pub env_vars_max_count: usize,
+    pub env_var_max_value_bytes: usize,
+    pub env_vars_max_total_bytes: usize,
+    pub db_template_max_bytes: usize,
     pub db_max_connections: u32,
-    pub timeout_normal: u64,
-    pub timeout_long: u64,
+    pub db_acquire_timeout_seconds: u64,
+    pub db_max_lifetime_seconds: u64,
+    pub db_idle_timeout_seconds: u64,
+    pub mariadb_user_limits: UserResourceLimits,
+    pub timeouts: HashMap<RouteClass, u64>,
+    pub http_connect_timeout: u64,
+    pub http_request_timeout: u64,
+    pub quota_default_projects: i32,
+    pub quota_default_databases: i32,
+    pub quota_default_team_projects: i32,
+    // Compte les participants acceptés comme les invitations en attente.
+    pub max_participants_per_project: i64,
+    pub invitation_expiry_days: i32,
+    pub legacy_direct_participants: bool,
+    pub auth_max_failed_per_minute: i64,
+    pub db_reveal_max_auth_age_seconds: u64,
+    pub metrics_cache_fresh_seconds: i64,
+    pub broadcast_max_concurrent: usize,
+    pub broadcast_container_timeout_seconds: u64,
+    pub volume_quota_default_mb: i64,
+    pub volume_quota_policy: VolumeQuotaPolicy,
+    pub volume_quota_grace_period_seconds: i64,
+    pub volume_quota_check_interval_seconds: u64,
+    pub cleanup_give_up_days: i64,
+    pub project_deletion_grace_hours: i64,
+    pub status_sync_interval_seconds: u64,
+    pub status_drift_threshold_seconds: u64,
+    pub schedule_utc_offset_hours: i8,
+    pub availability_retention_days: i64,
+    pub strict_startup: bool,
+    pub cors_allowed_origins: Vec<String>,
+    // Développement uniquement : toute origine, sans credentials.
+    pub cors_dev_allow_any_origin: bool,
     pub admin_logins: HashSet<String>,
-    pub encryption_key: Vec<u8>,
+    pub encryption_key: EncryptionKeys,
+}
+
+// L'environnement l'emporte sur le fichier, dont les clés sont les noms des variables, dans n'importe quelle casse.
+struct ConfigSource
+{
+    file: HashMap<String, String>,
+    // Une clé du fichier absente de cet ensemble est inconnue.
+    read: RefCell<HashSet<String>>,
+}
+
+impl ConfigSource
+{
+    fn load(path: Option<&Path>) -> Result<Self, ConfigError>
+    {
+        let mut file = HashMap::new();
+
+        if let Some(path) = path
+        {
+            let invalid = |reason: String| ConfigError::Invalid("HANGAR_CONFIG_FILE".to_string(), format!("{}: {}", path.display(), reason));
+
+            let contents = std::fs::read_to_string(path).map_err(|e| invalid(e.to_string()))?;
+            let table: toml::Table = toml::from_str(&contents).map_err(|e| invalid(e.to_string()))?;
+
+            for (key, value) in table
+            {
+                let value = match value
+                {
+                    toml::Value::String(value) => value,
+                    // Les listes s'écrivent comme dans l'environnement, séparées par des virgules.
+                    toml::Value::Array(items) => items.iter()
+                        .map(|item| item.as_str().map_or_else(|| item.to_string(), str::to_string))
+                        .collect::<Vec<_>>()
+                        .join(","),
+                    toml::Value::Table(_) => return Err(invalid(format!("'{}' must be a value, not a table", key))),
+                    other => other.to_string(),
+                };
+                file.insert(key.to_uppercase(), value);
+            }
+        }
+
+        Ok(Self { file, read: RefCell::new(HashSet::new()) })
+    }
+
+    fn var(&self, name: &str) -> Result<String, std::env::VarError>
+    {
+        self.read.borrow_mut().insert(name.to_string());
+        std::env::var(name).or_else(|e| self.file.get(name).cloned().ok_or(e))
+    }
+
+    fn unknown_keys(&self) -> Vec<String>
+    {
+        let read = self.read.borrow();
+        let mut unknown: Vec<String> = self.file.keys().filter(|key| !read.contains(*key)).map(|key| key.to_lowercase()).collect();
+        unknown.sort();
+        unknown
+    }
 }
 
 impl Config
 {
     pub fn from_env() -> Result<Self, ConfigError>
     {
-        let host = std::env::var("APP_HOST").map_err(|_| ConfigError::Missing("APP_HOST".to_string()))?;
+        let path = std::env::var("HANGAR_CONFIG_FILE").ok().filter(|path| !path.is_empty()).map(PathBuf::from);
+        Self::from_sources(path.as_deref())
+    }
+
+    // Les secrets ont plutôt leur place dans l'environnement.
+    pub fn from_sources(path: Option<&Path>) -> Result<Self, ConfigError>
+    {
+        let source = ConfigSource::load(path)?;
+        let config = Self::build(&source)?;
+
+        let unknown = source.unknown_keys();
+        if !unknown.is_empty()
+        {
+            tracing::warn!("Unknown keys in the configuration file, ignored: {}.", unknown.join(", "));
+        }
+
+        Ok(config)
+    }
 
-        let port_str = std::env::var("APP_PORT").map_err(|_| ConfigError::Missing("APP_PORT".to_string()))?;
+    #[cfg(test)]
+    pub fn for_tests(overrides: &[(&str, &str)]) -> Result<Self, ConfigError>
+    {
+        let mut file: HashMap<String, String> = [
+            ("APP_HOST", "127.0.0.1"),
+            ("APP_PORT", "3000"),
+            ("APP_PUBLIC_ADDRESS", "https://hangar.test"),
+            ("DATABASE_URL", "postgres://hangar@localhost/hangar"),
+            ("MARIADB_URL", "mysql://hangar@localhost/hangar"),
+            ("MARIADB_PUBLIC_HOST", "db.hangar.test"),
+            ("MARIADB_PUBLIC_PORT", "3306"),
+            ("APP_JWT_SECRET", "un-secret-de-test-suffisamment-long-pour-la-validation"),
+            ("JWT_EXPIRATION_SECONDS", "3600"),
+            ("CAS_VALIDATION_URL", "https://cas.hangar.test/serviceValidate"),
+            ("APP_PREFIX", "hangar"),
+            ("APP_DOMAIN_SUFFIX", "apps.hangar.test"),
+            ("BUILD_BASE_IMAGE", "hangar-base:latest"),
+            ("BUILD_TMP_DIR", "/tmp"),
+            ("GITHUB_APP_ID", "1"),
+            ("DOCKER_NETWORK", "hangar"),
+            ("DOCKER_TRAEFIK_ENTRYPOINT", "websecure"),
+            ("DOCKER_TRAEFIK_CERTRESOLVER", "letsencrypt"),
+            ("GRYPE_ENABLED", "false"),
+            ("GRYPE_FAIL_ON_SEVERITY", "high"),
+            ("DOCKER_CONTAINER_MEMORY_MB", "512"),
+            ("DOCKER_CONTAINER_CPU_QUOTA", "100000"),
+            ("DB_MAX_CONNECTIONS", "5"),
+            ("TIMEOUTS_SHORT", "30"),
+            ("TIMEOUTS_LONG", "600"),
+            ("CORS_ALLOWED_ORIGINS", "https://hangar.test"),
+            ("APP_ADMINS", "admin"),
+            ("APP_ENCRYPTION_KEY", "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f"),
+        ]
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        file.insert("GITHUB_PRIVATE_KEY_B64".to_string(), BASE64_STANDARD.encode(include_bytes!("../testdata/github_app_key.pem")));
+        file.extend(overrides.iter().map(|(name, value)| (name.to_string(), value.to_string())));
+
+        Self::build(&ConfigSource { file, read: RefCell::new(HashSet::new()) })
+    }
+
+    fn build(source: &ConfigSource) -> Result<Self, ConfigError>
+    {
+        let host = source.var("APP_HOST").map_err(|_| ConfigError::Missing("APP_HOST".to_string()))?;
+
+        let port_str = source.var("APP_PORT").map_err(|_| ConfigError::Missing("APP_PORT".to_string()))?;
         let port = port_str.parse::<u16>().map_err(|_|
         {
             ConfigError::Invalid("APP_PORT".to_string(), port_str)
         })?;
 
-        let public_address = std::env::var("APP_PUBLIC_ADDRESS")
+        let public_address = source.var("APP_PUBLIC_ADDRESS")
             .map_err(|_| ConfigError::Missing("APP_PUBLIC_ADDRESS".to_string()))?;
 
-        let db_url = std::env::var("DATABASE_URL")
+        let db_url = source.var("DATABASE_URL")
             .map_err(|_| ConfigError::Missing("DATABASE_URL".to_string()))?;
 
-        let mariadb_url = std::env::var("MARIADB_URL")
+        let mariadb_url = source.var("MARIADB_URL")
             .map_err(|_| ConfigError::Missing("MARIADB_URL".to_string()))?;
             
-        let mariadb_public_host = std::env::var("MARIADB_PUBLIC_HOST")
+        let mariadb_public_host = source.var("MARIADB_PUBLIC_HOST")
             .map_err(|_| ConfigError::Missing("MARIADB_PUBLIC_HOST".to_string()))?;
             
-        let mariadb_public_port_str = std::env::var("MARIADB_PUBLIC_PORT")
+        let mariadb_public_port_str = source.var("MARIADB_PUBLIC_PORT")
             .map_err(|_| ConfigError::Missing("MARIADB_PUBLIC_PORT".to_string()))?;
         
         let mariadb_public_port = mariadb_public_port_str.parse::<u16>().map_err(|_|
@@ -67,37 +378,87 @@ impl Config
             ConfigError::Invalid("MARIADB_PUBLIC_PORT".to_string(), mariadb_public_port_str)
         })?;
 
-        let jwt_secret = std::env::var("APP_JWT_SECRET")
+        let jwt_secret = source.var("APP_JWT_SECRET")
             .map_err(|_| ConfigError::Missing("APP_JWT_SECRET".to_string()))?;
+        if jwt_secret.len() < JWT_SECRET_MIN_LENGTH
+        {
+            return Err(ConfigError::Invalid(
+                "APP_JWT_SECRET".to_string(),
+                format!("Must be at least {} characters long, got {}", JWT_SECRET_MIN_LENGTH, jwt_secret.len())
+            ));
+        }
 
-        let jwt_expiration_seconds = std::env::var("JWT_EXPIRATION_SECONDS")
+        let jwt_expiration_seconds = source.var("JWT_EXPIRATION_SECONDS")
             .map_err(|_| ConfigError::Missing("JWT_EXPIRATION_SECONDS".to_string()))?
             .parse().map_err(|_| ConfigError::Invalid("JWT_EXPIRATION_SECONDS".to_string(), "Invalid number".to_string()))?;
 
-        let cas_validation_url = std::env::var("CAS_VALIDATION_URL")
+        let cas_validation_url = source.var("CAS_VALIDATION_URL")
             .map_err(|_| ConfigError::Missing("CAS_VALIDATION_URL".to_string()))?;
 
-        let app_prefix = std::env::var("APP_PREFIX").map_err(|_| ConfigError::Missing("APP_PREFIX".to_string()))?;
-        let app_domain_suffix = std::env::var("APP_DOMAIN_SUFFIX").map_err(|_| ConfigError::Missing("APP_DOMAIN_SUFFIX".to_string()))?;
+        let app_prefix = source.var("APP_PREFIX").map_err(|_| ConfigError::Missing("APP_PREFIX".to_string()))?;
+        let app_domain_suffix = source.var("APP_DOMAIN_SUFFIX").map_err(|_| ConfigError::Missing("APP_DOMAIN_SUFFIX".to_string()))?;
 
-        let build_base_image = std::env::var("BUILD_BASE_IMAGE")
+        let build_base_image = source.var("BUILD_BASE_IMAGE")
             .map_err(|_| ConfigError::Missing("BUILD_BASE_IMAGE".to_string()))?;
 
-        let github_app_id = std::env::var("GITHUB_APP_ID")
+        let github_app_id = source.var("GITHUB_APP_ID")
             .map_err(|_| ConfigError::Missing("GITHUB_APP_ID".to_string()))?;
 
-        let private_key_b64 = std::env::var("GITHUB_PRIVATE_KEY_B64")
+        let private_key_b64 = source.var("GITHUB_PRIVATE_KEY_B64")
             .map_err(|_| ConfigError::Missing("GITHUB_PRIVATE_KEY_B64".to_string()))?;
 
         let github_private_key = BASE64_STANDARD.decode(private_key_b64)
             .map_err(|_| ConfigError::Invalid("GITHUB_PRIVATE_KEY_B64".to_string(), "Invalid Base64".to_string()))?;
+        // Sinon la clé n'est lue qu'au premier appel à GitHub, bien après le démarrage.
+        jsonwebtoken::EncodingKey::from_rsa_pem(&github_private_key)
+            .map_err(|e| ConfigError::Invalid("GITHUB_PRIVATE_KEY_B64".to_string(), format!("Not an RSA private key in PEM format: {}", e)))?;
 
-        let docker_network = std::env::var("DOCKER_NETWORK").map_err(|_| ConfigError::Missing("DOCKER_NETWORK".to_string()))?;
-        let traefik_entrypoint = std::env::var("DOCKER_TRAEFIK_ENTRYPOINT").map_err(|_| ConfigError::Missing("DOCKER_TRAEFIK_ENTRYPOINT".to_string()))?;
-        let traefik_cert_resolver = std::env::var("DOCKER_TRAEFIK_CERTRESOLVER")
+        let docker_network = source.var("DOCKER_NETWORK").map_err(|_| ConfigError::Missing("DOCKER_NETWORK".to_string()))?;
+
+        // Format : "nom=url,nom2=url2". Sans valeur, un hôte unique 'default' utilise DOCKER_HOST,
+        // ou à défaut le socket local.
+        let mut docker_hosts = match source.var("DOCKER_HOSTS")
+        {
+            Ok(raw) => parse_docker_hosts(&raw)?,
+            Err(_) =>
+            {
+                let url = source.var("DOCKER_HOST").ok().map(|url| url.trim().to_string()).filter(|url| !url.is_empty());
+                if let Some(url) = &url
+                {
+                    validate_docker_url("DOCKER_HOST", url)?;
+                }
+                vec![DockerHostConfig { name: "default".to_string(), url, tls_cert_dir: None }]
+            }
+        };
+        let docker_tls_cert_dir = source.var("DOCKER_TLS_CERT_DIR").ok().map(PathBuf::from);
+        for host in &mut docker_hosts
+        {
+            if host.url.as_deref().is_some_and(|url| url.starts_with("https://"))
+            {
+                host.tls_cert_dir = Some(docker_tls_cert_dir_for(docker_tls_cert_dir.as_deref(), &host.name)?);
+            }
+        }
+        let docker_api_timeout_seconds = parse_optional_env(source, "DOCKER_API_TIMEOUT", 120)?;
+
+        let egress_internal_network = source.var("EGRESS_INTERNAL_NETWORK").ok()
+            .map(|network| network.trim().to_string())
+            .filter(|network| !network.is_empty());
+        let edge_hostname = source.var("EDGE_HOSTNAME").ok()
+            .map(|hostname| hostname.trim().trim_end_matches('.').to_string())
+            .filter(|hostname| !hostname.is_empty());
+        let traefik_entrypoint = source.var("DOCKER_TRAEFIK_ENTRYPOINT").map_err(|_| ConfigError::Missing("DOCKER_TRAEFIK_ENTRYPOINT".to_string()))?;
+        let traefik_cert_resolver = source.var("DOCKER_TRAEFIK_CERTRESOLVER")
             .map_err(|_| ConfigError::Missing("DOCKER_TRAEFIK_CERTRESOLVER".to_string()))?;
+        let traefik_tls_enabled = parse_optional_env(source, "TRAEFIK_TLS_ENABLED", true)?;
+
+        let rate_limit_min_rps = parse_optional_env(source, "RATE_LIMIT_MIN_RPS", 1)?;
+        let rate_limit_max_rps = parse_optional_env(source, "RATE_LIMIT_MAX_RPS", 1000)?;
+        let rate_limit_owner_max_rps = parse_optional_env(source, "RATE_LIMIT_OWNER_MAX_RPS", 100)?;
 
-        let grype_enabled_str = std::env::var("GRYPE_ENABLED")
+        let replicas_max = parse_optional_env(source, "REPLICAS_MAX", 8)?;
+        let replicas_owner_max = parse_optional_env(source, "REPLICAS_OWNER_MAX", 2)?;
+
+        let grype_enabled_str = source.var("GRYPE_ENABLED")
             .map_err(|_| ConfigError::Missing("GRYPE_ENABLED".to_string()))?;
         let grype_enabled = grype_enabled_str.parse::<bool>().map_err(|_|
         {
@@ -105,52 +466,222 @@ impl Config
         })?;
 
 
-        let grype_fail_on_severity = std::env::var("GRYPE_FAIL_ON_SEVERITY")
+        let grype_fail_on_severity_str = source.var("GRYPE_FAIL_ON_SEVERITY")
             .map_err(|_| ConfigError::Missing("GRYPE_FAIL_ON_SEVERITY".to_string()))?;
+        let grype_fail_on_severity = grype_fail_on_severity_str.parse::<Severity>().map_err(|_|
+        {
+            ConfigError::Invalid("GRYPE_FAIL_ON_SEVERITY".to_string(), grype_fail_on_severity_str)
+        })?;
+
+        let syft_enabled = parse_optional_env(source, "SYFT_ENABLED", false)?;
+        let scan_timeout_seconds = parse_optional_env(source, "SCAN_TIMEOUT_SECONDS", 600)?;
+        let syft_path = parse_optional_env(source, "SYFT_PATH", "syft".to_string())?;
+
+        let build_cache_enabled = parse_optional_env(source, "BUILD_CACHE_ENABLED", true)?;
+        let build_cache_max_age_days = parse_optional_env(source, "BUILD_CACHE_MAX_AGE_DAYS", 14)?;
+        let build_cache_max_size_mb = parse_optional_env(source, "BUILD_CACHE_MAX_SIZE_MB", 10 * 1024)?;
+        // Le répertoire temporaire système est souvent un petit tmpfs, trop juste pour les gros dépôts.
+        let build_tmp_dir = source.var("BUILD_TMP_DIR").ok()
+            .map(|dir| dir.trim().to_string())
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .unwrap_or_else(std::env::temp_dir);
+        let build_min_free_mb = parse_optional_env(source, "BUILD_MIN_FREE_MB", 2048)?;
+        let build_max_concurrent = parse_optional_env(source, "BUILD_MAX_CONCURRENT", 2)?;
+        let clone_timeout_seconds = parse_optional_env(source, "CLONE_TIMEOUT_SECONDS", 300)?;
+        let clone_stall_timeout_seconds = parse_optional_env(source, "CLONE_STALL_TIMEOUT_SECONDS", 30)?;
+
+        let maintenance_page_image = parse_optional_env(source, "MAINTENANCE_PAGE_IMAGE", "hangar-maintenance-page:latest".to_string())?;
+        let image_validation_max_concurrent = parse_optional_env(source, "IMAGE_VALIDATION_MAX_CONCURRENT", 2)?;
+        let image_update_check_interval_seconds = parse_optional_env(source, "IMAGE_UPDATE_CHECK_INTERVAL_SECONDS", 60)?;
+        let default_language = parse_optional_env(source, "DEFAULT_LANGUAGE", Language::Fr)?;
+
+        // Le secret précédent reste valide pendant une rotation, le temps de mettre à jour GitHub.
+        let github_webhook_secrets = ["GITHUB_WEBHOOK_SECRET", "GITHUB_WEBHOOK_SECRET_PREVIOUS"]
+            .iter()
+            .filter_map(|name| source.var(name).ok())
+            .filter(|secret| !secret.is_empty())
+            .collect::<Vec<String>>();
+        let webhook_max_payload_bytes = parse_optional_env(source, "WEBHOOK_MAX_PAYLOAD_BYTES", 1024 * 1024)?;
+        let webhook_replay_window_seconds = parse_optional_env(source, "WEBHOOK_REPLAY_WINDOW_SECONDS", 24 * 3600)?;
+
+        let cosign_enabled = parse_optional_env(source, "COSIGN_ENABLED", false)?;
+        let cosign_path = parse_optional_env(source, "COSIGN_PATH", "cosign".to_string())?;
+        let cosign_public_keys = parse_list_env(source, "COSIGN_PUBLIC_KEYS");
+        let cosign_required_registries = parse_list_env(source, "COSIGN_REQUIRED_REGISTRIES").into_iter().collect::<HashSet<String>>();
+        if cosign_enabled && !cosign_required_registries.is_empty() && cosign_public_keys.is_empty()
+        {
+            return Err(ConfigError::Missing("COSIGN_PUBLIC_KEYS".to_string()));
+        }
 
-        let container_memory_mb = std::env::var("DOCKER_CONTAINER_MEMORY_MB")
+        let forbidden_env_vars = parse_env_var_patterns(source, "FORBIDDEN_ENV_VARS")?;
+        let forbidden_env_prefixes = parse_env_var_patterns(source, "FORBIDDEN_ENV_PREFIXES")?;
+
+        let env_vars_max_count: usize = parse_optional_env(source, "ENV_VARS_MAX_COUNT", 200)?;
+        let env_var_max_value_kb: usize = parse_optional_env(source, "ENV_VAR_MAX_VALUE_KB", 32)?;
+        let env_vars_max_total_kb: usize = parse_optional_env(source, "ENV_VARS_MAX_TOTAL_KB", 256)?;
+        if env_vars_max_count == 0
+        {
+            return Err(ConfigError::Invalid("ENV_VARS_MAX_COUNT".to_string(), env_vars_max_count.to_string()));
+        }
+        if env_var_max_value_kb == 0 || env_var_max_value_kb > env_vars_max_total_kb
+        {
+            return Err(ConfigError::Invalid("ENV_VAR_MAX_VALUE_KB".to_string(), env_var_max_value_kb.to_string()));
+        }
+        let env_var_max_value_bytes = env_var_max_value_kb * 1024;
+        let env_vars_max_total_bytes = env_vars_max_total_kb * 1024;
+
+        let db_template_max_kb: usize = parse_optional_env(source, "DB_TEMPLATE_MAX_KB", 512)?;
+        let db_template_max_bytes = db_template_max_kb * 1024;
+
+        let container_memory_mb = source.var("DOCKER_CONTAINER_MEMORY_MB")
             .map_err(|_| ConfigError::Missing("DOCKER_CONTAINER_MEMORY_MB".to_string()))?
             .parse().map_err(|_| ConfigError::Invalid("DOCKER_CONTAINER_MEMORY_MB".to_string(), "Invalid number".to_string()))?;
 
-        let container_cpu_quota = std::env::var("DOCKER_CONTAINER_CPU_QUOTA")
+        let container_cpu_quota = source.var("DOCKER_CONTAINER_CPU_QUOTA")
             .map_err(|_| ConfigError::Missing("DOCKER_CONTAINER_CPU_QUOTA".to_string()))?
             .parse().map_err(|_| ConfigError::Invalid("DOCKER_CONTAINER_CPU_QUOTA".to_string(), "Invalid number".to_string()))?;
 
-        let db_max_connections = std::env::var("DB_MAX_CONNECTIONS")
+        let db_max_connections = source.var("DB_MAX_CONNECTIONS")
             .map_err(|_| ConfigError::Missing("DB_MAX_CONNECTIONS".to_string()))?
             .parse().map_err(|_| ConfigError::Invalid("DB_MAX_CONNECTIONS".to_string(), "Invalid number".to_string()))?;
 
-        let timeout_normal = std::env::var("TIMEOUT_SECONDS_NORMAL")
-            .map_err(|_| ConfigError::Missing("TIMEOUT_SECONDS_NORMAL".to_string()))?
-            .parse().map_err(|_| ConfigError::Invalid("TIMEOUT_SECONDS_NORMAL".to_string(), "Invalid number".to_string()))?;
+        let db_acquire_timeout_seconds: u64 = parse_optional_env(source, "DB_ACQUIRE_TIMEOUT_SECONDS", 10)?;
+        if db_acquire_timeout_seconds == 0
+        {
+            return Err(ConfigError::Invalid("DB_ACQUIRE_TIMEOUT_SECONDS".to_string(), "0".to_string()));
+        }
+        let db_max_lifetime_seconds = parse_optional_env(source, "DB_MAX_LIFETIME_SECONDS", 30 * 60)?;
+        let db_idle_timeout_seconds = parse_optional_env(source, "DB_IDLE_TIMEOUT_SECONDS", 10 * 60)?;
+
+        // Sans limite, une seule application peut épuiser les connexions du serveur MariaDB partagé.
+        let mariadb_user_limits = UserResourceLimits
+        {
+            max_user_connections: parse_optional_env(source, "MARIADB_MAX_USER_CONNECTIONS", 20)?,
+            max_queries_per_hour: parse_optional_env(source, "MARIADB_MAX_QUERIES_PER_HOUR", 0)?,
+            max_updates_per_hour: parse_optional_env(source, "MARIADB_MAX_UPDATES_PER_HOUR", 0)?,
+            max_connections_per_hour: parse_optional_env(source, "MARIADB_MAX_CONNECTIONS_PER_HOUR", 0)?,
+        };
+        if let Some((field, value)) = mariadb_user_limits.first_invalid()
+        {
+            return Err(ConfigError::Invalid(format!("MARIADB_{}", field.to_uppercase()), value.to_string()));
+        }
+
+        // TIMEOUT_SECONDS_NORMAL / TIMEOUT_SECONDS_LONG restent acceptés pour les anciens déploiements
+        let timeouts = HashMap::from([
+            (RouteClass::Short, parse_timeout(source, "TIMEOUTS_SHORT", Some("TIMEOUT_SECONDS_NORMAL"))?),
+            (RouteClass::Long, parse_timeout(source, "TIMEOUTS_LONG", Some("TIMEOUT_SECONDS_LONG"))?),
+            (RouteClass::Streaming, parse_optional_env(source, "TIMEOUTS_STREAMING_IDLE", 60)?),
+        ]);
+
+        let http_connect_timeout = parse_optional_env(source, "HTTP_CONNECT_TIMEOUT_SECONDS", 5)?;
+        let http_request_timeout = parse_optional_env(source, "HTTP_REQUEST_TIMEOUT_SECONDS", 15)?;
+
+        let quota_default_projects = parse_optional_env(source, "QUOTA_DEFAULT_PROJECTS", 1)?;
+        let quota_default_databases = parse_optional_env(source, "QUOTA_DEFAULT_DATABASES", 1)?;
+        let quota_default_team_projects = parse_optional_env(source, "QUOTA_DEFAULT_TEAM_PROJECTS", 3)?;
+
+        let max_participants_per_project = parse_optional_env(source, "MAX_PARTICIPANTS_PER_PROJECT", 10)?;
+        if max_participants_per_project < 0
+        {
+            return Err(ConfigError::Invalid("MAX_PARTICIPANTS_PER_PROJECT".to_string(), max_participants_per_project.to_string()));
+        }
+
+        let invitation_expiry_days = parse_optional_env(source, "INVITATION_EXPIRY_DAYS", 14)?;
+        if invitation_expiry_days < 1
+        {
+            return Err(ConfigError::Invalid("INVITATION_EXPIRY_DAYS".to_string(), invitation_expiry_days.to_string()));
+        }
+        let legacy_direct_participants = parse_optional_env(source, "LEGACY_DIRECT_PARTICIPANTS", true)?;
+
+        let auth_max_failed_per_minute = parse_optional_env(source, "AUTH_MAX_FAILED_PER_MINUTE", 10)?;
+        let db_reveal_max_auth_age_seconds = parse_optional_env(source, "DB_REVEAL_MAX_AUTH_AGE_SECONDS", 300)?;
+
+        let metrics_cache_fresh_seconds = parse_optional_env(source, "METRICS_CACHE_FRESH_SECONDS", 10)?;
+        if metrics_cache_fresh_seconds < 0
+        {
+            return Err(ConfigError::Invalid("METRICS_CACHE_FRESH_SECONDS".to_string(), metrics_cache_fresh_seconds.to_string()));
+        }
+
+        let broadcast_max_concurrent = parse_optional_env(source, "BROADCAST_MAX_CONCURRENT", 4)?;
+        let broadcast_container_timeout_seconds = parse_optional_env(source, "BROADCAST_CONTAINER_TIMEOUT_SECONDS", 60)?;
+
+        let volume_quota_default_mb = parse_optional_env(source, "VOLUME_QUOTA_DEFAULT_MB", 1024)?;
+        if volume_quota_default_mb < 1
+        {
+            return Err(ConfigError::Invalid("VOLUME_QUOTA_DEFAULT_MB".to_string(), volume_quota_default_mb.to_string()));
+        }
+        let volume_quota_policy = parse_optional_env(source, "VOLUME_QUOTA_POLICY", VolumeQuotaPolicy::Warn)?;
+        let volume_quota_grace_period_seconds = parse_optional_env(source, "VOLUME_QUOTA_GRACE_PERIOD_SECONDS", 24 * 60 * 60)?;
+        let volume_quota_check_interval_seconds: u64 = parse_optional_env(source, "VOLUME_QUOTA_CHECK_INTERVAL_SECONDS", 15 * 60)?;
+        if volume_quota_check_interval_seconds == 0
+        {
+            return Err(ConfigError::Invalid("VOLUME_QUOTA_CHECK_INTERVAL_SECONDS".to_string(), "0".to_string()));
+        }
+
+        let cleanup_give_up_days = parse_optional_env(source, "CLEANUP_GIVE_UP_DAYS", 7)?;
+        if cleanup_give_up_days < 1
+        {
+            return Err(ConfigError::Invalid("CLEANUP_GIVE_UP_DAYS".to_string(), cleanup_give_up_days.to_string()));
+        }
+
+        let project_deletion_grace_hours = parse_optional_env(source, "PROJECT_DELETION_GRACE_HOURS", 72)?;
+        if project_deletion_grace_hours < 0
+        {
+            return Err(ConfigError::Invalid("PROJECT_DELETION_GRACE_HOURS".to_string(), project_deletion_grace_hours.to_string()));
+        }
+
+        let status_sync_interval_seconds: u64 = parse_optional_env(source, "STATUS_SYNC_INTERVAL_SECONDS", 60)?;
+        if status_sync_interval_seconds == 0
+        {
+            return Err(ConfigError::Invalid("STATUS_SYNC_INTERVAL_SECONDS".to_string(), "0".to_string()));
+        }
+        let status_drift_threshold_seconds = parse_optional_env(source, "STATUS_DRIFT_THRESHOLD_SECONDS", 10 * 60)?;
+
+        let schedule_utc_offset_hours: i8 = parse_optional_env(source, "SCHEDULE_UTC_OFFSET_HOURS", 0)?;
+        if !(-12..=14).contains(&schedule_utc_offset_hours)
+        {
+            return Err(ConfigError::Invalid("SCHEDULE_UTC_OFFSET_HOURS".to_string(), schedule_utc_offset_hours.to_string()));
+        }
+
+        let availability_retention_days = parse_optional_env(source, "AVAILABILITY_RETENTION_DAYS", 35)?;
+        if availability_retention_days < 1
+        {
+            return Err(ConfigError::Invalid("AVAILABILITY_RETENTION_DAYS".to_string(), availability_retention_days.to_string()));
+        }
 
-        let timeout_long = std::env::var("TIMEOUT_SECONDS_LONG")
-            .map_err(|_| ConfigError::Missing("TIMEOUT_SECONDS_LONG".to_string()))?
-            .parse().map_err(|_| ConfigError::Invalid("TIMEOUT_SECONDS_LONG".to_string(), "Invalid number".to_string()))?;
+        let strict_startup = parse_optional_env(source, "STRICT_STARTUP", false)?;
 
-        let admin_logins = std::env::var("APP_ADMINS")
+        let cors_dev_allow_any_origin = parse_optional_env(source, "CORS_DEV_ALLOW_ANY_ORIGIN", false)?;
+        if cors_dev_allow_any_origin && strict_startup
+        {
+            // Un déploiement strict est un déploiement de production : jamais d'origine quelconque.
+            return Err(ConfigError::Invalid("CORS_DEV_ALLOW_ANY_ORIGIN".to_string(), "true".to_string()));
+        }
+        let cors_allowed_origins = parse_cors_origins(source, "CORS_ALLOWED_ORIGINS")?;
+        if cors_allowed_origins.is_empty() && !cors_dev_allow_any_origin
+        {
+            // Le front ne pourrait pas appeler l'API : mieux vaut refuser de démarrer que de tout ouvrir.
+            return Err(ConfigError::Missing("CORS_ALLOWED_ORIGINS".to_string()));
+        }
+
+        let admin_logins = source.var("APP_ADMINS")
             .map_err(|_| ConfigError::Missing("APP_ADMINS".to_string()))?
             .split(',')
             .map(|s| s.trim().to_string())
             .filter(|s| !s.is_empty())
             .collect::<HashSet<String>>();
 
-        let encryption_key_hex = std::env::var("APP_ENCRYPTION_KEY")
+        let encryption_key_hex = source.var("APP_ENCRYPTION_KEY")
             .map_err(|_| ConfigError::Missing("APP_ENCRYPTION_KEY".to_string()))?;
+        let current_encryption_key = parse_encryption_key("APP_ENCRYPTION_KEY", &encryption_key_hex)?;
 
-        let encryption_key: Vec<u8> = (0..encryption_key_hex.len())
-                                        .step_by(2)
-                                        .map(|i| u8::from_str_radix(&encryption_key_hex[i..i + 2], 16))
-                                        .collect::<Result<_, _>>()
-                                        .map_err(|_| ConfigError::Invalid(
-                                            "APP_ENCRYPTION_KEY".to_string(), 
-                                            "Invalid hex format".to_string()
-                                        ))?;
-
-        if encryption_key.len() != 32
-        {
-            return Err(ConfigError::Invalid("APP_ENCRYPTION_KEY".to_string(), "Key must be 32 bytes (64 hex characters)".to_string()));
-        }
+        let previous_encryption_keys = parse_list_env(source, "PREVIOUS_ENCRYPTION_KEYS")
+            .iter()
+            .map(|hex| parse_encryption_key("PREVIOUS_ENCRYPTION_KEYS", hex))
+            .collect::<Result<Vec<_>, _>>()?;
+        let encryption_algorithm = parse_optional_env(source, "ENCRYPTION_ALGORITHM", EncryptionAlgorithm::default())?;
+        let encryption_key = EncryptionKeys::new(current_encryption_key, previous_encryption_keys, encryption_algorithm);
 
 
         Ok(Config 
@@ -168,20 +699,409 @@ impl Config
             app_prefix,
             app_domain_suffix,
             build_base_image,
+            build_cache_enabled,
+            build_cache_max_age_days,
+            build_cache_max_size_mb,
+            build_tmp_dir,
+            build_min_free_mb,
+            build_max_concurrent,
+            clone_timeout_seconds,
+            clone_stall_timeout_seconds,
             github_app_id,
             github_private_key,
             docker_network,
+            docker_hosts,
+            docker_api_timeout_seconds,
+            egress_internal_network,
+            edge_hostname,
             traefik_entrypoint,
             traefik_cert_resolver,
+            traefik_tls_enabled,
+            rate_limit_min_rps,
+            rate_limit_max_rps,
+            rate_limit_owner_max_rps,
+            replicas_max,
+            replicas_owner_max,
             container_memory_mb,
             container_cpu_quota,
             grype_enabled,
             grype_fail_on_severity,
+            scan_timeout_seconds,
+            syft_enabled,
+            syft_path,
+            maintenance_page_image,
+            image_validation_max_concurrent,
+            image_update_check_interval_seconds,
+            default_language,
+            github_webhook_secrets,
+            webhook_max_payload_bytes,
+            webhook_replay_window_seconds,
+            cosign_enabled,
+            cosign_path,
+            cosign_public_keys,
+            cosign_required_registries,
+            forbidden_env_vars,
+            forbidden_env_prefixes,
+            env_vars_max_count,
+            env_var_max_value_bytes,
+            env_vars_max_total_bytes,
+            db_template_max_bytes,
             db_max_connections,
-            timeout_normal,
-            timeout_long,
+            db_acquire_timeout_seconds,
+            db_max_lifetime_seconds,
+            db_idle_timeout_seconds,
+            mariadb_user_limits,
+            timeouts,
+            http_connect_timeout,
+            http_request_timeout,
+            quota_default_projects,
+            quota_default_databases,
+            quota_default_team_projects,
+            max_participants_per_project,
+            invitation_expiry_days,
+            legacy_direct_participants,
+            auth_max_failed_per_minute,
+            db_reveal_max_auth_age_seconds,
+            metrics_cache_fresh_seconds,
+            broadcast_max_concurrent,
+            broadcast_container_timeout_seconds,
+            volume_quota_default_mb,
+            volume_quota_policy,
+            volume_quota_grace_period_seconds,
+            volume_quota_check_interval_seconds,
+            cleanup_give_up_days,
+            project_deletion_grace_hours,
+            status_sync_interval_seconds,
+            status_drift_threshold_seconds,
+            schedule_utc_offset_hours,
+            availability_retention_days,
+            strict_startup,
+            cors_allowed_origins,
+            cors_dev_allow_any_origin,
             admin_logins,
             encryption_key
         })
     }
-}
\ No newline at end of file
+
+    pub fn with_reloaded(&self, reloaded: &Config) -> (Config, Vec<ConfigChange>)
+    {
+        let mut next = self.clone();
+        let mut changes = Vec::new();
+
+        macro_rules! reload
+        {
+            ($($field:ident),* $(,)?) =>
+            {
+                $(
+                    if self.$field != reloaded.$field
+                    {
+                        changes.push(ConfigChange
+                        {
+                            setting: stringify!($field),
+                            previous: serde_json::json!(self.$field),
+                            current: serde_json::json!(reloaded.$field),
+                        });
+                        next.$field = reloaded.$field;
+                    }
+                )*
+            };
+        }
+
+        reload!(
+            grype_fail_on_severity,
+            scan_timeout_seconds,
+            container_memory_mb,
+            container_cpu_quota,
+            rate_limit_min_rps,
+            rate_limit_max_rps,
+            rate_limit_owner_max_rps,
+            replicas_max,
+            replicas_owner_max,
+            env_vars_max_count,
+            env_var_max_value_bytes,
+            env_vars_max_total_bytes,
+            db_template_max_bytes,
+            webhook_max_payload_bytes,
+            quota_default_projects,
+            quota_default_databases,
+            quota_default_team_projects,
+            max_participants_per_project,
+            invitation_expiry_days,
+            auth_max_failed_per_minute,
+            mariadb_user_limits,
+            clone_timeout_seconds,
+            build_min_free_mb,
+            broadcast_container_timeout_seconds,
+            volume_quota_default_mb,
+            volume_quota_grace_period_seconds,
+            status_drift_threshold_seconds,
+        );
+
+        if self.timeouts != reloaded.timeouts
+        {
+            changes.push(ConfigChange
+            {
+                setting: "timeouts",
+                previous: serde_json::json!(self.timeouts),
+                current: serde_json::json!(reloaded.timeouts),
+            });
+            next.timeouts = reloaded.timeouts.clone();
+        }
+
+        (next, changes)
+    }
+
+    pub fn summary(&self) -> ConfigSummary
+    {
+        let mut cosign_required_registries: Vec<String> = self.cosign_required_registries.iter().cloned().collect();
+        cosign_required_registries.sort();
+
+        ConfigSummary
+        {
+            public_address: self.public_address.clone(),
+            app_prefix: self.app_prefix.clone(),
+            app_domain_suffix: self.app_domain_suffix.clone(),
+            docker_network: self.docker_network.clone(),
+            docker_hosts: self.docker_hosts.iter().map(|host| host.name.clone()).collect(),
+            docker_api_timeout_seconds: self.docker_api_timeout_seconds,
+            route_timeout_short_seconds: self.timeout_for(RouteClass::Short).as_secs(),
+            route_timeout_long_seconds: self.timeout_for(RouteClass::Long).as_secs(),
+            route_timeout_streaming_idle_seconds: self.timeout_for(RouteClass::Streaming).as_secs(),
+            egress_internal_network: self.egress_internal_network.clone(),
+            edge_hostname: self.edge_hostname.clone(),
+            traefik_entrypoint: self.traefik_entrypoint.clone(),
+            traefik_tls_enabled: self.traefik_tls_enabled,
+            mariadb_public_host: self.mariadb_public_host.clone(),
+            mariadb_public_port: self.mariadb_public_port,
+            build_base_image: self.build_base_image.clone(),
+            build_cache_enabled: self.build_cache_enabled,
+            build_max_concurrent: self.build_max_concurrent,
+            scanner: if self.grype_enabled { "grype" } else { "disabled" },
+            scan_fail_on_severity: self.grype_fail_on_severity,
+            sbom_enabled: self.syft_enabled,
+            cosign_enabled: self.cosign_enabled,
+            cosign_required_registries,
+            replicas_max: self.replicas_max,
+            container_memory_mb: self.container_memory_mb,
+            container_cpu_quota: self.container_cpu_quota,
+            quota_default_projects: self.quota_default_projects,
+            quota_default_databases: self.quota_default_databases,
+            quota_default_team_projects: self.quota_default_team_projects,
+            max_participants_per_project: self.max_participants_per_project,
+            env_vars_max_count: self.env_vars_max_count,
+            env_var_max_value_bytes: self.env_var_max_value_bytes,
+            env_vars_max_total_bytes: self.env_vars_max_total_bytes,
+            volume_quota_default_mb: self.volume_quota_default_mb,
+            volume_quota_policy: self.volume_quota_policy,
+            project_deletion_grace_hours: self.project_deletion_grace_hours,
+            status_sync_interval_seconds: self.status_sync_interval_seconds,
+            status_drift_threshold_seconds: self.status_drift_threshold_seconds,
+            schedule_utc_offset_hours: self.schedule_utc_offset_hours,
+            availability_retention_days: self.availability_retention_days,
+            strict_startup: self.strict_startup,
+            cors_allowed_origins: self.cors_allowed_origins.clone(),
+            cors_dev_allow_any_origin: self.cors_dev_allow_any_origin,
+            mariadb_user_limits: self.mariadb_user_limits,
+            db_max_connections: self.db_max_connections,
+            encryption_algorithm: self.encryption_key.algorithm().as_str(),
+            previous_encryption_keys: self.encryption_key.previous_count(),
+            default_language: match self.default_language { Language::Fr => "fr", Language::En => "en" },
+        }
+    }
+
+    pub fn docker_host(&self, name: &str) -> Option<&DockerHostConfig>
+    {
+        self.docker_hosts.iter().find(|host| host.name == name)
+    }
+
+    pub fn timeout_for(&self, class: RouteClass) -> Duration
+    {
+        Duration::from_secs(self.timeouts.get(&class).copied().unwrap_or(30))
+    }
+
+    pub fn project_hostname(&self, project_name: &str) -> String
+    {
+        format!("{}.{}", project_name, self.app_domain_suffix)
+    }
+
+    pub fn custom_domain_cname_target(&self, project_name: &str) -> String
+    {
+        self.edge_hostname.clone().unwrap_or_else(|| self.project_hostname(project_name))
+    }
+
+    pub fn project_public_url(&self, project_name: &str) -> String
+    {
+        let scheme = if self.traefik_tls_enabled { "https" } else { "http" };
+        format!("{}://{}", scheme, self.project_hostname(project_name))
+    }
+}
+
+fn parse_timeout(source: &ConfigSource, name: &str, legacy_name: Option<&str>) -> Result<u64, ConfigError>
+{
+    let (name, raw) = match (source.var(name), legacy_name)
+    {
+        (Ok(raw), _) => (name, raw),
+        (Err(_), Some(legacy)) => (legacy, source.var(legacy).map_err(|_| ConfigError::Missing(name.to_string()))?),
+        (Err(_), None) => return Err(ConfigError::Missing(name.to_string())),
+    };
+
+    raw.parse().map_err(|_| ConfigError::Invalid(name.to_string(), "Invalid number".to_string()))
+}
+
+fn parse_optional_env<T: std::str::FromStr>(source: &ConfigSource, name: &str, default: T) -> Result<T, ConfigError>
+{
+    match source.var(name)
+    {
+        Ok(raw) => raw.parse::<T>().map_err(|_| ConfigError::Invalid(name.to_string(), raw)),
+        Err(_) => Ok(default),
+    }
+}
+
+fn parse_encryption_key(name: &str, value: &str) -> Result<Vec<u8>, ConfigError>
+{
+    let value = value.trim();
+
+    let key = if value.len() == 2 * ENCRYPTION_KEY_LENGTH && value.bytes().all(|b| b.is_ascii_hexdigit())
+    {
+        (0..value.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&value[i..i + 2], 16))
+            .collect::<Result<Vec<u8>, _>>()
+            .map_err(|_| ConfigError::Invalid(name.to_string(), "Invalid hex format".to_string()))?
+    }
+    else
+    {
+        BASE64_STANDARD.decode(value).map_err(|_| ConfigError::Invalid(
+            name.to_string(),
+            format!("Key must be {} bytes, written as {} hex characters or in base64", ENCRYPTION_KEY_LENGTH, 2 * ENCRYPTION_KEY_LENGTH)
+        ))?
+    };
+
+    if key.len() != ENCRYPTION_KEY_LENGTH
+    {
+        return Err(ConfigError::Invalid(
+            name.to_string(),
+            format!("Key must be {} bytes, got {}", ENCRYPTION_KEY_LENGTH, key.len())
+        ));
+    }
+
+    Ok(key)
+}
+
+fn parse_list_env(source: &ConfigSource, name: &str) -> Vec<String>
+{
+    source.var(name)
+        .unwrap_or_default()
+        .split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+fn parse_env_var_patterns(source: &ConfigSource, name: &str) -> Result<Vec<String>, ConfigError>
+{
+    let patterns = parse_list_env(source, name);
+    if let Some(invalid) = patterns.iter().find(|pattern| !pattern.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '*'))
+    {
+        return Err(ConfigError::Invalid(name.to_string(), invalid.clone()));
+    }
+    Ok(patterns)
+}
+
+// Origines telles que les envoient les navigateurs : sans chemin, sans barre finale, sans port par défaut.
+fn parse_cors_origins(source: &ConfigSource, name: &str) -> Result<Vec<String>, ConfigError>
+{
+    let origins = parse_list_env(source, name);
+    for origin in &origins
+    {
+        let serialized = reqwest::Url::parse(origin).ok().map(|url| url.origin().ascii_serialization());
+        if serialized.as_deref() != Some(origin.as_str())
+        {
+            return Err(ConfigError::Invalid(name.to_string(), origin.clone()));
+        }
+    }
+    Ok(origins)
+}
+
+fn parse_docker_hosts(raw: &str) -> Result<Vec<DockerHostConfig>, ConfigError>
+{
+    let mut hosts: Vec<DockerHostConfig> = Vec::new();
+
+    for entry in raw.split(',').map(str::trim).filter(|s| !s.is_empty())
+    {
+        let (name, url) = entry.split_once('=')
+            .ok_or_else(|| ConfigError::Invalid("DOCKER_HOSTS".to_string(), entry.to_string()))?;
+
+        let name = name.trim().to_string();
+        let url = url.trim().to_string();
+
+        if name.is_empty() || url.is_empty() || hosts.iter().any(|h| h.name == name)
+        {
+            return Err(ConfigError::Invalid("DOCKER_HOSTS".to_string(), entry.to_string()));
+        }
+        validate_docker_url("DOCKER_HOSTS", &url)?;
+
+        hosts.push(DockerHostConfig { name, url: Some(url), tls_cert_dir: None });
+    }
+
+    if hosts.is_empty()
+    {
+        return Err(ConfigError::Invalid("DOCKER_HOSTS".to_string(), raw.to_string()));
+    }
+
+    Ok(hosts)
+}
+
+fn validate_docker_url(name: &str, url: &str) -> Result<(), ConfigError>
+{
+    if ["unix://", "tcp://", "http://", "https://"].iter().any(|scheme| url.starts_with(scheme))
+    {
+        Ok(())
+    }
+    else
+    {
+        Err(ConfigError::Invalid(name.to_string(), url.to_string()))
+    }
+}
+
+// Un sous-dossier par hôte permet à chaque démon d'avoir son propre CA.
+fn docker_tls_cert_dir_for(dir: Option<&Path>, host_name: &str) -> Result<PathBuf, ConfigError>
+{
+    let dir = dir.ok_or_else(|| ConfigError::Missing("DOCKER_TLS_CERT_DIR".to_string()))?;
+
+    let host_dir = dir.join(host_name);
+    let cert_dir = if host_dir.is_dir() { host_dir } else { dir.to_path_buf() };
+
+    // Sinon un fichier manquant n'apparaîtrait qu'à la première requête vers le démon.
+    if let Some(missing) = DOCKER_TLS_FILES.iter().map(|file| cert_dir.join(file)).find(|path| !path.is_file())
+    {
+        return Err(ConfigError::Invalid("DOCKER_TLS_CERT_DIR".to_string(), format!("{} not found", missing.display())));
+    }
+
+    Ok(cert_dir)
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn route_timeouts_are_reloaded_but_not_the_docker_api_one()
+    {
+        let booted = Config::for_tests(&[]).unwrap();
+        let reloaded = Config::for_tests(&[("DOCKER_API_TIMEOUT", "5"), ("TIMEOUTS_SHORT", "1"), ("SCAN_TIMEOUT_SECONDS", "42")]).unwrap();
+
+        let (next, changes) = booted.with_reloaded(&reloaded);
+        let changed: Vec<_> = changes.iter().map(|change| change.setting).collect();
+
+        assert_eq!(changed, ["scan_timeout_seconds", "timeouts"]);
+        assert_eq!(next.timeout_for(RouteClass::Short), Duration::from_secs(1));
+        assert_eq!(next.docker_api_timeout_seconds, booted.docker_api_timeout_seconds);
+
+        // Seul le délai de l'API Docker reste à l'écart : la réponse du rechargement demande un redémarrage.
+        let wanted = reloaded.summary();
+        assert_eq!(wanted.route_timeout_short_seconds, next.summary().route_timeout_short_seconds);
+        assert_ne!(wanted.docker_api_timeout_seconds, next.summary().docker_api_timeout_seconds);
+    }
+}
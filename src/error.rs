@@ -4,6 +4,8 @@ use serde_json::json;
 use thiserror::Error;
 use tracing::{error, trace};
 
+use crate::{i18n::Language, model::scan::ScanReport};
+
 #[derive(Debug, Error)]
 pub enum AppError
 {
@@ -17,7 +19,19 @@ pub enum AppError
     Unauthorized(String),
 
     #[error("Error occurred while calling external service")]
-    ExternalServiceError(#[from] reqwest::Error),
+    ExternalServiceError(#[source] reqwest::Error),
+
+    #[error("{service} did not respond in time")]
+    UpstreamTimeout { service: &'static str },
+
+    #[error("{service} is unavailable")]
+    UpstreamUnavailable { service: &'static str },
+
+    #[error("{service} rejected the platform's credentials")]
+    UpstreamAuthFailure { service: &'static str },
+
+    #[error("Database connection pool exhausted")]
+    DatabaseBusy,
 
     #[error("Error parsing response")]
     ParsingError(#[from] quick_xml::DeError),
@@ -25,6 +39,18 @@ pub enum AppError
     #[error("Bad Request: {0}")]
     BadRequest(String),
 
+    #[error("Invalid payload: {message}")]
+    InvalidPayload { field: Option<String>, message: String },
+
+    #[error("Unsupported Media Type: {0}")]
+    UnsupportedMediaType(String),
+
+    #[error("Payload Too Large: {0}")]
+    PayloadTooLarge(String),
+
+    #[error("Too Many Requests: {0}")]
+    TooManyRequests(String),
+
     #[error("Project operation failed: {0}")]
     ProjectError(#[from] ProjectErrorCode),
 
@@ -48,7 +74,7 @@ pub enum ProjectErrorCode
 {
     #[error("This project name is already taken.")]
     ProjectNameTaken,
-    #[error("You already own a project. Only one is allowed per user.")]
+    #[error("You have reached your project quota.")]
     OwnerAlreadyExists,
     #[error("The project owner cannot be added as a participant.")]
     OwnerCannotBeParticipant,
@@ -57,9 +83,15 @@ pub enum ProjectErrorCode
     #[error("The provided Docker image URL is invalid or contains forbidden characters.")]
     InvalidImageUrl,
     #[error("Failed to pull the Docker image. Please check the URL and registry access.")]
-    ImagePullFailed,
+    ImagePullFailed(Vec<String>),
     #[error("Security scan failed: vulnerabilities were found in the image.")]
-    ImageScanFailed(String),
+    ImageScanFailed(ScanReport),
+    #[error("The security scan did not finish within {0} seconds.")]
+    ImageScanTimedOut(u64),
+    #[error("The image signature could not be verified. Only signed images can be deployed from this registry.")]
+    ImageSignatureInvalid(String),
+    #[error("The image is built for {0}, but the server runs {1}. Please push an image for {1}.")]
+    UnsupportedImageArchitecture(String, String),
     #[error("Failed to create the project container.")]
     ContainerCreationFailed,
     #[error("Failed to delete the project.")]
@@ -72,21 +104,61 @@ pub enum ProjectErrorCode
     GithubRepoNotAccessible,
     #[error("Images from ghcr.io must be public for direct deployment.")]
     GithubPackageNotPublic, 
-    #[error("Usage of the environment variable '{0}' is forbidden.")]
-    ForbiddenEnvVar(String), 
+    #[error("Usage of the environment variable(s) '{}' is forbidden.", .0.join("', '"))]
+    ForbiddenEnvVar(Vec<String>),
+    #[error("Environment variables starting with 'HANGAR_' are provided by the platform and cannot be set: '{}'.", .0.join("', '"))]
+    ReservedEnvVar(Vec<String>),
+    #[error("The value of the environment variable(s) '{}' contains control characters.", .0.join("', '"))]
+    InvalidEnvVarValue(Vec<String>),
+    #[error("Environment variable names must start with a letter or '_' and contain only letters, digits and '_': '{}'.", .0.join("', '"))]
+    InvalidEnvVarName(Vec<String>),
+    #[error("The value of the environment variable(s) '{}' is larger than {} bytes.", .0.join("', '"), .1)]
+    EnvVarValueTooLarge(Vec<String>, usize),
+    #[error("Unknown placeholder(s) in environment variable values: '{}'. Write '$${{' for a literal '${{'.", .0.join("', '"))]
+    UnknownEnvPlaceholder(Vec<String>),
+    #[error("A project can have at most {0} environment variables.")]
+    TooManyEnvVars(usize),
+    #[error("The environment variables of a project cannot exceed {0} bytes in total.")]
+    EnvVarsTooLarge(usize),
     #[error("The specified persistent volume path is invalid.")]
     InvalidVolumePath,
     #[error("A database operation failed during project creation.")]
     ProjectCreationFailedWithDatabaseError,
     #[error("The specified source root directory is invalid.")]
     InvalidSourceRootDir,
+    #[error("A deployment is already in progress for this user.")]
+    DeploymentAlreadyInProgress,
+    #[error("The deployment was interrupted by a server restart. Please try again.")]
+    InterruptedByRestart,
+    #[error("An export is already in progress for this user. Please wait for it to finish.")]
+    ExportAlreadyInProgress,
+    #[error("The environment variables changed since the diff was previewed. Please review the changes again.")]
+    EnvVarsChangedSincePreview,
+    #[error("Running more than one replica requires the persistent volume to be marked as safe to share between containers.")]
+    VolumeNotShareable,
+    #[error("The build server is low on disk space ({0} MB free, {1} MB required). Please try again later.")]
+    InsufficientBuildSpace(u64, u64),
+    #[error("The repository could not be cloned within {0} seconds: the Git server is too slow or stopped responding.")]
+    CloneTimedOut(u64),
+    #[error("The owner of this project restricted its logs to the owners.")]
+    LogsRestricted,
+    #[error("The new image must come from the project's repository '{0}'. Set 'allow_repository_change' to deploy another repository.")]
+    ImageRepositoryChanged(String),
+    #[error("A project can have at most {0} participants, pending invitations included.")]
+    TooManyParticipants(i64),
+    #[error("The domain '{0}' is already used by another project.")]
+    CustomDomainTaken(String),
+    #[error("This project is archived. Unarchive it before starting it.")]
+    ProjectArchived,
+    #[error("Configuration documents of version {0} are not supported; this instance reads version {1}.")]
+    UnsupportedConfigVersion(u64, u32),
 }
 
 #[derive(Debug, Error, Serialize, PartialEq)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum DatabaseErrorCode
 {
-    #[error("You already own a database. Only one is allowed per user.")]
+    #[error("You have reached your database quota.")]
     DatabaseAlreadyExists,
     #[error("Failed to provision the database.")]
     ProvisioningFailed,
@@ -94,12 +166,14 @@ pub enum DatabaseErrorCode
     DeprovisioningFailed,
     #[error("Database not found.")]
     NotFound,
+    #[error("Failed to apply the database template.")]
+    TemplateFailed,
 }
 
 
 impl ProjectErrorCode 
 {
-    fn as_str(&self) -> &'static str 
+    pub fn as_str(&self) -> &'static str 
     {
         match self 
         {
@@ -108,25 +182,186 @@ impl ProjectErrorCode
             ProjectErrorCode::OwnerCannotBeParticipant => "OWNER_CANNOT_BE_PARTICIPANT",
             ProjectErrorCode::InvalidProjectName => "INVALID_PROJECT_NAME",
             ProjectErrorCode::InvalidImageUrl => "INVALID_IMAGE_URL",
-            ProjectErrorCode::ImagePullFailed => "IMAGE_PULL_FAILED",
+            ProjectErrorCode::ImagePullFailed(_) => "IMAGE_PULL_FAILED",
             ProjectErrorCode::ImageScanFailed(_) => "IMAGE_SCAN_FAILED",
+            ProjectErrorCode::ImageScanTimedOut(_) => "IMAGE_SCAN_TIMED_OUT",
+            ProjectErrorCode::ImageSignatureInvalid(_) => "IMAGE_SIGNATURE_INVALID",
+            ProjectErrorCode::UnsupportedImageArchitecture(_, _) => "UNSUPPORTED_IMAGE_ARCHITECTURE",
             ProjectErrorCode::ContainerCreationFailed => "CONTAINER_CREATION_FAILED",
             ProjectErrorCode::DeleteFailed => "DELETE_FAILED",
             ProjectErrorCode::GithubAccountNotLinked => "GITHUB_ACCOUNT_NOT_LINKED",
             ProjectErrorCode::GithubRepoNotAccessible => "GITHUB_REPO_NOT_ACCESSIBLE",
             ProjectErrorCode::GithubPackageNotPublic => "GITHUB_PACKAGE_NOT_PUBLIC",
             ProjectErrorCode::ForbiddenEnvVar(_) => "FORBIDDEN_ENV_VAR",
+            ProjectErrorCode::ReservedEnvVar(_) => "RESERVED_ENV_VAR",
+            ProjectErrorCode::InvalidEnvVarValue(_) => "INVALID_ENV_VAR_VALUE",
+            ProjectErrorCode::InvalidEnvVarName(_) => "INVALID_ENV_VAR_NAME",
+            ProjectErrorCode::EnvVarValueTooLarge(_, _) => "ENV_VAR_VALUE_TOO_LARGE",
+            ProjectErrorCode::UnknownEnvPlaceholder(_) => "UNKNOWN_ENV_PLACEHOLDER",
+            ProjectErrorCode::TooManyEnvVars(_) => "TOO_MANY_ENV_VARS",
+            ProjectErrorCode::EnvVarsTooLarge(_) => "ENV_VARS_TOO_LARGE",
             ProjectErrorCode::InvalidVolumePath => "INVALID_VOLUME_PATH",
             ProjectErrorCode::InvalidGithubUrl => "INVALID_GITHUB_URL",
             ProjectErrorCode::ProjectCreationFailedWithDatabaseError => "PROJECT_CREATION_FAILED_WITH_DATABASE_ERROR",
             ProjectErrorCode::InvalidSourceRootDir => "INVALID_SOURCE_ROOT_DIR",
+            ProjectErrorCode::DeploymentAlreadyInProgress => "DEPLOYMENT_ALREADY_IN_PROGRESS",
+            ProjectErrorCode::InterruptedByRestart => "INTERRUPTED_BY_RESTART",
+            ProjectErrorCode::ExportAlreadyInProgress => "EXPORT_ALREADY_IN_PROGRESS",
+            ProjectErrorCode::EnvVarsChangedSincePreview => "ENV_VARS_CHANGED_SINCE_PREVIEW",
+            ProjectErrorCode::VolumeNotShareable => "VOLUME_NOT_SHAREABLE",
+            ProjectErrorCode::InsufficientBuildSpace(_, _) => "INSUFFICIENT_BUILD_SPACE",
+            ProjectErrorCode::CloneTimedOut(_) => "CLONE_TIMED_OUT",
+            ProjectErrorCode::LogsRestricted => "LOGS_RESTRICTED",
+            ProjectErrorCode::ImageRepositoryChanged(_) => "IMAGE_REPOSITORY_CHANGED",
+            ProjectErrorCode::TooManyParticipants(_) => "TOO_MANY_PARTICIPANTS",
+            ProjectErrorCode::CustomDomainTaken(_) => "CUSTOM_DOMAIN_TAKEN",
+            ProjectErrorCode::ProjectArchived => "PROJECT_ARCHIVED",
+            ProjectErrorCode::UnsupportedConfigVersion(_, _) => "UNSUPPORTED_CONFIG_VERSION",
+        }
+    }
+
+    pub fn status_code(&self) -> StatusCode
+    {
+        match self
+        {
+            ProjectErrorCode::ImagePullFailed(_) | ProjectErrorCode::ContainerCreationFailed => StatusCode::INTERNAL_SERVER_ERROR,
+            ProjectErrorCode::EnvVarsChangedSincePreview => StatusCode::CONFLICT,
+            ProjectErrorCode::ImageScanTimedOut(_) | ProjectErrorCode::CloneTimedOut(_) => StatusCode::GATEWAY_TIMEOUT,
+            ProjectErrorCode::InsufficientBuildSpace(_, _) => StatusCode::INSUFFICIENT_STORAGE,
+            ProjectErrorCode::LogsRestricted => StatusCode::FORBIDDEN,
+            ProjectErrorCode::CustomDomainTaken(_) | ProjectErrorCode::ProjectArchived => StatusCode::CONFLICT,
+            _ => StatusCode::BAD_REQUEST
+        }
+    }
+
+    // Exhaustif pour que le catalogue d'erreurs reste exact.
+    pub fn has_details(&self) -> bool
+    {
+        match self
+        {
+            ProjectErrorCode::ImageScanFailed(_)
+            | ProjectErrorCode::ImageSignatureInvalid(_)
+            | ProjectErrorCode::ImagePullFailed(_)
+            | ProjectErrorCode::ForbiddenEnvVar(_)
+            | ProjectErrorCode::ReservedEnvVar(_)
+            | ProjectErrorCode::InvalidEnvVarValue(_)
+            | ProjectErrorCode::InvalidEnvVarName(_)
+            | ProjectErrorCode::EnvVarValueTooLarge(_, _)
+            | ProjectErrorCode::UnknownEnvPlaceholder(_)
+            | ProjectErrorCode::TooManyEnvVars(_)
+            | ProjectErrorCode::EnvVarsTooLarge(_)
+            | ProjectErrorCode::TooManyParticipants(_)
+            | ProjectErrorCode::CustomDomainTaken(_)
+            | ProjectErrorCode::UnsupportedConfigVersion(_, _) => true,
+            ProjectErrorCode::ProjectNameTaken
+            | ProjectErrorCode::OwnerAlreadyExists
+            | ProjectErrorCode::OwnerCannotBeParticipant
+            | ProjectErrorCode::InvalidProjectName
+            | ProjectErrorCode::InvalidImageUrl
+            | ProjectErrorCode::ImageScanTimedOut(_)
+            | ProjectErrorCode::UnsupportedImageArchitecture(_, _)
+            | ProjectErrorCode::ContainerCreationFailed
+            | ProjectErrorCode::DeleteFailed
+            | ProjectErrorCode::InvalidGithubUrl
+            | ProjectErrorCode::GithubAccountNotLinked
+            | ProjectErrorCode::GithubRepoNotAccessible
+            | ProjectErrorCode::GithubPackageNotPublic
+            | ProjectErrorCode::InvalidVolumePath
+            | ProjectErrorCode::ProjectCreationFailedWithDatabaseError
+            | ProjectErrorCode::InvalidSourceRootDir
+            | ProjectErrorCode::DeploymentAlreadyInProgress
+            | ProjectErrorCode::InterruptedByRestart
+            | ProjectErrorCode::ExportAlreadyInProgress
+            | ProjectErrorCode::EnvVarsChangedSincePreview
+            | ProjectErrorCode::VolumeNotShareable
+            | ProjectErrorCode::InsufficientBuildSpace(_, _)
+            | ProjectErrorCode::CloneTimedOut(_)
+            | ProjectErrorCode::LogsRestricted
+            | ProjectErrorCode::ImageRepositoryChanged(_)
+            | ProjectErrorCode::ProjectArchived => false,
+        }
+    }
+}
+
+impl ProjectErrorCode
+{
+    // Exhaustif : un nouveau code ne compile pas sans ses deux traductions.
+    pub fn message_in(&self, language: Language) -> String
+    {
+        if language == Language::En
+        {
+            return self.to_string();
+        }
+
+        match self
+        {
+            ProjectErrorCode::ProjectNameTaken => "Ce nom de projet est déjà utilisé.".to_string(),
+            ProjectErrorCode::OwnerAlreadyExists => "Vous avez atteint votre quota de projets.".to_string(),
+            ProjectErrorCode::OwnerCannotBeParticipant => "Le propriétaire du projet ne peut pas en être participant.".to_string(),
+            ProjectErrorCode::InvalidProjectName => "Le nom du projet est invalide. Il doit faire de 1 à 63 caractères, ne contenir que a-z, 0-9 ou '-', et ne pas commencer ni finir par un tiret.".to_string(),
+            ProjectErrorCode::InvalidImageUrl => "L'URL de l'image Docker est invalide ou contient des caractères interdits.".to_string(),
+            ProjectErrorCode::ImagePullFailed(_) => "Impossible de récupérer l'image Docker. Vérifiez l'URL et l'accès au registre.".to_string(),
+            ProjectErrorCode::ImageScanFailed(_) => "L'analyse de sécurité a échoué : des vulnérabilités ont été trouvées dans l'image.".to_string(),
+            ProjectErrorCode::ImageScanTimedOut(seconds) => format!("L'analyse de sécurité ne s'est pas terminée en {} secondes.", seconds),
+            ProjectErrorCode::ImageSignatureInvalid(_) => "La signature de l'image n'a pas pu être vérifiée. Seules les images signées peuvent être déployées depuis ce registre.".to_string(),
+            ProjectErrorCode::UnsupportedImageArchitecture(image, host) => format!("L'image est construite pour {}, mais le serveur tourne sous {}. Publiez une image pour {}.", image, host, host),
+            ProjectErrorCode::ContainerCreationFailed => "Impossible de créer le conteneur du projet.".to_string(),
+            ProjectErrorCode::DeleteFailed => "Impossible de supprimer le projet.".to_string(),
+            ProjectErrorCode::InvalidGithubUrl => "L'URL GitHub fournie est invalide ou non prise en charge.".to_string(),
+            ProjectErrorCode::GithubAccountNotLinked => "L'application GitHub n'est pas installée sur le compte du propriétaire du dépôt.".to_string(),
+            ProjectErrorCode::GithubRepoNotAccessible => "L'installation de l'application GitHub n'a pas accès à ce dépôt. Mettez à jour les paramètres de l'installation.".to_string(),
+            ProjectErrorCode::GithubPackageNotPublic => "Les images de ghcr.io doivent être publiques pour un déploiement direct.".to_string(),
+            ProjectErrorCode::ForbiddenEnvVar(variables) => format!("L'utilisation des variables d'environnement '{}' est interdite.", variables.join("', '")),
+            ProjectErrorCode::ReservedEnvVar(variables) => format!("Les variables d'environnement commençant par 'HANGAR_' sont fournies par la plateforme et ne peuvent pas être définies : '{}'.", variables.join("', '")),
+            ProjectErrorCode::InvalidEnvVarValue(variables) => format!("La valeur des variables d'environnement '{}' contient des caractères de contrôle.", variables.join("', '")),
+            ProjectErrorCode::InvalidEnvVarName(variables) => format!("Le nom d'une variable d'environnement doit commencer par une lettre ou '_' et ne contenir que des lettres, des chiffres et '_' : '{}'.", variables.join("', '")),
+            ProjectErrorCode::EnvVarValueTooLarge(variables, max) => format!("La valeur des variables d'environnement '{}' dépasse {} octets.", variables.join("', '"), max),
+            ProjectErrorCode::UnknownEnvPlaceholder(placeholders) => format!("Espace(s) réservé(s) inconnu(s) dans les valeurs des variables d'environnement : '{}'. Écrivez '$${{' pour obtenir '${{'.", placeholders.join("', '")),
+            ProjectErrorCode::TooManyEnvVars(max) => format!("Un projet ne peut pas avoir plus de {} variables d'environnement.", max),
+            ProjectErrorCode::EnvVarsTooLarge(max) => format!("Les variables d'environnement d'un projet ne peuvent pas dépasser {} octets au total.", max),
+            ProjectErrorCode::InvalidVolumePath => "Le chemin du volume persistant est invalide.".to_string(),
+            ProjectErrorCode::ProjectCreationFailedWithDatabaseError => "Une opération en base de données a échoué pendant la création du projet.".to_string(),
+            ProjectErrorCode::InvalidSourceRootDir => "Le répertoire racine indiqué est invalide.".to_string(),
+            ProjectErrorCode::DeploymentAlreadyInProgress => "Un déploiement est déjà en cours pour cet utilisateur.".to_string(),
+            ProjectErrorCode::InterruptedByRestart => "Le déploiement a été interrompu par un redémarrage du serveur. Veuillez réessayer.".to_string(),
+            ProjectErrorCode::ExportAlreadyInProgress => "Un export est déjà en cours pour cet utilisateur. Veuillez attendre qu'il se termine.".to_string(),
+            ProjectErrorCode::EnvVarsChangedSincePreview => "Les variables d'environnement ont changé depuis l'aperçu des différences. Veuillez revoir les changements.".to_string(),
+            ProjectErrorCode::VolumeNotShareable => "Plusieurs répliques nécessitent que le volume persistant soit marqué comme partageable entre conteneurs.".to_string(),
+            ProjectErrorCode::InsufficientBuildSpace(available, required) => format!("Le serveur de build manque d'espace disque ({} Mo libres, {} Mo requis). Réessayez plus tard.", available, required),
+            ProjectErrorCode::CloneTimedOut(seconds) => format!("Le dépôt n'a pas pu être cloné en {} secondes : le serveur Git est trop lent ou ne répond plus.", seconds),
+            ProjectErrorCode::LogsRestricted => "Le propriétaire de ce projet a réservé ses logs aux propriétaires.".to_string(),
+            ProjectErrorCode::ImageRepositoryChanged(repository) => format!("La nouvelle image doit provenir du dépôt du projet '{}'. Indiquez 'allow_repository_change' pour déployer un autre dépôt.", repository),
+            ProjectErrorCode::TooManyParticipants(max) => format!("Un projet peut avoir au plus {} participants, invitations en attente comprises.", max),
+            ProjectErrorCode::CustomDomainTaken(domain) => format!("Le domaine '{}' est déjà utilisé par un autre projet.", domain),
+            ProjectErrorCode::ProjectArchived => "Ce projet est archivé. Désarchivez-le avant de le démarrer.".to_string(),
+            ProjectErrorCode::UnsupportedConfigVersion(found, supported) => format!("Les documents de configuration en version {} ne sont pas pris en charge ; cette instance lit la version {}.", found, supported),
+        }
+    }
+}
+
+impl DatabaseErrorCode
+{
+    pub fn message_in(&self, language: Language) -> String
+    {
+        if language == Language::En
+        {
+            return self.to_string();
+        }
+
+        match self
+        {
+            DatabaseErrorCode::DatabaseAlreadyExists => "Vous avez atteint votre quota de bases de données.".to_string(),
+            DatabaseErrorCode::ProvisioningFailed => "Impossible de créer la base de données.".to_string(),
+            DatabaseErrorCode::DeprovisioningFailed => "Impossible de supprimer la base de données.".to_string(),
+            DatabaseErrorCode::NotFound => "Base de données introuvable.".to_string(),
+            DatabaseErrorCode::TemplateFailed => "Impossible d'appliquer le modèle de base de données.".to_string(),
         }
     }
 }
 
 impl DatabaseErrorCode 
 {
-    fn as_str(&self) -> &'static str 
+    pub fn as_str(&self) -> &'static str 
     {
         match self 
         {
@@ -134,14 +369,202 @@ impl DatabaseErrorCode
             DatabaseErrorCode::ProvisioningFailed => "PROVISIONING_FAILED",
             DatabaseErrorCode::DeprovisioningFailed => "DEPROVISIONING_FAILED",
             DatabaseErrorCode::NotFound => "NOT_FOUND",
+            DatabaseErrorCode::TemplateFailed => "TEMPLATE_FAILED",
+        }
+    }
+
+    pub fn status_code(&self) -> StatusCode
+    {
+        match self
+        {
+            DatabaseErrorCode::ProvisioningFailed | DatabaseErrorCode::DeprovisioningFailed | DatabaseErrorCode::TemplateFailed => StatusCode::INTERNAL_SERVER_ERROR,
+            _ => StatusCode::BAD_REQUEST
         }
     }
 }
 
+impl From<reqwest::Error> for AppError
+{
+    fn from(e: reqwest::Error) -> Self
+    {
+        AppError::upstream("An external service", e)
+    }
+}
+
+impl AppError
+{
+    pub fn upstream(service: &'static str, e: reqwest::Error) -> Self
+    {
+        error!("Call to {} failed: {}", service, e);
+
+        if e.is_timeout()
+        {
+            return AppError::UpstreamTimeout { service };
+        }
+
+        if e.is_connect()
+        {
+            return AppError::UpstreamUnavailable { service };
+        }
+
+        match e.status()
+        {
+            Some(status) => AppError::upstream_status(service, status),
+            None => AppError::ExternalServiceError(e),
+        }
+    }
+
+    pub fn upstream_status(service: &'static str, status: reqwest::StatusCode) -> Self
+    {
+        match status
+        {
+            reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::FORBIDDEN => AppError::UpstreamAuthFailure { service },
+            reqwest::StatusCode::GATEWAY_TIMEOUT => AppError::UpstreamTimeout { service },
+            status if status.is_server_error() => AppError::UpstreamUnavailable { service },
+            _ => AppError::InternalServerError,
+        }
+    }
+
+    // Un pool épuisé est une surcharge, pas un bogue : 503, que le client peut réessayer.
+    pub fn database(e: &sqlx::Error) -> Self
+    {
+        match e
+        {
+            sqlx::Error::PoolTimedOut =>
+            {
+                crate::services::pool_service::record_acquire_timeout();
+                AppError::DatabaseBusy
+            }
+            _ => AppError::InternalServerError,
+        }
+    }
+
+    // Seules les erreurs de transport signifient que le démon est injoignable.
+    pub fn docker(e: &bollard::errors::Error) -> Self
+    {
+        match e
+        {
+            bollard::errors::Error::RequestTimeoutError => AppError::UpstreamTimeout { service: "Docker" },
+            bollard::errors::Error::IOError { .. }
+            | bollard::errors::Error::HyperResponseError { .. }
+            | bollard::errors::Error::HyperLegacyError { .. }
+            | bollard::errors::Error::SocketNotFoundError(_) => AppError::UpstreamUnavailable { service: "Docker" },
+            // Réponses d'un proxy devant un démon distant, pas du démon lui-même.
+            bollard::errors::Error::DockerResponseServerError { status_code: 502 | 503, .. } => AppError::UpstreamUnavailable { service: "Docker" },
+            bollard::errors::Error::DockerResponseServerError { status_code: 504, .. } => AppError::UpstreamTimeout { service: "Docker" },
+            _ => AppError::InternalServerError,
+        }
+    }
+
+    fn upstream_service(&self) -> Option<&'static str>
+    {
+        match self
+        {
+            AppError::UpstreamTimeout { service }
+            | AppError::UpstreamUnavailable { service }
+            | AppError::UpstreamAuthFailure { service } => Some(service),
+            _ => None,
+        }
+    }
+
+    pub fn error_code(&self) -> &'static str
+    {
+        match self
+        {
+            AppError::InternalServerError
+            | AppError::ExternalServiceError(_)
+            | AppError::ParsingError(_) => "INTERNAL_SERVER_ERROR",
+            AppError::UpstreamTimeout { .. } => "UPSTREAM_TIMEOUT",
+            AppError::UpstreamUnavailable { .. } => "UPSTREAM_UNAVAILABLE",
+            AppError::UpstreamAuthFailure { .. } => "UPSTREAM_AUTH_FAILURE",
+            AppError::DatabaseBusy => "DATABASE_BUSY",
+            AppError::Unauthorized(_) => "UNAUTHORIZED",
+            AppError::NotFound(_) => "NOT_FOUND",
+            AppError::BadRequest(_) => "BAD_REQUEST",
+            AppError::InvalidPayload { .. } => "INVALID_PAYLOAD",
+            AppError::UnsupportedMediaType(_) => "UNSUPPORTED_MEDIA_TYPE",
+            AppError::PayloadTooLarge(_) => "PAYLOAD_TOO_LARGE",
+            AppError::TooManyRequests(_) => "TOO_MANY_REQUESTS",
+            AppError::ProjectError(code) => code.as_str(),
+            AppError::DatabaseError(code) => code.as_str(),
+        }
+    }
+
+    pub fn status_code(&self) -> StatusCode
+    {
+        match self
+        {
+            AppError::InternalServerError
+            | AppError::ExternalServiceError(_)
+            | AppError::ParsingError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            AppError::UpstreamTimeout { .. } => StatusCode::GATEWAY_TIMEOUT,
+            AppError::UpstreamUnavailable { .. } => StatusCode::SERVICE_UNAVAILABLE,
+            AppError::UpstreamAuthFailure { .. } => StatusCode::BAD_GATEWAY,
+            AppError::DatabaseBusy => StatusCode::SERVICE_UNAVAILABLE,
+            AppError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
+            AppError::BadRequest(_) | AppError::InvalidPayload { .. } => StatusCode::BAD_REQUEST,
+            AppError::UnsupportedMediaType(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            AppError::PayloadTooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
+            AppError::TooManyRequests(_) => StatusCode::TOO_MANY_REQUESTS,
+            AppError::ProjectError(code) => code.status_code(),
+            AppError::DatabaseError(code) => code.status_code(),
+        }
+    }
+
+    pub fn has_details(&self) -> bool
+    {
+        match self
+        {
+            AppError::InvalidPayload { .. } => true,
+            AppError::ProjectError(code) => code.has_details(),
+            _ => false,
+        }
+    }
+
+    pub fn public_message(&self) -> String
+    {
+        self.public_message_in(Language::En)
+    }
+
+    pub fn public_message_in(&self, language: Language) -> String
+    {
+        match (self, language)
+        {
+            (AppError::InternalServerError | AppError::ExternalServiceError(_) | AppError::ParsingError(_), Language::En) =>
+                "An internal error has occurred".to_string(),
+            (AppError::InternalServerError | AppError::ExternalServiceError(_) | AppError::ParsingError(_), Language::Fr) =>
+                "Une erreur interne est survenue".to_string(),
+            (AppError::UpstreamTimeout { service }, Language::En) => format!("{} did not respond in time. Please try again later.", service),
+            (AppError::UpstreamTimeout { service }, Language::Fr) => format!("{} n'a pas répondu à temps. Veuillez réessayer plus tard.", service),
+            (AppError::UpstreamUnavailable { service }, Language::En) => format!("{} is currently unreachable. Please try again later.", service),
+            (AppError::UpstreamUnavailable { service }, Language::Fr) => format!("{} est actuellement injoignable. Veuillez réessayer plus tard.", service),
+            (AppError::UpstreamAuthFailure { service }, Language::En) => format!("{} refused the platform's credentials. Please contact an administrator.", service),
+            (AppError::UpstreamAuthFailure { service }, Language::Fr) => format!("{} a refusé les identifiants de la plateforme. Veuillez contacter un administrateur.", service),
+            (AppError::DatabaseBusy, Language::En) => "The service is under heavy load. Please try again in a moment.".to_string(),
+            (AppError::DatabaseBusy, Language::Fr) => "Le service est très sollicité. Veuillez réessayer dans un instant.".to_string(),
+            (AppError::Unauthorized(message)
+            | AppError::NotFound(message)
+            | AppError::BadRequest(message)
+            | AppError::InvalidPayload { message, .. }
+            | AppError::UnsupportedMediaType(message)
+            | AppError::PayloadTooLarge(message)
+            | AppError::TooManyRequests(message), _) => message.clone(),
+            (AppError::ProjectError(code), _) => code.message_in(language),
+            (AppError::DatabaseError(code), _) => code.message_in(language),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct LocalizedMessage(pub String);
+
 impl IntoResponse for AppError
 {
     fn into_response(self) -> Response
     {
+        let localized = LocalizedMessage(self.public_message_in(Language::Fr));
+
         let (status, body) = match self
         {
             AppError::InternalServerError
@@ -155,6 +578,27 @@ impl IntoResponse for AppError
                 )
             }
 
+            AppError::UpstreamTimeout { .. }
+            | AppError::UpstreamUnavailable { .. }
+            | AppError::UpstreamAuthFailure { .. } =>
+            {
+                let status = self.status_code();
+                error!("--> UPSTREAM ERROR ({}): {}", status.as_u16(), self);
+                (
+                    status,
+                    Json(json!({ "error_code": self.error_code(), "message": self.public_message(), "service": self.upstream_service() })),
+                )
+            }
+
+            AppError::DatabaseBusy =>
+            {
+                error!("--> DATABASE BUSY (503): a connection pool is exhausted");
+                (
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    Json(json!({ "error_code": self.error_code(), "message": self.public_message() })),
+                )
+            }
+
             AppError::Unauthorized(message) =>
             {
                 trace!("--> NOT AUTHORIZED (401): {}", message);
@@ -182,14 +626,46 @@ impl IntoResponse for AppError
                 )
             }
 
+            AppError::InvalidPayload { field, message } =>
+            {
+                trace!("--> INVALID PAYLOAD (400): {}", message);
+                (
+                    StatusCode::BAD_REQUEST,
+                    Json(json!({ "error_code": "INVALID_PAYLOAD", "message": message, "details": { "field": field } })),
+                )
+            }
+
+            AppError::UnsupportedMediaType(message) =>
+            {
+                trace!("--> UNSUPPORTED MEDIA TYPE (415): {}", message);
+                (
+                    StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                    Json(json!({ "error_code": "UNSUPPORTED_MEDIA_TYPE", "message": message })),
+                )
+            }
+
+            AppError::PayloadTooLarge(message) =>
+            {
+                trace!("--> PAYLOAD TOO LARGE (413): {}", message);
+                (
+                    StatusCode::PAYLOAD_TOO_LARGE,
+                    Json(json!({ "error_code": "PAYLOAD_TOO_LARGE", "message": message })),
+                )
+            }
+
+            AppError::TooManyRequests(message) =>
+            {
+                trace!("--> TOO MANY REQUESTS (429): {}", message);
+                (
+                    StatusCode::TOO_MANY_REQUESTS,
+                    Json(json!({ "error_code": "TOO_MANY_REQUESTS", "message": message })),
+                )
+            }
+
             AppError::DatabaseError(code) =>
             {
                 trace!("--> DATABASE ERROR (400): {}", code);
-                let status = match code 
-                {
-                    DatabaseErrorCode::ProvisioningFailed | DatabaseErrorCode::DeprovisioningFailed => StatusCode::INTERNAL_SERVER_ERROR,
-                    _ => StatusCode::BAD_REQUEST
-                };
+                let status = code.status_code();
 
                 let error_json = json!(
                 {
@@ -206,11 +682,7 @@ impl IntoResponse for AppError
             AppError::ProjectError(code) =>
             {
                 trace!("--> PROJECT ERROR (400): {}", code);
-                let status = match code 
-                {
-                    ProjectErrorCode::ImagePullFailed | ProjectErrorCode::ContainerCreationFailed => StatusCode::INTERNAL_SERVER_ERROR,
-                    _ => StatusCode::BAD_REQUEST
-                };
+                let status = code.status_code();
 
                 let mut error_json = json!(
                 {
@@ -226,9 +698,46 @@ impl IntoResponse for AppError
                         {
                             obj.insert("details".to_string(), json!(details));
                         }
-                        ProjectErrorCode::ForbiddenEnvVar(var) =>
+                        ProjectErrorCode::ImageSignatureInvalid(output) =>
+                        {
+                            obj.insert("details".to_string(), json!({ "cosign_output": output }));
+                        }
+                        ProjectErrorCode::ImagePullFailed(layers) if !layers.is_empty() =>
+                        {
+                            obj.insert("details".to_string(), json!({ "layers": layers }));
+                        }
+                        ProjectErrorCode::ForbiddenEnvVar(vars) | ProjectErrorCode::ReservedEnvVar(vars) | ProjectErrorCode::InvalidEnvVarValue(vars) | ProjectErrorCode::InvalidEnvVarName(vars) =>
+                        {
+                             // 'variable' reste pour les clients qui n'attendent qu'une seule clé.
+                             obj.insert("details".to_string(), json!({ "variable": vars.first(), "variables": vars }));
+                        }
+                        ProjectErrorCode::EnvVarValueTooLarge(vars, max) =>
+                        {
+                            obj.insert("details".to_string(), json!({ "variable": vars.first(), "variables": vars, "max_bytes": max }));
+                        }
+                        ProjectErrorCode::UnknownEnvPlaceholder(placeholders) =>
                         {
-                             obj.insert("details".to_string(), json!({ "variable": var }));
+                            obj.insert("details".to_string(), json!({ "placeholders": placeholders }));
+                        }
+                        ProjectErrorCode::TooManyEnvVars(max) =>
+                        {
+                            obj.insert("details".to_string(), json!({ "max_env_vars": max }));
+                        }
+                        ProjectErrorCode::EnvVarsTooLarge(max) =>
+                        {
+                            obj.insert("details".to_string(), json!({ "max_bytes": max }));
+                        }
+                        ProjectErrorCode::TooManyParticipants(max) =>
+                        {
+                            obj.insert("details".to_string(), json!({ "max_participants": max }));
+                        }
+                        ProjectErrorCode::CustomDomainTaken(domain) =>
+                        {
+                            obj.insert("details".to_string(), json!({ "domain": domain }));
+                        }
+                        ProjectErrorCode::UnsupportedConfigVersion(found, supported) =>
+                        {
+                            obj.insert("details".to_string(), json!({ "schema_version": found, "supported_version": supported }));
                         }
                         _ => {}
                     }
@@ -241,6 +750,76 @@ impl IntoResponse for AppError
             }
         };
 
-        (status, body).into_response()
+        let mut response = (status, body).into_response();
+        response.extensions_mut().insert(localized);
+        response
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::test_support::{closed_port, http_server, stalling_server};
+    use axum::body::to_bytes;
+    use std::time::Duration;
+
+    fn client() -> reqwest::Client
+    {
+        reqwest::Client::builder().timeout(Duration::from_secs(1)).build().unwrap()
+    }
+
+    async fn call(address: std::net::SocketAddr) -> AppError
+    {
+        let result = client().get(format!("http://{}/", address)).send().await.and_then(|response| response.error_for_status());
+        AppError::upstream("GitHub", result.unwrap_err())
+    }
+
+    #[tokio::test]
+    async fn transport_failures_map_to_the_upstream_variants()
+    {
+        let timeout = call(stalling_server()).await;
+        assert!(matches!(timeout, AppError::UpstreamTimeout { service: "GitHub" }), "{:?}", timeout);
+        assert_eq!(timeout.status_code(), StatusCode::GATEWAY_TIMEOUT);
+
+        let refused = call(closed_port()).await;
+        assert!(matches!(refused, AppError::UpstreamUnavailable { service: "GitHub" }), "{:?}", refused);
+        assert_eq!(refused.status_code(), StatusCode::SERVICE_UNAVAILABLE);
+
+        let (address, _) = http_server("HTTP/1.1 503 Service Unavailable\r\nContent-Length: 0\r\n\r\n");
+        let unavailable = call(address).await;
+        assert!(matches!(unavailable, AppError::UpstreamUnavailable { service: "GitHub" }), "{:?}", unavailable);
+    }
+
+    #[test]
+    fn upstream_status_codes_are_translated()
+    {
+        use reqwest::StatusCode as Upstream;
+
+        let cases = [
+            (Upstream::UNAUTHORIZED, StatusCode::BAD_GATEWAY, "UPSTREAM_AUTH_FAILURE"),
+            (Upstream::FORBIDDEN, StatusCode::BAD_GATEWAY, "UPSTREAM_AUTH_FAILURE"),
+            (Upstream::GATEWAY_TIMEOUT, StatusCode::GATEWAY_TIMEOUT, "UPSTREAM_TIMEOUT"),
+            (Upstream::INTERNAL_SERVER_ERROR, StatusCode::SERVICE_UNAVAILABLE, "UPSTREAM_UNAVAILABLE"),
+            (Upstream::BAD_GATEWAY, StatusCode::SERVICE_UNAVAILABLE, "UPSTREAM_UNAVAILABLE"),
+            (Upstream::NOT_FOUND, StatusCode::INTERNAL_SERVER_ERROR, "INTERNAL_SERVER_ERROR"),
+        ];
+
+        for (upstream, status, code) in cases
+        {
+            let error = AppError::upstream_status("Docker Hub", upstream);
+            assert_eq!(error.status_code(), status, "{}", upstream);
+            assert_eq!(error.error_code(), code, "{}", upstream);
+        }
+    }
+
+    #[tokio::test]
+    async fn upstream_errors_name_the_service_in_the_body()
+    {
+        let response = AppError::UpstreamTimeout { service: "CAS" }.into_response();
+        assert_eq!(response.status(), StatusCode::GATEWAY_TIMEOUT);
+
+        let body: serde_json::Value = serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await.unwrap()).unwrap();
+        assert_eq!(body["error_code"], "UPSTREAM_TIMEOUT");
+        assert_eq!(body["service"], "CAS");
+    }
+}
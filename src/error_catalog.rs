@@ -0,0 +1,202 @@
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::
+{
+    error::{AppError, DatabaseErrorCode, ProjectErrorCode},
+    i18n::Language,
+    model::scan::{ScanReport, Severity, ThresholdSource},
+};
+
+// Les motifs sont vérifiés exhaustivement : une nouvelle variante ne compile pas sans son entrée au catalogue.
+macro_rules! catalog_samples
+{
+    ($ty:ty { $( $pattern:pat => [ $( $sample:expr ),* ] ),* $(,)? }) =>
+    {{
+        fn _covers_every_variant(value: &$ty)
+        {
+            match value
+            {
+                $( $pattern => {} ),*
+            }
+        }
+
+        let samples: Vec<$ty> = vec![$( $( $sample ),* ),*];
+        samples
+    }};
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCategory
+{
+    Generic,
+    Project,
+    Database,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ErrorCatalogEntry
+{
+    pub category: ErrorCategory,
+    pub code: &'static str,
+    pub status: u16,
+    pub message_en: String,
+    pub message_fr: String,
+    pub has_details: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ErrorCatalog
+{
+    pub catalog_version: String,
+    pub errors: Vec<ErrorCatalogEntry>,
+}
+
+fn generic_samples() -> Vec<AppError>
+{
+    // Les messages libres sont écrits à l'appel ; le catalogue n'en donne qu'un exemple.
+    catalog_samples!(AppError
+    {
+        AppError::InternalServerError | AppError::ExternalServiceError(_) | AppError::ParsingError(_) => [AppError::InternalServerError],
+        AppError::UpstreamTimeout { .. } => [AppError::UpstreamTimeout { service: "{service}" }],
+        AppError::UpstreamUnavailable { .. } => [AppError::UpstreamUnavailable { service: "{service}" }],
+        AppError::UpstreamAuthFailure { .. } => [AppError::UpstreamAuthFailure { service: "{service}" }],
+        AppError::DatabaseBusy => [AppError::DatabaseBusy],
+        AppError::Unauthorized(_) => [AppError::Unauthorized("{message}".to_string())],
+        AppError::NotFound(_) => [AppError::NotFound("{message}".to_string())],
+        AppError::BadRequest(_) => [AppError::BadRequest("{message}".to_string())],
+        AppError::InvalidPayload { .. } => [AppError::InvalidPayload { field: None, message: "{message}".to_string() }],
+        AppError::UnsupportedMediaType(_) => [AppError::UnsupportedMediaType("{message}".to_string())],
+        AppError::PayloadTooLarge(_) => [AppError::PayloadTooLarge("{message}".to_string())],
+        AppError::TooManyRequests(_) => [AppError::TooManyRequests("{message}".to_string())],
+        AppError::ProjectError(_) | AppError::DatabaseError(_) => [],
+    })
+}
+
+fn project_samples() -> Vec<ProjectErrorCode>
+{
+    catalog_samples!(ProjectErrorCode
+    {
+        ProjectErrorCode::ProjectNameTaken => [ProjectErrorCode::ProjectNameTaken],
+        ProjectErrorCode::OwnerAlreadyExists => [ProjectErrorCode::OwnerAlreadyExists],
+        ProjectErrorCode::OwnerCannotBeParticipant => [ProjectErrorCode::OwnerCannotBeParticipant],
+        ProjectErrorCode::InvalidProjectName => [ProjectErrorCode::InvalidProjectName],
+        ProjectErrorCode::InvalidImageUrl => [ProjectErrorCode::InvalidImageUrl],
+        ProjectErrorCode::ImagePullFailed(_) => [ProjectErrorCode::ImagePullFailed(Vec::new())],
+        ProjectErrorCode::ImageScanFailed(_) => [ProjectErrorCode::ImageScanFailed(ScanReport
+        {
+            fail_on_severity: Severity::High,
+            threshold_source: ThresholdSource::Platform,
+            blocking: Vec::new(),
+            waived: Vec::new(),
+        })],
+        ProjectErrorCode::ImageScanTimedOut(_) => [ProjectErrorCode::ImageScanTimedOut(600)],
+        ProjectErrorCode::ImageSignatureInvalid(_) => [ProjectErrorCode::ImageSignatureInvalid(String::new())],
+        ProjectErrorCode::UnsupportedImageArchitecture(_, _) =>
+            [ProjectErrorCode::UnsupportedImageArchitecture("{image_platform}".to_string(), "{host_platform}".to_string())],
+        ProjectErrorCode::ContainerCreationFailed => [ProjectErrorCode::ContainerCreationFailed],
+        ProjectErrorCode::DeleteFailed => [ProjectErrorCode::DeleteFailed],
+        ProjectErrorCode::InvalidGithubUrl => [ProjectErrorCode::InvalidGithubUrl],
+        ProjectErrorCode::GithubAccountNotLinked => [ProjectErrorCode::GithubAccountNotLinked],
+        ProjectErrorCode::GithubRepoNotAccessible => [ProjectErrorCode::GithubRepoNotAccessible],
+        ProjectErrorCode::GithubPackageNotPublic => [ProjectErrorCode::GithubPackageNotPublic],
+        ProjectErrorCode::ForbiddenEnvVar(_) => [ProjectErrorCode::ForbiddenEnvVar(vec!["{variable}".to_string()])],
+        ProjectErrorCode::ReservedEnvVar(_) => [ProjectErrorCode::ReservedEnvVar(vec!["{variable}".to_string()])],
+        ProjectErrorCode::InvalidEnvVarValue(_) => [ProjectErrorCode::InvalidEnvVarValue(vec!["{variable}".to_string()])],
+        ProjectErrorCode::InvalidEnvVarName(_) => [ProjectErrorCode::InvalidEnvVarName(vec!["{variable}".to_string()])],
+        ProjectErrorCode::EnvVarValueTooLarge(_, _) => [ProjectErrorCode::EnvVarValueTooLarge(vec!["{variable}".to_string()], 32768)],
+        ProjectErrorCode::UnknownEnvPlaceholder(_) => [ProjectErrorCode::UnknownEnvPlaceholder(vec!["{placeholder}".to_string()])],
+        ProjectErrorCode::TooManyEnvVars(_) => [ProjectErrorCode::TooManyEnvVars(200)],
+        ProjectErrorCode::EnvVarsTooLarge(_) => [ProjectErrorCode::EnvVarsTooLarge(262144)],
+        ProjectErrorCode::InvalidVolumePath => [ProjectErrorCode::InvalidVolumePath],
+        ProjectErrorCode::ProjectCreationFailedWithDatabaseError => [ProjectErrorCode::ProjectCreationFailedWithDatabaseError],
+        ProjectErrorCode::InvalidSourceRootDir => [ProjectErrorCode::InvalidSourceRootDir],
+        ProjectErrorCode::DeploymentAlreadyInProgress => [ProjectErrorCode::DeploymentAlreadyInProgress],
+        ProjectErrorCode::InterruptedByRestart => [ProjectErrorCode::InterruptedByRestart],
+        ProjectErrorCode::ExportAlreadyInProgress => [ProjectErrorCode::ExportAlreadyInProgress],
+        ProjectErrorCode::EnvVarsChangedSincePreview => [ProjectErrorCode::EnvVarsChangedSincePreview],
+        ProjectErrorCode::VolumeNotShareable => [ProjectErrorCode::VolumeNotShareable],
+        ProjectErrorCode::InsufficientBuildSpace(_, _) => [ProjectErrorCode::InsufficientBuildSpace(512, 2048)],
+        ProjectErrorCode::CloneTimedOut(_) => [ProjectErrorCode::CloneTimedOut(300)],
+        ProjectErrorCode::LogsRestricted => [ProjectErrorCode::LogsRestricted],
+        ProjectErrorCode::ImageRepositoryChanged(_) => [ProjectErrorCode::ImageRepositoryChanged("{repository}".to_string())],
+        ProjectErrorCode::TooManyParticipants(_) => [ProjectErrorCode::TooManyParticipants(10)],
+        ProjectErrorCode::CustomDomainTaken(_) => [ProjectErrorCode::CustomDomainTaken("{domain}".to_string())],
+        ProjectErrorCode::ProjectArchived => [ProjectErrorCode::ProjectArchived],
+        ProjectErrorCode::UnsupportedConfigVersion(_, _) => [ProjectErrorCode::UnsupportedConfigVersion(2, 1)],
+    })
+}
+
+fn database_samples() -> Vec<DatabaseErrorCode>
+{
+    catalog_samples!(DatabaseErrorCode
+    {
+        DatabaseErrorCode::DatabaseAlreadyExists => [DatabaseErrorCode::DatabaseAlreadyExists],
+        DatabaseErrorCode::ProvisioningFailed => [DatabaseErrorCode::ProvisioningFailed],
+        DatabaseErrorCode::DeprovisioningFailed => [DatabaseErrorCode::DeprovisioningFailed],
+        DatabaseErrorCode::NotFound => [DatabaseErrorCode::NotFound],
+        DatabaseErrorCode::TemplateFailed => [DatabaseErrorCode::TemplateFailed],
+    })
+}
+
+fn entry(category: ErrorCategory, error: &AppError) -> ErrorCatalogEntry
+{
+    ErrorCatalogEntry
+    {
+        category,
+        code: error.error_code(),
+        status: error.status_code().as_u16(),
+        message_en: error.public_message_in(Language::En),
+        message_fr: error.public_message_in(Language::Fr),
+        has_details: error.has_details(),
+    }
+}
+
+pub fn error_catalog() -> ErrorCatalog
+{
+    let errors: Vec<ErrorCatalogEntry> = generic_samples().iter()
+        .map(|error| entry(ErrorCategory::Generic, error))
+        .chain(project_samples().into_iter().map(|code| entry(ErrorCategory::Project, &AppError::ProjectError(code))))
+        .chain(database_samples().into_iter().map(|code| entry(ErrorCategory::Database, &AppError::DatabaseError(code))))
+        .collect();
+
+    let mut hasher = Sha256::new();
+    hasher.update(serde_json::to_vec(&errors).unwrap_or_default());
+    let catalog_version = hasher.finalize().iter().take(8).map(|byte| format!("{:02x}", byte)).collect();
+
+    ErrorCatalog { catalog_version, errors }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use std::collections::HashSet;
+
+    fn placeholders(message: &str) -> HashSet<&str>
+    {
+        message.split('{').skip(1).filter_map(|rest| rest.split_once('}')).map(|(name, _)| name).collect()
+    }
+
+    #[test]
+    fn every_entry_is_translated_in_every_language()
+    {
+        let catalog = error_catalog();
+        let mut codes = HashSet::new();
+
+        for entry in &catalog.errors
+        {
+            assert!(codes.insert((format!("{:?}", entry.category), entry.code)), "{} is listed twice", entry.code);
+            assert!(!entry.message_en.trim().is_empty(), "{} has no English message", entry.code);
+            assert!(!entry.message_fr.trim().is_empty(), "{} has no French message", entry.code);
+            assert_eq!(placeholders(&entry.message_en), placeholders(&entry.message_fr), "{} has different placeholders", entry.code);
+
+            // Les messages libres sont écrits à l'appel, dans une seule langue.
+            if entry.message_en != "{message}"
+            {
+                assert_ne!(entry.message_en, entry.message_fr, "{} is not translated", entry.code);
+            }
+        }
+    }
+}
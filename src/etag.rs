@@ -0,0 +1,65 @@
+use std::convert::Infallible;
+
+use axum::
+{
+    extract::FromRequestParts,
+    http::{header, request::Parts, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+};
+
+pub struct IfNoneMatch(Option<String>);
+
+impl<S> FromRequestParts<S> for IfNoneMatch where S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection>
+    {
+        let value = parts.headers
+            .get(header::IF_NONE_MATCH)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+
+        Ok(Self(value))
+    }
+}
+
+impl IfNoneMatch
+{
+    // Comparaison faible de la RFC 9110 : le préfixe `W/` est ignoré des deux côtés.
+    pub fn matches(&self, etag: &str) -> bool
+    {
+        let Some(header) = &self.0 else { return false };
+        let etag = etag.trim_start_matches("W/");
+
+        header
+            .split(',')
+            .map(str::trim)
+            .any(|candidate| candidate == "*" || candidate.trim_start_matches("W/") == etag)
+    }
+}
+
+pub fn weak_etag(fingerprint: &str) -> String
+{
+    format!("W/\"{}\"", fingerprint)
+}
+
+pub fn not_modified(etag: &str) -> Response
+{
+    StatusCode::NOT_MODIFIED.with_etag(etag)
+}
+
+pub trait WithETag: IntoResponse + Sized
+{
+    fn with_etag(self, etag: &str) -> Response
+    {
+        let mut response = self.into_response();
+        if let Ok(value) = HeaderValue::from_str(etag)
+        {
+            response.headers_mut().insert(header::ETAG, value);
+        }
+        response
+    }
+}
+
+impl<T: IntoResponse> WithETag for T {}
@@ -0,0 +1,137 @@
+use axum::
+{
+    extract::State,
+    response::{IntoResponse, Json},
+};
+use serde_json::json;
+use tracing::{info, warn};
+
+use crate::
+{
+    api::json::ApiJson,
+    error::{AppError, ProjectErrorCode},
+    handlers::project_handler,
+    model::
+    {
+        account::{AccountCleanupPayload, AccountCleanupStep, AccountResourceKind, AccountResources},
+        deployment::DeploymentStatus,
+    },
+    services::{account_service, database_service, deployment_service, invitation_service, jwt::Claims, project_service},
+    state::AppState,
+};
+
+pub async fn get_my_resources_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+) -> Result<Json<AccountResources>, AppError>
+{
+    let pool = &state.db_pool;
+    let login = claims.sub.as_str();
+
+    let (owned, participations, database, pending_invitations, deployments) = tokio::try_join!(
+        project_service::get_projects_by_owner(pool, login, true),
+        project_service::get_participating_projects(pool, login, true),
+        database_service::get_database_by_owner(pool, login),
+        invitation_service::get_pending_invitations(pool, login, state.config().invitation_expiry_days),
+        deployment_service::get_deployments_by_owner(pool, login),
+    )?;
+
+    let active_deployments = deployments.into_iter()
+        .filter(|deployment| !matches!(deployment.status, DeploymentStatus::Succeeded | DeploymentStatus::Failed))
+        .collect();
+
+    record_account_action(&state, login, "inventory", None).await;
+
+    Ok(Json(AccountResources
+    {
+        owned_projects: owned,
+        participations,
+        database: database.map(|db| database_service::create_masked_db_details_response(db, &state.config())),
+        pending_invitations,
+        active_deployments,
+    }))
+}
+
+// Les étapes sont indépendantes : un échec est signalé et le nettoyage passe à la ressource suivante.
+pub async fn cleanup_my_resources_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    ApiJson(payload): ApiJson<AccountCleanupPayload>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let pool = &state.db_pool;
+    let login = claims.sub.as_str();
+
+    if payload.confirmation != login
+    {
+        return Err(AppError::BadRequest("Type your login in 'confirmation' to delete all your resources.".to_string()));
+    }
+
+    if deployment_service::has_pending_deployment(pool, login).await?
+    {
+        return Err(ProjectErrorCode::DeploymentAlreadyInProgress.into());
+    }
+
+    info!("User '{}' started a cleanup of all their resources.", login);
+
+    let mut steps = Vec::new();
+
+    for project in project_service::get_projects_by_owner(pool, login, true).await?
+    {
+        let result = project_handler::purge_project(&state, &project, login, false).await;
+        steps.push(cleanup_step(AccountResourceKind::Project, &project.name, result));
+    }
+
+    for project in project_service::get_participating_projects(pool, login, true).await?
+    {
+        let result = project_service::remove_participant_from_project(pool, project.id, login).await;
+        steps.push(cleanup_step(AccountResourceKind::Participation, &project.name, result));
+    }
+
+    for invitation in invitation_service::get_pending_invitations(pool, login, state.config().invitation_expiry_days).await?
+    {
+        let result = invitation_service::decline_invitation(pool, invitation.id, login).await.map(|_| ());
+        steps.push(cleanup_step(AccountResourceKind::Invitation, &invitation.project_name, result));
+    }
+
+    // Une base liée à un projet a déjà été supprimée avec lui ; il ne reste que la base indépendante.
+    if let Some(db) = database_service::get_database_by_owner(pool, login).await?
+    {
+        let result = database_service::deprovision_database(pool, &state.mariadb_pool, db.id, login, false).await;
+        steps.push(cleanup_step(AccountResourceKind::Database, &db.database_name, result));
+    }
+
+    let failed = steps.iter().filter(|step| !step.ok).count();
+    info!("Cleanup of '{}' done: {} resource(s), {} failed.", login, steps.len(), failed);
+
+    record_account_action(&state, login, "cleanup", Some(json!(steps))).await;
+
+    Ok(Json(json!({
+        "complete": failed == 0,
+        "results": steps,
+    })))
+}
+
+fn cleanup_step(kind: AccountResourceKind, name: &str, result: Result<(), AppError>) -> AccountCleanupStep
+{
+    if let Err(e) = &result
+    {
+        warn!("Account cleanup could not remove {:?} '{}': {}", kind, name, e);
+    }
+
+    AccountCleanupStep
+    {
+        kind,
+        name: name.to_string(),
+        ok: result.is_ok(),
+        error: result.err().map(|e| e.to_string()),
+    }
+}
+
+async fn record_account_action(state: &AppState, login: &str, action: &str, results: Option<serde_json::Value>)
+{
+    if let Err(e) = account_service::record_account_action(&state.db_pool, login, action, results).await
+    {
+        warn!("Account action '{}' of '{}' was not recorded: {}", action, login, e);
+    }
+}
@@ -1,31 +1,269 @@
-use axum::{extract::State, response::Json, response::IntoResponse};
+use std::{collections::HashSet, sync::atomic::{AtomicBool, Ordering}, time::Duration};
+use axum::{body::{Body, Bytes}, extract::{Path, Query, State}, http::header, response::Json, response::IntoResponse};
+use futures::{channel::mpsc, SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
 use serde_json::json;
-use crate::{error::AppError, services::{docker_service, project_service}, state::AppState};
+use tracing::{error, info, warn};
+use crate::{build_info::build_info, config::Config, api::list_params::{ListParams, ListSpec, SortOrder}, error::AppError, services::{audit_service, auth_service, availability_service, build_dir_service, cleanup_service, crypto_service, deployment_service, key_rotation_service, sbom_service, scan_service, docker_service::{self, ContainerRef}, jwt::Claims, pool_service, project_event_service, project_service, webhook_service}, state::AppState};
 use time::{OffsetDateTime, format_description::well_known::Rfc3339};
-use crate::model::project::DownProjectInfo;
+use crate::model::project::{BroadcastOutcome, BroadcastResult, CachedGlobalMetrics, DownProjectInfo, GlobalMetrics, Project, ProjectExportRow, ProjectFilters, ProjectSourceType, StaleImageInfo};
+use crate::model::audit::{AuditFilters, AuditRecord, AuditTargetType};
+use crate::model::project_event::ProjectEventAction;
+use crate::model::webhook_delivery::WebhookDeliveryOutcome;
+
+pub struct AdminProjectsList;
+
+impl ListSpec for AdminProjectsList
+{
+    const DEFAULT_PER_PAGE: u32 = 50;
+    const MAX_PER_PAGE: u32 = 500;
+    const SORTABLE: &'static [&'static str] = &["name", "owner", "docker_host", "created_at", "updated_at", "last_deployed_at", "archived_at"];
+    const DEFAULT_SORT: &'static str = "created_at";
+    const DEFAULT_ORDER: SortOrder = SortOrder::Desc;
+}
+
+pub struct AdminAuditList;
+
+impl ListSpec for AdminAuditList
+{
+    const DEFAULT_PER_PAGE: u32 = 50;
+    const MAX_PER_PAGE: u32 = 500;
+    const SORTABLE: &'static [&'static str] = &["created_at", "actor", "action", "target_id"];
+    const DEFAULT_SORT: &'static str = "created_at";
+    const DEFAULT_ORDER: SortOrder = SortOrder::Desc;
+}
+
+#[derive(Deserialize)]
+pub struct ExportQuery
+{
+    format: Option<String>,
+    #[serde(default)]
+    include_deleted: bool,
+}
+
+#[derive(Deserialize)]
+pub struct GlobalMetricsQuery
+{
+    #[serde(default)]
+    force: bool,
+}
+
+#[derive(Deserialize)]
+pub struct InspectQuery
+{
+    lines: Option<u32>,
+}
+
+#[derive(Deserialize)]
+pub struct StaleImagesQuery
+{
+    older_than_days: Option<i32>,
+}
+
+#[derive(Deserialize)]
+pub struct SbomSearchQuery
+{
+    package: String,
+}
+
+#[derive(Deserialize)]
+pub struct AuthAttemptsQuery
+{
+    login: Option<String>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    from: Option<OffsetDateTime>,
+}
+
+#[derive(Deserialize)]
+pub struct DeployStatsQuery
+{
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    from: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    to: Option<OffsetDateTime>,
+    source_type: Option<ProjectSourceType>,
+}
+
+const DEPLOY_STATS_DEFAULT_DAYS: i64 = 30;
+
+#[derive(Deserialize)]
+pub struct BroadcastQuery
+{
+    owner: Option<String>,
+    #[serde(default)]
+    dry_run: bool,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum BroadcastAction
+{
+    Stop,
+    Start,
+}
+
+impl BroadcastAction
+{
+    fn as_str(self) -> &'static str
+    {
+        match self
+        {
+            Self::Stop => "stop",
+            Self::Start => "start",
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct WebhookDeliveriesQuery
+{
+    outcome: Option<WebhookDeliveryOutcome>,
+}
+
+const INSPECT_DEFAULT_LOG_LINES: u32 = 100;
+const INSPECT_MAX_LOG_LINES: u32 = 1000;
+const INSPECT_RECENT_DEPLOYMENTS: i64 = 10;
+const INSPECT_RECENT_EVENTS: i64 = 20;
+const STALE_IMAGES_DEFAULT_DAYS: i32 = 30;
+
+#[derive(Clone, Copy)]
+enum ExportFormat
+{
+    Csv,
+    Json,
+}
+
+const EXPORT_CSV_HEADER: &[&str] = &[
+    "name", "owner", "participants", "source_type", "source_url",
+    "deployed_image_tag", "created_at", "updated_at", "database_name", "has_volume",
+    "status", "archived_at", "expires_at",
+];
 
 pub async fn list_all_projects_handler(
-    State(state): State<AppState>
+    State(state): State<AppState>,
+    list_params: ListParams<AdminProjectsList>,
+    Query(filters): Query<ProjectFilters>,
 ) -> Result<impl IntoResponse, AppError> 
 {
-    let projects = project_service::get_all_projects(&state.db_pool).await?;
-    Ok(Json(json!({ "projects": projects })))
+    let (projects, total) = project_service::get_projects_page(
+        &state.db_pool,
+        &filters,
+        list_params.sort_by,
+        list_params.order,
+        i64::from(list_params.per_page),
+        list_params.offset(),
+    ).await?;
+    Ok(Json(list_params.page_of(&projects, total as usize)?))
+}
+
+pub async fn list_audit_log_handler(
+    State(state): State<AppState>,
+    list_params: ListParams<AdminAuditList>,
+    Query(filters): Query<AuditFilters>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let (entries, total) = audit_service::get_entries_page(
+        &state.db_pool,
+        &filters,
+        list_params.sort_by,
+        list_params.order,
+        i64::from(list_params.per_page),
+        list_params.offset(),
+    ).await?;
+    Ok(Json(list_params.page_of(&entries, total as usize)?))
 }
 
+// Une copie périmée est encore servie pendant qu'un seul rafraîchissement tourne en arrière-plan ;
+// seuls l'absence de cache ou `force` font attendre la requête.
 pub async fn get_global_metrics_handler(
     State(state): State<AppState>,
+    Query(query): Query<GlobalMetricsQuery>,
 ) -> Result<impl IntoResponse, AppError> 
 {
+    let cached = state.global_metrics.lock().map_err(|_| AppError::InternalServerError)?.clone();
 
-    let mut metrics = docker_service::get_global_container_stats(
-        &state.docker_client,
-        &state.config.app_prefix,
-    ).await?;
+    let cached = match cached
+    {
+        Some(cached) if !query.force => cached,
+        _ => return Ok(Json(json!({ "metrics": refresh_global_metrics(&state).await?, "stale": false }))),
+    };
+
+    let age = (OffsetDateTime::now_utc() - cached.computed_at).whole_seconds();
+    let stale = age >= state.config().metrics_cache_fresh_seconds;
+
+    if stale && claim_metrics_refresh(&state.global_metrics_refreshing)
+    {
+        let state = state.clone();
+        tokio::spawn(async move
+        {
+            if let Err(e) = refresh_global_metrics(&state).await
+            {
+                warn!("Background refresh of the global metrics failed, the stale copy is kept: {}", e);
+            }
+            state.global_metrics_refreshing.store(false, Ordering::Release);
+        });
+    }
+
+    Ok(Json(json!({ "metrics": cached, "stale": stale })))
+}
+
+// Un seul appelant l'obtient jusqu'à la fin du rafraîchissement ; les autres servent la copie périmée.
+fn claim_metrics_refresh(refreshing: &AtomicBool) -> bool
+{
+    refreshing.compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire).is_ok()
+}
+
+async fn refresh_global_metrics(state: &AppState) -> Result<CachedGlobalMetrics, AppError>
+{
+    let cached = CachedGlobalMetrics
+    {
+        metrics: compute_global_metrics(state).await?,
+        computed_at: OffsetDateTime::now_utc(),
+    };
+
+    if let Ok(mut slot) = state.global_metrics.lock()
+    {
+        *slot = Some(cached.clone());
+    }
+
+    Ok(cached)
+}
+
+async fn compute_global_metrics(state: &AppState) -> Result<GlobalMetrics, AppError>
+{
+    let mut metrics = GlobalMetrics
+    {
+        total_projects: 0,
+        running_containers: 0,
+        total_cpu_usage: 0.0,
+        total_memory_usage_mb: 0.0,
+        average_uptime_percent_30d: None,
+        degraded_hosts: Vec::new(),
+    };
+
+    for (host_name, docker) in &state.docker_hosts
+    {
+        let host_metrics = match docker_service::get_global_container_stats(docker, &state.config().app_prefix).await
+        {
+            Ok(host_metrics) => host_metrics,
+            Err(e) =>
+            {
+                warn!("Failed to collect metrics from Docker host '{}', leaving it out: {}", host_name, e);
+                metrics.degraded_hosts.push(host_name.clone());
+                continue;
+            }
+        };
+
+        metrics.running_containers += host_metrics.running_containers;
+        metrics.total_cpu_usage += host_metrics.total_cpu_usage;
+        metrics.total_memory_usage_mb += host_metrics.total_memory_usage_mb;
+    }
     
     let projects = project_service::get_all_projects(&state.db_pool).await?;
     metrics.total_projects = projects.len() as i64;
+    metrics.degraded_hosts.sort();
+    metrics.average_uptime_percent_30d = availability_service::get_fleet_average_uptime(&state.db_pool, 30).await?;
 
-    Ok(Json(metrics))
+    Ok(metrics)
 }
 
 pub async fn get_down_projects_handler(
@@ -35,28 +273,714 @@ pub async fn get_down_projects_handler(
     let all_projects = project_service::get_all_projects(&state.db_pool).await?;
     let mut down_projects: Vec<DownProjectInfo> = Vec::new();
 
+    // Les projets arrêtés volontairement par un stop-all ne sont pas des pannes.
+    let broadcast_stopped: HashSet<i32> = project_service::get_broadcast_stopped_project_ids(&state.db_pool).await?.into_iter().collect();
+
     let now = OffsetDateTime::now_utc();
 
-    for project in all_projects 
+    for project in all_projects.into_iter().filter(|project| !broadcast_stopped.contains(&project.id))
     {
-        if let Some(details) = docker_service::inspect_container_details(&state.docker_client, &project.container_name).await?
+        let docker = state.docker_for(&project.docker_host)?;
+        if let Some(details) = docker_service::inspect_container_details(docker, ContainerRef::ByName(&project.container_name)).await?
             && let Some(container_state) = details.state
-                && let Some(is_running) = container_state.running
-                    && !is_running
-                        && let Some(finished_at_str) = container_state.finished_at
-                            && let Ok(stopped_at) = OffsetDateTime::parse(&finished_at_str, &Rfc3339)
-                            {
-                                let downtime_seconds = (now - stopped_at).as_seconds_f64() as i64;
-                                down_projects.push(DownProjectInfo 
-                                {
-                                    project: project.clone(),
-                                    stopped_at: finished_at_str,
-                                    downtime_seconds,
-                                });
-                            }
+        {
+            let timing = docker_service::container_timing(&container_state, now);
+            if let (Some(stopped_at), Some(downtime_seconds)) = (timing.finished_at, timing.downtime_seconds)
+            {
+                down_projects.push(DownProjectInfo 
+                {
+                    project: project.clone(),
+                    stopped_at,
+                    downtime_seconds,
+                });
+            }
+        }
     }
 
     down_projects.sort_by(|a, b| b.downtime_seconds.cmp(&a.downtime_seconds));
 
-    Ok(Json(json!({ "down_projects": down_projects })))
-}
\ No newline at end of file
+    Ok(Json(json!({ "down_projects": down_projects, "maintenance_window": !broadcast_stopped.is_empty() })))
+}
+
+pub async fn get_stale_images_handler(
+    State(state): State<AppState>,
+    Query(query): Query<StaleImagesQuery>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let older_than_days = query.older_than_days.unwrap_or(STALE_IMAGES_DEFAULT_DAYS);
+    if older_than_days < 1
+    {
+        return Err(AppError::BadRequest("'older_than_days' must be at least 1.".to_string()));
+    }
+
+    let projects = project_service::get_stale_image_projects(&state.db_pool, older_than_days, state.config().grype_enabled).await?;
+
+    let now = OffsetDateTime::now_utc();
+    let mut stale_images: Vec<StaleImageInfo> = projects
+        .into_iter()
+        .map(|project| StaleImageInfo
+        {
+            image_age_days: project.image_created_at.map(|created_at| (now - created_at).whole_days()),
+            scan_age_days: project.last_scanned_at.map(|scanned_at| (now - scanned_at).whole_days()),
+            project,
+        })
+        .collect();
+
+    // Un projet jamais scanné passe devant tous les autres.
+    stale_images.sort_by_key(|info|
+    {
+        let scan_age = if state.config().grype_enabled { info.scan_age_days.unwrap_or(i64::MAX) } else { 0 };
+        std::cmp::Reverse(scan_age.max(info.image_age_days.unwrap_or(0)))
+    });
+
+    Ok(Json(json!({ "stale_images": stale_images })))
+}
+
+pub async fn inspect_project_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(project_id): Path<i32>,
+    Query(query): Query<InspectQuery>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let project = project_service::get_project_by_id_and_owner(&state.db_pool, project_id, &claims.sub, true)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Project with ID {} not found.", project_id)))?;
+
+    info!("Admin '{}' inspected project '{}' (ID {}).", claims.sub, project.name, project.id);
+
+    let docker = state.docker_for(&project.docker_host)?;
+    let lines = query.lines.unwrap_or(INSPECT_DEFAULT_LOG_LINES).clamp(1, INSPECT_MAX_LOG_LINES);
+
+    // L'inspection expose les logs du propriétaire : elle laisse une trace.
+    audit_service::record(&state.db_pool, AuditRecord
+    {
+        actor: &claims.sub,
+        action: "project_inspect",
+        target_type: AuditTargetType::Project,
+        target_id: project.id,
+        target_name: Some(&project.name),
+        performed_by_admin: project.owner != claims.sub,
+        details: Some(json!({ "log_lines": lines })),
+    }).await;
+
+    let lines = lines.to_string();
+    let container_ref = docker_service::resolve_container(docker, &project.container_name, project.container_id.as_deref()).await;
+
+    let (container, logs, metrics, deployments, last_scan, events) = tokio::join!(
+        docker_service::inspect_container_details(docker, container_ref),
+        docker_service::get_container_logs(docker, container_ref, &lines),
+        docker_service::get_container_metrics(docker, container_ref),
+        deployment_service::get_recent_deployments_for_project(&state.db_pool, &project.name, INSPECT_RECENT_DEPLOYMENTS),
+        scan_service::get_last_result(&state.db_pool, &project.name),
+        project_event_service::get_project_events(&state.db_pool, project.id, INSPECT_RECENT_EVENTS),
+    );
+
+    let container = container.map(|details| details.map(|details|
+    {
+        let container_state = details.state.unwrap_or_default();
+        json!({
+            "id": details.id,
+            "status": container_state.status,
+            "running": container_state.running,
+            "oom_killed": container_state.oom_killed,
+            "exit_code": container_state.exit_code,
+            "error": container_state.error,
+            "started_at": container_state.started_at,
+            "finished_at": container_state.finished_at,
+            "restart_count": details.restart_count,
+            "mounts": details.mounts,
+        })
+    }));
+
+    Ok(Json(json!({
+        "project": project,
+        "container": inspect_section(container),
+        "logs": inspect_section(logs),
+        "metrics": inspect_section(metrics),
+        "deployments": inspect_section(deployments),
+        "last_scan": inspect_section(last_scan),
+        "events": inspect_section(events),
+    })))
+}
+
+fn inspect_section<T: Serialize>(result: Result<T, AppError>) -> serde_json::Value
+{
+    match result
+    {
+        Ok(value) => json!({ "data": value }),
+        Err(e) => json!({ "error": e.error_code(), "message": e.public_message() }),
+    }
+}
+
+pub async fn get_reconciliation_report_handler(
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let report = state.last_reconciliation.lock().map_err(|_| AppError::InternalServerError)?.clone();
+    let lost_projects = project_service::get_lost_projects(&state.db_pool).await?;
+    let status_drift = project_service::get_status_drift(&state.db_pool, state.config().status_drift_threshold_seconds).await?;
+
+    Ok(Json(json!({ "report": report, "lost_projects": lost_projects, "status_drift": status_drift })))
+}
+
+pub async fn list_docker_hosts_handler(
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> 
+{
+    let project_counts = project_service::count_projects_per_docker_host(&state.db_pool).await?;
+    let mut hosts = Vec::with_capacity(state.config().docker_hosts.len());
+
+    for host in &state.config().docker_hosts
+    {
+        let docker = state.docker_for(&host.name)?;
+        let capacity = docker_service::get_host_capacity(docker, &host.name, &state.config().app_prefix).await?;
+
+        hosts.push(json!({
+            "capacity": capacity,
+            "projects": project_counts.get(&host.name).copied().unwrap_or(0),
+        }));
+    }
+
+    // Les sources sont clonées sur ce serveur, pas sur les hôtes Docker.
+    let build_directory = build_dir_service::get_space(&state.config());
+
+    Ok(Json(json!({ "docker_hosts": hosts, "build_directory": build_directory, "build": build_info() })))
+}
+
+pub async fn get_config_summary_handler(
+    State(state): State<AppState>,
+) -> Json<serde_json::Value>
+{
+    Json(json!({ "config": state.config().summary(), "build": build_info() }))
+}
+
+// Rien ne change si la nouvelle configuration est invalide.
+pub async fn reload_config_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+) -> Result<impl IntoResponse, AppError>
+{
+    let reloaded = Config::from_env().map_err(|e|
+    {
+        error!("Configuration reload by '{}' rejected: {}", claims.sub, e);
+        AppError::BadRequest(format!("The configuration is invalid, nothing was reloaded: {}", e))
+    })?;
+
+    let changes = state.reload_config(&reloaded);
+
+    // Les réglages lus au démarrage que le fichier change encore ne s'appliqueront qu'au prochain.
+    let restart_required: Vec<String> = match (serde_json::to_value(state.config().summary()), serde_json::to_value(reloaded.summary()))
+    {
+        (Ok(serde_json::Value::Object(applied)), Ok(serde_json::Value::Object(wanted))) => wanted.into_iter()
+            .filter(|(key, value)| applied.get(key) != Some(value))
+            .map(|(key, _)| key)
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    info!(
+        "Configuration reloaded by '{}': {} setting(s) changed{}.",
+        claims.sub,
+        changes.len(),
+        if restart_required.is_empty() { String::new() } else { format!(", restart required for {}", restart_required.join(", ")) }
+    );
+
+    Ok(Json(json!({ "changed": changes, "restart_required": restart_required })))
+}
+
+pub async fn get_pools_handler(
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let pools = [
+        pool_service::get_stats("postgres", &state.db_pool),
+        pool_service::get_stats("mariadb", &state.mariadb_pool),
+    ];
+
+    Ok(Json(json!({ "pools": pools, "acquire_timeouts": pool_service::acquire_timeouts() })))
+}
+
+pub async fn get_deploy_stats_handler(
+    State(state): State<AppState>,
+    Query(query): Query<DeployStatsQuery>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let to = query.to.unwrap_or_else(OffsetDateTime::now_utc);
+    let from = query.from.unwrap_or(to - time::Duration::days(DEPLOY_STATS_DEFAULT_DAYS));
+    if from >= to
+    {
+        return Err(AppError::BadRequest("'from' must be before 'to'.".to_string()));
+    }
+
+    let phases = deployment_service::get_deploy_stats(&state.db_pool, from, to, query.source_type).await?;
+
+    Ok(Json(json!({
+        "from": from.format(&Rfc3339).unwrap_or_default(),
+        "to": to.format(&Rfc3339).unwrap_or_default(),
+        "phases": phases,
+    })))
+}
+
+pub async fn export_projects_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Query(query): Query<ExportQuery>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let format = match query.format.as_deref().unwrap_or("csv")
+    {
+        "csv" => ExportFormat::Csv,
+        "json" => ExportFormat::Json,
+        other => return Err(AppError::BadRequest(format!("Unsupported export format '{}'. Use 'csv' or 'json'.", other))),
+    };
+
+    info!("Admin '{}' exported the project inventory.", claims.sub);
+
+    audit_service::record(&state.db_pool, AuditRecord
+    {
+        actor: &claims.sub,
+        action: "projects_export",
+        target_type: AuditTargetType::Inventory,
+        target_id: 0,
+        target_name: None,
+        performed_by_admin: true,
+        details: Some(json!({ "format": query.format.as_deref().unwrap_or("csv"), "include_deleted": query.include_deleted })),
+    }).await;
+
+    let (mut tx, rx) = mpsc::channel::<Result<Bytes, std::io::Error>>(32);
+    let deletion_grace_hours = state.config().project_deletion_grace_hours;
+
+    tokio::spawn(async move
+    {
+        let mut rows = project_service::export_all(&state.db_pool, query.include_deleted, deletion_grace_hours);
+        let mut first = true;
+
+        let opening = match format
+        {
+            ExportFormat::Csv => encode_csv_record(EXPORT_CSV_HEADER.iter().map(|s| s.to_string())),
+            ExportFormat::Json => Ok(b"[".to_vec()),
+        };
+
+        if let Ok(bytes) = opening
+            && tx.send(Ok(Bytes::from(bytes))).await.is_err()
+        {
+            return;
+        }
+
+        while let Some(row) = rows.next().await
+        {
+            let chunk = match row
+            {
+                Ok(row) => encode_export_row(&row, format, first),
+                Err(_) => Err(std::io::Error::other("Failed to fetch project export row")),
+            };
+            first = false;
+
+            let failed = chunk.is_err();
+            if tx.send(chunk.map(Bytes::from)).await.is_err() || failed
+            {
+                return;
+            }
+        }
+
+        if let ExportFormat::Json = format
+        {
+            let _ = tx.send(Ok(Bytes::from_static(b"]"))).await;
+        }
+    });
+
+    let (content_type, filename) = match format
+    {
+        ExportFormat::Csv => ("text/csv; charset=utf-8", "hangar-projects.csv"),
+        ExportFormat::Json => ("application/json", "hangar-projects.json"),
+    };
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", filename)),
+        ],
+        Body::from_stream(rx),
+    ))
+}
+
+fn encode_export_row(row: &ProjectExportRow, format: ExportFormat, first: bool) -> Result<Vec<u8>, std::io::Error>
+{
+    match format
+    {
+        ExportFormat::Csv => encode_csv_record([
+            row.name.clone(),
+            row.owner.clone(),
+            row.participants.join(";"),
+            serde_json::to_value(row.source).ok().and_then(|v| v.as_str().map(str::to_string)).unwrap_or_default(),
+            row.source_url.clone(),
+            row.deployed_image_tag.clone(),
+            row.created_at.format(&Rfc3339).unwrap_or_default(),
+            row.updated_at.format(&Rfc3339).unwrap_or_default(),
+            row.database_name.clone().unwrap_or_default(),
+            row.has_volume.to_string(),
+            row.status.and_then(|status| serde_json::to_value(status).ok()).and_then(|v| v.as_str().map(str::to_string)).unwrap_or_default(),
+            row.archived_at.and_then(|at| at.format(&Rfc3339).ok()).unwrap_or_default(),
+            row.expires_at.and_then(|at| at.format(&Rfc3339).ok()).unwrap_or_default(),
+        ]),
+        ExportFormat::Json =>
+        {
+            let mut bytes = if first { Vec::new() } else { b",".to_vec() };
+            serde_json::to_writer(&mut bytes, row).map_err(|e|
+            {
+                error!("Failed to serialize project export row: {}", e);
+                std::io::Error::other(e)
+            })?;
+            Ok(bytes)
+        }
+    }
+}
+
+fn encode_csv_record<I>(fields: I) -> Result<Vec<u8>, std::io::Error>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(fields).map_err(|e|
+    {
+        error!("Failed to write CSV record: {}", e);
+        std::io::Error::other(e)
+    })?;
+    writer.into_inner().map_err(|e| std::io::Error::other(e.to_string()))
+}
+
+pub async fn list_auth_attempts_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Query(query): Query<AuthAttemptsQuery>,
+) -> Result<impl IntoResponse, AppError>
+{
+    info!("Admin '{}' listed auth attempts (login: {:?}, from: {:?}).", claims.sub, query.login, query.from);
+
+    let attempts = auth_service::get_auth_attempts(&state.db_pool, query.login.as_deref(), query.from).await?;
+    Ok(Json(json!({ "attempts": attempts })))
+}
+
+// Par lots : un parcours interrompu peut simplement être relancé.
+pub async fn reencrypt_secrets_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+) -> Result<impl IntoResponse, AppError>
+{
+    warn!("Admin '{}' started re-encrypting the stored secrets.", claims.sub);
+
+    let projects = key_rotation_service::reencrypt_project_env_vars(&state.db_pool, &state.config().encryption_key).await?;
+    let databases = key_rotation_service::reencrypt_database_passwords(&state.db_pool, &state.config().encryption_key).await?;
+
+    info!(
+        "Re-encryption finished: projects {:?}, databases {:?}.",
+        projects, databases
+    );
+
+    Ok(Json(json!({ "projects": projects, "databases": databases })))
+}
+
+pub async fn get_crypto_status_handler(
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError>
+{
+    Ok(Json(json!({
+        "algorithm": state.config().encryption_key.algorithm().as_str(),
+        "previous_keys": state.config().encryption_key.previous_count(),
+        "legacy_decryptions": crypto_service::legacy_decryptions(),
+    })))
+}
+
+pub async fn stop_all_containers_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Query(query): Query<BroadcastQuery>,
+) -> Result<impl IntoResponse, AppError>
+{
+    run_container_broadcast(&state, &claims, BroadcastAction::Stop, query).await
+}
+
+pub async fn start_all_containers_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Query(query): Query<BroadcastQuery>,
+) -> Result<impl IntoResponse, AppError>
+{
+    run_container_broadcast(&state, &claims, BroadcastAction::Start, query).await
+}
+
+// Start-all ne relance que les projets marqués par stop-all : ceux que leur propriétaire avait arrêtés le restent.
+async fn run_container_broadcast(
+    state: &AppState,
+    claims: &Claims,
+    action: BroadcastAction,
+    query: BroadcastQuery,
+) -> Result<Json<serde_json::Value>, AppError>
+{
+    info!(
+        "Admin '{}' requested {}-all (owner: {:?}, dry run: {}).",
+        claims.sub, action.as_str(), query.owner, query.dry_run
+    );
+
+    let broadcast_stopped: HashSet<i32> = project_service::get_broadcast_stopped_project_ids(&state.db_pool).await?.into_iter().collect();
+    let projects: Vec<Project> = project_service::get_all_projects(&state.db_pool).await?
+        .into_iter()
+        .filter(|project| query.owner.as_ref().is_none_or(|owner| &project.owner == owner))
+        .collect();
+
+    let timeout = Duration::from_secs(state.config().broadcast_container_timeout_seconds);
+    let dry_run = query.dry_run;
+
+    let mut results: Vec<BroadcastResult> = futures::stream::iter(projects)
+        .map(|project|
+        {
+            let targeted = match action
+            {
+                BroadcastAction::Stop => project.intended_running,
+                BroadcastAction::Start => broadcast_stopped.contains(&project.id),
+            };
+            broadcast_project(state, project, action, targeted, dry_run, timeout, &claims.sub)
+        })
+        .buffer_unordered(state.config().broadcast_max_concurrent.max(1))
+        .collect()
+        .await;
+
+    results.sort_by(|a, b| a.project.cmp(&b.project));
+
+    let failed = results.iter().filter(|result| result.outcome == BroadcastOutcome::Failed).count();
+    info!(
+        "{}-all by '{}' done: {} project(s), {} failed (dry run: {}).",
+        action.as_str(), claims.sub, results.len(), failed, dry_run
+    );
+
+    if let Err(e) = project_service::record_container_broadcast(
+        &state.db_pool,
+        action.as_str(),
+        &claims.sub,
+        query.owner.as_deref(),
+        dry_run,
+        json!(results),
+    ).await
+    {
+        error!("Could not record {}-all by '{}' in the audit log: {}", action.as_str(), claims.sub, e);
+    }
+
+    let maintenance_window = !project_service::get_broadcast_stopped_project_ids(&state.db_pool).await?.is_empty();
+
+    Ok(Json(json!(
+    {
+        "action": action.as_str(),
+        "dry_run": dry_run,
+        "maintenance_window": maintenance_window,
+        "results": results,
+    })))
+}
+
+async fn broadcast_project(
+    state: &AppState,
+    project: Project,
+    action: BroadcastAction,
+    targeted: bool,
+    dry_run: bool,
+    timeout: Duration,
+    admin_login: &str,
+) -> BroadcastResult
+{
+    let result = |outcome: BroadcastOutcome, detail: Option<String>| BroadcastResult
+    {
+        project_id: project.id,
+        project: project.name.clone(),
+        outcome,
+        detail,
+    };
+
+    if !targeted
+    {
+        let reason = match action
+        {
+            BroadcastAction::Stop => "not running",
+            BroadcastAction::Start => "not stopped by stop-all",
+        };
+        return result(BroadcastOutcome::Skipped, Some(reason.to_string()));
+    }
+
+    if dry_run
+    {
+        return result(BroadcastOutcome::Ok, Some("dry run, no action taken".to_string()));
+    }
+
+    let docker = match state.docker_for(&project.docker_host)
+    {
+        Ok(docker) => docker,
+        Err(e) => return result(BroadcastOutcome::Failed, Some(e.to_string())),
+    };
+
+    for container_name in project.container_names()
+    {
+        let operation = async
+        {
+            match action
+            {
+                BroadcastAction::Stop => docker_service::stop_container_by_name(docker, &container_name).await,
+                BroadcastAction::Start => docker_service::start_container_by_name(docker, &container_name).await,
+            }
+        };
+
+        match tokio::time::timeout(timeout, operation).await
+        {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) =>
+            {
+                warn!("{}-all failed on container '{}': {}", action.as_str(), container_name, e);
+                return result(BroadcastOutcome::Failed, Some(e.to_string()));
+            }
+            Err(_) =>
+            {
+                warn!("{}-all timed out on container '{}'.", action.as_str(), container_name);
+                return result(BroadcastOutcome::Failed, Some(format!("timed out after {}s", timeout.as_secs())));
+            }
+        }
+    }
+
+    if let Err(e) = project_service::set_project_stopped_by_broadcast(&state.db_pool, project.id, action == BroadcastAction::Stop).await
+    {
+        return result(BroadcastOutcome::Failed, Some(e.to_string()));
+    }
+
+    let event_action = match action
+    {
+        BroadcastAction::Stop => ProjectEventAction::Stop,
+        BroadcastAction::Start => ProjectEventAction::Start,
+    };
+    let details = Some(json!({ "broadcast": true }));
+    if let Err(e) = project_event_service::record_event(&state.db_pool, &project, event_action, admin_login, project.owner != admin_login, details).await
+    {
+        warn!("Could not record {}-all event of project '{}': {}", action.as_str(), project.name, e);
+    }
+
+    result(BroadcastOutcome::Ok, None)
+}
+
+pub async fn list_webhook_deliveries_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Query(query): Query<WebhookDeliveriesQuery>,
+) -> Result<impl IntoResponse, AppError>
+{
+    info!("Admin '{}' listed webhook deliveries (outcome: {:?}).", claims.sub, query.outcome);
+
+    let deliveries = webhook_service::get_recent_deliveries(&state.db_pool, query.outcome).await?;
+    Ok(Json(json!({ "deliveries": deliveries })))
+}
+
+pub async fn list_pending_cleanups_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+) -> Result<impl IntoResponse, AppError>
+{
+    info!("Admin '{}' listed pending cleanups.", claims.sub);
+
+    let cleanups = cleanup_service::get_pending_cleanups(&state.db_pool).await?;
+    Ok(Json(json!({ "pending_cleanups": cleanups })))
+}
+
+pub async fn search_sboms_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Query(query): Query<SbomSearchQuery>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let package = query.package.trim();
+    if package.len() < 2
+    {
+        return Err(AppError::BadRequest("The package search term must be at least 2 characters long.".to_string()));
+    }
+
+    info!("Admin '{}' searched SBOMs for package '{}'.", claims.sub, package);
+
+    let matches = sbom_service::search_package(&state.db_pool, package).await?;
+    Ok(Json(json!({ "projects": matches })))
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::state::InnerState;
+    use std::{sync::Arc, thread};
+
+    fn cached_metrics(age_seconds: i64) -> CachedGlobalMetrics
+    {
+        CachedGlobalMetrics
+        {
+            metrics: GlobalMetrics
+            {
+                total_projects: 7,
+                running_containers: 3,
+                total_cpu_usage: 0.0,
+                total_memory_usage_mb: 0.0,
+                average_uptime_percent_30d: None,
+                degraded_hosts: Vec::new(),
+            },
+            computed_at: OffsetDateTime::now_utc() - time::Duration::seconds(age_seconds),
+        }
+    }
+
+    async fn request_metrics(state: &AppState) -> serde_json::Value
+    {
+        let response = get_global_metrics_handler(State(state.clone()), Query(GlobalMetricsQuery { force: false })).await.unwrap().into_response();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[test]
+    fn only_one_caller_claims_the_refresh()
+    {
+        let refreshing = Arc::new(AtomicBool::new(false));
+
+        let claims: Vec<bool> = (0..16)
+            .map(|_| { let refreshing = refreshing.clone(); thread::spawn(move || claim_metrics_refresh(&refreshing)) })
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect();
+
+        assert_eq!(claims.iter().filter(|claimed| **claimed).count(), 1);
+
+        refreshing.store(false, Ordering::Release);
+        assert!(claim_metrics_refresh(&refreshing));
+    }
+
+    #[tokio::test]
+    async fn stale_copy_is_served_while_a_single_refresh_runs()
+    {
+        let state = InnerState::for_tests(Config::for_tests(&[("METRICS_CACHE_FRESH_SECONDS", "5")]).unwrap());
+        *state.global_metrics.lock().unwrap() = Some(cached_metrics(60));
+
+        // Le rafraîchissement lancé par la première requête n'a pas encore tourné : la seconde ne peut pas en lancer un autre.
+        let first = request_metrics(&state).await;
+        assert!(state.global_metrics_refreshing.load(Ordering::Acquire));
+        let second = request_metrics(&state).await;
+
+        for body in [first, second]
+        {
+            assert_eq!(body["stale"], true);
+            assert_eq!(body["metrics"]["total_projects"], 7);
+        }
+        assert!(!claim_metrics_refresh(&state.global_metrics_refreshing));
+    }
+
+    #[tokio::test]
+    async fn fresh_copy_is_served_without_refreshing()
+    {
+        let state = InnerState::for_tests(Config::for_tests(&[("METRICS_CACHE_FRESH_SECONDS", "5")]).unwrap());
+        *state.global_metrics.lock().unwrap() = Some(cached_metrics(0));
+
+        let body = request_metrics(&state).await;
+
+        assert_eq!(body["stale"], false);
+        assert!(body["metrics"]["computed_at"].is_string());
+        assert!(!state.global_metrics_refreshing.load(Ordering::Acquire));
+    }
+}
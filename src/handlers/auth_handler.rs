@@ -1,15 +1,20 @@
 use axum::
 {
-    extract::{Query, State}, 
+    extract::{ConnectInfo, Query, State}, 
+    http::{header, HeaderMap},
     response::{IntoResponse, Json}
 };
 use axum_extra::extract::cookie::{Cookie, SameSite};
 use axum_extra::extract::CookieJar;
 use serde::Deserialize;
 use serde_json::json;
+use std::net::SocketAddr;
 use time::OffsetDateTime;
+use tracing::warn;
 
 use crate::{error::AppError, state::AppState};
+use crate::model::auth_attempt::AuthFailureReason;
+use crate::services::auth_service;
 use crate::services::jwt::Claims;
 
 #[derive(Debug, Deserialize)]
@@ -19,20 +24,46 @@ pub struct AuthCallbackQuery
 }
 
 pub async fn auth_callback_handler(State(state): State<AppState>, 
+                                   ConnectInfo(addr): ConnectInfo<SocketAddr>,
+                                   headers: HeaderMap,
                                    Query(query): Query<AuthCallbackQuery>, 
                                    jar: CookieJar) -> Result<impl IntoResponse, AppError>
 {
-    let service = format!("{}/auth/callback", state.config.public_address);
+    let ip_address = addr.ip().to_string();
+    let user_agent = headers.get(header::USER_AGENT).and_then(|value| value.to_str().ok());
 
-    let url = format!("{}?service={}&ticket={}", state.config.cas_validation_url, service, &query.ticket);
+    if auth_service::count_recent_failures(&state.db_pool, &ip_address).await? >= state.config().auth_max_failed_per_minute
+    {
+        warn!("Throttling authentication attempts from {}", ip_address);
+        return Err(AppError::TooManyRequests("Too many failed authentication attempts. Please try again in a minute.".to_string()));
+    }
+
+    let service = format!("{}/auth/callback", state.config().public_address);
+
+    let url = format!("{}?service={}&ticket={}", state.config().cas_validation_url, service, &query.ticket);
     tracing::debug!("Validating CAS ticket at URL: {}", url);
-    let user = crate::services::auth_service::validate_ticket(&url, &state.http_client).await?;
+    let user = match auth_service::validate_ticket(&url, &state.http_client).await
+    {
+        Ok(user) => user,
+        Err(rejection) =>
+        {
+            warn!("CAS ticket validation from {} failed: {:?}", ip_address, rejection.reason);
+            record_attempt(&state, None, Some(rejection.reason), &ip_address, user_agent).await;
+            return Err(rejection.error);
+        }
+    };
 
-    let is_admin = state.config.admin_logins.contains(&user.login);
+    record_attempt(&state, Some(&user.login), None, &ip_address, user_agent).await;
+    if let Err(e) = auth_service::record_login(&state.db_pool, &user.login).await
+    {
+        warn!("Could not update last login of '{}': {}", user.login, e);
+    }
+
+    let is_admin = state.config().admin_logins.contains(&user.login);
 
     let token = crate::services::jwt::generate_jwt(
-        &state.config.jwt_secret,
-        state.config.jwt_expiration_seconds,
+        &state.config().jwt_secret,
+        state.config().jwt_expiration_seconds,
         &user.login,
         &user.name,
         &user.email,
@@ -68,9 +99,26 @@ pub async fn auth_callback_handler(State(state): State<AppState>,
 
 }
 
-pub async fn get_current_user_handler(claims: Claims) -> impl IntoResponse 
+async fn record_attempt(
+    state: &AppState,
+    login: Option<&str>,
+    failure_reason: Option<AuthFailureReason>,
+    ip_address: &str,
+    user_agent: Option<&str>,
+)
+{
+    if let Err(e) = auth_service::record_auth_attempt(&state.db_pool, login, failure_reason, ip_address, user_agent).await
+    {
+        warn!("Could not record auth attempt from {}: {}", ip_address, e);
+    }
+}
+
+pub async fn get_current_user_handler(State(state): State<AppState>, claims: Claims) -> Result<impl IntoResponse, AppError> 
 {
-    Json
+    let last_login_at = auth_service::get_last_login(&state.db_pool, &claims.sub).await?
+        .and_then(|at| at.format(&time::format_description::well_known::Rfc3339).ok());
+
+    Ok(Json
     (
         json!
         (
@@ -80,12 +128,12 @@ pub async fn get_current_user_handler(claims: Claims) -> impl IntoResponse
                     "login": claims.sub,
                     "name": claims.name,
                     "email": claims.email,
-                    "is_admin": claims.is_admin
-                    
+                    "is_admin": claims.is_admin,
+                    "last_login_at": last_login_at
                 }
             }
         )
-    )
+    ))
 }
 
 
@@ -1,28 +1,82 @@
 use axum::
 {
-    extract::{Path, State},
-    http::StatusCode,
+    extract::{ConnectInfo, Path, State},
+    http::{header, HeaderMap, StatusCode},
     response::{IntoResponse, Json},
 };
+use serde::Deserialize;
 use serde_json::json;
+use std::net::SocketAddr;
+use tracing::{info, warn};
 use crate::
 {
+    api::json::ApiJson,
     error::AppError,
-    services::{database_service, jwt::Claims, project_service},
+    model::{audit::{AuditRecord, AuditTargetType}, database::{RevealMethod, UpdateUserLimitsPayload}, db_template::CreateDatabasePayload},
+    ops::Rollback,
+    services::{audit_service, auth_service, database_service, db_template_service, jwt::Claims, project_service, quota_service::{self, QuotaDimension}},
     state::AppState,
 };
 
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RevealPasswordPayload
+{
+    ticket: Option<String>,
+}
+
+async fn record_database_audit(
+    state: &AppState,
+    claims: &Claims,
+    action: &str,
+    database_id: i32,
+    database: Option<(&str, &str)>,
+    details: Option<serde_json::Value>,
+)
+{
+    let (name, owner) = database.unzip();
+    audit_service::record(&state.db_pool, AuditRecord
+    {
+        actor: &claims.sub,
+        action,
+        target_type: AuditTargetType::Database,
+        target_id: database_id,
+        target_name: name,
+        performed_by_admin: claims.is_admin && owner != Some(claims.sub.as_str()),
+        details,
+    }).await;
+}
+
 pub async fn create_database_handler(
     State(state): State<AppState>,
     claims: Claims,
+    payload: Option<ApiJson<CreateDatabasePayload>>,
 ) -> Result<impl IntoResponse, AppError>
 {
-    let (db_record, password) = database_service::provision_database(
+    quota_service::ensure_available(&state.db_pool, &state.config(), &claims.sub, QuotaDimension::Databases).await?;
+
+    let template = match payload.and_then(|ApiJson(payload)| payload.template)
+    {
+        Some(name) => Some(db_template_service::get_template_script(&state.db_pool, &name).await?),
+        None => None,
+    };
+
+    let limits = database_service::resolve_user_limits(&state.db_pool, &state.config(), &claims.sub).await?;
+
+    let mut rollback = Rollback::new(&state, format!("database provisioning for '{}'", claims.sub));
+    let provisioned = database_service::provision_database(
         &state.db_pool,
         &state.mariadb_pool,
         &claims.sub,
-        &state.config.encryption_key,
-    ).await?;
+        &state.config().encryption_key,
+        template.as_ref(),
+        &limits,
+        &mut rollback,
+    ).await;
+    let (db_record, password) = rollback.finish(provisioned).await?;
+
+    let details = json!({ "template": db_record.template_name });
+    record_database_audit(&state, &claims, "database_provision", db_record.id, Some((&db_record.database_name, &db_record.owner_login)), Some(details)).await;
 
     let response = json!({
         "message": "Database created successfully.",
@@ -31,8 +85,11 @@ pub async fn create_database_handler(
             "database_name": db_record.database_name,
             "username": db_record.username,
             "password": password,
-            "host": state.config.mariadb_public_host,
-            "port": state.config.mariadb_public_port,
+            "password_masked": false,
+            "host": state.config().mariadb_public_host,
+            "port": state.config().mariadb_public_port,
+            "template_name": db_record.template_name,
+            "limits": db_record.limits,
         }
     });
 
@@ -48,19 +105,73 @@ pub async fn get_my_database_handler(
     {
         Some(db) =>
         {
-            let details = database_service::create_db_details_response(db, &state.config, &state.config.encryption_key)?;
+            let details = database_service::create_masked_db_details_response(db, &state.config());
             Ok(Json(json!({ "database": details })))
         }
         None => Err(AppError::NotFound("No database found for the current user.".to_string())),
     }
 }
 
+// Les administrateurs passent le même contrôle de propriétaire : ils ne révèlent pas les mots de passe des autres.
+pub async fn reveal_database_password_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    Path(db_id): Path<i32>,
+    ApiJson(payload): ApiJson<RevealPasswordPayload>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let db = database_service::get_database_by_id_and_owner(&state.db_pool, db_id, &claims.sub, false).await?
+        .ok_or(AppError::NotFound("Database not found or you are not the owner.".to_string()))?;
+
+    let method = if claims.is_fresh(state.config().db_reveal_max_auth_age_seconds)
+    {
+        RevealMethod::FreshSession
+    }
+    else if let Some(ticket) = &payload.ticket
+    {
+        let service = format!("{}/auth/callback", state.config().public_address);
+        let url = format!("{}?service={}&ticket={}", state.config().cas_validation_url, service, ticket);
+        let user = auth_service::validate_ticket(&url, &state.http_client).await.map_err(|rejection|
+        {
+            warn!("CAS ticket for password reveal by '{}' was rejected: {:?}", claims.sub, rejection.reason);
+            rejection.error
+        })?;
+
+        if user.login != claims.sub
+        {
+            warn!("User '{}' tried to reveal a password with a CAS ticket of '{}'.", claims.sub, user.login);
+            return Err(AppError::Unauthorized("The CAS ticket does not belong to the current user.".to_string()));
+        }
+        RevealMethod::CasTicket
+    }
+    else
+    {
+        return Err(AppError::Unauthorized(
+            "Revealing the password requires a recent authentication. Log in again or provide a fresh CAS ticket.".to_string()
+        ));
+    };
+
+    let ip_address = addr.ip().to_string();
+    let user_agent = headers.get(header::USER_AGENT).and_then(|value| value.to_str().ok());
+    database_service::record_password_reveal(&state.db_pool, db.id, &claims.sub, method, &ip_address, user_agent).await?;
+
+    info!("User '{}' revealed the password of database {} ({:?}).", claims.sub, db.id, method);
+
+    let details = database_service::create_db_details_response(db, &state.config(), &state.config().encryption_key)?;
+    Ok(Json(json!({ "database": details })))
+}
+
 pub async fn delete_my_database_handler(
     State(state): State<AppState>,
     claims: Claims,
     Path(db_id): Path<i32>,
 ) -> Result<impl IntoResponse, AppError>
 {
+    // Lu avant la suppression pour le journal d'audit ; les droits restent vérifiés par deprovision_database.
+    let db = database_service::get_database_by_id_and_owner(&state.db_pool, db_id, &claims.sub, claims.is_admin).await.ok().flatten();
+
     database_service::deprovision_database(
         &state.db_pool,
         &state.mariadb_pool,
@@ -69,6 +180,9 @@ pub async fn delete_my_database_handler(
         claims.is_admin
     ).await?;
 
+    let database = db.as_ref().map(|db| (db.database_name.as_str(), db.owner_login.as_str()));
+    record_database_audit(&state, &claims, "database_deprovision", db_id, database, None).await;
+
     Ok((StatusCode::OK, Json(json!({"status": "success", "message": "Database deleted successfully."}))))
 }
 
@@ -92,6 +206,9 @@ pub async fn delete_linked_database_handler(
         claims.is_admin,
     ).await?;
 
+    let details = json!({ "project_id": project_id });
+    record_database_audit(&state, &claims, "database_deprovision", db.id, Some((&db.database_name, &db.owner_login)), Some(details)).await;
+
     Ok((StatusCode::OK, Json(json!({"status": "success", "message": "Linked database deleted successfully."}))))
 }
 
@@ -111,6 +228,9 @@ pub async fn link_database_handler(
 
     database_service::link_database_to_project(&state.db_pool, database.id, project.id, &database.owner_login).await?;
 
+    let details = json!({ "project_id": project.id, "project_name": project.name });
+    record_database_audit(&state, &claims, "database_link", database.id, Some((&database.database_name, &database.owner_login)), Some(details)).await;
+
     Ok((StatusCode::OK, Json(json!({"status": "success", "message": "Database linked to project successfully."}))))
 }
 
@@ -120,10 +240,45 @@ pub async fn unlink_database_handler(
     Path(project_id): Path<i32>,
 ) -> Result<impl IntoResponse, AppError>
 {
-    let project = project_service::get_project_by_id_and_owner(&state.db_pool, project_id, &claims.sub, claims.is_admin).await?
+    project_service::get_project_by_id_and_owner(&state.db_pool, project_id, &claims.sub, claims.is_admin).await?
     .ok_or(AppError::NotFound("Project not found or you are not the owner.".to_string()))?;
 
-    database_service::unlink_database_from_project(&state.db_pool, project_id, &project.owner).await?;
+    // Sur un projet d'équipe, la base liée peut appartenir à un autre propriétaire de l'équipe.
+    let db = database_service::get_database_by_project_id(&state.db_pool, project_id).await?
+        .ok_or(AppError::NotFound("No database linked to this project.".to_string()))?;
+
+    database_service::unlink_database_from_project(&state.db_pool, project_id, &db.owner_login).await?;
+
+    let details = json!({ "project_id": project_id });
+    record_database_audit(&state, &claims, "database_unlink", db.id, Some((&db.database_name, &db.owner_login)), Some(details)).await;
     
     Ok((StatusCode::OK, Json(json!({"status": "success", "message": "Database unlinked from project successfully."}))))
+}
+
+pub async fn update_database_limits_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(db_id): Path<i32>,
+    ApiJson(payload): ApiJson<UpdateUserLimitsPayload>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let mut db = database_service::get_database_by_id_and_owner(&state.db_pool, db_id, &claims.sub, true).await?
+        .ok_or(AppError::NotFound(format!("Database with ID {} not found.", db_id)))?;
+
+    let limits = payload.apply_to(db.limits);
+    if let Some((field, _)) = limits.first_invalid()
+    {
+        return Err(AppError::BadRequest(format!("{} must be 0 (no limit) or more.", field)));
+    }
+
+    database_service::update_user_limits(&state.db_pool, &state.mariadb_pool, &db, &limits).await?;
+
+    info!("Admin '{}' changed the resource limits of database {} from {:?} to {:?}.", claims.sub, db.id, db.limits, limits);
+
+    let details = json!({ "from": db.limits, "to": limits });
+    record_database_audit(&state, &claims, "database_limits_update", db.id, Some((&db.database_name, &db.owner_login)), Some(details)).await;
+
+    db.limits = limits;
+    let details = database_service::create_masked_db_details_response(db, &state.config());
+    Ok(Json(json!({ "database": details })))
 }
\ No newline at end of file
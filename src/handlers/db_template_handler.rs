@@ -0,0 +1,61 @@
+use axum::
+{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Json},
+};
+use serde_json::json;
+use tracing::info;
+
+use crate::
+{
+    api::json::ApiJson,
+    error::AppError,
+    model::db_template::DbTemplatePayload,
+    services::{db_template_service, jwt::Claims},
+    state::AppState,
+};
+
+pub async fn list_db_templates_handler(
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let templates = db_template_service::get_all_templates(&state.db_pool).await?;
+
+    Ok((StatusCode::OK, Json(json!({ "templates": templates }))))
+}
+
+pub async fn create_db_template_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    ApiJson(payload): ApiJson<DbTemplatePayload>,
+) -> Result<impl IntoResponse, AppError>
+{
+    db_template_service::validate_template(&payload, state.config().db_template_max_bytes)?;
+
+    let template = db_template_service::create_template(&state.db_pool, &payload, &claims.sub).await?;
+
+    info!("Admin '{}' created database template '{}' ({} bytes).", claims.sub, template.name, template.size_bytes);
+
+    Ok((StatusCode::CREATED, Json(json!({ "template": template }))))
+}
+
+// Les bases déjà créées depuis ce modèle ne sont pas touchées.
+pub async fn delete_db_template_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(name): Path<String>,
+) -> Result<impl IntoResponse, AppError>
+{
+    if !db_template_service::delete_template(&state.db_pool, &name).await?
+    {
+        return Err(AppError::NotFound(format!("Database template '{}' not found.", name)));
+    }
+
+    info!("Admin '{}' deleted database template '{}'.", claims.sub, name);
+
+    Ok((
+        StatusCode::OK,
+        Json(json!({"status": "success", "message": "Database template deleted."})),
+    ))
+}
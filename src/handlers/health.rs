@@ -4,7 +4,7 @@ use time::OffsetDateTime;
 use std::time::{Duration, Instant};
 use tracing::{debug, error, warn};
 
-use crate::{error::AppError, state::AppState};
+use crate::{error::AppError, preflight, state::AppState};
 
 #[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
 #[serde(rename_all = "lowercase")]
@@ -40,17 +40,20 @@ pub struct HealthComponents
     pub postgres: ComponentHealth,
     pub mariadb: ComponentHealth,
     pub docker: ComponentHealth,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scanner: Option<ComponentHealth>,
 }
 
 impl HealthCheckResponse
 {
     fn compute_global_status(components: &HealthComponents) -> HealthStatus
     {
-        let statuses = vec![
+        let mut statuses = vec![
             components.postgres.status,
             components.mariadb.status,
             components.docker.status,
         ];
+        statuses.extend(components.scanner.as_ref().map(|scanner| scanner.status));
 
         if statuses.iter().any(|s| *s == HealthStatus::Unhealthy)
         {
@@ -86,6 +89,7 @@ pub async fn health_check_handler(
         postgres: postgres_health,
         mariadb: mariadb_health,
         docker: docker_health,
+        scanner: check_scanner_health(&state),
     };
 
     let global_status = HealthCheckResponse::compute_global_status(&components);
@@ -116,24 +120,19 @@ pub async fn health_check_handler(
     Ok((status_code, Json(response)))
 }
 
-async fn check_postgres_health(state: &AppState) -> ComponentHealth
+fn component_health(name: &str, elapsed: Duration, slow_after: Duration, result: Result<(), String>, details: Option<String>) -> ComponentHealth
 {
-    let start = Instant::now();
+    let response_time_us = elapsed.as_micros() as u64;
 
-    match tokio::time::timeout(
-        Duration::from_secs(5),
-        sqlx::query("SELECT 1 as health_check").fetch_one(&state.db_pool),
-    )
-    .await
+    match result
     {
-        Ok(Ok(_)) =>
+        Ok(()) =>
         {
-            let response_time_us = start.elapsed().as_micros() as u64;
-            debug!("PostgreSQL health check passed in {}µs", response_time_us);
+            debug!("{} health check passed in {}µs", name, response_time_us);
 
-            let status = if response_time_us > 1_000_000
+            let status = if elapsed > slow_after
             {
-                warn!("PostgreSQL response time is slow: {}µs", response_time_us);
+                warn!("{} response time is slow: {}µs", name, response_time_us);
                 HealthStatus::Degraded
             }
             else
@@ -145,147 +144,102 @@ async fn check_postgres_health(state: &AppState) -> ComponentHealth
             {
                 status,
                 response_time_us,
-                details: Some("Connected to PostgreSQL".to_string()),
+                details,
                 error: None,
             }
         }
-        Ok(Err(e)) =>
+        Err(e) =>
         {
-            error!("PostgreSQL health check failed: {}", e);
+            error!("{} health check failed: {}", name, e);
             ComponentHealth
             {
                 status: HealthStatus::Unhealthy,
-                response_time_us: start.elapsed().as_micros() as u64,
-                details: None,
-                error: Some(format!("Database error: {}", e)),
-            }
-        }
-        Err(_) =>
-        {
-            error!("PostgreSQL health check timed out");
-            ComponentHealth
-            {
-                status: HealthStatus::Unhealthy,
-                response_time_us: 5_000_000,
+                response_time_us,
                 details: None,
-                error: Some("Connection timeout (5s)".to_string()),
+                error: Some(e),
             }
         }
     }
 }
 
+async fn check_postgres_health(state: &AppState) -> ComponentHealth
+{
+    let start = Instant::now();
+    let result = preflight::check_postgres(&state.db_pool).await;
+    component_health("PostgreSQL", start.elapsed(), Duration::from_secs(1), result, Some("Connected to PostgreSQL".to_string()))
+}
+
 async fn check_mariadb_health(state: &AppState) -> ComponentHealth
 {
     let start = Instant::now();
+    let result = preflight::check_mariadb(&state.mariadb_pool).await;
+    component_health("MariaDB", start.elapsed(), Duration::from_secs(1), result, Some("Connected to MariaDB".to_string()))
+}
+
+async fn check_docker_health(state: &AppState) -> ComponentHealth
+{
+    let checks = futures::future::join_all(
+        state.docker_hosts.iter().map(|(name, docker)| async move { (name, check_docker_host_health(docker, &state.config().docker_network).await) })
+    ).await;
 
-    match tokio::time::timeout(
-        Duration::from_secs(5),
-        sqlx::query("SELECT 1 as health_check").fetch_one(&state.mariadb_pool),
-    )
-    .await
+    let status = if checks.iter().any(|(_, c)| c.status == HealthStatus::Unhealthy)
     {
-        Ok(Ok(_)) =>
-        {
-            let response_time_us = start.elapsed().as_micros() as u64;
-            debug!("MariaDB health check passed in {}µs", response_time_us);
+        HealthStatus::Unhealthy
+    }
+    else if checks.iter().any(|(_, c)| c.status == HealthStatus::Degraded)
+    {
+        HealthStatus::Degraded
+    }
+    else
+    {
+        HealthStatus::Healthy
+    };
 
-            let status = if response_time_us > 1_000_000
-            {
-                warn!("MariaDB response time is slow: {}µs", response_time_us);
-                HealthStatus::Degraded
-            }
-            else
-            {
-                HealthStatus::Healthy
-            };
+    let errors: Vec<String> = checks.iter()
+        .filter_map(|(name, c)| c.error.as_ref().map(|err| format!("{}: {}", name, err)))
+        .collect();
 
-            ComponentHealth
-            {
-                status,
-                response_time_us,
-                details: Some("Connected to MariaDB".to_string()),
-                error: None,
-            }
-        }
-        Ok(Err(e)) =>
-        {
-            error!("MariaDB health check failed: {}", e);
-            ComponentHealth
-            {
-                status: HealthStatus::Unhealthy,
-                response_time_us: start.elapsed().as_micros() as u64,
-                details: None,
-                error: Some(format!("Database error: {}", e)),
-            }
-        }
-        Err(_) =>
-        {
-            error!("MariaDB health check timed out");
-            ComponentHealth
-            {
-                status: HealthStatus::Unhealthy,
-                response_time_us: 5_000_000,
-                details: None,
-                error: Some("Connection timeout (5s)".to_string()),
-            }
-        }
+    ComponentHealth
+    {
+        status,
+        response_time_us: checks.iter().map(|(_, c)| c.response_time_us).max().unwrap_or(0),
+        details: Some(format!("{} Docker host(s) checked", checks.len())),
+        error: if errors.is_empty() { None } else { Some(errors.join("; ")) },
     }
 }
 
-async fn check_docker_health(state: &AppState) -> ComponentHealth
+async fn check_docker_host_health(docker: &bollard::Docker, network: &str) -> ComponentHealth
 {
     let start = Instant::now();
 
-    match tokio::time::timeout(
-        Duration::from_secs(5),
-        state.docker_client.ping(),
-    )
-    .await
+    let result = match preflight::check_docker(docker).await
     {
-        Ok(Ok(_)) =>
-        {
-            let response_time_us = start.elapsed().as_micros() as u64;
-            debug!("Docker health check passed in {}µs", response_time_us);
+        Ok(()) => preflight::check_docker_network(docker, network).await,
+        Err(e) => Err(e),
+    };
 
-            let status = if response_time_us > 2_000_000
-            {
-                warn!("Docker response time is slow: {}µs", response_time_us);
-                HealthStatus::Degraded
-            }
-            else
-            {
-                HealthStatus::Healthy
-            };
+    component_health("Docker", start.elapsed(), Duration::from_secs(2), result, None)
+}
 
-            ComponentHealth
-            {
-                status,
-                response_time_us,
-                details: None,
-                error: None,
-            }
-        }
-        Ok(Err(e)) =>
-        {
-            error!("Docker health check failed: {}", e);
-            ComponentHealth
-            {
-                status: HealthStatus::Unhealthy,
-                response_time_us: start.elapsed().as_micros() as u64,
-                details: None,
-                error: Some(format!("Docker daemon error: {}", e)),
-            }
-        }
-        Err(_) =>
-        {
-            error!("Docker health check timed out");
-            ComponentHealth
-            {
-                status: HealthStatus::Unhealthy,
-                response_time_us: 5_000_000,
-                details: None,
-                error: Some("Connection timeout (5s)".to_string()),
-            }
-        }
+// Un scanner absent fait échouer les déploiements, pas l'API.
+fn check_scanner_health(state: &AppState) -> Option<ComponentHealth>
+{
+    if !state.config().grype_enabled
+    {
+        return None;
+    }
+
+    let start = Instant::now();
+    let mut health = match preflight::check_grype()
+    {
+        Ok(path) => component_health("Grype", start.elapsed(), Duration::from_secs(1), Ok(()), Some(format!("Found at {}", path.display()))),
+        Err(e) => component_health("Grype", start.elapsed(), Duration::from_secs(1), Err(e), None),
+    };
+
+    if health.status == HealthStatus::Unhealthy
+    {
+        health.status = HealthStatus::Degraded;
     }
+
+    Some(health)
 }
@@ -0,0 +1,82 @@
+use axum::
+{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Json},
+};
+use serde_json::json;
+use std::time::Duration;
+use tokio::time::interval;
+use tracing::{error, info};
+
+use crate::
+{
+    error::AppError,
+    services::{invitation_service, jwt::Claims},
+    state::AppState,
+};
+
+const INVITATION_CLEANUP_INTERVAL: Duration = Duration::from_secs(3600);
+
+pub async fn list_invitations_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+) -> Result<impl IntoResponse, AppError>
+{
+    let invitations = invitation_service::get_pending_invitations(&state.db_pool, &claims.sub, state.config().invitation_expiry_days).await?;
+
+    Ok((StatusCode::OK, Json(json!({ "invitations": invitations }))))
+}
+
+pub async fn accept_invitation_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(invitation_id): Path<i32>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let project_id = invitation_service::accept_invitation(&state.db_pool, invitation_id, &claims.sub, state.config().invitation_expiry_days)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Invitation with ID {} not found or expired.", invitation_id)))?;
+
+    info!("User '{}' accepted invitation {} to project {}", claims.sub, invitation_id, project_id);
+
+    Ok((
+        StatusCode::OK,
+        Json(json!({"status": "success", "message": "Invitation accepted.", "project_id": project_id})),
+    ))
+}
+
+pub async fn decline_invitation_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(invitation_id): Path<i32>,
+) -> Result<impl IntoResponse, AppError>
+{
+    if !invitation_service::decline_invitation(&state.db_pool, invitation_id, &claims.sub).await?
+    {
+        return Err(AppError::NotFound(format!("Invitation with ID {} not found.", invitation_id)));
+    }
+
+    info!("User '{}' declined invitation {}", claims.sub, invitation_id);
+
+    Ok((
+        StatusCode::OK,
+        Json(json!({"status": "success", "message": "Invitation declined."})),
+    ))
+}
+
+pub async fn run_invitation_cleanup(state: AppState)
+{
+    let mut ticker = interval(INVITATION_CLEANUP_INTERVAL);
+    loop
+    {
+        ticker.tick().await;
+
+        match invitation_service::delete_expired_invitations(&state.db_pool, state.config().invitation_expiry_days).await
+        {
+            Ok(0) => {}
+            Ok(count) => info!("Removed {} expired invitation(s).", count),
+            Err(e) => error!("Failed to clean up expired invitations: {}", e),
+        }
+    }
+}
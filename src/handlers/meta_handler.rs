@@ -0,0 +1,31 @@
+use axum::
+{
+    http::StatusCode,
+    response::{Json, Response},
+};
+
+use crate::
+{
+    build_info::{build_info, BuildInfo},
+    error_catalog::error_catalog,
+    etag::{self, IfNoneMatch, WithETag},
+};
+
+pub async fn get_version_handler() -> Json<BuildInfo>
+{
+    Json(build_info())
+}
+
+// Publique : le front charge ses messages d'erreur avant la connexion.
+pub async fn get_error_catalog_handler(if_none_match: IfNoneMatch) -> Response
+{
+    let catalog = error_catalog();
+    let etag = etag::weak_etag(&catalog.catalog_version);
+
+    if if_none_match.matches(&etag)
+    {
+        return etag::not_modified(&etag);
+    }
+
+    (StatusCode::OK, Json(catalog)).with_etag(&etag)
+}
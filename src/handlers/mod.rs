@@ -2,4 +2,12 @@ pub mod health;
 pub mod auth_handler;
 pub mod project_handler;
 pub mod admin_handler;
-pub mod database_handler;
\ No newline at end of file
+pub mod database_handler;
+pub mod quota_handler;
+pub mod invitation_handler;
+pub mod scan_waiver_handler;
+pub mod webhook_handler;
+pub mod account_handler;
+pub mod meta_handler;
+pub mod db_template_handler;
+pub mod team_handler;
@@ -0,0 +1,250 @@
+use super::*;
+
+const BUILD_CACHE_PRUNE_INTERVAL: Duration = Duration::from_secs(3600);
+
+pub async fn run_build_cache_prune(state: AppState)
+{
+    let mut ticker = tokio::time::interval(BUILD_CACHE_PRUNE_INTERVAL);
+    let max_size_bytes = state.config().build_cache_max_size_mb * 1024 * 1024;
+    loop
+    {
+        ticker.tick().await;
+
+        for (host, docker) in &state.docker_hosts
+        {
+            match docker_service::prune_build_cache(docker, state.config().build_cache_max_age_days, max_size_bytes).await
+            {
+                Ok(0) => {}
+                Ok(count) => info!("Removed {} build cache image(s) on host '{}'.", count, host),
+                Err(e) => error!("Failed to prune the build cache on host '{}': {}", host, e),
+            }
+        }
+    }
+}
+
+const DELETED_PROJECTS_REAPER_INTERVAL: Duration = Duration::from_secs(600);
+
+pub async fn run_deleted_projects_reaper(state: AppState)
+{
+    let mut ticker = tokio::time::interval(DELETED_PROJECTS_REAPER_INTERVAL);
+    loop
+    {
+        ticker.tick().await;
+
+        let projects = match project_service::get_projects_due_for_purge(&state.db_pool, state.config().project_deletion_grace_hours).await
+        {
+            Ok(projects) => projects,
+            Err(e) =>
+            {
+                error!("Skipping the purge of deleted projects, they could not be listed: {}", e);
+                continue;
+            }
+        };
+
+        for project in projects
+        {
+            match purge_project(&state, &project, &project.owner, true).await
+            {
+                Ok(()) =>
+                {
+                    if let Err(e) = project_event_service::record_event(&state.db_pool, &project, ProjectEventAction::Purge, &project.owner, false, None).await
+                    {
+                        warn!("Could not record the purge of project '{}': {}", project.name, e);
+                    }
+                }
+                Err(e) => error!("Failed to purge deleted project '{}': {}", project.name, e),
+            }
+        }
+    }
+}
+
+pub async fn run_project_status_sync(state: AppState)
+{
+    let mut ticker = tokio::time::interval(Duration::from_secs(state.config().status_sync_interval_seconds));
+    loop
+    {
+        ticker.tick().await;
+
+        let projects = match project_service::get_all_projects(&state.db_pool).await
+        {
+            Ok(projects) => projects,
+            Err(e) =>
+            {
+                error!("Skipping the project status sync, projects could not be listed: {}", e);
+                continue;
+            }
+        };
+
+        for project in projects
+        {
+            let observed = match state.docker_for(&project.docker_host)
+            {
+                Ok(docker) => docker_service::observe_project_status(docker, &project.container_names(), project.intended_running).await,
+                Err(e) => Err(e),
+            };
+
+            match observed
+            {
+                Ok(status) =>
+                {
+                    if let Err(e) = project_service::update_project_status(&state.db_pool, project.id, status).await
+                    {
+                        warn!("Could not store the status of project '{}': {}", project.name, e);
+                        continue;
+                    }
+
+                    if project.status != Some(status)
+                        && let Err(e) = availability_service::record_transition(&state.db_pool, project.id, status).await
+                    {
+                        warn!("Could not record the availability of project '{}': {}", project.name, e);
+                    }
+                }
+                Err(e) => warn!("Could not observe the status of project '{}': {}", project.name, e),
+            }
+        }
+    }
+}
+
+const PROJECT_SCHEDULER_INTERVAL: Duration = Duration::from_secs(20);
+
+// Évite de rejouer une journée de planifications après une longue pause.
+const PROJECT_SCHEDULER_MAX_CATCH_UP: i64 = 10;
+
+// Seules les heures planifiées agissent : un démarrage manuel tient jusqu'à l'arrêt planifié suivant.
+pub async fn run_project_scheduler(state: AppState)
+{
+    let offset = time::UtcOffset::from_hms(state.config().schedule_utc_offset_hours, 0, 0).unwrap_or(time::UtcOffset::UTC);
+    let current_minute = || time::OffsetDateTime::now_utc().to_offset(offset).replace_second(0).and_then(|t| t.replace_nanosecond(0));
+
+    let Ok(mut last_minute) = current_minute()
+    else
+    {
+        error!("The project scheduler could not read the current time and is disabled.");
+        return;
+    };
+
+    let mut ticker = tokio::time::interval(PROJECT_SCHEDULER_INTERVAL);
+    loop
+    {
+        ticker.tick().await;
+
+        let Ok(now) = current_minute()
+        else
+        {
+            continue;
+        };
+        if now <= last_minute
+        {
+            continue;
+        }
+
+        let projects = match project_service::get_scheduled_projects(&state.db_pool).await
+        {
+            Ok(projects) => projects,
+            Err(e) =>
+            {
+                error!("Skipping the scheduled starts and stops, projects could not be listed: {}", e);
+                continue;
+            }
+        };
+
+        let first = (last_minute + time::Duration::MINUTE).max(now - time::Duration::minutes(PROJECT_SCHEDULER_MAX_CATCH_UP - 1));
+        let minutes: Vec<time::OffsetDateTime> = std::iter::successors(Some(first), |minute| Some(*minute + time::Duration::MINUTE))
+            .take_while(|minute| *minute <= now)
+            .collect();
+
+        for project in projects
+        {
+            // A stop and a start due in the same pass leave the project stopped.
+            let action = if schedule_due(&project, project.auto_stop_cron.as_deref(), &minutes)
+            {
+                ProjectAction::Stop
+            }
+            else if schedule_due(&project, project.auto_start_cron.as_deref(), &minutes)
+            {
+                ProjectAction::Start
+            }
+            else
+            {
+                continue;
+            };
+
+            if let Err(e) = run_scheduled_action(&state, &project, action).await
+            {
+                warn!("Scheduled {} of project '{}' failed: {}", action.as_str(), project.name, e);
+            }
+        }
+
+        last_minute = now;
+    }
+}
+
+fn schedule_due(project: &crate::model::project::Project, expression: Option<&str>, minutes: &[time::OffsetDateTime]) -> bool
+{
+    let Some(expression) = expression
+    else
+    {
+        return false;
+    };
+
+    match CronSchedule::parse(expression)
+    {
+        Ok(schedule) => minutes.iter().any(|minute| schedule.matches(*minute)),
+        Err(e) =>
+        {
+            warn!("Ignoring the schedule '{}' of project '{}': {}", expression, project.name, e);
+            false
+        }
+    }
+}
+
+async fn run_scheduled_action(state: &AppState, project: &crate::model::project::Project, action: ProjectAction) -> Result<(), AppError>
+{
+    let intended_running = matches!(action, ProjectAction::Start);
+    if project.maintenance_page || project.intended_running == intended_running
+    {
+        return Ok(());
+    }
+
+    let docker = state.docker_for(&project.docker_host)?;
+    validate_container_exists_for_action(docker, project, action).await?;
+
+    for container_name in project.container_names()
+    {
+        action.execute(docker.clone(), container_name).await?;
+    }
+
+    project_service::set_project_intended_running(&state.db_pool, project.id, intended_running).await?;
+
+    let (event, cron) = match action
+    {
+        ProjectAction::Start => (ProjectEventAction::ScheduledStart, &project.auto_start_cron),
+        _ => (ProjectEventAction::ScheduledStop, &project.auto_stop_cron),
+    };
+    info!("Scheduled {} of project '{}' ({}).", action.as_str(), project.name, cron.as_deref().unwrap_or_default());
+
+    if let Err(e) = project_event_service::record_event(&state.db_pool, project, event, &project.owner, false, Some(json!({ "cron": cron }))).await
+    {
+        warn!("Could not record the scheduled {} of project '{}': {}", action.as_str(), project.name, e);
+    }
+
+    Ok(())
+}
+
+const AVAILABILITY_COMPACTION_INTERVAL: Duration = Duration::from_secs(3600);
+
+pub async fn run_availability_compaction(state: AppState)
+{
+    let mut ticker = tokio::time::interval(AVAILABILITY_COMPACTION_INTERVAL);
+    loop
+    {
+        ticker.tick().await;
+
+        match availability_service::compact(&state.db_pool, state.config().availability_retention_days).await
+        {
+            Ok(0) => {}
+            Ok(count) => info!("Compacted {} availability event(s) into daily totals.", count),
+            Err(e) => error!("Failed to compact the availability events: {}", e),
+        }
+    }
+}
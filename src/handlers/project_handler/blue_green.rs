@@ -0,0 +1,483 @@
+use super::*;
+
+pub(super) async fn prepare_blue_green_deployment(
+    state: &AppState,
+    project: &crate::model::project::Project,
+    new_image_url: &str,
+    old_image_tag: Option<&str>,
+) -> Result<BlueGreenDeployment, AppError>
+{
+    let docker = state.docker_for(&project.docker_host)?;
+
+    if old_image_tag.is_none()
+    {
+        let scan_threshold = policy_service::get_scan_threshold(&state.db_pool, &state.config(), &project.owner, Some(project.id), None).await?;
+        prepare_direct_source(state, &project.docker_host, docker, &project.name, new_image_url, scan_threshold, &mut DeployTimings::default()).await?;
+    }
+
+    let new_image_digest = get_image_digest(docker, new_image_url).await?;
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+
+    Ok(BlueGreenDeployment
+    {
+        old_container_names: project.container_names(),
+        new_container_name: format!("{}-{}-{}", state.config().app_prefix, project.name, timestamp),
+        replicas: project.replicas,
+        new_image_tag: new_image_url.to_string(),
+        new_image_digest,
+    })
+}
+
+pub(super) fn create_blue_green_deployment_for_env_update(
+    state: &AppState,
+    project: &crate::model::project::Project,
+) -> BlueGreenDeployment
+{
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+
+    BlueGreenDeployment
+    {
+        old_container_names: project.container_names(),
+        new_container_name: format!("{}-{}-{}", state.config().app_prefix, project.name, timestamp),
+        replicas: project.replicas,
+        new_image_tag: project.deployed_image_tag.clone(),
+        new_image_digest: project.deployed_image_digest.clone(),
+    }
+}
+
+pub(super) async fn execute_blue_green_deployment(
+    state: &AppState,
+    project: &crate::model::project::Project,
+    deployment: &BlueGreenDeployment,
+    env_vars: Option<&HashMap<String, String>>,
+    old_image_to_cleanup: &str,
+) -> Result<(), AppError>
+{
+    info!("Creating new container '{}' for project '{}'", deployment.new_container_name, project.name);
+
+    let docker = state.docker_for(&project.docker_host)?;
+
+    let mut rollback = Rollback::new(state, format!("update of project '{}'", project.name));
+
+    // The image of an environment update is the one the current containers run on.
+    if deployment.new_image_tag != project.deployed_image_tag
+    {
+        rollback.push(CleanupAction::RemoveImage { host: project.docker_host.clone(), image: deployment.new_image_tag.clone() });
+    }
+
+    let switched = async
+    {
+        let container_ids = create_replica_containers(state, project, &deployment.new_container_names(), &deployment.new_image_digest, env_vars, &mut rollback).await?;
+        wait_for_replicas_health(docker, &deployment.new_container_names(), 10).await?;
+        update_project_metadata(state, project.id, deployment, container_ids.first().map(String::as_str), &project.source).await
+    }.await;
+
+    rollback.finish(switched).await.inspect_err(|_|
+    {
+        error!("Failed to switch project '{}' to its new container. Aborting update.", project.name);
+    })?;
+
+
+    cleanup_old_deployment(state, docker, project, &deployment.old_container_names, old_image_to_cleanup).await;
+    keep_stopped_for_maintenance(docker, project, &deployment.new_container_names()).await;
+
+    info!(
+        "Project '{}' deployment completed successfully. New container is '{}'.",
+        project.name, deployment.new_container_name
+    );
+
+    record_image_metadata(state, docker, project.id, &deployment.new_image_tag).await;
+    spawn_sbom_generation(state, &project.docker_host, &deployment.new_image_tag, &deployment.new_image_digest);
+
+    if deployment.new_image_tag != project.deployed_image_tag
+    {
+        let warnings = image_config_warnings(docker, &deployment.new_image_tag).await;
+        record_deploy_warnings(state, project.id, &warnings).await;
+    }
+
+    Ok(())
+}
+
+async fn create_replica_containers(
+    state: &AppState,
+    project: &crate::model::project::Project,
+    container_names: &[String],
+    image_identifier: &str,
+    env_vars: Option<&HashMap<String, String>>,
+    rollback: &mut Rollback,
+) -> Result<Vec<String>, AppError>
+{
+    let docker = state.docker_for(&project.docker_host)?;
+    let database = match database_service::get_database_by_project_id(&state.db_pool, project.id).await?
+    {
+        Some(db) => Some(database_service::create_db_details_response(db, &state.config(), &state.config().encryption_key)?),
+        None => None,
+    };
+    let mut container_ids = Vec::with_capacity(container_names.len());
+
+    for container_name in container_names
+    {
+        let created = docker_service::create_project_container(docker, &state.config(), ContainerSpec
+        {
+            container_name,
+            project_name: &project.name,
+            owner: &project.owner,
+            image_identifier,
+            env_vars,
+            persistent_volume_path: project.persistent_volume_path.as_deref(),
+            volume_name: project.volume_name.as_deref(),
+            custom_domains: &project.custom_domains,
+            middlewares: &project.route_middlewares(),
+            egress_policy: project.egress_policy,
+            database: database.as_ref(),
+        }).await?;
+        rollback.push(CleanupAction::RemoveContainer { host: project.docker_host.clone(), container: container_name.clone() });
+        container_ids.push(created.id);
+    }
+
+    Ok(container_ids)
+}
+
+async fn wait_for_replicas_health(
+    docker: &bollard::Docker,
+    container_names: &[String],
+    max_attempts: u32,
+) -> Result<(), AppError>
+{
+    for container_name in container_names
+    {
+        wait_for_container_health(docker, container_name, max_attempts).await?;
+    }
+    Ok(())
+}
+
+async fn update_project_metadata(
+    state: &AppState,
+    project_id: i32,
+    deployment: &BlueGreenDeployment,
+    new_container_id: Option<&str>,
+    project_source: &ProjectSourceType,
+) -> Result<(), AppError>
+{
+    project_service::update_project_container_name(
+        &state.db_pool,
+        project_id,
+        &deployment.new_container_name,
+        new_container_id,
+    ).await?;
+
+    project_service::update_project_image_and_digest(
+        &state.db_pool,
+        project_id,
+        &deployment.new_image_tag,
+        &deployment.new_image_digest,
+    ).await?;
+
+    if *project_source == ProjectSourceType::Direct
+    {
+        project_service::update_project_source_url(
+            &state.db_pool,
+            project_id,
+            &deployment.new_image_tag,
+        ).await?;
+    }
+
+    Ok(())
+}
+
+async fn cleanup_old_deployment(
+    state: &AppState,
+    docker: &bollard::Docker,
+    project: &crate::model::project::Project,
+    old_container_names: &[String],
+    old_image_tag: &str,
+)
+{
+    remove_old_containers(docker, old_container_names).await;
+
+    let state = state.clone();
+    let host = project.docker_host.clone();
+    let old_image_tag = old_image_tag.to_string();
+    let operation = format!("update of project '{}'", project.name);
+
+    tokio::spawn(async move
+    {
+        ops::remove_image_or_defer(&state, &host, &old_image_tag, &operation).await;
+    });
+}
+
+async fn remove_old_containers(docker: &bollard::Docker, old_container_names: &[String])
+{
+    for old_container_name in old_container_names
+    {
+        info!("Removing old container '{}'", old_container_name);
+
+        if let Err(e) = docker_service::remove_container(docker, old_container_name).await
+        {
+            warn!(
+                "Could not remove old container '{}', but update is successful. Manual cleanup may be needed. Error: {}",
+                old_container_name, e
+            );
+        }
+    }
+}
+
+pub(super) async fn apply_route_middlewares(
+    state: &AppState,
+    project: &crate::model::project::Project,
+    middlewares: RouteMiddlewares,
+) -> Result<(), AppError>
+{
+    let mut updated_project = project.clone();
+    updated_project.rate_limit_average = middlewares.rate_limit.map(|limit| limit.average);
+    updated_project.rate_limit_burst = middlewares.rate_limit.map(|limit| limit.burst);
+    updated_project.ip_allowlist = middlewares.ip_allowlist.clone();
+    updated_project.healthcheck_path = middlewares.healthcheck.as_ref().map(|healthcheck| healthcheck.path.clone());
+    updated_project.healthcheck_interval = middlewares.healthcheck.as_ref().map(|healthcheck| healthcheck.interval_seconds);
+    updated_project.healthcheck_port = middlewares.healthcheck.as_ref().and_then(|healthcheck| healthcheck.port);
+    updated_project.routing_options = sqlx::types::Json(middlewares.routing.clone());
+
+    let env_vars = get_decrypted_env_vars(project, &state.config().encryption_key)?;
+    let deployment = create_blue_green_deployment_for_env_update(state, project);
+    let docker = state.docker_for(&project.docker_host)?;
+
+    let mut rollback = Rollback::new(state, format!("route update of project '{}'", project.name));
+    let created = async
+    {
+        let container_ids = create_replica_containers(state, &updated_project, &deployment.new_container_names(), &deployment.new_image_digest, env_vars.as_ref(), &mut rollback).await?;
+        wait_for_replicas_health(docker, &deployment.new_container_names(), 10).await?;
+        Ok(container_ids)
+    }.await;
+    let container_ids = rollback.finish(created).await?;
+
+    project_service::update_project_container_name(&state.db_pool, project.id, &deployment.new_container_name, container_ids.first().map(String::as_str)).await?;
+    project_service::update_project_route_middlewares(&state.db_pool, project.id, &middlewares).await?;
+
+    remove_old_containers(docker, &deployment.old_container_names).await;
+    keep_stopped_for_maintenance(docker, project, &deployment.new_container_names()).await;
+
+    Ok(())
+}
+
+pub(super) async fn apply_egress_policy(
+    state: &AppState,
+    project: &crate::model::project::Project,
+    egress_policy: EgressPolicy,
+) -> Result<(), AppError>
+{
+    let mut updated_project = project.clone();
+    updated_project.egress_policy = egress_policy;
+
+    let env_vars = get_decrypted_env_vars(project, &state.config().encryption_key)?;
+    let deployment = create_blue_green_deployment_for_env_update(state, project);
+    let docker = state.docker_for(&project.docker_host)?;
+
+    let mut rollback = Rollback::new(state, format!("egress policy update of project '{}'", project.name));
+    let created = async
+    {
+        let container_ids = create_replica_containers(state, &updated_project, &deployment.new_container_names(), &deployment.new_image_digest, env_vars.as_ref(), &mut rollback).await?;
+        wait_for_replicas_health(docker, &deployment.new_container_names(), 10).await?;
+        Ok(container_ids)
+    }.await;
+    let container_ids = rollback.finish(created).await?;
+
+    project_service::update_project_container_name(&state.db_pool, project.id, &deployment.new_container_name, container_ids.first().map(String::as_str)).await?;
+    project_service::update_project_egress_policy(&state.db_pool, project.id, egress_policy).await?;
+
+    remove_old_containers(docker, &deployment.old_container_names).await;
+    keep_stopped_for_maintenance(docker, project, &deployment.new_container_names()).await;
+
+    Ok(())
+}
+
+pub(super) async fn apply_custom_domains(
+    state: &AppState,
+    project: &crate::model::project::Project,
+    domains: &[String],
+) -> Result<(), AppError>
+{
+    let mut updated_project = project.clone();
+    updated_project.custom_domains = domains.to_vec();
+
+    let env_vars = get_decrypted_env_vars(project, &state.config().encryption_key)?;
+    let deployment = create_blue_green_deployment_for_env_update(state, project);
+    let docker = state.docker_for(&project.docker_host)?;
+
+    let mut rollback = Rollback::new(state, format!("custom domains update of project '{}'", project.name));
+    let created = async
+    {
+        let container_ids = create_replica_containers(state, &updated_project, &deployment.new_container_names(), &deployment.new_image_digest, env_vars.as_ref(), &mut rollback).await?;
+        wait_for_replicas_health(docker, &deployment.new_container_names(), 10).await?;
+        project_service::set_project_custom_domains(&state.db_pool, project.id, domains).await?;
+        Ok(container_ids)
+    }.await;
+    let container_ids = rollback.finish(created).await?;
+
+    project_service::update_project_container_name(&state.db_pool, project.id, &deployment.new_container_name, container_ids.first().map(String::as_str)).await?;
+
+    remove_old_containers(docker, &deployment.old_container_names).await;
+    keep_stopped_for_maintenance(docker, project, &deployment.new_container_names()).await;
+
+    if project.maintenance_page
+        && let Err(e) = docker_service::create_maintenance_container(docker, &maintenance_container_name(state, project), &project.name, domains, &state.config()).await
+    {
+        warn!("Could not update the maintenance page of project '{}' with its custom domains: {}", project.name, e);
+    }
+
+    Ok(())
+}
+
+pub(super) async fn apply_rename(
+    state: &AppState,
+    project: &crate::model::project::Project,
+    new_name: &str,
+) -> Result<(), AppError>
+{
+    let mut renamed_project = project.clone();
+    renamed_project.name = new_name.to_string();
+    renamed_project.container_name = format!("{}-{}", state.config().app_prefix, new_name);
+
+    let env_vars = get_decrypted_env_vars(project, &state.config().encryption_key)?;
+    let new_container_names = renamed_project.container_names();
+    let docker = state.docker_for(&project.docker_host)?;
+
+    let mut rollback = Rollback::new(state, format!("rename of project '{}' to '{}'", project.name, new_name));
+    let renamed = async
+    {
+        let container_ids = create_replica_containers(state, &renamed_project, &new_container_names, &project.deployed_image_digest, env_vars.as_ref(), &mut rollback).await?;
+        wait_for_replicas_health(docker, &new_container_names, 10).await?;
+
+        project_service::rename_project(
+            &state.db_pool,
+            project.id,
+            &project.name,
+            new_name,
+            &renamed_project.container_name,
+            container_ids.first().map(String::as_str),
+        ).await
+    }.await;
+    rollback.finish(renamed).await?;
+
+    remove_old_containers(docker, &project.container_names()).await;
+
+    if !project.intended_running
+    {
+        for container_name in &new_container_names
+        {
+            if let Err(e) = docker_service::stop_container_by_name(docker, container_name).await
+            {
+                warn!("Could not stop container '{}' of renamed project '{}': {}", container_name, new_name, e);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+pub(super) async fn apply_replicas(
+    state: &AppState,
+    project: &crate::model::project::Project,
+    replicas: i32,
+    volume_share_safe: bool,
+) -> Result<(), AppError>
+{
+    let docker = state.docker_for(&project.docker_host)?;
+    let current_names = project.container_names();
+    let target_names = replica_container_names(&project.container_name, replicas);
+
+    if target_names.len() > current_names.len()
+    {
+        let added_names = target_names[current_names.len()..].to_vec();
+        let env_vars = get_decrypted_env_vars(project, &state.config().encryption_key)?;
+
+        let mut rollback = Rollback::new(state, format!("scale-up of project '{}'", project.name));
+        let scaled = async
+        {
+            create_replica_containers(state, project, &added_names, &project.deployed_image_digest, env_vars.as_ref(), &mut rollback).await?;
+            wait_for_replicas_health(docker, &added_names, 10).await?;
+
+            if !project.intended_running
+            {
+                for container_name in &added_names
+                {
+                    docker_service::stop_container_by_name(docker, container_name).await?;
+                }
+            }
+
+            project_service::update_project_replicas(&state.db_pool, project.id, replicas, volume_share_safe).await
+        }.await;
+        rollback.finish(scaled).await?;
+    }
+    else
+    {
+        project_service::update_project_replicas(&state.db_pool, project.id, replicas, volume_share_safe).await?;
+        remove_old_containers(docker, &current_names[target_names.len()..]).await;
+    }
+
+    Ok(())
+}
+
+pub(super) async fn execute_env_vars_blue_green_deployment(
+    state: &AppState,
+    project: &crate::model::project::Project,
+    deployment: &BlueGreenDeployment,
+    env_vars: &HashMap<String, String>,
+) -> Result<(), AppError>
+{
+    info!(
+        "Creating new container '{}' for project '{}' with updated env vars",
+        deployment.new_container_name, project.name
+    );
+
+    let docker = state.docker_for(&project.docker_host)?;
+
+    let mut rollback = Rollback::new(state, format!("environment update of project '{}'", project.name));
+    let created = async
+    {
+        let container_ids = create_replica_containers(
+            state,
+            project,
+            &deployment.new_container_names(),
+            &project.deployed_image_tag,
+            Some(env_vars),
+            &mut rollback,
+        ).await?;
+        wait_for_replicas_health(docker, &deployment.new_container_names(), 10).await?;
+        Ok(container_ids)
+    }.await;
+
+    let container_ids = rollback.finish(created).await.inspect_err(|_|
+    {
+        error!("Failed to recreate container for project '{}' during env update. Aborting.", project.name);
+    })?;
+
+    project_service::update_project_container_name(
+        &state.db_pool,
+        project.id,
+        &deployment.new_container_name,
+        container_ids.first().map(String::as_str),
+    ).await?;
+
+    project_service::update_project_env_vars(
+        &state.db_pool,
+        project.id,
+        env_vars,
+        &state.config().encryption_key,
+    ).await?;
+
+    remove_old_containers(docker, &deployment.old_container_names).await;
+    keep_stopped_for_maintenance(docker, project, &deployment.new_container_names()).await;
+
+    info!(
+        "Project '{}' environment variables updated successfully. New container is '{}'.",
+        project.name, deployment.new_container_name
+    );
+
+    Ok(())
+}
@@ -0,0 +1,350 @@
+use super::*;
+
+pub async fn duplicate_project_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(project_id): Path<i32>,
+    ApiJson(payload): ApiJson<DuplicatePayload>,
+) -> Result<impl IntoResponse, AppError>
+{
+    if payload.copy_volume.unwrap_or(false)
+    {
+        return Err(AppError::BadRequest(
+            "Copying volume contents is not supported yet: no snapshot mechanism is available.".to_string()
+        ));
+    }
+
+    if payload.new_owner.is_some() && !claims.is_admin
+    {
+        return Err(AppError::Unauthorized("Only admins can duplicate a project for another user.".to_string()));
+    }
+
+    let user_login = &claims.sub;
+    let source = get_project_for_user(&state, project_id, user_login, claims.is_admin).await?;
+    let owner = payload.new_owner.as_deref().unwrap_or(user_login);
+
+    let env_vars_copied = team_service::can_reveal_secrets(&state.db_pool, &source, user_login, claims.is_admin).await?;
+    let mut deploy_payload = build_duplicate_payload(&state, &source, payload.new_name, env_vars_copied)?;
+    if payload.copy_participants.unwrap_or(false)
+    {
+        deploy_payload.participants = project_service::get_project_participants(&state.db_pool, source.id).await?
+            .into_iter()
+            .filter(|participant| participant != owner)
+            .collect();
+    }
+    validate_deploy_payload(&deploy_payload, &state.config())?;
+
+    let deployment = enqueue_project_deployment(&state, owner, &deploy_payload).await?;
+
+    info!(
+        "User '{}' duplicated project '{}' as '{}' for '{}'.",
+        user_login, source.name, deploy_payload.project_name, owner
+    );
+
+    Ok((StatusCode::ACCEPTED, Json(json!({ "deployment": deployment, "env_vars_copied": env_vars_copied }))))
+}
+
+pub async fn export_project_archive_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(project_id): Path<i32>,
+    Query(query): Query<ExportArchiveQuery>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let user_login = &claims.sub;
+    let include_secrets = query.include_secrets.unwrap_or(false);
+
+    let project = get_project_for_owner(&state, project_id, user_login, claims.is_admin).await?;
+
+    let export_guard = ExportGuard::acquire(&state, user_login)?;
+
+    if include_secrets
+    {
+        warn!("User '{}' exported project '{}' including its secrets.", user_login, project.name);
+    }
+    else
+    {
+        info!("User '{}' exported project '{}'.", user_login, project.name);
+    }
+
+    let database = export_database_details(&state, &claims, &project, include_secrets).await?;
+    let files = build_export_files(&state, &project, include_secrets, database).await?;
+
+    if include_secrets
+    {
+        let env_var_keys: Vec<String> = get_decrypted_env_vars(&project, &state.config().encryption_key)?
+            .map(|vars| vars.into_keys().collect())
+            .unwrap_or_default();
+
+        audit_service::record(&state.db_pool, AuditRecord
+        {
+            actor: user_login,
+            action: "env_export",
+            target_type: AuditTargetType::Project,
+            target_id: project.id,
+            target_name: Some(&project.name),
+            performed_by_admin: project_event_service::performed_by_admin(&project.owner, &claims),
+            details: Some(json!({ "keys": env_var_keys })),
+        }).await;
+    }
+    record_project_event(&state, &project, &claims, ProjectEventAction::Export, Some(json!({ "include_secrets": include_secrets }))).await;
+
+    let docker = state.docker_for(&project.docker_host)?.clone();
+    let filename = format!("{}-export.tar.gz", project.name);
+
+    let chunks = archive_service::stream_project_archive(
+        docker,
+        project.container_name.clone(),
+        files,
+        project.persistent_volume_path.clone(),
+    )
+    .map(move |chunk|
+    {
+        let _ = &export_guard;
+        chunk
+    });
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/gzip".to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", filename)),
+        ],
+        Body::from_stream(chunks),
+    ))
+}
+
+pub async fn export_project_config_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(project_id): Path<i32>,
+    Query(query): Query<ExportArchiveQuery>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let user_login = &claims.sub;
+    let include_secrets = query.include_secrets.unwrap_or(false);
+
+    let project = get_project_for_owner(&state, project_id, user_login, claims.is_admin).await?;
+
+    let env_vars = get_decrypted_env_vars(&project, &state.config().encryption_key)?.unwrap_or_default();
+    let participants = project_service::get_project_participants(&state.db_pool, project.id).await?;
+
+    let database = export_database_details(&state, &claims, &project, include_secrets).await?.map(|db| json!(db));
+
+    info!("User '{}' exported the configuration of project '{}'.", user_login, project.name);
+
+    let document = ProjectConfigDocument
+    {
+        schema_version: CONFIG_DOCUMENT_VERSION,
+        name: project.name.clone(),
+        source_type: project.source,
+        source_url: project.source_url.clone(),
+        source_branch: project.source_branch.clone(),
+        source_root_dir: project.source_root_dir.clone(),
+        image_tag: project.deployed_image_tag.clone(),
+        env_vars: env_vars.into_iter().collect(),
+        persistent_volume_path: project.persistent_volume_path.clone(),
+        participants,
+        description: project.description.clone(),
+        tags: project.tags.clone(),
+        settings: ProjectConfigSnapshot::of(&project),
+        database,
+    };
+
+    Ok((
+        [(header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}-config.json\"", project.name))],
+        Json(document),
+    ))
+}
+
+pub async fn import_project_config_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    ApiJson(payload): ApiJson<ImportConfigPayload>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let version = payload.document.get("schema_version").and_then(serde_json::Value::as_u64).unwrap_or(0);
+    if version != u64::from(CONFIG_DOCUMENT_VERSION)
+    {
+        return Err(ProjectErrorCode::UnsupportedConfigVersion(version, CONFIG_DOCUMENT_VERSION).into());
+    }
+
+    let document: ProjectConfigDocument = serde_json::from_value(payload.document)
+        .map_err(|e| AppError::BadRequest(format!("Invalid configuration document: {}", e)))?;
+
+    let user_login = &claims.sub;
+    let deploy_payload = build_import_payload(document, payload.new_name, user_login);
+    validate_deploy_payload(&deploy_payload, &state.config())?;
+
+    let deployment = enqueue_project_deployment(&state, user_login, &deploy_payload).await?;
+
+    info!("User '{}' imported a project configuration as '{}'.", user_login, deploy_payload.project_name);
+
+    Ok((StatusCode::ACCEPTED, Json(json!({ "deployment": deployment }))))
+}
+
+fn build_duplicate_payload(
+    state: &AppState,
+    source: &crate::model::project::Project,
+    new_name: String,
+    copy_env_vars: bool,
+) -> Result<DeployPayload, AppError>
+{
+    let env_vars = if copy_env_vars { get_decrypted_env_vars(source, &state.config().encryption_key)? } else { None };
+
+    let (image_url, github_repo_url) = match source.source
+    {
+        ProjectSourceType::Direct => (Some(source.source_url.clone()), None),
+        ProjectSourceType::Github => (None, Some(source.source_url.clone())),
+    };
+
+    Ok(DeployPayload
+    {
+        project_name: new_name,
+        image_url,
+        github_repo_url,
+        github_branch: source.source_branch.clone(),
+        github_root_dir: source.source_root_dir.clone(),
+        participants: Vec::new(),
+        env_vars,
+        persistent_volume_path: source.persistent_volume_path.clone(),
+        create_database: Some(false),
+        database_template: None,
+        docker_host: None,
+        scan_fail_on: None,
+        // Une copie est personnelle : la dupliquer ne demande qu'un accès au projet source.
+        team_id: None,
+        description: source.description.clone(),
+        tags: source.tags.clone(),
+    })
+}
+
+fn build_import_payload(document: ProjectConfigDocument, new_name: String, user_login: &str) -> DeployPayload
+{
+    let (image_url, github_repo_url) = match document.source_type
+    {
+        ProjectSourceType::Direct => (Some(document.source_url), None),
+        ProjectSourceType::Github => (None, Some(document.source_url)),
+    };
+
+    DeployPayload
+    {
+        project_name: new_name,
+        image_url,
+        github_repo_url,
+        github_branch: document.source_branch,
+        github_root_dir: document.source_root_dir,
+        participants: document.participants.into_iter().filter(|participant| participant != user_login).collect(),
+        env_vars: (!document.env_vars.is_empty()).then(|| document.env_vars.into_iter().collect()),
+        persistent_volume_path: document.persistent_volume_path,
+        create_database: Some(false),
+        database_template: None,
+        docker_host: None,
+        scan_fail_on: None,
+        team_id: None,
+        description: document.description,
+        tags: document.tags,
+    }
+}
+
+// Le mot de passe n'est exporté qu'avec `include_secrets`, et chaque export est audité.
+async fn export_database_details(
+    state: &AppState,
+    claims: &Claims,
+    project: &crate::model::project::Project,
+    include_secrets: bool,
+) -> Result<Option<crate::model::database::DatabaseDetailsResponse>, AppError>
+{
+    let Some(db) = database_service::get_database_by_project_id(&state.db_pool, project.id).await?
+    else
+    {
+        return Ok(None);
+    };
+
+    if !include_secrets
+    {
+        return Ok(Some(database_service::create_masked_db_details_response(db, &state.config())));
+    }
+
+    let details = database_service::create_db_details_response(db, &state.config(), &state.config().encryption_key)?;
+    warn!("User '{}' exported project '{}' with its database password.", claims.sub, project.name);
+
+    audit_service::record(&state.db_pool, AuditRecord
+    {
+        actor: &claims.sub,
+        action: "database_password_export",
+        target_type: AuditTargetType::Database,
+        target_id: details.id,
+        target_name: Some(&details.database_name),
+        performed_by_admin: project_event_service::performed_by_admin(&details.owner_login, claims),
+        details: Some(json!({ "project_id": project.id })),
+    }).await;
+
+    Ok(Some(details))
+}
+
+async fn build_export_files(
+    state: &AppState,
+    project: &crate::model::project::Project,
+    include_secrets: bool,
+    database: Option<crate::model::database::DatabaseDetailsResponse>,
+) -> Result<Vec<ArchiveFile>, AppError>
+{
+    let env_vars = get_decrypted_env_vars(project, &state.config().encryption_key)?.unwrap_or_default();
+    let participants = project_service::get_project_participants(&state.db_pool, project.id).await?;
+
+    let mut env_var_keys: Vec<&String> = env_vars.keys().collect();
+    env_var_keys.sort();
+
+    let mut metadata = serde_json::to_value(project).map_err(|_| AppError::InternalServerError)?;
+    if let Some(obj) = metadata.as_object_mut()
+    {
+        obj.remove("env_vars");
+        obj.insert("env_var_keys".to_string(), json!(env_var_keys));
+        obj.insert("participants".to_string(), json!(participants));
+        obj.insert("database".to_string(), match &database
+        {
+            Some(db) if include_secrets => json!(db),
+            Some(db) => json!({
+                "database_name": db.database_name,
+                "username": db.username,
+                "host": db.host,
+                "port": db.port,
+            }),
+            None => serde_json::Value::Null,
+        });
+    }
+
+    let mut files = vec![ArchiveFile
+    {
+        path: "project.json".to_string(),
+        contents: serde_json::to_vec_pretty(&metadata).map_err(|_| AppError::InternalServerError)?,
+    }];
+
+    if include_secrets && !env_vars.is_empty()
+    {
+        files.push(ArchiveFile
+        {
+            path: ".env".to_string(),
+            contents: format_dotenv(&env_vars).into_bytes(),
+        });
+    }
+
+    Ok(files)
+}
+
+fn format_dotenv(env_vars: &HashMap<String, String>) -> String
+{
+    let mut keys: Vec<&String> = env_vars.keys().collect();
+    keys.sort();
+
+    keys.into_iter()
+        .map(|key|
+        {
+            let escaped = env_vars[key]
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('\n', "\\n");
+            format!("{}=\"{}\"\n", key, escaped)
+        })
+        .collect()
+}
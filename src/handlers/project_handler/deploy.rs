@@ -0,0 +1,456 @@
+use super::*;
+
+pub async fn deploy_project_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    ApiJson(mut payload): ApiJson<DeployPayload>,
+) -> Result<impl IntoResponse, AppError>
+{
+    validate_deploy_payload(&payload, &state.config())?;
+    payload.description = validation_service::sanitize_project_description(payload.description)?;
+    payload.tags = validation_service::sanitize_project_tags(payload.tags)?;
+
+    if let Some(docker_host) = &payload.docker_host
+    {
+        if !claims.is_admin
+        {
+            return Err(AppError::Unauthorized("Only admins can pin a deployment to a Docker host.".to_string()));
+        }
+        state.docker_for(docker_host).map_err(|_| AppError::BadRequest(format!("Unknown Docker host '{}'.", docker_host)))?;
+    }
+    
+    let deployment = enqueue_project_deployment(&state, &claims.sub, &payload).await?;
+
+    Ok((StatusCode::ACCEPTED, Json(json!({ "deployment": deployment }))))
+}
+
+pub async fn validate_image_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    ApiJson(payload): ApiJson<ValidateImagePayload>,
+) -> Result<impl IntoResponse, AppError>
+{
+    // Without a project name, only waivers that apply to every project are considered.
+    let project_name = payload.project_name.as_deref().unwrap_or_default();
+    if !project_name.is_empty()
+    {
+        validation_service::validate_project_name(project_name)?;
+    }
+
+    if let Some(root_dir) = &payload.github_root_dir
+    {
+        validation_service::validate_source_root_dir(root_dir)?;
+    }
+
+    let scan_threshold = policy_service::get_scan_threshold(&state.db_pool, &state.config(), &claims.sub, None, payload.scan_fail_on).await?;
+
+    let _slot = state.image_validation_slots.try_acquire().map_err(|_|
+    {
+        AppError::TooManyRequests("Too many image validations are running. Please retry shortly.".to_string())
+    })?;
+
+    let docker_host = state.default_docker_host();
+    let docker = state.docker_for(&docker_host)?;
+
+    info!("User '{}' started an image dry-run.", claims.sub);
+
+    let mut guard = DryRunImageGuard { docker: docker.clone(), image: None };
+
+    let image = match (&payload.image_url, &payload.github_repo_url)
+    {
+        (Some(image_url), None) =>
+        {
+            validation_service::validate_image_url(image_url)?;
+
+            if docker_service::get_image_digest(docker, image_url).await?.is_none()
+            {
+                guard.image = Some(image_url.clone());
+            }
+
+            let host_platform = state.docker_platforms.get(&docker_host);
+            pull_image_with_error_handling(&state, docker, project_name, image_url, host_platform).await?;
+            check_platform_with_rollback(docker, image_url, host_platform).await?;
+            let host = state.docker_host_config(&docker_host)?;
+            signature_service::verify_image_signature(&state.db_pool, &state.config(), &host, docker, image_url).await?;
+            scan_service::scan_image(&state.db_pool, &state.config(), &host, image_url, project_name, scan_threshold).await?;
+            image_url.clone()
+        }
+        (None, Some(github_repo_url)) =>
+        {
+            let image_tag = generate_image_tag(&format!("dry-run/{}", if project_name.is_empty() { "image" } else { project_name }));
+            guard.image = Some(image_tag.clone());
+
+            build_github_image(
+                &state,
+                &docker_host,
+                project_name,
+                &image_tag,
+                GithubBuild
+                {
+                    source: GithubBuildSource
+                    {
+                        repo_url: github_repo_url,
+                        branch: payload.github_branch.as_deref(),
+                        root_dir: payload.github_root_dir.as_deref(),
+                    },
+                    cache: BuildCache::Bypass,
+                    scan_threshold,
+                },
+                &mut DeployTimings::default(),
+            ).await?;
+            image_tag
+        }
+        _ =>
+        {
+            return Err(AppError::BadRequest(
+                "You must provide either an 'image_url' or a 'github_repo_url'.".to_string()
+            ));
+        }
+    };
+
+    let report = ImageValidationReport
+    {
+        platform: docker_service::get_image_platform(docker, &image).await?.map(|platform| platform.to_string()),
+        size_bytes: docker_service::get_image_size(docker, &image).await?,
+        image,
+    };
+
+    Ok(Json(json!({ "passed": true, "report": report })))
+}
+
+pub async fn check_image_update_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(project_id): Path<i32>,
+    ApiJson(payload): ApiJson<CheckImageUpdatePayload>,
+) -> Result<Json<ImageUpdateCheck>, AppError>
+{
+    let project = get_project_for_user(&state, project_id, &claims.sub, claims.is_admin).await?;
+
+    validate_project_source(&project.source, ProjectSourceType::Direct, "Image update check")?;
+
+    let candidate = payload.candidate_tag.unwrap_or_else(|| project.source_url.clone());
+    validation_service::validate_image_url(&candidate)?;
+
+    let scan_threshold = policy_service::get_scan_threshold(&state.db_pool, &state.config(), &project.owner, Some(project.id), None).await?;
+
+    reserve_image_update_check(&state, project.id)?;
+
+    let _slot = state.image_validation_slots.try_acquire().map_err(|_|
+    {
+        AppError::TooManyRequests("Too many image validations are running. Please retry shortly.".to_string())
+    })?;
+
+    let docker = state.docker_for(&project.docker_host)?;
+    let host = state.docker_host_config(&project.docker_host)?;
+    let host_platform = state.docker_platforms.get(&project.docker_host);
+
+    info!("User '{}' is checking image '{}' as an update of project '{}'.", claims.sub, candidate, project.name);
+
+    let check_repo = format!("hangar-check/{}", project.name);
+    let check_tag = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs().to_string();
+    let check_ref = format!("{}:{}", check_repo, check_tag);
+    let mut guard = DryRunImageGuard { docker: docker.clone(), image: None };
+
+    let previous_image_id = docker_service::get_image_digest(docker, &candidate).await?;
+    pull_image_with_error_handling(&state, docker, &project.name, &candidate, host_platform).await?;
+    let tagged = docker_service::tag_image(docker, &candidate, &check_repo, &check_tag).await;
+    restore_image_tag(docker, &candidate, previous_image_id.as_deref()).await;
+    tagged?;
+    guard.image = Some(check_ref.clone());
+
+    let image_id = docker_service::get_image_digest(docker, &check_ref).await?.ok_or(AppError::InternalServerError)?;
+    let platform = docker_service::get_image_platform(docker, &check_ref).await?;
+
+    let gates = vec![
+        GateVerdict::from_result("platform", check_image_platform(platform.as_ref(), host_platform))?,
+        GateVerdict::from_result(
+            "signature",
+            signature_service::verify_local_image_signature(&state.db_pool, &state.config(), &host, docker, &check_ref, &candidate).await,
+        )?,
+        GateVerdict::from_result("scan", scan_service::scan_image(&state.db_pool, &state.config(), &host, &check_ref, &project.name, scan_threshold).await)?,
+    ];
+
+    let passed = gates.iter().all(|gate| gate.passed);
+    let changed = image_id != project.deployed_image_digest;
+
+    Ok(Json(ImageUpdateCheck
+    {
+        size_bytes: docker_service::get_image_size(docker, &check_ref).await?,
+        platform: platform.map(|platform| platform.to_string()),
+        current_image_id: project.deployed_image_digest,
+        changed,
+        passed,
+        ready_to_apply: passed && changed,
+        apply: ApplyImageUpdate
+        {
+            method: "PUT",
+            path: format!("/api/projects/{}/image", project.id),
+            body: json!({ "new_image_url": candidate }),
+        },
+        candidate,
+        image_id,
+        gates,
+    }))
+}
+
+pub(super) fn validate_deploy_payload(payload: &DeployPayload, config: &crate::config::Config) -> Result<(), AppError>
+{
+    validation_service::validate_project_name(&payload.project_name)?;
+
+    if let Some(vars) = &payload.env_vars
+    {
+        validation_service::validate_env_vars(vars, config)?;
+    }
+
+    if let Some(path) = &payload.persistent_volume_path
+    {
+        validation_service::validate_volume_path(path)?;
+    }
+
+    if let Some(root_dir) = &payload.github_root_dir
+    {
+        validation_service::validate_source_root_dir(root_dir)?;
+    }
+
+    if payload.database_template.is_some() && !payload.create_database.unwrap_or(false)
+    {
+        return Err(AppError::BadRequest("'database_template' requires 'create_database'.".to_string()));
+    }
+
+    if payload.image_url.is_none() && payload.github_repo_url.is_none()
+    {
+        return Err(AppError::BadRequest(
+            "You must provide either an 'image_url' or a 'github_repo_url'.".to_string()
+        ));
+    }
+
+    Ok(())
+}
+
+pub(super) fn check_rate_limit_editable(project: &crate::model::project::Project, claims: &Claims) -> Result<(), AppError>
+{
+    if project.rate_limit_set_by_admin && !claims.is_admin
+    {
+        return Err(AppError::Unauthorized("This rate limit was set by an administrator and can only be changed by one.".to_string()));
+    }
+    Ok(())
+}
+
+pub(super) fn validate_rate_limit(
+    state: &AppState,
+    payload: &RateLimitPayload,
+    is_admin: bool,
+) -> Result<ProjectRateLimit, AppError>
+{
+    let config = &state.config();
+    let max = if is_admin { config.rate_limit_max_rps } else { config.rate_limit_owner_max_rps.min(config.rate_limit_max_rps) };
+    let burst = payload.burst.unwrap_or(payload.average);
+
+    if payload.average < config.rate_limit_min_rps || payload.average > max
+    {
+        return Err(AppError::BadRequest(format!(
+            "The average rate must be between {} and {} requests per second.",
+            config.rate_limit_min_rps, max
+        )));
+    }
+
+    if burst < payload.average || burst > max
+    {
+        return Err(AppError::BadRequest(format!(
+            "The burst must be between the average rate ({}) and {}.",
+            payload.average, max
+        )));
+    }
+
+    Ok(ProjectRateLimit { average: payload.average, burst })
+}
+
+pub(super) fn validate_replicas(
+    state: &AppState,
+    project: &crate::model::project::Project,
+    payload: &ReplicasPayload,
+    is_admin: bool,
+) -> Result<bool, AppError>
+{
+    let config = &state.config();
+    let max = if is_admin { config.replicas_max } else { config.replicas_owner_max.min(config.replicas_max) };
+
+    if payload.replicas < 1 || payload.replicas > max
+    {
+        return Err(AppError::BadRequest(format!("The number of replicas must be between 1 and {}.", max)));
+    }
+
+    let volume_share_safe = payload.volume_share_safe.unwrap_or(project.volume_share_safe);
+    if payload.replicas > 1 && project.persistent_volume_path.is_some() && !volume_share_safe
+    {
+        return Err(ProjectErrorCode::VolumeNotShareable.into());
+    }
+
+    Ok(volume_share_safe)
+}
+
+pub(super) fn validate_project_source(
+    actual: &ProjectSourceType,
+    expected: ProjectSourceType,
+    operation: &str,
+) -> Result<(), AppError>
+{
+    if !matches!(actual, ref t if **t == expected)
+    {
+        let source_name = match expected
+        {
+            ProjectSourceType::Direct => "direct",
+            ProjectSourceType::Github => "github",
+        };
+        
+        return Err(AppError::BadRequest(
+            format!("{} is only supported for '{}' source projects.", operation, source_name)
+        ));
+    }
+    
+    Ok(())
+}
+
+pub(super) async fn enqueue_project_deployment(
+    state: &AppState,
+    user_login: &str,
+    payload: &DeployPayload,
+) -> Result<Deployment, AppError>
+{
+    check_deployment_preconditions(state, user_login, payload).await?;
+
+    prepare_participants(payload.participants.clone(), user_login, state.config().max_participants_per_project)?;
+
+    let payload_json = serde_json::to_string(payload).map_err(|_| AppError::InternalServerError)?;
+
+    let deployment = deployment_service::enqueue_deployment(
+        &state.db_pool,
+        user_login,
+        &payload.project_name,
+        &payload_json,
+        &state.config().encryption_key,
+    ).await?;
+
+    info!(
+        "Deployment {} of project '{}' queued for user '{}'.",
+        deployment.id, payload.project_name, user_login
+    );
+
+    Ok(deployment)
+}
+
+pub(super) async fn check_deployment_preconditions(
+    state: &AppState,
+    user_login: &str,
+    payload: &DeployPayload,
+) -> Result<ScanThreshold, AppError>
+{
+    match payload.team_id
+    {
+        Some(team_id) =>
+        {
+            let team = team_service::require_role(&state.db_pool, team_id, user_login, false, TeamRole::Owner).await?;
+            quota_service::ensure_team_available(&state.db_pool, &state.config(), &team).await?;
+        }
+        None => quota_service::ensure_available(&state.db_pool, &state.config(), user_login, QuotaDimension::Projects).await?,
+    }
+
+    let scan_threshold = policy_service::get_scan_threshold(&state.db_pool, &state.config(), user_login, None, payload.scan_fail_on).await?;
+
+    if project_service::check_project_name_exists(&state.db_pool, &payload.project_name).await?
+    {
+        return Err(ProjectErrorCode::ProjectNameTaken.into());
+    }
+
+    if payload.create_database.unwrap_or(false)
+    {
+        quota_service::ensure_available(&state.db_pool, &state.config(), user_login, QuotaDimension::Databases).await?;
+    }
+
+    // Un nom inconnu échoue avant la construction de l'image plutôt qu'à la fin du déploiement.
+    if let Some(template) = &payload.database_template
+    {
+        db_template_service::get_template_script(&state.db_pool, template).await?;
+    }
+
+    Ok(scan_threshold)
+}
+
+pub(super) fn prepare_participants(
+    participants: Vec<String>,
+    user_login: &str,
+    max_participants: i64,
+) -> Result<Vec<String>, AppError>
+{
+    let participants_set: HashSet<String> = participants.into_iter().collect();
+    
+    if participants_set.contains(user_login)
+    {
+        return Err(ProjectErrorCode::OwnerCannotBeParticipant.into());
+    }
+
+    if participants_set.len() as i64 > max_participants
+    {
+        return Err(ProjectErrorCode::TooManyParticipants(max_participants).into());
+    }
+    
+    Ok(participants_set.into_iter().collect())
+}
+
+pub(super) async fn prepare_deployment_source(
+    state: &AppState,
+    docker_host: &str,
+    docker: &bollard::Docker,
+    payload: &DeployPayload,
+    scan_threshold: ScanThreshold,
+    timings: &mut DeployTimings,
+) -> Result<DeploymentSource, AppError>
+{
+    if let Some(image_url) = &payload.image_url
+    {
+        let tag = prepare_direct_source(state, docker_host, docker, &payload.project_name, image_url, scan_threshold, timings).await?;
+        let image_warnings = image_config_warnings(docker, &tag).await;
+        return Ok(DeploymentSource
+        {
+            source_type: ProjectSourceType::Direct,
+            source_url: image_url.clone(),
+            image_tag: tag,
+            image_warnings,
+        });
+    }
+
+    if let Some(github_repo_url) = &payload.github_repo_url
+    {
+        let tag = build_image_from_github_source(
+            state,
+            docker_host,
+            &payload.project_name,
+            GithubBuild
+            {
+                source: GithubBuildSource
+                {
+                    repo_url: github_repo_url,
+                    branch: payload.github_branch.as_deref(),
+                    root_dir: payload.github_root_dir.as_deref(),
+                },
+                cache: BuildCache::Reuse,
+                scan_threshold,
+            },
+            timings,
+        ).await?;
+        let image_warnings = image_config_warnings(docker, &tag).await;
+
+        return Ok(DeploymentSource
+        {
+            source_type: ProjectSourceType::Github,
+            source_url: github_repo_url.clone(),
+            image_tag: tag,
+            image_warnings,
+        });
+    }
+
+    Err(AppError::BadRequest(
+        "You must provide either an 'image_url' or a 'github_repo_url'.".to_string()
+    ))
+}
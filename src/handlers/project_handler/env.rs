@@ -0,0 +1,568 @@
+use super::*;
+
+pub async fn update_env_vars_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(project_id): Path<i32>,
+    ApiJson(payload): ApiJson<UpdateEnvPayload>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let user_login = &claims.sub;
+    info!("User '{}' initiated blue-green env var update for project ID: {}", user_login, project_id);
+
+    validation_service::validate_env_vars(&payload.env_vars, &state.config())?;
+    if let Some(visibility) = &payload.visibility
+    {
+        validation_service::validate_env_var_visibility(visibility, &payload.env_vars)?;
+    }
+
+    let project = get_project_for_user(&state, project_id, user_login, claims.is_admin).await?;
+
+    if payload.visibility.is_some() && !claims.is_admin && !team_service::has_owner_rights(&state.db_pool, &project, user_login).await?
+    {
+        return Err(AppError::BadRequest("Only the project owner can change the visibility of environment variables.".to_string()));
+    }
+
+    if let Some(expected_hash) = &payload.expected_diff_hash
+    {
+        let current_env_vars = get_decrypted_env_vars(&project, &state.config().encryption_key)?.unwrap_or_default();
+        let diff = compute_env_vars_diff(&current_env_vars, &payload.env_vars);
+
+        if env_vars_diff_hash(&project, &diff)? != *expected_hash
+        {
+            return Err(ProjectErrorCode::EnvVarsChangedSincePreview.into());
+        }
+    }
+
+    let deployment = create_blue_green_deployment_for_env_update(&state, &project);
+
+    execute_env_vars_blue_green_deployment(
+        &state,
+        &project,
+        &deployment,
+        &payload.env_vars,
+    ).await?;
+
+    let current_shared = project_service::get_shared_env_keys(&state.db_pool, project.id).await?;
+    let shared_keys = shared_env_keys_after_update(payload.visibility.as_ref(), current_shared, &payload.env_vars);
+    project_service::set_shared_env_keys(&state.db_pool, project.id, &shared_keys).await?;
+
+    let performed_by_admin = record_project_event(&state, &project, &claims, ProjectEventAction::EnvUpdate, None).await;
+
+    Ok(create_attributed_response("Environment variables updated successfully. The project has been restarted.", performed_by_admin))
+}
+
+pub async fn patch_env_vars_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(project_id): Path<i32>,
+    ApiJson(payload): ApiJson<PatchEnvPayload>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let mut conflicting: Vec<&str> = payload.unset.iter()
+        .filter(|key| payload.set.contains_key(*key))
+        .map(String::as_str)
+        .collect();
+    if !conflicting.is_empty()
+    {
+        conflicting.sort();
+        conflicting.dedup();
+        return Err(AppError::BadRequest(format!(
+            "Keys cannot be both set and unset: '{}'.",
+            conflicting.join("', '")
+        )));
+    }
+
+    validation_service::validate_env_vars(&payload.set, &state.config())?;
+
+    let project = get_project_for_user(&state, project_id, &claims.sub, claims.is_admin).await?;
+
+    let current_env_vars = get_decrypted_env_vars(&project, &state.config().encryption_key)?.unwrap_or_default();
+    let mut env_vars = current_env_vars.clone();
+    for key in &payload.unset
+    {
+        env_vars.remove(key);
+    }
+    let mut set_keys: Vec<&String> = payload.set.keys().collect();
+    set_keys.sort();
+    let details = json!({ "set": set_keys, "unset": payload.unset });
+    env_vars.extend(payload.set.clone());
+    validation_service::validate_env_vars_size(&env_vars, &state.config())?;
+
+    let mut keys: Vec<&String> = env_vars.keys().collect();
+    keys.sort();
+
+    if env_vars == current_env_vars
+    {
+        let (status, Json(mut body)) = create_no_change_response("The environment variables already have these values.");
+        body["keys"] = json!(keys);
+        return Ok((status, Json(body)));
+    }
+
+    info!("User '{}' patched the environment variables of project '{}'.", claims.sub, project.name);
+
+    let deployment = create_blue_green_deployment_for_env_update(&state, &project);
+    execute_env_vars_blue_green_deployment(&state, &project, &deployment, &env_vars).await?;
+
+    let current_shared = project_service::get_shared_env_keys(&state.db_pool, project.id).await?;
+    let shared_keys = shared_env_keys_after_update(None, current_shared, &env_vars);
+    project_service::set_shared_env_keys(&state.db_pool, project.id, &shared_keys).await?;
+
+    let performed_by_admin = record_project_event(&state, &project, &claims, ProjectEventAction::EnvUpdate, Some(details)).await;
+
+    let (status, Json(mut body)) = create_attributed_response("Environment variables updated successfully. The project has been restarted.", performed_by_admin);
+    body["keys"] = json!(keys);
+    Ok((status, Json(body)))
+}
+
+const ENV_IMPORT_MAX_BYTES: usize = 64 * 1024;
+
+pub async fn import_env_file_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(project_id): Path<i32>,
+    mut multipart: Multipart,
+) -> Result<impl IntoResponse, AppError>
+{
+    let contents = read_env_file_field(&mut multipart).await?;
+    let entries = parse_dotenv(&contents)?;
+    if entries.len() > state.config().env_vars_max_count
+    {
+        return Err(ProjectErrorCode::TooManyEnvVars(state.config().env_vars_max_count).into());
+    }
+
+    let mut imported = HashMap::new();
+    let mut skipped = Vec::new();
+    for (key, value) in entries
+    {
+        let entry = HashMap::from([(key, value)]);
+        match validation_service::validate_env_vars(&entry, &state.config())
+        {
+            Ok(()) => imported.extend(entry),
+            Err(AppError::ProjectError(code)) =>
+            {
+                let key = entry.into_keys().next().unwrap_or_default();
+                skipped.push(json!({ "key": key, "reason": code.as_str() }));
+            }
+            Err(e) => return Err(e),
+        }
+    }
+
+    let project = get_project_for_user(&state, project_id, &claims.sub, claims.is_admin).await?;
+
+    let current_env_vars = get_decrypted_env_vars(&project, &state.config().encryption_key)?.unwrap_or_default();
+
+    let mut added: Vec<&String> = imported.keys().filter(|key| !current_env_vars.contains_key(*key)).collect();
+    let mut updated: Vec<&String> = imported.iter()
+        .filter(|(key, value)| current_env_vars.get(*key).is_some_and(|current| current != *value))
+        .map(|(key, _)| key)
+        .collect();
+    added.sort();
+    updated.sort();
+
+    let summary = json!({ "added": added, "updated": updated, "skipped": skipped });
+
+    if added.is_empty() && updated.is_empty()
+    {
+        let (status, Json(mut body)) = create_no_change_response("The file does not change any environment variable.");
+        body["import"] = summary;
+        return Ok((status, Json(body)));
+    }
+
+    info!("User '{}' imported a .env file into project '{}'.", claims.sub, project.name);
+
+    let mut env_vars = current_env_vars;
+    env_vars.extend(imported.clone());
+    validation_service::validate_env_vars_size(&env_vars, &state.config())?;
+
+    let deployment = create_blue_green_deployment_for_env_update(&state, &project);
+    execute_env_vars_blue_green_deployment(&state, &project, &deployment, &env_vars).await?;
+
+    let current_shared = project_service::get_shared_env_keys(&state.db_pool, project.id).await?;
+    let shared_keys = shared_env_keys_after_update(None, current_shared, &env_vars);
+    project_service::set_shared_env_keys(&state.db_pool, project.id, &shared_keys).await?;
+
+    let mut set_keys: Vec<&String> = imported.keys().collect();
+    set_keys.sort();
+    let details = json!({ "set": set_keys, "unset": [], "source": "dotenv_import" });
+    let performed_by_admin = record_project_event(&state, &project, &claims, ProjectEventAction::EnvUpdate, Some(details)).await;
+
+    let (status, Json(mut body)) = create_attributed_response("Environment variables imported successfully. The project has been restarted.", performed_by_admin);
+    body["import"] = summary;
+    Ok((status, Json(body)))
+}
+
+pub async fn get_env_vars_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(project_id): Path<i32>,
+    Query(query): Query<EnvVarsQuery>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let user_login = &claims.sub;
+    let project = get_project_for_user(&state, project_id, user_login, claims.is_admin).await?;
+
+    if !query.reveal.unwrap_or(false)
+    {
+        let shared_keys: HashSet<String> = project_service::get_shared_env_keys(&state.db_pool, project.id).await?.into_iter().collect();
+        let env_vars = env_vars_view(
+            get_decrypted_env_vars(&project, &state.config().encryption_key)?.unwrap_or_default(),
+            &shared_keys,
+            false,
+        );
+
+        return Ok((StatusCode::OK, Json(json!({ "env_vars": env_vars, "revealed": false }))));
+    }
+
+    // Même règle que pour la duplication d'un projet.
+    if !team_service::can_reveal_secrets(&state.db_pool, &project, user_login, claims.is_admin).await?
+    {
+        return Err(AppError::NotFound(format!("Project with ID {} not found or you don't have access.", project_id)));
+    }
+
+    let env_vars: BTreeMap<String, String> = get_decrypted_env_vars(&project, &state.config().encryption_key)?
+        .unwrap_or_default()
+        .into_iter()
+        .collect();
+
+    warn!("User '{}' revealed the environment variables of project '{}'.", user_login, project.name);
+
+    audit_service::record(&state.db_pool, AuditRecord
+    {
+        actor: user_login,
+        action: "env_reveal",
+        target_type: AuditTargetType::Project,
+        target_id: project.id,
+        target_name: Some(&project.name),
+        performed_by_admin: project_event_service::performed_by_admin(&project.owner, &claims),
+        details: Some(json!({ "keys": env_vars.keys().collect::<Vec<_>>() })),
+    }).await;
+
+    Ok((StatusCode::OK, Json(json!({ "env_vars": env_vars, "revealed": true }))))
+}
+
+pub async fn diff_env_vars_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(project_id): Path<i32>,
+    ApiJson(payload): ApiJson<UpdateEnvPayload>,
+) -> Result<impl IntoResponse, AppError>
+{
+    validation_service::validate_env_vars(&payload.env_vars, &state.config())?;
+
+    let project = get_project_for_user(&state, project_id, &claims.sub, claims.is_admin).await?;
+
+    let current_env_vars = get_decrypted_env_vars(&project, &state.config().encryption_key)?.unwrap_or_default();
+    let diff = compute_env_vars_diff(&current_env_vars, &payload.env_vars);
+    let diff_hash = env_vars_diff_hash(&project, &diff)?;
+
+    Ok((StatusCode::OK, Json(json!({ "diff": diff, "diff_hash": diff_hash }))))
+}
+
+async fn read_env_file_field(multipart: &mut Multipart) -> Result<String, AppError>
+{
+    let mut field = loop
+    {
+        let field = multipart.next_field().await
+            .map_err(|e| AppError::BadRequest(format!("Invalid multipart body: {}.", e)))?
+            .ok_or_else(|| AppError::BadRequest("The upload does not contain a file.".to_string()))?;
+
+        if field.file_name().is_some()
+        {
+            break field;
+        }
+    };
+
+    let mut contents = Vec::new();
+    while let Some(chunk) = field.chunk().await
+        .map_err(|e| AppError::BadRequest(format!("Invalid multipart body: {}.", e)))?
+    {
+        if contents.len() + chunk.len() > ENV_IMPORT_MAX_BYTES
+        {
+            return Err(AppError::BadRequest(format!("The file is larger than {} KiB.", ENV_IMPORT_MAX_BYTES / 1024)));
+        }
+        contents.extend_from_slice(&chunk);
+    }
+
+    String::from_utf8(contents).map_err(|_| AppError::BadRequest("The file is not valid UTF-8.".to_string()))
+}
+
+// Les références `${VAR}` sont conservées telles quelles, jamais développées.
+fn parse_dotenv(contents: &str) -> Result<Vec<(String, String)>, AppError>
+{
+    let contents = contents.strip_prefix('\u{feff}').unwrap_or(contents).replace("\r\n", "\n");
+    let mut entries = Vec::new();
+    let mut lines = contents.split('\n').enumerate();
+
+    while let Some((index, line)) = lines.next()
+    {
+        let line_number = index + 1;
+        let line = line.trim_start();
+        if line.is_empty() || line.starts_with('#')
+        {
+            continue;
+        }
+
+        let line = line.strip_prefix("export ").map_or(line, str::trim_start);
+        let Some((key, rest)) = line.split_once('=')
+        else
+        {
+            return Err(AppError::BadRequest(format!("Line {}: expected KEY=value.", line_number)));
+        };
+        let key = key.trim_end().to_string();
+        let rest = rest.trim_start();
+
+        let (value, trailing) = match rest.chars().next()
+        {
+            Some(quote @ ('"' | '\'')) =>
+            {
+                let mut value = String::new();
+                let mut remaining = &rest[1..];
+                loop
+                {
+                    match parse_quoted(remaining, quote, &mut value)
+                    {
+                        Some(after) => break (value, after),
+                        None =>
+                        {
+                            // The closing quote is on a later line.
+                            let Some((_, next)) = lines.next()
+                            else
+                            {
+                                return Err(AppError::BadRequest(format!("Line {}: unterminated quoted value.", line_number)));
+                            };
+                            value.push('\n');
+                            remaining = next;
+                        }
+                    }
+                }
+            }
+            _ =>
+            {
+                let end = rest.find(" #").or_else(|| rest.find("\t#")).unwrap_or(rest.len());
+                (rest[..end].trim_end().to_string(), "")
+            }
+        };
+
+        let trailing = trailing.trim_start();
+        if !trailing.is_empty() && !trailing.starts_with('#')
+        {
+            return Err(AppError::BadRequest(format!("Line {}: unexpected characters after the closing quote.", line_number)));
+        }
+
+        entries.retain(|(existing, _): &(String, String)| *existing != key);
+        entries.push((key, value));
+    }
+
+    Ok(entries)
+}
+
+fn parse_quoted<'a>(text: &'a str, quote: char, value: &mut String) -> Option<&'a str>
+{
+    let mut chars = text.char_indices();
+    while let Some((index, c)) = chars.next()
+    {
+        match c
+        {
+            c if c == quote => return Some(&text[index + 1..]),
+            '\\' if quote == '"' => match chars.next()
+            {
+                Some((_, 'n')) => value.push('\n'),
+                Some((_, 'r')) => value.push('\r'),
+                Some((_, 't')) => value.push('\t'),
+                Some((_, escaped)) => value.push(escaped),
+                None => value.push('\\'),
+            },
+            c => value.push(c),
+        }
+    }
+    None
+}
+
+// Compare clés et valeurs mais ne renvoie que les clés : aucun secret ne quitte le serveur.
+fn compute_env_vars_diff(current: &HashMap<String, String>, new: &HashMap<String, String>) -> EnvVarsDiff
+{
+    let mut added: Vec<String> = new.keys().filter(|key| !current.contains_key(*key)).cloned().collect();
+    let mut removed: Vec<String> = current.keys().filter(|key| !new.contains_key(*key)).cloned().collect();
+    let mut changed: Vec<String> = new.iter()
+        .filter(|(key, value)| current.get(*key).is_some_and(|current_value| current_value != *value))
+        .map(|(key, _)| key.clone())
+        .collect();
+    let unchanged_count = new.iter().filter(|(key, value)| current.get(*key) == Some(*value)).count();
+
+    added.sort();
+    removed.sort();
+    changed.sort();
+
+    EnvVarsDiff { added, removed, changed, unchanged_count }
+}
+
+// Couvre les chiffrés stockés, renouvelés à chaque mise à jour : toute modification concurrente invalide le hash.
+fn env_vars_diff_hash(project: &crate::model::project::Project, diff: &EnvVarsDiff) -> Result<String, AppError>
+{
+    let stored = project.env_vars.as_ref().map(|vars| vars.to_string()).unwrap_or_default();
+    let diff_json = serde_json::to_string(diff).map_err(|_| AppError::InternalServerError)?;
+
+    let mut hasher = Sha256::new();
+    hasher.update(stored.as_bytes());
+    hasher.update(diff_json.as_bytes());
+
+    Ok(hasher.finalize().iter().map(|byte| format!("{:02x}", byte)).collect())
+}
+
+pub(super) fn decrypt_project_env_vars(
+    project: &mut crate::model::project::Project,
+    encryption_key: &EncryptionKeys,
+) -> Result<(), AppError>
+{
+    if let Some(env_vars_value) = &project.env_vars
+    {
+        let encrypted_vars: HashMap<String, String> = serde_json::from_value(env_vars_value.clone())
+            .unwrap_or_default();
+        
+        let decrypted_vars = decrypt_env_vars(&encrypted_vars, encryption_key)?;
+        
+        project.env_vars = Some(serde_json::to_value(decrypted_vars).unwrap());
+    }
+    
+    Ok(())
+}
+
+pub(super) fn mask_env_vars(
+    project: &mut crate::model::project::Project,
+    shared_keys: &HashSet<String>,
+) -> BTreeMap<String, EnvVarVisibility>
+{
+    let Some(serde_json::Value::Object(vars)) = project.env_vars.as_mut()
+    else
+    {
+        return BTreeMap::new();
+    };
+
+    vars.iter_mut()
+        .map(|(key, value)|
+        {
+            let shared = shared_keys.contains(key);
+            *value = env_var_value(value.as_str().unwrap_or_default(), shared, false);
+            let visibility = if shared { EnvVarVisibility::Shared } else { EnvVarVisibility::Secret };
+            (key.clone(), visibility)
+        })
+        .collect()
+}
+
+fn env_vars_view(
+    env_vars: HashMap<String, String>,
+    shared_keys: &HashSet<String>,
+    reveal_secrets: bool,
+) -> BTreeMap<String, serde_json::Value>
+{
+    env_vars.into_iter()
+        .map(|(key, value)|
+        {
+            let value = env_var_value(&value, shared_keys.contains(&key), reveal_secrets);
+            (key, value)
+        })
+        .collect()
+}
+
+// Une clé partagée est en clair pour tous les membres ; une secrète seulement pour qui la révèle.
+fn env_var_value(value: &str, shared: bool, reveal_secrets: bool) -> serde_json::Value
+{
+    if shared || reveal_secrets
+    {
+        json!(value)
+    }
+    else
+    {
+        json!(MaskedEnvVar::of(value))
+    }
+}
+
+fn shared_env_keys_after_update(
+    visibility: Option<&HashMap<String, EnvVarVisibility>>,
+    current_shared: Vec<String>,
+    env_vars: &HashMap<String, String>,
+) -> Vec<String>
+{
+    let mut shared: Vec<String> = match visibility
+    {
+        Some(visibility) => visibility.iter()
+            .filter(|(_, visibility)| **visibility == EnvVarVisibility::Shared)
+            .map(|(key, _)| key.clone())
+            .collect(),
+        None => current_shared.into_iter().filter(|key| env_vars.contains_key(key)).collect(),
+    };
+    shared.sort();
+    shared
+}
+
+pub(super) fn get_decrypted_env_vars(
+    project: &crate::model::project::Project,
+    encryption_key: &EncryptionKeys,
+) -> Result<Option<HashMap<String, String>>, AppError>
+{
+    if let Some(env_vars_value) = &project.env_vars
+    {
+        let encrypted_vars: HashMap<String, String> = serde_json::from_value(env_vars_value.clone())
+            .unwrap_or_default();
+        
+        Ok(Some(decrypt_env_vars(&encrypted_vars, encryption_key)?))
+    }
+    else
+    {
+        Ok(None)
+    }
+}
+
+fn decrypt_env_vars(
+    encrypted_vars: &HashMap<String, String>,
+    key: &EncryptionKeys,
+) -> Result<HashMap<String, String>, AppError>
+{
+    encrypted_vars
+        .iter()
+        .map(|(k, v_b64)|
+        {
+            let encrypted_val = BASE64_STANDARD
+                .decode(v_b64)
+                .map_err(|_| AppError::InternalServerError)?;
+            
+            let decrypted_val = crypto_service::decrypt(&encrypted_val, key)?;
+            
+            Ok((k.clone(), decrypted_val))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn shared_keys_reach_participants_and_secrets_stay_with_owners()
+    {
+        let env_vars = HashMap::from([
+            ("LOG_LEVEL".to_string(), "debug".to_string()),
+            ("API_TOKEN".to_string(), "s3cr3t".to_string()),
+        ]);
+        let shared_keys = HashSet::from(["LOG_LEVEL".to_string()]);
+        let masked = json!(MaskedEnvVar::of("s3cr3t"));
+
+        // (lecteur, révélation des secrets) -> valeur de API_TOKEN ; LOG_LEVEL est toujours en clair
+        let cases = [
+            ("owner", true, json!("s3cr3t")),
+            ("owner without reveal", false, masked.clone()),
+            ("participant", false, masked.clone()),
+            ("admin", true, json!("s3cr3t")),
+        ];
+
+        for (reader, reveal_secrets, expected_secret) in cases
+        {
+            let view = env_vars_view(env_vars.clone(), &shared_keys, reveal_secrets);
+
+            assert_eq!(view["LOG_LEVEL"], json!("debug"), "{}", reader);
+            assert_eq!(view["API_TOKEN"], expected_secret, "{}", reader);
+        }
+    }
+}
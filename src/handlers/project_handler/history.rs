@@ -0,0 +1,199 @@
+use super::*;
+
+const PROJECT_EVENTS_LIMIT: i64 = 100;
+
+pub async fn get_project_events_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(project_id): Path<i32>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let project = get_project_for_user(&state, project_id, &claims.sub, claims.is_admin).await?;
+
+    let events = project_event_service::get_project_events(&state.db_pool, project.id, PROJECT_EVENTS_LIMIT).await?;
+
+    Ok((StatusCode::OK, Json(json!({ "events": events }))))
+}
+
+pub async fn get_project_audit_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(project_id): Path<i32>,
+    list_params: ListParams<ProjectAuditList>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let project = get_project_for_owner(&state, project_id, &claims.sub, claims.is_admin).await?;
+
+    let filters = AuditFilters
+    {
+        target_type: Some(AuditTargetType::Project),
+        target_id: Some(project.id),
+        ..Default::default()
+    };
+    let (entries, total) = audit_service::get_entries_page(
+        &state.db_pool,
+        &filters,
+        list_params.sort_by,
+        list_params.order,
+        i64::from(list_params.per_page),
+        list_params.offset(),
+    ).await?;
+
+    Ok(Json(list_params.page_of(&entries, total as usize)?))
+}
+
+pub async fn get_config_history_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(project_id): Path<i32>,
+    list_params: ListParams<ConfigHistoryList>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let project = get_project_for_owner(&state, project_id, &claims.sub, claims.is_admin).await?;
+
+    let revisions = config_revision_service::get_revisions(&state.db_pool, project.id).await?;
+
+    Ok((StatusCode::OK, Json(list_params.paginate(&revisions)?)))
+}
+
+pub async fn restore_config_revision_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path((project_id, revision)): Path<(i32, i32)>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let project = get_project_for_owner(&state, project_id, &claims.sub, true).await?;
+
+    let target = config_revision_service::get_revision(&state.db_pool, project.id, revision)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Revision {} of project {} not found.", revision, project_id)))?
+        .snapshot
+        .0;
+    let current = ProjectConfigSnapshot::of(&project);
+
+    let mut restored = Vec::new();
+
+    if target.route_middlewares() != current.route_middlewares()
+    {
+        apply_route_middlewares(&state, &project, target.route_middlewares()).await?;
+        restored.push("route");
+    }
+
+    if target.egress_policy != current.egress_policy
+    {
+        docker_service::project_network(&state.config(), target.egress_policy)?;
+        let project = get_project_for_owner(&state, project_id, &claims.sub, true).await?;
+        apply_egress_policy(&state, &project, target.egress_policy).await?;
+        restored.push("egress_policy");
+    }
+
+    if target.replicas != current.replicas || target.volume_share_safe != current.volume_share_safe
+    {
+        let project = get_project_for_owner(&state, project_id, &claims.sub, true).await?;
+        let payload = ReplicasPayload { replicas: target.replicas, volume_share_safe: Some(target.volume_share_safe) };
+        let volume_share_safe = validate_replicas(&state, &project, &payload, true)?;
+        apply_replicas(&state, &project, target.replicas, volume_share_safe).await?;
+        restored.push("replicas");
+    }
+
+    let mut not_restored = Vec::new();
+    if target.env_var_keys != current.env_var_keys
+    {
+        not_restored.push("env_var_keys");
+    }
+    if target.persistent_volume_path != current.persistent_volume_path
+    {
+        not_restored.push("persistent_volume_path");
+    }
+    if target.source_branch != current.source_branch || target.source_root_dir != current.source_root_dir
+    {
+        not_restored.push("source");
+    }
+
+    if restored.is_empty()
+    {
+        return Ok((StatusCode::OK, Json(json!({
+            "status": "no_change",
+            "message": "The restorable settings already match this revision.",
+            "not_restored": not_restored,
+        }))));
+    }
+
+    info!("Admin '{}' restored {:?} of project '{}' from revision {}.", claims.sub, restored, project.name, revision);
+
+    let performed_by_admin = record_project_event(&state, &project, &claims, ProjectEventAction::ConfigRestore, Some(json!({ "revision": revision, "restored": restored }))).await;
+
+    Ok((StatusCode::OK, Json(json!({
+        "status": "success",
+        "message": format!("Configuration restored from revision {}. The project has been recreated.", revision),
+        "performed_by_admin": performed_by_admin,
+        "restored": restored,
+        "not_restored": not_restored,
+    }))))
+}
+
+pub async fn get_project_sbom_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(project_id): Path<i32>,
+) -> Result<Response, AppError>
+{
+    let project = get_project_for_owner(&state, project_id, &claims.sub, claims.is_admin).await?;
+
+    match sbom_service::get_document(&state.db_pool, &project.deployed_image_digest).await?
+    {
+        SbomLookup::Available(document) => Ok((
+            StatusCode::OK,
+            [(header::CONTENT_TYPE, "application/vnd.cyclonedx+json")],
+            document,
+        ).into_response()),
+        SbomLookup::Missing(reason) =>
+        {
+            let message = match reason
+            {
+                Some(reason) => format!("No SBOM is available for the deployed image of project {}: {}", project_id, reason),
+                None => format!("No SBOM is available for the deployed image of project {}.", project_id),
+            };
+            Err(AppError::NotFound(message))
+        }
+    }
+}
+
+pub(super) async fn record_project_event(
+    state: &AppState,
+    project: &crate::model::project::Project,
+    claims: &Claims,
+    action: ProjectEventAction,
+    details: Option<serde_json::Value>,
+) -> bool
+{
+    let performed_by_admin = project_event_service::performed_by_admin(&project.owner, claims);
+
+    if let Err(e) = project_event_service::record_event(&state.db_pool, project, action, &claims.sub, performed_by_admin, details).await
+    {
+        warn!("Could not record event {:?} of project '{}': {}", action, project.name, e);
+    }
+
+    if action.changes_config()
+    {
+        record_config_revision(state, project.id, &claims.sub).await;
+    }
+
+    performed_by_admin
+}
+
+// Le projet est relu : celui des handlers date d'avant la modification.
+pub(super) async fn record_config_revision(state: &AppState, project_id: i32, actor: &str)
+{
+    let recorded = match project_service::get_project_by_id_and_owner(&state.db_pool, project_id, actor, true).await
+    {
+        Ok(Some(project)) => config_revision_service::record_revision(&state.db_pool, &project, actor).await.map(drop),
+        Ok(None) => Ok(()),
+        Err(e) => Err(e),
+    };
+
+    if let Err(e) = recorded
+    {
+        warn!("Could not record a configuration revision of project {}: {}", project_id, e);
+    }
+}
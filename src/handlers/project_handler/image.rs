@@ -0,0 +1,121 @@
+use super::*;
+
+pub async fn update_project_image_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(project_id): Path<i32>,
+    ApiJson(payload): ApiJson<UpdateImagePayload>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let user_login = &claims.sub;
+    info!("User '{}' initiated blue-green image update for project ID: {}", user_login, project_id);
+
+    let project = get_project_for_user(&state, project_id, user_login, claims.is_admin).await?;
+
+    validate_project_source(&project.source, ProjectSourceType::Direct, "Image update")?;
+
+    if payload.allow_repository_change
+    {
+        warn!(
+            "User '{}' is switching project '{}' from '{}' to another repository with '{}'.",
+            user_login, project.name, project.source_url, payload.new_image_url
+        );
+    }
+    validation_service::validate_image_repository_change(&project.source_url, &payload.new_image_url, payload.allow_repository_change)?;
+
+    let deployment = prepare_blue_green_deployment(
+        &state,
+        &project,
+        &payload.new_image_url,
+        None,
+    ).await?;
+
+    if project.deployed_image_digest == deployment.new_image_digest
+    {
+        return Ok(create_no_change_response("The project is already running the latest version of the image."));
+    }
+
+    let env_vars = get_decrypted_env_vars(&project, &state.config().encryption_key)?;
+
+    execute_blue_green_deployment(
+        &state,
+        &project,
+        &deployment,
+        env_vars.as_ref(),
+        &deployment.new_image_tag,
+    ).await?;
+
+    let performed_by_admin = record_project_event(&state, &project, &claims, ProjectEventAction::ImageUpdate, Some(json!({ "image": payload.new_image_url }))).await;
+
+    Ok((
+        StatusCode::OK,
+        Json(json!({
+            "status": "success",
+            "message": "Project image updated successfully without downtime.",
+            "performed_by_admin": performed_by_admin,
+            "image_digest": deployment.new_image_digest,
+        })),
+    ))
+}
+
+pub async fn rebuild_project_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(project_id): Path<i32>,
+    Query(query): Query<RebuildQuery>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let user_login = &claims.sub;
+    info!("User '{}' initiated source rebuild for project ID: {} (no cache: {})", user_login, project_id, query.no_cache);
+
+    let project = get_project_for_user(&state, project_id, user_login, claims.is_admin).await?;
+
+    validate_project_source(&project.source, ProjectSourceType::Github, "Source rebuild")?;
+
+    let scan_threshold = policy_service::get_scan_threshold(&state.db_pool, &state.config(), &project.owner, Some(project.id), None).await?;
+
+    let new_image_tag = build_image_from_github_source(
+        &state,
+        &project.docker_host,
+        &project.name,
+        GithubBuild
+        {
+            source: GithubBuildSource
+            {
+                repo_url: &project.source_url,
+                branch: project.source_branch.as_deref(),
+                root_dir: project.source_root_dir.as_deref(),
+            },
+            cache: if query.no_cache { BuildCache::Refresh } else { BuildCache::Reuse },
+            scan_threshold,
+        },
+        &mut DeployTimings::default(),
+    ).await?;
+
+    let deployment = prepare_blue_green_deployment(
+        &state,
+        &project,
+        &new_image_tag,
+        Some(&project.deployed_image_tag),
+    ).await?;
+
+    if project.deployed_image_digest == deployment.new_image_digest
+    {
+        ops::remove_image_or_defer(&state, &project.docker_host, &new_image_tag, &format!("rebuild of project '{}'", project.name)).await;
+        return Ok(create_no_change_response("The project source is already up to date."));
+    }
+
+    let env_vars = get_decrypted_env_vars(&project, &state.config().encryption_key)?;
+
+    execute_blue_green_deployment(
+        &state,
+        &project,
+        &deployment,
+        env_vars.as_ref(),
+        &project.deployed_image_tag,
+    ).await?;
+
+    let performed_by_admin = record_project_event(&state, &project, &claims, ProjectEventAction::Rebuild, None).await;
+
+    Ok(create_attributed_response("Project rebuilt and updated successfully from the latest source.", performed_by_admin))
+}
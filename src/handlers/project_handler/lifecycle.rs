@@ -0,0 +1,356 @@
+use super::*;
+
+pub async fn purge_project_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(project_id): Path<i32>,
+    Query(query): Query<PurgeProjectQuery>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let user_login = &claims.sub;
+
+    if query.force.unwrap_or(false)
+    {
+        if !claims.is_admin
+        {
+            return Err(AppError::Unauthorized("Only admins can purge a project immediately.".to_string()));
+        }
+
+        info!("Admin '{}' initiated immediate purge for project ID: {}", user_login, project_id);
+
+        // Un projet déjà supprimé peut aussi être purgé sans attendre le nettoyeur.
+        let project = match project_service::get_project_by_id_and_owner(&state.db_pool, project_id, user_login, true).await?
+        {
+            Some(project) => project,
+            None => get_deleted_project_for_owner(&state, project_id, user_login, true).await?,
+        };
+
+        purge_project(&state, &project, user_login, true).await?;
+
+        // Les événements n'ont pas de clé étrangère : celui-ci survit au projet supprimé.
+        let performed_by_admin = record_project_event(&state, &project, &claims, ProjectEventAction::Purge, None).await;
+
+        return Ok(create_attributed_response("Project purged successfully.", performed_by_admin));
+    }
+
+    info!("User '{}' deleted project ID: {}", user_login, project_id);
+
+    let project = get_project_for_owner(&state, project_id, user_login, claims.is_admin).await?;
+    let docker = state.docker_for(&project.docker_host)?;
+
+    for container_name in project.container_names()
+    {
+        if let Err(e) = docker_service::stop_container_by_name(docker, &container_name).await
+        {
+            warn!("Could not stop container '{}' of deleted project '{}': {}", container_name, project.name, e);
+        }
+    }
+
+    if project.maintenance_page
+    {
+        hide_maintenance_page(&state, docker, &project).await?;
+    }
+
+    project_service::set_project_deleted(&state.db_pool, project.id, true).await?;
+
+    let purge_after = time::OffsetDateTime::now_utc() + time::Duration::hours(state.config().project_deletion_grace_hours);
+    let details = json!({ "purge_after": purge_after.format(&time::format_description::well_known::Rfc3339).ok() });
+    let performed_by_admin = record_project_event(&state, &project, &claims, ProjectEventAction::Delete, Some(details.clone())).await;
+
+    Ok((
+        StatusCode::OK,
+        Json(json!({
+            "status": "success",
+            "message": "Project deleted. It can be restored until it is purged.",
+            "performed_by_admin": performed_by_admin,
+            "purge_after": details["purge_after"],
+        })),
+    ))
+}
+
+pub async fn restore_project_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(project_id): Path<i32>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let project = get_deleted_project_for_owner(&state, project_id, &claims.sub, claims.is_admin).await?;
+
+    project_service::set_project_deleted(&state.db_pool, project.id, false).await?;
+
+    // Un projet archivé avant sa suppression reste arrêté, comme avant.
+    if project.archived_at.is_none()
+    {
+        let docker = state.docker_for(&project.docker_host)?;
+        validate_container_exists_for_action(docker, &project, ProjectAction::Start).await?;
+
+        for container_name in project.container_names()
+        {
+            ProjectAction::Start.execute(docker.clone(), container_name).await?;
+        }
+
+        project_service::set_project_intended_running(&state.db_pool, project.id, true).await?;
+    }
+
+    info!("User '{}' restored project '{}'.", claims.sub, project.name);
+
+    let performed_by_admin = record_project_event(&state, &project, &claims, ProjectEventAction::Restore, None).await;
+
+    Ok(create_attributed_response("Project restored successfully.", performed_by_admin))
+}
+
+pub async fn purge_project(
+    state: &AppState,
+    project: &crate::model::project::Project,
+    user_login: &str,
+    is_admin: bool,
+) -> Result<(), AppError>
+{
+    let docker = state.docker_for(&project.docker_host)?;
+
+    deprovision_linked_database(state, project.id, is_admin).await?;
+
+    for container_name in project.container_names()
+    {
+        docker_service::remove_container(docker, &container_name).await?;
+    }
+
+    if project.maintenance_page
+    {
+        docker_service::remove_container(docker, &maintenance_container_name(state, project)).await?;
+    }
+
+    remove_persistent_volume(docker, project).await?;
+
+    ops::remove_image_or_defer(state, &project.docker_host, &project.deployed_image_tag, &format!("purge of project '{}'", project.name)).await;
+
+    project_service::delete_project_by_id(&state.db_pool, project.id).await?;
+
+    info!("Successfully purged project '{}' for user '{}'.", project.name, user_login);
+    Ok(())
+}
+
+pub async fn start_project_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(project_id): Path<i32>,
+) -> Result<impl IntoResponse, AppError>
+{
+    project_control_handler(state, claims, project_id, ProjectAction::Start, false).await
+}
+
+pub async fn stop_project_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(project_id): Path<i32>,
+    Query(query): Query<StopProjectQuery>,
+) -> Result<impl IntoResponse, AppError>
+{
+    project_control_handler(state, claims, project_id, ProjectAction::Stop, query.maintenance_page.unwrap_or(false)).await
+}
+
+pub async fn restart_project_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(project_id): Path<i32>,
+) -> Result<impl IntoResponse, AppError>
+{
+    project_control_handler(state, claims, project_id, ProjectAction::Restart, false).await
+}
+
+pub async fn archive_project_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(project_id): Path<i32>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let project = get_project_for_owner(&state, project_id, &claims.sub, claims.is_admin).await?;
+
+    if project.archived_at.is_some()
+    {
+        return Ok(create_no_change_response("The project is already archived."));
+    }
+
+    let docker = state.docker_for(&project.docker_host)?;
+
+    for container_name in project.container_names()
+    {
+        // Un conteneur déjà arrêté ou perdu n'empêche pas l'archivage.
+        if let Err(e) = docker_service::stop_container_by_name(docker, &container_name).await
+        {
+            warn!("Could not stop container '{}' of archived project '{}': {}", container_name, project.name, e);
+        }
+    }
+
+    if project.maintenance_page
+    {
+        hide_maintenance_page(&state, docker, &project).await?;
+    }
+
+    project_service::set_project_archived(&state.db_pool, project.id, true).await?;
+
+    info!("User '{}' archived project '{}'.", claims.sub, project.name);
+
+    let performed_by_admin = record_project_event(&state, &project, &claims, ProjectEventAction::Archive, None).await;
+
+    Ok(create_attributed_response("Project archived successfully. Its containers have been stopped.", performed_by_admin))
+}
+
+pub async fn unarchive_project_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(project_id): Path<i32>,
+    Query(query): Query<UnarchiveProjectQuery>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let project = get_project_for_owner(&state, project_id, &claims.sub, claims.is_admin).await?;
+
+    if project.archived_at.is_none()
+    {
+        return Ok(create_no_change_response("The project is not archived."));
+    }
+
+    project_service::set_project_archived(&state.db_pool, project.id, false).await?;
+
+    let start = query.start.unwrap_or(false);
+    if start
+    {
+        let docker = state.docker_for(&project.docker_host)?;
+        validate_container_exists_for_action(docker, &project, ProjectAction::Start).await?;
+
+        for container_name in project.container_names()
+        {
+            ProjectAction::Start.execute(docker.clone(), container_name).await?;
+        }
+
+        project_service::set_project_intended_running(&state.db_pool, project.id, true).await?;
+    }
+
+    info!("User '{}' unarchived project '{}' (started: {}).", claims.sub, project.name, start);
+
+    let performed_by_admin = record_project_event(&state, &project, &claims, ProjectEventAction::Unarchive, Some(json!({ "started": start }))).await;
+
+    let message = if start { "Project unarchived and started successfully." } else { "Project unarchived successfully. It stays stopped until started." };
+    Ok(create_attributed_response(message, performed_by_admin))
+}
+
+async fn project_control_handler(
+    state: AppState,
+    claims: Claims,
+    project_id: i32,
+    action: ProjectAction,
+    maintenance_page: bool,
+) -> Result<impl IntoResponse, AppError>
+{
+    let project = get_project_for_user(&state, project_id, &claims.sub, claims.is_admin).await?;
+
+    if project.archived_at.is_some() && !matches!(action, ProjectAction::Stop)
+    {
+        return Err(ProjectErrorCode::ProjectArchived.into());
+    }
+
+    let docker = state.docker_for(&project.docker_host)?;
+
+    validate_container_exists_for_action(docker, &project, action).await?;
+
+    for container_name in project.container_names()
+    {
+        action.execute(docker.clone(), container_name).await?;
+    }
+
+    // Enregistré dès que les conteneurs ont changé d'état, même si la page de maintenance échoue
+    // ensuite : sinon la réconciliation redémarrerait un projet arrêté.
+    let intended_running = !matches!(action, ProjectAction::Stop);
+    project_service::set_project_intended_running(&state.db_pool, project.id, intended_running).await?;
+
+    if maintenance_page
+    {
+        show_maintenance_page(&state, docker, &project).await?;
+    }
+    else if project.maintenance_page
+    {
+        hide_maintenance_page(&state, docker, &project).await?;
+    }
+
+    let details = maintenance_page.then(|| json!({ "maintenance_page": true }));
+    let performed_by_admin = record_project_event(&state, &project, &claims, action.event_action(), details).await;
+
+    Ok(create_attributed_response(action.success_message(), performed_by_admin))
+}
+
+pub(super) fn maintenance_container_name(state: &AppState, project: &crate::model::project::Project) -> String
+{
+    // Project names cannot contain dots, so this never collides with a project container.
+    format!("{}-{}.maintenance", state.config().app_prefix, project.name)
+}
+
+async fn show_maintenance_page(
+    state: &AppState,
+    docker: &bollard::Docker,
+    project: &crate::model::project::Project,
+) -> Result<(), AppError>
+{
+    docker_service::create_maintenance_container(docker, &maintenance_container_name(state, project), &project.name, &project.custom_domains, &state.config()).await?;
+    project_service::set_project_maintenance_page(&state.db_pool, project.id, true).await
+}
+
+async fn hide_maintenance_page(
+    state: &AppState,
+    docker: &bollard::Docker,
+    project: &crate::model::project::Project,
+) -> Result<(), AppError>
+{
+    docker_service::remove_container(docker, &maintenance_container_name(state, project)).await?;
+    project_service::set_project_maintenance_page(&state.db_pool, project.id, false).await
+}
+
+// Une recréation démarre les conteneurs ; en maintenance, le placeholder doit rester seul.
+pub(super) async fn keep_stopped_for_maintenance(
+    docker: &bollard::Docker,
+    project: &crate::model::project::Project,
+    container_names: &[String],
+)
+{
+    if !project.maintenance_page
+    {
+        return;
+    }
+
+    for container_name in container_names
+    {
+        if let Err(e) = docker_service::stop_container_by_name(docker, container_name).await
+        {
+            warn!("Could not stop container '{}' of project '{}' in maintenance: {}", container_name, project.name, e);
+        }
+    }
+}
+
+pub(super) async fn validate_container_exists_for_action(
+    docker: &bollard::Docker,
+    project: &crate::model::project::Project,
+    action: ProjectAction,
+) -> Result<(), AppError>
+{
+    if !matches!(action, ProjectAction::Start | ProjectAction::Restart)
+    {
+        return Ok(());
+    }
+
+    for container_name in project.container_names()
+    {
+        if docker_service::get_container_status(docker, ContainerRef::ByName(&container_name)).await?.is_none()
+        {
+            warn!(
+                "Container '{}' not found for project ID {}. It might be lost.",
+                container_name, project.id
+            );
+            
+            return Err(AppError::NotFound(format!(
+                "Container for project '{}' seems to be lost. Please contact support or try to redeploy.",
+                project.name
+            )));
+        }
+    }
+
+    Ok(())
+}
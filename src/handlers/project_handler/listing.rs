@@ -0,0 +1,388 @@
+use super::*;
+
+pub async fn list_my_deployments_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    list_params: ListParams<DeploymentsList>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let deployments = deployment_service::get_deployments_by_owner(&state.db_pool, &claims.sub).await?;
+
+    Ok((StatusCode::OK, Json(list_params.paginate(&deployments)?)))
+}
+
+pub async fn list_my_deployment_events_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    list_params: ListParams<ProjectAuditList>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let filters = AuditFilters
+    {
+        actor: Some(claims.sub.clone()),
+        target_type: Some(AuditTargetType::Deployment),
+        ..Default::default()
+    };
+    let (entries, total) = audit_service::get_entries_page(
+        &state.db_pool,
+        &filters,
+        list_params.sort_by,
+        list_params.order,
+        i64::from(list_params.per_page),
+        list_params.offset(),
+    ).await?;
+
+    Ok(Json(list_params.page_of(&entries, total as usize)?))
+}
+
+pub async fn get_deployment_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(deployment_id): Path<i32>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let deployment = deployment_service::get_deployment_for_owner(&state.db_pool, deployment_id, &claims.sub, claims.is_admin)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Deployment with ID {} not found or you don't have access.", deployment_id)))?;
+
+    let (pull_progress, clone_progress) = match deployment.status
+    {
+        DeploymentStatus::Building =>
+        (
+            state.pull_progress.lock().ok().and_then(|pulls| pulls.get(&deployment.project_name).cloned()),
+            state.clone_progress.lock().ok().and_then(|clones| clones.get(&deployment.project_name).copied()),
+        ),
+        _ => (None, None),
+    };
+
+    Ok((StatusCode::OK, Json(json!({ "deployment": deployment, "pull_progress": pull_progress, "clone_progress": clone_progress }))))
+}
+
+pub async fn list_owned_projects_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    if_none_match: IfNoneMatch,
+    Query(query): Query<ArchivedProjectsQuery>,
+    list_params: ListParams<ProjectsList>,
+) -> Result<Response, AppError>
+{
+    let user_login = claims.sub;
+    let include_archived = query.include_archived.unwrap_or(false);
+    info!("Fetching owned projects for user '{}'", user_login);
+
+    let (count, last_update) = project_service::get_owned_projects_fingerprint(&state.db_pool, &user_login).await?;
+    let etag = etag::weak_etag(&format!(
+        "{}-{}-{}",
+        count,
+        last_update.map(|t| t.unix_timestamp_nanos()).unwrap_or(0),
+        include_archived
+    ));
+
+    if if_none_match.matches(&etag)
+    {
+        return Ok(etag::not_modified(&etag));
+    }
+    
+    let projects = project_service::get_projects_by_owner(&state.db_pool, &user_login, include_archived).await?;
+    
+    Ok((StatusCode::OK, Json(list_params.paginate(&projects)?)).with_etag(&etag))
+}
+
+pub async fn list_participating_projects_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Query(query): Query<ArchivedProjectsQuery>,
+    list_params: ListParams<ProjectsList>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let user_login = claims.sub;
+    info!("Fetching projects where user '{}' is a participant", user_login);
+    
+    let projects = project_service::get_participating_projects(&state.db_pool, &user_login, query.include_archived.unwrap_or(false)).await?;
+    
+    Ok((StatusCode::OK, Json(list_params.paginate(&projects)?)))
+}
+
+pub async fn list_my_projects_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Query(query): Query<ArchivedProjectsQuery>,
+    list_params: ListParams<ProjectsList>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let user_login = claims.sub;
+    info!("Fetching all projects of user '{}'", user_login);
+
+    let projects = project_service::get_my_projects(&state.db_pool, &user_login, query.include_archived.unwrap_or(false)).await?;
+
+    Ok((StatusCode::OK, Json(list_params.paginate(&projects)?)))
+}
+
+const PROJECT_SEARCH_MIN_LENGTH: usize = 2;
+
+const PROJECT_SEARCH_MAX_LENGTH: usize = 100;
+
+pub async fn search_projects_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Query(query): Query<ProjectSearchQuery>,
+    list_params: ListParams<ProjectsList>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let search = query.q.as_deref().unwrap_or_default().trim();
+    let length = search.chars().count();
+    if !(PROJECT_SEARCH_MIN_LENGTH..=PROJECT_SEARCH_MAX_LENGTH).contains(&length)
+    {
+        return Err(AppError::BadRequest(format!(
+            "The search query must be between {} and {} characters long.",
+            PROJECT_SEARCH_MIN_LENGTH, PROJECT_SEARCH_MAX_LENGTH
+        )));
+    }
+
+    debug!("User '{}' searching projects for '{}'", claims.sub, search);
+
+    let (projects, total) = project_service::search_projects(&state.db_pool, search, &claims.sub, claims.is_admin, &list_params).await?;
+
+    Ok((StatusCode::OK, Json(list_params.page_of(&projects, total as usize)?)))
+}
+
+pub async fn get_project_details_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(project_id): Path<i32>,
+    if_none_match: IfNoneMatch,
+) -> Result<Response, AppError>
+{
+    let user_login = claims.sub;
+    debug!("User '{}' fetching details for project ID: {}", user_login, project_id);
+
+    let project = get_project_for_user(&state, project_id, &user_login, claims.is_admin).await?;
+
+    let (participants, runtime) = tokio::join!(
+        project_service::get_project_participants(&state.db_pool, project.id),
+        get_project_runtime(&state, &project),
+    );
+    let participants = participants?;
+
+    let (runtime, container_missing) = match runtime
+    {
+        Ok(Some(runtime)) => (Some(runtime), false),
+        Ok(None) => (None, !project.maintenance_page),
+        Err(e) =>
+        {
+            warn!("Could not inspect project '{}' for its details: {}", project.name, e);
+            (None, false)
+        }
+    };
+
+    let etag = etag::weak_etag(&format!(
+        "{}-{}-{}-{:?}",
+        project.id,
+        project.updated_at.unix_timestamp_nanos(),
+        state.config().traefik_tls_enabled,
+        runtime.as_ref().map(|r| (&r.status, &r.health, r.timing.started_at, r.restart_count)),
+    ));
+    if if_none_match.matches(&etag)
+    {
+        return Ok(etag::not_modified(&etag));
+    }
+
+    let mut project_data = project;
+    decrypt_project_env_vars(&mut project_data, &state.config().encryption_key)?;
+
+    let shared_keys: HashSet<String> = project_service::get_shared_env_keys(&state.db_pool, project_data.id).await?.into_iter().collect();
+    let env_var_visibility = mask_env_vars(&mut project_data, &shared_keys);
+
+    let database_details = get_database_details(&state, project_data.id).await?;
+    let pending_participants = project_service::get_pending_participants(&state.db_pool, project_data.id).await?;
+    let notes = project_service::get_project_notes(&state.db_pool, project_data.id).await?;
+    let warnings = project_service::get_deploy_warnings(&state.db_pool, project_data.id).await?;
+
+    let public_url = state.config().project_public_url(&project_data.name);
+    let custom_domain_cname_target = state.config().custom_domain_cname_target(&project_data.name);
+    let platform_env_vars = docker_service::platform_env_vars(&state.config(), &project_data.name, &project_data.owner);
+
+    let response = ProjectDetailsResponse
+    {
+        project: project_data,
+        participants,
+        pending_participants,
+        database: database_details,
+        notes,
+        tls_enabled: state.config().traefik_tls_enabled,
+        public_url,
+        platform_env_vars,
+        env_var_visibility,
+        warnings,
+        custom_domain_cname_target,
+        runtime,
+        container_missing,
+    };
+
+    Ok((StatusCode::OK, Json(json!({ "project": response }))).with_etag(&etag))
+}
+
+// La page de maintenance remplace le conteneur : rien n'est inspecté tant qu'elle est affichée.
+async fn get_project_runtime(state: &AppState, project: &crate::model::project::Project) -> Result<Option<ProjectRuntime>, AppError>
+{
+    if project.maintenance_page
+    {
+        return Ok(None);
+    }
+
+    let docker = state.docker_for(&project.docker_host)?;
+    let container = docker_service::resolve_container(docker, &project.container_name, project.container_id.as_deref()).await;
+    let Some(details) = docker_service::inspect_container_details(docker, container).await?
+    else
+    {
+        return Ok(None);
+    };
+
+    let container_state = details.state.unwrap_or_default();
+
+    Ok(Some(ProjectRuntime
+    {
+        status: container_state.status.map(|s| s.to_string()),
+        health: container_state.health.as_ref().and_then(|h| h.status).map(|s| s.to_string()),
+        timing: docker_service::container_timing(&container_state, time::OffsetDateTime::now_utc()),
+        restart_count: details.restart_count,
+    }))
+}
+
+pub async fn get_project_status_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(project_id): Path<i32>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let project = get_project_for_user(&state, project_id, &claims.sub, claims.is_admin).await?;
+
+    if project.maintenance_page
+    {
+        return Ok(Json(json!({ "status": "stopped (maintenance page shown)" })));
+    }
+    
+    let docker = state.docker_for(&project.docker_host)?;
+    let container = docker_service::resolve_container(docker, &project.container_name, project.container_id.as_deref()).await;
+    let status = docker_service::get_container_status(docker, container).await?;
+    let timing = status.as_ref()
+        .map(|s| docker_service::container_timing(s, time::OffsetDateTime::now_utc()))
+        .unwrap_or_default();
+    
+    let mut body = json!(timing);
+    body["status"] = json!(status.and_then(|s| s.status));
+
+    Ok(Json(body))
+}
+
+const BATCH_STATUS_MAX_PROJECTS: usize = 100;
+
+const BATCH_STATUS_CONCURRENCY: usize = 8;
+
+pub async fn get_projects_status_batch_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    ApiJson(payload): ApiJson<BatchStatusPayload>,
+) -> Result<impl IntoResponse, AppError>
+{
+    if payload.project_ids.len() > BATCH_STATUS_MAX_PROJECTS
+    {
+        return Err(AppError::BadRequest(format!(
+            "At most {} projects can be queried at once.",
+            BATCH_STATUS_MAX_PROJECTS
+        )));
+    }
+
+    let projects = project_service::get_projects_by_ids_for_user(&state.db_pool, &payload.project_ids, &claims.sub, claims.is_admin).await?;
+
+    let mut statuses: BTreeMap<i32, serde_json::Value> = payload.project_ids.iter()
+        .map(|id| (*id, serde_json::Value::Null))
+        .collect();
+
+    let observed: Vec<(i32, serde_json::Value)> = futures::stream::iter(projects)
+        .map(|project|
+        {
+            let state = &state;
+            async move { (project.id, batch_project_status(state, &project).await) }
+        })
+        .buffer_unordered(BATCH_STATUS_CONCURRENCY)
+        .collect()
+        .await;
+
+    statuses.extend(observed);
+
+    Ok(Json(statuses))
+}
+
+async fn batch_project_status(state: &AppState, project: &crate::model::project::Project) -> serde_json::Value
+{
+    if project.maintenance_page
+    {
+        return json!({ "status": "stopped (maintenance page shown)", "health": null });
+    }
+
+    let status = match state.docker_for(&project.docker_host)
+    {
+        Ok(docker) =>
+        {
+            let container = docker_service::resolve_container(docker, &project.container_name, project.container_id.as_deref()).await;
+            docker_service::get_container_status(docker, container).await
+        }
+        Err(e) => Err(e),
+    };
+
+    match status
+    {
+        Ok(status) =>
+        {
+            let health = status.as_ref().and_then(|s| s.health.as_ref()).and_then(|h| h.status);
+            json!({ "status": status.and_then(|s| s.status), "health": health })
+        }
+        Err(e) =>
+        {
+            warn!("Could not inspect project '{}' for a batch status: {}", project.name, e);
+            json!({ "status": null, "health": null })
+        }
+    }
+}
+
+pub async fn probe_project_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(project_id): Path<i32>,
+    Query(query): Query<ProbeQuery>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let project = get_project_for_user(&state, project_id, &claims.sub, claims.is_admin).await?;
+
+    let url = match query.domain
+    {
+        Some(domain) if project.custom_domains.contains(&domain) =>
+        {
+            let scheme = if state.config().traefik_tls_enabled { "https" } else { "http" };
+            format!("{}://{}", scheme, domain)
+        }
+        Some(domain) => return Err(AppError::BadRequest(format!("'{}' is not a custom domain of this project.", domain))),
+        None => state.config().project_public_url(&project.name),
+    };
+
+    let result = probe_service::probe(&state.probe_client, &url).await;
+    debug!("User '{}' probed project '{}': {:?}", claims.sub, project.name, result.failure);
+
+    Ok(Json(result))
+}
+
+pub async fn get_project_uptime_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(project_id): Path<i32>,
+    Query(query): Query<UptimeQuery>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let project = get_project_for_user(&state, project_id, &claims.sub, claims.is_admin).await?;
+    let window_days = query.window.unwrap_or_default().days();
+
+    let availability = availability_service::get_project_stats(&state.db_pool, project.id, window_days).await?;
+
+    Ok(Json(json!({ "window_days": window_days, "availability": availability })))
+}
@@ -0,0 +1,652 @@
+use std::
+{
+    collections::{BTreeMap, HashMap, HashSet},
+    fs,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use axum::
+{
+    body::Body,
+    extract::{Multipart, Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Json, Response},
+};
+use futures::StreamExt;
+use base64::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use tokio::time::sleep;
+use tracing::{debug, error, info, warn};
+
+use crate::
+{
+    api::{json::ApiJson, list_params::{ListParams, ListSpec, SortOrder}},
+    error::{AppError, ProjectErrorCode},
+    etag::{self, IfNoneMatch, WithETag},
+    model::
+    {
+        cleanup::CleanupAction,
+        deployment::{CloneProgress, DeployPhase, DeployTimings, Deployment, DeploymentStatus, PullProgress},
+        invitation::ParticipantStatus,
+        config_revision::ProjectConfigSnapshot,
+        audit::{AuditFilters, AuditRecord, AuditTargetType},
+        config_document::{ProjectConfigDocument, CONFIG_DOCUMENT_VERSION},
+        availability::UptimeWindow,
+        project_event::ProjectEventAction,
+        project::{replica_container_names, DockerPlatform, EgressPolicy, EnvVarVisibility, LogsVisibility, MaskedEnvVar, ProjectDetailsResponse, ProjectMetrics, ProjectRateLimit, ProjectRuntime, ProjectSourceType, ReconciliationReport, RouteMiddlewares, RoutingOptions},
+        scan::{ScanThreshold, Severity},
+        team::TeamRole,
+        volume_quota::VolumeQuotaStatus,
+    },
+    ops::{self, Rollback},
+    services::
+    {
+        archive_service::{self, ArchiveFile},
+        audit_service,
+        availability_service,
+        build_dir_service,
+        config_revision_service, crypto_service::{self, EncryptionKeys}, database_service::{self, ProvisionParams}, db_template_service, deployment_service, docker_service::{self, ContainerRef, ContainerSpec}, github_service,
+        jwt::{self, Claims, StreamKind}, policy_service, probe_service, project_event_service, project_service::{self, NewProject}, quota_service::{self, QuotaDimension}, sbom_service::{self, SbomLookup}, scan_service, schedule_service::CronSchedule, signature_service, team_service, validation_service, volume_quota_service,
+    },
+    state::AppState,
+};
+
+mod deploy;
+mod listing;
+mod lifecycle;
+mod monitoring;
+mod settings;
+mod image;
+mod env;
+mod history;
+mod config_io;
+mod source;
+mod persistence;
+mod blue_green;
+mod queue;
+mod background;
+mod reconciliation;
+
+pub use deploy::*;
+pub use listing::*;
+pub use lifecycle::*;
+pub use monitoring::*;
+pub use settings::*;
+pub use image::*;
+pub use env::*;
+pub use history::*;
+pub use config_io::*;
+pub use queue::*;
+pub use background::*;
+pub use reconciliation::*;
+
+use source::*;
+use persistence::*;
+use blue_green::*;
+
+// ============================================================================
+// Request/Response Types
+// ============================================================================
+
+#[derive(Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct DeployPayload
+{
+    project_name: String,
+    image_url: Option<String>,
+    github_repo_url: Option<String>,
+    github_branch: Option<String>,
+    github_root_dir: Option<String>,
+    participants: Vec<String>,
+    env_vars: Option<HashMap<String, String>>,
+    persistent_volume_path: Option<String>,
+    create_database: Option<bool>,
+    database_template: Option<String>,
+    docker_host: Option<String>,
+    scan_fail_on: Option<Severity>,
+    team_id: Option<i32>,
+    description: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateEnvPayload
+{
+    env_vars: HashMap<String, String>,
+    expected_diff_hash: Option<String>,
+    // Propriétaire uniquement : remplace tous les drapeaux, les clés absentes deviennent secrètes.
+    visibility: Option<HashMap<String, EnvVarVisibility>>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PatchEnvPayload
+{
+    #[serde(default)]
+    set: HashMap<String, String>,
+    #[serde(default)]
+    unset: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateImagePayload
+{
+    new_image_url: String,
+    #[serde(default)]
+    allow_repository_change: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ParticipantPayload
+{
+    participant_id: String,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NotesPayload
+{
+    notes: String,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RateLimitPayload
+{
+    average: i32,
+    burst: Option<i32>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct IpAllowlistPayload
+{
+    ranges: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TraefikHealthcheckPayload
+{
+    path: String,
+    interval_seconds: i32,
+    port: Option<i32>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DuplicatePayload
+{
+    new_name: String,
+    copy_volume: Option<bool>,
+    new_owner: Option<String>,
+    copy_participants: Option<bool>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ImportConfigPayload
+{
+    new_name: String,
+    document: serde_json::Value,
+}
+
+#[derive(Deserialize)]
+pub struct ProbeQuery
+{
+    domain: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct UptimeQuery
+{
+    window: Option<UptimeWindow>,
+}
+
+#[derive(Deserialize)]
+pub struct EnvVarsQuery
+{
+    reveal: Option<bool>,
+}
+
+#[derive(Deserialize)]
+pub struct ExportArchiveQuery
+{
+    include_secrets: Option<bool>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ValidateImagePayload
+{
+    project_name: Option<String>,
+    image_url: Option<String>,
+    github_repo_url: Option<String>,
+    github_branch: Option<String>,
+    github_root_dir: Option<String>,
+    scan_fail_on: Option<Severity>,
+}
+
+#[derive(Serialize)]
+struct ImageValidationReport
+{
+    image: String,
+    platform: Option<String>,
+    size_bytes: Option<i64>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CheckImageUpdatePayload
+{
+    candidate_tag: Option<String>,
+}
+
+#[derive(Serialize)]
+struct GateVerdict
+{
+    gate: &'static str,
+    passed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error_code: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    details: Option<serde_json::Value>,
+}
+
+impl GateVerdict
+{
+    // Un refus de garde devient un verdict en échec ; toute autre erreur interrompt la vérification.
+    fn from_result(gate: &'static str, result: Result<(), AppError>) -> Result<Self, AppError>
+    {
+        let error = match result
+        {
+            Ok(()) => return Ok(GateVerdict { gate, passed: true, error_code: None, message: None, details: None }),
+            Err(error @ AppError::ProjectError(
+                ProjectErrorCode::ImageScanFailed(_)
+                | ProjectErrorCode::ImageScanTimedOut(_)
+                | ProjectErrorCode::ImageSignatureInvalid(_)
+                | ProjectErrorCode::UnsupportedImageArchitecture(_, _)
+            )) => error,
+            Err(e) => return Err(e),
+        };
+
+        let details = match &error
+        {
+            AppError::ProjectError(ProjectErrorCode::ImageScanFailed(report)) => Some(json!(report)),
+            AppError::ProjectError(ProjectErrorCode::ImageSignatureInvalid(output)) => Some(json!({ "cosign_output": output })),
+            _ => None,
+        };
+
+        Ok(GateVerdict
+        {
+            gate,
+            passed: false,
+            error_code: Some(error.error_code()),
+            message: Some(error.public_message()),
+            details,
+        })
+    }
+}
+
+#[derive(Serialize)]
+pub struct ImageUpdateCheck
+{
+    candidate: String,
+    image_id: String,
+    current_image_id: String,
+    changed: bool,
+    platform: Option<String>,
+    size_bytes: Option<i64>,
+    gates: Vec<GateVerdict>,
+    passed: bool,
+    ready_to_apply: bool,
+    apply: ApplyImageUpdate,
+}
+
+#[derive(Serialize)]
+struct ApplyImageUpdate
+{
+    method: &'static str,
+    path: String,
+    body: serde_json::Value,
+}
+
+pub struct ProjectsList;
+
+impl ListSpec for ProjectsList
+{
+    const MAX_PER_PAGE: u32 = 100;
+    const SORTABLE: &'static [&'static str] = &["name", "created_at", "updated_at", "last_deployed_at"];
+    const DEFAULT_SORT: &'static str = "created_at";
+    const DEFAULT_ORDER: SortOrder = SortOrder::Desc;
+}
+
+pub struct DeploymentsList;
+
+impl ListSpec for DeploymentsList
+{
+    const MAX_PER_PAGE: u32 = 100;
+    const SORTABLE: &'static [&'static str] = &["id", "project_name", "status", "created_at", "updated_at"];
+    const DEFAULT_SORT: &'static str = "created_at";
+    const DEFAULT_ORDER: SortOrder = SortOrder::Desc;
+}
+
+pub struct ConfigHistoryList;
+
+impl ListSpec for ConfigHistoryList
+{
+    const MAX_PER_PAGE: u32 = 100;
+    const SORTABLE: &'static [&'static str] = &["revision", "actor", "created_at"];
+    const DEFAULT_SORT: &'static str = "revision";
+    const DEFAULT_ORDER: SortOrder = SortOrder::Desc;
+}
+
+pub struct ProjectAuditList;
+
+impl ListSpec for ProjectAuditList
+{
+    const MAX_PER_PAGE: u32 = 100;
+    const SORTABLE: &'static [&'static str] = &["created_at", "actor", "action"];
+    const DEFAULT_SORT: &'static str = "created_at";
+    const DEFAULT_ORDER: SortOrder = SortOrder::Desc;
+}
+
+#[derive(Deserialize)]
+pub struct RebuildQuery
+{
+    #[serde(default)]
+    no_cache: bool,
+}
+
+#[derive(Deserialize)]
+pub struct StopProjectQuery
+{
+    maintenance_page: Option<bool>,
+}
+
+#[derive(Deserialize)]
+pub struct PurgeProjectQuery
+{
+    force: Option<bool>,
+}
+
+#[derive(Deserialize)]
+pub struct ArchivedProjectsQuery
+{
+    include_archived: Option<bool>,
+}
+
+#[derive(Deserialize)]
+pub struct UnarchiveProjectQuery
+{
+    start: Option<bool>,
+}
+
+#[derive(Deserialize)]
+pub struct ProjectSearchQuery
+{
+    q: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StreamTicketPayload
+{
+    project_id: i32,
+    stream: StreamKind,
+}
+
+#[derive(Deserialize)]
+pub struct ProjectLogsQuery
+{
+    replica: Option<i32>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ReplicasPayload
+{
+    replicas: i32,
+    volume_share_safe: Option<bool>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EgressPolicyPayload
+{
+    egress_policy: EgressPolicy,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProjectMetadataPayload
+{
+    description: Option<String>,
+    tags: Option<Vec<String>>,
+    #[serde(default, deserialize_with = "deserialize_present")]
+    auto_start_cron: Option<Option<String>>,
+    #[serde(default, deserialize_with = "deserialize_present")]
+    auto_stop_cron: Option<Option<String>>,
+}
+
+// Distingue un `null` explicite (`Some(None)`) d'un champ absent (`None`).
+fn deserialize_present<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    T::deserialize(deserializer).map(Some)
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RenamePayload
+{
+    new_name: String,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CustomDomainsPayload
+{
+    domains: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BatchStatusPayload
+{
+    project_ids: Vec<i32>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LogsSettingsPayload
+{
+    logs_visibility: Option<LogsVisibility>,
+    logs_redaction: Option<bool>,
+}
+
+// Les valeurs plus courtes ne sont pas masquées : elles correspondraient partout.
+const LOGS_REDACTION_MIN_VALUE_LEN: usize = 4;
+
+// ============================================================================
+// Internal Types
+// ============================================================================
+
+#[derive(Clone, Copy)]
+enum ProjectAction
+{
+    Start,
+    Stop,
+    Restart,
+}
+
+impl ProjectAction
+{
+    fn as_str(self) -> &'static str
+    {
+        match self
+        {
+            Self::Start => "start",
+            Self::Stop => "stop",
+            Self::Restart => "restart",
+        }
+    }
+
+    fn event_action(self) -> ProjectEventAction
+    {
+        match self
+        {
+            Self::Start => ProjectEventAction::Start,
+            Self::Stop => ProjectEventAction::Stop,
+            Self::Restart => ProjectEventAction::Restart,
+        }
+    }
+
+    fn success_message(self) -> &'static str
+    {
+        match self
+        {
+            Self::Start => "Project started successfully.",
+            Self::Stop => "Project stopped successfully.",
+            Self::Restart => "Project restarted successfully.",
+        }
+    }
+
+    async fn execute(
+        self,
+        docker: bollard::Docker,
+        container_name: String,
+    ) -> Result<(), AppError>
+    {
+        match self
+        {
+            Self::Start => docker_service::start_container_by_name(&docker, &container_name).await,
+            Self::Stop => docker_service::stop_container_by_name(&docker, &container_name).await,
+            Self::Restart => docker_service::restart_container_by_name(&docker, &container_name).await,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct EnvVarsDiff
+{
+    added: Vec<String>,
+    removed: Vec<String>,
+    changed: Vec<String>,
+    unchanged_count: usize,
+}
+
+struct DeploymentSource
+{
+    source_type: ProjectSourceType,
+    source_url: String,
+    image_tag: String,
+    image_warnings: Vec<String>,
+}
+
+struct ExportGuard
+{
+    state: AppState,
+    user_login: String,
+}
+
+impl ExportGuard
+{
+    fn acquire(state: &AppState, user_login: &str) -> Result<Self, AppError>
+    {
+        let mut active = state.active_exports.lock().map_err(|_| AppError::InternalServerError)?;
+        if !active.insert(user_login.to_string())
+        {
+            return Err(ProjectErrorCode::ExportAlreadyInProgress.into());
+        }
+
+        Ok(Self { state: state.clone(), user_login: user_login.to_string() })
+    }
+}
+
+impl Drop for ExportGuard
+{
+    fn drop(&mut self)
+    {
+        if let Ok(mut active) = self.state.active_exports.lock()
+        {
+            active.remove(&self.user_login);
+        }
+    }
+}
+
+// Supprime l'image du dry-run en fin de requête, y compris sur timeout ; une image déjà présente est conservée.
+struct DryRunImageGuard
+{
+    docker: bollard::Docker,
+    image: Option<String>,
+}
+
+impl Drop for DryRunImageGuard
+{
+    fn drop(&mut self)
+    {
+        if let Some(image) = self.image.take()
+        {
+            let docker = self.docker.clone();
+            tokio::spawn(async move
+            {
+                let _ = docker_service::remove_image(&docker, &image).await;
+            });
+        }
+    }
+}
+
+struct BlueGreenDeployment
+{
+    old_container_names: Vec<String>,
+    new_container_name: String,
+    replicas: i32,
+    new_image_tag: String,
+    new_image_digest: String,
+}
+
+impl BlueGreenDeployment
+{
+    fn new_container_names(&self) -> Vec<String>
+    {
+        replica_container_names(&self.new_container_name, self.replicas)
+    }
+}
+
+// ============================================================================
+// Response Building
+// ============================================================================
+
+fn create_no_change_response(message: &str) -> (StatusCode, Json<serde_json::Value>)
+{
+    (
+        StatusCode::OK,
+        Json(json!({
+            "status": "no_change",
+            "message": message
+        })),
+    )
+}
+
+fn create_attributed_response(message: &str, performed_by_admin: bool) -> (StatusCode, Json<serde_json::Value>)
+{
+    (
+        StatusCode::OK,
+        Json(json!({
+            "status": "success",
+            "message": message,
+            "performed_by_admin": performed_by_admin
+        })),
+    )
+}
@@ -0,0 +1,180 @@
+use super::*;
+
+pub async fn get_project_logs_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(project_id): Path<i32>,
+    Query(query): Query<ProjectLogsQuery>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let project = get_project_for_user(&state, project_id, &claims.sub, claims.is_admin).await?;
+    let redact = check_logs_access(&state, &project, &claims).await?;
+
+    let replica = query.replica.unwrap_or(0);
+    let container_name = project.container_names()
+        .into_iter()
+        .nth(usize::try_from(replica).unwrap_or(usize::MAX))
+        .ok_or_else(|| AppError::BadRequest(format!(
+            "The replica must be between 0 and {}.",
+            project.replicas - 1
+        )))?;
+    
+    let docker = state.docker_for(&project.docker_host)?;
+    let container = replica_container_ref(docker, &project, &container_name).await;
+    let logs = docker_service::get_container_logs(docker, container, "200").await?;
+    let logs = if redact { redact_env_values(&logs, &project, &state.config().encryption_key)? } else { logs };
+    
+    Ok(Json(json!({ "logs": logs, "replica": replica })))
+}
+
+pub async fn update_logs_settings_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(project_id): Path<i32>,
+    ApiJson(payload): ApiJson<LogsSettingsPayload>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let project = get_project_for_owner(&state, project_id, &claims.sub, claims.is_admin).await?;
+
+    let visibility = payload.logs_visibility.unwrap_or(project.logs_visibility);
+    let redaction = payload.logs_redaction.unwrap_or(project.logs_redaction);
+
+    if visibility == project.logs_visibility && redaction == project.logs_redaction
+    {
+        return Ok(create_no_change_response("The project already has these logs settings."));
+    }
+
+    project_service::update_logs_settings(&state.db_pool, project.id, visibility, redaction).await?;
+
+    info!(
+        "User '{}' set the logs of project '{}' to {:?} (redaction: {}).",
+        claims.sub, project.name, visibility, redaction
+    );
+
+    let details = json!({ "logs_visibility": visibility, "logs_redaction": redaction });
+    let performed_by_admin = record_project_event(&state, &project, &claims, ProjectEventAction::LogsSettingsUpdate, Some(details)).await;
+
+    Ok(create_attributed_response("Logs settings updated successfully.", performed_by_admin))
+}
+
+pub async fn issue_stream_ticket_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    ApiJson(payload): ApiJson<StreamTicketPayload>,
+) -> Result<impl IntoResponse, AppError>
+{
+    match payload.stream
+    {
+        StreamKind::Export => get_project_for_owner(&state, payload.project_id, &claims.sub, claims.is_admin).await?,
+        StreamKind::Logs =>
+        {
+            let project = get_project_for_user(&state, payload.project_id, &claims.sub, claims.is_admin).await?;
+            check_logs_access(&state, &project, &claims).await?;
+            project
+        }
+        StreamKind::Status => get_project_for_user(&state, payload.project_id, &claims.sub, claims.is_admin).await?,
+    };
+
+    let ticket = jwt::generate_stream_ticket(&state.config().jwt_secret, &claims, payload.project_id, payload.stream)?;
+
+    Ok((StatusCode::CREATED, Json(json!({ "ticket": ticket, "expires_in": jwt::STREAM_TICKET_TTL_SECONDS }))))
+}
+
+pub async fn get_project_metrics_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(project_id): Path<i32>,
+) -> Result<Json<ProjectMetrics>, AppError>
+{
+    let project = get_project_for_user(&state, project_id, &claims.sub, claims.is_admin).await?;
+    
+    debug!("Fetching metrics for {} replica(s) of '{}' (Project ID: {})", project.replicas, project.container_name, project.id);
+    
+    let docker = state.docker_for(&project.docker_host)?;
+    let mut metrics = ProjectMetrics
+    {
+        cpu_usage: 0.0,
+        memory_usage: 0.0,
+        memory_limit: 0.0,
+    };
+
+    for container_name in project.container_names()
+    {
+        let container = replica_container_ref(docker, &project, &container_name).await;
+        let replica_metrics = docker_service::get_container_metrics(docker, container).await?;
+        metrics.cpu_usage += replica_metrics.cpu_usage;
+        metrics.memory_usage += replica_metrics.memory_usage;
+        metrics.memory_limit += replica_metrics.memory_limit;
+    }
+    
+    Ok(Json(metrics))
+}
+
+// Seule la première réplique a un ID enregistré ; les autres sont adressées par leur nom.
+async fn replica_container_ref<'a>(
+    docker: &bollard::Docker,
+    project: &'a crate::model::project::Project,
+    container_name: &'a str,
+) -> ContainerRef<'a>
+{
+    if container_name == project.container_name
+    {
+        docker_service::resolve_container(docker, container_name, project.container_id.as_deref()).await
+    }
+    else
+    {
+        ContainerRef::ByName(container_name)
+    }
+}
+
+pub async fn get_volume_quota_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(project_id): Path<i32>,
+) -> Result<Json<VolumeQuotaStatus>, AppError>
+{
+    let project = get_project_for_user(&state, project_id, &claims.sub, claims.is_admin).await?;
+
+    let volume_name = project.volume_name
+        .ok_or_else(|| AppError::NotFound("This project has no persistent volume.".to_string()))?;
+
+    let status = volume_quota_service::get_status(&state.db_pool, &state.config(), project.id, &volume_name).await?;
+
+    Ok(Json(status))
+}
+
+async fn check_logs_access(state: &AppState, project: &crate::model::project::Project, claims: &Claims) -> Result<bool, AppError>
+{
+    if claims.is_admin || team_service::has_owner_rights(&state.db_pool, project, &claims.sub).await?
+    {
+        return Ok(false);
+    }
+
+    if project.logs_visibility == LogsVisibility::OwnerOnly
+    {
+        return Err(ProjectErrorCode::LogsRestricted.into());
+    }
+
+    Ok(project.logs_redaction)
+}
+
+// Les valeurs les plus longues d'abord, pour qu'une valeur qui en contient une autre soit masquée entière.
+fn redact_env_values(logs: &str, project: &crate::model::project::Project, encryption_key: &EncryptionKeys) -> Result<String, AppError>
+{
+    let mut project = project.clone();
+    decrypt_project_env_vars(&mut project, encryption_key)?;
+
+    let env_vars: HashMap<String, String> = project.env_vars
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default();
+
+    let mut values: Vec<String> = env_vars
+        .into_values()
+        .filter(|value| value.len() >= LOGS_REDACTION_MIN_VALUE_LEN)
+        .collect();
+    values.sort();
+    values.dedup();
+    values.sort_by_key(|value| std::cmp::Reverse(value.len()));
+
+    Ok(values.iter().fold(logs.to_string(), |logs, value| logs.replace(value.as_str(), "[REDACTED]")))
+}
@@ -0,0 +1,222 @@
+use super::*;
+
+pub(super) async fn persist_project(
+    state: &AppState,
+    new_project: &NewProject<'_>,
+    payload: &DeployPayload,
+    participants: &[String],
+    rollback: &mut Rollback,
+) -> Result<crate::model::project::Project, AppError>
+{
+    let mut tx = state.db_pool.begin()
+        .await
+        .map_err(|e| AppError::database(&e))?;
+
+    let project = create_project_in_transaction(&mut tx, state, new_project).await?;
+
+    if payload.create_database.unwrap_or(false)
+    {
+        provision_database_in_transaction(&mut tx, state, new_project.owner, project.id, payload.database_template.as_deref(), rollback).await?;
+    }
+
+    add_participants_in_transaction(&mut tx, state, project.id, participants, new_project.owner).await?;
+
+    tx.commit()
+        .await
+        .map_err(|e| AppError::database(&e))?;
+
+    Ok(project)
+}
+
+async fn create_project_in_transaction(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    state: &AppState,
+    new_project: &NewProject<'_>,
+) -> Result<crate::model::project::Project, AppError>
+{
+    match project_service::create_project(tx, new_project, &state.config().encryption_key).await
+    {
+        Ok(project) => Ok(project),
+        Err(db_error) =>
+        {
+            warn!("DB persistence failed for container '{}', rolling back...", new_project.container_name);
+            Err(db_error)
+        }
+    }
+}
+
+async fn provision_database_in_transaction(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    state: &AppState,
+    user_login: &str,
+    project_id: i32,
+    template_name: Option<&str>,
+    rollback: &mut Rollback,
+) -> Result<(), AppError>
+{
+    let template = match template_name
+    {
+        Some(name) => Some(db_template_service::get_template_script(&state.db_pool, name).await?),
+        None => None,
+    };
+    let limits = database_service::resolve_user_limits(&state.db_pool, &state.config(), user_login).await?;
+
+    if let Err(db_error) = database_service::provision_and_link_database_tx(
+        tx,
+        &state.mariadb_pool,
+        ProvisionParams { owner_login: user_login, project_id, template: template.as_ref(), limits: &limits },
+        &state.config().encryption_key,
+        rollback,
+    ).await
+    {
+        warn!("Database provisioning failed during project creation, rolling back transaction...");
+        Err(db_error)
+    }
+    else
+    {
+        Ok(())
+    }
+}
+
+async fn add_participants_in_transaction(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    state: &AppState,
+    project_id: i32,
+    participants: &[String],
+    user_login: &str,
+) -> Result<(), AppError>
+{
+    let status = if state.config().legacy_direct_participants
+    {
+        ParticipantStatus::Accepted
+    }
+    else
+    {
+        ParticipantStatus::Pending
+    };
+
+    if let Err(e) = project_service::add_project_participants(tx, project_id, participants, status, user_login, state.config().max_participants_per_project).await
+    {
+        warn!("Failed to add participants, rolling back transaction...");
+        Err(e)
+    }
+    else
+    {
+        Ok(())
+    }
+}
+
+// La base liée peut appartenir à un autre propriétaire d'un projet d'équipe.
+pub(super) async fn deprovision_linked_database(
+    state: &AppState,
+    project_id: i32,
+    is_admin: bool,
+) -> Result<(), AppError>
+{
+    if let Some(db) = database_service::get_database_by_project_id(&state.db_pool, project_id).await?
+    {
+        info!("Project has a linked database (ID: {}). Deprovisioning it.", db.id);
+        
+        database_service::deprovision_database(
+            &state.db_pool,
+            &state.mariadb_pool,
+            db.id,
+            &db.owner_login,
+            is_admin,
+        ).await?;
+        
+        info!("Linked database deprovisioned successfully.");
+    }
+    
+    Ok(())
+}
+
+pub(super) async fn remove_persistent_volume(
+    docker: &bollard::Docker,
+    project: &crate::model::project::Project,
+) -> Result<(), AppError>
+{
+    if project.persistent_volume_path.is_some()
+    {
+        let volume_name = project.volume_name
+            .as_ref()
+            .ok_or_else(||
+            {
+                error!("Project '{}' has a persistent volume path but no volume name recorded", project.name);
+                AppError::InternalServerError
+            })?;
+
+        docker_service::remove_volume_by_name(docker, volume_name).await?;
+    }
+    
+    Ok(())
+}
+
+pub(super) async fn get_database_details(
+    state: &AppState,
+    project_id: i32,
+) -> Result<Option<crate::model::database::DatabaseDetailsResponse>, AppError>
+{
+    match database_service::get_database_by_project_id(&state.db_pool, project_id).await?
+    {
+        Some(db) =>
+        {
+            Ok(Some(database_service::create_masked_db_details_response(db, &state.config())))
+        }
+        None => Ok(None),
+    }
+}
+
+pub(super) async fn get_project_for_owner(
+    state: &AppState,
+    project_id: i32,
+    user_login: &str,
+    is_admin: bool,
+) -> Result<crate::model::project::Project, AppError>
+{
+    project_service::get_project_by_id_and_owner(&state.db_pool, project_id, user_login, is_admin)
+        .await?
+        .ok_or_else(||
+        {
+            AppError::NotFound(format!(
+                "Project with ID {} not found or you don't have access.",
+                project_id
+            ))
+        })
+}
+
+pub(super) async fn get_deleted_project_for_owner(
+    state: &AppState,
+    project_id: i32,
+    user_login: &str,
+    is_admin: bool,
+) -> Result<crate::model::project::Project, AppError>
+{
+    project_service::get_deleted_project_by_id_and_owner(&state.db_pool, project_id, user_login, is_admin)
+        .await?
+        .ok_or_else(||
+        {
+            AppError::NotFound(format!(
+                "No deleted project with ID {} awaiting purge, or you don't have access.",
+                project_id
+            ))
+        })
+}
+
+pub(super) async fn get_project_for_user(
+    state: &AppState,
+    project_id: i32,
+    user_login: &str,
+    is_admin: bool,
+) -> Result<crate::model::project::Project, AppError>
+{
+    project_service::get_project_by_id_for_user(&state.db_pool, project_id, user_login, is_admin)
+        .await?
+        .ok_or_else(||
+        {
+            AppError::NotFound(format!(
+                "Project with ID {} not found or you don't have access.",
+                project_id
+            ))
+        })
+}
@@ -0,0 +1,367 @@
+use super::*;
+
+const DEPLOY_QUEUE_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+const DEPLOY_HEARTBEAT_INTERVAL: Duration = Duration::from_secs(15);
+
+// Un worker vivant rafraîchit sa ligne au moins trois fois dans ce délai.
+const DEPLOY_STALE_AFTER: Duration = Duration::from_secs(60);
+
+const DEPLOY_RECOVERY_INTERVAL: Duration = Duration::from_secs(60);
+
+pub async fn run_deploy_queue_worker(state: AppState)
+{
+    info!("Deploy queue worker started.");
+    let mut last_recovery = tokio::time::Instant::now();
+
+    loop
+    {
+        if last_recovery.elapsed() >= DEPLOY_RECOVERY_INTERVAL
+        {
+            recover_interrupted_deployments(&state).await;
+            last_recovery = tokio::time::Instant::now();
+        }
+
+        match deployment_service::claim_next_deployment(&state.db_pool).await
+        {
+            Ok(Some(deployment)) => process_queued_deployment(&state, deployment).await,
+            Ok(None) => sleep(DEPLOY_QUEUE_POLL_INTERVAL).await,
+            Err(e) =>
+            {
+                error!("Deploy queue worker failed to claim a deployment: {}", e);
+                sleep(DEPLOY_QUEUE_POLL_INTERVAL).await;
+            }
+        }
+    }
+}
+
+// Au démarrage puis depuis le worker de la file : couvre aussi une instance morte.
+pub async fn recover_interrupted_deployments(state: &AppState)
+{
+    let interrupted = match deployment_service::get_interrupted_deployments(&state.db_pool, DEPLOY_STALE_AFTER.as_secs()).await
+    {
+        Ok(deployments) => deployments,
+        Err(e) =>
+        {
+            error!("Could not look up interrupted deployments: {}", e);
+            return;
+        }
+    };
+
+    for deployment in interrupted
+    {
+        recover_interrupted_deployment(state, &deployment).await;
+    }
+}
+
+async fn process_queued_deployment(state: &AppState, deployment: Deployment)
+{
+    info!(
+        "Processing deployment {} of project '{}' for user '{}'.",
+        deployment.id, deployment.project_name, deployment.owner
+    );
+
+    let heartbeat = tokio::spawn(send_deployment_heartbeats(state.db_pool.clone(), deployment.id));
+    let outcome = execute_queued_deployment(state, &deployment).await;
+    heartbeat.abort();
+
+    match outcome
+    {
+        Ok(project) =>
+        {
+            info!("Project '{}' by user '{}' created successfully.", project.name, deployment.owner);
+
+            audit_service::record(&state.db_pool, AuditRecord
+            {
+                actor: &deployment.owner,
+                action: "deploy",
+                target_type: AuditTargetType::Project,
+                target_id: project.id,
+                target_name: Some(&project.name),
+                performed_by_admin: false,
+                details: Some(json!({ "deployment_id": deployment.id, "source": project.source, "image": project.deployed_image_tag })),
+            }).await;
+
+            if let Err(e) = deployment_service::mark_deployment_succeeded(&state.db_pool, deployment.id, project.id).await
+            {
+                error!("Project '{}' was created but deployment {} could not be marked as succeeded: {}", project.name, deployment.id, e);
+            }
+        }
+        Err(e) =>
+        {
+            warn!("Deployment {} of project '{}' failed: {}", deployment.id, deployment.project_name, e);
+
+            let message = match &e
+            {
+                AppError::ProjectError(ProjectErrorCode::ImageScanFailed(report)) => format!("{}\n{}", e.public_message(), report.summary()),
+                AppError::ProjectError(ProjectErrorCode::ImageSignatureInvalid(output)) => format!("{}\n{}", e.public_message(), output),
+                AppError::ProjectError(ProjectErrorCode::ImagePullFailed(layers)) => format!("{}\n{}", e.public_message(), layers.join("\n")),
+                _ => e.public_message(),
+            };
+
+            if let Err(mark_error) = deployment_service::mark_deployment_failed(&state.db_pool, deployment.id, e.error_code(), &message).await
+            {
+                error!("Could not mark deployment {} as failed: {}", deployment.id, mark_error);
+            }
+        }
+    }
+}
+
+async fn send_deployment_heartbeats(pool: sqlx::PgPool, deployment_id: i32)
+{
+    let mut ticker = tokio::time::interval(DEPLOY_HEARTBEAT_INTERVAL);
+    loop
+    {
+        ticker.tick().await;
+        if let Err(e) = deployment_service::touch_deployment(&pool, deployment_id).await
+        {
+            warn!("Could not refresh the heartbeat of deployment {}: {}", deployment_id, e);
+        }
+    }
+}
+
+async fn execute_queued_deployment(
+    state: &AppState,
+    deployment: &Deployment,
+) -> Result<crate::model::project::Project, AppError>
+{
+    let mut rollback = Rollback::new(state, format!("deployment {} of '{}'", deployment.id, deployment.project_name));
+    let mut timings = DeployTimings::default();
+    let result = run_queued_deployment(state, deployment, &mut rollback, &mut timings).await;
+    let result = rollback.finish(result).await;
+
+    record_deploy_timings(state, deployment.id, &timings).await;
+
+    result
+}
+
+async fn record_deploy_timings(state: &AppState, deployment_id: i32, timings: &DeployTimings)
+{
+    for (phase, duration) in &timings.phases
+    {
+        info!(
+            deployment_id,
+            phase = phase.as_str(),
+            duration_ms = duration.as_millis() as u64,
+            "Deployment {} spent {:.1}s in phase '{}'.",
+            deployment_id, duration.as_secs_f64(), phase.as_str()
+        );
+    }
+
+    if let Err(e) = deployment_service::record_deployment_timings(&state.db_pool, deployment_id, timings).await
+    {
+        warn!("Could not record the timings of deployment {}: {}", deployment_id, e);
+    }
+}
+
+async fn run_queued_deployment(
+    state: &AppState,
+    deployment: &Deployment,
+    rollback: &mut Rollback,
+    timings: &mut DeployTimings,
+) -> Result<crate::model::project::Project, AppError>
+{
+    let payload_json = deployment_service::decrypt_payload(deployment, &state.config().encryption_key)?;
+    let payload: DeployPayload = serde_json::from_str(&payload_json).map_err(|e|
+    {
+        error!("Failed to decode payload of deployment {}: {}", deployment.id, e);
+        AppError::InternalServerError
+    })?;
+
+    let user_login = deployment.owner.as_str();
+    timings.source_type = Some(if payload.image_url.is_some() { ProjectSourceType::Direct } else { ProjectSourceType::Github });
+
+    let scan_threshold = check_deployment_preconditions(state, user_login, &payload).await?;
+
+    let participants = prepare_participants(payload.participants.clone(), user_login, state.config().max_participants_per_project)?;
+
+    let docker_host = match &payload.docker_host
+    {
+        Some(pinned) => pinned.clone(),
+        None => select_docker_host(state).await?,
+    };
+    let docker = state.docker_for(&docker_host)?;
+
+    deployment_service::assign_deployment_host(&state.db_pool, deployment.id, &docker_host).await?;
+    info!("Deployment {} placed on Docker host '{}'.", deployment.id, docker_host);
+
+    let deployment_source = prepare_deployment_source(state, &docker_host, docker, &payload, scan_threshold, timings).await?;
+    rollback.push(CleanupAction::RemoveImage { host: docker_host.clone(), image: deployment_source.image_tag.clone() });
+
+    advance_deployment_best_effort(state, deployment.id, DeploymentStatus::Building, Some(&deployment_source.image_tag), None, None).await;
+
+    let deployed_image_digest = get_image_digest(docker, &deployment_source.image_tag).await.map_err(|e|
+    {
+        error!("Failed to retrieve image digest for '{}': {}", &deployment_source.image_tag, e);
+        AppError::InternalServerError
+    })?;
+
+    let container_name = format!("{}-{}", state.config().app_prefix, payload.project_name);
+
+    advance_deployment_best_effort(state, deployment.id, DeploymentStatus::CreatingContainer, None, Some(&container_name), None).await;
+    
+    let created = timings.measure(DeployPhase::CreateContainer, docker_service::create_project_container(docker, &state.config(), ContainerSpec
+    {
+        container_name: &container_name,
+        project_name: &payload.project_name,
+        owner: user_login,
+        image_identifier: &deployed_image_digest,
+        env_vars: payload.env_vars.as_ref(),
+        persistent_volume_path: payload.persistent_volume_path.as_deref(),
+        volume_name: None,
+        custom_domains: &[],
+        middlewares: &RouteMiddlewares::default(),
+        egress_policy: EgressPolicy::default(),
+        database: None,
+    })).await?;
+    let volume_name = created.volume_name;
+    if let Some(volume) = &volume_name
+    {
+        rollback.push(CleanupAction::RemoveVolume { host: docker_host.clone(), volume: volume.clone() });
+    }
+    rollback.push(CleanupAction::RemoveContainer { host: docker_host.clone(), container: container_name.clone() });
+
+    advance_deployment_best_effort(state, deployment.id, DeploymentStatus::Persisting, None, None, volume_name.as_deref()).await;
+
+    let new_project = NewProject
+    {
+        name: &payload.project_name,
+        owner: user_login,
+        container_name: &container_name,
+        container_id: &created.id,
+        source_type: deployment_source.source_type,
+        source_url: &deployment_source.source_url,
+        source_branch: payload.github_branch.as_deref(),
+        source_root_dir: payload.github_root_dir.as_deref(),
+        deployed_image_tag: &deployment_source.image_tag,
+        deployed_image_digest: &deployed_image_digest,
+        env_vars: payload.env_vars.as_ref(),
+        persistent_volume_path: payload.persistent_volume_path.as_deref(),
+        volume_name: volume_name.as_deref(),
+        docker_host: &docker_host,
+        team_id: payload.team_id,
+        description: payload.description.as_deref(),
+        tags: &payload.tags,
+    };
+    let project = timings.measure(DeployPhase::Persist, persist_project(state, &new_project, &payload, &participants, rollback)).await?;
+
+    record_image_metadata(state, docker, project.id, &deployment_source.image_tag).await;
+    spawn_sbom_generation(state, &docker_host, &deployment_source.image_tag, &deployed_image_digest);
+
+    let warnings: Vec<String> = deployment_source.image_warnings.iter().chain(&created.warnings).cloned().collect();
+    record_deploy_warnings(state, project.id, &warnings).await;
+    if let Err(e) = deployment_service::set_deployment_warnings(&state.db_pool, deployment.id, &warnings).await
+    {
+        warn!("Could not record the warnings of deployment {}: {}", deployment.id, e);
+    }
+    record_config_revision(state, project.id, user_login).await;
+
+    Ok(project)
+}
+
+async fn select_docker_host(state: &AppState) -> Result<String, AppError>
+{
+    let counts = project_service::count_projects_per_docker_host(&state.db_pool).await?;
+
+    let host = state.config().docker_hosts
+        .iter()
+        .min_by_key(|host| counts.get(&host.name).copied().unwrap_or(0))
+        .map(|host| host.name.clone())
+        .ok_or(AppError::InternalServerError)?;
+
+    Ok(host)
+}
+
+async fn advance_deployment_best_effort(
+    state: &AppState,
+    deployment_id: i32,
+    status: DeploymentStatus,
+    image_tag: Option<&str>,
+    container_name: Option<&str>,
+    volume_name: Option<&str>,
+)
+{
+    if let Err(e) = deployment_service::advance_deployment(&state.db_pool, deployment_id, status, image_tag, container_name, volume_name).await
+    {
+        warn!("Could not record progress of deployment {}: {}. Recovery after a restart may be incomplete.", deployment_id, e);
+    }
+}
+
+async fn recover_interrupted_deployment(state: &AppState, deployment: &Deployment)
+{
+    let persisted_project = match &deployment.container_name
+    {
+        Some(container_name) => project_service::get_project_by_container_name(&state.db_pool, container_name).await.ok().flatten(),
+        None => None,
+    };
+
+    if let Some(project) = &persisted_project
+        && project.owner == deployment.owner
+    {
+        info!("Deployment {} had already been persisted as project {}. Marking it as succeeded.", deployment.id, project.id);
+        if let Err(e) = deployment_service::mark_deployment_succeeded(&state.db_pool, deployment.id, project.id).await
+        {
+            error!("Could not mark recovered deployment {} as succeeded: {}", deployment.id, e);
+        }
+        return;
+    }
+
+    warn!(
+        "Rolling back deployment {} of project '{}' interrupted by a restart (state: {:?}).",
+        deployment.id, deployment.project_name, deployment.status
+    );
+
+    let docker_host = deployment.docker_host.clone().unwrap_or_else(|| state.default_docker_host());
+
+    // Steps are recorded in execution order: the rollback undoes them from the last one.
+    let mut rollback = Rollback::new(state, format!("interrupted deployment {} of '{}'", deployment.id, deployment.project_name));
+
+    if let Some(image_tag) = &deployment.image_tag
+    {
+        rollback.push(CleanupAction::RemoveImage { host: docker_host.clone(), image: image_tag.clone() });
+    }
+
+    if let Some(volume_name) = &deployment.volume_name
+    {
+        rollback.push(CleanupAction::RemoveVolume { host: docker_host.clone(), volume: volume_name.clone() });
+    }
+
+    if let Some(container_name) = &deployment.container_name
+        && persisted_project.is_none()
+    {
+        rollback.push(CleanupAction::RemoveContainer { host: docker_host.clone(), container: container_name.clone() });
+    }
+
+    if deployment.status == DeploymentStatus::Persisting
+        && let Ok(payload_json) = deployment_service::decrypt_payload(deployment, &state.config().encryption_key)
+        && let Ok(payload) = serde_json::from_str::<DeployPayload>(&payload_json)
+        && payload.create_database.unwrap_or(false)
+    {
+        match database_service::orphaned_project_database_cleanup(&state.db_pool, &deployment.owner).await
+        {
+            Ok(Some(action)) => rollback.push(action),
+            Ok(None) => {}
+            Err(e) => warn!("Could not check the database of interrupted deployment {}: {}", deployment.id, e),
+        }
+    }
+
+    rollback.run().await;
+
+    let code = ProjectErrorCode::InterruptedByRestart;
+    if let Err(e) = deployment_service::mark_deployment_failed(&state.db_pool, deployment.id, code.as_str(), &code.to_string()).await
+    {
+        error!("Could not mark interrupted deployment {} as failed: {}", deployment.id, e);
+        return;
+    }
+
+    audit_service::record(&state.db_pool, AuditRecord
+    {
+        actor: &deployment.owner,
+        action: "deploy_interrupted",
+        target_type: AuditTargetType::Deployment,
+        target_id: deployment.id,
+        target_name: Some(&deployment.project_name),
+        performed_by_admin: false,
+        details: Some(json!({ "status": deployment.status, "error_code": code.as_str() })),
+    }).await;
+}
@@ -0,0 +1,212 @@
+use super::*;
+
+// Évite d'inonder un hôte Docker qui vient de redémarrer.
+const RECONCILIATION_PACE: Duration = Duration::from_millis(250);
+
+pub async fn reconcile_project_containers(state: AppState)
+{
+    let mut report = ReconciliationReport
+    {
+        started_at: time::OffsetDateTime::now_utc(),
+        finished_at: None,
+        checked: 0,
+        started: Vec::new(),
+        lost: Vec::new(),
+        failed: Vec::new(),
+        network_drift: Vec::new(),
+    };
+
+    let projects = match project_service::get_all_projects(&state.db_pool).await
+    {
+        Ok(projects) => projects,
+        Err(e) =>
+        {
+            error!("Startup reconciliation aborted, could not list projects: {}", e);
+            return;
+        }
+    };
+
+    for project in projects.iter().filter(|project| project.intended_running)
+    {
+        report.checked += 1;
+
+        match reconcile_project_container(&state, project, &mut report.network_drift).await
+        {
+            Ok(ReconciliationOutcome::Running) => {}
+            Ok(ReconciliationOutcome::Started) => report.started.push(project.name.clone()),
+            Ok(ReconciliationOutcome::Lost) => report.lost.push(project.name.clone()),
+            Err(e) =>
+            {
+                warn!("Could not reconcile project '{}': {}", project.name, e);
+                report.failed.push(project.name.clone());
+            }
+        }
+
+        sleep(RECONCILIATION_PACE).await;
+    }
+
+    for project in projects.iter().filter(|project| project.maintenance_page && !project.intended_running)
+    {
+        if let Err(e) = ensure_maintenance_page(&state, project).await
+        {
+            warn!("Could not restore the maintenance page of project '{}': {}", project.name, e);
+        }
+    }
+
+    report.finished_at = Some(time::OffsetDateTime::now_utc());
+    info!(
+        "Startup reconciliation done: {} checked, {} started, {} lost, {} failed, {} with network drift.",
+        report.checked, report.started.len(), report.lost.len(), report.failed.len(), report.network_drift.len()
+    );
+
+    if let Ok(mut last) = state.last_reconciliation.lock()
+    {
+        *last = Some(report);
+    }
+}
+
+async fn ensure_maintenance_page(
+    state: &AppState,
+    project: &crate::model::project::Project,
+) -> Result<(), AppError>
+{
+    let docker = state.docker_for(&project.docker_host)?;
+    let container_name = maintenance_container_name(state, project);
+
+    let running = docker_service::get_container_status(docker, ContainerRef::ByName(&container_name)).await?
+        .and_then(|container_state| container_state.running)
+        .unwrap_or(false);
+
+    if !running
+    {
+        info!("Recreating the maintenance page of project '{}'.", project.name);
+        docker_service::create_maintenance_container(docker, &container_name, &project.name, &project.custom_domains, &state.config()).await?;
+    }
+
+    Ok(())
+}
+
+enum ReconciliationOutcome
+{
+    Running,
+    Started,
+    Lost,
+}
+
+async fn reconcile_project_container(
+    state: &AppState,
+    project: &crate::model::project::Project,
+    network_drift: &mut Vec<String>,
+) -> Result<ReconciliationOutcome, AppError>
+{
+    let docker = state.docker_for(&project.docker_host)?;
+    let expected_network = docker_service::project_network(&state.config(), project.egress_policy)?.to_string();
+
+    let mut stopped_containers = Vec::new();
+
+    for container_name in project.container_names()
+    {
+        let Some(details) = docker_service::inspect_container_details(docker, ContainerRef::ByName(&container_name)).await?
+        else
+        {
+            warn!("Container '{}' of project '{}' is missing.", container_name, project.name);
+            project_service::set_project_lost_container(&state.db_pool, project.id, true).await?;
+            if !project.lost_container
+            {
+                record_reconciliation_event(state, project, ProjectEventAction::ContainerLost, &[container_name]).await;
+            }
+            return Ok(ReconciliationOutcome::Lost);
+        };
+
+        if container_name == project.container_name
+        {
+            repair_container_id(state, project, details.id.as_deref()).await?;
+        }
+
+        // Signalé seulement : rattacher le conteneur au bon réseau demande de le recréer.
+        let networks: Vec<String> = details.network_settings.as_ref()
+            .and_then(|settings| settings.networks.as_ref())
+            .map(|networks| networks.keys().cloned().collect())
+            .unwrap_or_default();
+        if networks.len() != 1 || networks[0] != expected_network
+        {
+            warn!(
+                "Container '{}' of project '{}' is attached to {:?}, but its egress policy {:?} expects only '{}'.",
+                container_name, project.name, networks, project.egress_policy, expected_network
+            );
+            if !network_drift.contains(&project.name)
+            {
+                network_drift.push(project.name.clone());
+            }
+        }
+
+        let container_state = details.state.unwrap_or_default();
+
+        if !container_state.running.unwrap_or(false)
+        {
+            stopped_containers.push(container_name);
+        }
+    }
+
+    if project.lost_container
+    {
+        project_service::set_project_lost_container(&state.db_pool, project.id, false).await?;
+    }
+
+    if stopped_containers.is_empty()
+    {
+        return Ok(ReconciliationOutcome::Running);
+    }
+
+    for container_name in &stopped_containers
+    {
+        info!("Starting container '{}' of project '{}' to match its intended state.", container_name, project.name);
+        docker_service::start_container_by_name(docker, container_name).await?;
+    }
+    record_reconciliation_event(state, project, ProjectEventAction::ReconciledStart, &stopped_containers).await;
+    Ok(ReconciliationOutcome::Started)
+}
+
+async fn record_reconciliation_event(
+    state: &AppState,
+    project: &crate::model::project::Project,
+    action: ProjectEventAction,
+    container_names: &[String],
+)
+{
+    let details = json!({ "containers": container_names });
+    if let Err(e) = project_event_service::record_event(&state.db_pool, project, action, &project.owner, false, Some(details)).await
+    {
+        warn!("Could not record the {} event of project '{}': {}", action.as_str(), project.name, e);
+    }
+}
+
+// Le conteneur qui porte le nom du projet fait foi : un ID stocké différent vient d'une recréation hors plateforme.
+async fn repair_container_id(
+    state: &AppState,
+    project: &crate::model::project::Project,
+    live_id: Option<&str>,
+) -> Result<(), AppError>
+{
+    let Some(live_id) = live_id
+    else
+    {
+        return Ok(());
+    };
+
+    if project.container_id.as_deref() == Some(live_id)
+    {
+        return Ok(());
+    }
+
+    match &project.container_id
+    {
+        Some(stored_id) => warn!(
+            "Stored container {} of project '{}' is stale, '{}' is now {}.",
+            ContainerRef::ById(stored_id), project.name, project.container_name, ContainerRef::ById(live_id)
+        ),
+        None => info!("Recording container {} of project '{}'.", ContainerRef::ById(live_id), project.name),
+    }
+
+    project_service::update_project_container_id(&state.db_pool, project.id, live_id).await
+}
@@ -0,0 +1,514 @@
+use super::*;
+
+pub async fn update_project_metadata_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(project_id): Path<i32>,
+    ApiJson(payload): ApiJson<ProjectMetadataPayload>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let project = get_project_for_owner(&state, project_id, &claims.sub, claims.is_admin).await?;
+
+    let description = match payload.description
+    {
+        Some(description) => validation_service::sanitize_project_description(Some(description))?,
+        None => project.description.clone(),
+    };
+    let tags = match payload.tags
+    {
+        Some(tags) => validation_service::sanitize_project_tags(tags)?,
+        None => project.tags.clone(),
+    };
+
+    let auto_start_cron = resolve_schedule(payload.auto_start_cron, &project.auto_start_cron)?;
+    let auto_stop_cron = resolve_schedule(payload.auto_stop_cron, &project.auto_stop_cron)?;
+
+    let metadata_changed = description != project.description || tags != project.tags;
+    let schedule_changed = auto_start_cron != project.auto_start_cron || auto_stop_cron != project.auto_stop_cron;
+
+    if !metadata_changed && !schedule_changed
+    {
+        return Ok(create_no_change_response("The project already has this description, these tags and this schedule."));
+    }
+
+    let mut performed_by_admin = false;
+
+    if metadata_changed
+    {
+        project_service::update_project_metadata(&state.db_pool, project.id, description.as_deref(), &tags).await?;
+        info!("User '{}' updated the description and tags of project '{}'.", claims.sub, project.name);
+        performed_by_admin = record_project_event(&state, &project, &claims, ProjectEventAction::MetadataUpdate, Some(json!({ "tags": tags }))).await;
+    }
+
+    if schedule_changed
+    {
+        project_service::update_project_schedule(&state.db_pool, project.id, auto_start_cron.as_deref(), auto_stop_cron.as_deref()).await?;
+        info!("User '{}' updated the schedule of project '{}'.", claims.sub, project.name);
+        let details = json!({ "auto_start_cron": auto_start_cron, "auto_stop_cron": auto_stop_cron });
+        performed_by_admin = record_project_event(&state, &project, &claims, ProjectEventAction::ScheduleUpdate, Some(details)).await;
+    }
+
+    Ok(create_attributed_response("Project metadata updated successfully.", performed_by_admin))
+}
+
+fn resolve_schedule(requested: Option<Option<String>>, current: &Option<String>) -> Result<Option<String>, AppError>
+{
+    match requested
+    {
+        None => Ok(current.clone()),
+        Some(None) => Ok(None),
+        Some(Some(expression)) =>
+        {
+            let expression = expression.split_whitespace().collect::<Vec<_>>().join(" ");
+            CronSchedule::parse(&expression)?;
+            Ok(Some(expression))
+        }
+    }
+}
+
+// Les nouveaux conteneurs sont créés avant la bascule : un échec laisse l'ancien nom en place.
+pub async fn rename_project_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(project_id): Path<i32>,
+    ApiJson(payload): ApiJson<RenamePayload>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let project = get_project_for_owner(&state, project_id, &claims.sub, claims.is_admin).await?;
+
+    if project.name == payload.new_name
+    {
+        return Ok(create_no_change_response("The project already has this name."));
+    }
+
+    validation_service::validate_project_name(&payload.new_name)?;
+
+    if project.maintenance_page
+    {
+        return Err(AppError::BadRequest("A project showing its maintenance page cannot be renamed. Start it first.".to_string()));
+    }
+
+    if project_service::check_project_name_exists(&state.db_pool, &payload.new_name).await?
+    {
+        return Err(ProjectErrorCode::ProjectNameTaken.into());
+    }
+
+    apply_rename(&state, &project, &payload.new_name).await?;
+
+    info!("User '{}' renamed project '{}' to '{}'.", claims.sub, project.name, payload.new_name);
+
+    let details = json!({ "old_name": project.name, "new_name": payload.new_name });
+    let performed_by_admin = record_project_event(&state, &project, &claims, ProjectEventAction::Rename, Some(details)).await;
+
+    Ok((
+        StatusCode::OK,
+        Json(json!({
+            "status": "success",
+            "message": "Project renamed successfully. The previous URL no longer serves it.",
+            "performed_by_admin": performed_by_admin,
+            "public_url": state.config().project_public_url(&payload.new_name),
+        })),
+    ))
+}
+
+pub async fn update_custom_domains_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(project_id): Path<i32>,
+    ApiJson(payload): ApiJson<CustomDomainsPayload>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let domains = validation_service::validate_custom_domains(payload.domains, &state.config())?;
+
+    let project = get_project_for_owner(&state, project_id, &claims.sub, claims.is_admin).await?;
+
+    if project.custom_domains == domains
+    {
+        return Ok(create_no_change_response("The project already has these custom domains."));
+    }
+
+    if let Some(domain) = project_service::find_taken_custom_domain(&state.db_pool, project.id, &domains).await?
+    {
+        return Err(ProjectErrorCode::CustomDomainTaken(domain).into());
+    }
+
+    apply_custom_domains(&state, &project, &domains).await?;
+
+    info!("User '{}' set the custom domains of project '{}' to {:?}.", claims.sub, project.name, domains);
+
+    let details = json!({ "from": project.custom_domains, "to": domains });
+    let performed_by_admin = record_project_event(&state, &project, &claims, ProjectEventAction::DomainsUpdate, Some(details)).await;
+
+    Ok((
+        StatusCode::OK,
+        Json(json!({
+            "status": "success",
+            "message": "Custom domains updated successfully. The project has been restarted.",
+            "performed_by_admin": performed_by_admin,
+            "custom_domains": domains,
+            "cname_target": state.config().custom_domain_cname_target(&project.name),
+        })),
+    ))
+}
+
+pub async fn add_participant_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(project_id): Path<i32>,
+    ApiJson(payload): ApiJson<ParticipantPayload>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let user_login = &claims.sub;
+    info!(
+        "User '{}' trying to add participant '{}' to project {}",
+        user_login, payload.participant_id, project_id
+    );
+
+    let project = get_project_for_owner(&state, project_id, user_login, claims.is_admin).await?;
+
+    if project.owner == payload.participant_id
+    {
+        return Err(ProjectErrorCode::OwnerCannotBeParticipant.into());
+    }
+
+    if !project_service::invite_participant_to_project(&state.db_pool, project_id, &payload.participant_id, user_login, state.config().max_participants_per_project).await?
+    {
+        return Ok(create_no_change_response("This user is already a participant or has a pending invitation.").into_response());
+    }
+
+    info!("Participant '{}' invited to project {}", payload.participant_id, project_id);
+
+    let performed_by_admin = record_project_event(
+        &state, &project, &claims, ProjectEventAction::ParticipantInvite, Some(json!({ "participant": payload.participant_id })),
+    ).await;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(json!({"status": "success", "message": "Invitation sent.", "performed_by_admin": performed_by_admin})),
+    ).into_response())
+}
+
+pub async fn remove_participant_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path((project_id, participant_id)): Path<(i32, String)>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let user_login = &claims.sub;
+    info!(
+        "User '{}' trying to remove participant '{}' from project {}",
+        user_login, participant_id, project_id
+    );
+
+    let project = get_project_for_owner(&state, project_id, user_login, claims.is_admin).await?;
+
+    project_service::remove_participant_from_project(&state.db_pool, project_id, &participant_id).await?;
+
+    info!("Participant '{}' removed successfully from project {}", participant_id, project_id);
+
+    let performed_by_admin = record_project_event(
+        &state, &project, &claims, ProjectEventAction::ParticipantRemove, Some(json!({ "participant": participant_id })),
+    ).await;
+
+    Ok(create_attributed_response("Participant removed.", performed_by_admin))
+}
+
+pub async fn update_notes_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(project_id): Path<i32>,
+    ApiJson(payload): ApiJson<NotesPayload>,
+) -> Result<impl IntoResponse, AppError>
+{
+    validation_service::validate_project_notes(&payload.notes)?;
+
+    let project = get_project_for_user(&state, project_id, &claims.sub, claims.is_admin).await?;
+
+    let notes = Some(payload.notes.as_str()).filter(|notes| !notes.trim().is_empty());
+    project_service::update_project_notes(&state.db_pool, project.id, notes, &claims.sub).await?;
+
+    info!("User '{}' updated the notes of project '{}'.", claims.sub, project.name);
+
+    let performed_by_admin = record_project_event(&state, &project, &claims, ProjectEventAction::NotesUpdate, None).await;
+
+    Ok(create_attributed_response("Notes updated successfully.", performed_by_admin))
+}
+
+pub async fn list_note_revisions_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(project_id): Path<i32>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let project = get_project_for_user(&state, project_id, &claims.sub, claims.is_admin).await?;
+
+    let revisions = project_service::get_project_note_revisions(&state.db_pool, project.id).await?;
+
+    Ok((StatusCode::OK, Json(json!({ "revisions": revisions }))))
+}
+
+pub async fn set_replicas_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(project_id): Path<i32>,
+    ApiJson(payload): ApiJson<ReplicasPayload>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let user_login = &claims.sub;
+
+    let project = get_project_for_owner(&state, project_id, user_login, claims.is_admin).await?;
+
+    let volume_share_safe = validate_replicas(&state, &project, &payload, claims.is_admin)?;
+
+    if project.replicas == payload.replicas && project.volume_share_safe == volume_share_safe
+    {
+        return Ok(create_no_change_response("The project already has this number of replicas."));
+    }
+
+    apply_replicas(&state, &project, payload.replicas, volume_share_safe).await?;
+
+    info!(
+        "User '{}' scaled project '{}' from {} to {} replica(s).",
+        user_login, project.name, project.replicas, payload.replicas
+    );
+
+    let performed_by_admin = record_project_event(&state, &project, &claims, ProjectEventAction::ReplicasUpdate, Some(json!({ "replicas": payload.replicas }))).await;
+
+    Ok(create_attributed_response("Replicas updated successfully.", performed_by_admin))
+}
+
+pub async fn set_egress_policy_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(project_id): Path<i32>,
+    ApiJson(payload): ApiJson<EgressPolicyPayload>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let project = get_project_for_owner(&state, project_id, &claims.sub, true).await?;
+
+    if project.egress_policy == payload.egress_policy
+    {
+        return Ok(create_no_change_response("The project already has this egress policy."));
+    }
+
+    // Refuse avant de recréer quoi que ce soit si le réseau interne n'est pas configuré.
+    docker_service::project_network(&state.config(), payload.egress_policy)?;
+
+    apply_egress_policy(&state, &project, payload.egress_policy).await?;
+
+    info!(
+        "Admin '{}' changed the egress policy of project '{}' from {:?} to {:?}.",
+        claims.sub, project.name, project.egress_policy, payload.egress_policy
+    );
+
+    let performed_by_admin = record_project_event(&state, &project, &claims, ProjectEventAction::EgressPolicyUpdate, Some(json!({ "egress_policy": payload.egress_policy }))).await;
+
+    Ok(create_attributed_response("Egress policy updated successfully.", performed_by_admin))
+}
+
+pub async fn set_rate_limit_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(project_id): Path<i32>,
+    ApiJson(payload): ApiJson<RateLimitPayload>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let user_login = &claims.sub;
+
+    let rate_limit = validate_rate_limit(&state, &payload, claims.is_admin)?;
+
+    let project = get_project_for_owner(&state, project_id, user_login, claims.is_admin).await?;
+    check_rate_limit_editable(&project, &claims)?;
+
+    if project.rate_limit() == Some(rate_limit)
+    {
+        return Ok(create_no_change_response("The project already has this rate limit."));
+    }
+
+    let mut middlewares = project.route_middlewares();
+    middlewares.rate_limit = Some(rate_limit);
+    apply_route_middlewares(&state, &project, middlewares).await?;
+    project_service::set_rate_limit_set_by_admin(&state.db_pool, project.id, claims.is_admin && project.owner != *user_login).await?;
+
+    info!(
+        "User '{}' set rate limit of project '{}' to {} req/s (burst {}).",
+        user_login, project.name, rate_limit.average, rate_limit.burst
+    );
+
+    let performed_by_admin = record_project_event(&state, &project, &claims, ProjectEventAction::RateLimitUpdate, Some(json!({ "rate_limit": rate_limit }))).await;
+
+    Ok(create_attributed_response("Rate limit updated successfully. The project has been restarted.", performed_by_admin))
+}
+
+pub async fn clear_rate_limit_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(project_id): Path<i32>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let user_login = &claims.sub;
+
+    let project = get_project_for_owner(&state, project_id, user_login, claims.is_admin).await?;
+    check_rate_limit_editable(&project, &claims)?;
+
+    if project.rate_limit().is_none()
+    {
+        return Ok(create_no_change_response("The project has no rate limit."));
+    }
+
+    let mut middlewares = project.route_middlewares();
+    middlewares.rate_limit = None;
+    apply_route_middlewares(&state, &project, middlewares).await?;
+    project_service::set_rate_limit_set_by_admin(&state.db_pool, project.id, false).await?;
+
+    info!("User '{}' removed the rate limit of project '{}'.", user_login, project.name);
+
+    let performed_by_admin = record_project_event(&state, &project, &claims, ProjectEventAction::RateLimitUpdate, Some(json!({ "rate_limit": null }))).await;
+
+    Ok(create_attributed_response("Rate limit removed successfully. The project has been restarted.", performed_by_admin))
+}
+
+pub async fn set_ip_allowlist_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(project_id): Path<i32>,
+    ApiJson(payload): ApiJson<IpAllowlistPayload>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let user_login = &claims.sub;
+
+    let ranges = validation_service::validate_ip_allowlist(&payload.ranges)?;
+
+    let project = get_project_for_owner(&state, project_id, user_login, claims.is_admin).await?;
+
+    if project.ip_allowlist.as_ref() == Some(&ranges)
+    {
+        return Ok(create_no_change_response("The project already has this IP allowlist."));
+    }
+
+    let mut middlewares = project.route_middlewares();
+    middlewares.ip_allowlist = Some(ranges);
+    apply_route_middlewares(&state, &project, middlewares).await?;
+
+    info!("User '{}' updated the IP allowlist of project '{}'.", user_login, project.name);
+
+    let performed_by_admin = record_project_event(&state, &project, &claims, ProjectEventAction::IpAllowlistUpdate, None).await;
+
+    Ok(create_attributed_response("IP allowlist updated successfully. The project has been restarted.", performed_by_admin))
+}
+
+pub async fn clear_ip_allowlist_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(project_id): Path<i32>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let user_login = &claims.sub;
+
+    let project = get_project_for_owner(&state, project_id, user_login, claims.is_admin).await?;
+
+    if project.ip_allowlist.is_none()
+    {
+        return Ok(create_no_change_response("The project has no IP allowlist."));
+    }
+
+    let mut middlewares = project.route_middlewares();
+    middlewares.ip_allowlist = None;
+    apply_route_middlewares(&state, &project, middlewares).await?;
+
+    info!("User '{}' removed the IP allowlist of project '{}'.", user_login, project.name);
+
+    let performed_by_admin = record_project_event(&state, &project, &claims, ProjectEventAction::IpAllowlistUpdate, Some(json!({ "ip_allowlist": null }))).await;
+
+    Ok(create_attributed_response("IP allowlist removed successfully. The project has been restarted.", performed_by_admin))
+}
+
+pub async fn set_traefik_healthcheck_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(project_id): Path<i32>,
+    ApiJson(payload): ApiJson<TraefikHealthcheckPayload>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let user_login = &claims.sub;
+
+    let healthcheck = validation_service::validate_traefik_healthcheck(&payload.path, payload.interval_seconds, payload.port)?;
+
+    let project = get_project_for_owner(&state, project_id, user_login, claims.is_admin).await?;
+
+    if project.healthcheck() == Some(healthcheck.clone())
+    {
+        return Ok(create_no_change_response("The project already has this healthcheck."));
+    }
+
+    let mut middlewares = project.route_middlewares();
+    middlewares.healthcheck = Some(healthcheck);
+    apply_route_middlewares(&state, &project, middlewares).await?;
+
+    info!(
+        "User '{}' set the Traefik healthcheck of project '{}' to '{}' every {}s.",
+        user_login, project.name, payload.path, payload.interval_seconds
+    );
+
+    let performed_by_admin = record_project_event(&state, &project, &claims, ProjectEventAction::HealthcheckUpdate, None).await;
+
+    Ok(create_attributed_response("Healthcheck updated successfully. The project has been restarted.", performed_by_admin))
+}
+
+pub async fn set_routing_options_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(project_id): Path<i32>,
+    ApiJson(payload): ApiJson<RoutingOptions>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let user_login = &claims.sub;
+
+    let routing = validation_service::validate_routing_options(payload)?;
+
+    let project = get_project_for_owner(&state, project_id, user_login, claims.is_admin).await?;
+
+    if project.routing_options.0 == routing
+    {
+        return Ok(create_no_change_response("The project already has these routing options."));
+    }
+
+    let mut middlewares = project.route_middlewares();
+    middlewares.routing = routing;
+    apply_route_middlewares(&state, &project, middlewares).await?;
+
+    info!(
+        "User '{}' updated the routing options of project '{}'.",
+        user_login, project.name
+    );
+
+    let performed_by_admin = record_project_event(&state, &project, &claims, ProjectEventAction::RoutingUpdate, None).await;
+
+    Ok(create_attributed_response("Routing options updated successfully. The project has been restarted.", performed_by_admin))
+}
+
+pub async fn clear_traefik_healthcheck_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(project_id): Path<i32>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let user_login = &claims.sub;
+
+    let project = get_project_for_owner(&state, project_id, user_login, claims.is_admin).await?;
+
+    if project.healthcheck().is_none()
+    {
+        return Ok(create_no_change_response("The project has no healthcheck."));
+    }
+
+    let mut middlewares = project.route_middlewares();
+    middlewares.healthcheck = None;
+    apply_route_middlewares(&state, &project, middlewares).await?;
+
+    info!("User '{}' removed the Traefik healthcheck of project '{}'.", user_login, project.name);
+
+    let performed_by_admin = record_project_event(&state, &project, &claims, ProjectEventAction::HealthcheckUpdate, Some(json!({ "healthcheck": null }))).await;
+
+    Ok(create_attributed_response("Healthcheck removed successfully. The project has been restarted.", performed_by_admin))
+}
@@ -0,0 +1,509 @@
+use super::*;
+
+pub(super) struct GithubBuildSource<'a>
+{
+    pub(super) repo_url: &'a str,
+    pub(super) branch: Option<&'a str>,
+    pub(super) root_dir: Option<&'a str>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub(super) enum BuildCache
+{
+    Reuse,
+    Refresh,
+    Bypass,
+}
+
+pub(super) struct GithubBuild<'a>
+{
+    pub(super) source: GithubBuildSource<'a>,
+    pub(super) cache: BuildCache,
+    pub(super) scan_threshold: ScanThreshold,
+}
+
+pub(super) async fn build_image_from_github_source(
+    state: &AppState,
+    docker_host: &str,
+    project_name: &str,
+    build: GithubBuild<'_>,
+    timings: &mut DeployTimings,
+) -> Result<String, AppError>
+{
+    info!(
+        "Building from GitHub source for project '{}'. Repo: '{}', Branch: {:?}, Root Dir: {:?}",
+        project_name, build.source.repo_url, build.source.branch, build.source.root_dir
+    );
+
+    let image_tag = generate_image_tag(project_name);
+
+    build_github_image(state, docker_host, project_name, &image_tag, build, timings).await?;
+
+    Ok(image_tag)
+}
+
+pub(super) async fn build_github_image(
+    state: &AppState,
+    docker_host: &str,
+    project_name: &str,
+    image_tag: &str,
+    build: GithubBuild<'_>,
+    timings: &mut DeployTimings,
+) -> Result<(), AppError>
+{
+    let GithubBuild { source, cache, scan_threshold } = build;
+    let docker = state.docker_for(docker_host)?;
+    let host = state.docker_host_config(docker_host)?;
+    let cache = if state.config().build_cache_enabled { cache } else { BuildCache::Bypass };
+    let cache_tag = docker_service::build_cache_tag(project_name);
+
+    let _build_slot = timings.measure(DeployPhase::BuildSlotWait, state.build_slots.acquire()).await.map_err(|_| AppError::InternalServerError)?;
+
+    let temp_dir = build_dir_service::create_build_dir(&state.config())?;
+
+    let cloned = timings.measure(DeployPhase::Clone, clone_repository(state, project_name, source.repo_url, temp_dir.path(), source.branch)).await;
+    if let Ok(mut clones) = state.clone_progress.lock()
+    {
+        clones.remove(project_name);
+    }
+    cloned?;
+
+    create_dockerfile(&state.config().build_base_image, source.root_dir, temp_dir.path())?;
+
+    let tarball = docker_service::create_tarball(temp_dir.path())?;
+    
+    let build_started = Instant::now();
+    timings.measure(DeployPhase::Build, docker_service::build_image_from_tar(
+        docker,
+        tarball,
+        image_tag,
+        (cache == BuildCache::Reuse).then_some(cache_tag.as_str()),
+        cache == BuildCache::Refresh,
+    )).await?;
+    info!(
+        "Built image '{}' for project '{}' in {:.1}s (cache: {}).",
+        image_tag, project_name, build_started.elapsed().as_secs_f64(),
+        match cache { BuildCache::Reuse => "reused", BuildCache::Refresh => "refreshed", BuildCache::Bypass => "bypassed" }
+    );
+
+    if let Err(scan_error) = timings.measure(DeployPhase::Scan, scan_service::scan_image(&state.db_pool, &state.config(), &host, image_tag, project_name, scan_threshold)).await
+    {
+        warn!("Image scan failed, rolling back by removing built image '{}'", image_tag);
+        let _ = docker_service::remove_image(docker, image_tag).await;
+        return Err(scan_error);
+    }
+
+    // Seul un build analysé sans erreur sert de cache aux suivants.
+    if cache != BuildCache::Bypass
+        && let Err(e) = docker_service::update_build_cache(docker, image_tag, project_name).await
+    {
+        warn!("Could not update the build cache of project '{}': {}", project_name, e);
+    }
+
+    Ok(())
+}
+
+fn clone_progress_publisher(state: &AppState, project_name: &str) -> impl Fn(&CloneProgress) + Send + 'static
+{
+    let state = state.clone();
+    let project_name = project_name.to_string();
+
+    move |progress: &CloneProgress|
+    {
+        if let Ok(mut clones) = state.clone_progress.lock()
+        {
+            clones.insert(project_name.clone(), *progress);
+        }
+    }
+}
+
+async fn clone_repository(
+    state: &AppState,
+    project_name: &str,
+    repo_url: &str,
+    destination: &std::path::Path,
+    branch: Option<&str>,
+) -> Result<(), AppError>
+{
+    let publish_progress = clone_progress_publisher(state, project_name);
+
+    match github_service::clone_repo(&state.config(), repo_url, destination, None, branch, publish_progress).await
+    {
+        Ok(_) =>
+        {
+            info!("Successfully cloned public repository '{}'", repo_url);
+            Ok(())
+        }
+        Err(AppError::ProjectError(ProjectErrorCode::GithubAccountNotLinked))
+        | Err(AppError::ProjectError(ProjectErrorCode::InvalidGithubUrl)) =>
+        {
+            warn!(
+                "Public clone failed for '{}'. Assuming private repo and trying authenticated clone.",
+                repo_url
+            );
+            clone_private_repository(state, project_name, repo_url, destination, branch).await
+        }
+        Err(e) => Err(e),
+    }
+}
+
+async fn clone_private_repository(
+    state: &AppState,
+    project_name: &str,
+    repo_url: &str,
+    destination: &std::path::Path,
+    branch: Option<&str>,
+) -> Result<(), AppError>
+{
+    let (github_owner, repo_name) = github_service::extract_repo_owner_and_name(repo_url).await?;
+    
+    let installation_id = github_service::get_installation_id_by_user(
+        &state.http_client,
+        &state.config(),
+        &github_owner,
+    ).await?;
+    
+    let token = github_service::get_installation_token(
+        installation_id,
+        &state.http_client,
+        &state.config(),
+    ).await?;
+    
+    github_service::check_repo_accessibility(
+        &state.http_client,
+        &token,
+        &github_owner,
+        &repo_name,
+    ).await?;
+    
+    let publish_progress = clone_progress_publisher(state, project_name);
+    github_service::clone_repo(&state.config(), repo_url, destination, Some(&token), branch, publish_progress).await?;
+    
+    info!("Successfully cloned private repository '{}' using GitHub App token", repo_url);
+    
+    Ok(())
+}
+
+fn create_dockerfile(
+    base_image: &str,
+    root_dir: Option<&str>,
+    temp_dir: &std::path::Path,
+) -> Result<(), AppError>
+{
+    let dockerfile_content = format!(
+        "FROM {}\nCOPY --chown=appuser:appgroup . /var/www/html/\n",
+        base_image
+    );
+
+    let dockerfile_content = if let Some(dir) = root_dir 
+    {
+        format!(
+            "{}ENV HANGAR_WEBROOT_DIR=/var/www/html/{}\n",
+            dockerfile_content,
+            dir
+        )
+    } 
+    else 
+    {
+        dockerfile_content
+    };
+    
+    fs::write(temp_dir.join("Dockerfile"), dockerfile_content)
+        .map_err(|_| AppError::InternalServerError)?;
+    
+    Ok(())
+}
+
+pub(super) async fn prepare_direct_source(
+    state: &AppState,
+    docker_host: &str,
+    docker: &bollard::Docker,
+    project_name: &str,
+    image_url: &str,
+    scan_threshold: ScanThreshold,
+    timings: &mut DeployTimings,
+) -> Result<String, AppError>
+{
+    info!("Preparing 'direct' source from image '{}'", image_url);
+    
+    validation_service::validate_image_url(image_url)?;
+
+    let host_platform = state.docker_platforms.get(docker_host);
+
+    timings.measure(DeployPhase::Pull, pull_image_with_error_handling(state, docker, project_name, image_url, host_platform)).await?;
+
+    timings.measure(DeployPhase::Verify, check_platform_with_rollback(docker, image_url, host_platform)).await?;
+
+    timings.measure(DeployPhase::Verify, verify_signature_with_rollback(state, docker_host, image_url)).await?;
+
+    timings.measure(DeployPhase::Scan, scan_image_with_rollback(state, docker_host, project_name, image_url, scan_threshold)).await?;
+
+    Ok(image_url.to_string())
+}
+
+pub(super) async fn pull_image_with_error_handling(
+    state: &AppState,
+    docker: &bollard::Docker,
+    project_name: &str,
+    image_url: &str,
+    platform: Option<&DockerPlatform>,
+) -> Result<(), AppError>
+{
+    let publish_progress = |progress: &PullProgress|
+    {
+        if let Ok(mut pulls) = state.pull_progress.lock()
+        {
+            pulls.insert(project_name.to_string(), progress.clone());
+        }
+    };
+
+    let result = docker_service::pull_image(docker, image_url, platform, None, publish_progress).await;
+
+    if let Ok(mut pulls) = state.pull_progress.lock()
+    {
+        pulls.remove(project_name);
+    }
+
+    match result
+    {
+        Ok(_) =>
+        {
+            info!("Successfully pulled public image '{}'", image_url);
+            Ok(())
+        }
+        Err(failure) =>
+        {
+            if image_url.starts_with("ghcr.io/")
+            {
+                if let bollard::errors::Error::DockerResponseServerError { status_code, .. } = &failure.error
+                {
+                    if *status_code == 401 || *status_code == 403
+                    {
+                        warn!("Failed to pull private image from ghcr.io: {}", image_url);
+                        return Err(ProjectErrorCode::GithubPackageNotPublic.into());
+                    }
+                }
+            }
+
+            error!("Failed to pull image '{}': {}", image_url, failure.error);
+            Err(ProjectErrorCode::ImagePullFailed(failure.progress.summary()).into())
+        }
+    }
+}
+
+pub(super) fn reserve_image_update_check(state: &AppState, project_id: i32) -> Result<(), AppError>
+{
+    let interval = Duration::from_secs(state.config().image_update_check_interval_seconds);
+    let now = Instant::now();
+    let mut checks = state.image_update_checks.lock().map_err(|_| AppError::InternalServerError)?;
+
+    if let Some(last) = checks.get(&project_id)
+        && now.duration_since(*last) < interval
+    {
+        let wait = interval - now.duration_since(*last);
+        return Err(AppError::TooManyRequests(format!(
+            "An image update check already ran for this project. Please retry in {} seconds.",
+            wait.as_secs().max(1)
+        )));
+    }
+
+    checks.retain(|_, last| now.duration_since(*last) < interval);
+    checks.insert(project_id, now);
+    Ok(())
+}
+
+// Le pull déplace le tag candidat : on le remet sur l'image d'avant, ou on le supprime s'il n'existait pas.
+pub(super) async fn restore_image_tag(docker: &bollard::Docker, image_url: &str, previous_image_id: Option<&str>)
+{
+    let Some((repository, tag)) = docker_service::image_tag_parts(image_url) else
+    {
+        return;
+    };
+
+    let restored = match previous_image_id
+    {
+        Some(image_id) => docker_service::tag_image(docker, image_id, repository, tag).await,
+        None => docker_service::remove_image(docker, image_url).await,
+    };
+
+    if let Err(e) = restored
+    {
+        warn!("Could not restore tag '{}' after an image update check: {}", image_url, e);
+    }
+}
+
+pub(super) fn check_image_platform(image_platform: Option<&DockerPlatform>, host_platform: Option<&DockerPlatform>) -> Result<(), AppError>
+{
+    match (image_platform, host_platform)
+    {
+        (Some(image_platform), Some(host_platform)) if image_platform != host_platform =>
+            Err(ProjectErrorCode::UnsupportedImageArchitecture(image_platform.to_string(), host_platform.to_string()).into()),
+        _ => Ok(()),
+    }
+}
+
+// Une image d'une autre architecture échouerait plus tard avec `exec format error`.
+pub(super) async fn check_platform_with_rollback(docker: &bollard::Docker, image_url: &str, host_platform: Option<&DockerPlatform>) -> Result<(), AppError>
+{
+    let Some(host_platform) = host_platform else
+    {
+        return Ok(());
+    };
+
+    let Some(image_platform) = docker_service::get_image_platform(docker, image_url).await? else
+    {
+        warn!("Image '{}' does not declare its platform, skipping the architecture check.", image_url);
+        return Ok(());
+    };
+
+    if image_platform != *host_platform
+    {
+        warn!("Image '{}' targets {} but the host runs {}, rolling back by removing it", image_url, image_platform, host_platform);
+        let _ = docker_service::remove_image(docker, image_url).await;
+        return Err(ProjectErrorCode::UnsupportedImageArchitecture(image_platform.to_string(), host_platform.to_string()).into());
+    }
+
+    Ok(())
+}
+
+async fn verify_signature_with_rollback(state: &AppState, docker_host: &str, image_url: &str) -> Result<(), AppError>
+{
+    let docker = state.docker_for(docker_host)?;
+    let host = state.docker_host_config(docker_host)?;
+    if let Err(verification_error) = signature_service::verify_image_signature(&state.db_pool, &state.config(), &host, docker, image_url).await
+    {
+        warn!("Signature verification failed, rolling back by removing pulled image '{}'", image_url);
+        let _ = docker_service::remove_image(docker, image_url).await;
+        return Err(verification_error);
+    }
+
+    Ok(())
+}
+
+async fn scan_image_with_rollback(
+    state: &AppState,
+    docker_host: &str,
+    project_name: &str,
+    image_url: &str,
+    scan_threshold: ScanThreshold,
+) -> Result<(), AppError>
+{
+    let docker = state.docker_for(docker_host)?;
+    let host = state.docker_host_config(docker_host)?;
+    if let Err(scan_error) = scan_service::scan_image(&state.db_pool, &state.config(), &host, image_url, project_name, scan_threshold).await
+    {
+        warn!("Image scan failed, rolling back by removing pulled image '{}'", image_url);
+        let _ = docker_service::remove_image(docker, image_url).await;
+        return Err(scan_error);
+    }
+    
+    Ok(())
+}
+
+pub(super) async fn record_image_metadata(state: &AppState, docker: &bollard::Docker, project_id: i32, image: &str)
+{
+    let platform = match docker_service::get_image_platform(docker, image).await
+    {
+        Ok(platform) => platform.map(|platform| platform.to_string()),
+        Err(_) => return,
+    };
+    let created_at = docker_service::get_image_created_at(docker, image).await.ok().flatten();
+
+    if let Err(e) = project_service::update_project_image_metadata(
+        &state.db_pool,
+        project_id,
+        platform.as_deref(),
+        created_at,
+        state.config().grype_enabled,
+    ).await
+    {
+        warn!("Could not record the image metadata of project {}: {}", project_id, e);
+    }
+}
+
+pub(super) async fn image_config_warnings(docker: &bollard::Docker, image: &str) -> Vec<String>
+{
+    docker_service::get_image_config_warnings(docker, image).await.unwrap_or_else(|e|
+    {
+        warn!("Could not read the configuration of image '{}' for deploy warnings: {}", image, e);
+        Vec::new()
+    })
+}
+
+pub(super) async fn record_deploy_warnings(state: &AppState, project_id: i32, warnings: &[String])
+{
+    if let Err(e) = project_service::set_deploy_warnings(&state.db_pool, project_id, warnings).await
+    {
+        warn!("Could not record the deploy warnings of project {}: {}", project_id, e);
+    }
+}
+
+// En arrière-plan : un Syft lent ou en échec ne doit jamais retarder un déploiement.
+pub(super) fn spawn_sbom_generation(state: &AppState, docker_host: &str, image_ref: &str, image_digest: &str)
+{
+    let Ok(host) = state.docker_host_config(docker_host)
+    else
+    {
+        return;
+    };
+    let state = state.clone();
+    let image_ref = image_ref.to_string();
+    let image_digest = image_digest.to_string();
+    tokio::spawn(async move
+    {
+        sbom_service::generate_and_store(&state.db_pool, &state.config(), &host, &image_ref, &image_digest).await;
+    });
+}
+
+pub(super) async fn get_image_digest(docker: &bollard::Docker, image_tag: &str) -> Result<String, AppError>
+{
+    docker_service::get_image_digest(docker, image_tag)
+        .await?
+        .ok_or_else(|| AppError::InternalServerError)
+}
+
+pub(super) fn generate_image_tag(project_name: &str) -> String
+{
+    format!(
+        "hangar-local/{}:{}",
+        project_name,
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+    )
+}
+
+pub(super) async fn wait_for_container_health(
+    docker: &bollard::Docker,
+    container_name: &str,
+    max_attempts: u32,
+) -> Result<(), AppError>
+{
+    info!("Waiting for new container '{}' to be healthy...", container_name);
+
+    for _ in 0..max_attempts
+    {
+        if is_container_healthy(docker, container_name).await?
+        {
+            info!("Container '{}' is healthy", container_name);
+            return Ok(());
+        }
+        sleep(Duration::from_secs(1)).await;
+    }
+
+    error!("Container '{}' did not become healthy in time", container_name);
+    Err(AppError::InternalServerError)
+}
+
+async fn is_container_healthy(docker: &bollard::Docker, container_name: &str) -> Result<bool, AppError>
+{
+    if let Ok(Some(details)) = docker_service::inspect_container_details(docker, ContainerRef::ByName(container_name)).await
+    {
+        if let Some(container_state) = details.state
+        {
+            return Ok(container_state.running.unwrap_or(false));
+        }
+    }
+    Ok(false)
+}
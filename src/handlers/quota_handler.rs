@@ -0,0 +1,281 @@
+use std::time::Duration;
+
+use axum::
+{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Json},
+};
+use bollard::Docker;
+use serde_json::json;
+use sqlx::PgPool;
+use time::OffsetDateTime;
+use tracing::{error, info, warn};
+
+use crate::
+{
+    api::json::ApiJson,
+    error::AppError,
+    model::{project::Project, scan::{ProjectScanProfilePayload, ScanProfilePayload, Severity}, volume_quota::{VolumeQuotaAction, VolumeQuotaOverridePayload}},
+    services::{docker_service, jwt::Claims, policy_service, project_service, quota_service::{self, UserQuotaOverrides}, validation_service, volume_quota_service},
+    state::AppState,
+};
+
+pub async fn get_my_quotas_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+) -> Result<impl IntoResponse, AppError>
+{
+    let quotas = quota_service::get_all_usage(&state.db_pool, &state.config(), &claims.sub).await?;
+    let scan_threshold = policy_service::get_scan_threshold(&state.db_pool, &state.config(), &claims.sub, None, None).await?;
+
+    Ok((StatusCode::OK, Json(json!({ "quotas": quotas, "scan_threshold": scan_threshold }))))
+}
+
+pub async fn set_user_quotas_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(login): Path<String>,
+    ApiJson(payload): ApiJson<UserQuotaOverrides>,
+) -> Result<impl IntoResponse, AppError>
+{
+    quota_service::validate_overrides(&payload)?;
+    if let Some(profile) = &payload.scan_profile
+    {
+        require_scan_profile(&state.db_pool, profile).await?;
+    }
+
+    quota_service::set_user_overrides(&state.db_pool, &login, &payload, &claims.sub).await?;
+
+    info!(
+        "Admin '{}' set quotas of '{}': projects={:?}, databases={:?}, scan threshold={:?}, scan profile={:?}, database connections={:?}.",
+        claims.sub, login, payload.max_projects, payload.max_databases, payload.scan_fail_on_severity, payload.scan_profile, payload.db_max_user_connections
+    );
+
+    let quotas = quota_service::get_all_usage(&state.db_pool, &state.config(), &login).await?;
+
+    Ok((StatusCode::OK, Json(json!({ "login": login, "overrides": payload, "quotas": quotas }))))
+}
+
+pub async fn list_scan_profiles_handler(
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let profiles = policy_service::get_all_profiles(&state.db_pool).await?;
+
+    Ok((StatusCode::OK, Json(json!({ "profiles": profiles }))))
+}
+
+pub async fn set_scan_profile_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(name): Path<String>,
+    ApiJson(payload): ApiJson<ScanProfilePayload>,
+) -> Result<impl IntoResponse, AppError>
+{
+    validation_service::validate_project_name(&name)
+        .map_err(|_| AppError::BadRequest("A scan profile name uses lowercase letters, digits and dashes, up to 63 characters.".to_string()))?;
+    if payload.fail_on_severity == Severity::Unknown
+    {
+        return Err(AppError::BadRequest("fail_on_severity must be one of negligible, low, medium, high or critical.".to_string()));
+    }
+
+    let profile = policy_service::set_profile(&state.db_pool, &name, payload.fail_on_severity, &claims.sub).await?;
+
+    info!("Admin '{}' set scan profile '{}' to fail on {}.", claims.sub, name, payload.fail_on_severity.as_str());
+
+    Ok((StatusCode::OK, Json(json!({ "profile": profile }))))
+}
+
+pub async fn delete_scan_profile_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(name): Path<String>,
+) -> Result<impl IntoResponse, AppError>
+{
+    if !policy_service::delete_profile(&state.db_pool, &name).await?
+    {
+        return Err(AppError::NotFound(format!("Scan profile '{}' not found.", name)));
+    }
+
+    info!("Admin '{}' deleted scan profile '{}'.", claims.sub, name);
+
+    Ok((StatusCode::OK, Json(json!({"status": "success", "message": "Scan profile deleted."}))))
+}
+
+pub async fn set_project_scan_profile_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(project_id): Path<i32>,
+    ApiJson(payload): ApiJson<ProjectScanProfilePayload>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let project = project_service::get_project_by_id_for_user(&state.db_pool, project_id, &claims.sub, true).await?
+        .ok_or_else(|| AppError::NotFound(format!("Project with ID {} not found.", project_id)))?;
+    if let Some(profile) = &payload.profile
+    {
+        require_scan_profile(&state.db_pool, profile).await?;
+    }
+
+    policy_service::set_project_profile(&state.db_pool, project.id, payload.profile.as_deref()).await?;
+
+    info!("Admin '{}' set the scan profile of project '{}' to {:?}.", claims.sub, project.name, payload.profile);
+
+    let scan_threshold = policy_service::get_scan_threshold(&state.db_pool, &state.config(), &project.owner, Some(project.id), None).await?;
+
+    Ok((StatusCode::OK, Json(json!({ "project_id": project.id, "scan_profile": payload.profile, "scan_threshold": scan_threshold }))))
+}
+
+async fn require_scan_profile(pool: &PgPool, name: &str) -> Result<(), AppError>
+{
+    match policy_service::get_profile(pool, name).await?
+    {
+        Some(_) => Ok(()),
+        None => Err(AppError::BadRequest(format!("Scan profile '{}' does not exist.", name))),
+    }
+}
+
+pub async fn set_project_volume_quota_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(project_id): Path<i32>,
+    ApiJson(payload): ApiJson<VolumeQuotaOverridePayload>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let max_bytes = volume_quota_service::validate_override(payload.max_mb)?;
+
+    let project = project_service::get_project_by_id_for_user(&state.db_pool, project_id, &claims.sub, true).await?
+        .ok_or_else(|| AppError::NotFound(format!("Project with ID {} not found.", project_id)))?;
+    let volume_name = project.volume_name
+        .ok_or_else(|| AppError::BadRequest("This project has no persistent volume.".to_string()))?;
+
+    volume_quota_service::set_override(&state.db_pool, project.id, max_bytes, &claims.sub).await?;
+
+    let status = volume_quota_service::get_status(&state.db_pool, &state.config(), project.id, &volume_name).await?;
+    let action = if max_bytes.is_some() { VolumeQuotaAction::OverrideSet } else { VolumeQuotaAction::OverrideRemoved };
+    record_volume_quota_event(&state.db_pool, project.id, action, status.used_bytes, status.quota_bytes, Some(&claims.sub)).await;
+
+    info!(
+        "Admin '{}' set the volume quota of project '{}' to {} bytes (override: {}).",
+        claims.sub, project.name, status.quota_bytes, status.quota_overridden
+    );
+
+    Ok((StatusCode::OK, Json(json!({ "project_id": project.id, "volume_quota": status }))))
+}
+
+// ============================================================================
+// Volume Quota Enforcement
+// ============================================================================
+
+pub async fn run_volume_quota_enforcement(state: AppState)
+{
+    let mut ticker = tokio::time::interval(Duration::from_secs(state.config().volume_quota_check_interval_seconds));
+    loop
+    {
+        ticker.tick().await;
+
+        let projects = match project_service::get_all_projects(&state.db_pool).await
+        {
+            Ok(projects) => projects,
+            Err(e) =>
+            {
+                error!("Skipping the volume quota check, projects could not be listed: {}", e);
+                continue;
+            }
+        };
+
+        for (host, docker) in &state.docker_hosts
+        {
+            let sizes = match docker_service::get_volume_sizes(docker).await
+            {
+                Ok(sizes) => sizes,
+                Err(e) =>
+                {
+                    error!("Skipping the volume quota check on host '{}': {}", host, e);
+                    continue;
+                }
+            };
+
+            for project in projects.iter().filter(|project| &project.docker_host == host)
+            {
+                let Some(used_bytes) = project.volume_name.as_ref().and_then(|name| sizes.get(name)).copied()
+                else
+                {
+                    continue;
+                };
+
+                if let Err(e) = check_volume_quota(&state, docker, project, used_bytes).await
+                {
+                    error!("Volume quota check failed for project '{}': {}", project.name, e);
+                }
+            }
+        }
+    }
+}
+
+async fn check_volume_quota(state: &AppState, docker: &Docker, project: &Project, used_bytes: i64) -> Result<(), AppError>
+{
+    let pool = &state.db_pool;
+    let quota_bytes = volume_quota_service::get_override(pool, project.id).await?
+        .unwrap_or_else(|| volume_quota_service::default_quota_bytes(&state.config()));
+    let previous = volume_quota_service::get_usage(pool, project.id).await?;
+    let over_quota_since = previous.as_ref().and_then(|usage| usage.over_quota_since);
+
+    if used_bytes <= quota_bytes
+    {
+        if over_quota_since.is_some()
+        {
+            info!("Project '{}' is back under its volume quota ({} / {} bytes).", project.name, used_bytes, quota_bytes);
+            record_volume_quota_event(pool, project.id, VolumeQuotaAction::Cleared, Some(used_bytes), quota_bytes, None).await;
+        }
+        return volume_quota_service::save_usage(pool, project.id, used_bytes, None, false).await;
+    }
+
+    let now = OffsetDateTime::now_utc();
+    let since = match over_quota_since
+    {
+        Some(since) => since,
+        None =>
+        {
+            warn!("Project '{}' exceeds its volume quota ({} / {} bytes).", project.name, used_bytes, quota_bytes);
+            record_volume_quota_event(pool, project.id, VolumeQuotaAction::Exceeded, Some(used_bytes), quota_bytes, None).await;
+            now
+        }
+    };
+
+    let mut stopped_by_quota = previous.as_ref().is_some_and(|usage| usage.stopped_by_quota);
+
+    // Une fois le délai de grâce écoulé, un projet redémarré par son propriétaire sans avoir libéré d'espace est arrêté à nouveau.
+    if project.intended_running
+        && volume_quota_service::enforcement_deadline(&state.config(), since).is_some_and(|deadline| now >= deadline)
+    {
+        warn!("Stopping project '{}': its volume has been over quota since {}.", project.name, since);
+        for container_name in project.container_names()
+        {
+            if let Err(e) = docker_service::stop_container_by_name(docker, &container_name).await
+            {
+                warn!("Could not stop container '{}' of project '{}' over its volume quota: {}", container_name, project.name, e);
+            }
+        }
+        project_service::set_project_intended_running(pool, project.id, false).await?;
+        record_volume_quota_event(pool, project.id, VolumeQuotaAction::Stopped, Some(used_bytes), quota_bytes, None).await;
+        stopped_by_quota = true;
+    }
+
+    volume_quota_service::save_usage(pool, project.id, used_bytes, Some(since), stopped_by_quota).await
+}
+
+async fn record_volume_quota_event(
+    pool: &PgPool,
+    project_id: i32,
+    action: VolumeQuotaAction,
+    used_bytes: Option<i64>,
+    quota_bytes: i64,
+    actor: Option<&str>,
+)
+{
+    if let Err(e) = volume_quota_service::record_event(pool, project_id, action, used_bytes, quota_bytes, actor).await
+    {
+        warn!("Volume quota event {:?} of project {} was not recorded: {}", action, project_id, e);
+    }
+}
@@ -0,0 +1,81 @@
+use axum::
+{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Json},
+};
+use serde_json::json;
+use tracing::info;
+
+use crate::
+{
+    api::json::ApiJson,
+    error::AppError,
+    model::scan::ScanWaiverPayload,
+    services::{jwt::Claims, scan_service, validation_service},
+    state::AppState,
+};
+
+pub async fn list_scan_waivers_handler(
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let waivers = scan_service::get_all_waivers(&state.db_pool).await?;
+
+    Ok((StatusCode::OK, Json(json!({ "waivers": waivers }))))
+}
+
+pub async fn create_scan_waiver_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    ApiJson(payload): ApiJson<ScanWaiverPayload>,
+) -> Result<impl IntoResponse, AppError>
+{
+    validation_service::validate_scan_waiver(&payload)?;
+
+    let waiver = scan_service::create_waiver(&state.db_pool, &payload, &claims.sub).await?;
+
+    info!(
+        "Admin '{}' created scan waiver {} for '{}' (package: {:?}, project: {:?}) until {}.",
+        claims.sub, waiver.id, waiver.cve_id, waiver.package, waiver.project_name, waiver.expires_at
+    );
+
+    Ok((StatusCode::CREATED, Json(json!({ "waiver": waiver }))))
+}
+
+pub async fn update_scan_waiver_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(waiver_id): Path<i32>,
+    ApiJson(payload): ApiJson<ScanWaiverPayload>,
+) -> Result<impl IntoResponse, AppError>
+{
+    validation_service::validate_scan_waiver(&payload)?;
+
+    let waiver = scan_service::update_waiver(&state.db_pool, waiver_id, &payload, &claims.sub)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Scan waiver with ID {} not found.", waiver_id)))?;
+
+    info!("Admin '{}' updated scan waiver {}.", claims.sub, waiver_id);
+
+    Ok((StatusCode::OK, Json(json!({ "waiver": waiver }))))
+}
+
+pub async fn delete_scan_waiver_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(waiver_id): Path<i32>,
+) -> Result<impl IntoResponse, AppError>
+{
+    if !scan_service::delete_waiver(&state.db_pool, waiver_id).await?
+    {
+        return Err(AppError::NotFound(format!("Scan waiver with ID {} not found.", waiver_id)));
+    }
+
+    info!("Admin '{}' deleted scan waiver {}.", claims.sub, waiver_id);
+
+    Ok((
+        StatusCode::OK,
+        Json(json!({"status": "success", "message": "Scan waiver deleted."})),
+    ))
+}
@@ -0,0 +1,162 @@
+use axum::
+{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Json},
+};
+use serde_json::json;
+use tracing::info;
+
+use crate::
+{
+    api::{json::ApiJson, list_params::ListParams},
+    error::AppError,
+    handlers::project_handler::ProjectsList,
+    model::team::{CreateTeamPayload, TeamMemberPayload, TeamMemberRolePayload, TeamQuotaPayload, TeamRole},
+    services::{jwt::Claims, project_service, quota_service, team_service, validation_service},
+    state::AppState,
+};
+
+pub async fn create_team_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    ApiJson(payload): ApiJson<CreateTeamPayload>,
+) -> Result<impl IntoResponse, AppError>
+{
+    validation_service::validate_team_name(&payload.name)?;
+
+    let team = team_service::create_team(&state.db_pool, &payload.name, &claims.sub).await?;
+
+    info!("User '{}' created team '{}' (ID {}).", claims.sub, team.name, team.id);
+
+    Ok((StatusCode::CREATED, Json(json!({ "team": team }))))
+}
+
+pub async fn list_my_teams_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+) -> Result<impl IntoResponse, AppError>
+{
+    let teams = team_service::get_teams_for_user(&state.db_pool, &claims.sub).await?;
+
+    Ok((StatusCode::OK, Json(json!({ "teams": teams }))))
+}
+
+pub async fn get_team_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(team_id): Path<i32>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let team = team_service::require_role(&state.db_pool, team_id, &claims.sub, claims.is_admin, TeamRole::Member).await?;
+    let members = team_service::get_members(&state.db_pool, team.id).await?;
+    let quota = quota_service::get_team_usage(&state.db_pool, &state.config(), &team).await?;
+
+    Ok((StatusCode::OK, Json(json!({ "team": team, "members": members, "quota": quota }))))
+}
+
+pub async fn delete_team_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(team_id): Path<i32>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let team = team_service::require_role(&state.db_pool, team_id, &claims.sub, claims.is_admin, TeamRole::Owner).await?;
+
+    team_service::delete_team(&state.db_pool, team.id).await?;
+
+    info!("User '{}' deleted team '{}'.", claims.sub, team.name);
+
+    Ok((StatusCode::OK, Json(json!({"status": "success", "message": "Team deleted successfully."}))))
+}
+
+pub async fn list_team_projects_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(team_id): Path<i32>,
+    list_params: ListParams<ProjectsList>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let team = team_service::require_role(&state.db_pool, team_id, &claims.sub, claims.is_admin, TeamRole::Member).await?;
+
+    let projects = project_service::get_projects_by_team(&state.db_pool, team.id).await?;
+
+    Ok((StatusCode::OK, Json(list_params.paginate(&projects)?)))
+}
+
+pub async fn add_team_member_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(team_id): Path<i32>,
+    ApiJson(payload): ApiJson<TeamMemberPayload>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let team = team_service::require_role(&state.db_pool, team_id, &claims.sub, claims.is_admin, TeamRole::Owner).await?;
+
+    let login = payload.login.trim();
+    if login.is_empty()
+    {
+        return Err(AppError::BadRequest("The member login cannot be empty.".to_string()));
+    }
+
+    team_service::add_member(&state.db_pool, team.id, login, payload.role, &claims.sub).await?;
+
+    info!("User '{}' added '{}' to team '{}' as {:?}.", claims.sub, login, team.name, payload.role);
+
+    Ok((StatusCode::CREATED, Json(json!({"status": "success", "message": "Member added to the team."}))))
+}
+
+pub async fn update_team_member_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path((team_id, login)): Path<(i32, String)>,
+    ApiJson(payload): ApiJson<TeamMemberRolePayload>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let team = team_service::require_role(&state.db_pool, team_id, &claims.sub, claims.is_admin, TeamRole::Owner).await?;
+
+    team_service::set_member_role(&state.db_pool, team.id, &login, payload.role).await?;
+
+    info!("User '{}' made '{}' {:?} of team '{}'.", claims.sub, login, payload.role, team.name);
+
+    Ok((StatusCode::OK, Json(json!({"status": "success", "message": "Member role updated."}))))
+}
+
+// Un membre ne peut retirer que lui-même, pour quitter l'équipe.
+pub async fn remove_team_member_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path((team_id, login)): Path<(i32, String)>,
+) -> Result<impl IntoResponse, AppError>
+{
+    let required = if login == claims.sub { TeamRole::Member } else { TeamRole::Owner };
+    let team = team_service::require_role(&state.db_pool, team_id, &claims.sub, claims.is_admin, required).await?;
+
+    team_service::remove_member(&state.db_pool, team.id, &login).await?;
+
+    info!("User '{}' removed '{}' from team '{}'.", claims.sub, login, team.name);
+
+    Ok((StatusCode::OK, Json(json!({"status": "success", "message": "Member removed from the team."}))))
+}
+
+pub async fn set_team_quota_handler(
+    State(state): State<AppState>,
+    claims: Claims,
+    Path(team_id): Path<i32>,
+    ApiJson(payload): ApiJson<TeamQuotaPayload>,
+) -> Result<impl IntoResponse, AppError>
+{
+    quota_service::validate_team_max_projects(payload.max_projects)?;
+
+    let mut team = team_service::get_team(&state.db_pool, team_id).await?
+        .ok_or_else(|| AppError::NotFound(format!("Team with ID {} not found.", team_id)))?;
+
+    team_service::set_max_projects(&state.db_pool, team.id, payload.max_projects).await?;
+    team.max_projects = payload.max_projects;
+
+    info!("Admin '{}' set the project quota of team '{}' to {:?}.", claims.sub, team.name, payload.max_projects);
+
+    let quota = quota_service::get_team_usage(&state.db_pool, &state.config(), &team).await?;
+
+    Ok((StatusCode::OK, Json(json!({ "team": team, "quota": quota }))))
+}
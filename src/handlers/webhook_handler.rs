@@ -0,0 +1,141 @@
+use axum::
+{
+    body::{to_bytes, Body},
+    extract::{ConnectInfo, State},
+    http::{header, HeaderMap},
+    response::{IntoResponse, Json},
+};
+use serde_json::json;
+use std::net::SocketAddr;
+use time::OffsetDateTime;
+use tracing::{info, warn};
+
+use crate::{error::AppError, state::AppState};
+use crate::model::webhook_delivery::WebhookDeliveryOutcome;
+use crate::services::webhook_service;
+
+// Toutes les livraisons, refusées ou non, sont gardées le temps de la fenêtre de rejeu pour l'audit.
+pub async fn github_webhook_handler(
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    body: Body,
+) -> Result<impl IntoResponse, AppError>
+{
+    let ip_address = addr.ip().to_string();
+    let delivery_id = header_value(&headers, "x-github-delivery");
+    let event = header_value(&headers, "x-github-event");
+    let max_payload_bytes = state.config().webhook_max_payload_bytes;
+    let window_seconds = state.config().webhook_replay_window_seconds;
+
+    if let Err(e) = webhook_service::prune_deliveries(&state.db_pool, window_seconds).await
+    {
+        warn!("Could not prune old webhook deliveries: {}", e);
+    }
+
+    let delivery = DeliveryRecord { state: &state, delivery_id: delivery_id.as_deref(), event: event.as_deref(), ip_address: &ip_address };
+
+    let payload = match to_bytes(body, max_payload_bytes).await
+    {
+        Ok(payload) => payload,
+        Err(e) =>
+        {
+            warn!("Webhook delivery {:?} from {} could not be read within {} bytes: {}", delivery_id, ip_address, max_payload_bytes, e);
+            let declared_size = header_value(&headers, header::CONTENT_LENGTH.as_str())
+                .and_then(|value| value.parse::<i64>().ok())
+                .unwrap_or(max_payload_bytes as i64);
+            delivery.record(WebhookDeliveryOutcome::PayloadTooLarge, declared_size).await;
+            return Err(AppError::PayloadTooLarge(format!("Webhook payload exceeds {} bytes.", max_payload_bytes)));
+        }
+    };
+    let payload_size = payload.len() as i64;
+
+    let (Some(delivery_id), Some(event)) = (delivery_id.as_deref(), event.as_deref()) else
+    {
+        delivery.record(WebhookDeliveryOutcome::Malformed, payload_size).await;
+        return Err(AppError::BadRequest("Missing X-GitHub-Delivery or X-GitHub-Event header.".to_string()));
+    };
+
+    let signature = header_value(&headers, "x-hub-signature-256");
+    if !webhook_service::verify_signature(&state.config().github_webhook_secrets, &payload, signature.as_deref())
+    {
+        warn!("Rejected webhook delivery '{}' from {}: invalid signature.", delivery_id, ip_address);
+        delivery.record(WebhookDeliveryOutcome::InvalidSignature, payload_size).await;
+        return Err(AppError::Unauthorized("Invalid webhook signature.".to_string()));
+    }
+
+    let Ok(json) = serde_json::from_slice::<serde_json::Value>(&payload) else
+    {
+        delivery.record(WebhookDeliveryOutcome::Malformed, payload_size).await;
+        return Err(AppError::BadRequest("Webhook payload is not valid JSON.".to_string()));
+    };
+
+    if let Some(timestamp) = webhook_service::stale_timestamp(&json, OffsetDateTime::now_utc(), window_seconds)
+    {
+        warn!("Rejected webhook delivery '{}' from {}: payload dated {} is outside the replay window.", delivery_id, ip_address, timestamp);
+        delivery.record(WebhookDeliveryOutcome::Stale, payload_size).await;
+        return Err(AppError::BadRequest("Webhook delivery is older than the replay window.".to_string()));
+    }
+
+    if !webhook_service::claim_delivery(&state.db_pool, delivery_id, event, payload_size, &ip_address).await?
+    {
+        // GitHub retries on non-2xx: answer 200 so a legitimate redelivery is not retried forever.
+        info!("Ignored duplicate webhook delivery '{}' ({}).", delivery_id, event);
+        delivery.record(WebhookDeliveryOutcome::Duplicate, payload_size).await;
+        return Ok(Json(json!({ "message": "Delivery already processed.", "delivery_id": delivery_id })));
+    }
+
+    info!("Accepted webhook delivery '{}' ({}) from {}.", delivery_id, event, ip_address);
+    Ok(Json(json!({ "message": "Delivery accepted.", "delivery_id": delivery_id })))
+}
+
+fn header_value(headers: &HeaderMap, name: &str) -> Option<String>
+{
+    headers.get(name).and_then(|value| value.to_str().ok()).map(str::to_string)
+}
+
+struct DeliveryRecord<'a>
+{
+    state: &'a AppState,
+    delivery_id: Option<&'a str>,
+    event: Option<&'a str>,
+    ip_address: &'a str,
+}
+
+impl DeliveryRecord<'_>
+{
+    async fn record(&self, outcome: WebhookDeliveryOutcome, payload_size: i64)
+    {
+        if let Err(e) = webhook_service::record_delivery(&self.state.db_pool, self.delivery_id, self.event, outcome, payload_size, self.ip_address).await
+        {
+            warn!("Could not record webhook delivery {:?} from {}: {}", self.delivery_id, self.ip_address, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::{config::Config, state::InnerState};
+    use axum::http::StatusCode;
+    use std::net::{Ipv4Addr, SocketAddr};
+
+    #[tokio::test]
+    async fn payload_over_the_limit_is_rejected_with_413()
+    {
+        let state = InnerState::for_tests(Config::for_tests(&[("WEBHOOK_MAX_PAYLOAD_BYTES", "64")]).unwrap());
+        let mut headers = HeaderMap::new();
+        headers.insert("x-github-delivery", "delivery-1".parse().unwrap());
+        headers.insert("x-github-event", "push".parse().unwrap());
+
+        let response = github_webhook_handler(
+            State(state),
+            ConnectInfo(SocketAddr::from((Ipv4Addr::LOCALHOST, 4242))),
+            headers,
+            Body::from(vec![b'a'; 65]),
+        ).await.into_response();
+
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+}
@@ -0,0 +1,47 @@
+use serde::Deserialize;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub enum Language
+{
+    Fr,
+    En,
+}
+
+impl FromStr for Language
+{
+    type Err = ();
+
+    fn from_str(value: &str) -> Result<Self, Self::Err>
+    {
+        let primary = value.split(['-', '_']).next().unwrap_or_default();
+        match primary.trim().to_ascii_lowercase().as_str()
+        {
+            "fr" => Ok(Language::Fr),
+            "en" => Ok(Language::En),
+            _ => Err(()),
+        }
+    }
+}
+
+pub fn negotiate(accept_language: Option<&str>, default: Language) -> Language
+{
+    let Some(header) = accept_language else { return default };
+
+    header
+        .split(',')
+        .filter_map(|entry|
+        {
+            let mut parts = entry.split(';');
+            let language = parts.next()?.trim().parse::<Language>().ok()?;
+            let weight = parts
+                .find_map(|param| param.trim().strip_prefix("q="))
+                .and_then(|q| q.parse::<f32>().ok())
+                .unwrap_or(1.0);
+            Some((language, weight))
+        })
+        .filter(|(_, weight)| *weight > 0.0)
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(language, _)| language)
+        .unwrap_or(default)
+}
@@ -1,18 +1,27 @@
+mod api;
+mod build_info;
 mod config;
 mod error;
+mod error_catalog;
+mod etag;
 mod handlers;
+mod i18n;
 mod router;
 mod state;
 mod services;
 mod model;
 mod middleware;
+mod ops;
+mod preflight;
+#[cfg(test)]
+mod test_support;
 
 use crate::config::Config;
 use crate::state::InnerState;
 
+use std::collections::HashMap;
 use std::net::{SocketAddr, Ipv4Addr};
-use sqlx::postgres::PgPoolOptions;
-use sqlx::mysql::MySqlPoolOptions;
+use sqlx::{MySql, Postgres};
 use tokio::net::TcpListener;
 use tracing::info;
 
@@ -23,6 +32,8 @@ async fn main()
 
     tracing_subscriber::fmt().with_env_filter(tracing_subscriber::EnvFilter::from_default_env()).init();
 
+    info!("🛫 Starting {}", build_info::build_info());
+
     let config = match Config::from_env() 
     {
         Ok(config) => config,
@@ -33,7 +44,19 @@ async fn main()
         }
     };
 
-    let db_pool = match PgPoolOptions::new().max_connections(config.db_max_connections).connect(&config.db_url).await
+    if config.cors_dev_allow_any_origin
+    {
+        tracing::warn!("⚠️ CORS_DEV_ALLOW_ANY_ORIGIN is enabled: any website can call the API without credentials. Never use it in production.");
+    }
+
+    // Pour les scripts de déploiement : valide la configuration sans rien démarrer.
+    if std::env::args().any(|arg| arg == "--check-config")
+    {
+        info!("✅ Configuration is valid.");
+        return;
+    }
+
+    let db_pool = match services::pool_service::pool_options::<Postgres>(&config).connect(&config.db_url).await
     {
         Ok(pool) => 
         {
@@ -58,7 +81,7 @@ async fn main()
         }
     }
 
-    let mariadb_pool = match MySqlPoolOptions::new().max_connections(config.db_max_connections).connect(&config.mariadb_url).await
+    let mariadb_pool = match services::pool_service::pool_options::<MySql>(&config).connect(&config.mariadb_url).await
     {
         Ok(pool) => 
         {
@@ -73,17 +96,102 @@ async fn main()
     };
 
 
-    let docker_client = match bollard::Docker::connect_with_local_defaults() 
+    if let Err(e) = services::build_dir_service::check_writable(&config.build_tmp_dir)
+    {
+        tracing::error!("❌ Build directory '{}' is not usable: {}", config.build_tmp_dir.display(), e);
+        std::process::exit(1);
+    }
+    info!("✅ Build directory '{}' is writable.", config.build_tmp_dir.display());
+
+    services::github_service::configure_git_timeouts(&config);
+
+    let mut docker_hosts = HashMap::new();
+    let mut docker_platforms = HashMap::new();
+    for host in &config.docker_hosts
+    {
+        match services::docker_service::connect(host, &config) 
+        {
+            Ok(client) => 
+            {
+                info!("✅ Docker host '{}' configured.", host.name);
+                match services::docker_service::get_host_platform(&client).await
+                {
+                    Ok(platform) =>
+                    {
+                        info!("🖥️ Docker host '{}' runs on {}.", host.name, platform);
+                        docker_platforms.insert(host.name.clone(), platform);
+                    }
+                    Err(e) => tracing::warn!("⚠️ Could not query the platform of Docker host '{}', image architecture checks are disabled for it: {}", host.name, e),
+                }
+                docker_hosts.insert(host.name.clone(), client);
+            }
+            Err(e) => 
+            {
+                tracing::error!("❌ Docker connection error for host '{}': {}", host.name, e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let http_client = match state::build_http_client(&config)
     {
         Ok(client) => client,
-        Err(e) => 
+        Err(e) =>
+        {
+            tracing::error!("❌ Failed to build HTTP client: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let probe_client = match state::build_probe_client(&config)
+    {
+        Ok(client) => client,
+        Err(e) =>
         {
-            tracing::error!("❌ Docker connection error: {}", e);
+            tracing::error!("❌ Failed to build the probe HTTP client: {}", e);
             std::process::exit(1);
         }
     };
 
-    let app_state = InnerState::new(config.clone(), docker_client, db_pool, mariadb_pool);
+    let app_state = InnerState::new(config.clone(), http_client, probe_client, docker_hosts, docker_platforms, db_pool, mariadb_pool);
+
+    info!("🩺 Running preflight checks...");
+    let preflight_failures = preflight::run(&app_state).await;
+    if preflight_failures > 0
+    {
+        if config.strict_startup
+        {
+            tracing::error!("❌ {} preflight check(s) failed and STRICT_STARTUP is enabled, refusing to start.", preflight_failures);
+            std::process::exit(1);
+        }
+        tracing::warn!("⚠️ {} preflight check(s) failed, starting in degraded mode. Set STRICT_STARTUP=true to refuse to start instead.", preflight_failures);
+    }
+
+    let default_host = app_state.default_docker_host();
+    match services::project_service::assign_default_docker_host(&app_state.db_pool, &default_host, config.docker_host("default").is_some()).await
+    {
+        Ok(0) => {}
+        Ok(count) => info!("🐳 {} project(s) without a Docker host assigned to '{}'.", count, default_host),
+        Err(e) =>
+        {
+            tracing::error!("❌ Failed to assign the default Docker host to projects: {}", e);
+            std::process::exit(1);
+        }
+    }
+
+    info!("🔄 Recovering deployments interrupted by a previous shutdown...");
+    handlers::project_handler::recover_interrupted_deployments(&app_state).await;
+    tokio::spawn(handlers::project_handler::run_deploy_queue_worker(app_state.clone()));
+    tokio::spawn(handlers::project_handler::reconcile_project_containers(app_state.clone()));
+    tokio::spawn(handlers::invitation_handler::run_invitation_cleanup(app_state.clone()));
+    tokio::spawn(handlers::project_handler::run_build_cache_prune(app_state.clone()));
+    tokio::spawn(handlers::project_handler::run_deleted_projects_reaper(app_state.clone()));
+    tokio::spawn(handlers::project_handler::run_project_status_sync(app_state.clone()));
+    tokio::spawn(handlers::project_handler::run_project_scheduler(app_state.clone()));
+    tokio::spawn(handlers::project_handler::run_availability_compaction(app_state.clone()));
+    tokio::spawn(handlers::quota_handler::run_volume_quota_enforcement(app_state.clone()));
+    tokio::spawn(ops::run_pending_cleanup_janitor(app_state.clone()));
+
     let app = router::create_router(app_state);
 
     let addr = SocketAddr::from((config.host.parse::<Ipv4Addr>().unwrap(), config.port));
@@ -1,32 +1,102 @@
 use axum::
 {
-    extract::{Request, State, FromRequestParts},
+    extract::{Path, Query, Request, State, FromRequestParts},
+    body::{to_bytes, Body},
     http::request::Parts,
+    http::header,
     middleware::Next,
     response::Response,
 };
 use axum_extra::extract::CookieJar;
+use serde::Deserialize;
+use std::{collections::HashMap, sync::Mutex, time::{SystemTime, UNIX_EPOCH}};
 
 use crate::
 {
-    error::AppError,
-    services::jwt::{self, Claims},
+    error::{AppError, LocalizedMessage},
+    i18n::{self, Language},
+    services::jwt::{self, Claims, StreamKind, StreamTicket},
     state::AppState,
 };
 
 pub async fn auth(State(state): State<AppState>,jar: CookieJar, mut req: Request, next: Next) -> Result<Response, AppError> 
 {
    
-    let token = jar.get("auth_token").map(|cookie| cookie.value())
-        .ok_or_else(|| AppError::Unauthorized("Authentication token missing.".to_string()))?;
+    // Les pipelines CI n'ont pas de cookie : ils envoient le même JWT en en-tête Bearer.
+    let token = match jar.get("auth_token")
+    {
+        Some(cookie) => cookie.value().to_string(),
+        None => req.headers()
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .map(str::to_string)
+            .ok_or_else(|| AppError::Unauthorized("Authentication token missing.".to_string()))?,
+    };
 
-    let token_data = jwt::validate_jwt(token, &state.config.jwt_secret)?;
+    let token_data = jwt::validate_jwt(&token, &state.config().jwt_secret)?;
 
     req.extensions_mut().insert(token_data.claims);
 
     Ok(next.run(req).await)
 }
 
+#[derive(Deserialize)]
+pub struct StreamTicketQuery
+{
+    ticket: Option<String>,
+}
+
+// Un `?ticket=` remplace le cookie une seule fois, pour le projet et le flux de son émission.
+pub async fn stream_auth(
+    State((state, stream)): State<(AppState, StreamKind)>,
+    Path(project_id): Path<i32>,
+    Query(query): Query<StreamTicketQuery>,
+    jar: CookieJar,
+    mut req: Request,
+    next: Next,
+) -> Result<Response, AppError>
+{
+    let Some(token) = query.ticket else
+    {
+        return auth(State(state), jar, req, next).await;
+    };
+
+    let ticket = jwt::validate_stream_ticket(&token, &state.config().jwt_secret)?;
+    check_ticket_target(&ticket, project_id, stream)?;
+
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs() as i64).unwrap_or(0);
+    consume_stream_ticket(&state.consumed_stream_tickets, &ticket, now)?;
+
+    req.extensions_mut().insert(ticket.into_claims());
+
+    Ok(next.run(req).await)
+}
+
+fn check_ticket_target(ticket: &StreamTicket, project_id: i32, stream: StreamKind) -> Result<(), AppError>
+{
+    if ticket.project_id != project_id || ticket.stream != stream
+    {
+        return Err(AppError::Unauthorized("This stream ticket was issued for another stream.".to_string()));
+    }
+    Ok(())
+}
+
+fn consume_stream_ticket(consumed: &Mutex<HashMap<String, i64>>, ticket: &StreamTicket, now: i64) -> Result<(), AppError>
+{
+    let mut consumed = consumed.lock().map_err(|_| AppError::InternalServerError)?;
+
+    // Un ticket expiré est déjà refusé par sa validation : inutile de s'en souvenir.
+    consumed.retain(|_, exp| *exp >= now);
+
+    if consumed.insert(ticket.jti.clone(), ticket.exp).is_some()
+    {
+        return Err(AppError::Unauthorized("This stream ticket has already been used.".to_string()));
+    }
+
+    Ok(())
+}
+
 pub async fn admin_auth(claims: Claims, req: Request, next: Next) -> Result<Response, AppError> 
 {
     if !claims.is_admin 
@@ -36,6 +106,48 @@ pub async fn admin_auth(claims: Claims, req: Request, next: Next) -> Result<Resp
     Ok(next.run(req).await)
 }
 
+pub async fn localize_errors(State(state): State<AppState>, req: Request, next: Next) -> Response
+{
+    let accept_language = req.headers()
+        .get(header::ACCEPT_LANGUAGE)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+
+    let response = next.run(req).await;
+
+    let language = i18n::negotiate(accept_language.as_deref(), state.config().default_language);
+    if language != Language::Fr
+    {
+        return response;
+    }
+
+    let Some(LocalizedMessage(message)) = response.extensions().get::<LocalizedMessage>().cloned() else
+    {
+        return response;
+    };
+
+    let (mut parts, body) = response.into_parts();
+    let Ok(bytes) = to_bytes(body, usize::MAX).await else
+    {
+        tracing::error!("Failed to read an error body for localization.");
+        return Response::from_parts(parts, Body::empty());
+    };
+
+    let localized = match serde_json::from_slice::<serde_json::Value>(&bytes)
+    {
+        Ok(mut json) =>
+        {
+            json["message"] = serde_json::Value::String(message);
+            serde_json::to_vec(&json).unwrap_or_else(|_| bytes.to_vec())
+        }
+        Err(_) => bytes.to_vec(),
+    };
+
+    parts.headers.remove(header::CONTENT_LENGTH);
+    parts.headers.insert(header::CONTENT_LANGUAGE, header::HeaderValue::from_static("fr"));
+    Response::from_parts(parts, Body::from(localized))
+}
+
 impl<S> FromRequestParts<S> for Claims where S: Send + Sync,
 {
     type Rejection = AppError;
@@ -50,3 +162,42 @@ impl<S> FromRequestParts<S> for Claims where S: Send + Sync,
     }
 }
 
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    const SECRET: &str = "un-secret-de-test-suffisamment-long-pour-la-validation";
+
+    fn ticket(project_id: i32, stream: StreamKind) -> StreamTicket
+    {
+        let claims = Claims { sub: "alice".to_string(), name: String::new(), email: String::new(), exp: 0, iat: 0, is_admin: false };
+        let token = jwt::generate_stream_ticket(SECRET, &claims, project_id, stream).unwrap();
+        jwt::validate_stream_ticket(&token, SECRET).unwrap()
+    }
+
+    #[test]
+    fn ticket_is_single_use()
+    {
+        let consumed = Mutex::new(HashMap::new());
+        let first = ticket(1, StreamKind::Logs);
+        let second = ticket(1, StreamKind::Logs);
+        let now = first.iat;
+
+        assert!(consume_stream_ticket(&consumed, &first, now).is_ok());
+        assert!(consume_stream_ticket(&consumed, &first, now).is_err());
+        assert!(consume_stream_ticket(&consumed, &second, now).is_ok());
+    }
+
+    #[test]
+    fn ticket_only_opens_its_own_stream()
+    {
+        let ticket = ticket(1, StreamKind::Logs);
+
+        assert!(check_ticket_target(&ticket, 1, StreamKind::Logs).is_ok());
+        assert!(check_ticket_target(&ticket, 1, StreamKind::Status).is_err());
+        assert!(check_ticket_target(&ticket, 1, StreamKind::Export).is_err());
+        assert!(check_ticket_target(&ticket, 2, StreamKind::Logs).is_err());
+    }
+}
@@ -0,0 +1,45 @@
+use serde::{Deserialize, Serialize};
+
+use crate::model::
+{
+    database::DatabaseDetailsResponse,
+    deployment::Deployment,
+    invitation::Invitation,
+    project::Project,
+};
+
+#[derive(Debug, Serialize)]
+pub struct AccountResources
+{
+    pub owned_projects: Vec<Project>,
+    pub participations: Vec<Project>,
+    pub database: Option<DatabaseDetailsResponse>,
+    pub pending_invitations: Vec<Invitation>,
+    pub active_deployments: Vec<Deployment>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AccountCleanupPayload
+{
+    pub confirmation: String,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum AccountResourceKind
+{
+    Project,
+    Participation,
+    Invitation,
+    Database,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AccountCleanupStep
+{
+    pub kind: AccountResourceKind,
+    pub name: String,
+    pub ok: bool,
+    pub error: Option<String>,
+}
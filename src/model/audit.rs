@@ -0,0 +1,51 @@
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, sqlx::Type)]
+#[sqlx(type_name = "audit_target_type", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum AuditTargetType
+{
+    Project,
+    Database,
+    Deployment,
+    // `target_id` vaut 0.
+    Inventory,
+}
+
+#[derive(Debug, Serialize, Clone, sqlx::FromRow)]
+pub struct AuditEntry
+{
+    pub id: i64,
+    pub actor: String,
+    pub action: String,
+    pub target_type: AuditTargetType,
+    pub target_id: i32,
+    // Nom au moment de l'action : la cible a pu être renommée ou purgée depuis.
+    pub target_name: Option<String>,
+    pub performed_by_admin: bool,
+    pub details: Option<serde_json::Value>,
+
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+pub struct AuditRecord<'a>
+{
+    pub actor: &'a str,
+    pub action: &'a str,
+    pub target_type: AuditTargetType,
+    pub target_id: i32,
+    pub target_name: Option<&'a str>,
+    pub performed_by_admin: bool,
+    pub details: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct AuditFilters
+{
+    pub actor: Option<String>,
+    pub action: Option<String>,
+    pub target_type: Option<AuditTargetType>,
+    pub target_id: Option<i32>,
+}
@@ -0,0 +1,28 @@
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, sqlx::Type)]
+#[sqlx(type_name = "auth_failure_reason", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum AuthFailureReason
+{
+    InvalidTicket,
+    CasUnreachable,
+    CasError,
+    MalformedResponse,
+    MissingAttributes,
+}
+
+#[derive(Debug, Serialize, Clone, sqlx::FromRow)]
+pub struct AuthAttempt
+{
+    pub id: i64,
+    pub login: Option<String>,
+    pub success: bool,
+    pub failure_reason: Option<AuthFailureReason>,
+    pub ip_address: String,
+    pub user_agent: Option<String>,
+
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
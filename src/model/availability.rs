@@ -0,0 +1,73 @@
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use crate::model::project::ProjectStatus;
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct AvailabilityEvent
+{
+    pub project_id: i32,
+    pub status: ProjectStatus,
+    pub observed_at: OffsetDateTime,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct AvailabilityDay
+{
+    pub project_id: i32,
+    pub up_seconds: i64,
+    pub down_seconds: i64,
+    pub incidents: i32,
+    pub longest_outage_seconds: i64,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq)]
+pub enum UptimeWindow
+{
+    #[default]
+    #[serde(rename = "7d")]
+    Week,
+    #[serde(rename = "30d")]
+    Month,
+}
+
+impl UptimeWindow
+{
+    pub fn days(self) -> i64
+    {
+        match self
+        {
+            Self::Week => 7,
+            Self::Month => 30,
+        }
+    }
+}
+
+// Le temps passé arrêté volontairement ne compte ni comme disponible ni comme indisponible.
+#[derive(Debug, Serialize, Clone, Default, PartialEq)]
+pub struct AvailabilityStats
+{
+    pub up_seconds: i64,
+    pub down_seconds: i64,
+    pub uptime_percent: Option<f64>,
+    pub incidents: i64,
+    pub longest_outage_seconds: i64,
+}
+
+impl AvailabilityStats
+{
+    pub fn add(&mut self, other: &AvailabilityStats)
+    {
+        self.up_seconds += other.up_seconds;
+        self.down_seconds += other.down_seconds;
+        self.incidents += other.incidents;
+        self.longest_outage_seconds = self.longest_outage_seconds.max(other.longest_outage_seconds);
+        self.uptime_percent = uptime_percent(self.up_seconds, self.down_seconds);
+    }
+}
+
+pub fn uptime_percent(up_seconds: i64, down_seconds: i64) -> Option<f64>
+{
+    let observed = up_seconds + down_seconds;
+    (observed > 0).then(|| up_seconds as f64 * 100.0 / observed as f64)
+}
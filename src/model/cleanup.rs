@@ -0,0 +1,45 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum CleanupAction
+{
+    RemoveContainer { host: String, container: String },
+    RemoveImage { host: String, image: String },
+    RemoveVolume { host: String, volume: String },
+    DeprovisionDatabase { database: String, username: String },
+}
+
+impl fmt::Display for CleanupAction
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result
+    {
+        match self
+        {
+            CleanupAction::RemoveContainer { host, container } => write!(f, "remove container '{}' on '{}'", container, host),
+            CleanupAction::RemoveImage { host, image } => write!(f, "remove image '{}' on '{}'", image, host),
+            CleanupAction::RemoveVolume { host, volume } => write!(f, "remove volume '{}' on '{}'", volume, host),
+            CleanupAction::DeprovisionDatabase { database, .. } => write!(f, "deprovision MariaDB database '{}'", database),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct PendingCleanup
+{
+    pub id: i64,
+    pub operation: String,
+    pub action: serde_json::Value,
+    pub attempts: i32,
+    pub last_error: String,
+
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub last_attempt_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub abandoned_at: Option<OffsetDateTime>,
+}
@@ -0,0 +1,32 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::model::{config_revision::ProjectConfigSnapshot, project::ProjectSourceType};
+
+// À incrémenter dès qu'un champ change de sens.
+pub const CONFIG_DOCUMENT_VERSION: u32 = 1;
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProjectConfigDocument
+{
+    pub schema_version: u32,
+    pub name: String,
+    pub source_type: ProjectSourceType,
+    pub source_url: String,
+    pub source_branch: Option<String>,
+    pub source_root_dir: Option<String>,
+    pub image_tag: String,
+    pub env_vars: BTreeMap<String, String>,
+    pub persistent_volume_path: Option<String>,
+    pub participants: Vec<String>,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    // Pour information : un import déploie avec les valeurs par défaut.
+    pub settings: ProjectConfigSnapshot,
+    // Ignorée à l'import : la base doit être recréée.
+    pub database: Option<Value>,
+}
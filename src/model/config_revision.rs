@@ -0,0 +1,105 @@
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use time::OffsetDateTime;
+
+use crate::model::project::{EgressPolicy, Project, ProjectRateLimit, RouteMiddlewares, RoutingOptions, TraefikHealthcheck};
+
+// Les variables d'environnement sont réduites à leurs clés : les valeurs ne quittent jamais la colonne chiffrée.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ProjectConfigSnapshot
+{
+    pub source_branch: Option<String>,
+    pub source_root_dir: Option<String>,
+    pub persistent_volume_path: Option<String>,
+    pub replicas: i32,
+    pub volume_share_safe: bool,
+    pub env_var_keys: BTreeSet<String>,
+    pub rate_limit: Option<ProjectRateLimit>,
+    pub ip_allowlist: Option<Vec<String>>,
+    pub healthcheck: Option<TraefikHealthcheck>,
+    pub routing: RoutingOptions,
+    pub egress_policy: EgressPolicy,
+}
+
+impl ProjectConfigSnapshot
+{
+    pub fn of(project: &Project) -> Self
+    {
+        let env_var_keys = project.env_vars
+            .as_ref()
+            .and_then(Value::as_object)
+            .map(|vars| vars.keys().cloned().collect())
+            .unwrap_or_default();
+
+        Self
+        {
+            source_branch: project.source_branch.clone(),
+            source_root_dir: project.source_root_dir.clone(),
+            persistent_volume_path: project.persistent_volume_path.clone(),
+            replicas: project.replicas,
+            volume_share_safe: project.volume_share_safe,
+            env_var_keys,
+            rate_limit: project.rate_limit(),
+            ip_allowlist: project.ip_allowlist.clone(),
+            healthcheck: project.healthcheck(),
+            routing: project.routing_options.0.clone(),
+            egress_policy: project.egress_policy,
+        }
+    }
+
+    pub fn route_middlewares(&self) -> RouteMiddlewares
+    {
+        RouteMiddlewares
+        {
+            ip_allowlist: self.ip_allowlist.clone(),
+            rate_limit: self.rate_limit,
+            healthcheck: self.healthcheck.clone(),
+            routing: self.routing.clone(),
+        }
+    }
+
+    pub fn diff(&self, previous: &Self) -> Value
+    {
+        let mut changes = Map::new();
+
+        let added: Vec<&String> = self.env_var_keys.difference(&previous.env_var_keys).collect();
+        let removed: Vec<&String> = previous.env_var_keys.difference(&self.env_var_keys).collect();
+        if !added.is_empty() || !removed.is_empty()
+        {
+            changes.insert("env_var_keys".to_string(), json!({ "added": added, "removed": removed }));
+        }
+
+        let (Value::Object(current), Value::Object(previous)) = (json!(self), json!(previous))
+        else
+        {
+            return Value::Object(changes);
+        };
+
+        for (field, value) in current
+        {
+            let old_value = previous.get(&field).cloned().unwrap_or(Value::Null);
+            if field != "env_var_keys" && old_value != value
+            {
+                changes.insert(field, json!({ "from": old_value, "to": value }));
+            }
+        }
+
+        Value::Object(changes)
+    }
+}
+
+#[derive(Debug, Serialize, Clone, sqlx::FromRow)]
+pub struct ProjectConfigRevision
+{
+    pub id: i64,
+    pub project_id: i32,
+    pub revision: i32,
+    pub snapshot: sqlx::types::Json<ProjectConfigSnapshot>,
+    pub diff: Option<Value>,
+    pub actor: String,
+
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
@@ -10,6 +10,11 @@ pub struct Database
     pub username: String,
     pub encrypted_password: String,
     pub project_id: Option<i32>,
+    #[sqlx(default)]
+    pub template_name: Option<String>,
+    #[sqlx(flatten)]
+    #[serde(flatten)]
+    pub limits: UserResourceLimits,
 
     #[serde(with = "time::serde::rfc3339")]
     pub created_at: OffsetDateTime,
@@ -23,10 +28,72 @@ pub struct DatabaseDetailsResponse
     pub project_id: Option<i32>,
     pub database_name: String,
     pub username: String,
-    pub password: String, // Mot de passe en clair
+    // Mot de passe en clair, absent sauf à la création ou via une révélation explicite.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    pub password_masked: bool,
     pub host: String,
     pub port: u16,
+    pub template_name: Option<String>,
+    pub limits: UserResourceLimits,
     
     #[serde(with = "time::serde::rfc3339")]
     pub created_at: OffsetDateTime,
-}
\ No newline at end of file
+}
+// 0 signifie sans limite, comme dans MariaDB.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, sqlx::FromRow)]
+pub struct UserResourceLimits
+{
+    pub max_user_connections: i32,
+    pub max_queries_per_hour: i32,
+    pub max_updates_per_hour: i32,
+    pub max_connections_per_hour: i32,
+}
+
+impl UserResourceLimits
+{
+    pub fn first_invalid(&self) -> Option<(&'static str, i32)>
+    {
+        [
+            ("max_user_connections", self.max_user_connections),
+            ("max_queries_per_hour", self.max_queries_per_hour),
+            ("max_updates_per_hour", self.max_updates_per_hour),
+            ("max_connections_per_hour", self.max_connections_per_hour),
+        ]
+        .into_iter()
+        .find(|(_, value)| *value < 0)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateUserLimitsPayload
+{
+    pub max_user_connections: Option<i32>,
+    pub max_queries_per_hour: Option<i32>,
+    pub max_updates_per_hour: Option<i32>,
+    pub max_connections_per_hour: Option<i32>,
+}
+
+impl UpdateUserLimitsPayload
+{
+    pub fn apply_to(&self, current: UserResourceLimits) -> UserResourceLimits
+    {
+        UserResourceLimits
+        {
+            max_user_connections: self.max_user_connections.unwrap_or(current.max_user_connections),
+            max_queries_per_hour: self.max_queries_per_hour.unwrap_or(current.max_queries_per_hour),
+            max_updates_per_hour: self.max_updates_per_hour.unwrap_or(current.max_updates_per_hour),
+            max_connections_per_hour: self.max_connections_per_hour.unwrap_or(current.max_connections_per_hour),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, sqlx::Type)]
+#[sqlx(type_name = "reveal_method", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum RevealMethod
+{
+    FreshSession,
+    CasTicket,
+}
@@ -0,0 +1,31 @@
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+#[derive(Debug, Serialize, Clone, sqlx::FromRow)]
+pub struct DbTemplate
+{
+    pub id: i32,
+    pub name: String,
+    pub description: Option<String>,
+    pub size_bytes: i32,
+    pub created_by: String,
+
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DbTemplatePayload
+{
+    pub name: String,
+    pub description: Option<String>,
+    pub script: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CreateDatabasePayload
+{
+    pub template: Option<String>,
+}
@@ -0,0 +1,176 @@
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use crate::model::project::ProjectSourceType;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, sqlx::Type)]
+#[sqlx(type_name = "deployment_status", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum DeploymentStatus
+{
+    Queued,
+    Building,
+    CreatingContainer,
+    Persisting,
+    Succeeded,
+    Failed,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
+pub struct Deployment
+{
+    pub id: i32,
+    pub owner: String,
+    pub project_name: String,
+    pub status: DeploymentStatus,
+
+    #[serde(skip)]
+    pub encrypted_payload: String,
+
+    pub image_tag: Option<String>,
+    pub container_name: Option<String>,
+    pub volume_name: Option<String>,
+    pub project_id: Option<i32>,
+    pub docker_host: Option<String>,
+    pub error_code: Option<String>,
+    pub error_message: Option<String>,
+    #[sqlx(default)]
+    pub warnings: Vec<String>,
+
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub updated_at: OffsetDateTime,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, sqlx::Type)]
+#[sqlx(type_name = "deploy_phase", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum DeployPhase
+{
+    BuildSlotWait,
+    Clone,
+    Build,
+    Pull,
+    Verify,
+    Scan,
+    CreateContainer,
+    Persist,
+}
+
+impl DeployPhase
+{
+    pub fn as_str(self) -> &'static str
+    {
+        match self
+        {
+            Self::BuildSlotWait => "build_slot_wait",
+            Self::Clone => "clone",
+            Self::Build => "build",
+            Self::Pull => "pull",
+            Self::Verify => "verify",
+            Self::Scan => "scan",
+            Self::CreateContainer => "create_container",
+            Self::Persist => "persist",
+        }
+    }
+}
+
+// Une phase mesurée plusieurs fois cumule ses durées.
+#[derive(Debug, Default)]
+pub struct DeployTimings
+{
+    pub source_type: Option<ProjectSourceType>,
+    pub phases: Vec<(DeployPhase, Duration)>,
+}
+
+impl DeployTimings
+{
+    pub async fn measure<F: Future>(&mut self, phase: DeployPhase, future: F) -> F::Output
+    {
+        let started = Instant::now();
+        let output = future.await;
+        self.record(phase, started.elapsed());
+        output
+    }
+
+    pub fn record(&mut self, phase: DeployPhase, duration: Duration)
+    {
+        match self.phases.iter_mut().find(|(recorded, _)| *recorded == phase)
+        {
+            Some((_, total)) => *total += duration,
+            None => self.phases.push((phase, duration)),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone, sqlx::FromRow)]
+pub struct DeployPhaseStats
+{
+    pub phase: DeployPhase,
+    pub source_type: ProjectSourceType,
+    pub samples: i64,
+    pub p50_ms: f64,
+    pub p90_ms: f64,
+    pub p99_ms: f64,
+    pub max_ms: i64,
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct PullProgress
+{
+    pub layers: BTreeMap<String, LayerProgress>,
+    pub current_bytes: i64,
+    pub total_bytes: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, Default)]
+pub struct CloneProgress
+{
+    pub received_objects: usize,
+    pub total_objects: usize,
+    pub received_bytes: usize,
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct LayerProgress
+{
+    pub status: String,
+    pub current_bytes: Option<i64>,
+    pub total_bytes: Option<i64>,
+}
+
+impl PullProgress
+{
+    pub fn record(&mut self, layer_id: &str, status: &str, current: Option<i64>, total: Option<i64>)
+    {
+        let layer = self.layers.entry(layer_id.to_string()).or_default();
+        layer.status = status.to_string();
+
+        if total.is_some_and(|total| total > 0)
+        {
+            layer.total_bytes = total;
+        }
+        layer.current_bytes = match status
+        {
+            // Ces statuts n'ont pas de progress_detail : la couche est entièrement présente.
+            "Download complete" | "Pull complete" | "Already exists" => layer.total_bytes,
+            _ => current.or(layer.current_bytes),
+        };
+
+        self.current_bytes = self.layers.values().filter_map(|layer| layer.current_bytes).sum();
+        self.total_bytes = self.layers.values().filter_map(|layer| layer.total_bytes).sum();
+    }
+
+    pub fn summary(&self) -> Vec<String>
+    {
+        self.layers
+            .iter()
+            .map(|(id, layer)| format!("{}: {}", id, layer.status))
+            .chain(self.error.clone())
+            .collect()
+    }
+}
@@ -0,0 +1,26 @@
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, sqlx::Type)]
+#[sqlx(type_name = "participant_status", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ParticipantStatus
+{
+    Pending,
+    Accepted,
+}
+
+#[derive(Debug, Serialize, Clone, sqlx::FromRow)]
+pub struct Invitation
+{
+    pub id: i32,
+    pub project_id: i32,
+    pub project_name: String,
+    pub project_owner: String,
+    pub invited_by: Option<String>,
+
+    #[serde(with = "time::serde::rfc3339")]
+    pub invited_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub expires_at: OffsetDateTime,
+}
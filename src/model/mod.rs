@@ -1,3 +1,18 @@
 pub mod user;
 pub mod project;
-pub mod database;
\ No newline at end of file
+pub mod database;
+pub mod deployment;
+pub mod invitation;
+pub mod auth_attempt;
+pub mod scan;
+pub mod webhook_delivery;
+pub mod volume_quota;
+pub mod cleanup;
+pub mod account;
+pub mod db_template;
+pub mod project_event;
+pub mod config_revision;
+pub mod team;
+pub mod audit;
+pub mod config_document;
+pub mod availability;
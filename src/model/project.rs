@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use serde::{Deserialize, Serialize};
 use time::OffsetDateTime;
 
@@ -12,6 +13,47 @@ pub enum ProjectSourceType
     Github,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, sqlx::Type)]
+#[sqlx(type_name = "egress_policy", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum EgressPolicy
+{
+    #[default]
+    Open,
+    // Réseau Docker interne : Traefik les atteint, mais ils ne peuvent pas sortir.
+    InternalOnly,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, sqlx::Type)]
+#[sqlx(type_name = "logs_visibility", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum LogsVisibility
+{
+    #[default]
+    All,
+    OwnerOnly,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, sqlx::Type)]
+#[sqlx(type_name = "project_status", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum ProjectStatus
+{
+    Running,
+    Stopped,
+    // Docker renvoie les mêmes codes de sortie pour un arrêt et pour un crash.
+    Crashed,
+    Missing,
+}
+
+impl ProjectStatus
+{
+    pub fn is_outage(self) -> bool
+    {
+        matches!(self, Self::Crashed | Self::Missing)
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
 pub struct Project 
 {
@@ -20,6 +62,9 @@ pub struct Project
     pub owner: String,
 
     pub container_name: String,
+    // Absent pour les projets antérieurs à son suivi, jusqu'à la prochaine réconciliation.
+    #[sqlx(default)]
+    pub container_id: Option<String>,
 
     #[sqlx(rename = "source_type")]
     pub source: ProjectSourceType,
@@ -39,8 +84,189 @@ pub struct Project
     #[sqlx(default)]
     pub volume_name: Option<String>,
 
+    pub docker_host: String,
+
+    #[sqlx(default)]
+    pub rate_limit_average: Option<i32>,
+    #[sqlx(default)]
+    pub rate_limit_burst: Option<i32>,
+    // Seuls les administrateurs peuvent modifier une limite qu'ils ont imposée.
+    #[sqlx(default)]
+    pub rate_limit_set_by_admin: bool,
+    #[sqlx(default)]
+    pub ip_allowlist: Option<Vec<String>>,
+    #[sqlx(default)]
+    pub healthcheck_path: Option<String>,
+    #[sqlx(default)]
+    pub healthcheck_interval: Option<i32>,
+    #[sqlx(default)]
+    pub healthcheck_port: Option<i32>,
+
+    pub intended_running: bool,
+    pub lost_container: bool,
+
+    pub replicas: i32,
+    pub volume_share_safe: bool,
+    pub maintenance_page: bool,
+
+    pub image_platform: Option<String>,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub image_created_at: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub last_scanned_at: Option<OffsetDateTime>,
+
+    #[sqlx(default)]
+    pub egress_policy: EgressPolicy,
+    #[sqlx(default)]
+    pub routing_options: sqlx::types::Json<RoutingOptions>,
+    // Pour un projet d'équipe, `owner` indique seulement qui l'a déployé.
+    #[sqlx(default)]
+    pub team_id: Option<i32>,
+    #[sqlx(default)]
+    pub logs_visibility: LogsVisibility,
+    #[sqlx(default)]
+    pub logs_redaction: bool,
+    #[sqlx(default)]
+    pub description: Option<String>,
+    #[sqlx(default)]
+    pub tags: Vec<String>,
+    #[sqlx(default)]
+    pub custom_domains: Vec<String>,
+    #[sqlx(default)]
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub archived_at: Option<OffsetDateTime>,
+    #[sqlx(default)]
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub deleted_at: Option<OffsetDateTime>,
+    // `None` jusqu'à la première synchronisation ; les vues détaillées interrogent Docker.
+    #[sqlx(default)]
+    pub status: Option<ProjectStatus>,
+    #[sqlx(default)]
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub status_changed_at: Option<OffsetDateTime>,
+    // Évaluées dans le décalage SCHEDULE_UTC_OFFSET_HOURS.
+    #[sqlx(default)]
+    pub auto_start_cron: Option<String>,
+    #[sqlx(default)]
+    pub auto_stop_cron: Option<String>,
+
     #[serde(with = "time::serde::rfc3339")]
     pub created_at: OffsetDateTime,
+
+    #[serde(with = "time::serde::rfc3339")]
+    pub updated_at: OffsetDateTime,
+
+    #[serde(with = "time::serde::rfc3339")]
+    pub last_deployed_at: OffsetDateTime,
+}
+
+impl Project
+{
+    pub fn rate_limit(&self) -> Option<ProjectRateLimit>
+    {
+        match (self.rate_limit_average, self.rate_limit_burst)
+        {
+            (Some(average), Some(burst)) => Some(ProjectRateLimit { average, burst }),
+            _ => None,
+        }
+    }
+
+    pub fn healthcheck(&self) -> Option<TraefikHealthcheck>
+    {
+        match (&self.healthcheck_path, self.healthcheck_interval)
+        {
+            (Some(path), Some(interval_seconds)) => Some(TraefikHealthcheck
+            {
+                path: path.clone(),
+                interval_seconds,
+                port: self.healthcheck_port,
+            }),
+            _ => None,
+        }
+    }
+
+    pub fn container_names(&self) -> Vec<String>
+    {
+        replica_container_names(&self.container_name, self.replicas)
+    }
+
+    pub fn route_middlewares(&self) -> RouteMiddlewares
+    {
+        RouteMiddlewares
+        {
+            ip_allowlist: self.ip_allowlist.clone(),
+            rate_limit: self.rate_limit(),
+            healthcheck: self.healthcheck(),
+            routing: self.routing_options.0.clone(),
+        }
+    }
+}
+
+// Le premier réplica garde le nom de base : un projet à un seul réplica ne change pas.
+pub fn replica_container_names(base_name: &str, replicas: i32) -> Vec<String>
+{
+    (0..replicas.max(1))
+        .map(|index| if index == 0 { base_name.to_string() } else { format!("{}-{}", base_name, index) })
+        .collect()
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RouteMiddlewares
+{
+    pub ip_allowlist: Option<Vec<String>>,
+    pub rate_limit: Option<ProjectRateLimit>,
+    pub healthcheck: Option<TraefikHealthcheck>,
+    pub routing: RoutingOptions,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct RoutingOptions
+{
+    pub sticky_sessions: bool,
+    pub response_headers: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TraefikHealthcheck
+{
+    pub path: String,
+    pub interval_seconds: i32,
+    pub port: Option<i32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct ProjectRateLimit
+{
+    pub average: i32,
+    pub burst: i32,
+}
+
+// Les clés restent visibles aux participants ; les valeurs ne sont révélées qu'au propriétaire et aux administrateurs.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum EnvVarVisibility
+{
+    #[default]
+    Secret,
+    Shared,
+}
+
+pub const MASKED_ENV_VALUE: &str = "•••";
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct MaskedEnvVar
+{
+    pub value: &'static str,
+    pub has_value: bool,
+}
+
+impl MaskedEnvVar
+{
+    pub fn of(value: &str) -> Self
+    {
+        Self { value: MASKED_ENV_VALUE, has_value: !value.is_empty() }
+    }
 }
 
 #[derive(Debug, Serialize, Clone)]
@@ -49,7 +275,27 @@ pub struct ProjectDetailsResponse
     #[serde(flatten)]
     pub project: Project,
     pub participants: Vec<String>,
+    pub pending_participants: Vec<String>,
     pub database: Option<DatabaseDetailsResponse>,
+    pub notes: Option<String>,
+    pub tls_enabled: bool,
+    pub public_url: String,
+    pub platform_env_vars: BTreeMap<String, String>,
+    pub env_var_visibility: BTreeMap<String, EnvVarVisibility>,
+    pub warnings: Vec<String>,
+    pub custom_domain_cname_target: String,
+    pub runtime: Option<ProjectRuntime>,
+    pub container_missing: bool,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ProjectRuntime
+{
+    pub status: Option<String>,
+    pub health: Option<String>,
+    #[serde(flatten)]
+    pub timing: ContainerTiming,
+    pub restart_count: Option<i64>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -67,6 +313,120 @@ pub struct GlobalMetrics
     pub running_containers: u64,
     pub total_cpu_usage: f64,
     pub total_memory_usage_mb: f64,
+    pub average_uptime_percent_30d: Option<f64>,
+    pub degraded_hosts: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct ProjectFilters
+{
+    pub owner: Option<String>,
+    pub name_contains: Option<String>,
+    pub source: Option<ProjectSourceType>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub created_after: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    pub created_before: Option<OffsetDateTime>,
+    pub archived: Option<bool>,
+    pub deleted: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Clone, sqlx::FromRow)]
+pub struct AdminProjectRow
+{
+    #[sqlx(flatten)]
+    #[serde(flatten)]
+    pub project: Project,
+    pub participant_count: i64,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ProjectRole
+{
+    Owner,
+    Participant,
+}
+
+impl From<bool> for ProjectRole
+{
+    fn from(is_owner: bool) -> Self
+    {
+        if is_owner { Self::Owner } else { Self::Participant }
+    }
+}
+
+#[derive(Debug, Serialize, Clone, sqlx::FromRow)]
+pub struct MyProjectRow
+{
+    #[sqlx(flatten)]
+    #[serde(flatten)]
+    pub project: Project,
+    // Lu depuis une colonne booléenne : le demandeur est-il le propriétaire ?
+    #[sqlx(try_from = "bool")]
+    pub role: ProjectRole,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct CachedGlobalMetrics
+{
+    #[serde(flatten)]
+    pub metrics: GlobalMetrics,
+
+    #[serde(with = "time::serde::rfc3339")]
+    pub computed_at: OffsetDateTime,
+}
+
+#[derive(Debug, Serialize, Clone, sqlx::FromRow)]
+pub struct ProjectNoteRevision
+{
+    pub id: i32,
+    pub project_id: i32,
+    pub previous_notes: Option<String>,
+    pub edited_by: String,
+
+    #[serde(with = "time::serde::rfc3339")]
+    pub edited_at: OffsetDateTime,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ReconciliationReport
+{
+    #[serde(with = "time::serde::rfc3339")]
+    pub started_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub finished_at: Option<OffsetDateTime>,
+    pub checked: usize,
+    pub started: Vec<String>,
+    pub lost: Vec<String>,
+    pub failed: Vec<String>,
+    pub network_drift: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DockerHostCapacity
+{
+    pub name: String,
+    pub total_containers: usize,
+    pub running_containers: usize,
+    pub images: usize,
+    pub volumes: usize,
+    pub layers_size_bytes: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct DockerPlatform
+{
+    pub os: String,
+    pub architecture: String,
+}
+
+impl std::fmt::Display for DockerPlatform
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    {
+        write!(f, "{}/{}", self.os, self.architecture)
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -74,6 +434,100 @@ pub struct DownProjectInfo
 {
     #[serde(flatten)]
     pub project: Project,
-    pub stopped_at: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub stopped_at: OffsetDateTime,
     pub downtime_seconds: i64,
-}
\ No newline at end of file
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum BroadcastOutcome
+{
+    Ok,
+    Failed,
+    Skipped,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct BroadcastResult
+{
+    pub project_id: i32,
+    pub project: String,
+    pub outcome: BroadcastOutcome,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ProbeFailure
+{
+    DnsFailure,
+    ConnectionRefused,
+    Timeout,
+    TlsError,
+    TooManyRedirects,
+    ConnectionFailed,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ProbeResult
+{
+    pub url: String,
+    pub final_url: Option<String>,
+    pub reachable: bool,
+    pub status_code: Option<u16>,
+    pub latency_ms: u64,
+    pub tls_valid: Option<bool>,
+    pub failure: Option<ProbeFailure>,
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct ContainerTiming
+{
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub started_at: Option<OffsetDateTime>,
+    pub uptime_seconds: Option<i64>,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub finished_at: Option<OffsetDateTime>,
+    pub downtime_seconds: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct StaleImageInfo
+{
+    #[serde(flatten)]
+    pub project: Project,
+    pub image_age_days: Option<i64>,
+    pub scan_age_days: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Clone, sqlx::FromRow)]
+pub struct ProjectExportRow
+{
+    pub name: String,
+    pub owner: String,
+    pub participants: Vec<String>,
+
+    #[sqlx(rename = "source_type")]
+    pub source: ProjectSourceType,
+
+    pub source_url: String,
+    pub deployed_image_tag: String,
+
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+
+    #[serde(with = "time::serde::rfc3339")]
+    pub updated_at: OffsetDateTime,
+
+    pub database_name: Option<String>,
+    pub has_volume: bool,
+    pub status: Option<ProjectStatus>,
+
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub archived_at: Option<OffsetDateTime>,
+
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub expires_at: Option<OffsetDateTime>,
+}
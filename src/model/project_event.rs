@@ -0,0 +1,110 @@
+use serde::Serialize;
+use time::OffsetDateTime;
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, sqlx::Type)]
+#[sqlx(type_name = "project_event_action", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ProjectEventAction
+{
+    Start,
+    Stop,
+    Restart,
+    Purge,
+    ImageUpdate,
+    Rebuild,
+    EnvUpdate,
+    NotesUpdate,
+    ParticipantInvite,
+    ParticipantRemove,
+    ReplicasUpdate,
+    RateLimitUpdate,
+    IpAllowlistUpdate,
+    HealthcheckUpdate,
+    RoutingUpdate,
+    EgressPolicyUpdate,
+    ConfigRestore,
+    LogsSettingsUpdate,
+    Rename,
+    MetadataUpdate,
+    DomainsUpdate,
+    Archive,
+    Unarchive,
+    Delete,
+    Restore,
+    ScheduleUpdate,
+    ScheduledStart,
+    ScheduledStop,
+    ReconciledStart,
+    ContainerLost,
+    Export,
+}
+
+impl ProjectEventAction
+{
+    pub fn as_str(self) -> &'static str
+    {
+        match self
+        {
+            Self::Start => "start",
+            Self::Stop => "stop",
+            Self::Restart => "restart",
+            Self::Purge => "purge",
+            Self::ImageUpdate => "image_update",
+            Self::Rebuild => "rebuild",
+            Self::EnvUpdate => "env_update",
+            Self::NotesUpdate => "notes_update",
+            Self::ParticipantInvite => "participant_invite",
+            Self::ParticipantRemove => "participant_remove",
+            Self::ReplicasUpdate => "replicas_update",
+            Self::RateLimitUpdate => "rate_limit_update",
+            Self::IpAllowlistUpdate => "ip_allowlist_update",
+            Self::HealthcheckUpdate => "healthcheck_update",
+            Self::RoutingUpdate => "routing_update",
+            Self::EgressPolicyUpdate => "egress_policy_update",
+            Self::ConfigRestore => "config_restore",
+            Self::LogsSettingsUpdate => "logs_settings_update",
+            Self::Rename => "rename",
+            Self::MetadataUpdate => "metadata_update",
+            Self::DomainsUpdate => "domains_update",
+            Self::Archive => "archive",
+            Self::Unarchive => "unarchive",
+            Self::Delete => "delete",
+            Self::Restore => "restore",
+            Self::ScheduleUpdate => "schedule_update",
+            Self::ScheduledStart => "scheduled_start",
+            Self::ScheduledStop => "scheduled_stop",
+            Self::ReconciledStart => "reconciled_start",
+            Self::ContainerLost => "container_lost",
+            Self::Export => "export",
+        }
+    }
+
+    pub fn changes_config(self) -> bool
+    {
+        matches!(
+            self,
+            Self::EnvUpdate
+                | Self::ReplicasUpdate
+                | Self::RateLimitUpdate
+                | Self::IpAllowlistUpdate
+                | Self::HealthcheckUpdate
+                | Self::RoutingUpdate
+                | Self::EgressPolicyUpdate
+                | Self::ConfigRestore
+        )
+    }
+}
+
+#[derive(Debug, Serialize, Clone, sqlx::FromRow)]
+pub struct ProjectEvent
+{
+    pub id: i64,
+    pub project_id: i32,
+    pub action: ProjectEventAction,
+    pub actor: String,
+    pub performed_by_admin: bool,
+    pub details: Option<serde_json::Value>,
+
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
@@ -0,0 +1,225 @@
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use time::OffsetDateTime;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, sqlx::Type)]
+#[sqlx(type_name = "scan_severity", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum Severity
+{
+    Unknown,
+    Negligible,
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl FromStr for Severity
+{
+    type Err = ();
+
+    fn from_str(value: &str) -> Result<Self, Self::Err>
+    {
+        match value.to_ascii_lowercase().as_str()
+        {
+            "unknown" => Ok(Severity::Unknown),
+            "negligible" => Ok(Severity::Negligible),
+            "low" => Ok(Severity::Low),
+            "medium" => Ok(Severity::Medium),
+            "high" => Ok(Severity::High),
+            "critical" => Ok(Severity::Critical),
+            _ => Err(()),
+        }
+    }
+}
+
+impl Severity
+{
+    pub fn as_str(&self) -> &'static str
+    {
+        match self
+        {
+            Severity::Unknown => "unknown",
+            Severity::Negligible => "negligible",
+            Severity::Low => "low",
+            Severity::Medium => "medium",
+            Severity::High => "high",
+            Severity::Critical => "critical",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct ScanFinding
+{
+    pub vulnerability_id: String,
+    pub severity: Severity,
+    pub package: String,
+    pub installed_version: String,
+    pub fixed_in: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct WaivedFinding
+{
+    #[serde(flatten)]
+    pub finding: ScanFinding,
+    pub waiver_id: i32,
+    pub justification: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub waiver_expires_at: OffsetDateTime,
+}
+
+// Du plus spécifique au plus général.
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ThresholdSource
+{
+    Deploy,
+    ProjectProfile,
+    OwnerOverride,
+    OwnerProfile,
+    Platform,
+}
+
+impl ThresholdSource
+{
+    pub fn describe(&self) -> &'static str
+    {
+        match self
+        {
+            ThresholdSource::Deploy => "as requested by this deploy",
+            ThresholdSource::ProjectProfile => "per this project's scan profile",
+            ThresholdSource::OwnerOverride => "per your account's scan policy",
+            ThresholdSource::OwnerProfile => "per your profile",
+            ThresholdSource::Platform => "per the platform default",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+pub struct ScanThreshold
+{
+    pub severity: Severity,
+    pub source: ThresholdSource,
+}
+
+#[derive(Debug, Serialize, Clone, sqlx::FromRow)]
+pub struct ScanProfile
+{
+    pub name: String,
+    pub fail_on_severity: Severity,
+    pub updated_by: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub updated_at: OffsetDateTime,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScanProfilePayload
+{
+    pub fail_on_severity: Severity,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProjectScanProfilePayload
+{
+    // `null` retire le profil du projet.
+    pub profile: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct ScanReport
+{
+    pub fail_on_severity: Severity,
+    pub threshold_source: ThresholdSource,
+    pub blocking: Vec<ScanFinding>,
+    pub waived: Vec<WaivedFinding>,
+}
+
+impl ScanReport
+{
+    pub fn is_blocking(&self) -> bool
+    {
+        !self.blocking.is_empty()
+    }
+
+    pub fn summary(&self) -> String
+    {
+        let blocking = self.blocking.iter().map(|finding| format!(
+            "{} ({:?}) in {} {}, fixed in {}",
+            finding.vulnerability_id, finding.severity, finding.package, finding.installed_version, finding.fixed_in.join(", ")
+        ));
+        let waived = self.waived.iter().map(|waived| format!(
+            "[waived] {} in {} {}: {}",
+            waived.finding.vulnerability_id, waived.finding.package, waived.finding.installed_version, waived.justification
+        ));
+        let outcome = if self.is_blocking() { "Failed" } else { "Passed" };
+        let verdict = format!("{} at threshold {} {}.", outcome, self.fail_on_severity.as_str(), self.threshold_source.describe());
+        std::iter::once(verdict).chain(blocking).chain(waived).collect::<Vec<_>>().join("\n")
+    }
+}
+
+#[derive(Debug, Serialize, Clone, sqlx::FromRow)]
+pub struct ScanResult
+{
+    pub image_ref: String,
+    pub passed: bool,
+    pub fail_on_severity: Severity,
+    pub blocking_count: i32,
+    pub waived_count: i32,
+    pub summary: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub scanned_at: OffsetDateTime,
+}
+
+#[derive(Debug, Serialize, Clone, sqlx::FromRow)]
+pub struct ScanWaiver
+{
+    pub id: i32,
+    pub cve_id: String,
+    pub package: Option<String>,
+    pub project_name: Option<String>,
+    pub justification: String,
+    pub created_by: String,
+
+    #[serde(with = "time::serde::rfc3339")]
+    pub expires_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub updated_at: OffsetDateTime,
+}
+
+impl ScanWaiver
+{
+    pub fn covers(&self, finding: &ScanFinding) -> bool
+    {
+        self.cve_id.eq_ignore_ascii_case(&finding.vulnerability_id)
+            && self.package.as_ref().is_none_or(|package| *package == finding.package)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScanWaiverPayload
+{
+    pub cve_id: String,
+    pub package: Option<String>,
+    pub project_name: Option<String>,
+    pub justification: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub expires_at: OffsetDateTime,
+}
+
+#[derive(Debug, Serialize, Clone, sqlx::FromRow)]
+pub struct SbomPackageMatch
+{
+    pub project_id: i32,
+    pub project_name: String,
+    pub owner: String,
+    pub image_digest: String,
+    pub packages: Vec<String>,
+}
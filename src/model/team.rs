@@ -0,0 +1,74 @@
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, sqlx::Type)]
+#[sqlx(type_name = "team_role", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum TeamRole
+{
+    Owner,
+    Member,
+}
+
+#[derive(Debug, Serialize, Clone, sqlx::FromRow)]
+pub struct Team
+{
+    pub id: i32,
+    pub name: String,
+    pub max_projects: Option<i32>,
+    pub created_by: String,
+
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+#[derive(Debug, Serialize, Clone, sqlx::FromRow)]
+pub struct TeamMembership
+{
+    pub team_id: i32,
+    pub team_name: String,
+    pub role: TeamRole,
+
+    #[serde(with = "time::serde::rfc3339")]
+    pub added_at: OffsetDateTime,
+}
+
+#[derive(Debug, Serialize, Clone, sqlx::FromRow)]
+pub struct TeamMember
+{
+    pub login: String,
+    pub role: TeamRole,
+    pub added_by: String,
+
+    #[serde(with = "time::serde::rfc3339")]
+    pub added_at: OffsetDateTime,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CreateTeamPayload
+{
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TeamMemberPayload
+{
+    pub login: String,
+    pub role: TeamRole,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TeamMemberRolePayload
+{
+    pub role: TeamRole,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TeamQuotaPayload
+{
+    pub max_projects: Option<i32>,
+}
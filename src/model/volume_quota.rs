@@ -0,0 +1,74 @@
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum VolumeQuotaPolicy
+{
+    Warn,
+    Enforce,
+}
+
+impl FromStr for VolumeQuotaPolicy
+{
+    type Err = ();
+
+    fn from_str(value: &str) -> Result<Self, Self::Err>
+    {
+        match value.to_ascii_lowercase().as_str()
+        {
+            "warn" => Ok(VolumeQuotaPolicy::Warn),
+            "enforce" => Ok(VolumeQuotaPolicy::Enforce),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, sqlx::Type)]
+#[sqlx(type_name = "volume_quota_action", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum VolumeQuotaAction
+{
+    Exceeded,
+    Stopped,
+    Cleared,
+    OverrideSet,
+    OverrideRemoved,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct VolumeUsage
+{
+    pub used_bytes: i64,
+    pub measured_at: OffsetDateTime,
+    pub over_quota_since: Option<OffsetDateTime>,
+    pub stopped_by_quota: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VolumeQuotaStatus
+{
+    pub volume_name: String,
+    pub used_bytes: Option<i64>,
+    pub quota_bytes: i64,
+    pub quota_overridden: bool,
+    pub policy: VolumeQuotaPolicy,
+    pub over_quota: bool,
+
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub measured_at: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub over_quota_since: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub enforced_at: Option<OffsetDateTime>,
+    pub stopped_by_quota: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct VolumeQuotaOverridePayload
+{
+    pub max_mb: Option<i64>,
+}
@@ -0,0 +1,29 @@
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, sqlx::Type)]
+#[sqlx(type_name = "webhook_delivery_outcome", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookDeliveryOutcome
+{
+    Accepted,
+    Duplicate,
+    InvalidSignature,
+    PayloadTooLarge,
+    Stale,
+    Malformed,
+}
+
+#[derive(Debug, Serialize, Clone, sqlx::FromRow)]
+pub struct WebhookDelivery
+{
+    pub id: i64,
+    pub delivery_id: Option<String>,
+    pub event: Option<String>,
+    pub outcome: WebhookDeliveryOutcome,
+    pub payload_size: i64,
+    pub ip_address: String,
+
+    #[serde(with = "time::serde::rfc3339")]
+    pub received_at: OffsetDateTime,
+}
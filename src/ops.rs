@@ -0,0 +1,345 @@
+use std::time::Duration;
+
+use time::OffsetDateTime;
+use tokio::time::{interval, sleep};
+use tracing::{error, info, warn};
+
+use crate::
+{
+    error::AppError,
+    model::cleanup::{CleanupAction, PendingCleanup},
+    services::{cleanup_service, database_service, docker_service},
+    state::AppState,
+};
+
+const CLEANUP_ATTEMPTS: i32 = 3;
+const CLEANUP_RETRY_DELAY: Duration = Duration::from_secs(2);
+const JANITOR_INTERVAL: Duration = Duration::from_secs(600);
+const JANITOR_MAX_RETRY_DELAY: Duration = Duration::from_secs(24 * 3600);
+
+// Journal de compensation d'une opération en plusieurs étapes. Sans `commit`, les actions sont rejouées
+// en ordre inverse, y compris quand le garde est abandonné par un `?` ou une requête annulée. Celles qui
+// échouent encore après CLEANUP_ATTEMPTS sont confiées au concierge.
+pub struct Rollback
+{
+    state: AppState,
+    operation: String,
+    actions: Vec<CleanupAction>,
+}
+
+impl Rollback
+{
+    pub fn new(state: &AppState, operation: impl Into<String>) -> Self
+    {
+        Self { state: state.clone(), operation: operation.into(), actions: Vec::new() }
+    }
+
+    pub fn push(&mut self, action: CleanupAction)
+    {
+        self.actions.push(action);
+    }
+
+    pub fn commit(mut self)
+    {
+        self.actions.clear();
+    }
+
+    pub async fn finish<T>(self, result: Result<T, AppError>) -> Result<T, AppError>
+    {
+        match result
+        {
+            Ok(value) =>
+            {
+                self.commit();
+                Ok(value)
+            }
+            Err(e) =>
+            {
+                self.run().await;
+                Err(e)
+            }
+        }
+    }
+
+    pub async fn run(mut self)
+    {
+        let actions = std::mem::take(&mut self.actions);
+        compensate(&self.state, &self.operation, actions).await;
+    }
+}
+
+impl Drop for Rollback
+{
+    fn drop(&mut self)
+    {
+        if self.actions.is_empty()
+        {
+            return;
+        }
+
+        let state = self.state.clone();
+        let operation = std::mem::take(&mut self.operation);
+        let actions = std::mem::take(&mut self.actions);
+
+        match tokio::runtime::Handle::try_current()
+        {
+            Ok(runtime) =>
+            {
+                runtime.spawn(async move { compensate(&state, &operation, actions).await });
+            }
+            Err(_) => error!("{} was abandoned outside of the runtime, {} cleanup action(s) were skipped.", operation, actions.len()),
+        }
+    }
+}
+
+async fn compensate(state: &AppState, operation: &str, actions: Vec<CleanupAction>)
+{
+    warn!("Rolling back {} ({} step(s)).", operation, actions.len());
+
+    let failed = undo_in_reverse(operation, actions, CLEANUP_RETRY_DELAY, |action|
+    {
+        let action = action.clone();
+        async move { execute(state, &action).await }
+    }).await;
+
+    for (action, attempts, e) in failed
+    {
+        error!("Cleanup '{}' of {} failed, handing it over to the janitor: {}", action, operation, e);
+        if let Err(persist_error) = cleanup_service::insert_pending_cleanup(&state.db_pool, operation, &action, attempts, &e.to_string()).await
+        {
+            error!("Cleanup '{}' of {} is lost and needs manual action: {}", action, operation, persist_error);
+        }
+    }
+}
+
+// Un échec n'arrête pas les actions suivantes ; celles qui échouent encore sont rendues avec leur nombre d'essais.
+async fn undo_in_reverse<F, Fut>(
+    operation: &str,
+    actions: Vec<CleanupAction>,
+    retry_delay: Duration,
+    execute: F,
+) -> Vec<(CleanupAction, i32, AppError)>
+where
+    F: Fn(&CleanupAction) -> Fut,
+    Fut: Future<Output = Result<(), AppError>>,
+{
+    let mut failed = Vec::new();
+
+    for action in actions.into_iter().rev()
+    {
+        let mut attempt = 1;
+        let result = loop
+        {
+            match execute(&action).await
+            {
+                Ok(()) => break Ok(()),
+                Err(e) if attempt >= CLEANUP_ATTEMPTS => break Err(e),
+                Err(e) =>
+                {
+                    warn!("Cleanup '{}' of {} failed (attempt {}/{}): {}", action, operation, attempt, CLEANUP_ATTEMPTS, e);
+                    attempt += 1;
+                    sleep(retry_delay).await;
+                }
+            }
+        };
+
+        if let Err(e) = result
+        {
+            failed.push((action, attempt, e));
+        }
+    }
+
+    failed
+}
+
+async fn execute(state: &AppState, action: &CleanupAction) -> Result<(), AppError>
+{
+    match action
+    {
+        CleanupAction::RemoveContainer { host, container } => docker_service::remove_container(state.docker_for(host)?, container).await,
+        CleanupAction::RemoveImage { host, image } => docker_service::remove_image(state.docker_for(host)?, image).await,
+        CleanupAction::RemoveVolume { host, volume } => docker_service::remove_volume_by_name(state.docker_for(host)?, volume).await,
+        CleanupAction::DeprovisionDatabase { database, username } =>
+            database_service::execute_mariadb_deprovisioning(&state.mariadb_pool, database, username).await,
+    }
+}
+
+// L'image est souvent encore utilisée par un autre projet : l'échec est confié au concierge.
+pub async fn remove_image_or_defer(state: &AppState, host: &str, image: &str, operation: &str)
+{
+    let action = CleanupAction::RemoveImage { host: host.to_string(), image: image.to_string() };
+
+    if let Err(e) = execute(state, &action).await
+    {
+        warn!("Could not {} after {}, deferring it to the janitor: {}", action, operation, e);
+        if let Err(persist_error) = cleanup_service::insert_pending_cleanup(&state.db_pool, operation, &action, 1, &e.to_string()).await
+        {
+            error!("Cleanup '{}' of {} is lost and needs manual action: {}", action, operation, persist_error);
+        }
+    }
+}
+
+pub async fn run_pending_cleanup_janitor(state: AppState)
+{
+    let mut ticker = interval(JANITOR_INTERVAL);
+    loop
+    {
+        ticker.tick().await;
+
+        let pending = match cleanup_service::get_retryable_cleanups(&state.db_pool).await
+        {
+            Ok(pending) => pending,
+            Err(e) =>
+            {
+                error!("Failed to list pending cleanups: {}", e);
+                continue;
+            }
+        };
+
+        let now = OffsetDateTime::now_utc();
+        for cleanup in pending
+        {
+            if !retry_is_due(&cleanup, now)
+            {
+                continue;
+            }
+
+            let result = match serde_json::from_value::<CleanupAction>(cleanup.action)
+            {
+                Ok(action) => execute(&state, &action).await.map(|()| action),
+                Err(e) =>
+                {
+                    error!("Pending cleanup {} holds an unreadable action: {}", cleanup.id, e);
+                    continue;
+                }
+            };
+
+            match result
+            {
+                Ok(action) =>
+                {
+                    info!("Pending cleanup '{}' of {} succeeded after {} attempt(s).", action, cleanup.operation, cleanup.attempts + 1);
+                    let _ = cleanup_service::delete_pending_cleanup(&state.db_pool, cleanup.id).await;
+                }
+                Err(e) =>
+                {
+                    let _ = cleanup_service::record_cleanup_failure(&state.db_pool, cleanup.id, &e.to_string()).await;
+
+                    if now - cleanup.created_at >= time::Duration::days(state.config().cleanup_give_up_days)
+                    {
+                        error!(
+                            "Giving up pending cleanup {} of {} after {} attempt(s), it needs manual action: {}",
+                            cleanup.id, cleanup.operation, cleanup.attempts + 1, e
+                        );
+                        let _ = cleanup_service::mark_cleanup_abandoned(&state.db_pool, cleanup.id).await;
+                    }
+                }
+            }
+        }
+    }
+}
+
+// L'attente double après chaque échec, d'un intervalle du concierge jusqu'à un jour.
+fn retry_is_due(cleanup: &PendingCleanup, now: OffsetDateTime) -> bool
+{
+    let doublings = u32::try_from(cleanup.attempts.saturating_sub(1)).unwrap_or(0).min(16);
+    let delay = JANITOR_INTERVAL.saturating_mul(1 << doublings).min(JANITOR_MAX_RETRY_DELAY);
+
+    // Le ticker n'est pas exact : une marge d'une minute évite de sauter un tour entier.
+    now - cleanup.last_attempt_at + time::Duration::minutes(1) >= delay
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use std::sync::Mutex;
+
+    fn container(name: &str) -> CleanupAction
+    {
+        CleanupAction::RemoveContainer { host: "local".to_string(), container: name.to_string() }
+    }
+
+    #[tokio::test]
+    async fn actions_are_undone_in_reverse_order()
+    {
+        let executed = Mutex::new(Vec::new());
+        let actions = vec![container("first"), container("second"), container("third")];
+
+        let failed = undo_in_reverse("test", actions, Duration::ZERO, |action|
+        {
+            executed.lock().unwrap().push(action.clone());
+            async { Ok(()) }
+        }).await;
+
+        assert!(failed.is_empty());
+        assert_eq!(*executed.lock().unwrap(), [container("third"), container("second"), container("first")]);
+    }
+
+    #[tokio::test]
+    async fn failing_action_is_retried_then_handed_over_without_blocking_the_others()
+    {
+        let attempts = Mutex::new(Vec::new());
+        let actions = vec![container("removed"), container("stuck"), container("flaky")];
+
+        let failed = undo_in_reverse("test", actions, Duration::ZERO, |action|
+        {
+            let mut attempts = attempts.lock().unwrap();
+            attempts.push(action.clone());
+            let flaky_attempts = attempts.iter().filter(|attempt| **attempt == container("flaky")).count();
+            let result = match action
+            {
+                CleanupAction::RemoveContainer { container, .. } if container == "stuck" => Err(AppError::InternalServerError),
+                CleanupAction::RemoveContainer { container, .. } if container == "flaky" && flaky_attempts < 2 => Err(AppError::InternalServerError),
+                _ => Ok(()),
+            };
+            async move { result }
+        }).await;
+
+        let failed: Vec<_> = failed.into_iter().map(|(action, attempts, _)| (action, attempts)).collect();
+        assert_eq!(failed, [(container("stuck"), CLEANUP_ATTEMPTS)]);
+        assert_eq!(attempts.lock().unwrap().last(), Some(&container("removed")));
+    }
+
+    #[test]
+    fn janitor_backs_off_after_each_failure()
+    {
+        let now = OffsetDateTime::from_unix_timestamp(1_700_000_000).unwrap();
+        let cleanup = |attempts, minutes_ago| PendingCleanup
+        {
+            id: 1,
+            operation: "test".to_string(),
+            action: serde_json::to_value(container("stuck")).unwrap(),
+            attempts,
+            last_error: String::new(),
+            created_at: now - time::Duration::days(1),
+            last_attempt_at: now - time::Duration::minutes(minutes_ago),
+            abandoned_at: None,
+        };
+
+        // Un intervalle après le premier échec, puis 20 et 40 minutes.
+        assert!(retry_is_due(&cleanup(1, 10), now));
+        assert!(!retry_is_due(&cleanup(2, 15), now));
+        assert!(!retry_is_due(&cleanup(3, 30), now));
+        assert!(retry_is_due(&cleanup(3, 40), now));
+        assert!(!retry_is_due(&cleanup(30, 23 * 60), now));
+        assert!(retry_is_due(&cleanup(30, 24 * 60), now));
+    }
+
+    #[test]
+    fn stored_actions_round_trip()
+    {
+        let actions = [
+            container("web"),
+            CleanupAction::RemoveImage { host: "local".to_string(), image: "hangar/app:1".to_string() },
+            CleanupAction::RemoveVolume { host: "local".to_string(), volume: "data".to_string() },
+            CleanupAction::DeprovisionDatabase { database: "app".to_string(), username: "app".to_string() },
+        ];
+
+        for action in actions
+        {
+            let stored = serde_json::to_value(&action).unwrap();
+            assert_eq!(serde_json::from_value::<CleanupAction>(stored).unwrap(), action);
+        }
+    }
+}
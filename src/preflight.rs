@@ -0,0 +1,141 @@
+use std::{future::Future, path::PathBuf, time::Duration};
+
+use bollard::{errors::Error as BollardError, query_parameters::InspectNetworkOptions, Docker};
+use rustix::fs::Access;
+use sqlx::{MySqlPool, PgPool};
+use tracing::{error, info};
+
+use crate::{services::docker_service::GRYPE_BINARY, state::AppState};
+
+pub const CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+async fn within_timeout<T, E: std::fmt::Display>(check: impl Future<Output = Result<T, E>>, context: &str) -> Result<T, String>
+{
+    match tokio::time::timeout(CHECK_TIMEOUT, check).await
+    {
+        Ok(Ok(value)) => Ok(value),
+        Ok(Err(e)) => Err(format!("{}: {}", context, e)),
+        Err(_) => Err(format!("Connection timeout ({}s)", CHECK_TIMEOUT.as_secs())),
+    }
+}
+
+pub async fn check_postgres(pool: &PgPool) -> Result<(), String>
+{
+    within_timeout(sqlx::query("SELECT 1 as health_check").fetch_one(pool), "Database error").await.map(drop)
+}
+
+pub async fn check_mariadb(pool: &MySqlPool) -> Result<(), String>
+{
+    within_timeout(sqlx::query("SELECT 1 as health_check").fetch_one(pool), "Database error").await.map(drop)
+}
+
+pub async fn check_docker(docker: &Docker) -> Result<(), String>
+{
+    within_timeout(docker.ping(), "Docker daemon error").await.map(drop)
+}
+
+// Hangar ne crée jamais ce réseau.
+pub async fn check_docker_network(docker: &Docker, network: &str) -> Result<(), String>
+{
+    match tokio::time::timeout(CHECK_TIMEOUT, docker.inspect_network(network, None::<InspectNetworkOptions>)).await
+    {
+        Ok(Ok(_)) => Ok(()),
+        Ok(Err(BollardError::DockerResponseServerError { status_code: 404, .. })) => Err(format!("Network '{}' does not exist", network)),
+        Ok(Err(e)) => Err(format!("Docker daemon error: {}", e)),
+        Err(_) => Err(format!("Connection timeout ({}s)", CHECK_TIMEOUT.as_secs())),
+    }
+}
+
+pub fn check_grype() -> Result<PathBuf, String>
+{
+    let path = std::env::var_os("PATH").unwrap_or_default();
+    let candidates: Vec<PathBuf> = std::env::split_paths(&path)
+        .map(|dir| dir.join(GRYPE_BINARY))
+        .filter(|candidate| candidate.is_file())
+        .collect();
+
+    if candidates.is_empty()
+    {
+        return Err(format!("'{}' was not found in PATH", GRYPE_BINARY));
+    }
+
+    candidates.iter()
+        .find(|candidate| rustix::fs::access(candidate.as_path(), Access::EXEC_OK).is_ok())
+        .cloned()
+        .ok_or_else(|| format!("'{}' is not executable by the server's user", candidates[0].display()))
+}
+
+pub async fn run(state: &AppState) -> usize
+{
+    let mut failures = 0;
+
+    match check_postgres(&state.db_pool).await
+    {
+        Ok(()) => info!("✅ PostgreSQL answers queries."),
+        Err(e) =>
+        {
+            error!("❌ PostgreSQL does not answer queries ({}). Check DATABASE_URL and that the server accepts connections from this host.", e);
+            failures += 1;
+        }
+    }
+
+    match check_mariadb(&state.mariadb_pool).await
+    {
+        Ok(()) => info!("✅ MariaDB answers queries."),
+        Err(e) =>
+        {
+            error!("❌ MariaDB does not answer queries ({}). Check MARIADB_URL and that the server accepts connections from this host.", e);
+            failures += 1;
+        }
+    }
+
+    for host in &state.config().docker_hosts
+    {
+        let Some(docker) = state.docker_hosts.get(&host.name)
+        else
+        {
+            continue;
+        };
+
+        if let Err(e) = check_docker(docker).await
+        {
+            let tls_hint = host.tls_cert_dir.as_ref()
+                .map(|dir| format!(" and that it trusts the client certificate in '{}'", dir.display()))
+                .unwrap_or_default();
+            error!(
+                "❌ Docker host '{}' does not answer pings ({}). Check that the daemon runs, that {} is reachable by the server's user{}.",
+                host.name, e, host.url.as_deref().unwrap_or("the local Docker socket"), tls_hint
+            );
+            failures += 1;
+            continue;
+        }
+
+        match check_docker_network(docker, &state.config().docker_network).await
+        {
+            Ok(()) => info!("✅ Docker host '{}' answers and has the network '{}'.", host.name, state.config().docker_network),
+            Err(e) =>
+            {
+                error!(
+                    "❌ Docker host '{}' cannot provide DOCKER_NETWORK ({}). Create it with `docker network create {}` and attach Traefik to it.",
+                    host.name, e, state.config().docker_network
+                );
+                failures += 1;
+            }
+        }
+    }
+
+    if state.config().grype_enabled
+    {
+        match check_grype()
+        {
+            Ok(path) => info!("✅ Grype found at '{}'.", path.display()),
+            Err(e) =>
+            {
+                error!("❌ Grype cannot run ({}). Install it in the server's PATH or set GRYPE_ENABLED=false to deploy without scanning.", e);
+                failures += 1;
+            }
+        }
+    }
+
+    failures
+}
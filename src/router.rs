@@ -1,75 +1,399 @@
-use crate::{handlers, state::AppState, middleware};
-use axum::{error_handling::HandleErrorLayer, http::StatusCode, middleware as axum_middleware, routing::{delete, get, post, put}, BoxError, Router};
-use tower::{timeout::TimeoutLayer, ServiceBuilder};
-use tower_http::{compression::CompressionLayer, cors::CorsLayer, trace::TraceLayer};
-use std::time::Duration;
+use crate::{config::{Config, RouteClass}, handlers, services::jwt::StreamKind, state::AppState, middleware};
+use axum::{body::Body, extract::{Request, State}, http::{header, HeaderValue, Method, StatusCode}, middleware::{self as axum_middleware, Next}, response::{IntoResponse, Response}, routing::{delete, get, patch, post, put}, Router};
+use std::{sync::Arc, time::Duration};
+use tower::ServiceBuilder;
+use tower_http::{compression::CompressionLayer, cors::{AllowOrigin, CorsLayer}, timeout::TimeoutBody, trace::TraceLayer};
 
-pub fn create_router(state: AppState) -> Router 
+// Relu à chaque requête, pour qu'un rechargement de la configuration s'applique sans redémarrer.
+type TimeoutSource = Arc<dyn Fn() -> Duration + Send + Sync>;
+
+// Les autres origines ne reçoivent aucun en-tête CORS.
+fn cors_layer(config: &Config) -> CorsLayer
+{
+    // En développement, toute origine est acceptée mais sans cookies : seul un jeton Bearer authentifie.
+    let (allow_origin, allow_credentials) = if config.cors_dev_allow_any_origin
+    {
+        (AllowOrigin::any(), false)
+    }
+    else
+    {
+        (AllowOrigin::list(config.cors_allowed_origins.iter().filter_map(|origin| HeaderValue::from_str(origin).ok())), true)
+    };
+
+    CorsLayer::new()
+        .allow_origin(allow_origin)
+        .allow_credentials(allow_credentials)
+        .allow_methods([Method::GET, Method::POST, Method::PUT, Method::PATCH, Method::DELETE])
+        .allow_headers([header::AUTHORIZATION, header::CONTENT_TYPE, header::ACCEPT_LANGUAGE, header::IF_NONE_MATCH])
+        .expose_headers([header::ETAG, header::CONTENT_DISPOSITION, header::CONTENT_LANGUAGE])
+        .max_age(Duration::from_secs(600))
+}
+
+fn classify<S: Clone + Send + Sync + 'static>(routes: Router<S>, class: RouteClass, timeout: TimeoutSource, cors: CorsLayer) -> Router<S>
 {
     let common_layer = ServiceBuilder::new()
                 .layer(TraceLayer::new_for_http())
-                .layer(CorsLayer::permissive())
-                .layer(CompressionLayer::new())
-                .layer(HandleErrorLayer::new(|_: BoxError| async {StatusCode::REQUEST_TIMEOUT}))
-                .layer(TimeoutLayer::new(Duration::from_secs(state.config.timeout_normal)));
+                .layer(cors)
+                .layer(CompressionLayer::new());
 
-    let long_running_layer = ServiceBuilder::new()
-                .layer(TraceLayer::new_for_http())
-                .layer(CorsLayer::permissive())
-                .layer(CompressionLayer::new())
-                .layer(HandleErrorLayer::new(|_: BoxError| async {StatusCode::REQUEST_TIMEOUT}))
-                .layer(TimeoutLayer::new(Duration::from_secs(state.config.timeout_long)));
-    
+    match class
+    {
+        RouteClass::Short | RouteClass::Long => routes.route_layer(
+            common_layer.layer(axum_middleware::from_fn_with_state(timeout, request_deadline))
+        ),
+        RouteClass::Streaming => routes.route_layer(
+            common_layer.layer(axum_middleware::from_fn_with_state(timeout, body_idle_timeout))
+        ),
+    }
+}
+
+async fn request_deadline(State(timeout): State<TimeoutSource>, request: Request, next: Next) -> Response
+{
+    tokio::time::timeout(timeout(), next.run(request))
+        .await
+        .unwrap_or_else(|_| StatusCode::REQUEST_TIMEOUT.into_response())
+}
+
+// Les corps enveloppés par le délai sont reconvertis en `Body`, le seul type qu'accepte le routeur.
+async fn body_idle_timeout(State(timeout): State<TimeoutSource>, request: Request, next: Next) -> Response
+{
+    let timeout = timeout();
+    let request = request.map(|body| Body::new(TimeoutBody::new(timeout, body)));
+    next.run(request).await.map(|body| Body::new(TimeoutBody::new(timeout, body)))
+}
+
+fn timeout_source(state: &AppState, class: RouteClass) -> TimeoutSource
+{
+    let state = state.clone();
+    Arc::new(move || state.config().timeout_for(class))
+}
+
+pub fn create_router(state: AppState) -> Router 
+{
     let admin_routes = Router::new()
         .route("/api/admin/projects", get(handlers::admin_handler::list_all_projects_handler))
         .route("/api/admin/metrics", get(handlers::admin_handler::get_global_metrics_handler))
         .route("/api/admin/projects/down", get(handlers::admin_handler::get_down_projects_handler))
+        .route("/api/admin/stale-images", get(handlers::admin_handler::get_stale_images_handler))
+        .route("/api/admin/docker-hosts", get(handlers::admin_handler::list_docker_hosts_handler))
+        .route("/api/admin/config", get(handlers::admin_handler::get_config_summary_handler))
+        .route("/api/admin/config/reload", post(handlers::admin_handler::reload_config_handler))
+        .route("/api/admin/audit", get(handlers::admin_handler::list_audit_log_handler))
+        .route("/api/admin/pools", get(handlers::admin_handler::get_pools_handler))
+        .route("/api/admin/crypto", get(handlers::admin_handler::get_crypto_status_handler))
+        .route("/api/admin/deploy-stats", get(handlers::admin_handler::get_deploy_stats_handler))
+        .route("/api/admin/projects/{project_id}/inspect", get(handlers::admin_handler::inspect_project_handler))
+        .route("/api/admin/reconciliation", get(handlers::admin_handler::get_reconciliation_report_handler))
+        .route("/api/admin/auth-attempts", get(handlers::admin_handler::list_auth_attempts_handler))
+        .route("/api/admin/webhook-deliveries", get(handlers::admin_handler::list_webhook_deliveries_handler))
+        .route("/api/admin/pending-cleanups", get(handlers::admin_handler::list_pending_cleanups_handler))
+        .route("/api/admin/sboms/search", get(handlers::admin_handler::search_sboms_handler))
+        .route("/api/admin/scan-waivers", get(handlers::scan_waiver_handler::list_scan_waivers_handler).post(handlers::scan_waiver_handler::create_scan_waiver_handler))
+        .route("/api/admin/scan-waivers/{waiver_id}", put(handlers::scan_waiver_handler::update_scan_waiver_handler).delete(handlers::scan_waiver_handler::delete_scan_waiver_handler))
+        .route("/api/admin/users/{login}/quotas", put(handlers::quota_handler::set_user_quotas_handler))
+        .route("/api/admin/scan-profiles", get(handlers::quota_handler::list_scan_profiles_handler))
+        .route("/api/admin/scan-profiles/{name}", put(handlers::quota_handler::set_scan_profile_handler).delete(handlers::quota_handler::delete_scan_profile_handler))
+        .route("/api/admin/projects/{project_id}/scan-profile", put(handlers::quota_handler::set_project_scan_profile_handler))
+        .route("/api/admin/projects/{project_id}/volume-quota", put(handlers::quota_handler::set_project_volume_quota_handler))
+        .route("/api/admin/db-templates", post(handlers::db_template_handler::create_db_template_handler))
+        .route("/api/admin/db-templates/{name}", delete(handlers::db_template_handler::delete_db_template_handler))
+        .route("/api/admin/databases/{db_id}/limits", patch(handlers::database_handler::update_database_limits_handler))
+        .route("/api/admin/teams/{team_id}/quotas", put(handlers::team_handler::set_team_quota_handler))
+        .route_layer(axum_middleware::from_fn(middleware::admin_auth))
+        .route_layer(axum_middleware::from_fn_with_state(state.clone(), middleware::auth));
+
+    let admin_long_running_routes = Router::new()
+        .route("/api/admin/containers/stop-all", post(handlers::admin_handler::stop_all_containers_handler))
+        .route("/api/admin/containers/start-all", post(handlers::admin_handler::start_all_containers_handler))
+        .route("/api/admin/crypto/reencrypt", post(handlers::admin_handler::reencrypt_secrets_handler))
+        .route("/api/admin/projects/{project_id}/egress-policy", put(handlers::project_handler::set_egress_policy_handler))
+        .route("/api/projects/{project_id}/config-history/{revision}/restore", post(handlers::project_handler::restore_config_revision_handler))
         .route_layer(axum_middleware::from_fn(middleware::admin_auth))
-        .route_layer(axum_middleware::from_fn_with_state(state.clone(), middleware::auth))
-        .route_layer(common_layer.clone());
+        .route_layer(axum_middleware::from_fn_with_state(state.clone(), middleware::auth));
+
+    let admin_streaming_routes = Router::new()
+        .route("/api/admin/projects/export", get(handlers::admin_handler::export_projects_handler))
+        .route_layer(axum_middleware::from_fn(middleware::admin_auth))
+        .route_layer(axum_middleware::from_fn_with_state(state.clone(), middleware::auth));
 
     let public_routes = Router::new()
         .route("/api/health", get(handlers::health::health_check_handler))
+        .route("/api/meta/error-codes", get(handlers::meta_handler::get_error_catalog_handler))
+        .route("/api/meta/version", get(handlers::meta_handler::get_version_handler))
         .route("/api/auth/callback", get(handlers::auth_handler::auth_callback_handler))
-        .route_layer(common_layer.clone());
+        .route("/api/webhooks/github", post(handlers::webhook_handler::github_webhook_handler));
 
     let protected_routes = Router::new()
         .route("/api/auth/me", get(handlers::auth_handler::get_current_user_handler))
         .route("/api/auth/logout", get(handlers::auth_handler::logout_handler))
+        .route("/api/streams/ticket", post(handlers::project_handler::issue_stream_ticket_handler))
         .route("/api/projects/owned", get(handlers::project_handler::list_owned_projects_handler))
+        .route("/api/projects/search", get(handlers::project_handler::search_projects_handler))
         .route("/api/projects/participations", get(handlers::project_handler::list_participating_projects_handler))
-        .route("/api/projects/{project_id}", get(handlers::project_handler::get_project_details_handler))
-        .route("/api/projects/{project_id}/status", get(handlers::project_handler::get_project_status_handler))
+        .route("/api/projects/mine", get(handlers::project_handler::list_my_projects_handler))
+        .route("/api/projects/status", post(handlers::project_handler::get_projects_status_batch_handler))
+        .route("/api/deployments", get(handlers::project_handler::list_my_deployments_handler))
+        .route("/api/deployments/events", get(handlers::project_handler::list_my_deployment_events_handler))
+        .route("/api/deployments/{deployment_id}", get(handlers::project_handler::get_deployment_handler))
+        .route("/api/projects/{project_id}", get(handlers::project_handler::get_project_details_handler).patch(handlers::project_handler::update_project_metadata_handler))
         .route("/api/projects/{project_id}/start", post(handlers::project_handler::start_project_handler))
         .route("/api/projects/{project_id}/stop", post(handlers::project_handler::stop_project_handler))
         .route("/api/projects/{project_id}/restart", post(handlers::project_handler::restart_project_handler))
-        .route("/api/projects/{project_id}/logs", get(handlers::project_handler::get_project_logs_handler))
+        .route("/api/projects/{project_id}/archive", post(handlers::project_handler::archive_project_handler))
+        .route("/api/projects/{project_id}/unarchive", post(handlers::project_handler::unarchive_project_handler))
+        .route("/api/projects/{project_id}/restore", post(handlers::project_handler::restore_project_handler))
         .route("/api/projects/{project_id}/metrics", get(handlers::project_handler::get_project_metrics_handler))
+        .route("/api/projects/{project_id}/volume-quota", get(handlers::project_handler::get_volume_quota_handler))
+        .route("/api/projects/{project_id}/notes", put(handlers::project_handler::update_notes_handler))
+        .route("/api/projects/{project_id}/notes/revisions", get(handlers::project_handler::list_note_revisions_handler))
+        .route("/api/projects/{project_id}/logs/settings", put(handlers::project_handler::update_logs_settings_handler))
+        .route("/api/projects/{project_id}/events", get(handlers::project_handler::get_project_events_handler))
+        .route("/api/projects/{project_id}/audit", get(handlers::project_handler::get_project_audit_handler))
+        .route("/api/projects/{project_id}/config-history", get(handlers::project_handler::get_config_history_handler))
+        .route("/api/projects/{project_id}/config-export", get(handlers::project_handler::export_project_config_handler))
+        .route("/api/projects/{project_id}/sbom", get(handlers::project_handler::get_project_sbom_handler))
+        .route("/api/projects/{project_id}/env", get(handlers::project_handler::get_env_vars_handler))
+        .route("/api/projects/{project_id}/env/diff", post(handlers::project_handler::diff_env_vars_handler))
         .route("/api/projects/{project_id}/participants", post(handlers::project_handler::add_participant_handler))
         .route("/api/projects/{project_id}/participants/{participant_id}", delete(handlers::project_handler::remove_participant_handler))
+        .route("/api/quotas/me", get(handlers::quota_handler::get_my_quotas_handler))
+        .route("/api/me/resources", get(handlers::account_handler::get_my_resources_handler))
+        .route("/api/teams", get(handlers::team_handler::list_my_teams_handler).post(handlers::team_handler::create_team_handler))
+        .route("/api/teams/{team_id}", get(handlers::team_handler::get_team_handler).delete(handlers::team_handler::delete_team_handler))
+        .route("/api/teams/{team_id}/projects", get(handlers::team_handler::list_team_projects_handler))
+        .route("/api/teams/{team_id}/members", post(handlers::team_handler::add_team_member_handler))
+        .route("/api/teams/{team_id}/members/{login}", put(handlers::team_handler::update_team_member_handler).delete(handlers::team_handler::remove_team_member_handler))
+        .route("/api/invitations", get(handlers::invitation_handler::list_invitations_handler))
+        .route("/api/invitations/{invitation_id}/accept", post(handlers::invitation_handler::accept_invitation_handler))
+        .route("/api/invitations/{invitation_id}/decline", post(handlers::invitation_handler::decline_invitation_handler))
         .route("/api/databases/mine", get(handlers::database_handler::get_my_database_handler))
-        .route("/api/databases", post(handlers::database_handler::create_database_handler))
+        .route("/api/db-templates", get(handlers::db_template_handler::list_db_templates_handler))
         .route("/api/databases/{db_id}", delete(handlers::database_handler::delete_my_database_handler))
+        .route("/api/databases/{db_id}/reveal", post(handlers::database_handler::reveal_database_password_handler))
         .route("/api/projects/{project_id}/database/{db_id}", put(handlers::database_handler::link_database_handler))
         .route("/api/projects/{project_id}/database", delete(handlers::database_handler::unlink_database_handler))
         .route("/api/projects/{project_id}/database/delete", delete(handlers::database_handler::delete_linked_database_handler))
-        .route_layer(axum_middleware::from_fn_with_state(state.clone(), middleware::auth))
-        .route_layer(common_layer.clone());
+        .route_layer(axum_middleware::from_fn_with_state(state.clone(), middleware::auth));
 
     let long_running_protected_routes = Router::new()
+        .route("/api/databases", post(handlers::database_handler::create_database_handler))
+        .route("/api/me/cleanup", post(handlers::account_handler::cleanup_my_resources_handler))
         .route("/api/projects/deploy", post(handlers::project_handler::deploy_project_handler))
+        .route("/api/projects/validate-image", post(handlers::project_handler::validate_image_handler))
         .route("/api/projects/{project_id}", delete(handlers::project_handler::purge_project_handler))
         .route("/api/projects/{project_id}/image", put(handlers::project_handler::update_project_image_handler))
-        .route("/api/projects/{project_id}/env", put(handlers::project_handler::update_env_vars_handler))
+        .route("/api/projects/{project_id}/image/check-update", post(handlers::project_handler::check_image_update_handler))
+        .route("/api/projects/{project_id}/env", put(handlers::project_handler::update_env_vars_handler).patch(handlers::project_handler::patch_env_vars_handler))
         .route("/api/projects/{project_id}/rebuild", put(handlers::project_handler::rebuild_project_handler))
-        .route_layer(axum_middleware::from_fn_with_state(state.clone(), middleware::auth))
-        .route_layer(long_running_layer);
+        .route("/api/projects/{project_id}/duplicate", post(handlers::project_handler::duplicate_project_handler))
+        .route("/api/projects/{project_id}/env/import", post(handlers::project_handler::import_env_file_handler))
+        .route("/api/projects/import", post(handlers::project_handler::import_project_config_handler))
+        .route("/api/projects/{project_id}/replicas", put(handlers::project_handler::set_replicas_handler))
+        .route("/api/projects/{project_id}/rate-limit", put(handlers::project_handler::set_rate_limit_handler).delete(handlers::project_handler::clear_rate_limit_handler))
+        .route("/api/projects/{project_id}/ip-allowlist", put(handlers::project_handler::set_ip_allowlist_handler).delete(handlers::project_handler::clear_ip_allowlist_handler))
+        .route("/api/projects/{project_id}/routing", put(handlers::project_handler::set_routing_options_handler))
+        .route("/api/projects/{project_id}/rename", post(handlers::project_handler::rename_project_handler))
+        .route("/api/projects/{project_id}/domains", put(handlers::project_handler::update_custom_domains_handler))
+        .route("/api/projects/{project_id}/traefik-healthcheck", put(handlers::project_handler::set_traefik_healthcheck_handler).delete(handlers::project_handler::clear_traefik_healthcheck_handler))
+        .route_layer(axum_middleware::from_fn_with_state(state.clone(), middleware::auth));
+
+    // Ouvertes par le navigateur sans en-têtes : un ticket à usage unique remplace le cookie.
+    let status_stream_routes = Router::new()
+        .route("/api/projects/{project_id}/status", get(handlers::project_handler::get_project_status_handler))
+        .route("/api/projects/{project_id}/uptime", get(handlers::project_handler::get_project_uptime_handler))
+        .route("/api/projects/{project_id}/probe", get(handlers::project_handler::probe_project_handler))
+        .route_layer(axum_middleware::from_fn_with_state((state.clone(), StreamKind::Status), middleware::stream_auth));
+
+    let logs_stream_routes = Router::new()
+        .route("/api/projects/{project_id}/logs", get(handlers::project_handler::get_project_logs_handler))
+        .route_layer(axum_middleware::from_fn_with_state((state.clone(), StreamKind::Logs), middleware::stream_auth));
+
+    let export_stream_routes = Router::new()
+        .route("/api/projects/{project_id}/export", get(handlers::project_handler::export_project_archive_handler))
+        .route_layer(axum_middleware::from_fn_with_state((state.clone(), StreamKind::Export), middleware::stream_auth));
+
+    let config = &state.config();
 
     Router::new()
-        .merge(public_routes)
-        .merge(protected_routes)
-        .merge(admin_routes)
-        .merge(long_running_protected_routes)
+        .merge(classify(public_routes, RouteClass::Short, timeout_source(&state, RouteClass::Short), cors_layer(config)))
+        .merge(classify(protected_routes, RouteClass::Short, timeout_source(&state, RouteClass::Short), cors_layer(config)))
+        .merge(classify(admin_routes, RouteClass::Short, timeout_source(&state, RouteClass::Short), cors_layer(config)))
+        .merge(classify(admin_long_running_routes, RouteClass::Long, timeout_source(&state, RouteClass::Long), cors_layer(config)))
+        .merge(classify(admin_streaming_routes, RouteClass::Streaming, timeout_source(&state, RouteClass::Streaming), cors_layer(config)))
+        .merge(classify(long_running_protected_routes, RouteClass::Long, timeout_source(&state, RouteClass::Long), cors_layer(config)))
+        .merge(classify(status_stream_routes, RouteClass::Short, timeout_source(&state, RouteClass::Short), cors_layer(config)))
+        .merge(classify(logs_stream_routes, RouteClass::Long, timeout_source(&state, RouteClass::Long), cors_layer(config)))
+        .merge(classify(export_stream_routes, RouteClass::Streaming, timeout_source(&state, RouteClass::Streaming), cors_layer(config)))
+        .layer(axum_middleware::from_fn_with_state(state.clone(), middleware::localize_errors))
         .with_state(state)
 }
 
+
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use axum::http::Request;
+    use futures::stream;
+    use tower::ServiceExt;
+
+    const TIMEOUT: Duration = Duration::from_millis(100);
+
+    fn fixed(timeout: Duration) -> TimeoutSource
+    {
+        Arc::new(move || timeout)
+    }
+
+    // Corps dont le premier fragment n'arrive qu'après `delay`.
+    fn slow_body(delay: Duration) -> Body
+    {
+        Body::from_stream(stream::once(async move
+        {
+            tokio::time::sleep(delay).await;
+            Ok::<_, std::io::Error>("payload")
+        }))
+    }
+
+    fn echo_router(class: RouteClass) -> Router
+    {
+        classify(Router::new().route("/", post(|body: String| async move { body })), class, fixed(TIMEOUT), CorsLayer::new())
+    }
+
+    async fn post_slow_body(class: RouteClass) -> StatusCode
+    {
+        let request = Request::post("/").body(slow_body(TIMEOUT * 5)).unwrap();
+        echo_router(class).oneshot(request).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn short_route_times_out_on_slow_body()
+    {
+        assert_eq!(post_slow_body(RouteClass::Short).await, StatusCode::REQUEST_TIMEOUT);
+    }
+
+    #[tokio::test]
+    async fn long_route_times_out_on_slow_body()
+    {
+        assert_eq!(post_slow_body(RouteClass::Long).await, StatusCode::REQUEST_TIMEOUT);
+    }
+
+    #[tokio::test]
+    async fn changed_timeout_applies_to_the_next_request()
+    {
+        use std::sync::atomic::{AtomicU64, Ordering};
+
+        let millis = Arc::new(AtomicU64::new(TIMEOUT.as_millis() as u64));
+        let source: TimeoutSource = { let millis = millis.clone(); Arc::new(move || Duration::from_millis(millis.load(Ordering::Relaxed))) };
+        let routes = Router::new().route("/", get(|| async
+        {
+            tokio::time::sleep(TIMEOUT * 3).await;
+            "done"
+        }));
+        let router = classify(routes, RouteClass::Short, source, CorsLayer::new());
+
+        let first = router.clone().oneshot(Request::get("/").body(Body::empty()).unwrap()).await.unwrap();
+        assert_eq!(first.status(), StatusCode::REQUEST_TIMEOUT);
+
+        millis.store((TIMEOUT * 10).as_millis() as u64, Ordering::Relaxed);
+        let second = router.oneshot(Request::get("/").body(Body::empty()).unwrap()).await.unwrap();
+        assert_eq!(second.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn streaming_route_has_no_overall_deadline()
+    {
+        let routes = Router::new().route("/", get(|| async
+        {
+            tokio::time::sleep(TIMEOUT * 3).await;
+            "done"
+        }));
+        let router = classify(routes, RouteClass::Streaming, fixed(TIMEOUT), CorsLayer::new());
+
+        let response = router.oneshot(Request::get("/").body(Body::empty()).unwrap()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    async fn send_from_origin(config: &Config, request: axum::http::request::Builder, origin: &str) -> axum::http::HeaderMap
+    {
+        let router = classify(Router::new().route("/", get(|| async { "ok" }).post(|| async { "ok" })), RouteClass::Short, fixed(TIMEOUT), cors_layer(config));
+        let request = request.uri("/").header(header::ORIGIN, origin).body(Body::empty()).unwrap();
+        router.oneshot(request).await.unwrap().headers().clone()
+    }
+
+    async fn get_from_origin(origin: &str) -> axum::http::HeaderMap
+    {
+        send_from_origin(&Config::for_tests(&[]).unwrap(), Request::get("/"), origin).await
+    }
+
+    async fn preflight_from_origin(origin: &str) -> axum::http::HeaderMap
+    {
+        let request = Request::builder()
+            .method(Method::OPTIONS)
+            .header(header::ACCESS_CONTROL_REQUEST_METHOD, "POST")
+            .header(header::ACCESS_CONTROL_REQUEST_HEADERS, "content-type");
+        send_from_origin(&Config::for_tests(&[]).unwrap(), request, origin).await
+    }
+
+    #[tokio::test]
+    async fn allowed_origin_gets_credentials()
+    {
+        let headers = get_from_origin("https://hangar.test").await;
+
+        assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_ORIGIN], "https://hangar.test");
+        assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_CREDENTIALS], "true");
+    }
+
+    #[tokio::test]
+    async fn other_origin_is_not_allowed()
+    {
+        let headers = get_from_origin("https://evil.test").await;
+
+        assert!(headers.get(header::ACCESS_CONTROL_ALLOW_ORIGIN).is_none());
+    }
+
+    #[tokio::test]
+    async fn preflight_from_allowed_origin_is_accepted()
+    {
+        let headers = preflight_from_origin("https://hangar.test").await;
+
+        assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_ORIGIN], "https://hangar.test");
+        assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_CREDENTIALS], "true");
+        assert!(headers[header::ACCESS_CONTROL_ALLOW_METHODS].to_str().unwrap().contains("POST"));
+    }
+
+    #[tokio::test]
+    async fn preflight_from_other_origin_is_rejected()
+    {
+        let headers = preflight_from_origin("https://evil.test").await;
+
+        // Sans `Access-Control-Allow-Origin`, le navigateur bloque la requête qui devait suivre.
+        assert!(headers.get(header::ACCESS_CONTROL_ALLOW_ORIGIN).is_none());
+    }
+
+    #[tokio::test]
+    async fn dev_any_origin_never_sends_credentials()
+    {
+        let config = Config::for_tests(&[("CORS_DEV_ALLOW_ANY_ORIGIN", "true")]).unwrap();
+        let headers = send_from_origin(&config, Request::get("/"), "https://evil.test").await;
+
+        assert_eq!(headers[header::ACCESS_CONTROL_ALLOW_ORIGIN], "*");
+        assert!(headers.get(header::ACCESS_CONTROL_ALLOW_CREDENTIALS).is_none());
+    }
+
+    #[test]
+    fn dev_any_origin_is_refused_by_strict_startup()
+    {
+        let result = Config::for_tests(&[("CORS_DEV_ALLOW_ANY_ORIGIN", "true"), ("STRICT_STARTUP", "true")]);
+
+        assert!(matches!(result, Err(crate::error::ConfigError::Invalid(name, _)) if name == "CORS_DEV_ALLOW_ANY_ORIGIN"));
+    }
+
+    #[test]
+    fn empty_origin_list_stops_startup()
+    {
+        let result = Config::for_tests(&[("CORS_ALLOWED_ORIGINS", "")]);
+
+        assert!(matches!(result, Err(crate::error::ConfigError::Missing(name)) if name == "CORS_ALLOWED_ORIGINS"));
+    }
+}
@@ -0,0 +1,25 @@
+use sqlx::PgPool;
+use tracing::error;
+
+use crate::error::AppError;
+
+pub async fn record_account_action(
+    pool: &PgPool,
+    login: &str,
+    action: &str,
+    results: Option<serde_json::Value>,
+) -> Result<(), AppError>
+{
+    sqlx::query("INSERT INTO account_actions (login, action, results) VALUES ($1, $2, $3)")
+        .bind(login)
+        .bind(action)
+        .bind(results)
+        .execute(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to record account action '{}' of '{}': {}", action, login, e);
+            AppError::database(&e)
+        })?;
+    Ok(())
+}
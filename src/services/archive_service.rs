@@ -0,0 +1,161 @@
+use std::io::{self, BufWriter, Read, Write};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use axum::body::Bytes;
+use bollard::Docker;
+use bollard::query_parameters::DownloadFromContainerOptionsBuilder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use futures::{channel::mpsc, executor::block_on, SinkExt, StreamExt};
+use tar::{Archive, Builder, Header};
+use tracing::{error, info};
+
+const CHUNK_CHANNEL_CAPACITY: usize = 16;
+const OUTPUT_BUFFER_SIZE: usize = 64 * 1024;
+
+pub type ArchiveChunk = Result<Bytes, io::Error>;
+
+pub struct ArchiveFile
+{
+    pub path: String,
+    pub contents: Vec<u8>,
+}
+
+struct ChannelWriter
+{
+    tx: mpsc::Sender<ArchiveChunk>,
+}
+
+impl Write for ChannelWriter
+{
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize>
+    {
+        block_on(self.tx.send(Ok(Bytes::copy_from_slice(buf))))
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "Archive consumer disconnected"))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()>
+    {
+        Ok(())
+    }
+}
+
+struct ChannelReader
+{
+    rx: mpsc::Receiver<ArchiveChunk>,
+    current: Bytes,
+}
+
+impl Read for ChannelReader
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize>
+    {
+        while self.current.is_empty()
+        {
+            match block_on(self.rx.next())
+            {
+                Some(chunk) => self.current = chunk?,
+                None => return Ok(0),
+            }
+        }
+
+        let len = buf.len().min(self.current.len());
+        buf[..len].copy_from_slice(&self.current[..len]);
+        self.current = self.current.slice(len..);
+        Ok(len)
+    }
+}
+
+// L'archive est produite au fil de l'eau : le récepteur reçoit les morceaux compressés dès leur écriture.
+pub fn stream_project_archive(
+    docker: Docker,
+    container_name: String,
+    files: Vec<ArchiveFile>,
+    volume_path: Option<String>,
+) -> mpsc::Receiver<ArchiveChunk>
+{
+    let (out_tx, out_rx) = mpsc::channel(CHUNK_CHANNEL_CAPACITY);
+
+    let volume_reader = volume_path.map(|path|
+    {
+        let (mut volume_tx, volume_rx) = mpsc::channel::<ArchiveChunk>(CHUNK_CHANNEL_CAPACITY);
+
+        tokio::spawn(async move
+        {
+            let options = DownloadFromContainerOptionsBuilder::new().path(&path).build();
+            let mut stream = docker.download_from_container(&container_name, Some(options));
+
+            while let Some(chunk) = stream.next().await
+            {
+                let chunk = chunk.map_err(|e|
+                {
+                    error!("Failed to download '{}' from container '{}': {}", path, container_name, e);
+                    io::Error::other(e)
+                });
+
+                let failed = chunk.is_err();
+                if volume_tx.send(chunk).await.is_err() || failed
+                {
+                    break;
+                }
+            }
+        });
+
+        ChannelReader { rx: volume_rx, current: Bytes::new() }
+    });
+
+    let mut error_tx = out_tx.clone();
+    tokio::task::spawn_blocking(move ||
+    {
+        match write_archive(out_tx, files, volume_reader)
+        {
+            Ok(()) => info!("Project archive streamed successfully."),
+            Err(e) =>
+            {
+                error!("Failed to build project archive: {}", e);
+                let _ = block_on(error_tx.send(Err(e)));
+            }
+        }
+    });
+
+    out_rx
+}
+
+fn write_archive(
+    out_tx: mpsc::Sender<ArchiveChunk>,
+    files: Vec<ArchiveFile>,
+    volume_reader: Option<ChannelReader>,
+) -> io::Result<()>
+{
+    let writer = BufWriter::with_capacity(OUTPUT_BUFFER_SIZE, ChannelWriter { tx: out_tx });
+    let mut builder = Builder::new(GzEncoder::new(writer, Compression::default()));
+
+    let mtime = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+
+    for file in files
+    {
+        let mut header = Header::new_gnu();
+        header.set_size(file.contents.len() as u64);
+        header.set_mode(0o600);
+        header.set_mtime(mtime);
+        builder.append_data(&mut header, &file.path, file.contents.as_slice())?;
+    }
+
+    if let Some(reader) = volume_reader
+    {
+        let mut volume_archive = Archive::new(reader);
+        for entry in volume_archive.entries()?
+        {
+            let mut entry = entry?;
+            let path = Path::new("volume").join(entry.path()?);
+            let mut header = entry.header().clone();
+            builder.append_data(&mut header, path, &mut entry)?;
+        }
+    }
+
+    let mut writer = builder.into_inner()?.finish()?;
+    writer.flush()?;
+    Ok(())
+}
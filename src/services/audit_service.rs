@@ -0,0 +1,99 @@
+use sqlx::{PgPool, Postgres, QueryBuilder};
+use tracing::error;
+
+use crate::
+{
+    api::list_params::SortOrder,
+    error::AppError,
+    model::audit::{AuditEntry, AuditFilters, AuditRecord},
+};
+
+// Au mieux : un échec d'insertion est journalisé et ne fait jamais échouer l'opération auditée.
+pub async fn record(pool: &PgPool, record: AuditRecord<'_>)
+{
+    let result = sqlx::query(
+        "INSERT INTO audit_log (actor, action, target_type, target_id, target_name, performed_by_admin, details)
+         VALUES ($1, $2, $3, $4, $5, $6, $7)"
+    )
+        .bind(record.actor)
+        .bind(record.action)
+        .bind(record.target_type)
+        .bind(record.target_id)
+        .bind(record.target_name)
+        .bind(record.performed_by_admin)
+        .bind(&record.details)
+        .execute(pool)
+        .await;
+
+    if let Err(e) = result
+    {
+        error!(
+            "AUDIT ENTRY LOST: action '{}' by '{}' on {:?} {} could not be recorded (details: {:?}): {}",
+            record.action, record.actor, record.target_type, record.target_id, record.details, e
+        );
+    }
+}
+
+fn push_audit_filters(query: &mut QueryBuilder<'_, Postgres>, filters: &AuditFilters)
+{
+    query.push(" WHERE TRUE");
+
+    if let Some(actor) = &filters.actor
+    {
+        query.push(" AND actor = ").push_bind(actor.clone());
+    }
+    if let Some(action) = &filters.action
+    {
+        query.push(" AND action = ").push_bind(action.clone());
+    }
+    if let Some(target_type) = filters.target_type
+    {
+        query.push(" AND target_type = ").push_bind(target_type);
+    }
+    if let Some(target_id) = filters.target_id
+    {
+        query.push(" AND target_id = ").push_bind(target_id);
+    }
+}
+
+// `sort_by` vient toujours d'une liste blanche `ListSpec`, jamais de l'utilisateur : il est écrit dans la requête.
+pub async fn get_entries_page(
+    pool: &PgPool,
+    filters: &AuditFilters,
+    sort_by: &'static str,
+    order: SortOrder,
+    limit: i64,
+    offset: i64,
+) -> Result<(Vec<AuditEntry>, i64), AppError>
+{
+    let mut query = QueryBuilder::new(
+        "SELECT id, actor, action, target_type, target_id, target_name, performed_by_admin, details, created_at FROM audit_log"
+    );
+    push_audit_filters(&mut query, filters);
+    query.push(format!(" ORDER BY {} {}, id {}", sort_by, order.as_sql(), order.as_sql()));
+    query.push(" LIMIT ").push_bind(limit);
+    query.push(" OFFSET ").push_bind(offset);
+
+    let entries = query.build_query_as::<AuditEntry>()
+        .fetch_all(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to fetch a page of the audit log: {}", e);
+            AppError::database(&e)
+        })?;
+
+    let mut count = QueryBuilder::new("SELECT COUNT(*) FROM audit_log");
+    push_audit_filters(&mut count, filters);
+
+    let total: i64 = count.build_query_scalar()
+        .fetch_one(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to count the audit log entries: {}", e);
+            AppError::database(&e)
+        })?;
+
+    Ok((entries, total))
+}
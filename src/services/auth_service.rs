@@ -1,8 +1,18 @@
 use serde::Deserialize;
+use sqlx::PgPool;
+use time::OffsetDateTime;
 use tracing::error;
 use crate::error::AppError;
+use crate::model::auth_attempt::{AuthAttempt, AuthFailureReason};
 use crate::model::user::User;
 
+// Les pannes du CAS ne sont pas la faute du client et ne comptent pas pour la limitation.
+const THROTTLED_FAILURES: &str = "failure_reason NOT IN ('cas_unreachable', 'cas_error')";
+
+const CAS: &str = "CAS";
+
+const AUTH_ATTEMPTS_LIMIT: i64 = 500;
+
 #[derive(Debug, Deserialize)]
 struct ServiceResponse {
     #[serde(rename = "authenticationSuccess", alias = "cas:authenticationSuccess")]
@@ -30,36 +40,181 @@ struct CasAttributes
 }
 
 
-pub async fn validate_ticket(url: &str, client: &reqwest::Client)  -> Result<User, AppError>
+#[derive(Debug)]
+pub struct TicketRejection
+{
+    pub reason: AuthFailureReason,
+    pub error: AppError,
+}
+
+fn reject(reason: AuthFailureReason, error: impl Into<AppError>) -> TicketRejection
 {
+    TicketRejection { reason, error: error.into() }
+}
 
-    let response = client.get(url).send().await?;
+pub async fn validate_ticket(url: &str, client: &reqwest::Client)  -> Result<User, TicketRejection>
+{
+
+    let response = client.get(url).send().await
+        .map_err(|e| reject(AuthFailureReason::CasUnreachable, AppError::upstream(CAS, e)))?;
     
     if !response.status().is_success() {
         error!("The CAS service responded with an error status: {}", response.status());
-        return Err(AppError::Unauthorized("The authentication service refused validation.".to_string()));
+        let error = if response.status().is_server_error()
+        {
+            AppError::upstream_status(CAS, response.status())
+        }
+        else
+        {
+            AppError::Unauthorized("The authentication service refused validation.".to_string())
+        };
+        return Err(reject(AuthFailureReason::CasError, error));
     }
 
-    let xml_body = response.text().await?;
+    let xml_body = response.text().await
+        .map_err(|e| reject(AuthFailureReason::CasUnreachable, AppError::upstream(CAS, e)))?;
 
     tracing::debug!("CAS response body: {}", xml_body);
 
-    let service_response: ServiceResponse = quick_xml::de::from_str(&xml_body)?;
+    let service_response: ServiceResponse = quick_xml::de::from_str(&xml_body)
+        .map_err(|e| reject(AuthFailureReason::MalformedResponse, e))?;
 
     let auth = service_response.authentication_success
-        .ok_or_else(|| { AppError::Unauthorized("Invalid ticket".to_string()) })?;
+        .ok_or_else(|| { reject(AuthFailureReason::InvalidTicket, AppError::Unauthorized("Invalid ticket".to_string())) })?;
 
     let attributes = auth.attributes
-        .ok_or_else(|| { AppError::Unauthorized("Missing attributes".to_string()) })?;
+        .ok_or_else(|| { reject(AuthFailureReason::MissingAttributes, AppError::Unauthorized("Missing attributes".to_string())) })?;
 
     let email = attributes.mail
-        .ok_or_else(|| { error!("Missing mail in CAS"); AppError::Unauthorized("Missing mail".to_string()) })?;
+        .ok_or_else(|| { error!("Missing mail in CAS"); reject(AuthFailureReason::MissingAttributes, AppError::Unauthorized("Missing mail".to_string())) })?;
 
     let login = attributes.login
-        .ok_or_else(|| { error!("Missing login in CAS"); AppError::Unauthorized("Missing login".to_string()) })?;
+        .ok_or_else(|| { error!("Missing login in CAS"); reject(AuthFailureReason::MissingAttributes, AppError::Unauthorized("Missing login".to_string())) })?;
 
     let prenom = attributes.prenom
-        .ok_or_else(|| { error!("Missing prenom in CAS"); AppError::Unauthorized("Missing prenom".to_string()) })?;
+        .ok_or_else(|| { error!("Missing prenom in CAS"); reject(AuthFailureReason::MissingAttributes, AppError::Unauthorized("Missing prenom".to_string())) })?;
 
     Ok(User { email, name : prenom, login })
-}
\ No newline at end of file
+}
+
+pub async fn record_auth_attempt(
+    pool: &PgPool,
+    login: Option<&str>,
+    failure_reason: Option<AuthFailureReason>,
+    ip_address: &str,
+    user_agent: Option<&str>,
+) -> Result<(), AppError>
+{
+    sqlx::query(
+        "INSERT INTO auth_attempts (login, success, failure_reason, ip_address, user_agent) VALUES ($1, $2, $3, $4, $5)"
+    )
+        .bind(login)
+        .bind(failure_reason.is_none())
+        .bind(failure_reason)
+        .bind(ip_address)
+        .bind(user_agent)
+        .execute(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to record auth attempt from {}: {}", ip_address, e);
+            AppError::database(&e)
+        })?;
+    Ok(())
+}
+
+pub async fn count_recent_failures(pool: &PgPool, ip_address: &str) -> Result<i64, AppError>
+{
+    let query = format!(
+        "SELECT COUNT(*) FROM auth_attempts WHERE ip_address = $1 AND NOT success AND {} AND created_at > NOW() - INTERVAL '1 minute'",
+        THROTTLED_FAILURES
+    );
+    sqlx::query_scalar(&query)
+        .bind(ip_address)
+        .fetch_one(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to count recent auth failures for {}: {}", ip_address, e);
+            AppError::database(&e)
+        })
+}
+
+pub async fn record_login(pool: &PgPool, login: &str) -> Result<(), AppError>
+{
+    sqlx::query(
+        "INSERT INTO user_logins (login) VALUES ($1) ON CONFLICT (login) DO UPDATE SET last_login_at = NOW()"
+    )
+        .bind(login)
+        .execute(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to record last login for '{}': {}", login, e);
+            AppError::database(&e)
+        })?;
+    Ok(())
+}
+
+pub async fn get_last_login(pool: &PgPool, login: &str) -> Result<Option<OffsetDateTime>, AppError>
+{
+    sqlx::query_scalar("SELECT last_login_at FROM user_logins WHERE login = $1")
+        .bind(login)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to fetch last login for '{}': {}", login, e);
+            AppError::database(&e)
+        })
+}
+
+pub async fn get_auth_attempts(
+    pool: &PgPool,
+    login: Option<&str>,
+    from: Option<OffsetDateTime>,
+) -> Result<Vec<AuthAttempt>, AppError>
+{
+    sqlx::query_as::<_, AuthAttempt>(
+        "SELECT id, login, success, failure_reason, ip_address, user_agent, created_at
+         FROM auth_attempts
+         WHERE ($1::VARCHAR IS NULL OR login = $1) AND ($2::TIMESTAMPTZ IS NULL OR created_at >= $2)
+         ORDER BY created_at DESC
+         LIMIT $3"
+    )
+        .bind(login)
+        .bind(from)
+        .bind(AUTH_ATTEMPTS_LIMIT)
+        .fetch_all(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to fetch auth attempts: {}", e);
+            AppError::database(&e)
+        })
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::{config::Config, state::build_http_client, test_support::stalling_server};
+    use axum::http::StatusCode;
+
+    #[tokio::test]
+    async fn stalled_cas_is_reported_as_an_upstream_timeout()
+    {
+        let config = Config::for_tests(&[("HTTP_REQUEST_TIMEOUT_SECONDS", "1")]).unwrap();
+        let client = build_http_client(&config).unwrap();
+
+        let Err(rejection) = validate_ticket(&format!("http://{}/serviceValidate", stalling_server()), &client).await
+        else
+        {
+            panic!("a stalled CAS cannot validate a ticket");
+        };
+
+        assert_eq!(rejection.reason, AuthFailureReason::CasUnreachable);
+        assert!(matches!(rejection.error, AppError::UpstreamTimeout { service: "CAS" }));
+        assert_eq!(rejection.error.status_code(), StatusCode::GATEWAY_TIMEOUT);
+    }
+}
@@ -0,0 +1,265 @@
+use std::collections::BTreeMap;
+
+use sqlx::PgPool;
+use time::{Date, Duration, OffsetDateTime, Time};
+use tracing::error;
+
+use crate::
+{
+    error::AppError,
+    model::
+    {
+        availability::{uptime_percent, AvailabilityDay, AvailabilityEvent, AvailabilityStats},
+        project::ProjectStatus,
+    },
+};
+
+pub async fn record_transition(pool: &PgPool, project_id: i32, status: ProjectStatus) -> Result<(), AppError>
+{
+    sqlx::query("INSERT INTO availability_events (project_id, status) VALUES ($1, $2)")
+        .bind(project_id)
+        .bind(status)
+        .execute(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to record the availability of project {}: {}", project_id, e);
+            AppError::database(&e)
+        })?;
+
+    Ok(())
+}
+
+pub async fn get_project_stats(pool: &PgPool, project_id: i32, window_days: i64) -> Result<AvailabilityStats, AppError>
+{
+    let now = OffsetDateTime::now_utc();
+    let from = now - Duration::days(window_days);
+
+    let events = get_timeline(pool, Some(project_id), from).await?;
+    let mut stats = summarize(&events, from, now);
+
+    for day in get_days(pool, Some(project_id), from.date()).await?
+    {
+        stats.add(&day_stats(&day));
+    }
+
+    Ok(stats)
+}
+
+pub async fn get_fleet_average_uptime(pool: &PgPool, window_days: i64) -> Result<Option<f64>, AppError>
+{
+    let now = OffsetDateTime::now_utc();
+    let from = now - Duration::days(window_days);
+
+    let mut per_project: BTreeMap<i32, AvailabilityStats> = BTreeMap::new();
+
+    let events = get_timeline(pool, None, from).await?;
+    for project_events in events.chunk_by(|a, b| a.project_id == b.project_id)
+    {
+        per_project.entry(project_events[0].project_id).or_default().add(&summarize(project_events, from, now));
+    }
+
+    for day in get_days(pool, None, from.date()).await?
+    {
+        per_project.entry(day.project_id).or_default().add(&day_stats(&day));
+    }
+
+    let uptimes: Vec<f64> = per_project.values().filter_map(|stats| stats.uptime_percent).collect();
+
+    Ok((!uptimes.is_empty()).then(|| uptimes.iter().sum::<f64>() / uptimes.len() as f64))
+}
+
+// L'état à la date limite est gardé comme événement ; une panne à cheval sur minuit compte pour chaque jour.
+pub async fn compact(pool: &PgPool, retention_days: i64) -> Result<usize, AppError>
+{
+    let cutoff = OffsetDateTime::now_utc().replace_time(Time::MIDNIGHT) - Duration::days(retention_days);
+
+    let events: Vec<AvailabilityEvent> = sqlx::query_as(
+        "SELECT project_id, status, observed_at FROM availability_events WHERE observed_at < $1 ORDER BY project_id, observed_at"
+    )
+        .bind(cutoff)
+        .fetch_all(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to fetch availability events to compact: {}", e);
+            AppError::database(&e)
+        })?;
+
+    if events.is_empty()
+    {
+        return Ok(0);
+    }
+
+    let mut tx = pool.begin().await.map_err(|e| AppError::database(&e))?;
+
+    for project_events in events.chunk_by(|a, b| a.project_id == b.project_id)
+    {
+        let project_id = project_events[0].project_id;
+
+        let mut day = project_events[0].observed_at.date();
+        while day < cutoff.date()
+        {
+            let start = day.midnight().assume_utc();
+            let stats = summarize(project_events, start, start + Duration::DAY);
+
+            if stats != AvailabilityStats::default()
+            {
+                sqlx::query(
+                    "INSERT INTO availability_daily (project_id, day, up_seconds, down_seconds, incidents, longest_outage_seconds)
+                     VALUES ($1, $2, $3, $4, $5, $6)
+                     ON CONFLICT (project_id, day) DO UPDATE SET
+                         up_seconds = availability_daily.up_seconds + EXCLUDED.up_seconds,
+                         down_seconds = availability_daily.down_seconds + EXCLUDED.down_seconds,
+                         incidents = availability_daily.incidents + EXCLUDED.incidents,
+                         longest_outage_seconds = GREATEST(availability_daily.longest_outage_seconds, EXCLUDED.longest_outage_seconds)"
+                )
+                    .bind(project_id)
+                    .bind(day)
+                    .bind(stats.up_seconds)
+                    .bind(stats.down_seconds)
+                    .bind(i32::try_from(stats.incidents).unwrap_or(i32::MAX))
+                    .bind(stats.longest_outage_seconds)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(|e|
+                    {
+                        error!("Failed to store the availability of project {} on {}: {}", project_id, day, e);
+                        AppError::database(&e)
+                    })?;
+            }
+
+            let Some(next) = day.next_day()
+            else
+            {
+                break;
+            };
+            day = next;
+        }
+
+        if let Some(last) = project_events.last()
+        {
+            sqlx::query("INSERT INTO availability_events (project_id, status, observed_at) VALUES ($1, $2, $3)")
+                .bind(project_id)
+                .bind(last.status)
+                .bind(cutoff)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| AppError::database(&e))?;
+        }
+    }
+
+    sqlx::query("DELETE FROM availability_events WHERE observed_at < $1")
+        .bind(cutoff)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to delete compacted availability events: {}", e);
+            AppError::database(&e)
+        })?;
+
+    tx.commit().await.map_err(|e| AppError::database(&e))?;
+
+    Ok(events.len())
+}
+
+// Précédés pour chaque projet du dernier événement antérieur, pour connaître l'état à `from`.
+async fn get_timeline(pool: &PgPool, project_id: Option<i32>, from: OffsetDateTime) -> Result<Vec<AvailabilityEvent>, AppError>
+{
+    sqlx::query_as(
+        "SELECT project_id, status, observed_at FROM (
+             (SELECT DISTINCT ON (project_id) project_id, status, observed_at
+              FROM availability_events
+              WHERE observed_at < $1 AND ($2::INTEGER IS NULL OR project_id = $2)
+              ORDER BY project_id, observed_at DESC)
+             UNION ALL
+             (SELECT project_id, status, observed_at
+              FROM availability_events
+              WHERE observed_at >= $1 AND ($2::INTEGER IS NULL OR project_id = $2))
+         ) timeline
+         ORDER BY project_id, observed_at"
+    )
+        .bind(from)
+        .bind(project_id)
+        .fetch_all(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to fetch availability events: {}", e);
+            AppError::database(&e)
+        })
+}
+
+async fn get_days(pool: &PgPool, project_id: Option<i32>, from_day: Date) -> Result<Vec<AvailabilityDay>, AppError>
+{
+    sqlx::query_as(
+        "SELECT project_id, up_seconds, down_seconds, incidents, longest_outage_seconds
+         FROM availability_daily
+         WHERE day >= $1 AND ($2::INTEGER IS NULL OR project_id = $2)"
+    )
+        .bind(from_day)
+        .bind(project_id)
+        .fetch_all(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to fetch daily availability: {}", e);
+            AppError::database(&e)
+        })
+}
+
+fn day_stats(day: &AvailabilityDay) -> AvailabilityStats
+{
+    AvailabilityStats
+    {
+        up_seconds: day.up_seconds,
+        down_seconds: day.down_seconds,
+        uptime_percent: uptime_percent(day.up_seconds, day.down_seconds),
+        incidents: i64::from(day.incidents),
+        longest_outage_seconds: day.longest_outage_seconds,
+    }
+}
+
+fn summarize(events: &[AvailabilityEvent], from: OffsetDateTime, to: OffsetDateTime) -> AvailabilityStats
+{
+    let mut stats = AvailabilityStats::default();
+    let mut outage_seconds = 0;
+    let mut in_outage = false;
+
+    for (index, event) in events.iter().enumerate()
+    {
+        let start = event.observed_at.max(from);
+        let end = events.get(index + 1).map_or(to, |next| next.observed_at).min(to);
+        let seconds = (end - start).whole_seconds();
+        if seconds <= 0
+        {
+            continue;
+        }
+
+        match event.status
+        {
+            status if status.is_outage() =>
+            {
+                if !in_outage
+                {
+                    stats.incidents += 1;
+                    outage_seconds = 0;
+                    in_outage = true;
+                }
+                outage_seconds += seconds;
+                stats.down_seconds += seconds;
+                stats.longest_outage_seconds = stats.longest_outage_seconds.max(outage_seconds);
+            }
+            ProjectStatus::Running =>
+            {
+                in_outage = false;
+                stats.up_seconds += seconds;
+            }
+            _ => in_outage = false,
+        }
+    }
+
+    stats.uptime_percent = uptime_percent(stats.up_seconds, stats.down_seconds);
+    stats
+}
@@ -0,0 +1,150 @@
+use std::{io, path::Path};
+
+use serde::Serialize;
+use tempfile::{Builder as TempBuilder, TempDir};
+use tracing::{error, warn};
+
+use crate::
+{
+    config::Config,
+    error::{AppError, ProjectErrorCode},
+};
+
+const BYTES_PER_MB: u64 = 1024 * 1024;
+
+#[derive(Debug, Serialize)]
+pub struct BuildDirSpace
+{
+    pub path: String,
+    pub available_bytes: Option<u64>,
+    pub min_free_bytes: u64,
+}
+
+pub fn check_writable(dir: &Path) -> io::Result<()>
+{
+    if !dir.is_dir()
+    {
+        return Err(io::Error::new(io::ErrorKind::NotFound, format!("'{}' is not a directory", dir.display())));
+    }
+
+    // Le fichier est supprimé dès qu'il sort de la portée.
+    tempfile::tempfile_in(dir).map(drop)
+}
+
+pub fn available_bytes(dir: &Path) -> io::Result<u64>
+{
+    let stats = rustix::fs::statvfs(dir)?;
+    Ok(stats.f_bavail.saturating_mul(stats.f_frsize))
+}
+
+pub fn get_space(config: &Config) -> BuildDirSpace
+{
+    let available_bytes = available_bytes(&config.build_tmp_dir)
+        .inspect_err(|e| warn!("Could not read free space of build directory '{}': {}", config.build_tmp_dir.display(), e))
+        .ok();
+
+    BuildDirSpace
+    {
+        path: config.build_tmp_dir.display().to_string(),
+        available_bytes,
+        min_free_bytes: config.build_min_free_mb * BYTES_PER_MB,
+    }
+}
+
+// Mieux vaut refuser que d'échouer à mi-chemin sur une erreur d'E/S.
+pub fn ensure_free_space(config: &Config) -> Result<(), AppError>
+{
+    let available = available_bytes(&config.build_tmp_dir).map_err(|e|
+    {
+        error!("Failed to read free space of build directory '{}': {}", config.build_tmp_dir.display(), e);
+        AppError::InternalServerError
+    })?;
+
+    if available < config.build_min_free_mb * BYTES_PER_MB
+    {
+        warn!(
+            "Build refused: {} MB free in '{}', {} MB required.",
+            available / BYTES_PER_MB, config.build_tmp_dir.display(), config.build_min_free_mb
+        );
+        return Err(ProjectErrorCode::InsufficientBuildSpace(available / BYTES_PER_MB, config.build_min_free_mb).into());
+    }
+
+    Ok(())
+}
+
+// Supprimé à la destruction : chaque sortie de l'appelant le nettoie.
+pub fn create_build_dir(config: &Config) -> Result<TempDir, AppError>
+{
+    ensure_free_space(config)?;
+
+    TempBuilder::new()
+        .prefix("hangar-build-")
+        .tempdir_in(&config.build_tmp_dir)
+        .map_err(|e|
+        {
+            error!("Failed to create a build directory in '{}': {}", config.build_tmp_dir.display(), e);
+            AppError::InternalServerError
+        })
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn config_in(dir: &Path, min_free_mb: &str) -> Config
+    {
+        Config::for_tests(&[("BUILD_TMP_DIR", dir.to_str().unwrap()), ("BUILD_MIN_FREE_MB", min_free_mb)]).unwrap()
+    }
+
+    fn entries(dir: &Path) -> usize
+    {
+        std::fs::read_dir(dir).unwrap().count()
+    }
+
+    #[test]
+    fn build_dir_is_removed_on_every_exit_path()
+    {
+        let root = tempfile::tempdir().unwrap();
+        let config = config_in(root.path(), "0");
+
+        let build_dir = create_build_dir(&config).unwrap();
+        std::fs::write(build_dir.path().join("Dockerfile"), "FROM scratch").unwrap();
+        assert!(build_dir.path().starts_with(root.path()));
+        drop(build_dir);
+        assert_eq!(entries(root.path()), 0);
+
+        // Une étape qui échoue sort par `?` : le répertoire est détruit avec la pile.
+        let failing_step = || -> Result<(), AppError>
+        {
+            let build_dir = create_build_dir(&config)?;
+            std::fs::create_dir(build_dir.path().join("src")).unwrap();
+            Err(AppError::InternalServerError)
+        };
+        assert!(failing_step().is_err());
+        assert_eq!(entries(root.path()), 0);
+    }
+
+    #[test]
+    fn low_free_space_refuses_before_creating_anything()
+    {
+        let root = tempfile::tempdir().unwrap();
+        let config = config_in(root.path(), &u32::MAX.to_string());
+
+        assert!(matches!(
+            create_build_dir(&config),
+            Err(AppError::ProjectError(ProjectErrorCode::InsufficientBuildSpace(_, required))) if required == u64::from(u32::MAX)
+        ));
+        assert_eq!(entries(root.path()), 0);
+    }
+
+    #[test]
+    fn missing_directory_is_not_writable()
+    {
+        let root = tempfile::tempdir().unwrap();
+
+        assert!(check_writable(root.path()).is_ok());
+        assert!(check_writable(&root.path().join("missing")).is_err());
+        assert_eq!(entries(root.path()), 0);
+    }
+}
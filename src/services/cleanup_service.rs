@@ -0,0 +1,119 @@
+use sqlx::PgPool;
+use tracing::error;
+
+use crate::
+{
+    error::AppError,
+    model::cleanup::{CleanupAction, PendingCleanup},
+};
+
+const PENDING_CLEANUPS_LIMIT: i64 = 500;
+
+pub async fn insert_pending_cleanup(
+    pool: &PgPool,
+    operation: &str,
+    action: &CleanupAction,
+    attempts: i32,
+    last_error: &str,
+) -> Result<(), AppError>
+{
+    let action_json = serde_json::to_value(action).map_err(|e|
+    {
+        error!("Failed to serialize cleanup action '{}': {}", action, e);
+        AppError::InternalServerError
+    })?;
+
+    sqlx::query("INSERT INTO pending_cleanups (operation, action, attempts, last_error) VALUES ($1, $2, $3, $4)")
+        .bind(operation)
+        .bind(action_json)
+        .bind(attempts)
+        .bind(last_error)
+        .execute(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to persist pending cleanup '{}' of {}: {}", action, operation, e);
+            AppError::database(&e)
+        })?;
+    Ok(())
+}
+
+pub async fn get_pending_cleanups(pool: &PgPool) -> Result<Vec<PendingCleanup>, AppError>
+{
+    sqlx::query_as::<_, PendingCleanup>(
+        "SELECT id, operation, action, attempts, last_error, created_at, last_attempt_at, abandoned_at
+         FROM pending_cleanups
+         ORDER BY created_at
+         LIMIT $1"
+    )
+        .bind(PENDING_CLEANUPS_LIMIT)
+        .fetch_all(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to fetch pending cleanups: {}", e);
+            AppError::database(&e)
+        })
+}
+
+pub async fn get_retryable_cleanups(pool: &PgPool) -> Result<Vec<PendingCleanup>, AppError>
+{
+    sqlx::query_as::<_, PendingCleanup>(
+        "SELECT id, operation, action, attempts, last_error, created_at, last_attempt_at, abandoned_at
+         FROM pending_cleanups
+         WHERE abandoned_at IS NULL
+         ORDER BY created_at
+         LIMIT $1"
+    )
+        .bind(PENDING_CLEANUPS_LIMIT)
+        .fetch_all(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to fetch retryable cleanups: {}", e);
+            AppError::database(&e)
+        })
+}
+
+pub async fn mark_cleanup_abandoned(pool: &PgPool, cleanup_id: i64) -> Result<(), AppError>
+{
+    sqlx::query("UPDATE pending_cleanups SET abandoned_at = NOW() WHERE id = $1")
+        .bind(cleanup_id)
+        .execute(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to mark pending cleanup {} as abandoned: {}", cleanup_id, e);
+            AppError::database(&e)
+        })?;
+    Ok(())
+}
+
+pub async fn record_cleanup_failure(pool: &PgPool, cleanup_id: i64, last_error: &str) -> Result<(), AppError>
+{
+    sqlx::query("UPDATE pending_cleanups SET attempts = attempts + 1, last_error = $1, last_attempt_at = NOW() WHERE id = $2")
+        .bind(last_error)
+        .bind(cleanup_id)
+        .execute(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to record failed attempt of pending cleanup {}: {}", cleanup_id, e);
+            AppError::database(&e)
+        })?;
+    Ok(())
+}
+
+pub async fn delete_pending_cleanup(pool: &PgPool, cleanup_id: i64) -> Result<(), AppError>
+{
+    sqlx::query("DELETE FROM pending_cleanups WHERE id = $1")
+        .bind(cleanup_id)
+        .execute(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to delete pending cleanup {}: {}", cleanup_id, e);
+            AppError::database(&e)
+        })?;
+    Ok(())
+}
@@ -0,0 +1,87 @@
+use sqlx::PgPool;
+use tracing::error;
+
+use crate::
+{
+    error::AppError,
+    model::{config_revision::{ProjectConfigRevision, ProjectConfigSnapshot}, project::Project},
+};
+
+const SELECT_REVISION_FIELDS: &str = "SELECT id, project_id, revision, snapshot, diff, actor, created_at FROM project_config_revisions";
+
+// Rien n'est enregistré si la configuration est identique à la dernière révision.
+pub async fn record_revision(pool: &PgPool, project: &Project, actor: &str) -> Result<Option<i32>, AppError>
+{
+    let snapshot = ProjectConfigSnapshot::of(project);
+    let latest = get_latest_revision(pool, project.id).await?;
+
+    let diff = match &latest
+    {
+        Some(latest) if latest.snapshot.0 == snapshot => return Ok(None),
+        Some(latest) => Some(snapshot.diff(&latest.snapshot.0)),
+        None => None,
+    };
+    let revision = latest.map_or(1, |latest| latest.revision + 1);
+
+    sqlx::query(
+        "INSERT INTO project_config_revisions (project_id, revision, snapshot, diff, actor)
+         VALUES ($1, $2, $3, $4, $5)"
+    )
+        .bind(project.id)
+        .bind(revision)
+        .bind(sqlx::types::Json(&snapshot))
+        .bind(diff)
+        .bind(actor)
+        .execute(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to record configuration revision {} of project {}: {}", revision, project.id, e);
+            AppError::database(&e)
+        })?;
+
+    Ok(Some(revision))
+}
+
+pub async fn get_revisions(pool: &PgPool, project_id: i32) -> Result<Vec<ProjectConfigRevision>, AppError>
+{
+    let query = format!("{} WHERE project_id = $1 ORDER BY revision DESC", SELECT_REVISION_FIELDS);
+    sqlx::query_as::<_, ProjectConfigRevision>(&query)
+        .bind(project_id)
+        .fetch_all(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to fetch configuration history of project {}: {}", project_id, e);
+            AppError::database(&e)
+        })
+}
+
+pub async fn get_revision(pool: &PgPool, project_id: i32, revision: i32) -> Result<Option<ProjectConfigRevision>, AppError>
+{
+    let query = format!("{} WHERE project_id = $1 AND revision = $2", SELECT_REVISION_FIELDS);
+    sqlx::query_as::<_, ProjectConfigRevision>(&query)
+        .bind(project_id)
+        .bind(revision)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to fetch configuration revision {} of project {}: {}", revision, project_id, e);
+            AppError::database(&e)
+        })
+}
+
+async fn get_latest_revision(pool: &PgPool, project_id: i32) -> Result<Option<ProjectConfigRevision>, AppError>
+{
+    let query = format!("{} WHERE project_id = $1 ORDER BY revision DESC LIMIT 1", SELECT_REVISION_FIELDS);
+    sqlx::query_as::<_, ProjectConfigRevision>(&query)
+        .bind(project_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to fetch the latest configuration revision of project {}: {}", project_id, e);
+            AppError::database(&e)
+        })
+}
@@ -1,53 +1,352 @@
+use std::{str::FromStr, sync::atomic::{AtomicU64, Ordering}};
+
 use aes_gcm::{
-    aead::{Aead, KeyInit, OsRng, AeadCore},
+    aead::{generic_array::typenum::Unsigned, Aead, KeyInit, OsRng, AeadCore, Payload},
     Aes256Gcm, Nonce, Key
 };
+use chacha20poly1305::XChaCha20Poly1305;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
 use crate::error::AppError;
 
+// Ancien format sans en-tête, toujours AES-GCM.
 const NONCE_SIZE: usize = 12; // 96 bits, standard pour AES-GCM
 
+// Enveloppe : version (qui fixe l'algorithme et la taille du nonce), identifiant de clé, nonce, texte chiffré.
+// La version et l'identifiant sont authentifiés avec le texte chiffré.
+const KEY_ID_SIZE: usize = 4;
+const HEADER_SIZE: usize = 1 + KEY_ID_SIZE;
+
+// Quand il reste à zéro après un re-chiffrement, l'ancien format peut disparaître.
+static LEGACY_DECRYPTIONS: AtomicU64 = AtomicU64::new(0);
 
-pub fn encrypt(plaintext: &str, key: &[u8]) -> Result<Vec<u8>, AppError>
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Default)]
+pub enum EncryptionAlgorithm
 {
-    let key = Key::<Aes256Gcm>::from_slice(key);
-    let cipher = Aes256Gcm::new(key);
-    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    #[default]
+    Aes256Gcm,
+    XChaCha20Poly1305,
+}
 
-    let ciphertext = cipher.encrypt(&nonce, plaintext.as_bytes())
-        .map_err(|e|
+impl EncryptionAlgorithm
+{
+    pub fn as_str(self) -> &'static str
+    {
+        match self
         {
-            tracing::error!("Encryption failed: {}", e);
-            AppError::InternalServerError
-        })?;
-    
-    let mut result = Vec::with_capacity(NONCE_SIZE + ciphertext.len());
+            Self::Aes256Gcm => "aes-256-gcm",
+            Self::XChaCha20Poly1305 => "xchacha20-poly1305",
+        }
+    }
+
+    fn envelope_version(self) -> u8
+    {
+        match self
+        {
+            Self::Aes256Gcm => 1,
+            Self::XChaCha20Poly1305 => 2,
+        }
+    }
+
+    fn from_envelope_version(version: u8) -> Option<Self>
+    {
+        match version
+        {
+            1 => Some(Self::Aes256Gcm),
+            2 => Some(Self::XChaCha20Poly1305),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for EncryptionAlgorithm
+{
+    type Err = ();
+
+    fn from_str(value: &str) -> Result<Self, Self::Err>
+    {
+        match value.to_ascii_lowercase().as_str()
+        {
+            "aes-256-gcm" => Ok(Self::Aes256Gcm),
+            "xchacha20-poly1305" => Ok(Self::XChaCha20Poly1305),
+            _ => Err(()),
+        }
+    }
+}
+
+// Les clés précédentes ne servent qu'au déchiffrement, le temps que le re-chiffrement passe tout sur la clé courante.
+#[derive(Deserialize, Clone)]
+pub struct EncryptionKeys
+{
+    current: Vec<u8>,
+    previous: Vec<Vec<u8>>,
+    algorithm: EncryptionAlgorithm,
+}
+
+impl EncryptionKeys
+{
+    pub fn new(current: Vec<u8>, previous: Vec<Vec<u8>>, algorithm: EncryptionAlgorithm) -> Self
+    {
+        Self { current, previous, algorithm }
+    }
+
+    pub fn algorithm(&self) -> EncryptionAlgorithm
+    {
+        self.algorithm
+    }
+
+    pub fn previous_count(&self) -> usize
+    {
+        self.previous.len()
+    }
+
+    fn all(&self) -> impl Iterator<Item = &Vec<u8>>
+    {
+        std::iter::once(&self.current).chain(&self.previous)
+    }
+}
+
+// Assez pour distinguer les clés configurées sans les révéler.
+fn key_id(key: &[u8]) -> [u8; KEY_ID_SIZE]
+{
+    let digest = Sha256::digest(key);
+    let mut id = [0; KEY_ID_SIZE];
+    id.copy_from_slice(&digest[..KEY_ID_SIZE]);
+    id
+}
+
+pub fn legacy_decryptions() -> u64
+{
+    LEGACY_DECRYPTIONS.load(Ordering::Relaxed)
+}
+
+pub fn encrypt(plaintext: &str, keys: &EncryptionKeys) -> Result<Vec<u8>, AppError>
+{
+    let mut header = [0; HEADER_SIZE];
+    header[0] = keys.algorithm.envelope_version();
+    header[1..].copy_from_slice(&key_id(&keys.current));
+
+    let sealed = match keys.algorithm
+    {
+        EncryptionAlgorithm::Aes256Gcm => seal::<Aes256Gcm>(&keys.current, &header, plaintext.as_bytes()),
+        EncryptionAlgorithm::XChaCha20Poly1305 => seal::<XChaCha20Poly1305>(&keys.current, &header, plaintext.as_bytes()),
+    };
+
+    sealed.map_err(|e|
+    {
+        tracing::error!("Encryption failed: {}", e);
+        AppError::InternalServerError
+    })
+}
+
+fn seal<C: Aead + AeadCore + KeyInit>(key: &[u8], header: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, aes_gcm::aead::Error>
+{
+    let cipher = C::new_from_slice(key).map_err(|_| aes_gcm::aead::Error)?;
+    let nonce = C::generate_nonce(&mut OsRng);
+    let ciphertext = cipher.encrypt(&nonce, Payload { msg: plaintext, aad: header })?;
+
+    let mut result = Vec::with_capacity(header.len() + nonce.len() + ciphertext.len());
+    result.extend_from_slice(header);
     result.extend_from_slice(nonce.as_slice());
     result.extend_from_slice(&ciphertext);
-
     Ok(result)
 }
 
-pub fn decrypt(ciphertext_with_nonce: &[u8], key: &[u8]) -> Result<String, AppError>
+fn open<C: Aead + AeadCore + KeyInit>(key: &[u8], header: &[u8], sealed: &[u8]) -> Result<Vec<u8>, String>
 {
-    if ciphertext_with_nonce.len() < NONCE_SIZE
+    let nonce_size = C::NonceSize::USIZE;
+    if sealed.len() < nonce_size
     {
-        tracing::error!("Ciphertext is too short to contain a nonce.");
-        return Err(AppError::InternalServerError);
+        return Err("the ciphertext is too short".to_string());
     }
 
-    let key = Key::<Aes256Gcm>::from_slice(key);
-    let cipher = Aes256Gcm::new(key);
+    let (nonce_bytes, ciphertext) = sealed.split_at(nonce_size);
+    let cipher = C::new_from_slice(key).map_err(|_| "the key has the wrong size".to_string())?;
+    cipher.decrypt(aes_gcm::aead::Nonce::<C>::from_slice(nonce_bytes), Payload { msg: ciphertext, aad: header })
+        .map_err(|e| format!("authentication failed ({})", e))
+}
 
-    let (nonce_bytes, ciphertext) = ciphertext_with_nonce.split_at(NONCE_SIZE);
-    let nonce = Nonce::from_slice(nonce_bytes);
+pub fn decrypt(data: &[u8], keys: &EncryptionKeys) -> Result<String, AppError>
+{
+    decrypt_reporting_key(data, keys).map(|(plaintext, _)| plaintext)
+}
 
-    let plaintext_bytes = cipher.decrypt(nonce, ciphertext)
-        .map_err(|e|
+// Le booléen indique une enveloppe déjà sous la clé et l'algorithme courants : la re-chiffrer est inutile.
+pub fn decrypt_reporting_key(data: &[u8], keys: &EncryptionKeys) -> Result<(String, bool), AppError>
+{
+    let envelope_error = match decrypt_envelope(data, keys)
+    {
+        Ok(result) => return Ok(result),
+        Err(reason) => reason,
+    };
+
+    // Un blob hérité dont le nonce commence par l'octet de version ressemble à une enveloppe :
+    // l'ancien format est donc toujours essayé avant de conclure.
+    match decrypt_legacy(data, keys)
+    {
+        Some(plaintext) =>
         {
-            tracing::error!("Decryption failed: {}. This might happen if the key is wrong or the data is corrupted.", e);
-            AppError::InternalServerError
-        })?;
+            if LEGACY_DECRYPTIONS.fetch_add(1, Ordering::Relaxed) == 0
+            {
+                tracing::warn!("Decrypted a secret in the legacy format without envelope; re-encrypt the stored secrets to upgrade them.");
+            }
+            Ok((plaintext?, false))
+        }
+        None =>
+        {
+            tracing::error!("Decryption failed: {}. This might happen if the key is wrong or the data is corrupted.", envelope_error);
+            Err(AppError::InternalServerError)
+        }
+    }
+}
+
+fn decrypt_envelope(data: &[u8], keys: &EncryptionKeys) -> Result<(String, bool), String>
+{
+    if data.len() < HEADER_SIZE
+    {
+        return Err("the ciphertext is too short".to_string());
+    }
+
+    let (header, rest) = data.split_at(HEADER_SIZE);
+    let Some(algorithm) = EncryptionAlgorithm::from_envelope_version(header[0])
+    else
+    {
+        return Err(format!("unknown envelope version {}", header[0]));
+    };
+
+    let Some((index, key)) = keys.all().enumerate().find(|(_, key)| key_id(key) == header[1..])
+    else
+    {
+        return Err(format!("no configured key has the id {:02x?}", &header[1..]));
+    };
+
+    let plaintext_bytes = match algorithm
+    {
+        EncryptionAlgorithm::Aes256Gcm => open::<Aes256Gcm>(key, header, rest)?,
+        EncryptionAlgorithm::XChaCha20Poly1305 => open::<XChaCha20Poly1305>(key, header, rest)?,
+    };
+
+    let plaintext = String::from_utf8(plaintext_bytes).map_err(|_| "the plaintext is not UTF-8".to_string())?;
+    Ok((plaintext, index == 0 && algorithm == keys.algorithm))
+}
+
+fn decrypt_legacy(data: &[u8], keys: &EncryptionKeys) -> Option<Result<String, AppError>>
+{
+    if data.len() < NONCE_SIZE
+    {
+        return None;
+    }
+
+    let (nonce_bytes, ciphertext) = data.split_at(NONCE_SIZE);
+    let nonce = Nonce::from_slice(nonce_bytes);
+
+    keys.all()
+        .find_map(|key| Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key)).decrypt(nonce, ciphertext).ok())
+        .map(|plaintext_bytes| String::from_utf8(plaintext_bytes).map_err(|_| AppError::InternalServerError))
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    const CURRENT_KEY: [u8; 32] = [7; 32];
+    const PREVIOUS_KEY: [u8; 32] = [9; 32];
+
+    fn keys() -> EncryptionKeys
+    {
+        EncryptionKeys::new(CURRENT_KEY.to_vec(), vec![PREVIOUS_KEY.to_vec()], EncryptionAlgorithm::Aes256Gcm)
+    }
+
+    // Ancien format : nonce || texte chiffré, sans en-tête ni données authentifiées.
+    fn legacy_blob(key: &[u8], nonce_bytes: [u8; NONCE_SIZE], plaintext: &str) -> Vec<u8>
+    {
+        let ciphertext = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key))
+            .encrypt(Nonce::from_slice(&nonce_bytes), plaintext.as_bytes())
+            .unwrap();
+        [nonce_bytes.as_slice(), &ciphertext].concat()
+    }
 
-    String::from_utf8(plaintext_bytes)
-        .map_err(|_| AppError::InternalServerError)
-}
\ No newline at end of file
+    #[test]
+    fn round_trip_under_the_current_key()
+    {
+        let keys = keys();
+        let blob = encrypt("s3cret", &keys).unwrap();
+
+        assert_eq!(blob[0], 1);
+        assert_eq!(decrypt_reporting_key(&blob, &keys).unwrap(), ("s3cret".to_string(), true));
+    }
+
+    #[test]
+    fn previous_key_still_decrypts_but_asks_for_re_encryption()
+    {
+        let old_keys = EncryptionKeys::new(PREVIOUS_KEY.to_vec(), Vec::new(), EncryptionAlgorithm::Aes256Gcm);
+        let blob = encrypt("s3cret", &old_keys).unwrap();
+
+        assert_eq!(decrypt_reporting_key(&blob, &keys()).unwrap(), ("s3cret".to_string(), false));
+    }
+
+    #[test]
+    fn xchacha_round_trip()
+    {
+        let keys = EncryptionKeys::new(CURRENT_KEY.to_vec(), Vec::new(), EncryptionAlgorithm::XChaCha20Poly1305);
+        let blob = encrypt("s3cret", &keys).unwrap();
+
+        assert_eq!(blob[0], 2);
+        assert_eq!(blob.len(), HEADER_SIZE + 24 + "s3cret".len() + 16);
+        assert_eq!(decrypt_reporting_key(&blob, &keys).unwrap(), ("s3cret".to_string(), true));
+    }
+
+    #[test]
+    fn aes_envelope_is_read_while_xchacha_is_the_default()
+    {
+        let blob = encrypt("s3cret", &keys()).unwrap();
+        let xchacha_keys = EncryptionKeys::new(CURRENT_KEY.to_vec(), Vec::new(), EncryptionAlgorithm::XChaCha20Poly1305);
+
+        // Même clé, autre algorithme : lisible, mais à re-chiffrer.
+        assert_eq!(decrypt_reporting_key(&blob, &xchacha_keys).unwrap(), ("s3cret".to_string(), false));
+    }
+
+    #[test]
+    fn truncated_input_is_rejected()
+    {
+        let keys = keys();
+        let blob = encrypt("s3cret", &keys).unwrap();
+
+        assert!(decrypt(&blob[..blob.len() - 1], &keys).is_err());
+        assert!(decrypt(&blob[..HEADER_SIZE + 4], &keys).is_err());
+        assert!(decrypt(&blob[..2], &keys).is_err());
+        assert!(decrypt(&[], &keys).is_err());
+    }
+
+    #[test]
+    fn unknown_key_id_is_rejected()
+    {
+        let other_keys = EncryptionKeys::new([3; 32].to_vec(), Vec::new(), EncryptionAlgorithm::Aes256Gcm);
+        let blob = encrypt("s3cret", &other_keys).unwrap();
+
+        assert!(decrypt(&blob, &keys()).is_err());
+    }
+
+    #[test]
+    fn legacy_blob_is_decrypted_and_flagged_for_re_encryption()
+    {
+        let blob = legacy_blob(&PREVIOUS_KEY, [42; NONCE_SIZE], "legacy");
+
+        assert_eq!(decrypt_reporting_key(&blob, &keys()).unwrap(), ("legacy".to_string(), false));
+    }
+
+    #[test]
+    fn legacy_blob_whose_nonce_looks_like_an_envelope_header_is_decrypted()
+    {
+        // Pire cas : le nonce commence par l'octet de version puis l'identifiant de la clé courante,
+        // l'enveloppe est donc tentée avec la bonne clé avant l'ancien format.
+        let mut nonce_bytes = [0; NONCE_SIZE];
+        nonce_bytes[0] = EncryptionAlgorithm::Aes256Gcm.envelope_version();
+        nonce_bytes[1..HEADER_SIZE].copy_from_slice(&key_id(&CURRENT_KEY));
+        let blob = legacy_blob(&CURRENT_KEY, nonce_bytes, "legacy");
+
+        assert_eq!(decrypt_reporting_key(&blob, &keys()).unwrap(), ("legacy".to_string(), false));
+    }
+}
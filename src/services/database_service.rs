@@ -2,11 +2,12 @@ use crate::
 {
     config::Config,
     error::{AppError, DatabaseErrorCode, ProjectErrorCode},
-    model::database::{Database, DatabaseDetailsResponse},
-    services::crypto_service,
+    model::{cleanup::CleanupAction, database::{Database, DatabaseDetailsResponse, RevealMethod, UserResourceLimits}},
+    ops::Rollback,
+    services::{crypto_service::{self, EncryptionKeys}, db_template_service::{self, TemplateScript}, quota_service},
 };
 use rand::distr::{Alphanumeric, SampleString};
-use sqlx::{MySqlPool, PgPool, Postgres, Transaction};
+use sqlx::{MySqlConnection, MySqlPool, PgPool, Postgres, Transaction};
 use tracing::{error, info, warn};
 use base64::prelude::*;
 use std::collections::HashSet;
@@ -28,7 +29,7 @@ fn valid_identifier(s: &str) -> bool
     s.chars().all(|c| allowed.contains(&c))
 }
 
-pub async fn check_database_exists_for_owner(pool: &PgPool, owner: &str) -> Result<bool, AppError>
+pub async fn count_databases_for_owner(pool: &PgPool, owner: &str) -> Result<i64, AppError>
 {
     let count: (i64, ) = sqlx::query_as("SELECT COUNT(*) FROM databases WHERE owner_login = $1")
         .bind(owner)
@@ -36,10 +37,21 @@ pub async fn check_database_exists_for_owner(pool: &PgPool, owner: &str) -> Resu
         .await
         .map_err(|e|
         {
-            error!("Failed to check if database exists for owner {}: {}", owner, e);
-            AppError::InternalServerError
+            error!("Failed to count databases for owner {}: {}", owner, e);
+            AppError::database(&e)
         })?;
-    Ok(count.0 > 0)
+    Ok(count.0)
+}
+
+pub async fn resolve_user_limits(pool: &PgPool, config: &Config, owner: &str) -> Result<UserResourceLimits, AppError>
+{
+    let overrides = quota_service::get_user_overrides(pool, owner).await?;
+    let mut limits = config.mariadb_user_limits;
+    if let Some(max_user_connections) = overrides.db_max_user_connections
+    {
+        limits.max_user_connections = max_user_connections;
+    }
+    Ok(limits)
 }
 
 fn generate_password() -> String
@@ -52,58 +64,55 @@ pub async fn provision_database(
     pg_pool: &PgPool,
     mariadb_pool: &MySqlPool,
     owner_login: &str,
-    encryption_key: &[u8],
+    encryption_key: &EncryptionKeys,
+    template: Option<&TemplateScript>,
+    limits: &UserResourceLimits,
+    rollback: &mut Rollback,
 ) -> Result<(Database, String), AppError>
 {
-    if check_database_exists_for_owner(pg_pool, owner_login).await?
-    {
-        return Err(DatabaseErrorCode::DatabaseAlreadyExists.into());
-    }
-
     let db_name = format!("{}_{}", DB_PREFIX, owner_login);
     let username = owner_login.to_string();
     let password = generate_password();
 
-    if let Err(e) = execute_mariadb_provisioning(mariadb_pool, &db_name, &username, &password).await
+    // Le déprovisionnement est idempotent : enregistré avant l'étape, il couvre aussi un provisionnement partiel.
+    rollback.push(CleanupAction::DeprovisionDatabase { database: db_name.clone(), username: username.clone() });
+
+    execute_mariadb_provisioning(mariadb_pool, &db_name, &username, &password, limits).await.map_err(|e|
     {
-        warn!("MariaDB provisioning failed for user '{}'. Attempting rollback. Error: {}", owner_login, e);
-        if let Err(e) = execute_mariadb_deprovisioning(mariadb_pool, &db_name, &username).await
-        {
-            error!("Failed to rollback MariaDB provisioning for user '{}': {}", owner_login, e);
-        }
-        return Err(e);
+        warn!("MariaDB provisioning failed for user '{}': {}", owner_login, e);
+        e
+    })?;
+
+    if let Some(template) = template
+    {
+        db_template_service::apply_template(mariadb_pool, &db_name, template).await?;
     }
 
     let encrypted_password_vec = crypto_service::encrypt(&password, encryption_key)?;
     let encrypted_password = BASE64_STANDARD.encode(encrypted_password_vec);
 
     let db_record = sqlx::query_as::<_, Database>(
-        "INSERT INTO databases (owner_login, database_name, username, encrypted_password)
-         VALUES ($1, $2, $3, $4)
-         RETURNING id, owner_login, database_name, username, encrypted_password, project_id, created_at",
+        "INSERT INTO databases (owner_login, database_name, username, encrypted_password, template_name,
+                                max_user_connections, max_queries_per_hour, max_updates_per_hour, max_connections_per_hour)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+         RETURNING id, owner_login, database_name, username, encrypted_password, project_id, template_name,
+                   max_user_connections, max_queries_per_hour, max_updates_per_hour, max_connections_per_hour, created_at",
     )
     .bind(owner_login)
     .bind(&db_name)
     .bind(&username)
     .bind(&encrypted_password)
+    .bind(template.map(|template| &template.name))
+    .bind(limits.max_user_connections)
+    .bind(limits.max_queries_per_hour)
+    .bind(limits.max_updates_per_hour)
+    .bind(limits.max_connections_per_hour)
     .fetch_one(pg_pool)
     .await
     .map_err(|e|
     {
         error!("Failed to persist database metadata for user '{}' after successful MariaDB provisioning: {}", owner_login, e);
-        let mariadb_pool = mariadb_pool.clone();
-        let db_name = db_name.clone();
-        let username = username.clone();
-        let owner_login = owner_login.to_string();
-        tokio::spawn(async move
-        {
-            warn!("CRITICAL: Rolling back MariaDB provisioning for {} due to PostgreSQL failure.", owner_login);
-            if let Err(e) = execute_mariadb_deprovisioning(&mariadb_pool, &db_name, &username).await
-            {
-                error!("Failed to rollback MariaDB provisioning for user '{}': {}", owner_login, e);
-            }
-        });
-        AppError::InternalServerError
+        AppError::database(&e)
     })?;
 
     info!("Database for user '{}' provisioned successfully.", owner_login);
@@ -123,7 +132,10 @@ pub async fn deprovision_database(
 
     execute_mariadb_deprovisioning(mariadb_pool, &db_record.database_name, &db_record.username).await?;
 
-    sqlx::query("DELETE FROM databases WHERE id = $1")
+    sqlx::query(
+        "WITH deleted AS (DELETE FROM databases WHERE id = $1 RETURNING project_id)
+         UPDATE projects SET updated_at = NOW() WHERE id IN (SELECT project_id FROM deleted)"
+    )
         .bind(db_id)
         .execute(pg_pool)
         .await
@@ -142,6 +154,7 @@ async fn execute_mariadb_provisioning(
     db_name: &str,
     username: &str,
     password: &str,
+    limits: &UserResourceLimits,
 ) -> Result<(), AppError> 
 {
     if !valid_identifier(db_name) || !valid_identifier(username) 
@@ -193,6 +206,13 @@ async fn execute_mariadb_provisioning(
             DatabaseErrorCode::ProvisioningFailed
         })?;
 
+    // Appliquées à part de CREATE USER pour que l'erreur soit journalisée sans exposer le mot de passe.
+    apply_user_limits(&mut conn, username, limits).await.map_err(|e|
+    {
+        error!("Failed to apply resource limits to user '{}': {}", username, e);
+        DatabaseErrorCode::ProvisioningFailed
+    })?;
+
     sqlx::query("FLUSH PRIVILEGES")
         .execute(&mut *conn)
         .await
@@ -205,9 +225,61 @@ async fn execute_mariadb_provisioning(
     Ok(())
 }
 
+async fn apply_user_limits(conn: &mut MySqlConnection, username: &str, limits: &UserResourceLimits) -> Result<(), sqlx::Error>
+{
+    let alter_user_sql = format!(
+        "ALTER USER `{}`@'%' WITH MAX_USER_CONNECTIONS {} MAX_QUERIES_PER_HOUR {} MAX_UPDATES_PER_HOUR {} MAX_CONNECTIONS_PER_HOUR {}",
+        username, limits.max_user_connections, limits.max_queries_per_hour, limits.max_updates_per_hour, limits.max_connections_per_hour
+    );
+    sqlx::query(&alter_user_sql).execute(&mut *conn).await?;
+    Ok(())
+}
+
+pub async fn update_user_limits(
+    pg_pool: &PgPool,
+    mariadb_pool: &MySqlPool,
+    db: &Database,
+    limits: &UserResourceLimits,
+) -> Result<(), AppError>
+{
+    if !valid_identifier(&db.username)
+    {
+        return Err(AppError::BadRequest("Invalid identifier".into()));
+    }
 
+    let mut conn = mariadb_pool.acquire().await.map_err(|e|
+    {
+        error!("Failed to acquire MariaDB connection: {}", e);
+        AppError::database(&e)
+    })?;
+
+    apply_user_limits(&mut conn, &db.username, limits).await.map_err(|e|
+    {
+        error!("Failed to change resource limits of user '{}': {}", db.username, e);
+        AppError::InternalServerError
+    })?;
 
-async fn execute_mariadb_deprovisioning(
+    sqlx::query(
+        "UPDATE databases SET max_user_connections = $2, max_queries_per_hour = $3, max_updates_per_hour = $4, max_connections_per_hour = $5
+         WHERE id = $1"
+    )
+        .bind(db.id)
+        .bind(limits.max_user_connections)
+        .bind(limits.max_queries_per_hour)
+        .bind(limits.max_updates_per_hour)
+        .bind(limits.max_connections_per_hour)
+        .execute(pg_pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Resource limits of database {} were applied but not recorded: {}", db.id, e);
+            AppError::database(&e)
+        })?;
+
+    Ok(())
+}
+
+pub async fn execute_mariadb_deprovisioning(
     pool: &MySqlPool,
     db_name: &str,
     username: &str,
@@ -250,7 +322,7 @@ pub async fn get_database_by_owner(pool: &PgPool, owner: &str) -> Result<Option<
         .map_err(|e|
         {
             error!("Failed to fetch database for owner {}: {}", owner, e);
-            AppError::InternalServerError
+            AppError::database(&e)
         })
 }
 
@@ -262,7 +334,7 @@ pub async fn get_database_by_id_and_owner(pool: &PgPool, db_id: i32, owner: &str
             .bind(db_id)
             .fetch_optional(pool)
             .await
-            .map_err(|_| AppError::InternalServerError);
+            .map_err(|e| AppError::database(&e));
     }
 
     sqlx::query_as("SELECT * FROM databases WHERE id = $1 AND owner_login = $2")
@@ -270,7 +342,7 @@ pub async fn get_database_by_id_and_owner(pool: &PgPool, db_id: i32, owner: &str
         .bind(owner)
         .fetch_optional(pool)
         .await
-        .map_err(|_| AppError::InternalServerError)
+        .map_err(|e| AppError::database(&e))
 }
 
 pub async fn get_database_by_project_id(pool: &PgPool, project_id: i32) -> Result<Option<Database>, AppError>
@@ -279,18 +351,23 @@ pub async fn get_database_by_project_id(pool: &PgPool, project_id: i32) -> Resul
         .bind(project_id)
         .fetch_optional(pool)
         .await
-        .map_err(|_| AppError::InternalServerError)
+        .map_err(|e| AppError::database(&e))
 }
 
 pub async fn link_database_to_project(pool: &PgPool, db_id: i32, project_id: i32, owner: &str) -> Result<(), AppError>
 {
-    let result = sqlx::query("UPDATE databases SET project_id = $1 WHERE id = $2 AND owner_login = $3")
+    // Le projet précédemment lié et le nouveau changent tous deux de détails
+    let result = sqlx::query(
+        "WITH previous AS (SELECT project_id FROM databases WHERE id = $2 AND owner_login = $3),
+              linked AS (UPDATE databases SET project_id = $1 WHERE id = $2 AND owner_login = $3 RETURNING project_id)
+         UPDATE projects SET updated_at = NOW() WHERE id IN (SELECT project_id FROM linked UNION SELECT project_id FROM previous)"
+    )
         .bind(project_id)
         .bind(db_id)
         .bind(owner)
         .execute(pool)
         .await
-        .map_err(|_| AppError::InternalServerError)?;
+        .map_err(|e| AppError::database(&e))?;
     
     if result.rows_affected() == 0 {
         return Err(DatabaseErrorCode::NotFound.into());
@@ -300,12 +377,15 @@ pub async fn link_database_to_project(pool: &PgPool, db_id: i32, project_id: i32
 
 pub async fn unlink_database_from_project(pool: &PgPool, project_id: i32, owner: &str) -> Result<(), AppError>
 {
-    let result = sqlx::query("UPDATE databases SET project_id = NULL WHERE project_id = $1 AND owner_login = $2")
+    let result = sqlx::query(
+        "WITH unlinked AS (UPDATE databases SET project_id = NULL WHERE project_id = $1 AND owner_login = $2 RETURNING id)
+         UPDATE projects SET updated_at = NOW() WHERE id = $1 AND EXISTS (SELECT 1 FROM unlinked)"
+    )
         .bind(project_id)
         .bind(owner)
         .execute(pool)
         .await
-        .map_err(|_| AppError::InternalServerError)?;
+        .map_err(|e| AppError::database(&e))?;
         
     if result.rows_affected() == 0 {
         return Err(DatabaseErrorCode::NotFound.into());
@@ -313,72 +393,139 @@ pub async fn unlink_database_from_project(pool: &PgPool, project_id: i32, owner:
     Ok(())
 }
 
+pub struct ProvisionParams<'a>
+{
+    pub owner_login: &'a str,
+    pub project_id: i32,
+    pub template: Option<&'a TemplateScript>,
+    pub limits: &'a UserResourceLimits,
+}
+
 pub async fn provision_and_link_database_tx<'a>(
     tx: &mut Transaction<'a, Postgres>,
     mariadb_pool: &MySqlPool,
-    owner_login: &str,
-    project_id: i32,
-    encryption_key: &[u8],
+    params: ProvisionParams<'_>,
+    encryption_key: &EncryptionKeys,
+    rollback: &mut Rollback,
 ) -> Result<(), AppError>
 {
+    let ProvisionParams { owner_login, project_id, template, limits } = params;
 
     let db_name = format!("{}_{}", DB_PREFIX, owner_login);
     let username = db_name.clone();
     let password = generate_password();
 
-    if let Err(e) = execute_mariadb_provisioning(mariadb_pool, &db_name, &username, &password).await
+    // The MariaDB side lives outside the PostgreSQL transaction: whatever fails after this point,
+    // including the participants added later in the same transaction, must drop it again.
+    rollback.push(CleanupAction::DeprovisionDatabase { database: db_name.clone(), username: username.clone() });
+
+    execute_mariadb_provisioning(mariadb_pool, &db_name, &username, &password, limits).await.map_err(|e|
     {
         warn!("MariaDB provisioning failed during transaction for user '{}'. Error: {}", owner_login, e);
-        if let Err(e) = execute_mariadb_deprovisioning(mariadb_pool, &db_name, &username).await 
-        {
-            error!("Failed to rollback MariaDB provisioning for user '{}': {}", owner_login, e);
-        }
-        return Err(e);
+        e
+    })?;
+
+    if let Some(template) = template
+    {
+        db_template_service::apply_template(mariadb_pool, &db_name, template).await?;
     }
     
     let encrypted_password_vec = crypto_service::encrypt(&password, encryption_key)?;
     let encrypted_password = BASE64_STANDARD.encode(encrypted_password_vec);
 
     let insert_result = sqlx::query(
-        "INSERT INTO databases (owner_login, database_name, username, encrypted_password, project_id)
-         VALUES ($1, $2, $3, $4, $5)",
+        "INSERT INTO databases (owner_login, database_name, username, encrypted_password, project_id, template_name,
+                                max_user_connections, max_queries_per_hour, max_updates_per_hour, max_connections_per_hour)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)",
     )
     .bind(owner_login)
     .bind(&db_name)
     .bind(&username)
     .bind(&encrypted_password)
     .bind(project_id)
+    .bind(template.map(|template| &template.name))
+    .bind(limits.max_user_connections)
+    .bind(limits.max_queries_per_hour)
+    .bind(limits.max_updates_per_hour)
+    .bind(limits.max_connections_per_hour)
     .execute(&mut **tx)
     .await;
 
     if let Err(db_error) = insert_result
     {
         error!("Failed to persist database metadata for user '{}' in transaction: {}", owner_login, db_error);
-        if let Err(e) = execute_mariadb_deprovisioning(mariadb_pool, &db_name, &username).await 
-        {
-            error!("Failed to rollback MariaDB provisioning for user '{}': {}", owner_login, e);
-        }
         return Err(AppError::ProjectError(ProjectErrorCode::ProjectCreationFailedWithDatabaseError));
     }
 
     Ok(())
 }
 
-pub fn create_db_details_response(db: Database, config: &Config, encryption_key: &[u8]) -> Result<DatabaseDetailsResponse, AppError>
+// Base et utilisateur créés par `provision_and_link_database_tx` dont la transaction n'a jamais été validée.
+pub async fn orphaned_project_database_cleanup(pg_pool: &PgPool, owner_login: &str) -> Result<Option<CleanupAction>, AppError>
+{
+    if count_databases_for_owner(pg_pool, owner_login).await? > 0
+    {
+        return Ok(None);
+    }
+
+    let db_name = format!("{}_{}", DB_PREFIX, owner_login);
+    Ok(Some(CleanupAction::DeprovisionDatabase { database: db_name.clone(), username: db_name }))
+}
+
+// Réservé à la révélation : les autres réponses passent par `create_masked_db_details_response`.
+pub fn create_db_details_response(db: Database, config: &Config, encryption_key: &EncryptionKeys) -> Result<DatabaseDetailsResponse, AppError>
 {
     let encrypted_pass_vec = BASE64_STANDARD.decode(&db.encrypted_password).map_err(|_| AppError::InternalServerError)?;
     let password = crypto_service::decrypt(&encrypted_pass_vec, encryption_key)?;
 
-    Ok(DatabaseDetailsResponse 
+    let mut details = create_masked_db_details_response(db, config);
+    details.password = Some(password);
+    details.password_masked = false;
+    Ok(details)
+}
+
+pub fn create_masked_db_details_response(db: Database, config: &Config) -> DatabaseDetailsResponse
+{
+    DatabaseDetailsResponse 
     {
         id: db.id,
         owner_login: db.owner_login,
         database_name: db.database_name,
         username: db.username,
-        password,
+        password: None,
+        password_masked: true,
         project_id: db.project_id,
         host: config.mariadb_public_host.clone(),
         port: config.mariadb_public_port,
+        template_name: db.template_name,
+        limits: db.limits,
         created_at: db.created_at,
-    })
+    }
+}
+
+pub async fn record_password_reveal(
+    pool: &PgPool,
+    database_id: i32,
+    login: &str,
+    method: RevealMethod,
+    ip_address: &str,
+    user_agent: Option<&str>,
+) -> Result<(), AppError>
+{
+    sqlx::query(
+        "INSERT INTO database_password_reveals (database_id, login, method, ip_address, user_agent) VALUES ($1, $2, $3, $4, $5)"
+    )
+        .bind(database_id)
+        .bind(login)
+        .bind(method)
+        .bind(ip_address)
+        .bind(user_agent)
+        .execute(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to record password reveal of database {} by '{}': {}", database_id, login, e);
+            AppError::database(&e)
+        })?;
+    Ok(())
 }
\ No newline at end of file
@@ -0,0 +1,323 @@
+use sqlx::{Executor, MySqlPool, PgPool};
+use tracing::{error, info};
+
+use crate::
+{
+    error::{AppError, DatabaseErrorCode},
+    model::db_template::{DbTemplate, DbTemplatePayload},
+};
+
+const SELECT_TEMPLATE_FIELDS: &str = "SELECT id, name, description, octet_length(script) AS size_bytes, created_by, created_at FROM db_templates";
+
+// Comparées à l'instruction sans commentaires, espaces réduits et en majuscules.
+const FORBIDDEN_STATEMENTS: &[&str] = &[
+    "CREATE DATABASE", "CREATE SCHEMA", "ALTER DATABASE", "ALTER SCHEMA", "DROP DATABASE", "DROP SCHEMA",
+    "GRANT", "REVOKE", "USE",
+    "CREATE USER", "ALTER USER", "DROP USER", "RENAME USER", "SET PASSWORD",
+    "CREATE ROLE", "DROP ROLE", "SET ROLE", "SET DEFAULT ROLE",
+    "CREATE SERVER", "ALTER SERVER", "DROP SERVER",
+    "SET GLOBAL", "SET @@GLOBAL", "FLUSH", "KILL", "SHUTDOWN", "INSTALL", "UNINSTALL",
+    "LOAD DATA", "LOAD XML", "DELIMITER",
+];
+
+pub struct TemplateScript
+{
+    pub name: String,
+    pub script: String,
+}
+
+pub fn validate_template(payload: &DbTemplatePayload, max_bytes: usize) -> Result<(), AppError>
+{
+    let name_is_valid = !payload.name.is_empty()
+        && payload.name.len() <= 64
+        && payload.name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_');
+    if !name_is_valid
+    {
+        return Err(AppError::BadRequest("A template name is 1 to 64 lowercase letters, digits, '-' or '_'.".to_string()));
+    }
+
+    if payload.script.len() > max_bytes
+    {
+        return Err(AppError::BadRequest(format!("The template script exceeds the limit of {} bytes.", max_bytes)));
+    }
+
+    let mut statement_count = 0;
+    for statement in split_statements(&payload.script)
+    {
+        statement_count += 1;
+
+        // Les commentaires exécutables (/*! ... */) sont lus par MariaDB : on ne peut pas les ignorer.
+        if statement.contains("/*!") || statement.contains("/*M!")
+        {
+            return Err(AppError::BadRequest(format!("Statement {} uses an executable comment, which templates cannot contain.", statement_count)));
+        }
+
+        let normalized = normalize_statement(statement);
+        let normalized = normalized.strip_prefix("CREATE OR REPLACE ").map(|rest| format!("CREATE {}", rest)).unwrap_or(normalized);
+        if let Some(forbidden) = FORBIDDEN_STATEMENTS.iter().find(|&&keyword| starts_with_keyword(&normalized, keyword))
+        {
+            return Err(AppError::BadRequest(format!("Statement {} is a '{}' statement, which templates cannot contain.", statement_count, forbidden)));
+        }
+        if normalized.contains("INTO OUTFILE") || normalized.contains("INTO DUMPFILE")
+        {
+            return Err(AppError::BadRequest(format!("Statement {} writes to a file, which templates cannot do.", statement_count)));
+        }
+    }
+
+    if statement_count == 0
+    {
+        return Err(AppError::BadRequest("The template script contains no statement.".to_string()));
+    }
+
+    Ok(())
+}
+
+pub async fn get_all_templates(pool: &PgPool) -> Result<Vec<DbTemplate>, AppError>
+{
+    let query = format!("{} ORDER BY name", SELECT_TEMPLATE_FIELDS);
+    sqlx::query_as::<_, DbTemplate>(&query)
+        .fetch_all(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to fetch database templates: {}", e);
+            AppError::database(&e)
+        })
+}
+
+pub async fn create_template(pool: &PgPool, payload: &DbTemplatePayload, admin_login: &str) -> Result<DbTemplate, AppError>
+{
+    sqlx::query_as::<_, DbTemplate>(
+        "INSERT INTO db_templates (name, description, script, created_by)
+         VALUES ($1, $2, $3, $4)
+         RETURNING id, name, description, octet_length(script) AS size_bytes, created_by, created_at"
+    )
+        .bind(&payload.name)
+        .bind(&payload.description)
+        .bind(&payload.script)
+        .bind(admin_login)
+        .fetch_one(pool)
+        .await
+        .map_err(|e|
+        {
+            if let Some(db_err) = e.as_database_error()
+                && db_err.is_unique_violation()
+            {
+                return AppError::BadRequest(format!("A database template named '{}' already exists.", payload.name));
+            }
+            error!("Failed to create database template '{}': {}", payload.name, e);
+            AppError::InternalServerError
+        })
+}
+
+pub async fn delete_template(pool: &PgPool, name: &str) -> Result<bool, AppError>
+{
+    let result = sqlx::query("DELETE FROM db_templates WHERE name = $1")
+        .bind(name)
+        .execute(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to delete database template '{}': {}", name, e);
+            AppError::database(&e)
+        })?;
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn get_template_script(pool: &PgPool, name: &str) -> Result<TemplateScript, AppError>
+{
+    let script: Option<String> = sqlx::query_scalar("SELECT script FROM db_templates WHERE name = $1")
+        .bind(name)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to fetch database template '{}': {}", name, e);
+            AppError::database(&e)
+        })?;
+
+    script
+        .map(|script| TemplateScript { name: name.to_string(), script })
+        .ok_or_else(|| AppError::BadRequest(format!("Unknown database template '{}'.", name)))
+}
+
+// La connexion est fermée au lieu de retourner au pool : son `USE` ne fuit pas vers les requêtes suivantes.
+pub async fn apply_template(mariadb_pool: &MySqlPool, db_name: &str, template: &TemplateScript) -> Result<(), AppError>
+{
+    let mut conn = mariadb_pool.acquire().await
+        .map_err(|e|
+        {
+            error!("Failed to acquire MariaDB connection for template '{}': {}", template.name, e);
+            DatabaseErrorCode::TemplateFailed
+        })?;
+    conn.close_on_drop();
+
+    // Des chaînes possédées : une requête empruntant au script rend le futur non `Send`.
+    let statements: Vec<String> = split_statements(&template.script).map(str::to_string).collect();
+
+    (&mut *conn).execute(format!("USE `{}`", db_name).as_str())
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to select database '{}' for template '{}': {}", db_name, template.name, e);
+            DatabaseErrorCode::TemplateFailed
+        })?;
+
+    for (index, statement) in statements.iter().enumerate()
+    {
+        (&mut *conn).execute(statement.as_str())
+            .await
+            .map_err(|e|
+            {
+                error!("Template '{}' failed on statement {} in database '{}': {}", template.name, index + 1, db_name, e);
+                DatabaseErrorCode::TemplateFailed
+            })?;
+    }
+
+    info!("Template '{}' applied to database '{}'.", template.name, db_name);
+    Ok(())
+}
+
+pub fn split_statements(script: &str) -> StatementSplitter<'_>
+{
+    StatementSplitter { script, position: 0 }
+}
+
+pub struct StatementSplitter<'a>
+{
+    script: &'a str,
+    position: usize,
+}
+
+impl<'a> Iterator for StatementSplitter<'a>
+{
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str>
+    {
+        while self.position < self.script.len()
+        {
+            let rest = &self.script[self.position..];
+            let end = statement_end(rest.as_bytes());
+            self.position += (end + 1).min(rest.len());
+
+            let statement = rest[..end].trim();
+            if !normalize_statement(statement).is_empty()
+            {
+                return Some(statement);
+            }
+        }
+        None
+    }
+}
+
+// Les délimiteurs sont ASCII : parcourir les octets ne s'arrête jamais au milieu d'un caractère.
+fn statement_end(bytes: &[u8]) -> usize
+{
+    let mut quote: Option<u8> = None;
+    let mut i = 0;
+
+    while i < bytes.len()
+    {
+        let byte = bytes[i];
+        match quote
+        {
+            Some(open) =>
+            {
+                if byte == b'\\' && open != b'`'
+                {
+                    i += 2;
+                    continue;
+                }
+                if byte == open
+                {
+                    quote = None;
+                }
+                i += 1;
+            }
+            None =>
+            {
+                if let Some(comment_end) = comment_end(bytes, i)
+                {
+                    i = comment_end;
+                    continue;
+                }
+                match byte
+                {
+                    b'\'' | b'"' | b'`' => quote = Some(byte),
+                    b';' => return i,
+                    _ => {}
+                }
+                i += 1;
+            }
+        }
+    }
+
+    bytes.len()
+}
+
+fn comment_end(bytes: &[u8], i: usize) -> Option<usize>
+{
+    let next = bytes.get(i + 1).copied();
+    let line_comment = bytes[i] == b'#'
+        || (bytes[i] == b'-' && next == Some(b'-') && bytes.get(i + 2).is_none_or(|c| c.is_ascii_whitespace()));
+
+    if line_comment
+    {
+        return Some(bytes[i..].iter().position(|&c| c == b'\n').map_or(bytes.len(), |offset| i + offset + 1));
+    }
+
+    if bytes[i] == b'/' && next == Some(b'*')
+    {
+        return Some(bytes[i + 2..].windows(2).position(|w| w == b"*/").map_or(bytes.len(), |offset| i + 2 + offset + 2));
+    }
+
+    None
+}
+
+// Mots entiers : `USE`, pas `USER_TABLE`.
+fn starts_with_keyword(normalized: &str, keyword: &str) -> bool
+{
+    normalized.strip_prefix(keyword).is_some_and(|rest| !rest.starts_with(|c: char| c.is_ascii_alphanumeric() || c == '_'))
+}
+
+fn normalize_statement(statement: &str) -> String
+{
+    let bytes = statement.as_bytes();
+    let mut code = Vec::with_capacity(bytes.len());
+    let mut quote: Option<u8> = None;
+    let mut i = 0;
+
+    while i < bytes.len()
+    {
+        let byte = bytes[i];
+        if quote.is_none()
+            && let Some(comment_end) = comment_end(bytes, i)
+        {
+            code.push(b' ');
+            i = comment_end;
+            continue;
+        }
+
+        match quote
+        {
+            Some(open) if byte == b'\\' && open != b'`' =>
+            {
+                code.extend_from_slice(&bytes[i..(i + 2).min(bytes.len())]);
+                i += 2;
+                continue;
+            }
+            Some(open) if byte == open => quote = None,
+            None if matches!(byte, b'\'' | b'"' | b'`') => quote = Some(byte),
+            _ => {}
+        }
+        code.push(byte);
+        i += 1;
+    }
+
+    String::from_utf8_lossy(&code)
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_uppercase()
+}
@@ -0,0 +1,337 @@
+use sqlx::PgPool;
+use time::OffsetDateTime;
+use tracing::error;
+use base64::prelude::*;
+
+use crate::
+{
+    error::{AppError, ProjectErrorCode},
+    model::{deployment::{DeployPhaseStats, DeployTimings, Deployment, DeploymentStatus}, project::ProjectSourceType},
+    services::crypto_service::{self, EncryptionKeys},
+};
+
+const SELECT_DEPLOYMENT_FIELDS: &str = "SELECT id, owner, project_name, status, encrypted_payload, image_tag, container_name, volume_name, project_id, docker_host, error_code, error_message, warnings, created_at, updated_at FROM deployments";
+
+const RETURNING_DEPLOYMENT_FIELDS: &str = "RETURNING id, owner, project_name, status, encrypted_payload, image_tag, container_name, volume_name, project_id, docker_host, error_code, error_message, warnings, created_at, updated_at";
+
+pub async fn enqueue_deployment(
+    pool: &PgPool,
+    owner: &str,
+    project_name: &str,
+    payload_json: &str,
+    encryption_key: &EncryptionKeys,
+) -> Result<Deployment, AppError>
+{
+    let encrypted_payload = BASE64_STANDARD.encode(crypto_service::encrypt(payload_json, encryption_key)?);
+
+    let query = format!(
+        "INSERT INTO deployments (owner, project_name, encrypted_payload) VALUES ($1, $2, $3) {}",
+        RETURNING_DEPLOYMENT_FIELDS
+    );
+
+    sqlx::query_as::<_, Deployment>(&query)
+        .bind(owner)
+        .bind(project_name)
+        .bind(encrypted_payload)
+        .fetch_one(pool)
+        .await
+        .map_err(|e|
+        {
+            // L'index unique partiel n'admet qu'un déploiement en cours par utilisateur.
+            if let Some(db_err) = e.as_database_error()
+                && db_err.is_unique_violation()
+            {
+                return ProjectErrorCode::DeploymentAlreadyInProgress.into();
+            }
+            error!("Failed to enqueue deployment of project '{}' for '{}': {}", project_name, owner, e);
+            AppError::database(&e)
+        })
+}
+
+pub fn decrypt_payload(deployment: &Deployment, encryption_key: &EncryptionKeys) -> Result<String, AppError>
+{
+    let encrypted = BASE64_STANDARD.decode(&deployment.encrypted_payload).map_err(|_| AppError::InternalServerError)?;
+    crypto_service::decrypt(&encrypted, encryption_key)
+}
+
+// `FOR UPDATE SKIP LOCKED` : deux instances ne prennent jamais la même ligne.
+pub async fn claim_next_deployment(pool: &PgPool) -> Result<Option<Deployment>, AppError>
+{
+    let query = format!(
+        "UPDATE deployments SET status = 'building', updated_at = NOW()
+         WHERE id = (SELECT id FROM deployments WHERE status = 'queued' ORDER BY created_at FOR UPDATE SKIP LOCKED LIMIT 1)
+         {}",
+        RETURNING_DEPLOYMENT_FIELDS
+    );
+
+    sqlx::query_as::<_, Deployment>(&query)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to claim next queued deployment: {}", e);
+            AppError::database(&e)
+        })
+}
+
+pub async fn has_pending_deployment(pool: &PgPool, owner: &str) -> Result<bool, AppError>
+{
+    let count: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM deployments WHERE owner = $1 AND status NOT IN ('succeeded', 'failed')")
+        .bind(owner)
+        .fetch_one(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to check pending deployments for '{}': {}", owner, e);
+            AppError::database(&e)
+        })?;
+    Ok(count.0 > 0)
+}
+
+// Un artefact `None` garde la valeur déjà enregistrée.
+pub async fn advance_deployment(
+    pool: &PgPool,
+    deployment_id: i32,
+    status: DeploymentStatus,
+    image_tag: Option<&str>,
+    container_name: Option<&str>,
+    volume_name: Option<&str>,
+) -> Result<(), AppError>
+{
+    sqlx::query(
+        "UPDATE deployments
+         SET status = $1,
+             image_tag = COALESCE($2, image_tag),
+             container_name = COALESCE($3, container_name),
+             volume_name = COALESCE($4, volume_name),
+             updated_at = NOW()
+         WHERE id = $5"
+    )
+    .bind(status)
+    .bind(image_tag)
+    .bind(container_name)
+    .bind(volume_name)
+    .bind(deployment_id)
+    .execute(pool)
+    .await
+    .map_err(|e|
+    {
+        error!("Failed to advance deployment {} to {:?}: {}", deployment_id, status, e);
+        AppError::database(&e)
+    })?;
+    Ok(())
+}
+
+pub async fn touch_deployment(pool: &PgPool, deployment_id: i32) -> Result<(), AppError>
+{
+    sqlx::query("UPDATE deployments SET updated_at = NOW() WHERE id = $1 AND status IN ('building', 'creating_container', 'persisting')")
+        .bind(deployment_id)
+        .execute(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to refresh the heartbeat of deployment {}: {}", deployment_id, e);
+            AppError::database(&e)
+        })?;
+    Ok(())
+}
+
+pub async fn assign_deployment_host(pool: &PgPool, deployment_id: i32, docker_host: &str) -> Result<(), AppError>
+{
+    sqlx::query("UPDATE deployments SET docker_host = $1, updated_at = NOW() WHERE id = $2")
+        .bind(docker_host)
+        .bind(deployment_id)
+        .execute(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to assign Docker host '{}' to deployment {}: {}", docker_host, deployment_id, e);
+            AppError::database(&e)
+        })?;
+    Ok(())
+}
+
+pub async fn mark_deployment_succeeded(pool: &PgPool, deployment_id: i32, project_id: i32) -> Result<(), AppError>
+{
+    sqlx::query("UPDATE deployments SET status = 'succeeded', project_id = $1, updated_at = NOW() WHERE id = $2")
+        .bind(project_id)
+        .bind(deployment_id)
+        .execute(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to mark deployment {} as succeeded: {}", deployment_id, e);
+            AppError::database(&e)
+        })?;
+    Ok(())
+}
+
+pub async fn set_deployment_warnings(pool: &PgPool, deployment_id: i32, warnings: &[String]) -> Result<(), AppError>
+{
+    sqlx::query("UPDATE deployments SET warnings = $1, updated_at = NOW() WHERE id = $2")
+        .bind(warnings)
+        .bind(deployment_id)
+        .execute(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to record the warnings of deployment {}: {}", deployment_id, e);
+            AppError::database(&e)
+        })?;
+    Ok(())
+}
+
+pub async fn mark_deployment_failed(pool: &PgPool, deployment_id: i32, error_code: &str, error_message: &str) -> Result<(), AppError>
+{
+    sqlx::query("UPDATE deployments SET status = 'failed', error_code = $1, error_message = $2, updated_at = NOW() WHERE id = $3")
+        .bind(error_code)
+        .bind(error_message)
+        .bind(deployment_id)
+        .execute(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to mark deployment {} as failed: {}", deployment_id, e);
+            AppError::database(&e)
+        })?;
+    Ok(())
+}
+
+pub async fn get_interrupted_deployments(pool: &PgPool, stale_after_seconds: u64) -> Result<Vec<Deployment>, AppError>
+{
+    let query = format!(
+        "{} WHERE status IN ('building', 'creating_container', 'persisting') AND updated_at < NOW() - make_interval(secs => $1) ORDER BY created_at",
+        SELECT_DEPLOYMENT_FIELDS
+    );
+
+    sqlx::query_as::<_, Deployment>(&query)
+        .bind(stale_after_seconds as f64)
+        .fetch_all(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to fetch interrupted deployments: {}", e);
+            AppError::database(&e)
+        })
+}
+
+pub async fn get_deployments_by_owner(pool: &PgPool, owner: &str) -> Result<Vec<Deployment>, AppError>
+{
+    let query = format!("{} WHERE owner = $1 ORDER BY created_at DESC LIMIT 50", SELECT_DEPLOYMENT_FIELDS);
+    sqlx::query_as::<_, Deployment>(&query)
+        .bind(owner)
+        .fetch_all(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to fetch deployments for owner '{}': {}", owner, e);
+            AppError::database(&e)
+        })
+}
+
+pub async fn get_recent_deployments_for_project(pool: &PgPool, project_name: &str, limit: i64) -> Result<Vec<Deployment>, AppError>
+{
+    let query = format!("{} WHERE project_name = $1 ORDER BY created_at DESC LIMIT $2", SELECT_DEPLOYMENT_FIELDS);
+    sqlx::query_as::<_, Deployment>(&query)
+        .bind(project_name)
+        .bind(limit)
+        .fetch_all(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to fetch deployments for project '{}': {}", project_name, e);
+            AppError::database(&e)
+        })
+}
+
+pub async fn get_deployment_for_owner(
+    pool: &PgPool,
+    deployment_id: i32,
+    owner: &str,
+    is_admin: bool,
+) -> Result<Option<Deployment>, AppError>
+{
+    if is_admin
+    {
+        let query = format!("{} WHERE id = $1", SELECT_DEPLOYMENT_FIELDS);
+        return sqlx::query_as::<_, Deployment>(&query)
+            .bind(deployment_id)
+            .fetch_optional(pool)
+            .await
+            .map_err(|e|
+            {
+                error!("Admin failed to fetch deployment {}: {}", deployment_id, e);
+                AppError::database(&e)
+            });
+    }
+
+    let query = format!("{} WHERE id = $1 AND owner = $2", SELECT_DEPLOYMENT_FIELDS);
+    sqlx::query_as::<_, Deployment>(&query)
+        .bind(deployment_id)
+        .bind(owner)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to fetch deployment {} for owner '{}': {}", deployment_id, owner, e);
+            AppError::database(&e)
+        })
+}
+
+pub async fn record_deployment_timings(pool: &PgPool, deployment_id: i32, timings: &DeployTimings) -> Result<(), AppError>
+{
+    let Some(source_type) = timings.source_type else { return Ok(()) };
+
+    for (phase, duration) in &timings.phases
+    {
+        sqlx::query(
+            "INSERT INTO deployment_timings (deployment_id, phase, source_type, duration_ms) VALUES ($1, $2, $3, $4)
+             ON CONFLICT (deployment_id, phase) DO UPDATE SET duration_ms = EXCLUDED.duration_ms, recorded_at = NOW()"
+        )
+            .bind(deployment_id)
+            .bind(phase)
+            .bind(source_type)
+            .bind(duration.as_millis() as i64)
+            .execute(pool)
+            .await
+            .map_err(|e|
+            {
+                error!("Failed to record the timings of deployment {}: {}", deployment_id, e);
+                AppError::database(&e)
+            })?;
+    }
+
+    Ok(())
+}
+
+pub async fn get_deploy_stats(
+    pool: &PgPool,
+    from: OffsetDateTime,
+    to: OffsetDateTime,
+    source_type: Option<ProjectSourceType>,
+) -> Result<Vec<DeployPhaseStats>, AppError>
+{
+    sqlx::query_as::<_, DeployPhaseStats>(
+        "SELECT t.phase, t.source_type, COUNT(*) AS samples,
+                percentile_cont(0.5) WITHIN GROUP (ORDER BY t.duration_ms) AS p50_ms,
+                percentile_cont(0.9) WITHIN GROUP (ORDER BY t.duration_ms) AS p90_ms,
+                percentile_cont(0.99) WITHIN GROUP (ORDER BY t.duration_ms) AS p99_ms,
+                MAX(t.duration_ms) AS max_ms
+         FROM deployment_timings t
+         JOIN deployments d ON d.id = t.deployment_id
+         WHERE d.status = 'succeeded' AND t.recorded_at >= $1 AND t.recorded_at < $2
+           AND ($3::project_source_type IS NULL OR t.source_type = $3)
+         GROUP BY t.source_type, t.phase
+         ORDER BY t.source_type, t.phase"
+    )
+        .bind(from)
+        .bind(to)
+        .bind(source_type)
+        .fetch_all(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to compute deploy stats: {}", e);
+            AppError::database(&e)
+        })
+}
@@ -3,35 +3,130 @@ use bollard::errors::Error as BollardError;
 use bollard::secret::{ContainerState, ContainerStatsResponse, Mount, MountTypeEnum, ResourcesUlimits, RestartPolicy};
 use bollard::models::VolumeCreateOptions;
 use bollard::Docker;
-use bollard::models::{ContainerCreateBody, HostConfig};
+use bollard::models::{ContainerCreateBody, CreateImageInfo, HostConfig, NetworkCreateRequest};
 use bollard::query_parameters::
 {
-    BuildImageOptions, CreateContainerOptionsBuilder, CreateImageOptions, InspectContainerOptions, ListContainersOptions, LogsOptions, RemoveContainerOptions, RemoveImageOptions, RemoveVolumeOptions, RestartContainerOptions, StartContainerOptions, StatsOptions, StopContainerOptions
+    BuildImageOptions, CreateContainerOptionsBuilder, CreateImageOptionsBuilder, DataUsageOptions, InspectContainerOptions, InspectNetworkOptions, ListContainersOptions, ListImagesOptions, LogsOptions, RemoveContainerOptions, RemoveImageOptions, RemoveVolumeOptions, RestartContainerOptions, StartContainerOptions, StatsOptions, StopContainerOptions, TagImageOptions
 };
 use flate2::write::GzEncoder;
 use flate2::Compression;
 use futures::stream::StreamExt;
+use serde::Deserialize;
 use tar::Builder;
+use tokio::io::AsyncReadExt;
 use tokio::process::Command;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::path::Path;
 use std::process::Stdio;
+use time::{OffsetDateTime, format_description::well_known::Rfc3339};
 use tracing::{debug, error, info, warn};
 
+use crate::config::DockerHostConfig;
 use crate::error::{AppError, ProjectErrorCode};
-use crate::model::project::{GlobalMetrics, ProjectMetrics};
+use crate::model::project::{ContainerTiming, DockerHostCapacity, DockerPlatform, EgressPolicy, GlobalMetrics, ProjectMetrics, ProjectStatus, RouteMiddlewares};
+use crate::model::database::DatabaseDetailsResponse;
+use crate::model::deployment::PullProgress;
+use crate::services::env_template_service;
+use crate::model::scan::{ScanFinding, Severity};
 use bollard::models::ContainerInspectResponse;
 
-pub async fn pull_image(docker: &Docker, image_url: &str, credentials: Option<DockerCredentials>) -> Result<(), BollardError> 
+const BUILD_CACHE_REPOSITORY: &str = "hangar-cache";
+
+// Posé sur chaque image construite par la plateforme : les nettoyages ne touchent jamais aux autres.
+const MANAGED_LABEL: &str = "hangar.managed";
+
+pub const CONTAINER_PORT: u16 = 80;
+
+pub const GRYPE_BINARY: &str = "grype";
+
+// L'ID désigne un conteneur exact mais change à chaque recréation ; le nom est stable mais réutilisé.
+#[derive(Debug, Clone, Copy)]
+pub enum ContainerRef<'a>
 {
-    let options = Some(CreateImageOptions 
+    ByName(&'a str),
+    ById(&'a str),
+}
+
+impl ContainerRef<'_>
+{
+    pub fn as_str(&self) -> &str
     {
-        from_image: Some(image_url.to_string()),
-        ..Default::default()
-    });
+        match self
+        {
+            ContainerRef::ByName(name) => name,
+            ContainerRef::ById(id) => id,
+        }
+    }
+}
+
+impl std::fmt::Display for ContainerRef<'_>
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    {
+        match self
+        {
+            ContainerRef::ByName(name) => write!(f, "'{}'", name),
+            ContainerRef::ById(id) => write!(f, "ID {}", &id[..id.len().min(12)]),
+        }
+    }
+}
+
+pub struct CreatedContainer
+{
+    pub id: String,
+    pub volume_name: Option<String>,
+    pub warnings: Vec<String>,
+}
+
+// Rien n'est envoyé avant le premier appel : un démon injoignable n'apparaît qu'au contrôle de démarrage.
+pub fn connect(host: &DockerHostConfig, config: &crate::config::Config) -> Result<Docker, BollardError>
+{
+    let timeout = config.docker_api_timeout_seconds;
+
+    match (host.url.as_deref(), host.tls_cert_dir.as_deref())
+    {
+        (None, _) => Docker::connect_with_local_defaults().map(|docker| docker.with_timeout(std::time::Duration::from_secs(timeout))),
+        (Some(url), _) if url.starts_with("unix://") => Docker::connect_with_unix(url, timeout, bollard::API_DEFAULT_VERSION),
+        (Some(url), Some(cert_dir)) => Docker::connect_with_ssl(
+            url,
+            &cert_dir.join("key.pem"),
+            &cert_dir.join("cert.pem"),
+            &cert_dir.join("ca.pem"),
+            timeout,
+            bollard::API_DEFAULT_VERSION,
+        ),
+        (Some(url), None) => Docker::connect_with_http(url, timeout, bollard::API_DEFAULT_VERSION),
+    }
+}
+
+const PULL_PROGRESS_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+pub struct PullFailure
+{
+    pub error: BollardError,
+    pub progress: PullProgress,
+}
+
+pub async fn pull_image(
+    docker: &Docker,
+    image_url: &str,
+    platform: Option<&DockerPlatform>,
+    credentials: Option<DockerCredentials>,
+    mut on_progress: impl FnMut(&PullProgress),
+) -> Result<(), PullFailure>
+{
+    let mut builder = CreateImageOptionsBuilder::default().from_image(image_url);
+    if let Some(platform) = platform
+    {
+        builder = builder.platform(&platform.to_string());
+    }
+    let options = Some(builder.build());
 
     let mut stream = docker.create_image(options, None, credentials);
 
+    let mut progress = PullProgress::default();
+    let mut last_report = std::time::Instant::now();
+
     info!("Pulling image {}", image_url);
     while let Some(result) = stream.next().await 
     {
@@ -39,77 +134,393 @@ pub async fn pull_image(docker: &Docker, image_url: &str, credentials: Option<Do
         {
             Ok(info) => 
             {
-                if let Some(error_detail) = info.error_detail
-                    && let Some(message) = error_detail.message
-                        && (message.to_lowercase().contains("unauthorized") || message.to_lowercase().contains("authentication required")) 
-                        {
-                            warn!("Authentication error during image pull for '{}': {}", image_url, message);
-                        }
+                if let Err(message) = apply_pull_message(&mut progress, info)
+                {
+                    if message.to_lowercase().contains("unauthorized") || message.to_lowercase().contains("authentication required")
+                    {
+                        warn!("Authentication error during image pull for '{}': {}", image_url, message);
+                    }
+                    on_progress(&progress);
+                    return Err(PullFailure { error: BollardError::DockerStreamError { error: message }, progress });
+                }
+
+                if last_report.elapsed() >= PULL_PROGRESS_INTERVAL
+                {
+                    on_progress(&progress);
+                    last_report = std::time::Instant::now();
+                }
             }
             Err(e) => 
             {
-                return Err(e);
+                on_progress(&progress);
+                return Err(PullFailure { error: e, progress });
             }
         }
     }
+    on_progress(&progress);
     info!("Image '{}' pulled successfully.", image_url);
     Ok(())
 }
 
+// Seuls les messages de couche portent un progressDetail ; "Pulling from ..." a un id (le tag) mais n'en est pas une.
+fn apply_pull_message(progress: &mut PullProgress, info: CreateImageInfo) -> Result<(), String>
+{
+    if let Some(message) = info.error_detail.and_then(|detail| detail.message)
+    {
+        progress.error = Some(message.clone());
+        return Err(message);
+    }
+
+    if let (Some(layer_id), Some(status), Some(detail)) = (info.id.as_deref(), info.status.as_deref(), info.progress_detail)
+    {
+        progress.record(layer_id, status, detail.current, detail.total);
+    }
+    Ok(())
+}
+
+
+#[derive(Deserialize)]
+struct GrypeOutput
+{
+    #[serde(default)]
+    matches: Vec<GrypeMatch>,
+}
+
+#[derive(Deserialize)]
+struct GrypeMatch
+{
+    vulnerability: GrypeVulnerability,
+    artifact: GrypeArtifact,
+}
+
+#[derive(Deserialize)]
+struct GrypeVulnerability
+{
+    id: String,
+    severity: String,
+    #[serde(default)]
+    fix: Option<GrypeFix>,
+}
+
+#[derive(Deserialize)]
+struct GrypeFix
+{
+    #[serde(default)]
+    versions: Vec<String>,
+}
 
-pub async fn scan_image_with_grype(image_url: &str, config: &crate::config::Config) -> Result<(), AppError> 
+#[derive(Deserialize)]
+struct GrypeArtifact
+{
+    name: String,
+    version: String,
+}
+
+// `None` quand le scan est désactivé. Seuils et dérogations sont appliqués par l'appelant.
+pub async fn scan_image_with_grype(image_url: &str, host: &DockerHostConfig, config: &crate::config::Config) -> Result<Option<Vec<ScanFinding>>, AppError> 
 {
     if !config.grype_enabled 
     {
         warn!("Grype scan is disabled via GRYPE_ENABLED=false. Skipping security scan for image '{}'.", image_url);
-        return Ok(());
+        return Ok(None);
     }
 
     info!("Scanning image '{}' with Grype...", image_url);
+    let scan_started = std::time::Instant::now();
 
-    let mut command = Command::new("grype");
-    command
+    let mut child = Command::new(GRYPE_BINARY)
+        .envs(host.client_env())
         .arg(image_url)
         .arg("--only-fixed")
-        .arg("--fail-on")
-        .arg(&config.grype_fail_on_severity)
+        .arg("--output")
+        .arg("json")
         .stdout(Stdio::piped())
-        .stderr(Stdio::piped());
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e|
+        {
+            error!("Failed to execute grype command: {}", e);
+            AppError::InternalServerError
+        })?;
+
+    let (Some(mut stdout), Some(mut stderr)) = (child.stdout.take(), child.stderr.take())
+    else
+    {
+        return Err(AppError::InternalServerError);
+    };
 
-    let output = command.output().await.map_err(|e| 
+    let mut report_bytes = Vec::new();
+    let mut error_bytes = Vec::new();
+    let run = tokio::time::timeout(std::time::Duration::from_secs(config.scan_timeout_seconds), async
     {
-        error!("Failed to execute grype command: {}", e);
+        let (stdout_read, stderr_read, status) = tokio::join!(
+            stdout.read_to_end(&mut report_bytes),
+            stderr.read_to_end(&mut error_bytes),
+            child.wait(),
+        );
+        stdout_read.and(stderr_read).and(status)
+    }).await;
+
+    let duration_ms = scan_started.elapsed().as_millis() as u64;
+
+    let status = match run
+    {
+        Ok(status) => status.map_err(|e|
+        {
+            error!("Failed to run grype on image '{}': {}", image_url, e);
+            AppError::InternalServerError
+        })?,
+        Err(_) =>
+        {
+            // kill_on_drop reste le filet de sécurité si le kill explicite échoue.
+            if let Err(e) = child.kill().await
+            {
+                warn!("Could not kill the grype process scanning '{}': {}", image_url, e);
+            }
+            error!(image = image_url, duration_ms, "Grype scan timed out after {}s.", config.scan_timeout_seconds);
+            return Err(ProjectErrorCode::ImageScanTimedOut(config.scan_timeout_seconds).into());
+        }
+    };
+
+    if !status.success() 
+    {
+        error!("Grype failed to scan image '{}': {}", image_url, String::from_utf8_lossy(&error_bytes).trim());
+        return Err(AppError::InternalServerError);
+    }
+
+    let report: GrypeOutput = serde_json::from_slice(&report_bytes).map_err(|e|
+    {
+        error!("Failed to parse Grype report for image '{}': {}", image_url, e);
         AppError::InternalServerError
     })?;
 
-    if !output.status.success() 
+    let findings = report.matches.into_iter().map(|m| ScanFinding
+    {
+        severity: m.vulnerability.severity.parse().unwrap_or(Severity::Unknown),
+        vulnerability_id: m.vulnerability.id,
+        package: m.artifact.name,
+        installed_version: m.artifact.version,
+        fixed_in: m.vulnerability.fix.map(|fix| fix.versions).unwrap_or_default(),
+    }).collect::<Vec<_>>();
+
+    info!(image = image_url, duration_ms, findings = findings.len(), "Grype scan of '{}' finished.", image_url);
+    Ok(Some(findings))
+}
+
+// Un échec de génération ne fait jamais échouer un déploiement : l'erreur est conservée comme raison.
+pub async fn generate_sbom_with_syft(image_url: &str, host: &DockerHostConfig, config: &crate::config::Config) -> Result<Vec<u8>, String>
+{
+    info!("Generating SBOM for image '{}' with Syft...", image_url);
+
+    let output = Command::new(&config.syft_path)
+        .envs(host.client_env())
+        .arg(image_url)
+        .arg("-o")
+        .arg("cyclonedx-json")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .await
+        .map_err(|e| format!("Failed to execute syft: {}", e))?;
+
+    if !output.status.success()
     {
-        warn!("Grype found vulnerabilities in image '{}'", image_url);
-        let report = String::from_utf8_lossy(&output.stdout).trim().to_string();
-        return Err(ProjectErrorCode::ImageScanFailed(report).into());
+        return Err(format!("Syft exited with {}: {}", output.status, String::from_utf8_lossy(&output.stderr).trim()));
     }
 
-    info!("Grype scan passed for image '{}'.", image_url);
-    Ok(())
+    Ok(output.stdout)
+}
+
+// Recalculées à chaque création de conteneur : un renommage n'atteint l'application qu'à la recréation suivante.
+pub fn platform_env_vars(config: &crate::config::Config, project_name: &str, owner: &str) -> BTreeMap<String, String>
+{
+    BTreeMap::from([
+        ("HANGAR_PROJECT_NAME".to_string(), project_name.to_string()),
+        ("HANGAR_PUBLIC_URL".to_string(), config.project_public_url(project_name)),
+        ("HANGAR_OWNER".to_string(), owner.to_string()),
+    ])
+}
+
+fn project_hostnames(config: &crate::config::Config, project_name: &str, custom_domains: &[String]) -> Vec<String>
+{
+    std::iter::once(config.project_hostname(project_name))
+        .chain(custom_domains.iter().cloned())
+        .collect()
+}
+
+pub fn traefik_labels(
+    config: &crate::config::Config,
+    project_name: &str,
+    hostnames: &[String],
+    middlewares: &RouteMiddlewares,
+) -> HashMap<String, String>
+{
+    let rule = hostnames
+        .iter()
+        .map(|host| format!("Host(`{}`)", host))
+        .collect::<Vec<_>>()
+        .join(" || ");
+
+    let mut labels = HashMap::new();
+    labels.insert("app".to_string(), config.app_prefix.clone());
+    labels.insert("traefik.enable".to_string(), "true".to_string());
+    labels.insert(format!("traefik.http.routers.{}.rule", project_name), rule);
+    labels.insert(format!("traefik.http.routers.{}.entrypoints", project_name), config.traefik_entrypoint.clone());
+    labels.insert(format!("traefik.http.services.{}.loadbalancer.server.port", project_name), CONTAINER_PORT.to_string());
+
+    if config.traefik_tls_enabled
+    {
+        labels.insert(format!("traefik.http.routers.{}.tls", project_name), "true".to_string());
+        labels.insert(format!("traefik.http.routers.{}.tls.certresolver", project_name), config.traefik_cert_resolver.clone());
+    }
+
+    // L'ordre compte : les IP refusées ne doivent pas consommer le quota de requêtes.
+    let mut chain = Vec::new();
+
+    if let Some(ranges) = &middlewares.ip_allowlist
+    {
+        let middleware = format!("{}-ipallow", project_name);
+        labels.insert(format!("traefik.http.middlewares.{}.ipwhitelist.sourcerange", middleware), ranges.join(","));
+        chain.push(middleware);
+    }
+
+    if let Some(limit) = middlewares.rate_limit
+    {
+        let middleware = format!("{}-ratelimit", project_name);
+        labels.insert(format!("traefik.http.middlewares.{}.ratelimit.average", middleware), limit.average.to_string());
+        labels.insert(format!("traefik.http.middlewares.{}.ratelimit.burst", middleware), limit.burst.to_string());
+        chain.push(middleware);
+    }
+
+    if !middlewares.routing.response_headers.is_empty()
+    {
+        let middleware = format!("{}-headers", project_name);
+        for (name, value) in &middlewares.routing.response_headers
+        {
+            labels.insert(format!("traefik.http.middlewares.{}.headers.customresponseheaders.{}", middleware, name), value.clone());
+        }
+        chain.push(middleware);
+    }
+
+    if !chain.is_empty()
+    {
+        labels.insert(format!("traefik.http.routers.{}.middlewares", project_name), chain.join(","));
+    }
+
+    if middlewares.routing.sticky_sessions
+    {
+        let cookie = format!("traefik.http.services.{}.loadbalancer.sticky.cookie", project_name);
+        labels.insert(cookie.clone(), "true".to_string());
+        labels.insert(format!("{}.name", cookie), format!("{}_sticky", project_name.replace('-', "_")));
+        labels.insert(format!("{}.httponly", cookie), "true".to_string());
+        labels.insert(format!("{}.secure", cookie), config.traefik_tls_enabled.to_string());
+    }
+
+    if let Some(healthcheck) = &middlewares.healthcheck
+    {
+        labels.insert(format!("traefik.http.services.{}.loadbalancer.healthcheck.path", project_name), healthcheck.path.clone());
+        labels.insert(format!("traefik.http.services.{}.loadbalancer.healthcheck.interval", project_name), format!("{}s", healthcheck.interval_seconds));
+        if let Some(port) = healthcheck.port
+        {
+            labels.insert(format!("traefik.http.services.{}.loadbalancer.healthcheck.port", project_name), port.to_string());
+        }
+    }
+
+    labels
+}
+
+// Sans EGRESS_INTERNAL_NETWORK, un projet `internal_only` est refusé plutôt que de sortir sur Internet.
+pub fn project_network(config: &crate::config::Config, policy: EgressPolicy) -> Result<&str, AppError>
+{
+    match policy
+    {
+        EgressPolicy::Open => Ok(&config.docker_network),
+        EgressPolicy::InternalOnly => config.egress_internal_network.as_deref().ok_or_else(||
+        {
+            error!("A project requires the 'internal_only' egress policy but EGRESS_INTERNAL_NETWORK is not set.");
+            AppError::InternalServerError
+        }),
+    }
+}
+
+pub async fn ensure_internal_network(docker: &Docker, network: &str) -> Result<(), AppError>
+{
+    match docker.inspect_network(network, None::<InspectNetworkOptions>).await
+    {
+        Ok(existing) =>
+        {
+            if existing.internal != Some(true)
+            {
+                error!("Docker network '{}' exists but is not internal; refusing to use it for egress restriction.", network);
+                return Err(AppError::InternalServerError);
+            }
+            Ok(())
+        }
+        Err(BollardError::DockerResponseServerError { status_code: 404, .. }) =>
+        {
+            let request = NetworkCreateRequest
+            {
+                name: network.to_string(),
+                internal: Some(true),
+                ..Default::default()
+            };
+            docker.create_network(request).await.map_err(|e|
+            {
+                error!("Failed to create internal Docker network '{}': {}", network, e);
+                AppError::docker(&e)
+            })?;
+            info!("Created internal Docker network '{}'.", network);
+            Ok(())
+        }
+        Err(e) =>
+        {
+            error!("Failed to inspect Docker network '{}': {}", network, e);
+            Err(AppError::docker(&e))
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct ContainerSpec<'a>
+{
+    pub container_name: &'a str,
+    pub project_name: &'a str,
+    pub owner: &'a str,
+    pub image_identifier: &'a str,
+    pub env_vars: Option<&'a HashMap<String, String>>,
+    pub persistent_volume_path: Option<&'a str>,
+    pub volume_name: Option<&'a str>,
+    pub custom_domains: &'a [String],
+    pub middlewares: &'a RouteMiddlewares,
+    pub egress_policy: EgressPolicy,
+    pub database: Option<&'a DatabaseDetailsResponse>,
 }
 
 pub async fn create_project_container(
     docker: &Docker,
-    container_name: &str,
-    project_name: &str,
-    image_identifier: &str,
     config: &crate::config::Config,
-    env_vars: &Option<HashMap<String, String>>,
-    persistent_volume_path: &Option<String>,
-) -> Result<Option<String>, AppError>
+    spec: ContainerSpec<'_>,
+) -> Result<CreatedContainer, AppError>
 {
-    let hostname = format!("{}.{}", project_name, &config.app_domain_suffix);
+    let ContainerSpec
+    {
+        container_name, project_name, owner, image_identifier, env_vars, persistent_volume_path,
+        volume_name, custom_domains, middlewares, egress_policy, database,
+    } = spec;
+
+    let network = project_network(config, egress_policy)?.to_string();
+    if egress_policy == EgressPolicy::InternalOnly
+    {
+        ensure_internal_network(docker, &network).await?;
+    }
 
     let mut mounts = vec![];
     let mut volume_name_created: Option<String> = None;
     if let Some(path) = persistent_volume_path
     {
-        let volume_name = format!("hangar-data-{}", project_name);
+        // Un projet renommé garde le volume créé sous son ancien nom.
+        let volume_name = volume_name.map_or_else(|| format!("hangar-data-{}", project_name), str::to_string);
 
         let options = VolumeCreateOptions
         {
@@ -127,7 +538,7 @@ pub async fn create_project_container(
 
         mounts.push(Mount
         {
-            target: Some(path.clone()),
+            target: Some(path.to_string()),
             source: Some(volume_name),
             typ: Some(MountTypeEnum::VOLUME),
             ..Default::default()
@@ -144,7 +555,7 @@ pub async fn create_project_container(
 
         memory: Some(config.container_memory_mb * 1024 * 1024),
         cpu_quota: Some(config.container_cpu_quota),
-        network_mode: Some(config.docker_network.clone()),
+        network_mode: Some(network.clone()),
         security_opt: Some(vec![
             "no-new-privileges:true".to_string(),
             "apparmor:docker-default".to_string()
@@ -166,18 +577,22 @@ pub async fn create_project_container(
         ..Default::default()
     };
 
-    let env = env_vars.as_ref().map(|vars|
-    {
-        vars.iter().map(|(k, v)| format!("{}={}", k, v)).collect()
-    });
-
-    let mut labels = HashMap::new();
-    labels.insert("app".to_string(), config.app_prefix.clone());
-    labels.insert("traefik.enable".to_string(), "true".to_string());
-    labels.insert(format!("traefik.http.routers.{}.rule", project_name), format!("Host(`{}`)", hostname));
-    labels.insert(format!("traefik.http.routers.{}.entrypoints", project_name), config.traefik_entrypoint.clone());
-    labels.insert(format!("traefik.http.routers.{}.tls.certresolver", project_name), config.traefik_cert_resolver.clone());
-    labels.insert(format!("traefik.http.services.{}.loadbalancer.server.port", project_name), "80".to_string());
+    // Les variables de la plateforme passent en dernier : un projet créé avant la réservation
+    // du préfixe HANGAR_ ne peut pas les masquer.
+    // Les espaces réservés sont résolus ici seulement : les valeurs stockées les gardent, si bien
+    // qu'un mot de passe changé est repris à la prochaine recréation du conteneur.
+    let template_context = env_template_service::context(config, project_name, owner, database);
+    let user_env = env_vars.into_iter()
+        .flatten()
+        .filter(|(k, _)| !k.to_uppercase().starts_with(crate::services::validation_service::PLATFORM_ENV_PREFIX));
+    let env = Some(user_env
+        .map(|(k, v)| format!("{}={}", k, env_template_service::render(v, &template_context)))
+        .chain(platform_env_vars(config, project_name, owner).iter().map(|(k, v)| format!("{}={}", k, v)))
+        .collect());
+
+    let mut labels = traefik_labels(config, project_name, &project_hostnames(config, project_name, custom_domains), middlewares);
+    // Traefik est relié à plusieurs réseaux : on lui indique celui par lequel joindre le conteneur.
+    labels.insert("traefik.docker.network".to_string(), network);
 
     let config = ContainerCreateBody 
     {
@@ -188,7 +603,7 @@ pub async fn create_project_container(
         ..Default::default()
     };
 
-    let options = Some(CreateContainerOptionsBuilder::new().name(&container_name).build());
+    let options = Some(CreateContainerOptionsBuilder::new().name(container_name).build());
 
     let response = docker.create_container(options, config).await.map_err(|e| 
     {
@@ -212,7 +627,7 @@ pub async fn create_project_container(
         ProjectErrorCode::ContainerCreationFailed
     })?;
 
-    docker.start_container(&container_name, None::<StartContainerOptions>).await.map_err(|e| 
+    docker.start_container(container_name, None::<StartContainerOptions>).await.map_err(|e| 
     {
         error!("Failed to start container '{}': {}", container_name, e);
         
@@ -248,7 +663,70 @@ pub async fn create_project_container(
     })?;
 
     info!("Container '{}' created and started with ID: {}", container_name, response.id);
-    Ok(volume_name_created)
+    for warning in &response.warnings
+    {
+        warn!("Docker warning while creating container '{}': {}", container_name, warning);
+    }
+    Ok(CreatedContainer { id: response.id, volume_name: volume_name_created, warnings: response.warnings })
+}
+
+// Traefik ignore les conteneurs arrêtés : tant que le projet est arrêté, ce conteneur sert seul ses noms d'hôte.
+pub async fn create_maintenance_container(
+    docker: &Docker,
+    container_name: &str,
+    project_name: &str,
+    custom_domains: &[String],
+    config: &crate::config::Config,
+) -> Result<(), AppError>
+{
+    if get_image_digest(docker, &config.maintenance_page_image).await?.is_none()
+    {
+        pull_image(docker, &config.maintenance_page_image, None, None, |_| {}).await.map_err(|failure|
+        {
+            error!("Failed to pull maintenance page image '{}': {}", config.maintenance_page_image, failure.error);
+            AppError::docker(&failure.error)
+        })?;
+    }
+
+    remove_container(docker, container_name).await?;
+
+    let host_config = HostConfig
+    {
+        restart_policy: Some(RestartPolicy
+        {
+            name: Some(bollard::secret::RestartPolicyNameEnum::UNLESS_STOPPED),
+            maximum_retry_count: None,
+        }),
+        memory: Some(32 * 1024 * 1024),
+        network_mode: Some(config.docker_network.clone()),
+        security_opt: Some(vec!["no-new-privileges:true".to_string()]),
+        privileged: Some(false),
+        pids_limit: Some(64),
+        ..Default::default()
+    };
+
+    let labels = traefik_labels(config, project_name, &project_hostnames(config, project_name, custom_domains), &RouteMiddlewares::default());
+
+    let body = ContainerCreateBody
+    {
+        image: Some(config.maintenance_page_image.clone()),
+        host_config: Some(host_config),
+        labels: Some(labels),
+        ..Default::default()
+    };
+
+    let options = Some(CreateContainerOptionsBuilder::new().name(container_name).build());
+
+    docker.create_container(options, body).await.map_err(|e|
+    {
+        error!("Failed to create maintenance container '{}': {}", container_name, e);
+        AppError::docker(&e)
+    })?;
+
+    start_container_by_name(docker, container_name).await?;
+
+    info!("Maintenance page container '{}' started for project '{}'.", container_name, project_name);
+    Ok(())
 }
 
 pub async fn remove_container(docker: &Docker, container_name: &str) -> Result<(), AppError> 
@@ -278,7 +756,7 @@ pub async fn remove_container(docker: &Docker, container_name: &str) -> Result<(
         Err(e) =>
         {
             error!("Error removing container {}: {}", container_name, e);
-            return Err(AppError::InternalServerError);
+            return Err(AppError::docker(&e));
         }
     }
 
@@ -295,15 +773,23 @@ pub async fn remove_image(docker: &Docker, image_url: &str) -> Result<(), AppErr
         force: true,
         ..Default::default()
     });
-    if let Err(e) = docker.remove_image(image_url, options, None).await 
-    {
-        error!("Could not remove image '{}': {}", image_url, e);
-        Err(AppError::InternalServerError)
-    } 
-    else 
+    match docker.remove_image(image_url, options, None).await 
     {
-        info!("Image {} successfully removed", image_url);
-        Ok(())
+        Ok(_) =>
+        {
+            info!("Image {} successfully removed", image_url);
+            Ok(())
+        }
+        Err(bollard::errors::Error::DockerResponseServerError { status_code: 404, .. }) =>
+        {
+            warn!("Image {} not found during removal. It might have been deleted already.", image_url);
+            Ok(())
+        }
+        Err(e) =>
+        {
+            error!("Could not remove image '{}': {}", image_url, e);
+            Err(AppError::docker(&e))
+        }
     }
 }
 
@@ -326,26 +812,77 @@ pub async fn remove_volume_by_name(docker: &Docker, volume_name: &str) -> Result
         Err(e) =>
         {
             error!("Error removing volume {}: {}", volume_name, e);
-            Err(AppError::InternalServerError)
+            Err(AppError::docker(&e))
         }
     }
 }
 
-pub async fn get_container_status(docker: &Docker, container_name: &str) -> Result<Option<ContainerState>, AppError> 
+pub async fn get_container_status(docker: &Docker, container: ContainerRef<'_>) -> Result<Option<ContainerState>, AppError> 
 {
-    match docker.inspect_container(container_name, None::<InspectContainerOptions>).await 
+    Ok(inspect_container_details(docker, container).await?.and_then(|details| details.state))
+}
+
+// En marche dès qu'un réplica tourne hors d'une boucle de redémarrage ; sinon arrêté ou planté selon `intended_running`.
+pub async fn observe_project_status(docker: &Docker, container_names: &[String], intended_running: bool) -> Result<ProjectStatus, AppError>
+{
+    let mut found = false;
+
+    for container_name in container_names
     {
-        Ok(details) => Ok(details.state),
-        Err(bollard::errors::Error::DockerResponseServerError { status_code: 404, .. }) => 
+        let Some(state) = get_container_status(docker, ContainerRef::ByName(container_name)).await?
+        else
         {
-            Ok(None)
-        },
-        Err(e) => 
+            continue;
+        };
+        found = true;
+
+        if state.running.unwrap_or(false) && !state.restarting.unwrap_or(false)
         {
-            error!("Failed to inspect container '{}': {}", container_name, e);
-            Err(AppError::InternalServerError)
+            return Ok(ProjectStatus::Running);
         }
     }
+
+    Ok(match (found, intended_running)
+    {
+        (false, _) => ProjectStatus::Missing,
+        (true, true) => ProjectStatus::Crashed,
+        (true, false) => ProjectStatus::Stopped,
+    })
+}
+
+pub fn container_timing(state: &ContainerState, now: OffsetDateTime) -> ContainerTiming
+{
+    let started_at = state.started_at.as_deref().and_then(parse_docker_timestamp);
+    let finished_at = state.finished_at.as_deref().and_then(parse_docker_timestamp);
+    let elapsed_since = |since: OffsetDateTime| (now - since).whole_seconds().max(0);
+
+    if state.running.unwrap_or(false)
+    {
+        ContainerTiming
+        {
+            started_at,
+            uptime_seconds: started_at.map(elapsed_since),
+            ..Default::default()
+        }
+    }
+    else
+    {
+        ContainerTiming
+        {
+            started_at,
+            finished_at,
+            downtime_seconds: finished_at.map(elapsed_since),
+            ..Default::default()
+        }
+    }
+}
+
+// Docker renvoie `0001-01-01T00:00:00Z` pour un événement qui n'a jamais eu lieu.
+fn parse_docker_timestamp(value: &str) -> Option<OffsetDateTime>
+{
+    OffsetDateTime::parse(value, &Rfc3339)
+        .ok()
+        .filter(|timestamp| timestamp.unix_timestamp() > 0)
 }
 
 pub async fn start_container_by_name(docker: &Docker, container_name: &str) -> Result<(), AppError> 
@@ -353,7 +890,7 @@ pub async fn start_container_by_name(docker: &Docker, container_name: &str) -> R
     docker.start_container(container_name, None::<StartContainerOptions>).await.map_err(|e| 
     {
         error!("Failed to start container '{}': {}", container_name, e);
-        AppError::InternalServerError
+        AppError::docker(&e)
     })
 }
 
@@ -362,7 +899,7 @@ pub async fn stop_container_by_name(docker: &Docker, container_name: &str) -> Re
     docker.stop_container(container_name, None::<StopContainerOptions>).await.map_err(|e| 
     {
         error!("Failed to stop container '{}': {}", container_name, e);
-        AppError::InternalServerError
+        AppError::docker(&e)
     })
 }
 
@@ -371,13 +908,13 @@ pub async fn restart_container_by_name(docker: &Docker, container_name: &str) ->
     docker.restart_container(container_name, None::<RestartContainerOptions>).await.map_err(|e| 
     {
         error!("Failed to restart container '{}': {}", container_name, e);
-        AppError::InternalServerError
+        AppError::docker(&e)
     })
 }
 
-pub async fn get_container_logs(docker: &Docker, container_name: &str, tail: &str) -> Result<String, AppError> 
+pub async fn get_container_logs(docker: &Docker, container: ContainerRef<'_>, tail: &str) -> Result<String, AppError> 
 {
-    info!("Fetching logs for container '{}' with tail '{}'", container_name, tail);
+    info!("Fetching logs for container {} with tail '{}'", container, tail);
     const MAX_LOG_SIZE: usize = 10 * 1024 * 1024; // 10 MB
 
     let options = Some(LogsOptions 
@@ -389,7 +926,7 @@ pub async fn get_container_logs(docker: &Docker, container_name: &str, tail: &st
         ..Default::default()
     });
 
-    let mut stream = docker.logs(container_name, options);
+    let mut stream = docker.logs(container.as_str(), options);
 
     let mut log_entries = Vec::new();
     let mut total_size = 0;
@@ -413,7 +950,7 @@ pub async fn get_container_logs(docker: &Docker, container_name: &str, tail: &st
             }
             Err(e) => 
             {
-                error!("Error streaming logs for container '{}': {}", container_name, e);
+                error!("Error streaming logs for container {}: {}", container, e);
             }
         }
     }
@@ -421,9 +958,9 @@ pub async fn get_container_logs(docker: &Docker, container_name: &str, tail: &st
     Ok(log_entries.join(""))
 }
 
-pub async fn get_container_metrics(docker: &Docker, container_name: &str) -> Result<ProjectMetrics, AppError> 
+pub async fn get_container_metrics(docker: &Docker, container: ContainerRef<'_>) -> Result<ProjectMetrics, AppError> 
 {
-    let mut stream = docker.stats(container_name, Some(StatsOptions 
+    let mut stream = docker.stats(container.as_str(), Some(StatsOptions 
     { 
         stream: false, 
         ..Default::default() 
@@ -435,7 +972,7 @@ pub async fn get_container_metrics(docker: &Docker, container_name: &str) -> Res
         {
             Ok(stats) => 
             {
-                debug!("Received stats for container '{}': {:?}", container_name, stats);
+                debug!("Received stats for container {}: {:?}", container, stats);
                 
                 let cpu_usage = calculate_cpu_percent(&stats);
                 let (memory_usage, memory_limit) = calculate_memory(&stats);
@@ -449,14 +986,14 @@ pub async fn get_container_metrics(docker: &Docker, container_name: &str) -> Res
             }
             Err(e) => 
             {
-                error!("Failed to get stats for container '{}': {}", container_name, e);
-                Err(AppError::InternalServerError)
+                error!("Failed to get stats for container {}: {}", container, e);
+                Err(AppError::docker(&e))
             }
         }
     } 
     else 
     {
-        Err(AppError::NotFound(format!("No stats received for container {}", container_name)))
+        Err(AppError::NotFound(format!("No stats received for container {}", container)))
     }
 }
 
@@ -533,10 +1070,18 @@ pub fn create_tarball(path: &Path) -> Result<Vec<u8>, AppError>
     Ok(tar_data)
 }
 
+pub fn build_cache_tag(project_name: &str) -> String
+{
+    format!("{}/{}:latest", BUILD_CACHE_REPOSITORY, project_name)
+}
+
+// `cache_from` n'est qu'une indication : le builder classique ignore une image de cache absente.
 pub async fn build_image_from_tar(
     docker: &Docker,
     tar_stream: Vec<u8>,
     image_tag: &str,
+    cache_from: Option<&str>,
+    no_cache: bool,
 ) -> Result<(), AppError>
 {
     let options = BuildImageOptions 
@@ -544,6 +1089,9 @@ pub async fn build_image_from_tar(
         dockerfile: "Dockerfile".to_string(),
         t: Some(image_tag.to_string()),
         rm: true,
+        nocache: no_cache,
+        cachefrom: cache_from.map(|image| vec![image.to_string()]),
+        labels: Some(HashMap::from([(MANAGED_LABEL.to_string(), "true".to_string())])),
         ..Default::default()
     };
 
@@ -568,7 +1116,7 @@ pub async fn build_image_from_tar(
             Err(e) =>
             {
                 error!("Docker build stream error for image '{}': {}", image_tag, e);
-                return Err(AppError::InternalServerError);
+                return Err(AppError::docker(&e));
             }
         }
     }
@@ -577,6 +1125,96 @@ pub async fn build_image_from_tar(
     Ok(())
 }
 
+pub async fn update_build_cache(docker: &Docker, image_tag: &str, project_name: &str) -> Result<(), AppError>
+{
+    let options = TagImageOptions
+    {
+        repo: Some(format!("{}/{}", BUILD_CACHE_REPOSITORY, project_name)),
+        tag: Some("latest".to_string()),
+    };
+
+    docker.tag_image(image_tag, Some(options)).await.map_err(|e|
+    {
+        error!("Could not tag '{}' as build cache of project '{}': {}", image_tag, project_name, e);
+        AppError::docker(&e)
+    })
+}
+
+pub async fn tag_image(docker: &Docker, image: &str, repo: &str, tag: &str) -> Result<(), AppError>
+{
+    let options = TagImageOptions
+    {
+        repo: Some(repo.to_string()),
+        tag: Some(tag.to_string()),
+    };
+
+    docker.tag_image(image, Some(options)).await.map_err(|e|
+    {
+        error!("Could not tag '{}' as '{}:{}': {}", image, repo, tag, e);
+        AppError::docker(&e)
+    })
+}
+
+// Une référence par digest désigne une image immuable et n'a pas de tag.
+pub fn image_tag_parts(image_url: &str) -> Option<(&str, &str)>
+{
+    if image_url.contains('@')
+    {
+        return None;
+    }
+
+    match image_url.rsplit_once(':')
+    {
+        Some((repository, tag)) if !tag.contains('/') => Some((repository, tag)),
+        _ => Some((image_url, "latest")),
+    }
+}
+
+pub async fn prune_build_cache(docker: &Docker, max_age_days: i64, max_size_bytes: i64) -> Result<usize, AppError>
+{
+    let mut filters = HashMap::new();
+    filters.insert("label".to_string(), vec![format!("{}=true", MANAGED_LABEL)]);
+    filters.insert("reference".to_string(), vec![format!("{}/*", BUILD_CACHE_REPOSITORY)]);
+
+    let options = Some(ListImagesOptions
+    {
+        filters: Some(filters),
+        ..Default::default()
+    });
+
+    let mut images = docker.list_images(options).await.map_err(|e|
+    {
+        error!("Failed to list build cache images: {}", e);
+        AppError::docker(&e)
+    })?;
+
+    images.sort_by_key(|image| std::cmp::Reverse(image.created));
+
+    let oldest_allowed = OffsetDateTime::now_utc().unix_timestamp() - max_age_days * 24 * 3600;
+    let mut kept_size = 0;
+    let mut removed = 0;
+
+    for image in images
+    {
+        if image.created >= oldest_allowed && kept_size + image.size <= max_size_bytes
+        {
+            kept_size += image.size;
+            continue;
+        }
+
+        let cache_prefix = format!("{}/", BUILD_CACHE_REPOSITORY);
+        for tag in image.repo_tags.iter().filter(|tag| tag.starts_with(&cache_prefix))
+        {
+            if remove_image(docker, tag).await.is_ok()
+            {
+                removed += 1;
+            }
+        }
+    }
+
+    Ok(removed)
+}
+
 pub async fn get_global_container_stats(docker: &Docker, app_prefix: &str) -> Result<GlobalMetrics, AppError> 
 {
     let mut filters = HashMap::new();
@@ -592,7 +1230,7 @@ pub async fn get_global_container_stats(docker: &Docker, app_prefix: &str) -> Re
     let containers = docker.list_containers(options).await.map_err(|e| 
     {
         error!("Failed to list hangar containers: {}", e);
-        AppError::InternalServerError
+        AppError::docker(&e)
     })?;
 
     let mut running_containers = 0;
@@ -631,12 +1269,14 @@ pub async fn get_global_container_stats(docker: &Docker, app_prefix: &str) -> Re
         running_containers,
         total_cpu_usage,
         total_memory_usage_mb: (total_memory_usage as f64) / (1024.0 * 1024.0),
+        average_uptime_percent_30d: None,
+        degraded_hosts: Vec::new(),
     })
 }
 
-pub async fn inspect_container_details(docker: &Docker, container_name: &str) -> Result<Option<ContainerInspectResponse>, AppError> 
+pub async fn inspect_container_details(docker: &Docker, container: ContainerRef<'_>) -> Result<Option<ContainerInspectResponse>, AppError> 
 {
-    match docker.inspect_container(container_name, None::<InspectContainerOptions>).await 
+    match docker.inspect_container(container.as_str(), None::<InspectContainerOptions>).await 
     {
         Ok(details) => Ok(Some(details)),
         Err(bollard::errors::Error::DockerResponseServerError { status_code: 404, .. }) => 
@@ -645,8 +1285,172 @@ pub async fn inspect_container_details(docker: &Docker, container_name: &str) ->
         },
         Err(e) => 
         {
-            error!("Failed to inspect container '{}': {}", container_name, e);
-            Err(AppError::InternalServerError)
+            error!("Failed to inspect container {}: {}", container, e);
+            Err(AppError::docker(&e))
+        }
+    }
+}
+
+// Sans ID enregistré, ou si l'ID ne désigne plus rien, on se rabat sur le nom.
+pub async fn resolve_container<'a>(docker: &Docker, container_name: &'a str, stored_id: Option<&'a str>) -> ContainerRef<'a>
+{
+    let Some(id) = stored_id
+    else
+    {
+        warn!("No container ID recorded for '{}', looking it up by name.", container_name);
+        return ContainerRef::ByName(container_name);
+    };
+
+    match inspect_container_details(docker, ContainerRef::ById(id)).await
+    {
+        Ok(Some(_)) => ContainerRef::ById(id),
+        Ok(None) =>
+        {
+            warn!("Stored container {} of '{}' no longer exists, looking it up by name.", ContainerRef::ById(id), container_name);
+            ContainerRef::ByName(container_name)
+        }
+        Err(_) => ContainerRef::ByName(container_name),
+    }
+}
+
+pub async fn get_host_platform(docker: &Docker) -> Result<DockerPlatform, BollardError>
+{
+    let version = docker.version().await?;
+    Ok(DockerPlatform
+    {
+        os: version.os.unwrap_or_default(),
+        architecture: version.arch.unwrap_or_default(),
+    })
+}
+
+pub async fn get_image_platform(docker: &Docker, image_url: &str) -> Result<Option<DockerPlatform>, AppError>
+{
+    let details = docker.inspect_image(image_url).await.map_err(|e|
+    {
+        error!("Failed to inspect image '{}': {}", image_url, e);
+        AppError::docker(&e)
+    })?;
+
+    Ok(details.os.zip(details.architecture).map(|(os, architecture)| DockerPlatform { os, architecture }))
+}
+
+pub async fn get_image_created_at(docker: &Docker, image_url: &str) -> Result<Option<OffsetDateTime>, AppError>
+{
+    let details = docker.inspect_image(image_url).await.map_err(|e|
+    {
+        error!("Failed to inspect image '{}': {}", image_url, e);
+        AppError::docker(&e)
+    })?;
+
+    Ok(details.created.and_then(|created| OffsetDateTime::parse(&created, &Rfc3339).ok()))
+}
+
+// De simples conseils : ils ne bloquent jamais un déploiement.
+pub async fn get_image_config_warnings(docker: &Docker, image_url: &str) -> Result<Vec<String>, AppError>
+{
+    let details = docker.inspect_image(image_url).await.map_err(|e|
+    {
+        error!("Failed to inspect image '{}': {}", image_url, e);
+        AppError::docker(&e)
+    })?;
+    let Some(image_config) = details.config else
+    {
+        return Ok(Vec::new());
+    };
+
+    let mut warnings = Vec::new();
+
+    let mut exposed_ports: Vec<String> = image_config.exposed_ports.iter().flatten().map(|(port, _)| port.clone()).collect();
+    exposed_ports.sort();
+    let routed_port = format!("{}/tcp", CONTAINER_PORT);
+    if !exposed_ports.is_empty() && !exposed_ports.contains(&routed_port)
+    {
+        warnings.push(format!(
+            "The image exposes {} but routing targets port {}; the application must listen on port {}.",
+            exposed_ports.join(", "), CONTAINER_PORT, CONTAINER_PORT
+        ));
+    }
+
+    // Sans utilisateur explicite, Docker lance le processus en root.
+    let user = image_config.user.unwrap_or_default();
+    let user_name = user.split(':').next().unwrap_or_default();
+    if user_name.is_empty() || user_name == "root" || user_name == "0"
+    {
+        warnings.push("The image runs as root; prefer a non-root USER in the Dockerfile.".to_string());
+    }
+
+    let has_entrypoint = image_config.entrypoint.is_some_and(|entrypoint| !entrypoint.is_empty());
+    let has_cmd = image_config.cmd.is_some_and(|cmd| !cmd.is_empty());
+    if !has_entrypoint && !has_cmd
+    {
+        warnings.push("The image defines neither an ENTRYPOINT nor a CMD; the container will exit right after starting.".to_string());
+    }
+
+    Ok(warnings)
+}
+
+pub async fn get_image_repo_digest(docker: &Docker, image_url: &str, repository: &str) -> Result<Option<String>, AppError>
+{
+    let details = docker.inspect_image(image_url).await.map_err(|e|
+    {
+        error!("Failed to inspect image '{}': {}", image_url, e);
+        AppError::docker(&e)
+    })?;
+
+    let repo_digests = details.repo_digests.unwrap_or_default();
+    let repo_digest = repo_digests
+        .iter()
+        .find(|digest| digest.split('@').next() == Some(repository))
+        .or_else(|| repo_digests.first())
+        .cloned();
+
+    Ok(repo_digest)
+}
+
+pub async fn verify_image_with_cosign(image_ref: &str, host: &DockerHostConfig, config: &crate::config::Config) -> Result<(), String>
+{
+    let mut failures = Vec::new();
+
+    for key in &config.cosign_public_keys
+    {
+        let output = Command::new(&config.cosign_path)
+            .envs(host.client_env())
+            .arg("verify")
+            .arg("--key")
+            .arg(key)
+            .arg(image_ref)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .output()
+            .await
+            .map_err(|e|
+            {
+                error!("Failed to execute cosign command: {}", e);
+                format!("Failed to execute cosign: {}", e)
+            })?;
+
+        if output.status.success()
+        {
+            info!("Signature of image '{}' verified with key '{}'.", image_ref, key);
+            return Ok(());
+        }
+
+        failures.push(format!("[{}] {}", key, String::from_utf8_lossy(&output.stderr).trim()));
+    }
+
+    Err(failures.join("\n"))
+}
+
+pub async fn get_image_size(docker: &Docker, image_url: &str) -> Result<Option<i64>, AppError>
+{
+    match docker.inspect_image(image_url).await
+    {
+        Ok(details) => Ok(details.size),
+        Err(bollard::errors::Error::DockerResponseServerError { status_code: 404, .. }) => Ok(None),
+        Err(e) =>
+        {
+            error!("Failed to inspect image '{}': {}", image_url, e);
+            Err(AppError::docker(&e))
         }
     }
 }
@@ -675,7 +1479,263 @@ pub async fn get_image_digest(docker: &Docker, image_tag: &str) -> Result<Option
         Err(e) => 
         {
             error!("Failed to inspect image '{}': {}", image_tag, e);
-            Err(AppError::InternalServerError)
+            Err(AppError::docker(&e))
         }
     }
-}
\ No newline at end of file
+}
+
+pub async fn get_host_capacity(docker: &Docker, host_name: &str, app_prefix: &str) -> Result<DockerHostCapacity, AppError>
+{
+    let mut filters = HashMap::new();
+    filters.insert("label".to_string(), vec![format!("app={}", app_prefix)]);
+
+    let containers = docker.list_containers(Some(ListContainersOptions 
+    {
+        all: true,
+        filters: Some(filters),
+        ..Default::default()
+    })).await.map_err(|e| 
+    {
+        error!("Failed to list hangar containers on host '{}': {}", host_name, e);
+        AppError::docker(&e)
+    })?;
+
+    let running_containers = containers.iter()
+        .filter(|c| c.state.as_ref().is_some_and(|state| state.to_string() == "running"))
+        .count();
+
+    let usage = docker.df(None::<DataUsageOptions>).await.map_err(|e|
+    {
+        error!("Failed to fetch disk usage of Docker host '{}': {}", host_name, e);
+        AppError::docker(&e)
+    })?;
+
+    Ok(DockerHostCapacity
+    {
+        name: host_name.to_string(),
+        total_containers: containers.len(),
+        running_containers,
+        images: usage.images.as_ref().map_or(0, Vec::len),
+        volumes: usage.volumes.as_ref().map_or(0, Vec::len),
+        layers_size_bytes: usage.layers_size.unwrap_or(0),
+    })
+}
+
+// Docker renvoie `-1` pour les volumes qu'il ne sait pas mesurer (pilotes non locaux) : ils sont ignorés.
+pub async fn get_volume_sizes(docker: &Docker) -> Result<HashMap<String, i64>, AppError>
+{
+    let usage = docker.df(None::<DataUsageOptions>).await.map_err(|e|
+    {
+        error!("Failed to fetch volume disk usage: {}", e);
+        AppError::docker(&e)
+    })?;
+
+    Ok(usage.volumes.unwrap_or_default()
+        .into_iter()
+        .filter_map(|volume| volume.usage_data.filter(|data| data.size >= 0).map(|data| (volume.name, data.size)))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::model::project::{ProjectRateLimit, RoutingOptions};
+
+    fn labels_with(middlewares: &RouteMiddlewares) -> HashMap<String, String>
+    {
+        let config = crate::config::Config::for_tests(&[]).unwrap();
+        traefik_labels(&config, "demo", &["demo.apps.hangar.test".to_string()], middlewares)
+    }
+
+
+    // Flux enregistré d'un `docker pull`, tel que le démon l'envoie.
+    const RECORDED_PULL: &str = r#"
+        {"status":"Pulling from library/nginx","id":"latest"}
+        {"status":"Already exists","progressDetail":{},"id":"aaa"}
+        {"status":"Pulling fs layer","progressDetail":{},"id":"bbb"}
+        {"status":"Pulling fs layer","progressDetail":{},"id":"ccc"}
+        {"status":"Downloading","progressDetail":{"current":400,"total":1000},"progress":"[==>  ]","id":"bbb"}
+        {"status":"Downloading","progressDetail":{"current":50,"total":200},"id":"ccc"}
+        {"status":"Verifying Checksum","progressDetail":{},"id":"bbb"}
+        {"status":"Download complete","progressDetail":{},"id":"bbb"}
+        {"status":"Downloading","progressDetail":{"current":120,"total":200},"id":"ccc"}
+        {"status":"Digest: sha256:0123"}
+    "#;
+
+    fn replay(recorded: &str) -> (PullProgress, Result<(), String>)
+    {
+        let mut progress = PullProgress::default();
+        let result = recorded
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| serde_json::from_str::<CreateImageInfo>(line).unwrap())
+            .try_for_each(|info| apply_pull_message(&mut progress, info));
+        (progress, result)
+    }
+
+    #[test]
+    fn recorded_pull_is_folded_per_layer()
+    {
+        let (progress, result) = replay(RECORDED_PULL);
+        assert!(result.is_ok());
+
+        assert_eq!(progress.layers.keys().collect::<Vec<_>>(), ["aaa", "bbb", "ccc"]);
+        assert_eq!(progress.layers["aaa"].status, "Already exists");
+        assert_eq!(progress.layers["bbb"].status, "Download complete");
+        assert_eq!(progress.layers["bbb"].current_bytes, Some(1000));
+        assert_eq!(progress.layers["ccc"].current_bytes, Some(120));
+        assert_eq!((progress.current_bytes, progress.total_bytes), (1120, 1200));
+        assert!(progress.error.is_none());
+    }
+
+    #[test]
+    fn stream_error_stops_the_pull_with_the_layer_states()
+    {
+        let recorded = format!(
+            "{}{}",
+            RECORDED_PULL,
+            r#"{"errorDetail":{"message":"write /var/lib/docker/tmp: no space left on device"},"error":"no space left on device"}"#,
+        );
+
+        let (progress, result) = replay(&recorded);
+
+        assert_eq!(result, Err("write /var/lib/docker/tmp: no space left on device".to_string()));
+        assert_eq!(
+            progress.summary(),
+            [
+                "aaa: Already exists",
+                "bbb: Download complete",
+                "ccc: Downloading",
+                "write /var/lib/docker/tmp: no space left on device",
+            ],
+        );
+    }
+
+    #[test]
+    fn rate_limit_adds_a_ratelimit_middleware()
+    {
+        let labels = labels_with(&RouteMiddlewares
+        {
+            rate_limit: Some(ProjectRateLimit { average: 20, burst: 50 }),
+            ..Default::default()
+        });
+
+        assert_eq!(labels["traefik.http.middlewares.demo-ratelimit.ratelimit.average"], "20");
+        assert_eq!(labels["traefik.http.middlewares.demo-ratelimit.ratelimit.burst"], "50");
+        assert_eq!(labels["traefik.http.routers.demo.middlewares"], "demo-ratelimit");
+    }
+
+    #[test]
+    fn no_rate_limit_means_no_middleware()
+    {
+        let labels = labels_with(&RouteMiddlewares::default());
+
+        assert!(labels.keys().all(|label| !label.contains("ratelimit")));
+        assert!(!labels.contains_key("traefik.http.routers.demo.middlewares"));
+    }
+
+    #[test]
+    fn ip_allowlist_runs_before_the_rate_limit()
+    {
+        // Les IP refusées ne doivent pas consommer le quota.
+        let labels = labels_with(&RouteMiddlewares
+        {
+            ip_allowlist: Some(vec!["10.0.0.0/8".to_string()]),
+            rate_limit: Some(ProjectRateLimit { average: 5, burst: 5 }),
+            ..Default::default()
+        });
+
+        assert_eq!(labels["traefik.http.routers.demo.middlewares"], "demo-ipallow,demo-ratelimit");
+    }
+
+    #[test]
+    fn routing_options_combine_with_the_other_middlewares()
+    {
+        let routing = RoutingOptions
+        {
+            sticky_sessions: true,
+            response_headers: [("X-Frame-Options", "DENY"), ("Cache-Control", "no-store")]
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+        };
+        let labels = labels_with(&RouteMiddlewares
+        {
+            ip_allowlist: Some(vec!["10.0.0.0/8".to_string()]),
+            rate_limit: Some(ProjectRateLimit { average: 5, burst: 5 }),
+            routing,
+            ..Default::default()
+        });
+
+        let expected = [
+            ("traefik.http.routers.demo.middlewares", "demo-ipallow,demo-ratelimit,demo-headers"),
+            ("traefik.http.middlewares.demo-headers.headers.customresponseheaders.X-Frame-Options", "DENY"),
+            ("traefik.http.middlewares.demo-headers.headers.customresponseheaders.Cache-Control", "no-store"),
+            ("traefik.http.services.demo.loadbalancer.sticky.cookie", "true"),
+            ("traefik.http.services.demo.loadbalancer.sticky.cookie.name", "demo_sticky"),
+            ("traefik.http.services.demo.loadbalancer.sticky.cookie.httponly", "true"),
+            ("traefik.http.services.demo.loadbalancer.sticky.cookie.secure", "true"),
+        ];
+        for (label, value) in expected
+        {
+            assert_eq!(labels.get(label).map(String::as_str), Some(value), "{}", label);
+        }
+        assert_eq!(labels.keys().filter(|label| label.contains("customresponseheaders")).count(), 2);
+    }
+
+    #[test]
+    fn default_routing_options_add_no_labels()
+    {
+        let labels = labels_with(&RouteMiddlewares::default());
+
+        assert!(labels.keys().all(|label| !label.contains("sticky") && !label.contains("headers")));
+    }
+
+    #[test]
+    fn container_refs_show_short_ids()
+    {
+        let id = "4f2a9c1b7d3e8a6f0c5b2e9d1a7f3c8b6e0d4a2f9c1b7d3e8a6f0c5b2e9d1a7f";
+
+        assert_eq!(ContainerRef::ById(id).to_string(), "ID 4f2a9c1b7d3e");
+        assert_eq!(ContainerRef::ById("abc").to_string(), "ID abc");
+        assert_eq!(ContainerRef::ByName("hangar-demo").to_string(), "'hangar-demo'");
+        assert_eq!(ContainerRef::ById(id).as_str(), id);
+    }
+
+    fn docker_at(address: std::net::SocketAddr) -> Docker
+    {
+        Docker::connect_with_http(&format!("http://{}", address), 1, bollard::API_DEFAULT_VERSION).unwrap()
+    }
+
+    #[tokio::test]
+    async fn live_stored_id_is_used()
+    {
+        let (address, requests) = crate::test_support::http_server("HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: 2\r\n\r\n{}");
+
+        let container = resolve_container(&docker_at(address), "hangar-demo", Some("4f2a9c1b7d3e")).await;
+
+        assert!(matches!(container, ContainerRef::ById("4f2a9c1b7d3e")), "{:?}", container);
+        let head = requests.recv().unwrap();
+        assert!(head.contains("/containers/4f2a9c1b7d3e/json"), "{}", head);
+    }
+
+    #[tokio::test]
+    async fn missing_or_unknown_id_falls_back_to_the_name()
+    {
+        let (gone, _) = crate::test_support::http_server("HTTP/1.1 404 Not Found\r\nContent-Type: application/json\r\nContent-Length: 34\r\n\r\n{\"message\":\"No such container: x\"}");
+        let (failing, _) = crate::test_support::http_server("HTTP/1.1 500 Internal Server Error\r\nContent-Type: application/json\r\nContent-Length: 2\r\n\r\n{}");
+
+        let cases = [
+            (gone, None),
+            (gone, Some("4f2a9c1b7d3e")),
+            (failing, Some("4f2a9c1b7d3e")),
+        ];
+
+        for (address, stored_id) in cases
+        {
+            let container = resolve_container(&docker_at(address), "hangar-demo", stored_id).await;
+            assert!(matches!(container, ContainerRef::ByName("hangar-demo")), "{:?} with {:?}", container, stored_id);
+        }
+    }
+}
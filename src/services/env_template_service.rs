@@ -0,0 +1,97 @@
+use std::collections::BTreeMap;
+
+use crate::{config::Config, model::database::DatabaseDetailsResponse, services::docker_service};
+
+// Celles de la base restent vides tant qu'aucune base n'est liée au projet.
+pub const PLACEHOLDERS: &[&str] = &[
+    "PROJECT_URL",
+    "HANGAR_PROJECT_NAME",
+    "HANGAR_PUBLIC_URL",
+    "HANGAR_OWNER",
+    "HANGAR_DB_HOST",
+    "HANGAR_DB_PORT",
+    "HANGAR_DB_NAME",
+    "HANGAR_DB_USER",
+    "HANGAR_DB_PASSWORD",
+];
+
+enum Segment<'a>
+{
+    Literal(&'a str),
+    Placeholder(&'a str),
+}
+
+// `$${` vaut un `${` littéral ; un `${` jamais refermé est gardé tel quel.
+fn segments(value: &str) -> Vec<Segment<'_>>
+{
+    let mut segments = Vec::new();
+    let mut rest = value;
+
+    while let Some(start) = rest.find("${")
+    {
+        if rest[..start].ends_with('$')
+        {
+            segments.push(Segment::Literal(&rest[..start - 1]));
+            segments.push(Segment::Literal("${"));
+            rest = &rest[start + 2..];
+            continue;
+        }
+
+        let Some(length) = rest[start + 2..].find('}')
+        else
+        {
+            break;
+        };
+
+        segments.push(Segment::Literal(&rest[..start]));
+        segments.push(Segment::Placeholder(&rest[start + 2..start + 2 + length]));
+        rest = &rest[start + 3 + length..];
+    }
+
+    segments.push(Segment::Literal(rest));
+    segments
+}
+
+pub fn unknown_placeholders(value: &str) -> Vec<String>
+{
+    segments(value).into_iter()
+        .filter_map(|segment| match segment
+        {
+            Segment::Placeholder(name) if !PLACEHOLDERS.contains(&name) => Some(name.to_string()),
+            _ => None,
+        })
+        .collect()
+}
+
+pub fn context(config: &Config, project_name: &str, owner: &str, database: Option<&DatabaseDetailsResponse>) -> BTreeMap<String, String>
+{
+    let mut values = docker_service::platform_env_vars(config, project_name, owner);
+    values.insert("PROJECT_URL".to_string(), config.project_public_url(project_name));
+
+    if let Some(database) = database
+    {
+        values.insert("HANGAR_DB_HOST".to_string(), database.host.clone());
+        values.insert("HANGAR_DB_PORT".to_string(), database.port.to_string());
+        values.insert("HANGAR_DB_NAME".to_string(), database.database_name.clone());
+        values.insert("HANGAR_DB_USER".to_string(), database.username.clone());
+        values.insert("HANGAR_DB_PASSWORD".to_string(), database.password.clone().unwrap_or_default());
+    }
+
+    values
+}
+
+pub fn render(value: &str, context: &BTreeMap<String, String>) -> String
+{
+    if !value.contains("${")
+    {
+        return value.to_string();
+    }
+
+    segments(value).into_iter()
+        .map(|segment| match segment
+        {
+            Segment::Literal(text) => text,
+            Segment::Placeholder(name) => context.get(name).map_or("", String::as_str),
+        })
+        .collect()
+}
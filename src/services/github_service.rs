@@ -1,11 +1,13 @@
-use std::path::Path;
+use std::{cell::Cell, path::Path, time::{Duration, Instant}};
 
-use crate::{config::Config, error::{AppError, ProjectErrorCode}};
+use crate::{config::Config, error::{AppError, ProjectErrorCode}, model::deployment::CloneProgress};
 use serde::{Deserialize, Serialize};
 use time::OffsetDateTime;
 use tracing::{debug, error, info, warn};
 use jsonwebtoken::{encode, Algorithm, EncodingKey, Header};
-use git2::{Cred, FetchOptions, RemoteCallbacks, build::RepoBuilder};
+use git2::{Cred, ErrorClass, FetchOptions, RemoteCallbacks, build::RepoBuilder};
+
+const GITHUB: &str = "GitHub";
 
 #[derive(Debug, Deserialize)]
 struct Installation
@@ -86,9 +88,9 @@ pub async fn check_repo_accessibility(
         .get(&url)
         .header("Authorization", format!("Bearer {}", token))
         .header("Accept", "application/vnd.github+json")
-        .header("User-Agent", "Hangar App")
         .send()
-        .await?;
+        .await
+        .map_err(|e| AppError::upstream(GITHUB, e))?;
 
     if response.status().is_success() 
     {
@@ -105,12 +107,13 @@ pub async fn check_repo_accessibility(
     } 
     else 
     {
+        let status = response.status();
         let error_body = response.text().await.unwrap_or_default();
         error!(
             "GitHub API request to check repo accessibility failed: {}",
             error_body
         );
-        Err(AppError::InternalServerError)
+        Err(AppError::upstream_status(GITHUB, status))
     }
 }
 
@@ -147,17 +150,17 @@ pub async fn get_installation_id_by_user(http_client: &reqwest::Client, config:
         .get("https://api.github.com/app/installations")
         .header("Authorization", format!("Bearer {}", app_jwt))
         .header("Accept", "application/vnd.github+json")
-        .header("User-Agent", "Hangar App")
         .send()
-        .await?;
+        .await
+        .map_err(|e| AppError::upstream(GITHUB, e))?;
 
     if !response.status().is_success()
     {
-        error!("Failed to fetch installations from GitHub.");
-        return Err(AppError::InternalServerError);
+        error!("Failed to fetch installations from GitHub: {}", response.status());
+        return Err(AppError::upstream_status(GITHUB, response.status()));
     }
 
-    let installations_response: Vec<Installation> = response.json().await?;
+    let installations_response: Vec<Installation> = response.json().await.map_err(|e| AppError::upstream(GITHUB, e))?;
 
     for inst in installations_response
     {
@@ -180,34 +183,87 @@ pub async fn get_installation_token(installation_id: u64, http_client: &reqwest:
         .post(&url)
         .header("Authorization", format!("Bearer {}", app_jwt))
         .header("Accept", "application/vnd.github+json")
-        .header("User-Agent", "Hangar App")
         .send()
-        .await?;
+        .await
+        .map_err(|e| AppError::upstream(GITHUB, e))?;
     
     if !response.status().is_success()
     {
+        let status = response.status();
         let error_body = response.text().await.unwrap_or_default();
         error!("GitHub installation token request failed: {}", error_body);
-        return Err(AppError::InternalServerError);
+        return Err(AppError::upstream_status(GITHUB, status));
     }
 
-    let token_response: InstallationTokenResponse = response.json().await?;
+    let token_response: InstallationTokenResponse = response.json().await.map_err(|e| AppError::upstream(GITHUB, e))?;
     Ok(token_response.token)
 }
 
-pub async fn clone_repo(repo_url: &str, target_dir: &Path, token: Option<&str>, branch: Option<&str>) -> Result<(), AppError>
+// Les rappels de transfert ne s'exécutent qu'à l'arrivée de données : seul libgit2 peut voir un socket muet.
+pub fn configure_git_timeouts(config: &Config)
+{
+    let stall_ms = i32::try_from(config.clone_stall_timeout_seconds.saturating_mul(1000)).unwrap_or(i32::MAX);
+
+    // SAFETY: ces options globales sont modifiées au démarrage, avant qu'un clone ne les lise.
+    let result = unsafe
+    {
+        git2::opts::set_server_connect_timeout_in_milliseconds(stall_ms)
+            .and_then(|_| git2::opts::set_server_timeout_in_milliseconds(stall_ms))
+    };
+
+    if let Err(e) = result
+    {
+        warn!("Could not set the libgit2 server timeouts: {}", e);
+    }
+}
+
+pub async fn clone_repo(
+    config: &Config,
+    repo_url: &str,
+    target_dir: &Path,
+    token: Option<&str>,
+    branch: Option<&str>,
+    on_progress: impl Fn(&CloneProgress) + Send + 'static,
+) -> Result<(), AppError>
 {
     let repo_url_owned = repo_url.to_string();
     let target_dir = target_dir.to_path_buf();
     let token = token.map(|s| s.to_string());
     let branch = branch.map(|s| s.to_string());
+    let total_limit = Duration::from_secs(config.clone_timeout_seconds);
+    let stall_limit = Duration::from_secs(config.clone_stall_timeout_seconds);
 
     let repo_url_for_log = repo_url_owned.clone();
 
-    let clone_result = tokio::task::spawn_blocking(move ||
+    let clone_task = tokio::task::spawn_blocking(move ||
     {
+        let started = Instant::now();
+        let last_received = Cell::new((0, started));
+        let timed_out = Cell::new(false);
+
         let mut callbacks = RemoteCallbacks::new();
 
+        callbacks.transfer_progress(|progress|
+        {
+            let now = Instant::now();
+            if progress.received_bytes() != last_received.get().0
+            {
+                last_received.set((progress.received_bytes(), now));
+            }
+
+            on_progress(&CloneProgress
+            {
+                received_objects: progress.received_objects(),
+                total_objects: progress.total_objects(),
+                received_bytes: progress.received_bytes(),
+            });
+
+            // Renvoyer false interrompt le fetch.
+            let expired = now - started > total_limit || now - last_received.get().1 > stall_limit;
+            timed_out.set(expired);
+            !expired
+        });
+
         if let Some(t) = &token
         {
             callbacks.credentials(move |_url, _username_from_url, _allowed_types|
@@ -228,15 +284,30 @@ pub async fn clone_repo(repo_url: &str, target_dir: &Path, token: Option<&str>,
             builder.branch(b);
         }
 
-        builder.clone(&repo_url_owned, &target_dir)
-    })
-    .await
-    .map_err(|_| AppError::InternalServerError)?;
+        let result = builder.clone(&repo_url_owned, &target_dir).map(drop);
+        (result, timed_out.get())
+    });
+
+    // Filet de sécurité si libgit2 reste bloqué hors des callbacks : le thread finira par échouer
+    // sur le délai serveur, mais la requête n'a pas à l'attendre.
+    let (clone_result, aborted) = tokio::time::timeout(total_limit + stall_limit, clone_task)
+        .await
+        .map_err(|_|
+        {
+            warn!("Clone of '{}' still blocked after {}s; giving up on it.", repo_url_for_log, (total_limit + stall_limit).as_secs());
+            ProjectErrorCode::CloneTimedOut(config.clone_timeout_seconds)
+        })?
+        .map_err(|_| AppError::InternalServerError)?;
 
     clone_result.map_err(|e|
     {
         let msg = e.message().to_lowercase();
-        if msg.contains("authentication required") || msg.contains("credentials callback returned an error")
+        if aborted || (e.class() == ErrorClass::Net && msg.contains("timed out"))
+        {
+            warn!("Clone of '{}' timed out: {}", repo_url_for_log, msg);
+            AppError::ProjectError(ProjectErrorCode::CloneTimedOut(config.clone_timeout_seconds))
+        }
+        else if msg.contains("authentication required") || msg.contains("credentials callback returned an error")
         {
             AppError::ProjectError(ProjectErrorCode::GithubAccountNotLinked)
         }
@@ -248,4 +319,24 @@ pub async fn clone_repo(repo_url: &str, target_dir: &Path, token: Option<&str>,
 
     info!("Repository {} cloned successfully.", repo_url_for_log);
     Ok(())
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::test_support::stalling_server;
+
+    #[tokio::test]
+    async fn stalled_clone_is_aborted()
+    {
+        let config = Config::for_tests(&[("CLONE_TIMEOUT_SECONDS", "1"), ("CLONE_STALL_TIMEOUT_SECONDS", "1")]).unwrap();
+        configure_git_timeouts(&config);
+        let target = tempfile::tempdir().unwrap();
+
+        let started = Instant::now();
+        let result = clone_repo(&config, &format!("http://{}/owner/repo.git", stalling_server()), &target.path().join("repo"), None, None, |_| {}).await;
+
+        assert!(matches!(result, Err(AppError::ProjectError(ProjectErrorCode::CloneTimedOut(1)))), "{:?}", result.err());
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+}
@@ -0,0 +1,92 @@
+use sqlx::PgPool;
+use tracing::error;
+
+use crate::{error::AppError, model::invitation::Invitation};
+
+pub async fn get_pending_invitations(pool: &PgPool, login: &str, expiry_days: i32) -> Result<Vec<Invitation>, AppError>
+{
+    sqlx::query_as::<_, Invitation>(
+        "SELECT pp.id, pp.project_id, p.name AS project_name, p.owner AS project_owner, pp.invited_by, pp.invited_at,
+                pp.invited_at + make_interval(days => $2) AS expires_at
+         FROM project_participants pp
+         JOIN projects p ON p.id = pp.project_id
+         WHERE pp.participant_id = $1 AND pp.status = 'pending' AND pp.invited_at > NOW() - make_interval(days => $2)
+         ORDER BY pp.invited_at DESC"
+    )
+        .bind(login)
+        .bind(expiry_days)
+        .fetch_all(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to fetch pending invitations for '{}': {}", login, e);
+            AppError::database(&e)
+        })
+}
+
+pub async fn accept_invitation(pool: &PgPool, invitation_id: i32, login: &str, expiry_days: i32) -> Result<Option<i32>, AppError>
+{
+    sqlx::query_scalar(
+        "WITH accepted AS (
+             UPDATE project_participants SET status = 'accepted'
+             WHERE id = $1 AND participant_id = $2 AND status = 'pending' AND invited_at > NOW() - make_interval(days => $3)
+             RETURNING project_id
+         )
+         UPDATE projects SET updated_at = NOW() WHERE id IN (SELECT project_id FROM accepted) RETURNING id"
+    )
+        .bind(invitation_id)
+        .bind(login)
+        .bind(expiry_days)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to accept invitation {} for '{}': {}", invitation_id, login, e);
+            AppError::database(&e)
+        })
+}
+
+pub async fn decline_invitation(pool: &PgPool, invitation_id: i32, login: &str) -> Result<bool, AppError>
+{
+    let result = sqlx::query(
+        "WITH declined AS (
+             DELETE FROM project_participants
+             WHERE id = $1 AND participant_id = $2 AND status = 'pending'
+             RETURNING project_id
+         )
+         UPDATE projects SET updated_at = NOW() WHERE id IN (SELECT project_id FROM declined)"
+    )
+        .bind(invitation_id)
+        .bind(login)
+        .execute(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to decline invitation {} for '{}': {}", invitation_id, login, e);
+            AppError::database(&e)
+        })?;
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn delete_expired_invitations(pool: &PgPool, expiry_days: i32) -> Result<i64, AppError>
+{
+    sqlx::query_scalar(
+        "WITH expired AS (
+             DELETE FROM project_participants
+             WHERE status = 'pending' AND invited_at <= NOW() - make_interval(days => $1)
+             RETURNING project_id
+         ),
+         touched AS (
+             UPDATE projects SET updated_at = NOW() WHERE id IN (SELECT project_id FROM expired)
+         )
+         SELECT COUNT(*) FROM expired"
+    )
+        .bind(expiry_days)
+        .fetch_one(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to delete expired invitations: {}", e);
+            AppError::database(&e)
+        })
+}
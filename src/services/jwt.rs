@@ -1,9 +1,13 @@
+use base64::prelude::*;
 use jsonwebtoken::{encode, decode, Header, Validation, EncodingKey, DecodingKey, TokenData};
 use serde::{Deserialize, Serialize};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use crate::error::AppError;
 
+const STREAM_TICKET_AUDIENCE: &str = "hangar-stream";
+pub const STREAM_TICKET_TTL_SECONDS: u64 = 30;
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Claims 
 {
@@ -11,6 +15,9 @@ pub struct Claims
     pub name: String,
     pub email: String,
     pub exp: i64,
+    // Les jetons émis avant son ajout le décodent à 0, donc jamais récents.
+    #[serde(default)]
+    pub iat: i64,
     pub is_admin: bool,
 }
 
@@ -23,14 +30,139 @@ pub fn generate_jwt(secret: &str, jwt_expiration_seconds : u64, login: &str, nam
         name: name.to_string(),
         email: email.to_string(),
         exp: (now + jwt_expiration_seconds) as i64,
+        iat: now as i64,
         is_admin,
     };
 
     encode(&Header::default(), &claims, &EncodingKey::from_secret(secret.as_bytes())).map_err(|_| AppError::InternalServerError)
 }
 
+impl Claims
+{
+    pub fn is_fresh(&self, max_age_seconds: u64) -> bool
+    {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs() as i64).unwrap_or(0);
+        now - self.iat <= max_age_seconds as i64
+    }
+}
+
 pub fn validate_jwt(token: &str, secret: &str) -> Result<TokenData<Claims>, AppError> 
 {
     decode::<Claims>(token, &DecodingKey::from_secret(secret.as_bytes()), &Validation::default())
     .map_err(|_| AppError::Unauthorized("Invalid token".to_string()))
 }
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum StreamKind
+{
+    Logs,
+    Status,
+    Export,
+}
+
+// Sans `name` ni `email` : un ticket ne se décode jamais comme des `Claims` de session.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StreamTicket
+{
+    pub sub: String,
+    pub is_admin: bool,
+    pub aud: String,
+    pub project_id: i32,
+    pub stream: StreamKind,
+    pub jti: String,
+    pub exp: i64,
+    pub iat: i64,
+}
+
+impl StreamTicket
+{
+    // Un ticket ne compte jamais comme une connexion récente.
+    pub fn into_claims(self) -> Claims
+    {
+        Claims
+        {
+            sub: self.sub,
+            name: String::new(),
+            email: String::new(),
+            exp: self.exp,
+            iat: 0,
+            is_admin: self.is_admin,
+        }
+    }
+}
+
+pub fn generate_stream_ticket(secret: &str, claims: &Claims, project_id: i32, stream: StreamKind) -> Result<String, AppError>
+{
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
+    let ticket = StreamTicket
+    {
+        sub: claims.sub.clone(),
+        is_admin: claims.is_admin,
+        aud: STREAM_TICKET_AUDIENCE.to_string(),
+        project_id,
+        stream,
+        jti: BASE64_URL_SAFE_NO_PAD.encode(rand::random::<[u8; 16]>()),
+        exp: (now + STREAM_TICKET_TTL_SECONDS) as i64,
+        iat: now as i64,
+    };
+
+    encode(&Header::default(), &ticket, &EncodingKey::from_secret(secret.as_bytes())).map_err(|_| AppError::InternalServerError)
+}
+
+// Le lien au flux demandé et l'usage unique sont vérifiés par l'appelant.
+pub fn validate_stream_ticket(token: &str, secret: &str) -> Result<StreamTicket, AppError>
+{
+    let mut validation = Validation::default();
+    validation.set_audience(&[STREAM_TICKET_AUDIENCE]);
+    validation.leeway = 0;
+
+    decode::<StreamTicket>(token, &DecodingKey::from_secret(secret.as_bytes()), &validation)
+        .map(|data| data.claims)
+        .map_err(|_| AppError::Unauthorized("Invalid or expired stream ticket.".to_string()))
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    const SECRET: &str = "un-secret-de-test-suffisamment-long-pour-la-validation";
+
+    fn encode_ticket(exp: i64, aud: &str) -> String
+    {
+        let ticket = StreamTicket
+        {
+            sub: "alice".to_string(),
+            is_admin: false,
+            aud: aud.to_string(),
+            project_id: 1,
+            stream: StreamKind::Logs,
+            jti: "jti".to_string(),
+            exp,
+            iat: exp - STREAM_TICKET_TTL_SECONDS as i64,
+        };
+        encode(&Header::default(), &ticket, &EncodingKey::from_secret(SECRET.as_bytes())).unwrap()
+    }
+
+    fn now() -> i64
+    {
+        SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64
+    }
+
+    #[test]
+    fn expired_ticket_is_rejected()
+    {
+        assert!(validate_stream_ticket(&encode_ticket(now() + 10, STREAM_TICKET_AUDIENCE), SECRET).is_ok());
+        assert!(validate_stream_ticket(&encode_ticket(now() - 1, STREAM_TICKET_AUDIENCE), SECRET).is_err());
+    }
+
+    #[test]
+    fn session_token_is_not_a_ticket()
+    {
+        let session = generate_jwt(SECRET, 3600, "alice", "Alice", "alice@hangar.test", false).unwrap();
+
+        assert!(validate_stream_ticket(&session, SECRET).is_err());
+        assert!(validate_stream_ticket(&encode_ticket(now() + 10, "other"), SECRET).is_err());
+    }
+}
@@ -0,0 +1,249 @@
+use std::collections::HashMap;
+
+use base64::prelude::*;
+use serde::Serialize;
+use sqlx::PgPool;
+use tracing::{error, warn};
+
+use crate::{error::AppError, services::crypto_service::{self, EncryptionKeys}};
+
+// Chaque ligne est mise à jour seule : aucun verrou ne lui survit.
+const REENCRYPT_BATCH_SIZE: i64 = 100;
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct ReencryptionCounts
+{
+    pub migrated: u64,
+    pub already_current: u64,
+    pub failed: u64,
+}
+
+enum RowOutcome<T>
+{
+    Migrated(T),
+    AlreadyCurrent,
+    Failed,
+}
+
+impl ReencryptionCounts
+{
+    fn count<T>(&mut self, outcome: &RowOutcome<T>)
+    {
+        match outcome
+        {
+            RowOutcome::Migrated(_) => self.migrated += 1,
+            RowOutcome::AlreadyCurrent => self.already_current += 1,
+            RowOutcome::Failed => self.failed += 1,
+        }
+    }
+}
+
+fn reencrypt_value(encoded: &str, keys: &EncryptionKeys) -> Result<Option<String>, AppError>
+{
+    let ciphertext = BASE64_STANDARD.decode(encoded).map_err(|_| AppError::InternalServerError)?;
+    let (plaintext, current) = crypto_service::decrypt_reporting_key(&ciphertext, keys)?;
+
+    if current
+    {
+        return Ok(None);
+    }
+
+    Ok(Some(BASE64_STANDARD.encode(crypto_service::encrypt(&plaintext, keys)?)))
+}
+
+fn reencrypt_env_vars(stored: &serde_json::Value, keys: &EncryptionKeys) -> RowOutcome<serde_json::Value>
+{
+    let Ok(mut vars) = serde_json::from_value::<HashMap<String, String>>(stored.clone())
+    else
+    {
+        return RowOutcome::Failed;
+    };
+
+    let mut changed = false;
+    for value in vars.values_mut()
+    {
+        match reencrypt_value(value, keys)
+        {
+            Ok(Some(reencrypted)) =>
+            {
+                *value = reencrypted;
+                changed = true;
+            }
+            Ok(None) => {}
+            Err(_) => return RowOutcome::Failed,
+        }
+    }
+
+    match (changed, serde_json::to_value(vars))
+    {
+        (false, _) => RowOutcome::AlreadyCurrent,
+        (true, Ok(value)) => RowOutcome::Migrated(value),
+        (true, Err(_)) => RowOutcome::Failed,
+    }
+}
+
+// Relançable après une interruption : les lignes déjà sous la clé courante sont seulement comptées.
+pub async fn reencrypt_project_env_vars(pool: &PgPool, keys: &EncryptionKeys) -> Result<ReencryptionCounts, AppError>
+{
+    let mut counts = ReencryptionCounts::default();
+    let mut last_id = 0;
+
+    loop
+    {
+        let rows: Vec<(i32, serde_json::Value)> = sqlx::query_as(
+            "SELECT id, env_vars FROM projects WHERE id > $1 AND env_vars IS NOT NULL ORDER BY id LIMIT $2"
+        )
+            .bind(last_id)
+            .bind(REENCRYPT_BATCH_SIZE)
+            .fetch_all(pool)
+            .await
+            .map_err(|e|
+            {
+                error!("Failed to fetch project env vars to re-encrypt: {}", e);
+                AppError::database(&e)
+            })?;
+
+        let Some((batch_last_id, _)) = rows.last()
+        else
+        {
+            break;
+        };
+        last_id = *batch_last_id;
+
+        for (project_id, stored) in rows
+        {
+            let outcome = reencrypt_env_vars(&stored, keys);
+            if let RowOutcome::Migrated(reencrypted) = &outcome
+            {
+                // Un enregistrement concurrent a déjà chiffré avec la clé courante : rien à écraser.
+                sqlx::query("UPDATE projects SET env_vars = $1 WHERE id = $2 AND env_vars = $3")
+                    .bind(reencrypted)
+                    .bind(project_id)
+                    .bind(&stored)
+                    .execute(pool)
+                    .await
+                    .map_err(|e|
+                    {
+                        error!("Failed to store the re-encrypted env vars of project {}: {}", project_id, e);
+                        AppError::database(&e)
+                    })?;
+            }
+            else if let RowOutcome::Failed = outcome
+            {
+                warn!("No configured key decrypts the env vars of project {}.", project_id);
+            }
+            counts.count(&outcome);
+        }
+    }
+
+    Ok(counts)
+}
+
+pub async fn reencrypt_database_passwords(pool: &PgPool, keys: &EncryptionKeys) -> Result<ReencryptionCounts, AppError>
+{
+    let mut counts = ReencryptionCounts::default();
+    let mut last_id = 0;
+
+    loop
+    {
+        let rows: Vec<(i32, String)> = sqlx::query_as(
+            "SELECT id, encrypted_password FROM databases WHERE id > $1 ORDER BY id LIMIT $2"
+        )
+            .bind(last_id)
+            .bind(REENCRYPT_BATCH_SIZE)
+            .fetch_all(pool)
+            .await
+            .map_err(|e|
+            {
+                error!("Failed to fetch database passwords to re-encrypt: {}", e);
+                AppError::database(&e)
+            })?;
+
+        let Some((batch_last_id, _)) = rows.last()
+        else
+        {
+            break;
+        };
+        last_id = *batch_last_id;
+
+        for (database_id, stored) in rows
+        {
+            let outcome = match reencrypt_value(&stored, keys)
+            {
+                Ok(Some(reencrypted)) => RowOutcome::Migrated(reencrypted),
+                Ok(None) => RowOutcome::AlreadyCurrent,
+                Err(_) => RowOutcome::Failed,
+            };
+
+            if let RowOutcome::Migrated(reencrypted) = &outcome
+            {
+                sqlx::query("UPDATE databases SET encrypted_password = $1 WHERE id = $2 AND encrypted_password = $3")
+                    .bind(reencrypted)
+                    .bind(database_id)
+                    .bind(&stored)
+                    .execute(pool)
+                    .await
+                    .map_err(|e|
+                    {
+                        error!("Failed to store the re-encrypted password of database {}: {}", database_id, e);
+                        AppError::database(&e)
+                    })?;
+            }
+            else if let RowOutcome::Failed = outcome
+            {
+                warn!("No configured key decrypts the password of database {}.", database_id);
+            }
+            counts.count(&outcome);
+        }
+    }
+
+    Ok(counts)
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::services::crypto_service::EncryptionAlgorithm;
+    use serde_json::json;
+
+    fn keys_with(current: u8, previous: &[u8]) -> EncryptionKeys
+    {
+        EncryptionKeys::new(vec![current; 32], previous.iter().map(|byte| vec![*byte; 32]).collect(), EncryptionAlgorithm::Aes256Gcm)
+    }
+
+    fn encrypted(plaintext: &str, keys: &EncryptionKeys) -> String
+    {
+        BASE64_STANDARD.encode(crypto_service::encrypt(plaintext, keys).unwrap())
+    }
+
+    #[test]
+    fn rows_are_classified_by_the_key_that_decrypts_them()
+    {
+        let keys = keys_with(1, &[2]);
+        let previous_only = keys_with(2, &[]);
+        let unknown = keys_with(3, &[]);
+
+        let rows = [
+            json!({ "A": encrypted("a", &previous_only), "B": encrypted("b", &keys) }),
+            json!({ "A": encrypted("a", &keys) }),
+            json!({ "A": encrypted("a", &unknown) }),
+            json!(["pas", "un", "objet"]),
+        ];
+
+        let mut counts = ReencryptionCounts::default();
+        let outcomes: Vec<_> = rows.iter().map(|row| reencrypt_env_vars(row, &keys)).collect();
+        outcomes.iter().for_each(|outcome| counts.count(outcome));
+
+        assert_eq!((counts.migrated, counts.already_current, counts.failed), (1, 1, 2));
+
+        // La ligne migrée est désormais entièrement sous la clé courante.
+        let RowOutcome::Migrated(migrated) = &outcomes[0] else { panic!("the first row should be migrated") };
+        for value in migrated.as_object().unwrap().values()
+        {
+            let ciphertext = BASE64_STANDARD.decode(value.as_str().unwrap()).unwrap();
+            assert!(crypto_service::decrypt_reporting_key(&ciphertext, &keys).unwrap().1);
+        }
+        assert!(matches!(reencrypt_env_vars(migrated, &keys), RowOutcome::AlreadyCurrent));
+    }
+}
@@ -5,4 +5,28 @@ pub mod docker_service;
 pub mod validation_service;
 pub mod github_service;
 pub mod crypto_service;
-pub mod database_service;
\ No newline at end of file
+pub mod database_service;
+pub mod deployment_service;
+pub mod archive_service;
+pub mod quota_service;
+pub mod invitation_service;
+pub mod scan_service;
+pub mod sbom_service;
+pub mod signature_service;
+pub mod webhook_service;
+pub mod volume_quota_service;
+pub mod cleanup_service;
+pub mod account_service;
+pub mod db_template_service;
+pub mod project_event_service;
+pub mod build_dir_service;
+pub mod policy_service;
+pub mod config_revision_service;
+pub mod team_service;
+pub mod pool_service;
+pub mod audit_service;
+pub mod schedule_service;
+pub mod availability_service;
+pub mod probe_service;
+pub mod env_template_service;
+pub mod key_rotation_service;
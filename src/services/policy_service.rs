@@ -0,0 +1,197 @@
+use sqlx::PgPool;
+use tracing::error;
+
+use crate::
+{
+    config::Config,
+    error::AppError,
+    model::scan::{ScanProfile, ScanThreshold, Severity, ThresholdSource},
+    services::{quota_service, validation_service},
+};
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ScanPolicyLevels
+{
+    pub project_profile: Option<Severity>,
+    pub owner_override: Option<Severity>,
+    pub owner_profile: Option<Severity>,
+}
+
+// Un déploiement peut ensuite demander un seuil plus strict, jamais plus lâche.
+pub async fn get_scan_threshold(
+    pool: &PgPool,
+    config: &Config,
+    owner: &str,
+    project_id: Option<i32>,
+    requested: Option<Severity>,
+) -> Result<ScanThreshold, AppError>
+{
+    let overrides = quota_service::get_user_overrides(pool, owner).await?;
+    let owner_profile = match &overrides.scan_profile
+    {
+        Some(name) => get_profile(pool, name).await?.map(|profile| profile.fail_on_severity),
+        None => None,
+    };
+    let project_profile = match project_id
+    {
+        Some(project_id) => get_project_profile_severity(pool, project_id).await?,
+        None => None,
+    };
+
+    let levels = ScanPolicyLevels { project_profile, owner_override: overrides.scan_fail_on_severity, owner_profile };
+    resolve_scan_threshold(config.grype_fail_on_severity, levels, requested)
+}
+
+pub fn resolve_scan_threshold(
+    platform_default: Severity,
+    levels: ScanPolicyLevels,
+    requested: Option<Severity>,
+) -> Result<ScanThreshold, AppError>
+{
+    let base = [
+        (levels.project_profile, ThresholdSource::ProjectProfile),
+        (levels.owner_override, ThresholdSource::OwnerOverride),
+        (levels.owner_profile, ThresholdSource::OwnerProfile),
+    ]
+        .into_iter()
+        .find_map(|(severity, source)| severity.map(|severity| ScanThreshold { severity, source }))
+        .unwrap_or(ScanThreshold { severity: platform_default, source: ThresholdSource::Platform });
+
+    validation_service::validate_scan_fail_on(requested, base.severity)?;
+
+    Ok(match requested
+    {
+        Some(severity) if severity < base.severity => ScanThreshold { severity, source: ThresholdSource::Deploy },
+        _ => base,
+    })
+}
+
+pub async fn get_all_profiles(pool: &PgPool) -> Result<Vec<ScanProfile>, AppError>
+{
+    sqlx::query_as::<_, ScanProfile>("SELECT name, fail_on_severity, updated_by, updated_at FROM scan_profiles ORDER BY name")
+        .fetch_all(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to fetch scan profiles: {}", e);
+            AppError::database(&e)
+        })
+}
+
+pub async fn get_profile(pool: &PgPool, name: &str) -> Result<Option<ScanProfile>, AppError>
+{
+    sqlx::query_as::<_, ScanProfile>("SELECT name, fail_on_severity, updated_by, updated_at FROM scan_profiles WHERE name = $1")
+        .bind(name)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to fetch scan profile '{}': {}", name, e);
+            AppError::database(&e)
+        })
+}
+
+// Le nouveau seuil s'applique au prochain scan, sans relancer ceux déjà passés.
+pub async fn set_profile(pool: &PgPool, name: &str, fail_on_severity: Severity, admin_login: &str) -> Result<ScanProfile, AppError>
+{
+    sqlx::query_as::<_, ScanProfile>(
+        "INSERT INTO scan_profiles (name, fail_on_severity, updated_by) VALUES ($1, $2, $3)
+         ON CONFLICT (name) DO UPDATE SET fail_on_severity = $2, updated_by = $3, updated_at = NOW()
+         RETURNING name, fail_on_severity, updated_by, updated_at"
+    )
+        .bind(name)
+        .bind(fail_on_severity)
+        .bind(admin_login)
+        .fetch_one(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to save scan profile '{}': {}", name, e);
+            AppError::database(&e)
+        })
+}
+
+pub async fn delete_profile(pool: &PgPool, name: &str) -> Result<bool, AppError>
+{
+    let result = sqlx::query("DELETE FROM scan_profiles WHERE name = $1")
+        .bind(name)
+        .execute(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to delete scan profile '{}': {}", name, e);
+            AppError::database(&e)
+        })?;
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn set_project_profile(pool: &PgPool, project_id: i32, profile: Option<&str>) -> Result<(), AppError>
+{
+    sqlx::query("UPDATE projects SET scan_profile = $1, updated_at = NOW() WHERE id = $2")
+        .bind(profile)
+        .bind(project_id)
+        .execute(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to set the scan profile of project {}: {}", project_id, e);
+            AppError::database(&e)
+        })?;
+    Ok(())
+}
+
+async fn get_project_profile_severity(pool: &PgPool, project_id: i32) -> Result<Option<Severity>, AppError>
+{
+    sqlx::query_scalar(
+        "SELECT sp.fail_on_severity FROM projects p JOIN scan_profiles sp ON sp.name = p.scan_profile WHERE p.id = $1"
+    )
+        .bind(project_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to fetch the scan profile of project {}: {}", project_id, e);
+            AppError::database(&e)
+        })
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn each_level_overrides_the_more_general_ones()
+    {
+        use Severity::{Critical, High, Low, Medium};
+        use ThresholdSource::{Deploy, OwnerOverride, OwnerProfile, Platform, ProjectProfile};
+
+        let levels = |project_profile, owner_override, owner_profile| ScanPolicyLevels { project_profile, owner_override, owner_profile };
+
+        // (niveaux, seuil demandé par le déploiement) -> seuil retenu et sa source
+        let cases = [
+            (levels(None, None, None), None, High, Platform),
+            (levels(None, None, Some(Critical)), None, Critical, OwnerProfile),
+            (levels(None, Some(Medium), Some(Critical)), None, Medium, OwnerOverride),
+            (levels(Some(Low), Some(Medium), Some(Critical)), None, Low, ProjectProfile),
+            (levels(Some(Critical), None, None), None, Critical, ProjectProfile),
+            (levels(Some(Critical), Some(Medium), None), Some(High), High, Deploy),
+            (levels(None, None, None), Some(Medium), Medium, Deploy),
+            (levels(None, None, Some(Critical)), Some(Critical), Critical, OwnerProfile),
+        ];
+
+        for (levels, requested, severity, source) in cases
+        {
+            let threshold = resolve_scan_threshold(High, levels, requested).unwrap();
+            assert_eq!(threshold, ScanThreshold { severity, source }, "{:?} with {:?} requested", levels, requested);
+        }
+    }
+
+    #[test]
+    fn a_deploy_cannot_loosen_the_resolved_threshold()
+    {
+        let levels = ScanPolicyLevels { project_profile: Some(Severity::Medium), ..Default::default() };
+
+        assert!(resolve_scan_threshold(Severity::Critical, levels, Some(Severity::High)).is_err());
+    }
+}
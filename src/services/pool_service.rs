@@ -0,0 +1,117 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use serde::Serialize;
+use sqlx::{pool::PoolOptions, Pool};
+
+use crate::config::Config;
+
+// `sqlx` ne dit pas quel pool a expiré : le compteur est commun.
+static ACQUIRE_TIMEOUTS: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, Serialize)]
+pub struct PoolStats
+{
+    pub name: &'static str,
+    pub max_connections: u32,
+    pub size: u32,
+    pub idle: usize,
+    pub in_use: u32,
+    pub acquire_timeout_seconds: u64,
+    pub max_lifetime_seconds: Option<u64>,
+    pub idle_timeout_seconds: Option<u64>,
+}
+
+pub fn record_acquire_timeout()
+{
+    ACQUIRE_TIMEOUTS.fetch_add(1, Ordering::Relaxed);
+}
+
+pub fn acquire_timeouts() -> u64
+{
+    ACQUIRE_TIMEOUTS.load(Ordering::Relaxed)
+}
+
+pub fn get_stats<DB: sqlx::Database>(name: &'static str, pool: &Pool<DB>) -> PoolStats
+{
+    let options = pool.options();
+    let size = pool.size();
+    let idle = pool.num_idle();
+
+    PoolStats
+    {
+        name,
+        max_connections: options.get_max_connections(),
+        size,
+        idle,
+        in_use: size.saturating_sub(idle as u32),
+        acquire_timeout_seconds: options.get_acquire_timeout().as_secs(),
+        max_lifetime_seconds: options.get_max_lifetime().map(|lifetime| lifetime.as_secs()),
+        idle_timeout_seconds: options.get_idle_timeout().map(|timeout| timeout.as_secs()),
+    }
+}
+
+pub fn pool_options<DB: sqlx::Database>(config: &Config) -> PoolOptions<DB>
+{
+    let seconds = |value: u64| (value > 0).then(|| std::time::Duration::from_secs(value));
+
+    PoolOptions::<DB>::new()
+        .max_connections(config.db_max_connections)
+        .acquire_timeout(std::time::Duration::from_secs(config.db_acquire_timeout_seconds))
+        .max_lifetime(seconds(config.db_max_lifetime_seconds))
+        .idle_timeout(seconds(config.db_idle_timeout_seconds))
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use axum::http::StatusCode;
+    use sqlx::Postgres;
+
+    use crate::{error::AppError, test_support::stalling_server};
+
+    fn config(database_url: &str) -> Config
+    {
+        Config::for_tests(&[("DATABASE_URL", database_url), ("DB_MAX_CONNECTIONS", "1"), ("DB_ACQUIRE_TIMEOUT_SECONDS", "1")]).unwrap()
+    }
+
+    #[tokio::test]
+    async fn exhausted_pool_answers_503_to_concurrent_requests()
+    {
+        // Aucune connexion n'aboutit : le pool reste à sec.
+        let config = config(&format!("postgres://hangar@{}/hangar", stalling_server()));
+        let pool = pool_options::<Postgres>(&config).connect_lazy(&config.db_url).unwrap();
+        let timeouts_before = acquire_timeouts();
+
+        let requests = (0..8).map(|_|
+        {
+            let pool = pool.clone();
+            tokio::spawn(async move
+            {
+                sqlx::query("SELECT 1").execute(&pool).await.map_err(|e| AppError::database(&e))
+            })
+        });
+
+        for request in requests.collect::<Vec<_>>()
+        {
+            let error = request.await.unwrap().unwrap_err();
+            assert_eq!(error.status_code(), StatusCode::SERVICE_UNAVAILABLE);
+            assert_eq!(error.error_code(), "DATABASE_BUSY");
+        }
+        assert!(acquire_timeouts() >= timeouts_before + 8);
+    }
+
+    #[tokio::test]
+    async fn stats_reflect_the_configured_options()
+    {
+        let config = config("postgres://hangar@127.0.0.1:1/hangar");
+        let pool = pool_options::<Postgres>(&config).connect_lazy(&config.db_url).unwrap();
+
+        let stats = get_stats("postgres", &pool);
+
+        assert_eq!((stats.max_connections, stats.size, stats.in_use), (1, 0, 0));
+        assert_eq!(stats.acquire_timeout_seconds, 1);
+        assert_eq!(stats.max_lifetime_seconds, Some(30 * 60));
+        assert_eq!(stats.idle_timeout_seconds, Some(10 * 60));
+    }
+}
@@ -0,0 +1,85 @@
+use std::time::Instant;
+
+use tracing::debug;
+
+use crate::model::project::{ProbeFailure, ProbeResult};
+
+// Une application injoignable est un résultat, pas une erreur.
+pub async fn probe(client: &reqwest::Client, url: &str) -> ProbeResult
+{
+    let https = url.starts_with("https://");
+    let started = Instant::now();
+    let response = client.get(url).send().await;
+    let latency_ms = started.elapsed().as_millis() as u64;
+
+    match response
+    {
+        Ok(response) => ProbeResult
+        {
+            url: url.to_string(),
+            final_url: Some(response.url().to_string()),
+            reachable: true,
+            status_code: Some(response.status().as_u16()),
+            latency_ms,
+            tls_valid: https.then_some(true),
+            failure: None,
+        },
+        Err(e) =>
+        {
+            debug!("Probe of '{}' failed: {:?}", url, e);
+            let failure = classify_failure(&e);
+
+            ProbeResult
+            {
+                url: url.to_string(),
+                final_url: None,
+                reachable: false,
+                status_code: None,
+                latency_ms,
+                tls_valid: (failure == ProbeFailure::TlsError).then_some(false),
+                failure: Some(failure),
+            }
+        }
+    }
+}
+
+fn classify_failure(error: &reqwest::Error) -> ProbeFailure
+{
+    if error.is_timeout()
+    {
+        return ProbeFailure::Timeout;
+    }
+    if error.is_redirect()
+    {
+        return ProbeFailure::TooManyRedirects;
+    }
+
+    // reqwest does not expose the cause of a connection error: the chain of sources tells it.
+    let mut source: Option<&(dyn std::error::Error + 'static)> = Some(error);
+    while let Some(cause) = source
+    {
+        if let Some(io_error) = cause.downcast_ref::<std::io::Error>()
+        {
+            match io_error.kind()
+            {
+                std::io::ErrorKind::ConnectionRefused => return ProbeFailure::ConnectionRefused,
+                std::io::ErrorKind::TimedOut => return ProbeFailure::Timeout,
+                _ => {}
+            }
+        }
+
+        let message = cause.to_string().to_lowercase();
+        if message.contains("dns error") || message.contains("failed to lookup address")
+        {
+            return ProbeFailure::DnsFailure;
+        }
+        if message.contains("certificate") || message.contains("tls") || message.contains("ssl")
+        {
+            return ProbeFailure::TlsError;
+        }
+
+        source = cause.source();
+    }
+
+    ProbeFailure::ConnectionFailed
+}
@@ -0,0 +1,101 @@
+use sqlx::PgPool;
+use tracing::error;
+
+use crate::
+{
+    error::AppError,
+    model::{audit::{AuditRecord, AuditTargetType}, project::Project, project_event::{ProjectEvent, ProjectEventAction}},
+    services::{audit_service, jwt::Claims},
+};
+
+// Seul un administrateur agissant sur le projet d'un autre est signalé ; ses propres projets restent les siens.
+pub fn performed_by_admin(project_owner: &str, claims: &Claims) -> bool
+{
+    claims.is_admin && claims.sub != project_owner
+}
+
+// Aussi écrit dans le journal d'audit, où les administrateurs cherchent tous projets confondus.
+pub async fn record_event(
+    pool: &PgPool,
+    project: &Project,
+    action: ProjectEventAction,
+    actor: &str,
+    performed_by_admin: bool,
+    details: Option<serde_json::Value>,
+) -> Result<(), AppError>
+{
+    audit_service::record(pool, AuditRecord
+    {
+        actor,
+        action: action.as_str(),
+        target_type: AuditTargetType::Project,
+        target_id: project.id,
+        target_name: Some(&project.name),
+        performed_by_admin,
+        details: details.clone(),
+    }).await;
+
+    sqlx::query(
+        "INSERT INTO project_events (project_id, project_name, action, actor, performed_by_admin, details)
+         VALUES ($1, $2, $3, $4, $5, $6)"
+    )
+        .bind(project.id)
+        .bind(&project.name)
+        .bind(action)
+        .bind(actor)
+        .bind(performed_by_admin)
+        .bind(details)
+        .execute(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to record event {:?} of project {}: {}", action, project.id, e);
+            AppError::database(&e)
+        })?;
+    Ok(())
+}
+
+pub async fn get_project_events(pool: &PgPool, project_id: i32, limit: i64) -> Result<Vec<ProjectEvent>, AppError>
+{
+    sqlx::query_as::<_, ProjectEvent>(
+        "SELECT id, project_id, action, actor, performed_by_admin, details, created_at
+         FROM project_events WHERE project_id = $1 ORDER BY created_at DESC LIMIT $2"
+    )
+        .bind(project_id)
+        .bind(limit)
+        .fetch_all(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to fetch events of project {}: {}", project_id, e);
+            AppError::database(&e)
+        })
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn claims(login: &str, is_admin: bool) -> Claims
+    {
+        Claims { sub: login.to_string(), name: login.to_string(), email: format!("{}@hangar.test", login), exp: 0, iat: 0, is_admin }
+    }
+
+    #[test]
+    fn only_an_admin_acting_on_someone_else_is_flagged()
+    {
+        // (acteur, administrateur, propriétaire) -> signalé
+        let cases = [
+            ("root", true, "alice", true),
+            ("alice", false, "alice", false),
+            ("root", true, "root", false),
+            ("bob", false, "alice", false),
+        ];
+
+        for (actor, is_admin, owner, expected) in cases
+        {
+            assert_eq!(performed_by_admin(owner, &claims(actor, is_admin)), expected, "{} acting on {}'s project", actor, owner);
+        }
+    }
+}
@@ -1,8 +1,10 @@
 use std::collections::HashMap;
-use sqlx::{PgPool, Postgres, Transaction};
+use futures::stream::{BoxStream, StreamExt};
+use sqlx::{PgPool, Postgres, QueryBuilder, Transaction};
 use tracing::{error, warn};
-use crate::{error::{AppError, ProjectErrorCode}, model::project::{Project, ProjectSourceType}, services::crypto_service};
+use crate::{api::list_params::{ListParams, ListSpec, SortOrder}, error::{AppError, ProjectErrorCode}, model::{invitation::ParticipantStatus, project::{AdminProjectRow, EgressPolicy, LogsVisibility, MyProjectRow, Project, ProjectFilters, ProjectExportRow, ProjectNoteRevision, ProjectSourceType, ProjectStatus, RouteMiddlewares}}, services::crypto_service::{self, EncryptionKeys}};
 use base64::prelude::*;
+use time::OffsetDateTime;
 
 pub async fn check_project_name_exists(pool: &PgPool, name: &str) -> Result<bool, AppError> 
 {
@@ -10,38 +12,48 @@ pub async fn check_project_name_exists(pool: &PgPool, name: &str) -> Result<bool
         .bind(name)
         .fetch_one(pool)
         .await
-        .map_err(|_| AppError::InternalServerError)?;
+        .map_err(|e| AppError::database(&e))?;
     Ok(count.0 > 0)
 }
 
-pub async fn check_owner_exists(pool: &PgPool, owner: &str) -> Result<bool, AppError> 
+pub async fn count_projects_by_owner(pool: &PgPool, owner: &str) -> Result<i64, AppError> 
 {
-    let count: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM projects WHERE owner = $1")
+    let count: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM projects WHERE owner = $1 AND team_id IS NULL")
         .bind(owner)
         .fetch_one(pool)
         .await
-        .map_err(|_| AppError::InternalServerError)?;
-    Ok(count.0 > 0)
+        .map_err(|e| AppError::database(&e))?;
+    Ok(count.0)
+}
+
+pub struct NewProject<'a>
+{
+    pub name: &'a str,
+    pub owner: &'a str,
+    pub container_name: &'a str,
+    pub container_id: &'a str,
+    pub source_type: ProjectSourceType,
+    pub source_url: &'a str,
+    pub source_branch: Option<&'a str>,
+    pub source_root_dir: Option<&'a str>,
+    pub deployed_image_tag: &'a str,
+    pub deployed_image_digest: &'a str,
+    pub env_vars: Option<&'a HashMap<String, String>>,
+    pub persistent_volume_path: Option<&'a str>,
+    pub volume_name: Option<&'a str>,
+    pub docker_host: &'a str,
+    pub team_id: Option<i32>,
+    pub description: Option<&'a str>,
+    pub tags: &'a [String],
 }
 
 pub async fn create_project<'a>(
     tx: &mut Transaction<'a, Postgres>,
-    name: &str,
-    owner: &str,
-    container_name: &str,
-    source_type: ProjectSourceType,
-    source_url: &str,
-    source_branch: &Option<String>,
-    source_root_dir: &Option<String>,
-    deployed_image_tag: &str,
-    deployed_image_digest: &str,
-    env_vars: &Option<HashMap<String, String>>,
-    persistent_volume_path: &Option<String>,
-    volume_name: &Option<String>,
-    encryption_key: &[u8]
+    new_project: &NewProject<'_>,
+    encryption_key: &EncryptionKeys
 ) -> Result<Project, AppError> 
 {
-    let encrypted_env_vars = match env_vars
+    let encrypted_env_vars = match new_project.env_vars
     {
         Some(vars) => Some(encrypt_env_vars(vars, encryption_key)?),
         None => None,
@@ -51,22 +63,27 @@ pub async fn create_project<'a>(
         .map_err(|_| AppError::InternalServerError)?;
 
     let project = sqlx::query_as::<_, Project>(
-        "INSERT INTO projects (name, owner, container_name, source_type, source_url, source_branch, source_root_dir, deployed_image_tag, deployed_image_digest, env_vars, persistent_volume_path, volume_name)
-         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
-         RETURNING id, name, owner, container_name, source_type, source_url, source_branch, source_root_dir, deployed_image_tag, deployed_image_digest, created_at, env_vars, persistent_volume_path, volume_name",
+        "INSERT INTO projects (name, owner, container_name, source_type, source_url, source_branch, source_root_dir, deployed_image_tag, deployed_image_digest, env_vars, persistent_volume_path, volume_name, docker_host, container_id, team_id, description, tags)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17)
+         RETURNING id, name, owner, container_name, source_type, source_url, source_branch, source_root_dir, deployed_image_tag, deployed_image_digest, created_at, updated_at, env_vars, persistent_volume_path, volume_name, docker_host, rate_limit_average, rate_limit_burst, rate_limit_set_by_admin, ip_allowlist, intended_running, lost_container, image_platform, healthcheck_path, healthcheck_interval, healthcheck_port, replicas, volume_share_safe, maintenance_page, image_created_at, last_scanned_at, container_id, egress_policy, routing_options, team_id, logs_visibility, logs_redaction, description, tags, custom_domains, archived_at, deleted_at, last_deployed_at, status, status_changed_at, auto_start_cron, auto_stop_cron",
     )
-    .bind(name)
-    .bind(owner)
-    .bind(container_name)
-    .bind(source_type)
-    .bind(source_url)
-    .bind(source_branch)
-    .bind(source_root_dir)
-    .bind(deployed_image_tag)
-    .bind(deployed_image_digest)
+    .bind(new_project.name)
+    .bind(new_project.owner)
+    .bind(new_project.container_name)
+    .bind(new_project.source_type)
+    .bind(new_project.source_url)
+    .bind(new_project.source_branch)
+    .bind(new_project.source_root_dir)
+    .bind(new_project.deployed_image_tag)
+    .bind(new_project.deployed_image_digest)
     .bind(env_vars_json)
-    .bind(persistent_volume_path)
-    .bind(volume_name)
+    .bind(new_project.persistent_volume_path)
+    .bind(new_project.volume_name)
+    .bind(new_project.docker_host)
+    .bind(new_project.container_id)
+    .bind(new_project.team_id)
+    .bind(new_project.description)
+    .bind(new_project.tags)
     .fetch_one(&mut **tx)
     .await
     .map_err(|e: sqlx::Error| 
@@ -103,19 +120,35 @@ pub async fn delete_project_by_id(pool: &PgPool, project_id: i32) -> Result<(),
     Ok(())
 }
 
-const SELECT_PROJECT_FIELDS: &str = "SELECT id, name, owner, container_name, source_type, source_url, source_branch, source_root_dir, deployed_image_tag, deployed_image_digest, created_at, env_vars, persistent_volume_path, volume_name FROM projects";
+const SELECT_PROJECT_FIELDS: &str = "SELECT id, name, owner, container_name, source_type, source_url, source_branch, source_root_dir, deployed_image_tag, deployed_image_digest, created_at, updated_at, env_vars, persistent_volume_path, volume_name, docker_host, rate_limit_average, rate_limit_burst, rate_limit_set_by_admin, ip_allowlist, intended_running, lost_container, image_platform, healthcheck_path, healthcheck_interval, healthcheck_port, replicas, volume_share_safe, maintenance_page, image_created_at, last_scanned_at, container_id, egress_policy, routing_options, team_id, logs_visibility, logs_redaction, description, tags, custom_domains, archived_at, deleted_at, last_deployed_at, status, status_changed_at, auto_start_cron, auto_stop_cron FROM projects";
 
-pub async fn get_projects_by_owner(pool: &PgPool, owner: &str) -> Result<Vec<Project>, AppError> 
+// Les projets d'équipe appartiennent à leur équipe, quel que soit celui qui les a déployés.
+pub async fn get_projects_by_owner(pool: &PgPool, owner: &str, include_archived: bool) -> Result<Vec<Project>, AppError> 
 {
-    let query = format!("{} WHERE owner = $1 ORDER BY created_at DESC", SELECT_PROJECT_FIELDS);
+    let query = format!("{} WHERE owner = $1 AND team_id IS NULL AND deleted_at IS NULL AND ($2 OR archived_at IS NULL) ORDER BY created_at DESC", SELECT_PROJECT_FIELDS);
     sqlx::query_as::<_, Project>(&query)
         .bind(owner)
+        .bind(include_archived)
         .fetch_all(pool)
         .await
         .map_err(|e| 
         {
             error!("Failed to fetch projects for owner '{}': {}", owner, e);
-            AppError::InternalServerError
+            AppError::database(&e)
+        })
+}
+
+// Nombre de lignes et dernière modification : de quoi détecter tout changement de la liste.
+pub async fn get_owned_projects_fingerprint(pool: &PgPool, owner: &str) -> Result<(i64, Option<OffsetDateTime>), AppError>
+{
+    sqlx::query_as("SELECT COUNT(*), MAX(updated_at) FROM projects WHERE owner = $1 AND team_id IS NULL AND deleted_at IS NULL")
+        .bind(owner)
+        .fetch_one(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to fetch projects fingerprint for owner '{}': {}", owner, e);
+            AppError::database(&e)
         })
 }
 
@@ -128,7 +161,7 @@ pub async fn get_project_by_id_and_owner(
 {
     if is_admin 
     {
-        let query = format!("{} WHERE id = $1", SELECT_PROJECT_FIELDS);
+        let query = format!("{} WHERE id = $1 AND deleted_at IS NULL", SELECT_PROJECT_FIELDS);
         return sqlx::query_as::<_, Project>(&query)
             .bind(project_id)
             .fetch_optional(pool)
@@ -136,11 +169,16 @@ pub async fn get_project_by_id_and_owner(
             .map_err(|e| 
             {
                 error!("Admin failed to fetch project by id {}: {}", project_id, e);
-                AppError::InternalServerError
+                AppError::database(&e)
             });
     }
 
-    let query = format!("{} WHERE id = $1 AND owner = $2", SELECT_PROJECT_FIELDS);
+    // Les droits sur un projet d'équipe viennent du rôle dans l'équipe, pas de qui l'a déployé.
+    let query = format!(
+        "{} WHERE id = $1 AND deleted_at IS NULL AND ((team_id IS NULL AND owner = $2) OR EXISTS (
+             SELECT 1 FROM team_members tm WHERE tm.team_id = projects.team_id AND tm.login = $2 AND tm.role = 'owner'))",
+        SELECT_PROJECT_FIELDS
+    );
     sqlx::query_as::<_, Project>(&query)
         .bind(project_id)
         .bind(owner)
@@ -149,29 +187,222 @@ pub async fn get_project_by_id_and_owner(
         .map_err(|e| 
         {
             error!("Failed to fetch project by id {} and owner '{}': {}", project_id, owner, e);
-            AppError::InternalServerError
+            AppError::database(&e)
+        })
+}
+
+pub async fn get_deleted_project_by_id_and_owner(
+    pool: &PgPool,
+    project_id: i32,
+    owner: &str,
+    is_admin: bool,
+) -> Result<Option<Project>, AppError>
+{
+    let query = format!(
+        "{} WHERE id = $1 AND deleted_at IS NOT NULL AND ($3 OR (team_id IS NULL AND owner = $2) OR EXISTS (
+             SELECT 1 FROM team_members tm WHERE tm.team_id = projects.team_id AND tm.login = $2 AND tm.role = 'owner'))",
+        SELECT_PROJECT_FIELDS
+    );
+    sqlx::query_as::<_, Project>(&query)
+        .bind(project_id)
+        .bind(owner)
+        .bind(is_admin)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to fetch deleted project {} for '{}': {}", project_id, owner, e);
+            AppError::database(&e)
+        })
+}
+
+pub async fn get_projects_due_for_purge(pool: &PgPool, grace_hours: i64) -> Result<Vec<Project>, AppError>
+{
+    let query = format!("{} WHERE deleted_at < NOW() - make_interval(hours => $1) ORDER BY deleted_at", SELECT_PROJECT_FIELDS);
+    sqlx::query_as::<_, Project>(&query)
+        .bind(grace_hours as i32)
+        .fetch_all(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to fetch the deleted projects due for purge: {}", e);
+            AppError::database(&e)
+        })
+}
+
+pub async fn get_projects_by_team(pool: &PgPool, team_id: i32) -> Result<Vec<Project>, AppError>
+{
+    let query = format!("{} WHERE team_id = $1 AND deleted_at IS NULL ORDER BY created_at DESC", SELECT_PROJECT_FIELDS);
+    sqlx::query_as::<_, Project>(&query)
+        .bind(team_id)
+        .fetch_all(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to fetch projects of team {}: {}", team_id, e);
+            AppError::database(&e)
+        })
+}
+
+pub async fn count_projects_by_team(pool: &PgPool, team_id: i32) -> Result<i64, AppError>
+{
+    sqlx::query_scalar("SELECT COUNT(*) FROM projects WHERE team_id = $1")
+        .bind(team_id)
+        .fetch_one(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to count projects of team {}: {}", team_id, e);
+            AppError::database(&e)
+        })
+}
+
+pub async fn get_project_by_container_name(pool: &PgPool, container_name: &str) -> Result<Option<Project>, AppError> 
+{
+    let query = format!("{} WHERE container_name = $1", SELECT_PROJECT_FIELDS);
+    sqlx::query_as::<_, Project>(&query)
+        .bind(container_name)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e| 
+        {
+            error!("Failed to fetch project by container name '{}': {}", container_name, e);
+            AppError::database(&e)
         })
 }
 
-pub async fn get_participating_projects(pool: &PgPool, participant_id: &str) -> Result<Vec<Project>, AppError> 
+pub async fn get_participating_projects(pool: &PgPool, participant_id: &str, include_archived: bool) -> Result<Vec<Project>, AppError> 
 {
     sqlx::query_as::<_, Project>(
-        "SELECT p.id, p.name, p.owner, p.container_name, p.source_type, p.source_url, p.source_branch, p.source_root_dir, p.deployed_image_tag, p.deployed_image_digest, p.created_at, p.env_vars, p.persistent_volume_path, p.volume_name
+        "SELECT p.id, p.name, p.owner, p.container_name, p.source_type, p.source_url, p.source_branch, p.source_root_dir, p.deployed_image_tag, p.deployed_image_digest, p.created_at, p.updated_at, p.env_vars, p.persistent_volume_path, p.volume_name, p.docker_host, p.rate_limit_average, p.rate_limit_burst, p.rate_limit_set_by_admin, p.ip_allowlist, p.intended_running, p.lost_container, p.image_platform, p.healthcheck_path, p.healthcheck_interval, p.healthcheck_port, p.replicas, p.volume_share_safe, p.maintenance_page, p.image_created_at, p.last_scanned_at, p.container_id, p.egress_policy, p.routing_options, p.team_id, p.logs_visibility, p.logs_redaction, p.description, p.tags, p.custom_domains, p.archived_at, p.deleted_at, p.last_deployed_at, p.status, p.status_changed_at, p.auto_start_cron, p.auto_stop_cron
          FROM projects p
          JOIN project_participants pp ON p.id = pp.project_id
-         WHERE pp.participant_id = $1
+         WHERE pp.participant_id = $1 AND pp.status = 'accepted' AND p.deleted_at IS NULL AND ($2 OR p.archived_at IS NULL)
          ORDER BY p.created_at DESC"
     )
         .bind(participant_id)
+        .bind(include_archived)
         .fetch_all(pool)
         .await
         .map_err(|e| 
         {
             error!("Failed to fetch participating projects for user '{}': {}", participant_id, e);
-            AppError::InternalServerError
+            AppError::database(&e)
+        })
+}
+
+pub async fn get_projects_by_ids_for_user(
+    pool: &PgPool,
+    project_ids: &[i32],
+    user_login: &str,
+    is_admin: bool,
+) -> Result<Vec<Project>, AppError>
+{
+    let query = format!(
+        "{} p
+         WHERE p.id = ANY($1) AND p.deleted_at IS NULL
+           AND ($3 OR (p.team_id IS NULL AND p.owner = $2) OR EXISTS (
+                SELECT 1 FROM project_participants pp
+                WHERE pp.project_id = p.id AND pp.participant_id = $2 AND pp.status = 'accepted') OR EXISTS (
+                SELECT 1 FROM team_members tm WHERE tm.team_id = p.team_id AND tm.login = $2))",
+        SELECT_PROJECT_FIELDS
+    );
+    sqlx::query_as::<_, Project>(&query)
+        .bind(project_ids)
+        .bind(user_login)
+        .bind(is_admin)
+        .fetch_all(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to fetch {} project(s) for user '{}': {}", project_ids.len(), user_login, e);
+            AppError::database(&e)
+        })
+}
+
+pub async fn get_my_projects(pool: &PgPool, login: &str, include_archived: bool) -> Result<Vec<MyProjectRow>, AppError>
+{
+    sqlx::query_as::<_, MyProjectRow>(
+        "SELECT p.id, p.name, p.owner, p.container_name, p.source_type, p.source_url, p.source_branch, p.source_root_dir, p.deployed_image_tag, p.deployed_image_digest, p.created_at, p.updated_at, p.env_vars, p.persistent_volume_path, p.volume_name, p.docker_host, p.rate_limit_average, p.rate_limit_burst, p.rate_limit_set_by_admin, p.ip_allowlist, p.intended_running, p.lost_container, p.image_platform, p.healthcheck_path, p.healthcheck_interval, p.healthcheck_port, p.replicas, p.volume_share_safe, p.maintenance_page, p.image_created_at, p.last_scanned_at, p.container_id, p.egress_policy, p.routing_options, p.team_id, p.logs_visibility, p.logs_redaction, p.description, p.tags, p.custom_domains, p.archived_at, p.deleted_at, p.last_deployed_at, p.status, p.status_changed_at, p.auto_start_cron, p.auto_stop_cron,
+                (p.team_id IS NULL AND p.owner = $1) AS role
+         FROM projects p
+         WHERE p.deleted_at IS NULL AND ($2 OR p.archived_at IS NULL)
+           AND ((p.team_id IS NULL AND p.owner = $1) OR EXISTS (
+                SELECT 1 FROM project_participants pp
+                WHERE pp.project_id = p.id AND pp.participant_id = $1 AND pp.status = 'accepted'))
+         ORDER BY p.created_at DESC"
+    )
+        .bind(login)
+        .bind(include_archived)
+        .fetch_all(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to fetch the projects of user '{}': {}", login, e);
+            AppError::database(&e)
         })
 }
 
+fn push_search_filters(query: &mut QueryBuilder<'_, Postgres>, search: &str, user_login: &str, is_admin: bool)
+{
+    let pattern = format!("{}%", search.to_lowercase().replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_"));
+
+    query.push(" WHERE p.deleted_at IS NULL AND (LOWER(p.name) LIKE ").push_bind(pattern.clone());
+    query.push(" OR LOWER(p.owner) LIKE ").push_bind(pattern.clone());
+    query.push(" OR EXISTS (SELECT 1 FROM UNNEST(p.tags) AS tag WHERE LOWER(tag) LIKE ").push_bind(pattern).push("))");
+
+    if !is_admin
+    {
+        query.push(" AND ((p.team_id IS NULL AND p.owner = ").push_bind(user_login.to_string());
+        query.push(") OR EXISTS (SELECT 1 FROM project_participants pp WHERE pp.project_id = p.id AND pp.status = 'accepted' AND pp.participant_id = ")
+            .push_bind(user_login.to_string());
+        query.push(") OR EXISTS (SELECT 1 FROM team_members tm WHERE tm.team_id = p.team_id AND tm.login = ")
+            .push_bind(user_login.to_string())
+            .push("))");
+    }
+}
+
+// Le nom, le propriétaire ou l'un des tags doit commencer par `search`, sans tenir compte de la casse.
+pub async fn search_projects<S: ListSpec>(
+    pool: &PgPool,
+    search: &str,
+    user_login: &str,
+    is_admin: bool,
+    list_params: &ListParams<S>,
+) -> Result<(Vec<Project>, i64), AppError>
+{
+    let order = list_params.order.as_sql();
+
+    let mut query = QueryBuilder::new(format!("{} p", SELECT_PROJECT_FIELDS));
+    push_search_filters(&mut query, search, user_login, is_admin);
+    query.push(format!(" ORDER BY p.{} {}, p.id {}", list_params.sort_by, order, order));
+    query.push(" LIMIT ").push_bind(i64::from(list_params.per_page));
+    query.push(" OFFSET ").push_bind(list_params.offset());
+
+    let projects = query.build_query_as::<Project>()
+        .fetch_all(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to search projects for user '{}': {}", user_login, e);
+            AppError::database(&e)
+        })?;
+
+    let mut count = QueryBuilder::new("SELECT COUNT(*) FROM projects p");
+    push_search_filters(&mut count, search, user_login, is_admin);
+
+    let total: i64 = count.build_query_scalar()
+        .fetch_one(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to count the search results of user '{}': {}", user_login, e);
+            AppError::database(&e)
+        })?;
+
+    Ok((projects, total))
+}
+
 pub async fn get_project_by_id_for_user(
     pool: &PgPool,
     project_id: i32,
@@ -181,22 +412,23 @@ pub async fn get_project_by_id_for_user(
 {
     if is_admin 
     {
-        return sqlx::query_as::<_, Project>(&format!("{} WHERE id = $1", SELECT_PROJECT_FIELDS))
+        return sqlx::query_as::<_, Project>(&format!("{} WHERE id = $1 AND deleted_at IS NULL", SELECT_PROJECT_FIELDS))
             .bind(project_id)
             .fetch_optional(pool)
             .await
             .map_err(|e| 
             {
                 error!("Failed to fetch project {} for admin '{}': {}", project_id, user_login, e);
-                AppError::InternalServerError
+                AppError::database(&e)
             });
     }
 
     sqlx::query_as::<_, Project>(
-        "SELECT p.id, p.name, p.owner, p.container_name, p.source_type, p.source_url, p.source_branch, p.source_root_dir, p.deployed_image_tag, p.deployed_image_digest, p.created_at, p.env_vars, p.persistent_volume_path, p.volume_name
+        "SELECT p.id, p.name, p.owner, p.container_name, p.source_type, p.source_url, p.source_branch, p.source_root_dir, p.deployed_image_tag, p.deployed_image_digest, p.created_at, p.updated_at, p.env_vars, p.persistent_volume_path, p.volume_name, p.docker_host, p.rate_limit_average, p.rate_limit_burst, p.rate_limit_set_by_admin, p.ip_allowlist, p.intended_running, p.lost_container, p.image_platform, p.healthcheck_path, p.healthcheck_interval, p.healthcheck_port, p.replicas, p.volume_share_safe, p.maintenance_page, p.image_created_at, p.last_scanned_at, p.container_id, p.egress_policy, p.routing_options, p.team_id, p.logs_visibility, p.logs_redaction, p.description, p.tags, p.custom_domains, p.archived_at, p.deleted_at, p.last_deployed_at, p.status, p.status_changed_at, p.auto_start_cron, p.auto_stop_cron
          FROM projects p
-         LEFT JOIN project_participants pp ON p.id = pp.project_id
-         WHERE p.id = $1 AND (p.owner = $2 OR pp.participant_id = $2)"
+         LEFT JOIN project_participants pp ON p.id = pp.project_id AND pp.status = 'accepted' AND pp.participant_id = $2
+         WHERE p.id = $1 AND p.deleted_at IS NULL AND ((p.team_id IS NULL AND p.owner = $2) OR pp.participant_id IS NOT NULL OR EXISTS (
+                SELECT 1 FROM team_members tm WHERE tm.team_id = p.team_id AND tm.login = $2))"
     )
         .bind(project_id)
         .bind(user_login)
@@ -205,41 +437,183 @@ pub async fn get_project_by_id_for_user(
         .map_err(|e| 
         {
             error!("Failed to fetch project {} for user '{}': {}", project_id, user_login, e);
-            AppError::InternalServerError
+            AppError::database(&e)
         })
 }
 
 pub async fn get_project_participants(pool: &PgPool, project_id: i32) -> Result<Vec<String>, AppError> 
 {
-    sqlx::query_scalar("SELECT participant_id FROM project_participants WHERE project_id = $1")
+    sqlx::query_scalar("SELECT participant_id FROM project_participants WHERE project_id = $1 AND status = 'accepted'")
         .bind(project_id)
         .fetch_all(pool)
         .await
         .map_err(|e| 
         {
             error!("Failed to fetch participants for project {}: {}", project_id, e);
-            AppError::InternalServerError
+            AppError::database(&e)
+        })
+}
+
+pub async fn get_pending_participants(pool: &PgPool, project_id: i32) -> Result<Vec<String>, AppError> 
+{
+    sqlx::query_scalar("SELECT participant_id FROM project_participants WHERE project_id = $1 AND status = 'pending' ORDER BY invited_at")
+        .bind(project_id)
+        .fetch_all(pool)
+        .await
+        .map_err(|e| 
+        {
+            error!("Failed to fetch pending participants for project {}: {}", project_id, e);
+            AppError::database(&e)
         })
 }
 
 pub async fn get_all_projects(pool: &PgPool) -> Result<Vec<Project>, AppError> 
 {
-    let query = format!("{} ORDER BY created_at DESC", SELECT_PROJECT_FIELDS);
+    let query = format!("{} WHERE deleted_at IS NULL ORDER BY created_at DESC", SELECT_PROJECT_FIELDS);
     sqlx::query_as::<_, Project>(&query)
         .fetch_all(pool)
         .await
         .map_err(|e| 
         {
             error!("Failed to fetch all projects: {}", e);
-            AppError::InternalServerError
+            AppError::database(&e)
         })
 }
 
+const SELECT_ADMIN_PROJECT_ROWS: &str =
+    "SELECT p.id, p.name, p.owner, p.container_name, p.source_type, p.source_url, p.source_branch, p.source_root_dir, p.deployed_image_tag, p.deployed_image_digest, p.created_at, p.updated_at, p.env_vars, p.persistent_volume_path, p.volume_name, p.docker_host, p.rate_limit_average, p.rate_limit_burst, p.rate_limit_set_by_admin, p.ip_allowlist, p.intended_running, p.lost_container, p.image_platform, p.healthcheck_path, p.healthcheck_interval, p.healthcheck_port, p.replicas, p.volume_share_safe, p.maintenance_page, p.image_created_at, p.last_scanned_at, p.container_id, p.egress_policy, p.routing_options, p.team_id, p.logs_visibility, p.logs_redaction, p.description, p.tags, p.custom_domains, p.archived_at, p.deleted_at, p.last_deployed_at, p.status, p.status_changed_at, p.auto_start_cron, p.auto_stop_cron,
+            pc.participant_count
+     FROM projects p
+     CROSS JOIN LATERAL (SELECT COUNT(*) AS participant_count FROM project_participants pp WHERE pp.project_id = p.id AND pp.status = 'accepted') pc";
+
+fn push_project_filters(query: &mut QueryBuilder<'_, Postgres>, filters: &ProjectFilters)
+{
+    query.push(" WHERE TRUE");
+
+    if let Some(owner) = &filters.owner
+    {
+        query.push(" AND p.owner = ").push_bind(owner.clone());
+    }
+    if let Some(name) = &filters.name_contains
+    {
+        let escaped = name.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+        query.push(" AND p.name ILIKE ").push_bind(format!("%{}%", escaped));
+    }
+    if let Some(source) = filters.source
+    {
+        query.push(" AND p.source_type = ").push_bind(source);
+    }
+    if let Some(after) = filters.created_after
+    {
+        query.push(" AND p.created_at >= ").push_bind(after);
+    }
+    if let Some(before) = filters.created_before
+    {
+        query.push(" AND p.created_at < ").push_bind(before);
+    }
+    if filters.deleted == Some(true)
+    {
+        query.push(" AND p.deleted_at IS NOT NULL");
+    }
+    else
+    {
+        query.push(" AND p.deleted_at IS NULL");
+    }
+    if let Some(archived) = filters.archived
+    {
+        query.push(if archived { " AND p.archived_at IS NOT NULL" } else { " AND p.archived_at IS NULL" });
+    }
+}
+
+// `sort_by` vient toujours d'une liste blanche `ListSpec`, jamais de l'utilisateur : il est écrit dans la requête.
+pub async fn get_projects_page(
+    pool: &PgPool,
+    filters: &ProjectFilters,
+    sort_by: &'static str,
+    order: SortOrder,
+    limit: i64,
+    offset: i64,
+) -> Result<(Vec<AdminProjectRow>, i64), AppError>
+{
+    let mut query = QueryBuilder::new(SELECT_ADMIN_PROJECT_ROWS);
+    push_project_filters(&mut query, filters);
+    query.push(format!(" ORDER BY p.{} {}, p.id {}", sort_by, order.as_sql(), order.as_sql()));
+    query.push(" LIMIT ").push_bind(limit);
+    query.push(" OFFSET ").push_bind(offset);
+
+    let projects = query.build_query_as::<AdminProjectRow>()
+        .fetch_all(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to fetch a page of projects sorted by '{}': {}", sort_by, e);
+            AppError::database(&e)
+        })?;
+
+    let mut count = QueryBuilder::new("SELECT COUNT(*) FROM projects p");
+    push_project_filters(&mut count, filters);
+
+    let total: i64 = count.build_query_scalar()
+        .fetch_one(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to count projects: {}", e);
+            AppError::database(&e)
+        })?;
+
+    Ok((projects, total))
+}
+
+const EXPORT_PROJECTS_QUERY: &str =
+    "SELECT p.name, p.owner,
+            COALESCE(ARRAY_AGG(pp.participant_id ORDER BY pp.participant_id) FILTER (WHERE pp.participant_id IS NOT NULL), '{}') AS participants,
+            p.source_type, p.source_url, p.deployed_image_tag, p.created_at, p.updated_at,
+            d.database_name,
+            (p.volume_name IS NOT NULL) AS has_volume,
+            p.status, p.archived_at,
+            p.deleted_at + make_interval(hours => $2) AS expires_at
+     FROM projects p
+     LEFT JOIN project_participants pp ON pp.project_id = p.id AND pp.status = 'accepted'
+     LEFT JOIN databases d ON d.project_id = p.id
+     WHERE p.deleted_at IS NULL OR $1
+     GROUP BY p.id, d.database_name
+     ORDER BY p.created_at DESC";
+
+pub fn export_all(pool: &PgPool, include_deleted: bool, deletion_grace_hours: i64) -> BoxStream<'_, Result<ProjectExportRow, AppError>>
+{
+    sqlx::query_as::<_, ProjectExportRow>(EXPORT_PROJECTS_QUERY)
+        .bind(include_deleted)
+        .bind(i32::try_from(deletion_grace_hours).unwrap_or(i32::MAX))
+        .fetch(pool)
+        .map(|row| row.map_err(|e|
+        {
+            error!("Failed to fetch project export row: {}", e);
+            AppError::InternalServerError
+        }))
+        .boxed()
+}
+
+pub async fn count_projects_per_docker_host(pool: &PgPool) -> Result<HashMap<String, i64>, AppError> 
+{
+    let rows: Vec<(String, i64)> = sqlx::query_as("SELECT docker_host, COUNT(*) FROM projects GROUP BY docker_host")
+        .fetch_all(pool)
+        .await
+        .map_err(|e| 
+        {
+            error!("Failed to count projects per Docker host: {}", e);
+            AppError::database(&e)
+        })?;
+    Ok(rows.into_iter().collect())
+}
 
 pub async fn add_project_participants<'a>(
     tx: &mut Transaction<'a, Postgres>,
     project_id: i32,
     participants: &[String],
+    status: ParticipantStatus,
+    invited_by: &str,
+    max_participants: i64,
 ) -> Result<(), AppError> 
 {
     if participants.is_empty() 
@@ -247,14 +621,21 @@ pub async fn add_project_participants<'a>(
         return Ok(());
     }
 
+    ensure_participant_capacity(tx, project_id, participants.len() as i64, max_participants).await?;
+
+    // Les participations ajoutées directement (mode historique) n'ont pas d'auteur d'invitation.
+    let invited_by = (status == ParticipantStatus::Pending).then_some(invited_by);
+
     let mut query_builder = sqlx::QueryBuilder::new(
-        "INSERT INTO project_participants (project_id, participant_id) "
+        "INSERT INTO project_participants (project_id, participant_id, status, invited_by) "
     );
 
     query_builder.push_values(participants.iter(), |mut b, participant| 
     {
         b.push_bind(project_id)
-         .push_bind(participant);
+         .push_bind(participant)
+         .push_bind(status)
+         .push_bind(invited_by);
     });
 
     let query = query_builder.build();
@@ -269,25 +650,92 @@ pub async fn add_project_participants<'a>(
 }
 
 
-pub async fn add_participant_to_project(
+// Verrouille la ligne du projet pour que deux ajouts simultanés soient comptés l'un après l'autre.
+async fn ensure_participant_capacity(
+    tx: &mut Transaction<'_, Postgres>,
+    project_id: i32,
+    incoming: i64,
+    max_participants: i64,
+) -> Result<(), AppError>
+{
+    sqlx::query("SELECT id FROM projects WHERE id = $1 FOR UPDATE")
+        .bind(project_id)
+        .execute(&mut **tx)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to lock project {} to add participants: {}", project_id, e);
+            AppError::database(&e)
+        })?;
+
+    let existing: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM project_participants WHERE project_id = $1")
+        .bind(project_id)
+        .fetch_one(&mut **tx)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to count participants of project {}: {}", project_id, e);
+            AppError::database(&e)
+        })?;
+
+    if existing + incoming > max_participants
+    {
+        return Err(ProjectErrorCode::TooManyParticipants(max_participants).into());
+    }
+
+    Ok(())
+}
+
+pub async fn invite_participant_to_project(
     pool: &PgPool,
     project_id: i32,
     participant_id: &str,
-) -> Result<(), AppError> 
+    invited_by: &str,
+    max_participants: i64,
+) -> Result<bool, AppError> 
 {
-    sqlx::query(
-        "INSERT INTO project_participants (project_id, participant_id) VALUES ($1, $2) ON CONFLICT DO NOTHING"
+    let mut tx = pool.begin().await.map_err(|e| AppError::database(&e))?;
+
+    let already_listed: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM project_participants WHERE project_id = $1 AND participant_id = $2)")
+        .bind(project_id)
+        .bind(participant_id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to check participant '{}' of project {}: {}", participant_id, project_id, e);
+            AppError::database(&e)
+        })?;
+    if already_listed
+    {
+        return Ok(false);
+    }
+
+    ensure_participant_capacity(&mut tx, project_id, 1, max_participants).await?;
+
+    let result = sqlx::query(
+        "WITH added AS (
+             INSERT INTO project_participants (project_id, participant_id, status, invited_by)
+             VALUES ($1, $2, 'pending', $3)
+             ON CONFLICT DO NOTHING
+             RETURNING project_id
+         )
+         UPDATE projects SET updated_at = NOW() WHERE id IN (SELECT project_id FROM added)"
     )
     .bind(project_id)
     .bind(participant_id)
-    .execute(pool)
+    .bind(invited_by)
+    .execute(&mut *tx)
     .await
     .map_err(|e| 
     {
-        error!("Failed to add participant '{}' to project {}: {}", participant_id, project_id, e);
-        AppError::InternalServerError
+        error!("Failed to invite participant '{}' to project {}: {}", participant_id, project_id, e);
+        AppError::database(&e)
     })?;
-    Ok(())
+
+    tx.commit().await.map_err(|e| AppError::database(&e))?;
+
+    Ok(result.rows_affected() > 0)
 }
 
 pub async fn remove_participant_from_project(
@@ -297,7 +745,8 @@ pub async fn remove_participant_from_project(
 ) -> Result<(), AppError> 
 {
     let result = sqlx::query(
-        "DELETE FROM project_participants WHERE project_id = $1 AND participant_id = $2"
+        "WITH removed AS (DELETE FROM project_participants WHERE project_id = $1 AND participant_id = $2 RETURNING project_id)
+         UPDATE projects SET updated_at = NOW() WHERE id IN (SELECT project_id FROM removed)"
     )
     .bind(project_id)
     .bind(participant_id)
@@ -306,7 +755,7 @@ pub async fn remove_participant_from_project(
     .map_err(|e| 
     {
         error!("Failed to remove participant '{}' from project {}: {}", participant_id, project_id, e);
-        AppError::InternalServerError
+        AppError::database(&e)
     })?;
 
     if result.rows_affected() == 0 
@@ -319,7 +768,7 @@ pub async fn remove_participant_from_project(
 
 fn encrypt_env_vars(
     env_vars: &HashMap<String, String>,
-    key: &[u8],
+    key: &EncryptionKeys,
 ) -> Result<HashMap<String, String>, AppError>
 {
     env_vars.iter()
@@ -335,13 +784,13 @@ pub async fn update_project_env_vars(
     pool: &PgPool,
     project_id: i32,
     env_vars: &HashMap<String, String>,
-    encryption_key: &[u8],
+    encryption_key: &EncryptionKeys,
 ) -> Result<(), AppError>
 {
     let encrypted_vars = encrypt_env_vars(env_vars, encryption_key)?;
     let env_vars_json = serde_json::to_value(encrypted_vars).map_err(|_| AppError::InternalServerError)?;
 
-    sqlx::query("UPDATE projects SET env_vars = $1 WHERE id = $2")
+    sqlx::query("UPDATE projects SET env_vars = $1, updated_at = NOW() WHERE id = $2")
         .bind(env_vars_json)
         .bind(project_id)
         .execute(pool)
@@ -349,47 +798,715 @@ pub async fn update_project_env_vars(
         .map_err(|e|
         {
             error!("Failed to update env vars for project {}: {}", project_id, e);
-            AppError::InternalServerError
+            AppError::database(&e)
         })?;
     Ok(())
 }
 
-pub async fn update_project_container_name(
-    pool: &PgPool,
-    project_id: i32,
-    new_container_name: &str,
-) -> Result<(), AppError>
+pub async fn get_shared_env_keys(pool: &PgPool, project_id: i32) -> Result<Vec<String>, AppError>
 {
-    sqlx::query("UPDATE projects SET container_name = $1 WHERE id = $2")
-        .bind(new_container_name)
+    sqlx::query_scalar("SELECT shared_env_keys FROM projects WHERE id = $1")
         .bind(project_id)
-        .execute(pool)
+        .fetch_optional(pool)
         .await
+        .map(Option::unwrap_or_default)
         .map_err(|e|
         {
-            error!("Failed to update container name for project {}: {}", project_id, e);
+            error!("Failed to fetch shared env keys for project {}: {}", project_id, e);
             AppError::InternalServerError
-        })?;
-    Ok(())
+        })
 }
 
-pub async fn update_project_image_and_digest(
-    pool: &PgPool,
-    project_id: i32,
-    new_image_tag: &str,
-    new_image_digest: &str,
-) -> Result<(), AppError> 
+pub async fn set_shared_env_keys(pool: &PgPool, project_id: i32, shared_keys: &[String]) -> Result<(), AppError>
 {
-    sqlx::query("UPDATE projects SET deployed_image_tag = $1, deployed_image_digest = $2 WHERE id = $3")
-        .bind(new_image_tag)
-        .bind(new_image_digest)
+    sqlx::query("UPDATE projects SET shared_env_keys = $1, updated_at = NOW() WHERE id = $2")
+        .bind(shared_keys)
         .bind(project_id)
         .execute(pool)
         .await
-        .map_err(|e| 
+        .map_err(|e|
         {
-            error!("Failed to update project {} with new image and digest: {}", project_id, e);
-            AppError::InternalServerError
+            error!("Failed to update shared env keys for project {}: {}", project_id, e);
+            AppError::database(&e)
+        })?;
+    Ok(())
+}
+
+pub async fn get_deploy_warnings(pool: &PgPool, project_id: i32) -> Result<Vec<String>, AppError>
+{
+    sqlx::query_scalar("SELECT deploy_warnings FROM projects WHERE id = $1")
+        .bind(project_id)
+        .fetch_optional(pool)
+        .await
+        .map(Option::unwrap_or_default)
+        .map_err(|e|
+        {
+            error!("Failed to fetch deploy warnings for project {}: {}", project_id, e);
+            AppError::InternalServerError
+        })
+}
+
+pub async fn set_deploy_warnings(pool: &PgPool, project_id: i32, warnings: &[String]) -> Result<(), AppError>
+{
+    sqlx::query("UPDATE projects SET deploy_warnings = $1, updated_at = NOW() WHERE id = $2")
+        .bind(warnings)
+        .bind(project_id)
+        .execute(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to update deploy warnings for project {}: {}", project_id, e);
+            AppError::database(&e)
+        })?;
+    Ok(())
+}
+
+pub async fn update_project_route_middlewares(
+    pool: &PgPool,
+    project_id: i32,
+    middlewares: &RouteMiddlewares,
+) -> Result<(), AppError>
+{
+    let healthcheck = middlewares.healthcheck.as_ref();
+    sqlx::query(
+        "UPDATE projects SET rate_limit_average = $1, rate_limit_burst = $2, ip_allowlist = $3,
+                healthcheck_path = $4, healthcheck_interval = $5, healthcheck_port = $6, routing_options = $7, updated_at = NOW()
+         WHERE id = $8"
+    )
+        .bind(middlewares.rate_limit.map(|limit| limit.average))
+        .bind(middlewares.rate_limit.map(|limit| limit.burst))
+        .bind(&middlewares.ip_allowlist)
+        .bind(healthcheck.map(|healthcheck| &healthcheck.path))
+        .bind(healthcheck.map(|healthcheck| healthcheck.interval_seconds))
+        .bind(healthcheck.and_then(|healthcheck| healthcheck.port))
+        .bind(sqlx::types::Json(&middlewares.routing))
+        .bind(project_id)
+        .execute(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to update route middlewares for project {}: {}", project_id, e);
+            AppError::database(&e)
+        })?;
+    Ok(())
+}
+
+pub async fn set_rate_limit_set_by_admin(pool: &PgPool, project_id: i32, set_by_admin: bool) -> Result<(), AppError>
+{
+    sqlx::query("UPDATE projects SET rate_limit_set_by_admin = $1 WHERE id = $2")
+        .bind(set_by_admin)
+        .bind(project_id)
+        .execute(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to record who set the rate limit of project {}: {}", project_id, e);
+            AppError::database(&e)
+        })?;
+    Ok(())
+}
+
+pub async fn update_project_egress_policy(pool: &PgPool, project_id: i32, egress_policy: EgressPolicy) -> Result<(), AppError>
+{
+    sqlx::query("UPDATE projects SET egress_policy = $1, updated_at = NOW() WHERE id = $2")
+        .bind(egress_policy)
+        .bind(project_id)
+        .execute(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to update egress policy for project {}: {}", project_id, e);
+            AppError::database(&e)
+        })?;
+
+    Ok(())
+}
+
+pub async fn update_project_metadata(pool: &PgPool, project_id: i32, description: Option<&str>, tags: &[String]) -> Result<(), AppError>
+{
+    sqlx::query("UPDATE projects SET description = $1, tags = $2, updated_at = NOW() WHERE id = $3")
+        .bind(description)
+        .bind(tags)
+        .bind(project_id)
+        .execute(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to update metadata for project {}: {}", project_id, e);
+            AppError::database(&e)
+        })?;
+
+    Ok(())
+}
+
+pub async fn update_project_schedule(pool: &PgPool, project_id: i32, auto_start_cron: Option<&str>, auto_stop_cron: Option<&str>) -> Result<(), AppError>
+{
+    sqlx::query("UPDATE projects SET auto_start_cron = $1, auto_stop_cron = $2, updated_at = NOW() WHERE id = $3")
+        .bind(auto_start_cron)
+        .bind(auto_stop_cron)
+        .bind(project_id)
+        .execute(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to update the schedule of project {}: {}", project_id, e);
+            AppError::database(&e)
+        })?;
+
+    Ok(())
+}
+
+pub async fn get_scheduled_projects(pool: &PgPool) -> Result<Vec<Project>, AppError>
+{
+    let query = format!(
+        "{} WHERE deleted_at IS NULL AND archived_at IS NULL AND (auto_start_cron IS NOT NULL OR auto_stop_cron IS NOT NULL)",
+        SELECT_PROJECT_FIELDS
+    );
+    sqlx::query_as::<_, Project>(&query)
+        .fetch_all(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to fetch scheduled projects: {}", e);
+            AppError::database(&e)
+        })
+}
+
+// `set_project_custom_domains` le vérifie encore, au cas où un autre projet le prendrait entre-temps.
+pub async fn find_taken_custom_domain(pool: &PgPool, project_id: i32, domains: &[String]) -> Result<Option<String>, AppError>
+{
+    sqlx::query_scalar("SELECT domain FROM project_domains WHERE domain = ANY($1) AND project_id <> $2 ORDER BY domain LIMIT 1")
+        .bind(domains)
+        .bind(project_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to check the custom domains of project {}: {}", project_id, e);
+            AppError::database(&e)
+        })
+}
+
+// Réservations et liste changent dans la même transaction : un domaine ne finit jamais sur deux projets.
+pub async fn set_project_custom_domains(pool: &PgPool, project_id: i32, domains: &[String]) -> Result<(), AppError>
+{
+    let mut tx = pool.begin().await.map_err(|e| AppError::database(&e))?;
+
+    sqlx::query("DELETE FROM project_domains WHERE project_id = $1")
+        .bind(project_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to release the custom domains of project {}: {}", project_id, e);
+            AppError::database(&e)
+        })?;
+
+    for domain in domains
+    {
+        sqlx::query("INSERT INTO project_domains (domain, project_id) VALUES ($1, $2)")
+            .bind(domain)
+            .bind(project_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e|
+            {
+                if let Some(db_err) = e.as_database_error()
+                    && db_err.is_unique_violation()
+                {
+                    return AppError::ProjectError(ProjectErrorCode::CustomDomainTaken(domain.clone()));
+                }
+                error!("Failed to reserve domain '{}' for project {}: {}", domain, project_id, e);
+                AppError::database(&e)
+            })?;
+    }
+
+    sqlx::query("UPDATE projects SET custom_domains = $1, updated_at = NOW() WHERE id = $2")
+        .bind(domains)
+        .bind(project_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to update the custom domains of project {}: {}", project_id, e);
+            AppError::database(&e)
+        })?;
+
+    tx.commit().await.map_err(|e| AppError::database(&e))?;
+
+    Ok(())
+}
+
+// Efface aussi `intended_running` : la réconciliation laisse les conteneurs arrêtés.
+pub async fn set_project_archived(pool: &PgPool, project_id: i32, archived: bool) -> Result<(), AppError>
+{
+    let query = if archived
+    {
+        "UPDATE projects SET archived_at = NOW(), intended_running = FALSE, updated_at = NOW() WHERE id = $1"
+    }
+    else
+    {
+        "UPDATE projects SET archived_at = NULL, updated_at = NOW() WHERE id = $1"
+    };
+
+    sqlx::query(query)
+        .bind(project_id)
+        .execute(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to set archived state of project {} to {}: {}", project_id, archived, e);
+            AppError::database(&e)
+        })?;
+
+    Ok(())
+}
+
+// Efface aussi `intended_running` : la réconciliation laisse les conteneurs arrêtés.
+pub async fn set_project_deleted(pool: &PgPool, project_id: i32, deleted: bool) -> Result<(), AppError>
+{
+    let query = if deleted
+    {
+        "UPDATE projects SET deleted_at = NOW(), intended_running = FALSE, updated_at = NOW() WHERE id = $1"
+    }
+    else
+    {
+        "UPDATE projects SET deleted_at = NULL, updated_at = NOW() WHERE id = $1"
+    };
+
+    sqlx::query(query)
+        .bind(project_id)
+        .execute(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to set deleted state of project {} to {}: {}", project_id, deleted, e);
+            AppError::database(&e)
+        })?;
+
+    Ok(())
+}
+
+// Ne bougent que si le statut change, pour que les ETag des listes restent valides.
+pub async fn update_project_status(pool: &PgPool, project_id: i32, status: ProjectStatus) -> Result<(), AppError>
+{
+    sqlx::query(
+        "UPDATE projects SET
+             status_changed_at = CASE WHEN status IS DISTINCT FROM $1 THEN NOW() ELSE status_changed_at END,
+             updated_at = CASE WHEN status IS DISTINCT FROM $1 THEN NOW() ELSE updated_at END,
+             status = $1
+         WHERE id = $2"
+    )
+        .bind(status)
+        .bind(project_id)
+        .execute(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to update status of project {}: {}", project_id, e);
+            AppError::database(&e)
+        })?;
+
+    Ok(())
+}
+
+// `updated_at` bouge dès que le statut ou l'intention change : un écart tout récent n'est jamais signalé.
+pub async fn get_status_drift(pool: &PgPool, threshold_seconds: u64) -> Result<Vec<Project>, AppError>
+{
+    let query = format!(
+        "{} WHERE deleted_at IS NULL AND status IS NOT NULL
+             AND ((intended_running AND status <> 'running') OR (NOT intended_running AND status = 'running'))
+             AND updated_at < NOW() - make_interval(secs => $1)
+         ORDER BY updated_at",
+        SELECT_PROJECT_FIELDS
+    );
+    sqlx::query_as::<_, Project>(&query)
+        .bind(threshold_seconds as f64)
+        .fetch_all(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to fetch projects with status drift: {}", e);
+            AppError::database(&e)
+        })
+}
+
+pub async fn update_logs_settings(pool: &PgPool, project_id: i32, visibility: LogsVisibility, redaction: bool) -> Result<(), AppError>
+{
+    sqlx::query("UPDATE projects SET logs_visibility = $1, logs_redaction = $2, updated_at = NOW() WHERE id = $3")
+        .bind(visibility)
+        .bind(redaction)
+        .bind(project_id)
+        .execute(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to update logs settings for project {}: {}", project_id, e);
+            AppError::database(&e)
+        })?;
+
+    Ok(())
+}
+
+pub async fn update_project_replicas(
+    pool: &PgPool,
+    project_id: i32,
+    replicas: i32,
+    volume_share_safe: bool,
+) -> Result<(), AppError>
+{
+    sqlx::query("UPDATE projects SET replicas = $1, volume_share_safe = $2, updated_at = NOW() WHERE id = $3")
+        .bind(replicas)
+        .bind(volume_share_safe)
+        .bind(project_id)
+        .execute(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to update replicas for project {}: {}", project_id, e);
+            AppError::database(&e)
+        })?;
+    Ok(())
+}
+
+pub async fn set_project_maintenance_page(pool: &PgPool, project_id: i32, maintenance_page: bool) -> Result<(), AppError>
+{
+    sqlx::query("UPDATE projects SET maintenance_page = $1, updated_at = NOW() WHERE id = $2")
+        .bind(maintenance_page)
+        .bind(project_id)
+        .execute(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to update maintenance page flag for project {}: {}", project_id, e);
+            AppError::database(&e)
+        })?;
+    Ok(())
+}
+
+pub async fn set_project_intended_running(pool: &PgPool, project_id: i32, intended_running: bool) -> Result<(), AppError>
+{
+    sqlx::query("UPDATE projects SET intended_running = $1, updated_at = NOW() WHERE id = $2 AND intended_running <> $1")
+        .bind(intended_running)
+        .bind(project_id)
+        .execute(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to update intended state for project {}: {}", project_id, e);
+            AppError::database(&e)
+        })?;
+    Ok(())
+}
+
+// L'ancien nom 'default' n'est remplacé que si aucun hôte configuré ne le porte.
+pub async fn assign_default_docker_host(pool: &PgPool, default_host: &str, legacy_name_configured: bool) -> Result<u64, AppError>
+{
+    let result = sqlx::query("UPDATE projects SET docker_host = $1 WHERE docker_host IS NULL OR (docker_host = 'default' AND NOT $2)")
+        .bind(default_host)
+        .bind(legacy_name_configured)
+        .execute(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to assign the default Docker host to projects: {}", e);
+            AppError::database(&e)
+        })?;
+    Ok(result.rows_affected())
+}
+
+pub async fn get_broadcast_stopped_project_ids(pool: &PgPool) -> Result<Vec<i32>, AppError>
+{
+    sqlx::query_scalar("SELECT id FROM projects WHERE stopped_by_broadcast")
+        .fetch_all(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to fetch projects stopped by broadcast: {}", e);
+            AppError::database(&e)
+        })
+}
+
+// Un projet arrêté par diffusion ne doit pas tourner : la réconciliation le laisse arrêté si l'hôte redémarre avant start-all.
+pub async fn set_project_stopped_by_broadcast(pool: &PgPool, project_id: i32, stopped: bool) -> Result<(), AppError>
+{
+    sqlx::query("UPDATE projects SET stopped_by_broadcast = $1, intended_running = NOT $1, updated_at = NOW() WHERE id = $2")
+        .bind(stopped)
+        .bind(project_id)
+        .execute(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to update broadcast stop marker for project {}: {}", project_id, e);
+            AppError::database(&e)
+        })?;
+    Ok(())
+}
+
+pub async fn record_container_broadcast(
+    pool: &PgPool,
+    action: &str,
+    admin_login: &str,
+    owner_filter: Option<&str>,
+    dry_run: bool,
+    results: serde_json::Value,
+) -> Result<(), AppError>
+{
+    sqlx::query(
+        "INSERT INTO container_broadcasts (action, admin_login, owner_filter, dry_run, results) VALUES ($1, $2, $3, $4, $5)"
+    )
+        .bind(action)
+        .bind(admin_login)
+        .bind(owner_filter)
+        .bind(dry_run)
+        .bind(results)
+        .execute(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to record container broadcast '{}' by '{}': {}", action, admin_login, e);
+            AppError::database(&e)
+        })?;
+    Ok(())
+}
+
+pub async fn set_project_lost_container(pool: &PgPool, project_id: i32, lost: bool) -> Result<(), AppError>
+{
+    sqlx::query("UPDATE projects SET lost_container = $1, updated_at = NOW() WHERE id = $2 AND lost_container <> $1")
+        .bind(lost)
+        .bind(project_id)
+        .execute(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to update lost container flag for project {}: {}", project_id, e);
+            AppError::database(&e)
+        })?;
+    Ok(())
+}
+
+pub async fn get_lost_projects(pool: &PgPool) -> Result<Vec<Project>, AppError>
+{
+    let query = format!("{} WHERE lost_container ORDER BY name", SELECT_PROJECT_FIELDS);
+    sqlx::query_as::<_, Project>(&query)
+        .fetch_all(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to fetch projects with a lost container: {}", e);
+            AppError::database(&e)
+        })
+}
+
+pub async fn get_project_notes(pool: &PgPool, project_id: i32) -> Result<Option<String>, AppError>
+{
+    let notes: (Option<String>,) = sqlx::query_as("SELECT notes FROM projects WHERE id = $1")
+        .bind(project_id)
+        .fetch_one(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to fetch notes for project {}: {}", project_id, e);
+            AppError::database(&e)
+        })?;
+    Ok(notes.0)
+}
+
+pub async fn update_project_notes(pool: &PgPool, project_id: i32, notes: Option<&str>, editor: &str) -> Result<(), AppError>
+{
+    sqlx::query(
+        "WITH previous AS (SELECT id, notes FROM projects WHERE id = $1 FOR UPDATE),
+              revision AS (INSERT INTO project_note_revisions (project_id, previous_notes, edited_by) SELECT id, notes, $3 FROM previous)
+         UPDATE projects SET notes = $2, updated_at = NOW() WHERE id = $1"
+    )
+    .bind(project_id)
+    .bind(notes)
+    .bind(editor)
+    .execute(pool)
+    .await
+    .map_err(|e|
+    {
+        error!("Failed to update notes for project {}: {}", project_id, e);
+        AppError::database(&e)
+    })?;
+    Ok(())
+}
+
+pub async fn get_project_note_revisions(pool: &PgPool, project_id: i32) -> Result<Vec<ProjectNoteRevision>, AppError>
+{
+    sqlx::query_as::<_, ProjectNoteRevision>(
+        "SELECT id, project_id, previous_notes, edited_by, edited_at FROM project_note_revisions
+         WHERE project_id = $1 ORDER BY edited_at DESC LIMIT 50"
+    )
+    .bind(project_id)
+    .fetch_all(pool)
+    .await
+    .map_err(|e|
+    {
+        error!("Failed to fetch note revisions for project {}: {}", project_id, e);
+        AppError::database(&e)
+    })
+}
+
+// Les dérogations de scan suivent le projet ; l'historique garde les noms de l'époque.
+pub async fn rename_project(
+    pool: &PgPool,
+    project_id: i32,
+    old_name: &str,
+    new_name: &str,
+    new_container_name: &str,
+    new_container_id: Option<&str>,
+) -> Result<(), AppError>
+{
+    let mut tx = pool.begin().await.map_err(|e| AppError::database(&e))?;
+
+    sqlx::query("UPDATE projects SET name = $1, container_name = $2, container_id = $3, lost_container = FALSE, updated_at = NOW() WHERE id = $4")
+        .bind(new_name)
+        .bind(new_container_name)
+        .bind(new_container_id)
+        .bind(project_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to rename project {} to '{}': {}", project_id, new_name, e);
+            if let Some(db_err) = e.as_database_error()
+                && db_err.is_unique_violation()
+            {
+                return AppError::ProjectError(ProjectErrorCode::ProjectNameTaken);
+            }
+            AppError::database(&e)
+        })?;
+
+    sqlx::query("UPDATE scan_waivers SET project_name = $1 WHERE project_name = $2")
+        .bind(new_name)
+        .bind(old_name)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to move the scan waivers of project '{}' to '{}': {}", old_name, new_name, e);
+            AppError::database(&e)
+        })?;
+
+    sqlx::query("UPDATE project_scan_results SET project_name = $1 WHERE project_name = $2")
+        .bind(new_name)
+        .bind(old_name)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to move the scan result of project '{}' to '{}': {}", old_name, new_name, e);
+            AppError::database(&e)
+        })?;
+
+    tx.commit().await.map_err(|e| AppError::database(&e))?;
+
+    Ok(())
+}
+
+// Premier réplica d'un jeu de conteneurs fraîchement recréé.
+pub async fn update_project_container_name(
+    pool: &PgPool,
+    project_id: i32,
+    new_container_name: &str,
+    new_container_id: Option<&str>,
+) -> Result<(), AppError>
+{
+    sqlx::query("UPDATE projects SET container_name = $1, container_id = $2, lost_container = FALSE, updated_at = NOW() WHERE id = $3")
+        .bind(new_container_name)
+        .bind(new_container_id)
+        .bind(project_id)
+        .execute(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to update container name for project {}: {}", project_id, e);
+            AppError::database(&e)
+        })?;
+    Ok(())
+}
+
+pub async fn update_project_container_id(pool: &PgPool, project_id: i32, container_id: &str) -> Result<(), AppError>
+{
+    sqlx::query("UPDATE projects SET container_id = $1 WHERE id = $2")
+        .bind(container_id)
+        .bind(project_id)
+        .execute(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to update container ID for project {}: {}", project_id, e);
+            AppError::database(&e)
+        })?;
+    Ok(())
+}
+
+pub async fn update_project_image_metadata(
+    pool: &PgPool,
+    project_id: i32,
+    image_platform: Option<&str>,
+    image_created_at: Option<OffsetDateTime>,
+    scanned: bool,
+) -> Result<(), AppError>
+{
+    sqlx::query(
+        "UPDATE projects SET image_platform = $1, image_created_at = $2,
+                last_scanned_at = CASE WHEN $3 THEN NOW() ELSE last_scanned_at END
+         WHERE id = $4"
+    )
+        .bind(image_platform)
+        .bind(image_created_at)
+        .bind(scanned)
+        .bind(project_id)
+        .execute(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to update image metadata for project {}: {}", project_id, e);
+            AppError::database(&e)
+        })?;
+    Ok(())
+}
+
+// Une image jamais scannée ne compte que si `include_unscanned` est vrai, c'est-à-dire si le scan est actif.
+pub async fn get_stale_image_projects(pool: &PgPool, older_than_days: i32, include_unscanned: bool) -> Result<Vec<Project>, AppError>
+{
+    let query = format!(
+        "{} WHERE image_created_at < NOW() - make_interval(days => $1)
+               OR last_scanned_at < NOW() - make_interval(days => $1)
+               OR ($2 AND last_scanned_at IS NULL)",
+        SELECT_PROJECT_FIELDS
+    );
+    sqlx::query_as::<_, Project>(&query)
+        .bind(older_than_days)
+        .bind(include_unscanned)
+        .fetch_all(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to fetch projects with stale images: {}", e);
+            AppError::database(&e)
+        })
+}
+
+pub async fn update_project_image_and_digest(
+    pool: &PgPool,
+    project_id: i32,
+    new_image_tag: &str,
+    new_image_digest: &str,
+) -> Result<(), AppError> 
+{
+    sqlx::query("UPDATE projects SET deployed_image_tag = $1, deployed_image_digest = $2, last_deployed_at = NOW(), updated_at = NOW() WHERE id = $3")
+        .bind(new_image_tag)
+        .bind(new_image_digest)
+        .bind(project_id)
+        .execute(pool)
+        .await
+        .map_err(|e| 
+        {
+            error!("Failed to update project {} with new image and digest: {}", project_id, e);
+            AppError::database(&e)
         })?;
     Ok(())
 }
@@ -400,7 +1517,7 @@ pub async fn update_project_source_url(
     new_source_url: &str,
 ) -> Result<(), AppError>
 {
-    sqlx::query("UPDATE projects SET source_url = $1 WHERE id = $2")
+    sqlx::query("UPDATE projects SET source_url = $1, updated_at = NOW() WHERE id = $2")
         .bind(new_source_url)
         .bind(project_id)
         .execute(pool)
@@ -408,7 +1525,7 @@ pub async fn update_project_source_url(
         .map_err(|e|
         {
             error!("Failed to update source_url for project {}: {}", project_id, e);
-            AppError::InternalServerError
+            AppError::database(&e)
         })?;
     Ok(())
 }
\ No newline at end of file
@@ -0,0 +1,193 @@
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use tracing::error;
+
+use crate::
+{
+    config::Config,
+    error::{AppError, DatabaseErrorCode, ProjectErrorCode},
+    model::{scan::Severity, team::Team},
+    services::{database_service, project_service},
+};
+
+// Les bases portent le nom de leur propriétaire : une seule par utilisateur pour l'instant.
+pub const MAX_DATABASES_PER_USER: i32 = 1;
+pub const MAX_PROJECTS_PER_USER: i32 = 100;
+pub const MAX_PROJECTS_PER_TEAM: i32 = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuotaDimension
+{
+    Projects,
+    Databases,
+}
+
+#[derive(Debug, Serialize)]
+pub struct QuotaUsage
+{
+    pub dimension: QuotaDimension,
+    pub limit: i64,
+    pub used: i64,
+    pub remaining: i64,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, sqlx::FromRow)]
+#[serde(deny_unknown_fields)]
+pub struct UserQuotaOverrides
+{
+    pub max_projects: Option<i32>,
+    pub max_databases: Option<i32>,
+    pub scan_fail_on_severity: Option<Severity>,
+    pub db_max_user_connections: Option<i32>,
+    pub scan_profile: Option<String>,
+}
+
+const ALL_DIMENSIONS: &[QuotaDimension] = &[QuotaDimension::Projects, QuotaDimension::Databases];
+
+pub async fn get_user_overrides(pool: &PgPool, login: &str) -> Result<UserQuotaOverrides, AppError>
+{
+    let overrides = sqlx::query_as::<_, UserQuotaOverrides>("SELECT max_projects, max_databases, scan_fail_on_severity, db_max_user_connections, scan_profile FROM user_quotas WHERE login = $1")
+        .bind(login)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to fetch quota overrides for '{}': {}", login, e);
+            AppError::database(&e)
+        })?;
+    Ok(overrides.unwrap_or_default())
+}
+
+pub async fn set_user_overrides(pool: &PgPool, login: &str, overrides: &UserQuotaOverrides, admin_login: &str) -> Result<(), AppError>
+{
+    sqlx::query(
+        "INSERT INTO user_quotas (login, max_projects, max_databases, scan_fail_on_severity, db_max_user_connections, scan_profile, updated_by) VALUES ($1, $2, $3, $4, $5, $6, $7)
+         ON CONFLICT (login) DO UPDATE SET max_projects = $2, max_databases = $3, scan_fail_on_severity = $4, db_max_user_connections = $5, scan_profile = $6, updated_by = $7, updated_at = NOW()"
+    )
+    .bind(login)
+    .bind(overrides.max_projects)
+    .bind(overrides.max_databases)
+    .bind(overrides.scan_fail_on_severity)
+    .bind(overrides.db_max_user_connections)
+    .bind(&overrides.scan_profile)
+    .bind(admin_login)
+    .execute(pool)
+    .await
+    .map_err(|e|
+    {
+        error!("Failed to save quota overrides for '{}': {}", login, e);
+        AppError::database(&e)
+    })?;
+    Ok(())
+}
+
+pub fn validate_overrides(overrides: &UserQuotaOverrides) -> Result<(), AppError>
+{
+    if let Some(max) = overrides.max_projects && !(0..=MAX_PROJECTS_PER_USER).contains(&max)
+    {
+        return Err(AppError::BadRequest(format!("max_projects must be between 0 and {}.", MAX_PROJECTS_PER_USER)));
+    }
+
+    if let Some(max) = overrides.max_databases && !(0..=MAX_DATABASES_PER_USER).contains(&max)
+    {
+        return Err(AppError::BadRequest(format!("max_databases must be between 0 and {}.", MAX_DATABASES_PER_USER)));
+    }
+
+    if overrides.scan_fail_on_severity == Some(Severity::Unknown)
+    {
+        return Err(AppError::BadRequest("scan_fail_on_severity must be one of negligible, low, medium, high or critical.".to_string()));
+    }
+
+    if overrides.db_max_user_connections.is_some_and(|max| max < 0)
+    {
+        return Err(AppError::BadRequest("db_max_user_connections must be 0 (no limit) or more.".to_string()));
+    }
+
+    Ok(())
+}
+
+fn limit_for(config: &Config, overrides: &UserQuotaOverrides, dimension: QuotaDimension) -> i64
+{
+    let limit = match dimension
+    {
+        QuotaDimension::Projects => overrides.max_projects.unwrap_or(config.quota_default_projects),
+        QuotaDimension::Databases => overrides.max_databases.unwrap_or(config.quota_default_databases),
+    };
+    i64::from(limit)
+}
+
+async fn count_used(pool: &PgPool, login: &str, dimension: QuotaDimension) -> Result<i64, AppError>
+{
+    match dimension
+    {
+        QuotaDimension::Projects => project_service::count_projects_by_owner(pool, login).await,
+        QuotaDimension::Databases => database_service::count_databases_for_owner(pool, login).await,
+    }
+}
+
+pub async fn get_usage(pool: &PgPool, config: &Config, login: &str, dimension: QuotaDimension) -> Result<QuotaUsage, AppError>
+{
+    let overrides = get_user_overrides(pool, login).await?;
+    let limit = limit_for(config, &overrides, dimension);
+    let used = count_used(pool, login, dimension).await?;
+
+    Ok(QuotaUsage { dimension, limit, used, remaining: (limit - used).max(0) })
+}
+
+pub async fn get_all_usage(pool: &PgPool, config: &Config, login: &str) -> Result<Vec<QuotaUsage>, AppError>
+{
+    let overrides = get_user_overrides(pool, login).await?;
+    let mut usage = Vec::with_capacity(ALL_DIMENSIONS.len());
+
+    for &dimension in ALL_DIMENSIONS
+    {
+        let limit = limit_for(config, &overrides, dimension);
+        let used = count_used(pool, login, dimension).await?;
+        usage.push(QuotaUsage { dimension, limit, used, remaining: (limit - used).max(0) });
+    }
+
+    Ok(usage)
+}
+
+pub async fn ensure_available(pool: &PgPool, config: &Config, login: &str, dimension: QuotaDimension) -> Result<(), AppError>
+{
+    let usage = get_usage(pool, config, login, dimension).await?;
+    if usage.remaining > 0
+    {
+        return Ok(());
+    }
+
+    Err(match dimension
+    {
+        QuotaDimension::Projects => ProjectErrorCode::OwnerAlreadyExists.into(),
+        QuotaDimension::Databases => DatabaseErrorCode::DatabaseAlreadyExists.into(),
+    })
+}
+
+
+pub async fn get_team_usage(pool: &PgPool, config: &Config, team: &Team) -> Result<QuotaUsage, AppError>
+{
+    let limit = i64::from(team.max_projects.unwrap_or(config.quota_default_team_projects));
+    let used = project_service::count_projects_by_team(pool, team.id).await?;
+
+    Ok(QuotaUsage { dimension: QuotaDimension::Projects, limit, used, remaining: (limit - used).max(0) })
+}
+
+pub async fn ensure_team_available(pool: &PgPool, config: &Config, team: &Team) -> Result<(), AppError>
+{
+    if get_team_usage(pool, config, team).await?.remaining > 0
+    {
+        return Ok(());
+    }
+    Err(AppError::BadRequest(format!("The team '{}' has reached its project quota.", team.name)))
+}
+
+pub fn validate_team_max_projects(max_projects: Option<i32>) -> Result<(), AppError>
+{
+    if let Some(max) = max_projects && !(0..=MAX_PROJECTS_PER_TEAM).contains(&max)
+    {
+        return Err(AppError::BadRequest(format!("max_projects must be between 0 and {}.", MAX_PROJECTS_PER_TEAM)));
+    }
+    Ok(())
+}
\ No newline at end of file
@@ -0,0 +1,212 @@
+use std::io::{Read, Write};
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::Deserialize;
+use sqlx::PgPool;
+use tracing::{error, info, warn};
+
+use crate::
+{
+    config::{Config, DockerHostConfig},
+    error::AppError,
+    model::scan::SbomPackageMatch,
+    services::docker_service,
+};
+
+const SBOM_SEARCH_LIMIT: i64 = 200;
+
+#[derive(Deserialize)]
+struct CycloneDxDocument
+{
+    #[serde(default)]
+    components: Vec<CycloneDxComponent>,
+}
+
+#[derive(Deserialize)]
+struct CycloneDxComponent
+{
+    name: String,
+    #[serde(default)]
+    version: Option<String>,
+}
+
+#[derive(sqlx::FromRow)]
+struct StoredSbom
+{
+    document: Option<Vec<u8>>,
+    error_message: Option<String>,
+}
+
+pub enum SbomLookup
+{
+    Available(Vec<u8>),
+    Missing(Option<String>),
+}
+
+// Les échecs sont enregistrés comme SBOM manquant et ne remontent jamais au déploiement.
+pub async fn generate_and_store(pool: &PgPool, config: &Config, host: &DockerHostConfig, image_ref: &str, image_digest: &str)
+{
+    if !config.syft_enabled
+    {
+        return;
+    }
+
+    match has_document(pool, image_digest).await
+    {
+        Ok(true) => return,
+        Ok(false) => {}
+        Err(e) =>
+        {
+            warn!("Skipping SBOM generation for '{}': {}", image_ref, e);
+            return;
+        }
+    }
+
+    let result = docker_service::generate_sbom_with_syft(image_ref, host, config).await
+        .and_then(|raw| prepare_document(&raw));
+
+    let stored = match result
+    {
+        Ok((document, packages)) =>
+        {
+            info!("SBOM generated for image '{}' ({} packages).", image_ref, packages.len());
+            store(pool, image_digest, image_ref, Some(&document), &packages, None).await
+        }
+        Err(reason) =>
+        {
+            warn!("SBOM generation failed for image '{}': {}", image_ref, reason);
+            store(pool, image_digest, image_ref, None, &[], Some(&reason)).await
+        }
+    };
+
+    if let Err(e) = stored
+    {
+        error!("Could not store SBOM for image '{}': {}", image_ref, e);
+    }
+}
+
+fn prepare_document(raw: &[u8]) -> Result<(Vec<u8>, Vec<String>), String>
+{
+    let parsed: CycloneDxDocument = serde_json::from_slice(raw)
+        .map_err(|e| format!("Syft produced an invalid CycloneDX document: {}", e))?;
+
+    let mut packages: Vec<String> = parsed.components
+        .into_iter()
+        .map(|component| match component.version
+        {
+            Some(version) => format!("{}@{}", component.name, version),
+            None => component.name,
+        })
+        .collect();
+    packages.sort();
+    packages.dedup();
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(raw)
+        .and_then(|_| encoder.finish())
+        .map(|document| (document, packages))
+        .map_err(|e| format!("Failed to compress SBOM: {}", e))
+}
+
+async fn has_document(pool: &PgPool, image_digest: &str) -> Result<bool, AppError>
+{
+    sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM sboms WHERE image_digest = $1 AND document IS NOT NULL)")
+        .bind(image_digest)
+        .fetch_one(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to check SBOM for digest '{}': {}", image_digest, e);
+            AppError::database(&e)
+        })
+}
+
+async fn store(
+    pool: &PgPool,
+    image_digest: &str,
+    image_ref: &str,
+    document: Option<&[u8]>,
+    packages: &[String],
+    error_message: Option<&str>,
+) -> Result<(), AppError>
+{
+    sqlx::query(
+        "INSERT INTO sboms (image_digest, image_ref, document, packages, error_message) VALUES ($1, $2, $3, $4, $5)
+         ON CONFLICT (image_digest) DO UPDATE
+         SET image_ref = $2, document = $3, packages = $4, error_message = $5, generated_at = NOW()"
+    )
+        .bind(image_digest)
+        .bind(image_ref)
+        .bind(document)
+        .bind(packages)
+        .bind(error_message)
+        .execute(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to store SBOM for digest '{}': {}", image_digest, e);
+            AppError::database(&e)
+        })?;
+    Ok(())
+}
+
+pub async fn get_document(pool: &PgPool, image_digest: &str) -> Result<SbomLookup, AppError>
+{
+    let stored = sqlx::query_as::<_, StoredSbom>("SELECT document, error_message FROM sboms WHERE image_digest = $1")
+        .bind(image_digest)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to fetch SBOM for digest '{}': {}", image_digest, e);
+            AppError::database(&e)
+        })?;
+
+    let Some(stored) = stored else
+    {
+        return Ok(SbomLookup::Missing(None));
+    };
+
+    let Some(compressed) = stored.document else
+    {
+        return Ok(SbomLookup::Missing(stored.error_message));
+    };
+
+    let mut document = Vec::new();
+    GzDecoder::new(compressed.as_slice()).read_to_end(&mut document).map_err(|e|
+    {
+        error!("Failed to decompress SBOM for digest '{}': {}", image_digest, e);
+        AppError::InternalServerError
+    })?;
+
+    Ok(SbomLookup::Available(document))
+}
+
+fn escape_like(value: &str) -> String
+{
+    value.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+}
+
+pub async fn search_package(pool: &PgPool, package: &str) -> Result<Vec<SbomPackageMatch>, AppError>
+{
+    sqlx::query_as::<_, SbomPackageMatch>(
+        "SELECT p.id AS project_id, p.name AS project_name, p.owner, s.image_digest,
+                ARRAY(SELECT pkg FROM unnest(s.packages) AS pkg WHERE pkg ILIKE '%' || $1 || '%' ORDER BY pkg) AS packages
+         FROM projects p
+         JOIN sboms s ON s.image_digest = p.deployed_image_digest
+         WHERE EXISTS (SELECT 1 FROM unnest(s.packages) AS pkg WHERE pkg ILIKE '%' || $1 || '%')
+         ORDER BY p.name
+         LIMIT $2"
+    )
+        .bind(escape_like(package))
+        .bind(SBOM_SEARCH_LIMIT)
+        .fetch_all(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to search SBOMs for package '{}': {}", package, e);
+            AppError::database(&e)
+        })
+}
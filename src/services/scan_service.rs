@@ -0,0 +1,211 @@
+use sqlx::PgPool;
+use tracing::{error, info, warn};
+
+use crate::
+{
+    config::{Config, DockerHostConfig},
+    error::{AppError, ProjectErrorCode},
+    model::scan::{ScanFinding, ScanReport, ScanResult, ScanThreshold, ScanWaiver, ScanWaiverPayload, WaivedFinding},
+    services::docker_service,
+};
+
+const SELECT_WAIVER_FIELDS: &str =
+    "SELECT id, cve_id, package, project_name, justification, created_by, expires_at, created_at, updated_at FROM scan_waivers";
+
+pub async fn scan_image(
+    pool: &PgPool,
+    config: &Config,
+    host: &DockerHostConfig,
+    image_url: &str,
+    project_name: &str,
+    threshold: ScanThreshold,
+) -> Result<(), AppError>
+{
+    let Some(findings) = docker_service::scan_image_with_grype(image_url, host, config).await? else
+    {
+        return Ok(());
+    };
+
+    let waivers = get_active_waivers_for_project(pool, project_name).await?;
+    let report = evaluate_findings(findings, &waivers, threshold);
+    record_result(pool, project_name, image_url, &report).await;
+
+    if report.is_blocking()
+    {
+        warn!("Grype found {} blocking vulnerabilities in image '{}'", report.blocking.len(), image_url);
+        return Err(ProjectErrorCode::ImageScanFailed(report).into());
+    }
+
+    if !report.waived.is_empty()
+    {
+        info!("Grype scan passed for image '{}' with {} waived findings.", image_url, report.waived.len());
+    }
+    else
+    {
+        info!("Grype scan passed for image '{}'.", image_url);
+    }
+
+    Ok(())
+}
+
+// Un échec d'enregistrement ne doit pas changer l'issue du scan.
+async fn record_result(pool: &PgPool, project_name: &str, image_url: &str, report: &ScanReport)
+{
+    let result = sqlx::query(
+        "INSERT INTO project_scan_results (project_name, image_ref, passed, fail_on_severity, blocking_count, waived_count, summary, scanned_at)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, NOW())
+         ON CONFLICT (project_name) DO UPDATE SET
+             image_ref = EXCLUDED.image_ref, passed = EXCLUDED.passed, fail_on_severity = EXCLUDED.fail_on_severity,
+             blocking_count = EXCLUDED.blocking_count, waived_count = EXCLUDED.waived_count,
+             summary = EXCLUDED.summary, scanned_at = EXCLUDED.scanned_at",
+    )
+    .bind(project_name)
+    .bind(image_url)
+    .bind(!report.is_blocking())
+    .bind(report.fail_on_severity)
+    .bind(report.blocking.len() as i32)
+    .bind(report.waived.len() as i32)
+    .bind(report.summary())
+    .execute(pool)
+    .await;
+
+    if let Err(e) = result
+    {
+        error!("Failed to record the scan result of image '{}' for project '{}': {}", image_url, project_name, e);
+    }
+}
+
+pub async fn get_last_result(pool: &PgPool, project_name: &str) -> Result<Option<ScanResult>, AppError>
+{
+    sqlx::query_as::<_, ScanResult>(
+        "SELECT image_ref, passed, fail_on_severity, blocking_count, waived_count, summary, scanned_at
+         FROM project_scan_results WHERE project_name = $1",
+    )
+    .bind(project_name)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e|
+    {
+        error!("Failed to fetch the last scan result of project '{}': {}", project_name, e);
+        AppError::database(&e)
+    })
+}
+
+fn evaluate_findings(findings: Vec<ScanFinding>, waivers: &[ScanWaiver], threshold: ScanThreshold) -> ScanReport
+{
+    let mut report = ScanReport
+    {
+        fail_on_severity: threshold.severity,
+        threshold_source: threshold.source,
+        blocking: Vec::new(),
+        waived: Vec::new(),
+    };
+
+    for finding in findings
+    {
+        if let Some(waiver) = waivers.iter().find(|waiver| waiver.covers(&finding))
+        {
+            report.waived.push(WaivedFinding
+            {
+                finding,
+                waiver_id: waiver.id,
+                justification: waiver.justification.clone(),
+                waiver_expires_at: waiver.expires_at,
+            });
+        }
+        else if finding.severity >= threshold.severity
+        {
+            report.blocking.push(finding);
+        }
+    }
+
+    report
+}
+
+// Les dérogations expirées sont filtrées ici : elles cessent de s'appliquer sans nettoyage.
+async fn get_active_waivers_for_project(pool: &PgPool, project_name: &str) -> Result<Vec<ScanWaiver>, AppError>
+{
+    let query = format!("{} WHERE expires_at > NOW() AND (project_name IS NULL OR project_name = $1)", SELECT_WAIVER_FIELDS);
+    sqlx::query_as::<_, ScanWaiver>(&query)
+        .bind(project_name)
+        .fetch_all(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to fetch scan waivers for project '{}': {}", project_name, e);
+            AppError::database(&e)
+        })
+}
+
+pub async fn get_all_waivers(pool: &PgPool) -> Result<Vec<ScanWaiver>, AppError>
+{
+    let query = format!("{} ORDER BY expires_at DESC", SELECT_WAIVER_FIELDS);
+    sqlx::query_as::<_, ScanWaiver>(&query)
+        .fetch_all(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to fetch scan waivers: {}", e);
+            AppError::database(&e)
+        })
+}
+
+pub async fn create_waiver(pool: &PgPool, payload: &ScanWaiverPayload, admin_login: &str) -> Result<ScanWaiver, AppError>
+{
+    sqlx::query_as::<_, ScanWaiver>(
+        "INSERT INTO scan_waivers (cve_id, package, project_name, justification, expires_at, created_by)
+         VALUES ($1, $2, $3, $4, $5, $6)
+         RETURNING id, cve_id, package, project_name, justification, created_by, expires_at, created_at, updated_at"
+    )
+        .bind(&payload.cve_id)
+        .bind(&payload.package)
+        .bind(&payload.project_name)
+        .bind(&payload.justification)
+        .bind(payload.expires_at)
+        .bind(admin_login)
+        .fetch_one(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to create scan waiver for '{}': {}", payload.cve_id, e);
+            AppError::database(&e)
+        })
+}
+
+pub async fn update_waiver(pool: &PgPool, waiver_id: i32, payload: &ScanWaiverPayload, admin_login: &str) -> Result<Option<ScanWaiver>, AppError>
+{
+    sqlx::query_as::<_, ScanWaiver>(
+        "UPDATE scan_waivers
+         SET cve_id = $2, package = $3, project_name = $4, justification = $5, expires_at = $6, created_by = $7, updated_at = NOW()
+         WHERE id = $1
+         RETURNING id, cve_id, package, project_name, justification, created_by, expires_at, created_at, updated_at"
+    )
+        .bind(waiver_id)
+        .bind(&payload.cve_id)
+        .bind(&payload.package)
+        .bind(&payload.project_name)
+        .bind(&payload.justification)
+        .bind(payload.expires_at)
+        .bind(admin_login)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to update scan waiver {}: {}", waiver_id, e);
+            AppError::database(&e)
+        })
+}
+
+pub async fn delete_waiver(pool: &PgPool, waiver_id: i32) -> Result<bool, AppError>
+{
+    let result = sqlx::query("DELETE FROM scan_waivers WHERE id = $1")
+        .bind(waiver_id)
+        .execute(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to delete scan waiver {}: {}", waiver_id, e);
+            AppError::database(&e)
+        })?;
+    Ok(result.rows_affected() > 0)
+}
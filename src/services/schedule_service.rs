@@ -0,0 +1,119 @@
+use time::OffsetDateTime;
+
+use crate::error::AppError;
+
+// Cron à cinq champs : `*`, valeurs, intervalles, pas (`*/15`, `8-18/2`) et listes de ceux-ci.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CronSchedule
+{
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+    // Comme cron : quand les deux champs de jour sont restreints, il suffit que l'un corresponde.
+    either_day: bool,
+}
+
+impl CronSchedule
+{
+    pub fn parse(expression: &str) -> Result<Self, AppError>
+    {
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        let [minute, hour, day_of_month, month, day_of_week] = fields[..]
+        else
+        {
+            return Err(invalid(expression, "expected five fields: minute, hour, day of month, month and day of week"));
+        };
+
+        let days_of_week = parse_field(day_of_week, 0, 7).map_err(|reason| invalid(expression, &reason))?;
+
+        Ok(Self
+        {
+            minutes: parse_field(minute, 0, 59).map_err(|reason| invalid(expression, &reason))?,
+            hours: parse_field(hour, 0, 23).map_err(|reason| invalid(expression, &reason))?,
+            days_of_month: parse_field(day_of_month, 1, 31).map_err(|reason| invalid(expression, &reason))?,
+            months: parse_field(month, 1, 12).map_err(|reason| invalid(expression, &reason))?,
+            // 7 is another name for Sunday.
+            days_of_week: (days_of_week | (days_of_week >> 7)) & 0x7f,
+            either_day: day_of_month != "*" && day_of_week != "*",
+        })
+    }
+
+    pub fn matches(&self, at: OffsetDateTime) -> bool
+    {
+        let day_of_month = has(self.days_of_month, at.day());
+        let day_of_week = has(self.days_of_week, at.weekday().number_days_from_sunday());
+        let day = if self.either_day { day_of_month || day_of_week } else { day_of_month && day_of_week };
+
+        day
+            && has(self.minutes, at.minute())
+            && has(self.hours, at.hour())
+            && has(self.months, u8::from(at.month()))
+    }
+}
+
+fn has(set: u64, value: u8) -> bool
+{
+    set & (1 << value) != 0
+}
+
+fn invalid(expression: &str, reason: &str) -> AppError
+{
+    AppError::BadRequest(format!("Invalid cron expression '{}': {}.", expression, reason))
+}
+
+fn parse_field(field: &str, min: u8, max: u8) -> Result<u64, String>
+{
+    let mut set = 0;
+
+    for part in field.split(',')
+    {
+        let (range, step) = match part.split_once('/')
+        {
+            Some((range, step)) => match step.parse::<u8>()
+            {
+                Ok(step) if step > 0 => (range, step),
+                _ => return Err(format!("invalid step in '{}'", part)),
+            },
+            None => (part, 1),
+        };
+
+        let (start, end) = match range
+        {
+            "*" => (min, max),
+            _ => match range.split_once('-')
+            {
+                Some((start, end)) => (parse_value(start, min, max)?, parse_value(end, min, max)?),
+                // `5/10` runs from 5 to the end of the field, like `5-max/10`.
+                None if step > 1 => (parse_value(range, min, max)?, max),
+                None =>
+                {
+                    let value = parse_value(range, min, max)?;
+                    (value, value)
+                }
+            },
+        };
+
+        if start > end
+        {
+            return Err(format!("range '{}' is reversed", range));
+        }
+
+        for value in (start..=end).step_by(usize::from(step))
+        {
+            set |= 1 << value;
+        }
+    }
+
+    Ok(set)
+}
+
+fn parse_value(value: &str, min: u8, max: u8) -> Result<u8, String>
+{
+    match value.parse::<u8>()
+    {
+        Ok(number) if (min..=max).contains(&number) => Ok(number),
+        _ => Err(format!("'{}' is not a number between {} and {}", value, min, max)),
+    }
+}
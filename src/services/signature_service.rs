@@ -0,0 +1,92 @@
+use bollard::Docker;
+use sqlx::PgPool;
+use tracing::{error, info, warn};
+
+use crate::
+{
+    config::{Config, DockerHostConfig},
+    error::{AppError, ProjectErrorCode},
+    services::{docker_service, validation_service},
+};
+
+// Les images des autres registres, ou toutes quand cosign est désactivé, passent sans vérification.
+pub async fn verify_image_signature(pool: &PgPool, config: &Config, host: &DockerHostConfig, docker: &Docker, image_url: &str) -> Result<(), AppError>
+{
+    verify_local_image_signature(pool, config, host, docker, image_url, image_url).await
+}
+
+// Pour une image inspectée sous une autre référence locale, comme un tag temporaire.
+pub async fn verify_local_image_signature(
+    pool: &PgPool,
+    config: &Config,
+    host: &DockerHostConfig,
+    docker: &Docker,
+    local_image: &str,
+    image_url: &str,
+) -> Result<(), AppError>
+{
+    if !config.cosign_enabled
+    {
+        return Ok(());
+    }
+
+    let registry = validation_service::image_registry(image_url);
+    if !config.cosign_required_registries.contains(registry)
+    {
+        return Ok(());
+    }
+
+    let repository = validation_service::image_repository(image_url);
+    let repo_digest = docker_service::get_image_repo_digest(docker, local_image, repository)
+        .await?
+        .ok_or_else(||
+        {
+            warn!("No registry digest found for image '{}', cannot verify its signature.", image_url);
+            ProjectErrorCode::ImageSignatureInvalid("The image has no registry digest to verify.".to_string())
+        })?;
+
+    if is_verified(pool, &repo_digest).await?
+    {
+        info!("Signature of '{}' already verified, skipping cosign.", repo_digest);
+        return Ok(());
+    }
+
+    info!("Verifying signature of '{}' with cosign...", repo_digest);
+
+    docker_service::verify_image_with_cosign(&repo_digest, host, config)
+        .await
+        .map_err(|output|
+        {
+            warn!("Signature verification failed for image '{}'.", repo_digest);
+            ProjectErrorCode::ImageSignatureInvalid(output)
+        })?;
+
+    mark_verified(pool, &repo_digest).await
+}
+
+async fn is_verified(pool: &PgPool, image_digest: &str) -> Result<bool, AppError>
+{
+    sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM image_signature_verifications WHERE image_digest = $1)")
+        .bind(image_digest)
+        .fetch_one(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to check signature cache for '{}': {}", image_digest, e);
+            AppError::database(&e)
+        })
+}
+
+async fn mark_verified(pool: &PgPool, image_digest: &str) -> Result<(), AppError>
+{
+    sqlx::query("INSERT INTO image_signature_verifications (image_digest) VALUES ($1) ON CONFLICT (image_digest) DO UPDATE SET verified_at = NOW()")
+        .bind(image_digest)
+        .execute(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to cache signature verification for '{}': {}", image_digest, e);
+            AppError::database(&e)
+        })?;
+    Ok(())
+}
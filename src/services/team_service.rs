@@ -0,0 +1,364 @@
+use sqlx::PgPool;
+use tracing::error;
+
+use crate::
+{
+    error::AppError,
+    model::{project::Project, team::{Team, TeamMember, TeamMembership, TeamRole}},
+};
+
+pub async fn create_team(pool: &PgPool, name: &str, creator: &str) -> Result<Team, AppError>
+{
+    let mut tx = pool.begin().await.map_err(|e| AppError::database(&e))?;
+
+    let team = sqlx::query_as::<_, Team>(
+        "INSERT INTO teams (name, created_by) VALUES ($1, $2) RETURNING id, name, max_projects, created_by, created_at"
+    )
+        .bind(name)
+        .bind(creator)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(|e|
+        {
+            if let Some(db_err) = e.as_database_error()
+                && db_err.is_unique_violation()
+            {
+                return AppError::BadRequest(format!("The team name '{}' is already taken.", name));
+            }
+            error!("Failed to create team '{}': {}", name, e);
+            AppError::InternalServerError
+        })?;
+
+    sqlx::query("INSERT INTO team_members (team_id, login, role, added_by) VALUES ($1, $2, 'owner', $2)")
+        .bind(team.id)
+        .bind(creator)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to add '{}' as owner of team '{}': {}", creator, name, e);
+            AppError::database(&e)
+        })?;
+
+    tx.commit().await.map_err(|e| AppError::database(&e))?;
+    Ok(team)
+}
+
+pub async fn get_team(pool: &PgPool, team_id: i32) -> Result<Option<Team>, AppError>
+{
+    sqlx::query_as::<_, Team>("SELECT id, name, max_projects, created_by, created_at FROM teams WHERE id = $1")
+        .bind(team_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to fetch team {}: {}", team_id, e);
+            AppError::database(&e)
+        })
+}
+
+pub async fn get_member_role(pool: &PgPool, team_id: i32, login: &str) -> Result<Option<TeamRole>, AppError>
+{
+    sqlx::query_scalar("SELECT role FROM team_members WHERE team_id = $1 AND login = $2")
+        .bind(team_id)
+        .bind(login)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to fetch the role of '{}' in team {}: {}", login, team_id, e);
+            AppError::database(&e)
+        })
+}
+
+// Un non-membre reçoit la même réponse que pour une équipe inconnue.
+pub async fn require_role(pool: &PgPool, team_id: i32, login: &str, is_admin: bool, required: TeamRole) -> Result<Team, AppError>
+{
+    let not_found = || AppError::NotFound(format!("Team with ID {} not found or you are not a member.", team_id));
+
+    let team = get_team(pool, team_id).await?.ok_or_else(not_found)?;
+    let role = if is_admin { None } else { get_member_role(pool, team_id, login).await? };
+
+    match team_access(role, is_admin, required)
+    {
+        TeamAccess::Granted => Ok(team),
+        TeamAccess::NotMember => Err(not_found()),
+        TeamAccess::Denied => Err(AppError::Unauthorized("Only the owners of the team can do this.".to_string())),
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum TeamAccess
+{
+    Granted,
+    NotMember,
+    Denied,
+}
+
+fn team_access(role: Option<TeamRole>, is_admin: bool, required: TeamRole) -> TeamAccess
+{
+    match (role, required)
+    {
+        _ if is_admin => TeamAccess::Granted,
+        (None, _) => TeamAccess::NotMember,
+        (Some(TeamRole::Member), TeamRole::Owner) => TeamAccess::Denied,
+        (Some(_), _) => TeamAccess::Granted,
+    }
+}
+
+pub async fn has_owner_rights(pool: &PgPool, project: &Project, login: &str) -> Result<bool, AppError>
+{
+    let role = match project.team_id
+    {
+        Some(team_id) => get_member_role(pool, team_id, login).await?,
+        None => None,
+    };
+    Ok(owner_rights(&project.owner, project.team_id, login, role))
+}
+
+pub async fn can_reveal_secrets(pool: &PgPool, project: &Project, login: &str, is_admin: bool) -> Result<bool, AppError>
+{
+    let role = match project.team_id
+    {
+        Some(team_id) if !is_admin => get_member_role(pool, team_id, login).await?,
+        _ => None,
+    };
+    Ok(reveal_rights(&project.owner, project.team_id, login, role, is_admin))
+}
+
+fn reveal_rights(project_owner: &str, team_id: Option<i32>, login: &str, role: Option<TeamRole>, is_admin: bool) -> bool
+{
+    is_admin || owner_rights(project_owner, team_id, login, role)
+}
+
+// Sur un projet d'équipe, celui qui l'a déployé n'a que les droits de son rôle.
+fn owner_rights(project_owner: &str, team_id: Option<i32>, login: &str, role: Option<TeamRole>) -> bool
+{
+    match team_id
+    {
+        None => project_owner == login,
+        Some(_) => role == Some(TeamRole::Owner),
+    }
+}
+
+pub async fn get_teams_for_user(pool: &PgPool, login: &str) -> Result<Vec<TeamMembership>, AppError>
+{
+    sqlx::query_as::<_, TeamMembership>(
+        "SELECT t.id AS team_id, t.name AS team_name, tm.role, tm.added_at
+         FROM team_members tm
+         JOIN teams t ON t.id = tm.team_id
+         WHERE tm.login = $1
+         ORDER BY t.name"
+    )
+        .bind(login)
+        .fetch_all(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to fetch teams of '{}': {}", login, e);
+            AppError::database(&e)
+        })
+}
+
+pub async fn get_members(pool: &PgPool, team_id: i32) -> Result<Vec<TeamMember>, AppError>
+{
+    sqlx::query_as::<_, TeamMember>(
+        "SELECT login, role, added_by, added_at FROM team_members WHERE team_id = $1 ORDER BY role, login"
+    )
+        .bind(team_id)
+        .fetch_all(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to fetch members of team {}: {}", team_id, e);
+            AppError::database(&e)
+        })
+}
+
+pub async fn add_member(pool: &PgPool, team_id: i32, login: &str, role: TeamRole, added_by: &str) -> Result<(), AppError>
+{
+    sqlx::query("INSERT INTO team_members (team_id, login, role, added_by) VALUES ($1, $2, $3, $4)")
+        .bind(team_id)
+        .bind(login)
+        .bind(role)
+        .bind(added_by)
+        .execute(pool)
+        .await
+        .map_err(|e|
+        {
+            if let Some(db_err) = e.as_database_error()
+                && db_err.is_unique_violation()
+            {
+                return AppError::BadRequest(format!("'{}' is already a member of this team.", login));
+            }
+            error!("Failed to add '{}' to team {}: {}", login, team_id, e);
+            AppError::InternalServerError
+        })?;
+    Ok(())
+}
+
+// Une équipe garde toujours au moins un propriétaire.
+pub async fn set_member_role(pool: &PgPool, team_id: i32, login: &str, role: TeamRole) -> Result<(), AppError>
+{
+    let result = sqlx::query(
+        "UPDATE team_members SET role = $3
+         WHERE team_id = $1 AND login = $2
+           AND ($3 = 'owner' OR role = 'member'
+                OR (SELECT COUNT(*) FROM team_members WHERE team_id = $1 AND role = 'owner') > 1)"
+    )
+        .bind(team_id)
+        .bind(login)
+        .bind(role)
+        .execute(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to change the role of '{}' in team {}: {}", login, team_id, e);
+            AppError::database(&e)
+        })?;
+
+    if result.rows_affected() == 0
+    {
+        return Err(member_change_refused(pool, team_id, login).await);
+    }
+    Ok(())
+}
+
+// Une équipe garde toujours au moins un propriétaire.
+pub async fn remove_member(pool: &PgPool, team_id: i32, login: &str) -> Result<(), AppError>
+{
+    let result = sqlx::query(
+        "DELETE FROM team_members
+         WHERE team_id = $1 AND login = $2
+           AND (role = 'member' OR (SELECT COUNT(*) FROM team_members WHERE team_id = $1 AND role = 'owner') > 1)"
+    )
+        .bind(team_id)
+        .bind(login)
+        .execute(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to remove '{}' from team {}: {}", login, team_id, e);
+            AppError::database(&e)
+        })?;
+
+    if result.rows_affected() == 0
+    {
+        return Err(member_change_refused(pool, team_id, login).await);
+    }
+    Ok(())
+}
+
+async fn member_change_refused(pool: &PgPool, team_id: i32, login: &str) -> AppError
+{
+    match get_member_role(pool, team_id, login).await
+    {
+        Ok(Some(_)) => AppError::BadRequest("A team must keep at least one owner.".to_string()),
+        Ok(None) => AppError::NotFound(format!("'{}' is not a member of this team.", login)),
+        Err(e) => e,
+    }
+}
+
+// Refusé tant que l'équipe possède des projets.
+pub async fn delete_team(pool: &PgPool, team_id: i32) -> Result<(), AppError>
+{
+    sqlx::query("DELETE FROM teams WHERE id = $1")
+        .bind(team_id)
+        .execute(pool)
+        .await
+        .map_err(|e|
+        {
+            if let Some(db_err) = e.as_database_error()
+                && db_err.is_foreign_key_violation()
+            {
+                return AppError::BadRequest("The team still owns projects. Purge them first.".to_string());
+            }
+            error!("Failed to delete team {}: {}", team_id, e);
+            AppError::InternalServerError
+        })?;
+    Ok(())
+}
+
+pub async fn set_max_projects(pool: &PgPool, team_id: i32, max_projects: Option<i32>) -> Result<(), AppError>
+{
+    sqlx::query("UPDATE teams SET max_projects = $2 WHERE id = $1")
+        .bind(team_id)
+        .bind(max_projects)
+        .execute(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to set the project quota of team {}: {}", team_id, e);
+            AppError::database(&e)
+        })?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    #[test]
+    fn team_permission_matrix()
+    {
+        use TeamAccess::{Denied, Granted, NotMember};
+
+        // (rôle, admin, rôle requis) -> accès
+        let cases = [
+            (None, false, TeamRole::Member, NotMember),
+            (None, false, TeamRole::Owner, NotMember),
+            (Some(TeamRole::Member), false, TeamRole::Member, Granted),
+            (Some(TeamRole::Member), false, TeamRole::Owner, Denied),
+            (Some(TeamRole::Owner), false, TeamRole::Member, Granted),
+            (Some(TeamRole::Owner), false, TeamRole::Owner, Granted),
+            (None, true, TeamRole::Member, Granted),
+            (None, true, TeamRole::Owner, Granted),
+        ];
+
+        for (role, is_admin, required, expected) in cases
+        {
+            assert_eq!(team_access(role, is_admin, required), expected, "role {:?}, admin {}, requires {:?}", role, is_admin, required);
+        }
+    }
+
+    #[test]
+    fn project_owner_rights_matrix()
+    {
+        // (propriétaire, équipe, utilisateur, rôle dans l'équipe) -> droits de propriétaire
+        let cases = [
+            ("alice", None, "alice", None, true),
+            ("alice", None, "bob", None, false),
+            ("alice", Some(1), "alice", Some(TeamRole::Member), false),
+            ("alice", Some(1), "alice", None, false),
+            ("alice", Some(1), "bob", Some(TeamRole::Owner), true),
+            ("alice", Some(1), "bob", Some(TeamRole::Member), false),
+            ("alice", Some(1), "carol", None, false),
+        ];
+
+        for (owner, team_id, login, role, expected) in cases
+        {
+            assert_eq!(owner_rights(owner, team_id, login, role), expected, "{} on a project of {} (team {:?}, role {:?})", login, owner, team_id, role);
+        }
+    }
+
+    #[test]
+    fn secret_reveal_matrix()
+    {
+        // (propriétaire, équipe, utilisateur, rôle dans l'équipe, admin) -> révélation permise
+        let cases = [
+            ("alice", None, "alice", None, false, true),
+            ("alice", Some(1), "bob", Some(TeamRole::Owner), false, true),
+            ("alice", Some(1), "alice", Some(TeamRole::Member), false, false),
+            ("alice", Some(1), "carol", Some(TeamRole::Member), false, false),
+            ("alice", None, "bob", None, false, false),
+            ("alice", None, "admin", None, true, true),
+            ("alice", Some(1), "admin", None, true, true),
+        ];
+
+        for (owner, team_id, login, role, is_admin, expected) in cases
+        {
+            assert_eq!(reveal_rights(owner, team_id, login, role, is_admin), expected, "{} (admin {}) on a project of {} (team {:?}, role {:?})", login, is_admin, owner, team_id, role);
+        }
+    }
+}
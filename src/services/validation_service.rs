@@ -1,5 +1,12 @@
+use crate::config::Config;
 use crate::error::{AppError, ProjectErrorCode};
+use crate::model::project::{EnvVarVisibility, RoutingOptions, TraefikHealthcheck};
+use crate::model::scan::{ScanWaiverPayload, Severity};
+use crate::services::env_template_service;
+use ipnet::IpNet;
 use std::collections::{HashMap, HashSet};
+use std::net::IpAddr;
+use time::{Duration, OffsetDateTime};
 
 pub fn validate_project_name(name: &str) -> Result<(), AppError>
 {
@@ -26,6 +33,17 @@ pub fn validate_project_name(name: &str) -> Result<(), AppError>
     Ok(())
 }
 
+pub fn validate_team_name(name: &str) -> Result<(), AppError>
+{
+    if validate_project_name(name).is_err()
+    {
+        return Err(AppError::BadRequest(
+            "The team name must be 1-63 characters, contain only a-z, 0-9, or '-', and not start/end with a hyphen.".to_string()
+        ));
+    }
+    Ok(())
+}
+
 pub fn validate_image_url(url: &str) -> Result<(), AppError> 
 {
     if url.is_empty() 
@@ -41,22 +59,201 @@ pub fn validate_image_url(url: &str) -> Result<(), AppError>
     Ok(())
 }
 
-pub fn validate_env_vars(vars: &HashMap<String, String>) -> Result<(), AppError>
+// Règle de Docker : le premier composant n'est un registre que s'il ressemble à un hôte.
+pub fn image_registry(image_url: &str) -> &str
+{
+    match image_url.split_once('/')
+    {
+        Some((first, _)) if first.contains('.') || first.contains(':') || first == "localhost" => first,
+        _ => "docker.io",
+    }
+}
+
+pub fn image_repository(image_url: &str) -> &str
+{
+    if let Some((repository, _)) = image_url.split_once('@')
+    {
+        return repository;
+    }
+
+    match image_url.rsplit_once(':')
+    {
+        Some((repository, tag)) if !tag.contains('/') => repository,
+        _ => image_url,
+    }
+}
+
+// Parties implicites de Docker Hub explicitées : `nginx` et `docker.io/library/nginx:1.27` sont égales.
+fn canonical_repository(image_url: &str) -> String
+{
+    let registry = image_registry(image_url);
+    let repository = image_repository(image_url);
+    let path = repository.strip_prefix(registry).and_then(|path| path.strip_prefix('/')).unwrap_or(repository);
+
+    match registry
+    {
+        "docker.io" | "index.docker.io" | "registry-1.docker.io" if !path.contains('/') => format!("docker.io/library/{}", path),
+        "docker.io" | "index.docker.io" | "registry-1.docker.io" => format!("docker.io/{}", path),
+        _ => format!("{}/{}", registry.to_ascii_lowercase(), path),
+    }
+}
+
+// Seul le tag ou le digest peut changer : une faute dans le dépôt remplacerait le projet par une image publique quelconque.
+pub fn validate_image_repository_change(current_image_url: &str, new_image_url: &str, allow_repository_change: bool) -> Result<(), AppError>
+{
+    if allow_repository_change
+    {
+        return Ok(());
+    }
+
+    let current = canonical_repository(current_image_url);
+    if canonical_repository(new_image_url) != current
+    {
+        return Err(ProjectErrorCode::ImageRepositoryChanged(current).into());
+    }
+
+    Ok(())
+}
+
+pub const PLATFORM_ENV_PREFIX: &str = "HANGAR_";
+
+const BUILTIN_FORBIDDEN_ENV_VARS: &[&str] = &[
+    "PATH", "LD_PRELOAD", "DOCKER_HOST", "HOST", "HOSTNAME",
+    "TRAEFIK_ENABLE",
+];
+
+const BUILTIN_FORBIDDEN_ENV_PREFIXES: &[&str] = &["TRAEFIK_"];
+
+// FORBIDDEN_ENV_VARS et FORBIDDEN_ENV_PREFIXES s'ajoutent aux listes intégrées sans les remplacer.
+pub fn validate_env_vars(vars: &HashMap<String, String>, config: &Config) -> Result<(), AppError>
+{
+    validate_env_vars_size(vars, config)?;
+
+    let mut keys: Vec<&String> = vars.keys().collect();
+    keys.sort();
+
+    // Un nom contenant '=' ou un espace casserait la ligne 'KEY=VALUE' passée à Docker.
+    let invalid_names: Vec<String> = keys.iter()
+        .filter(|key| !is_valid_env_var_name(key))
+        .map(|key| key.to_string())
+        .collect();
+    if !invalid_names.is_empty()
+    {
+        return Err(ProjectErrorCode::InvalidEnvVarName(invalid_names).into());
+    }
+
+    let forbidden: Vec<String> = keys.iter()
+        .filter(|key| is_forbidden_env_var(key, config))
+        .map(|key| key.to_string())
+        .collect();
+    if !forbidden.is_empty()
+    {
+        return Err(ProjectErrorCode::ForbiddenEnvVar(forbidden).into());
+    }
+
+    let reserved: Vec<String> = keys.iter()
+        .filter(|key| key.to_uppercase().starts_with(PLATFORM_ENV_PREFIX))
+        .map(|key| key.to_string())
+        .collect();
+    if !reserved.is_empty()
+    {
+        return Err(ProjectErrorCode::ReservedEnvVar(reserved).into());
+    }
+
+    // Un retour à la ligne ou un octet nul dans une valeur pourrait fabriquer une autre entrée
+    // (par exemple un label 'traefik.') là où la valeur est recopiée telle quelle.
+    let invalid_values: Vec<String> = keys.iter()
+        .filter(|key| vars[key.as_str()].chars().any(char::is_control))
+        .map(|key| key.to_string())
+        .collect();
+    if !invalid_values.is_empty()
+    {
+        return Err(ProjectErrorCode::InvalidEnvVarValue(invalid_values).into());
+    }
+
+    let mut unknown_placeholders: Vec<String> = keys.iter()
+        .flat_map(|key| env_template_service::unknown_placeholders(&vars[key.as_str()]))
+        .collect();
+    if !unknown_placeholders.is_empty()
+    {
+        unknown_placeholders.sort();
+        unknown_placeholders.dedup();
+        return Err(ProjectErrorCode::UnknownEnvPlaceholder(unknown_placeholders).into());
+    }
+
+    let oversized: Vec<String> = keys.iter()
+        .filter(|key| vars[key.as_str()].len() > config.env_var_max_value_bytes)
+        .map(|key| key.to_string())
+        .collect();
+    if !oversized.is_empty()
+    {
+        return Err(ProjectErrorCode::EnvVarValueTooLarge(oversized, config.env_var_max_value_bytes).into());
+    }
+
+    Ok(())
+}
+
+// Les mises à jour partielles vérifient le résultat fusionné : `validate_env_vars` ne voit que les clés modifiées.
+pub fn validate_env_vars_size(vars: &HashMap<String, String>, config: &Config) -> Result<(), AppError>
+{
+    if vars.len() > config.env_vars_max_count
+    {
+        return Err(ProjectErrorCode::TooManyEnvVars(config.env_vars_max_count).into());
+    }
+
+    let total: usize = vars.iter().map(|(key, value)| key.len() + 1 + value.len()).sum();
+    if total > config.env_vars_max_total_bytes
+    {
+        return Err(ProjectErrorCode::EnvVarsTooLarge(config.env_vars_max_total_bytes).into());
+    }
+
+    Ok(())
+}
+
+fn is_valid_env_var_name(key: &str) -> bool
+{
+    let mut chars = key.chars();
+    chars.next().is_some_and(|first| first.is_ascii_alphabetic() || first == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn is_forbidden_env_var(key: &str, config: &Config) -> bool
 {
-    const FORBIDDEN_ENV_VARS: &[&str] = &[
-        "PATH", "LD_PRELOAD", "DOCKER_HOST", "HOST", "HOSTNAME",
-        "TRAEFIK_ENABLE",
-    ];
+    let key = key.to_uppercase();
 
-    for key in vars.keys()
+    let matches_name = BUILTIN_FORBIDDEN_ENV_VARS.iter().copied()
+        .chain(config.forbidden_env_vars.iter().map(String::as_str))
+        .any(|pattern| glob_matches(&pattern.to_uppercase(), &key));
+    let matches_prefix = BUILTIN_FORBIDDEN_ENV_PREFIXES.iter().copied()
+        .chain(config.forbidden_env_prefixes.iter().map(String::as_str))
+        .any(|prefix| key.starts_with(&prefix.to_uppercase()));
+
+    matches_name || matches_prefix
+}
+
+fn glob_matches(pattern: &str, name: &str) -> bool
+{
+    let Some((head, rest)) = pattern.split_once('*') else
+    {
+        return pattern == name;
+    };
+    let Some(mut remaining) = name.strip_prefix(head) else
     {
-        if FORBIDDEN_ENV_VARS.iter().any(|&forbidden| key.eq_ignore_ascii_case(forbidden))
-            || key.to_uppercase().starts_with("TRAEFIK_")
+        return false;
+    };
+
+    let mut parts: Vec<&str> = rest.split('*').collect();
+    let tail = parts.pop().unwrap_or_default();
+    for part in parts
+    {
+        match remaining.find(part)
         {
-            return Err(ProjectErrorCode::ForbiddenEnvVar(key.clone()).into());
+            Some(index) => remaining = &remaining[index + part.len()..],
+            None => return false,
         }
     }
-    Ok(())
+
+    remaining.ends_with(tail)
 }
 
 pub fn validate_volume_path(path: &str) -> Result<(), AppError>
@@ -106,4 +303,510 @@ pub fn validate_source_root_dir(path: &str) -> Result<(), AppError>
     }
     
     Ok(())
-}
\ No newline at end of file
+}
+
+pub fn validate_ip_allowlist(ranges: &[String]) -> Result<Vec<String>, AppError>
+{
+    const MAX_IP_ALLOWLIST_ENTRIES: usize = 32;
+
+    if ranges.is_empty()
+    {
+        return Err(AppError::BadRequest("The IP allowlist must contain at least one range.".to_string()));
+    }
+
+    if ranges.len() > MAX_IP_ALLOWLIST_ENTRIES
+    {
+        return Err(AppError::BadRequest(format!(
+            "The IP allowlist cannot contain more than {} ranges.",
+            MAX_IP_ALLOWLIST_ENTRIES
+        )));
+    }
+
+    ranges
+        .iter()
+        .map(|range|
+        {
+            let range = range.trim();
+            // Une adresse seule est acceptée comme un réseau /32 ou /128
+            range.parse::<IpNet>()
+                .or_else(|_| range.parse::<IpAddr>().map(IpNet::from))
+                .map(|net| net.to_string())
+                .map_err(|_| AppError::BadRequest(format!("Invalid CIDR range: '{}'.", range)))
+        })
+        .collect()
+}
+
+const ALLOWED_RESPONSE_HEADERS: &[&str] = &[
+    "Access-Control-Allow-Origin", "Access-Control-Allow-Methods", "Access-Control-Allow-Headers",
+    "Access-Control-Allow-Credentials", "Access-Control-Expose-Headers", "Access-Control-Max-Age",
+    "Content-Security-Policy", "Content-Security-Policy-Report-Only", "Strict-Transport-Security",
+    "X-Frame-Options", "X-Content-Type-Options", "Referrer-Policy", "Permissions-Policy",
+    "Cross-Origin-Opener-Policy", "Cross-Origin-Embedder-Policy", "Cross-Origin-Resource-Policy",
+    "Cache-Control", "X-Robots-Tag",
+];
+
+pub fn validate_routing_options(options: RoutingOptions) -> Result<RoutingOptions, AppError>
+{
+    const MAX_RESPONSE_HEADERS: usize = 16;
+    const MAX_HEADER_VALUE_LENGTH: usize = 1024;
+
+    if options.response_headers.len() > MAX_RESPONSE_HEADERS
+    {
+        return Err(AppError::BadRequest(format!("A project cannot set more than {} response headers.", MAX_RESPONSE_HEADERS)));
+    }
+
+    let mut response_headers = std::collections::BTreeMap::new();
+    for (name, value) in options.response_headers
+    {
+        let Some(&allowed) = ALLOWED_RESPONSE_HEADERS.iter().find(|allowed| allowed.eq_ignore_ascii_case(&name))
+        else
+        {
+            return Err(AppError::BadRequest(format!(
+                "The response header '{}' cannot be set. Allowed headers: {}.",
+                name, ALLOWED_RESPONSE_HEADERS.join(", ")
+            )));
+        };
+
+        // Valeurs ASCII imprimables uniquement : elles sont recopiées telles quelles dans un label Traefik.
+        if value.trim().is_empty() || value.len() > MAX_HEADER_VALUE_LENGTH || !value.chars().all(|c| c == ' ' || c.is_ascii_graphic())
+        {
+            return Err(AppError::BadRequest(format!(
+                "The value of the response header '{}' must be 1 to {} printable ASCII characters.",
+                allowed, MAX_HEADER_VALUE_LENGTH
+            )));
+        }
+
+        if response_headers.insert(allowed.to_string(), value.trim().to_string()).is_some()
+        {
+            return Err(AppError::BadRequest(format!("The response header '{}' is set more than once.", allowed)));
+        }
+    }
+
+    Ok(RoutingOptions { sticky_sessions: options.sticky_sessions, response_headers })
+}
+
+pub fn validate_project_notes(notes: &str) -> Result<(), AppError>
+{
+    const MAX_NOTES_BYTES: usize = 16 * 1024;
+
+    if notes.len() > MAX_NOTES_BYTES
+    {
+        return Err(AppError::BadRequest(format!("Notes cannot exceed {} KB.", MAX_NOTES_BYTES / 1024)));
+    }
+
+    if notes.chars().any(|c| c.is_control() && c != '\n' && c != '\r' && c != '\t')
+    {
+        return Err(AppError::BadRequest("Notes cannot contain control characters other than newlines and tabs.".to_string()));
+    }
+
+    Ok(())
+}
+
+const MAX_DESCRIPTION_CHARS: usize = 500;
+const MAX_TAGS: usize = 10;
+const MAX_TAG_CHARS: usize = 32;
+
+pub fn sanitize_project_description(description: Option<String>) -> Result<Option<String>, AppError>
+{
+    let Some(description) = description else { return Ok(None) };
+
+    let description: String = description.chars().filter(|c| !c.is_control()).collect();
+    let description = description.trim();
+
+    if description.chars().count() > MAX_DESCRIPTION_CHARS
+    {
+        return Err(AppError::BadRequest(format!("The description cannot exceed {} characters.", MAX_DESCRIPTION_CHARS)));
+    }
+
+    Ok(Some(description.to_string()).filter(|description| !description.is_empty()))
+}
+
+pub fn sanitize_project_tags(tags: Vec<String>) -> Result<Vec<String>, AppError>
+{
+    let mut sanitized: Vec<String> = Vec::with_capacity(tags.len());
+
+    for tag in tags
+    {
+        let tag: String = tag.chars().filter(|c| !c.is_control()).collect();
+        let tag = tag.trim();
+
+        if tag.chars().count() > MAX_TAG_CHARS
+        {
+            return Err(AppError::BadRequest(format!("Tags cannot exceed {} characters: '{}'.", MAX_TAG_CHARS, tag)));
+        }
+        if !tag.is_empty() && !sanitized.iter().any(|existing| existing == tag)
+        {
+            sanitized.push(tag.to_string());
+        }
+    }
+
+    if sanitized.len() > MAX_TAGS
+    {
+        return Err(AppError::BadRequest(format!("A project can have at most {} tags.", MAX_TAGS)));
+    }
+
+    Ok(sanitized)
+}
+
+const MAX_CUSTOM_DOMAINS: usize = 5;
+
+// Les sous-domaines de `app_domain_suffix` sont réservés aux noms d'hôte générés des projets.
+// Traefik enverrait le trafic de la plateforme vers le conteneur qui revendiquerait l'un de ces noms.
+fn platform_hostnames(config: &Config) -> HashSet<String>
+{
+    let public_host = reqwest::Url::parse(&config.public_address).ok()
+        .and_then(|url| url.host_str().map(str::to_string));
+
+    [public_host, config.edge_hostname.clone(), Some(config.mariadb_public_host.clone())]
+        .into_iter()
+        .flatten()
+        .map(|host| host.trim().trim_end_matches('.').to_ascii_lowercase())
+        .filter(|host| !host.is_empty())
+        .collect()
+}
+
+pub fn validate_custom_domains(domains: Vec<String>, config: &Config) -> Result<Vec<String>, AppError>
+{
+    let suffix = config.app_domain_suffix.to_ascii_lowercase();
+    let platform_hosts = platform_hostnames(config);
+    let mut validated: Vec<String> = Vec::with_capacity(domains.len());
+
+    for domain in domains
+    {
+        let domain = domain.trim().trim_end_matches('.').to_ascii_lowercase();
+
+        let labels: Vec<&str> = domain.split('.').collect();
+        let valid = domain.len() <= 253
+            && labels.len() >= 2
+            && labels.iter().all(|label| validate_project_name(label).is_ok())
+            && labels.last().is_some_and(|tld| tld.chars().any(|c| c.is_ascii_alphabetic()));
+        if !valid
+        {
+            return Err(AppError::BadRequest(format!("'{}' is not a valid fully qualified domain name.", domain)));
+        }
+
+        if domain == suffix || domain.ends_with(&format!(".{}", suffix))
+        {
+            return Err(AppError::BadRequest(format!("'{}' is under '{}', which is reserved for the platform's hostnames.", domain, suffix)));
+        }
+
+        if platform_hosts.contains(&domain)
+        {
+            return Err(AppError::BadRequest(format!("'{}' is one of the platform's own hostnames.", domain)));
+        }
+
+        if !validated.contains(&domain)
+        {
+            validated.push(domain);
+        }
+    }
+
+    if validated.len() > MAX_CUSTOM_DOMAINS
+    {
+        return Err(AppError::BadRequest(format!("A project can have at most {} custom domains.", MAX_CUSTOM_DOMAINS)));
+    }
+
+    Ok(validated)
+}
+
+pub fn validate_traefik_healthcheck(path: &str, interval_seconds: i32, port: Option<i32>) -> Result<TraefikHealthcheck, AppError>
+{
+    const MIN_INTERVAL_SECONDS: i32 = 5;
+    const MAX_INTERVAL_SECONDS: i32 = 300;
+
+    if !path.starts_with('/') || path.len() > 255 || path.chars().any(|c| c.is_whitespace() || c.is_control())
+    {
+        return Err(AppError::BadRequest("The healthcheck path must start with '/', contain no spaces and be at most 255 characters.".to_string()));
+    }
+
+    if !(MIN_INTERVAL_SECONDS..=MAX_INTERVAL_SECONDS).contains(&interval_seconds)
+    {
+        return Err(AppError::BadRequest(format!(
+            "The healthcheck interval must be between {} and {} seconds.",
+            MIN_INTERVAL_SECONDS, MAX_INTERVAL_SECONDS
+        )));
+    }
+
+    if port.is_some_and(|port| !(1..=65535).contains(&port))
+    {
+        return Err(AppError::BadRequest("The healthcheck port must be between 1 and 65535.".to_string()));
+    }
+
+    Ok(TraefikHealthcheck { path: path.to_string(), interval_seconds, port })
+}
+
+pub fn validate_env_var_visibility(
+    visibility: &HashMap<String, EnvVarVisibility>,
+    vars: &HashMap<String, String>,
+) -> Result<(), AppError>
+{
+    match visibility.keys().find(|key| !vars.contains_key(*key))
+    {
+        Some(key) => Err(AppError::BadRequest(format!("The visibility of '{}' is set, but the variable itself is not.", key))),
+        None => Ok(()),
+    }
+}
+
+// Un déploiement peut bloquer sur des sévérités plus basses que le seuil de son propriétaire, jamais plus hautes.
+pub fn validate_scan_fail_on(requested: Option<Severity>, owner_threshold: Severity) -> Result<(), AppError>
+{
+    match requested
+    {
+        Some(Severity::Unknown) => Err(AppError::BadRequest(
+            "scan_fail_on must be one of negligible, low, medium, high or critical.".to_string()
+        )),
+        Some(severity) if severity > owner_threshold => Err(AppError::BadRequest(format!(
+            "scan_fail_on cannot be looser than your scan threshold '{}'.",
+            owner_threshold.as_str()
+        ))),
+        _ => Ok(()),
+    }
+}
+
+pub fn validate_scan_waiver(payload: &ScanWaiverPayload) -> Result<(), AppError>
+{
+    const MAX_JUSTIFICATION_CHARS: usize = 2000;
+    const MAX_WAIVER_DAYS: i64 = 365;
+
+    let cve_id = payload.cve_id.as_str();
+    let upper_cve_id = cve_id.to_ascii_uppercase();
+    let has_known_prefix = ["CVE-", "GHSA-"].iter()
+        .any(|prefix| upper_cve_id.strip_prefix(prefix).is_some_and(|rest| !rest.is_empty()));
+    if !has_known_prefix || cve_id.len() > 64 || !cve_id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+    {
+        return Err(AppError::BadRequest(format!("Invalid vulnerability identifier: '{}'. Expected a CVE or GHSA id.", cve_id)));
+    }
+
+    if payload.package.as_ref().is_some_and(|package| package.trim().is_empty() || package.len() > 255)
+    {
+        return Err(AppError::BadRequest("The package name must be between 1 and 255 characters.".to_string()));
+    }
+
+    if let Some(project_name) = &payload.project_name
+    {
+        validate_project_name(project_name)?;
+    }
+
+    let justification = payload.justification.trim();
+    if justification.is_empty() || justification.chars().count() > MAX_JUSTIFICATION_CHARS
+    {
+        return Err(AppError::BadRequest(format!(
+            "A justification of at most {} characters is required.",
+            MAX_JUSTIFICATION_CHARS
+        )));
+    }
+
+    let now = OffsetDateTime::now_utc();
+    if payload.expires_at <= now || payload.expires_at > now + Duration::days(MAX_WAIVER_DAYS)
+    {
+        return Err(AppError::BadRequest(format!(
+            "A waiver must expire in the future and within {} days.",
+            MAX_WAIVER_DAYS
+        )));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    fn config() -> Config
+    {
+        Config::for_tests(&[("EDGE_HOSTNAME", "edge.hangar.test")]).unwrap()
+    }
+
+    #[test]
+    fn platform_hostnames_cannot_be_claimed()
+    {
+        for domain in ["hangar.test", "Edge.Hangar.Test.", "db.hangar.test"]
+        {
+            assert!(validate_custom_domains(vec![domain.to_string()], &config()).is_err(), "{}", domain);
+        }
+    }
+
+    #[test]
+    fn platform_subdomains_cannot_be_claimed()
+    {
+        assert!(validate_custom_domains(vec!["apps.hangar.test".to_string()], &config()).is_err());
+        assert!(validate_custom_domains(vec!["blog.apps.hangar.test".to_string()], &config()).is_err());
+    }
+
+    #[test]
+    fn other_domains_are_normalized_and_deduplicated()
+    {
+        let domains = vec!["WWW.Example.org.".to_string(), "www.example.org".to_string(), "api.hangar.test".to_string()];
+
+        assert_eq!(validate_custom_domains(domains, &config()).unwrap(), vec!["www.example.org", "api.hangar.test"]);
+    }
+
+    #[test]
+    fn env_var_names_follow_the_shell_rules()
+    {
+        for name in ["A", "_", "DATABASE_URL", "_private", "v2"]
+        {
+            assert!(is_valid_env_var_name(name), "{} should be valid", name);
+        }
+        for name in ["", "2FA", "MY-VAR", "MY VAR", "ÉTÉ", "A=B"]
+        {
+            assert!(!is_valid_env_var_name(name), "{} should be invalid", name);
+        }
+    }
+
+    #[test]
+    fn glob_matches_whole_names_only()
+    {
+        let cases = [
+            ("PATH", "PATH", true),
+            ("PATH", "PATHS", false),
+            ("AWS_*", "AWS_SECRET_KEY", true),
+            ("AWS_*", "MY_AWS_KEY", false),
+            ("*_TOKEN", "GITHUB_TOKEN", true),
+            ("*_TOKEN", "GITHUB_TOKEN_ID", false),
+            ("*SECRET*", "MY_SECRET_VALUE", true),
+            ("A*B*C", "AXBYC", true),
+            ("A*B*C", "AXCYB", false),
+            ("*", "ANYTHING", true),
+        ];
+
+        for (pattern, name, expected) in cases
+        {
+            assert_eq!(glob_matches(pattern, name), expected, "{} against {}", pattern, name);
+        }
+    }
+
+    #[test]
+    fn configured_patterns_add_to_the_builtin_ones()
+    {
+        let config = Config::for_tests(&[("FORBIDDEN_ENV_VARS", "aws_*"), ("FORBIDDEN_ENV_PREFIXES", "internal_")]).unwrap();
+
+        for key in ["path", "TRAEFIK_HTTP", "AWS_SECRET", "internal_api"]
+        {
+            assert!(is_forbidden_env_var(key, &config), "{} should be forbidden", key);
+        }
+        assert!(!is_forbidden_env_var("APP_SECRET", &config));
+    }
+
+    fn vars(entries: &[(&str, &str)]) -> HashMap<String, String>
+    {
+        entries.iter().map(|(key, value)| (key.to_string(), value.to_string())).collect()
+    }
+
+    #[test]
+    fn builtin_forbidden_names_survive_any_configured_list()
+    {
+        let configs = [
+            Config::for_tests(&[]).unwrap(),
+            Config::for_tests(&[("FORBIDDEN_ENV_VARS", "")]).unwrap(),
+            Config::for_tests(&[("FORBIDDEN_ENV_VARS", "AWS_*,GCP_*"), ("FORBIDDEN_ENV_PREFIXES", "CUSTOM_")]).unwrap(),
+        ];
+
+        for config in &configs
+        {
+            for key in BUILTIN_FORBIDDEN_ENV_VARS.iter().chain(&["TRAEFIK_HTTP_ROUTERS"])
+            {
+                assert!(is_forbidden_env_var(key, config), "{} should stay forbidden", key);
+                assert!(is_forbidden_env_var(&key.to_lowercase(), config), "{} should stay forbidden", key.to_lowercase());
+            }
+        }
+    }
+
+    #[test]
+    fn every_forbidden_key_is_reported_at_once()
+    {
+        let config = Config::for_tests(&[("FORBIDDEN_ENV_VARS", "AWS_*")]).unwrap();
+        let result = validate_env_vars(&vars(&[("PATH", "/bin"), ("AWS_KEY", "x"), ("APP_MODE", "prod"), ("TRAEFIK_X", "y")]), &config);
+
+        assert!(matches!(
+            result,
+            Err(AppError::ProjectError(ProjectErrorCode::ForbiddenEnvVar(keys))) if keys == ["AWS_KEY", "PATH", "TRAEFIK_X"]
+        ));
+    }
+
+    #[test]
+    fn control_characters_in_values_are_rejected()
+    {
+        let entries = [("NEWLINE", "a\ntraefik.enable=true"), ("NULL", "a\0b"), ("TAB", "a\tb"), ("PLAIN", "value with spaces")];
+        let result = validate_env_vars(&vars(&entries), &config());
+
+        assert!(matches!(
+            result,
+            Err(AppError::ProjectError(ProjectErrorCode::InvalidEnvVarValue(keys))) if keys == ["NEWLINE", "NULL", "TAB"]
+        ));
+        assert!(validate_env_vars(&vars(&[("PLAIN", "value with spaces")]), &config()).is_ok());
+    }
+
+    fn routing(headers: &[(&str, &str)]) -> RoutingOptions
+    {
+        RoutingOptions
+        {
+            sticky_sessions: false,
+            response_headers: headers.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect(),
+        }
+    }
+
+    #[test]
+    fn response_header_names_are_canonicalized_from_the_whitelist()
+    {
+        let options = validate_routing_options(routing(&[("x-frame-options", " DENY "), ("CACHE-CONTROL", "no-store")])).unwrap();
+
+        assert_eq!(options, routing(&[("X-Frame-Options", "DENY"), ("Cache-Control", "no-store")]));
+    }
+
+    #[test]
+    fn unsafe_response_headers_are_refused()
+    {
+        let too_long = "a".repeat(1025);
+        let cases = [
+            routing(&[("Set-Cookie", "session=1")]),
+            routing(&[("X-Frame-Options", "")]),
+            routing(&[("X-Frame-Options", "DENY\r\nSet-Cookie: a=b")]),
+            routing(&[("X-Frame-Options", too_long.as_str())]),
+            routing(&[("x-frame-options", "DENY"), ("X-Frame-Options", "SAMEORIGIN")]),
+        ];
+
+        for options in cases
+        {
+            assert!(validate_routing_options(options.clone()).is_err(), "{:?} should be refused", options);
+        }
+    }
+
+    #[test]
+    fn image_update_may_only_change_the_tag_or_digest()
+    {
+        let cases = [
+            ("ghcr.io/acme/app:1.0", "ghcr.io/acme/app:1.1"),
+            ("ghcr.io/acme/app:1.0", "ghcr.io/acme/app@sha256:0123"),
+            ("nginx", "docker.io/library/nginx:1.27"),
+            ("acme/app:latest", "index.docker.io/acme/app:2"),
+            ("registry.local:5000/team/app:1", "registry.local:5000/team/app:2"),
+        ];
+
+        for (current, new) in cases
+        {
+            assert!(validate_image_repository_change(current, new, false).is_ok(), "{} -> {}", current, new);
+        }
+    }
+
+    #[test]
+    fn image_update_to_another_repository_needs_the_override()
+    {
+        let cases = [
+            ("ghcr.io/acme/app:1.0", "ghcr.io/acme/other:1.0", "ghcr.io/acme/app"),
+            ("ghcr.io/acme/app:1.0", "docker.io/acme/app:1.0", "ghcr.io/acme/app"),
+            ("nginx:1.27", "ngnix:1.27", "docker.io/library/nginx"),
+            ("registry.local:5000/app:1", "registry.local:5001/app:1", "registry.local:5000/app"),
+        ];
+
+        for (current, new, repository) in cases
+        {
+            assert!(matches!(
+                validate_image_repository_change(current, new, false),
+                Err(AppError::ProjectError(ProjectErrorCode::ImageRepositoryChanged(reported))) if reported == repository
+            ), "{} -> {}", current, new);
+            assert!(validate_image_repository_change(current, new, true).is_ok(), "{} -> {} with the override", current, new);
+        }
+    }
+}
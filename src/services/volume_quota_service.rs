@@ -0,0 +1,163 @@
+use sqlx::PgPool;
+use time::OffsetDateTime;
+use tracing::error;
+
+use crate::
+{
+    config::Config,
+    error::AppError,
+    model::volume_quota::{VolumeQuotaAction, VolumeQuotaPolicy, VolumeQuotaStatus, VolumeUsage},
+};
+
+const BYTES_PER_MB: i64 = 1024 * 1024;
+
+pub const MAX_VOLUME_QUOTA_MB: i64 = 1024 * 1024;
+
+pub fn default_quota_bytes(config: &Config) -> i64
+{
+    config.volume_quota_default_mb * BYTES_PER_MB
+}
+
+pub fn validate_override(max_mb: Option<i64>) -> Result<Option<i64>, AppError>
+{
+    match max_mb
+    {
+        Some(max) if !(1..=MAX_VOLUME_QUOTA_MB).contains(&max) =>
+            Err(AppError::BadRequest(format!("max_mb must be between 1 and {}.", MAX_VOLUME_QUOTA_MB))),
+        Some(max) => Ok(Some(max * BYTES_PER_MB)),
+        None => Ok(None),
+    }
+}
+
+pub async fn get_override(pool: &PgPool, project_id: i32) -> Result<Option<i64>, AppError>
+{
+    sqlx::query_scalar("SELECT max_bytes FROM project_volume_quotas WHERE project_id = $1")
+        .bind(project_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to fetch volume quota override for project {}: {}", project_id, e);
+            AppError::database(&e)
+        })
+}
+
+pub async fn set_override(pool: &PgPool, project_id: i32, max_bytes: Option<i64>, admin_login: &str) -> Result<(), AppError>
+{
+    let query = match max_bytes
+    {
+        Some(max_bytes) => sqlx::query(
+            "INSERT INTO project_volume_quotas (project_id, max_bytes, updated_by) VALUES ($1, $2, $3)
+             ON CONFLICT (project_id) DO UPDATE SET max_bytes = $2, updated_by = $3, updated_at = NOW()"
+        )
+            .bind(project_id)
+            .bind(max_bytes)
+            .bind(admin_login),
+        None => sqlx::query("DELETE FROM project_volume_quotas WHERE project_id = $1")
+            .bind(project_id),
+    };
+
+    query.execute(pool).await.map_err(|e|
+    {
+        error!("Failed to save volume quota override for project {}: {}", project_id, e);
+        AppError::InternalServerError
+    })?;
+    Ok(())
+}
+
+pub async fn get_usage(pool: &PgPool, project_id: i32) -> Result<Option<VolumeUsage>, AppError>
+{
+    sqlx::query_as::<_, VolumeUsage>(
+        "SELECT used_bytes, measured_at, over_quota_since, stopped_by_quota FROM project_volume_usage WHERE project_id = $1"
+    )
+        .bind(project_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to fetch volume usage of project {}: {}", project_id, e);
+            AppError::database(&e)
+        })
+}
+
+pub async fn save_usage(
+    pool: &PgPool,
+    project_id: i32,
+    used_bytes: i64,
+    over_quota_since: Option<OffsetDateTime>,
+    stopped_by_quota: bool,
+) -> Result<(), AppError>
+{
+    sqlx::query(
+        "INSERT INTO project_volume_usage (project_id, used_bytes, over_quota_since, stopped_by_quota) VALUES ($1, $2, $3, $4)
+         ON CONFLICT (project_id) DO UPDATE SET used_bytes = $2, measured_at = NOW(), over_quota_since = $3, stopped_by_quota = $4"
+    )
+        .bind(project_id)
+        .bind(used_bytes)
+        .bind(over_quota_since)
+        .bind(stopped_by_quota)
+        .execute(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to save volume usage of project {}: {}", project_id, e);
+            AppError::database(&e)
+        })?;
+    Ok(())
+}
+
+pub async fn record_event(
+    pool: &PgPool,
+    project_id: i32,
+    action: VolumeQuotaAction,
+    used_bytes: Option<i64>,
+    quota_bytes: i64,
+    actor: Option<&str>,
+) -> Result<(), AppError>
+{
+    sqlx::query(
+        "INSERT INTO volume_quota_events (project_id, action, used_bytes, quota_bytes, actor) VALUES ($1, $2, $3, $4, $5)"
+    )
+        .bind(project_id)
+        .bind(action)
+        .bind(used_bytes)
+        .bind(quota_bytes)
+        .bind(actor)
+        .execute(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to record volume quota event for project {}: {}", project_id, e);
+            AppError::database(&e)
+        })?;
+    Ok(())
+}
+
+pub fn enforcement_deadline(config: &Config, over_quota_since: OffsetDateTime) -> Option<OffsetDateTime>
+{
+    (config.volume_quota_policy == VolumeQuotaPolicy::Enforce)
+        .then(|| over_quota_since + time::Duration::seconds(config.volume_quota_grace_period_seconds))
+}
+
+pub async fn get_status(pool: &PgPool, config: &Config, project_id: i32, volume_name: &str) -> Result<VolumeQuotaStatus, AppError>
+{
+    let quota_override = get_override(pool, project_id).await?;
+    let quota_bytes = quota_override.unwrap_or_else(|| default_quota_bytes(config));
+    let usage = get_usage(pool, project_id).await?;
+
+    let over_quota_since = usage.as_ref().and_then(|usage| usage.over_quota_since);
+
+    Ok(VolumeQuotaStatus
+    {
+        volume_name: volume_name.to_string(),
+        used_bytes: usage.as_ref().map(|usage| usage.used_bytes),
+        quota_bytes,
+        quota_overridden: quota_override.is_some(),
+        policy: config.volume_quota_policy,
+        over_quota: usage.as_ref().is_some_and(|usage| usage.used_bytes > quota_bytes),
+        measured_at: usage.as_ref().map(|usage| usage.measured_at),
+        over_quota_since,
+        enforced_at: over_quota_since.and_then(|since| enforcement_deadline(config, since)),
+        stopped_by_quota: usage.as_ref().is_some_and(|usage| usage.stopped_by_quota),
+    })
+}
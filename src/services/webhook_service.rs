@@ -0,0 +1,215 @@
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use sqlx::PgPool;
+use time::OffsetDateTime;
+use tracing::error;
+use crate::error::AppError;
+use crate::model::webhook_delivery::{WebhookDelivery, WebhookDeliveryOutcome};
+
+type HmacSha256 = Hmac<Sha256>;
+
+const WEBHOOK_DELIVERIES_LIMIT: i64 = 500;
+
+// Les deux secrets sont acceptés pendant une rotation.
+pub fn verify_signature(secrets: &[String], payload: &[u8], signature_header: Option<&str>) -> bool
+{
+    let Some(signature) = signature_header
+        .and_then(|value| value.strip_prefix("sha256="))
+        .and_then(decode_hex)
+    else
+    {
+        return false;
+    };
+
+    secrets.iter().any(|secret|
+    {
+        let Ok(mut mac) = HmacSha256::new_from_slice(secret.as_bytes()) else { return false };
+        mac.update(payload);
+        mac.verify_slice(&signature).is_ok()
+    })
+}
+
+// GitHub n'envoie pas d'horodatage signé ; seuls les push portent `repository.pushed_at`, couvert par la signature.
+fn payload_timestamp(payload: &serde_json::Value) -> Option<OffsetDateTime>
+{
+    payload
+        .pointer("/repository/pushed_at")?
+        .as_i64()
+        .and_then(|timestamp| OffsetDateTime::from_unix_timestamp(timestamp).ok())
+}
+
+pub fn stale_timestamp(payload: &serde_json::Value, now: OffsetDateTime, window_seconds: i64) -> Option<OffsetDateTime>
+{
+    payload_timestamp(payload).filter(|timestamp| now - *timestamp > time::Duration::seconds(window_seconds))
+}
+
+fn decode_hex(value: &str) -> Option<Vec<u8>>
+{
+    if !value.len().is_multiple_of(2)
+    {
+        return None;
+    }
+    (0..value.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(value.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+// L'index unique fait perdre la course aux rejeux simultanés au lieu de les laisser passer tous les deux.
+pub async fn claim_delivery(
+    pool: &PgPool,
+    delivery_id: &str,
+    event: &str,
+    payload_size: i64,
+    ip_address: &str,
+) -> Result<bool, AppError>
+{
+    let inserted: Option<i64> = sqlx::query_scalar(
+        "INSERT INTO webhook_deliveries (delivery_id, event, outcome, payload_size, ip_address)
+         VALUES ($1, $2, 'accepted', $3, $4)
+         ON CONFLICT (delivery_id) WHERE outcome = 'accepted' DO NOTHING
+         RETURNING id"
+    )
+        .bind(delivery_id)
+        .bind(event)
+        .bind(payload_size)
+        .bind(ip_address)
+        .fetch_optional(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to claim webhook delivery '{}': {}", delivery_id, e);
+            AppError::database(&e)
+        })?;
+    Ok(inserted.is_some())
+}
+
+pub async fn record_delivery(
+    pool: &PgPool,
+    delivery_id: Option<&str>,
+    event: Option<&str>,
+    outcome: WebhookDeliveryOutcome,
+    payload_size: i64,
+    ip_address: &str,
+) -> Result<(), AppError>
+{
+    sqlx::query(
+        "INSERT INTO webhook_deliveries (delivery_id, event, outcome, payload_size, ip_address) VALUES ($1, $2, $3, $4, $5)"
+    )
+        .bind(delivery_id)
+        .bind(event)
+        .bind(outcome)
+        .bind(payload_size)
+        .bind(ip_address)
+        .execute(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to record webhook delivery {:?} from {}: {}", delivery_id, ip_address, e);
+            AppError::database(&e)
+        })?;
+    Ok(())
+}
+
+// Au-delà de la fenêtre, c'est l'horodatage du contenu qui arrête les rejeux.
+pub async fn prune_deliveries(pool: &PgPool, window_seconds: i64) -> Result<(), AppError>
+{
+    sqlx::query("DELETE FROM webhook_deliveries WHERE received_at < NOW() - make_interval(secs => $1)")
+        .bind(window_seconds as f64)
+        .execute(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to prune webhook deliveries: {}", e);
+            AppError::database(&e)
+        })?;
+    Ok(())
+}
+
+pub async fn get_recent_deliveries(
+    pool: &PgPool,
+    outcome: Option<WebhookDeliveryOutcome>,
+) -> Result<Vec<WebhookDelivery>, AppError>
+{
+    sqlx::query_as::<_, WebhookDelivery>(
+        "SELECT id, delivery_id, event, outcome, payload_size, ip_address, received_at
+         FROM webhook_deliveries
+         WHERE ($1::webhook_delivery_outcome IS NULL OR outcome = $1)
+         ORDER BY received_at DESC
+         LIMIT $2"
+    )
+        .bind(outcome)
+        .bind(WEBHOOK_DELIVERIES_LIMIT)
+        .fetch_all(pool)
+        .await
+        .map_err(|e|
+        {
+            error!("Failed to fetch webhook deliveries: {}", e);
+            AppError::database(&e)
+        })
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use serde_json::json;
+
+    const PAYLOAD: &[u8] = br#"{"ref":"refs/heads/main"}"#;
+
+    fn signature(secret: &str, payload: &[u8]) -> String
+    {
+        let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).unwrap();
+        mac.update(payload);
+        let digest = mac.finalize().into_bytes();
+        format!("sha256={}", digest.iter().map(|byte| format!("{:02x}", byte)).collect::<String>())
+    }
+
+    fn secrets() -> Vec<String>
+    {
+        vec!["current".to_string(), "previous".to_string()]
+    }
+
+    #[test]
+    fn valid_signature_is_accepted()
+    {
+        assert!(verify_signature(&secrets(), PAYLOAD, Some(&signature("current", PAYLOAD))));
+    }
+
+    #[test]
+    fn previous_secret_is_accepted_during_rotation()
+    {
+        assert!(verify_signature(&secrets(), PAYLOAD, Some(&signature("previous", PAYLOAD))));
+        assert!(!verify_signature(&secrets()[..1], PAYLOAD, Some(&signature("previous", PAYLOAD))));
+    }
+
+    #[test]
+    fn wrong_secret_or_altered_payload_is_rejected()
+    {
+        assert!(!verify_signature(&secrets(), PAYLOAD, Some(&signature("other", PAYLOAD))));
+        assert!(!verify_signature(&secrets(), br#"{"ref":"refs/heads/evil"}"#, Some(&signature("current", PAYLOAD))));
+    }
+
+    #[test]
+    fn malformed_header_is_rejected()
+    {
+        let valid = signature("current", PAYLOAD);
+        let hex = valid.strip_prefix("sha256=").unwrap();
+
+        for header in [None, Some(""), Some("sha256="), Some(hex), Some(&format!("sha1={}", hex)), Some(&valid[..valid.len() - 1]), Some("sha256=zz")]
+        {
+            assert!(!verify_signature(&secrets(), PAYLOAD, header), "{:?} should be rejected", header);
+        }
+    }
+
+    #[test]
+    fn payload_older_than_the_replay_window_is_stale()
+    {
+        let now = OffsetDateTime::from_unix_timestamp(1_700_000_000).unwrap();
+        let pushed_at = |seconds_ago: i64| json!({ "repository": { "pushed_at": now.unix_timestamp() - seconds_ago } });
+
+        assert_eq!(stale_timestamp(&pushed_at(301), now, 300), Some(now - time::Duration::seconds(301)));
+        assert_eq!(stale_timestamp(&pushed_at(299), now, 300), None);
+        assert_eq!(stale_timestamp(&json!({ "action": "ping" }), now, 300), None);
+    }
+}
@@ -1,30 +1,192 @@
-use std::sync::Arc;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex, PoisonError, RwLock};
+use std::sync::atomic::AtomicBool;
+use std::time::{Duration, Instant};
 use bollard::Docker;
+use tokio::sync::Semaphore;
 use sqlx::{MySqlPool, PgPool};
-use crate::config::Config;
+use tracing::error;
+use crate::config::{Config, ConfigChange, DockerHostConfig};
+use crate::error::AppError;
+use crate::model::deployment::{CloneProgress, PullProgress};
+use crate::model::project::{CachedGlobalMetrics, DockerPlatform, ReconciliationReport};
 
 pub type AppState = Arc<InnerState>;
 
 pub struct InnerState 
 {
-    pub config : Config,
+    // Remplacée en entier au rechargement : une copie en main reste cohérente.
+    config: RwLock<Arc<Config>>,
     pub http_client: reqwest::Client,
-    pub docker_client: Docker,
+    pub probe_client: reqwest::Client,
+    pub docker_hosts: HashMap<String, Docker>,
+    // Interrogées une fois au démarrage ; un hôte injoignable à ce moment-là en est absent.
+    pub docker_platforms: HashMap<String, DockerPlatform>,
     pub db_pool: PgPool,
     pub mariadb_pool: MySqlPool,
+    pub active_exports: Mutex<HashSet<String>>,
+    pub last_reconciliation: Mutex<Option<ReconciliationReport>>,
+    pub pull_progress: Mutex<HashMap<String, PullProgress>>,
+    pub clone_progress: Mutex<HashMap<String, CloneProgress>>,
+    pub build_slots: Semaphore,
+    pub image_validation_slots: Semaphore,
+    pub image_update_checks: Mutex<HashMap<i32, Instant>>,
+    // Gardés jusqu'à leur expiration pour refuser les rejeux.
+    pub consumed_stream_tickets: Mutex<HashMap<String, i64>>,
+    pub global_metrics: Mutex<Option<CachedGlobalMetrics>>,
+    pub global_metrics_refreshing: AtomicBool,
 }
 
 impl InnerState 
 {
-    pub fn new(config: Config, docker_client: Docker, db_pool: PgPool, mariadb_pool: MySqlPool) -> AppState 
+    pub fn new(
+        config: Config,
+        http_client: reqwest::Client,
+        probe_client: reqwest::Client,
+        docker_hosts: HashMap<String, Docker>,
+        docker_platforms: HashMap<String, DockerPlatform>,
+        db_pool: PgPool,
+        mariadb_pool: MySqlPool,
+    ) -> AppState 
     {
+        let image_validation_slots = Semaphore::new(config.image_validation_max_concurrent);
+        let build_slots = Semaphore::new(config.build_max_concurrent.max(1));
+
         Arc::new(Self 
         {
-            config,
-            http_client: reqwest::Client::new(),
-            docker_client,
+            config: RwLock::new(Arc::new(config)),
+            http_client,
+            probe_client,
+            docker_hosts,
+            docker_platforms,
             db_pool,
             mariadb_pool,
+            active_exports: Mutex::new(HashSet::new()),
+            last_reconciliation: Mutex::new(None),
+            pull_progress: Mutex::new(HashMap::new()),
+            clone_progress: Mutex::new(HashMap::new()),
+            build_slots,
+            image_validation_slots,
+            image_update_checks: Mutex::new(HashMap::new()),
+            consumed_stream_tickets: Mutex::new(HashMap::new()),
+            global_metrics: Mutex::new(None),
+            global_metrics_refreshing: AtomicBool::new(false),
+        })
+    }
+
+    pub fn docker_for(&self, host: &str) -> Result<&Docker, AppError>
+    {
+        self.docker_hosts.get(host).ok_or_else(||
+        {
+            error!("Docker host '{}' is not configured in DOCKER_HOSTS.", host);
+            AppError::InternalServerError
         })
     }
-}
\ No newline at end of file
+
+    pub fn docker_host_config(&self, host: &str) -> Result<DockerHostConfig, AppError>
+    {
+        self.config().docker_host(host).cloned().ok_or_else(||
+        {
+            error!("Docker host '{}' is not configured in DOCKER_HOSTS.", host);
+            AppError::InternalServerError
+        })
+    }
+
+    pub fn default_docker_host(&self) -> String
+    {
+        self.config().docker_hosts[0].name.clone()
+    }
+
+    // À relire pour chaque opération plutôt que de la garder, pour que les rechargements s'appliquent.
+    pub fn config(&self) -> Arc<Config>
+    {
+        // Le verrou ne protège qu'un échange de pointeur : il reste cohérent même empoisonné.
+        self.config.read().unwrap_or_else(PoisonError::into_inner).clone()
+    }
+
+    pub fn reload_config(&self, reloaded: &Config) -> Vec<ConfigChange>
+    {
+        let mut config = self.config.write().unwrap_or_else(PoisonError::into_inner);
+        let (next, changes) = config.with_reloaded(reloaded);
+        *config = Arc::new(next);
+        changes
+    }
+
+    // Pools paresseux : rien n'est ouvert tant qu'un test n'y touche pas, et une base absente échoue vite.
+    #[cfg(test)]
+    pub fn for_tests(config: Config) -> AppState
+    {
+        let db_pool = sqlx::postgres::PgPoolOptions::new()
+            .acquire_timeout(Duration::from_millis(100))
+            .connect_lazy(&config.db_url)
+            .unwrap();
+        let mariadb_pool = sqlx::mysql::MySqlPoolOptions::new()
+            .acquire_timeout(Duration::from_millis(100))
+            .connect_lazy(&config.mariadb_url)
+            .unwrap();
+
+        Self::new(config, reqwest::Client::new(), reqwest::Client::new(), HashMap::new(), HashMap::new(), db_pool, mariadb_pool)
+    }
+}
+
+// Délais inférieurs à ceux des routes : un service distant bloqué remonte en 504.
+pub fn build_http_client(config: &Config) -> reqwest::Result<reqwest::Client>
+{
+    reqwest::Client::builder()
+        .user_agent(format!("Hangar/{} (+{})", env!("CARGO_PKG_VERSION"), config.public_address))
+        .connect_timeout(Duration::from_secs(config.http_connect_timeout))
+        .timeout(Duration::from_secs(config.http_request_timeout))
+        .pool_idle_timeout(Duration::from_secs(90))
+        .pool_max_idle_per_host(8)
+        .build()
+}
+
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+const PROBE_MAX_REDIRECTS: usize = 2;
+
+// Sans magasin de cookies : rien de la session de l'utilisateur n'atteint l'application sondée.
+pub fn build_probe_client(config: &Config) -> reqwest::Result<reqwest::Client>
+{
+    reqwest::Client::builder()
+        .user_agent(format!("Hangar-Probe/{} (+{})", env!("CARGO_PKG_VERSION"), config.public_address))
+        .connect_timeout(Duration::from_secs(config.http_connect_timeout).min(PROBE_TIMEOUT))
+        .timeout(PROBE_TIMEOUT)
+        .redirect(reqwest::redirect::Policy::limited(PROBE_MAX_REDIRECTS))
+        .pool_max_idle_per_host(0)
+        .build()
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+    use crate::test_support::{http_server, stalling_server};
+
+    fn client() -> reqwest::Client
+    {
+        let config = Config::for_tests(&[("HTTP_CONNECT_TIMEOUT_SECONDS", "1"), ("HTTP_REQUEST_TIMEOUT_SECONDS", "1")]).unwrap();
+        build_http_client(&config).unwrap()
+    }
+
+    #[tokio::test]
+    async fn shared_client_identifies_the_platform()
+    {
+        let (address, requests) = http_server("HTTP/1.1 204 No Content\r\nContent-Length: 0\r\n\r\n");
+
+        client().get(format!("http://{}/", address)).send().await.unwrap();
+
+        let head = requests.recv().unwrap().to_lowercase();
+        assert!(head.contains(&format!("user-agent: hangar/{} (+https://hangar.test)", env!("CARGO_PKG_VERSION"))), "{}", head);
+    }
+
+    #[tokio::test]
+    async fn stalled_upstream_is_abandoned_after_the_request_timeout()
+    {
+        let started = Instant::now();
+        let error = client().get(format!("http://{}/", stalling_server())).send().await.unwrap_err();
+
+        assert!(error.is_timeout());
+        assert!(started.elapsed() < Duration::from_secs(3));
+    }
+}
@@ -0,0 +1,56 @@
+use std::
+{
+    io::{Read, Write},
+    net::{SocketAddr, TcpListener},
+    sync::mpsc,
+    thread,
+};
+
+// Accepte les connexions sans jamais répondre, comme un hôte qui garde le TCP ouvert sans envoyer de données.
+pub fn stalling_server() -> SocketAddr
+{
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    thread::spawn(move ||
+    {
+        let mut held = Vec::new();
+        for stream in listener.incoming().flatten()
+        {
+            held.push(stream);
+        }
+    });
+    address
+}
+
+// Répond `response` tel quel à chaque connexion et transmet l'en-tête de la requête reçue.
+pub fn http_server(response: &'static str) -> (SocketAddr, mpsc::Receiver<String>)
+{
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move ||
+    {
+        for mut stream in listener.incoming().flatten()
+        {
+            let mut head = Vec::new();
+            let mut buffer = [0; 1024];
+            while !head.ends_with(b"\r\n\r\n")
+            {
+                match stream.read(&mut buffer)
+                {
+                    Ok(0) | Err(_) => break,
+                    Ok(read) => head.extend_from_slice(&buffer[..read]),
+                }
+            }
+            let _ = sender.send(String::from_utf8_lossy(&head).into_owned());
+            let _ = stream.write_all(response.as_bytes());
+        }
+    });
+    (address, receiver)
+}
+
+// Le port est libéré avant de revenir : une connexion y est refusée.
+pub fn closed_port() -> SocketAddr
+{
+    TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap()
+}